@@ -0,0 +1,80 @@
+// Scaffolds a new RustHDL design project for one of the supported boards.
+// Installed as `cargo-rust-hdl`, so that it can be run as
+//
+//   cargo rust-hdl new <board> <directory> [--path <rust-hdl checkout>]
+//   cargo rust-hdl boards
+//
+// The `--path` option makes the generated project depend on a local
+// checkout of RustHDL instead of the published crates.
+use std::path::PathBuf;
+
+mod project;
+
+use project::{boards, find_board, Project};
+
+fn usage() -> ! {
+    eprintln!("Usage:");
+    eprintln!("  cargo rust-hdl new <board> <directory> [--path <rust-hdl checkout>]");
+    eprintln!("  cargo rust-hdl boards");
+    std::process::exit(1);
+}
+
+fn list_boards() {
+    for board in boards() {
+        println!("{:16} {}", board.name, board.description);
+    }
+}
+
+fn new_project(args: &[String]) -> Result<(), String> {
+    let mut positional = vec![];
+    let mut rust_hdl_path = None;
+    let mut args = args.iter();
+    while let Some(arg) = args.next() {
+        if arg == "--path" {
+            match args.next() {
+                Some(path) => rust_hdl_path = Some(PathBuf::from(path)),
+                None => usage(),
+            }
+        } else {
+            positional.push(arg);
+        }
+    }
+    if positional.len() != 2 {
+        usage();
+    }
+    let board = find_board(positional[0]).ok_or_else(|| {
+        format!(
+            "Unknown board {} - use `cargo rust-hdl boards` to list the supported boards",
+            positional[0]
+        )
+    })?;
+    let project = Project::new(board, PathBuf::from(positional[1]), rust_hdl_path)?;
+    project.write()?;
+    println!(
+        "Created {} project {} in {}",
+        project.board.name,
+        project.name,
+        project.directory.display()
+    );
+    Ok(())
+}
+
+fn main() {
+    // When run as `cargo rust-hdl`, cargo passes the subcommand name as the first argument
+    let mut args: Vec<String> = std::env::args().skip(1).collect();
+    if args.first().map(|x| x.as_str()) == Some("rust-hdl") {
+        args.remove(0);
+    }
+    let result = match args.first().map(|x| x.as_str()) {
+        Some("new") => new_project(&args[1..]),
+        Some("boards") => {
+            list_boards();
+            Ok(())
+        }
+        _ => usage(),
+    };
+    if let Err(msg) = result {
+        eprintln!("error: {}", msg);
+        std::process::exit(1);
+    }
+}
@@ -0,0 +1,311 @@
+use rust_hdl::fpga::board::BoardMetadata;
+use std::path::{Path, PathBuf};
+
+// The boards that projects can be generated for
+pub fn boards() -> Vec<BoardMetadata> {
+    vec![
+        rust_hdl_bsp_alchitry_cu::METADATA,
+        rust_hdl_bsp_arty_a7::METADATA,
+        rust_hdl_bsp_icebreaker::METADATA,
+        rust_hdl_bsp_tang_nano_9k::METADATA,
+        rust_hdl_bsp_ulx3s::METADATA,
+    ]
+}
+
+pub fn find_board(name: &str) -> Option<BoardMetadata> {
+    boards().into_iter().find(|x| x.name == name)
+}
+
+// A new project for a board.  The package name is taken from the
+// last component of the directory.
+pub struct Project {
+    pub board: BoardMetadata,
+    pub name: String,
+    pub directory: PathBuf,
+    pub rust_hdl_path: Option<PathBuf>,
+}
+
+impl Project {
+    pub fn new(
+        board: BoardMetadata,
+        directory: PathBuf,
+        rust_hdl_path: Option<PathBuf>,
+    ) -> Result<Self, String> {
+        let name = directory
+            .file_name()
+            .and_then(|x| x.to_str())
+            .ok_or_else(|| format!("Cannot name a project after {}", directory.display()))?
+            .to_string();
+        if name.is_empty()
+            || name.starts_with(|c: char| c.is_ascii_digit())
+            || !name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!("{} is not a valid package name", name));
+        }
+        Ok(Self {
+            board,
+            name,
+            directory,
+            rust_hdl_path,
+        })
+    }
+    fn crate_ident(&self) -> String {
+        self.name.replace('-', "_")
+    }
+    fn dependency(&self, crate_name: &str, features: &str) -> String {
+        let version = env!("CARGO_PKG_VERSION");
+        match &self.rust_hdl_path {
+            Some(path) => format!(
+                "{} = {{ version = \"{}\", path = \"{}\"{} }}",
+                crate_name,
+                version,
+                path.join(crate_name).display(),
+                features
+            ),
+            None => format!(
+                "{} = {{ version = \"{}\"{} }}",
+                crate_name, version, features
+            ),
+        }
+    }
+    pub fn cargo_toml(&self) -> String {
+        format!(
+            r#"[package]
+name = "{name}"
+version = "0.1.0"
+edition = "2021"
+
+[dependencies]
+{rust_hdl}
+{bsp}
+"#,
+            name = self.name,
+            rust_hdl = self.dependency("rust-hdl", ", features = [\"fpga\"]"),
+            bsp = self.dependency(self.board.crate_name, "")
+        )
+    }
+    // The LED patterns for the on and off states, accounting for active low LEDs
+    fn led_patterns(&self) -> (u64, u64) {
+        let all = (1_u64 << self.board.led_count) - 1;
+        // Light every other LED when the pulse is active
+        let on = 0x5555_5555_5555_5555 & all;
+        if self.board.leds_active_low {
+            (!on & all, all)
+        } else {
+            (on, 0)
+        }
+    }
+    pub fn lib_rs(&self) -> String {
+        let (on, off) = self.led_patterns();
+        format!(
+            r#"use rust_hdl::prelude::*;
+use {bsp}::pins;
+use std::time::Duration;
+
+// The top level of the design.  The pulser blinks the LEDs on the
+// {description}.
+#[derive(LogicBlock)]
+pub struct Top {{
+    pub clock: Signal<In, Clock>,
+    pub leds: Signal<Out, Bits<{led_count}>>,
+    pulser: Pulser,
+}}
+
+pub const LEDS_ON: u64 = {on:#x};
+pub const LEDS_OFF: u64 = {off:#x};
+
+impl Top {{
+    pub fn new(pulse_rate_hz: f64, pulse_duration: Duration) -> Self {{
+        Self {{
+            clock: pins::clock(),
+            leds: pins::{leds}(),
+            pulser: Pulser::new(pins::{clock_speed}, pulse_rate_hz, pulse_duration),
+        }}
+    }}
+}}
+
+impl Default for Top {{
+    fn default() -> Self {{
+        Self::new(1.0, Duration::from_millis(250))
+    }}
+}}
+
+impl Logic for Top {{
+    #[hdl_gen]
+    fn update(&mut self) {{
+        clock!(self, clock, pulser);
+        self.pulser.enable.next = true;
+        self.leds.next = LEDS_OFF.into();
+        if self.pulser.pulse.val() {{
+            self.leds.next = LEDS_ON.into();
+        }}
+    }}
+}}
+"#,
+            bsp = self.board.crate_ident(),
+            description = self.board.description,
+            led_count = self.board.led_count,
+            leds = self.board.leds,
+            clock_speed = self.board.clock_speed,
+        )
+    }
+    pub fn main_rs(&self) -> String {
+        let args: String = self
+            .board
+            .synth_args
+            .iter()
+            .map(|x| format!(", {}", x))
+            .collect();
+        format!(
+            r#"use {bsp}::synth;
+use {project}::Top;
+
+// Generates the Verilog, constraints and bitstream for the board in firmware/{board}
+fn main() {{
+    let uut = Top::default();
+    synth::generate_bitstream(uut, "firmware/{board}"{args});
+}}
+"#,
+            bsp = self.board.crate_ident(),
+            project = self.crate_ident(),
+            board = self.board.name,
+        )
+    }
+    pub fn testbench_rs(&self) -> String {
+        format!(
+            r#"use rust_hdl::prelude::*;
+use {project}::{{Top, LEDS_OFF, LEDS_ON}};
+use std::time::Duration;
+
+#[test]
+fn test_leds_blink() {{
+    // Pulse much faster than in hardware to keep the simulation short
+    let mut uut = Top::new(10_000.0, Duration::from_micros(20));
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Top>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Top>| {{
+        let mut x = sim.init()?;
+        for _ in 0..3 {{
+            x = sim.watch(|x| x.leds.val() == LEDS_ON, x)?;
+            x = sim.watch(|x| x.leds.val() == LEDS_OFF, x)?;
+        }}
+        sim.done(x)
+    }});
+    sim.run(Box::new(uut), 10_000_000).unwrap();
+}}
+
+#[test]
+fn test_top_is_synthesizable() {{
+    let mut uut = Top::default();
+    uut.connect_all();
+    yosys_validate("top", &generate_verilog(&uut)).unwrap();
+}}
+"#,
+            project = self.crate_ident(),
+        )
+    }
+    // The files of the project, relative to its directory
+    pub fn files(&self) -> Vec<(PathBuf, String)> {
+        vec![
+            (PathBuf::from("Cargo.toml"), self.cargo_toml()),
+            (PathBuf::from(".gitignore"), "/target\n/firmware\n".into()),
+            (PathBuf::from("src/lib.rs"), self.lib_rs()),
+            (PathBuf::from("src/main.rs"), self.main_rs()),
+            (PathBuf::from("tests/simulate.rs"), self.testbench_rs()),
+        ]
+    }
+    pub fn write(&self) -> Result<(), String> {
+        if self.directory.join("Cargo.toml").exists() {
+            return Err(format!(
+                "{} already contains a Cargo project",
+                self.directory.display()
+            ));
+        }
+        for (path, contents) in self.files() {
+            write_file(&self.directory.join(path), &contents)?;
+        }
+        Ok(())
+    }
+}
+
+fn write_file(path: &Path, contents: &str) -> Result<(), String> {
+    if let Some(parent) = path.parent() {
+        std::fs::create_dir_all(parent)
+            .map_err(|e| format!("Cannot create {}: {}", parent.display(), e))?;
+    }
+    std::fs::write(path, contents).map_err(|e| format!("Cannot write {}: {}", path.display(), e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_all_boards_are_listed_once() {
+        let boards = boards();
+        for board in &boards {
+            assert_eq!(boards.iter().filter(|x| x.name == board.name).count(), 1);
+            assert_eq!(find_board(board.name).as_ref(), Some(board));
+        }
+        assert!(find_board("no-such-board").is_none());
+    }
+
+    #[test]
+    fn test_project_names() {
+        let board = find_board("icebreaker").unwrap();
+        let project = Project::new(board.clone(), PathBuf::from("work/my-blinky"), None).unwrap();
+        assert_eq!(project.name, "my-blinky");
+        assert!(project.main_rs().contains("use my_blinky::Top;"));
+        assert!(Project::new(board.clone(), PathBuf::from("work/2fast"), None).is_err());
+        assert!(Project::new(board, PathBuf::from("work/has space"), None).is_err());
+    }
+
+    #[test]
+    fn test_generated_project_uses_board_metadata() {
+        let project = Project::new(
+            find_board("tang-nano-9k").unwrap(),
+            PathBuf::from("blinky"),
+            Some(PathBuf::from("/src/rust-hdl")),
+        )
+        .unwrap();
+        let lib = project.lib_rs();
+        assert!(lib.contains("use rust_hdl_bsp_tang_nano_9k::pins;"));
+        assert!(lib.contains("pins::leds_n()"));
+        assert!(lib.contains("Bits<6>"));
+        assert!(lib.contains("pins::CLOCK_SPEED_27MHZ"));
+        // Active low LEDs are all high when off
+        assert!(lib.contains("LEDS_OFF: u64 = 0x3f;"));
+        assert!(lib.contains("LEDS_ON: u64 = 0x2a;"));
+        let cargo = project.cargo_toml();
+        assert!(cargo.contains("path = \"/src/rust-hdl/rust-hdl-bsp-tang-nano-9k\""));
+        assert!(cargo.contains("path = \"/src/rust-hdl/rust-hdl\", features = [\"fpga\"]"));
+        let ulx3s =
+            Project::new(find_board("ulx3s").unwrap(), PathBuf::from("blinky"), None).unwrap();
+        assert!(ulx3s
+            .main_rs()
+            .contains("\"firmware/ulx3s\", synth::ULX3SVariant::LFE5U85F);"));
+    }
+
+    #[test]
+    fn test_write_project() {
+        let directory = std::env::temp_dir().join(format!("rust-hdl-new-{}", std::process::id()));
+        let _ = std::fs::remove_dir_all(&directory);
+        let project = Project::new(
+            find_board("arty-a7").unwrap(),
+            directory.join("blinky"),
+            None,
+        )
+        .unwrap();
+        project.write().unwrap();
+        for (path, contents) in project.files() {
+            let written = std::fs::read_to_string(project.directory.join(path)).unwrap();
+            assert_eq!(written, contents);
+        }
+        // Refuse to overwrite an existing project
+        assert!(project.write().is_err());
+        std::fs::remove_dir_all(&directory).unwrap();
+    }
+}
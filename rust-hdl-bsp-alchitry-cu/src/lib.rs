@@ -1,2 +1,15 @@
 pub mod pins;
 pub mod synth;
+
+use rust_hdl::fpga::board::BoardMetadata;
+
+pub const METADATA: BoardMetadata = BoardMetadata {
+    name: "alchitry-cu",
+    description: "Alchitry Cu (Lattice iCE40HX8K)",
+    crate_name: "rust-hdl-bsp-alchitry-cu",
+    clock_speed: "CLOCK_SPEED_100MHZ",
+    leds: "leds",
+    led_count: 8,
+    leds_active_low: false,
+    synth_args: &[],
+};
@@ -1,4 +1,5 @@
 use rust_hdl::core::prelude::*;
+use rust_hdl::fpga::io_planner::{IOPlanner, PinDefinition};
 
 pub const CLOCK_SPEED_100MHZ: u64 = 100_000_000;
 
@@ -90,3 +91,29 @@ pub fn map_alchitry_pin_to_cu_pad(pin: &str) -> &str {
         }
     }
 }
+
+const HEADER_A: [&str; 32] = [
+    "A2", "A3", "A5", "A6", "A8", "A9", "A11", "A12", "A14", "A15", "A17", "A18", "A20", "A21",
+    "A23", "A24", "A27", "A28", "A30", "A31", "A33", "A34", "A36", "A37", "A39", "A40", "A42",
+    "A43", "A45", "A46", "A48", "A49",
+];
+
+// The full set of pins available on the Cu.  Use with an [IOPlanner] to
+// claim pins by name instead of by pad location.
+pub fn pin_catalog() -> Vec<PinDefinition> {
+    let mut ret = vec![PinDefinition::new("clock", "P7").clock_capable()];
+    for (ndx, pad) in ["J11", "K11", "K12", "K14", "L12", "L14", "M12", "N14"]
+        .iter()
+        .enumerate()
+    {
+        ret.push(PinDefinition::new(&format!("led{}", ndx), pad));
+    }
+    for pin in HEADER_A {
+        ret.push(PinDefinition::new(pin, map_alchitry_pin_to_cu_pad(pin)));
+    }
+    ret
+}
+
+pub fn io_planner() -> IOPlanner {
+    IOPlanner::new(&pin_catalog()).unwrap()
+}
@@ -0,0 +1,49 @@
+use rust_hdl::fpga::io_planner::IOPlanError;
+use rust_hdl::fpga::toolchains::icestorm::generate_pcf;
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_alchitry_cu::pins;
+use rust_hdl_bsp_alchitry_cu::pins::CLOCK_SPEED_100MHZ;
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+pub struct AlchitryCuPlannedPulser {
+    pulser: Pulser,
+    clock: Signal<In, Clock>,
+    leds: Signal<Out, Bits<4>>,
+    header: Signal<Out, Bit>,
+}
+
+impl Logic for AlchitryCuPlannedPulser {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pulser.enable.next = true;
+        clock!(self, clock, pulser);
+        self.leds.next = 0x00.into();
+        if self.pulser.pulse.val() {
+            self.leds.next = 0x0A.into();
+        }
+        self.header.next = self.pulser.pulse.val();
+    }
+}
+
+#[test]
+fn test_alchitry_cu_io_planner_generates_pcf() {
+    let mut planner = pins::io_planner();
+    let mut uut = AlchitryCuPlannedPulser {
+        pulser: Pulser::new(CLOCK_SPEED_100MHZ, 1.0, Duration::from_millis(250)),
+        clock: planner.request_clock("clock").unwrap(),
+        leds: planner
+            .request("leds", &["led0", "led1", "led2", "led3"])
+            .unwrap(),
+        header: planner.request_pin("A2").unwrap(),
+    };
+    assert!(matches!(
+        planner.request_pin::<Out>("led2"),
+        Err(IOPlanError::PinConflict { .. })
+    ));
+    uut.connect_all();
+    let pcf = generate_pcf(&uut);
+    assert!(pcf.contains("set_io clock P7"));
+    assert!(pcf.contains("set_io leds[3] K14"));
+    assert!(pcf.contains("set_io header M1"));
+}
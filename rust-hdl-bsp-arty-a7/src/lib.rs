@@ -0,0 +1,15 @@
+pub mod pins;
+pub mod synth;
+
+use rust_hdl::fpga::board::BoardMetadata;
+
+pub const METADATA: BoardMetadata = BoardMetadata {
+    name: "arty-a7",
+    description: "Digilent Arty A7 (Xilinx Artix-7)",
+    crate_name: "rust-hdl-bsp-arty-a7",
+    clock_speed: "CLOCK_SPEED_100MHZ",
+    leds: "leds",
+    led_count: 4,
+    leds_active_low: false,
+    synth_args: &["Default::default()"],
+};
@@ -0,0 +1,132 @@
+use rust_hdl::core::prelude::*;
+use rust_hdl::fpga::io_planner::{IOPlanner, PinDefinition};
+
+pub const CLOCK_SPEED_100MHZ: u64 = 100_000_000;
+
+fn lvcmos33<D: Direction, T: Synth>(locations: &[&str]) -> Signal<D, T> {
+    let mut x = Signal::<D, T>::default();
+    for (ndx, name) in locations.iter().enumerate() {
+        x.add_location(ndx, name);
+        x.add_signal_type(ndx, SignalType::LowVoltageCMOS_3v3);
+    }
+    x
+}
+
+pub fn clock() -> Signal<In, Clock> {
+    let mut x = lvcmos33(&["E3"]);
+    x.add_constraint(PinConstraint {
+        index: 0,
+        constraint: Constraint::Timing(Timing::Periodic(PeriodicTiming {
+            net: "sys_clk_pin".into(),
+            period_nanoseconds: 10.0,
+            duty_cycle: 50.0,
+        })),
+    });
+    x.connect();
+    x
+}
+
+pub fn leds() -> Signal<Out, Bits<4>> {
+    lvcmos33(&["H5", "J5", "T9", "T10"])
+}
+
+// The 4 RGB LEDs, ordered as [r0, g0, b0, r1, g1, b1, ...]
+pub fn rgb_leds() -> Signal<Out, Bits<12>> {
+    lvcmos33(&[
+        "G6", "F6", "E1", "G3", "J4", "G4", "J3", "J2", "H4", "K1", "H6", "K2",
+    ])
+}
+
+pub fn switches() -> Signal<In, Bits<4>> {
+    let mut x = lvcmos33(&["A8", "C11", "C10", "A10"]);
+    x.connect();
+    x
+}
+
+pub fn buttons() -> Signal<In, Bits<4>> {
+    let mut x = lvcmos33(&["D9", "C9", "B9", "B8"]);
+    x.connect();
+    x
+}
+
+// The red reset button is active low.
+pub fn reset_n() -> Signal<In, Bit> {
+    let mut x = lvcmos33(&["C2"]);
+    x.connect();
+    x
+}
+
+pub fn uart_rxd_out() -> Signal<Out, Bit> {
+    lvcmos33(&["D10"])
+}
+
+pub fn uart_txd_in() -> Signal<In, Bit> {
+    let mut x = lvcmos33(&["A9"]);
+    x.connect();
+    x
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Pmod {
+    JA,
+    JB,
+    JC,
+    JD,
+}
+
+impl Pmod {
+    // Pins 1-4 and 7-10 of the header, in that order.
+    pub fn locations(&self) -> [&'static str; 8] {
+        match self {
+            Pmod::JA => ["G13", "B11", "A11", "D12", "D13", "B18", "A18", "K16"],
+            Pmod::JB => ["E15", "E16", "D15", "C15", "J17", "J18", "K15", "J15"],
+            Pmod::JC => ["U12", "V12", "V10", "V11", "U14", "V14", "T13", "U13"],
+            Pmod::JD => ["D4", "D3", "F4", "F3", "E2", "D2", "H2", "G2"],
+        }
+    }
+    fn name(&self) -> &str {
+        match self {
+            Pmod::JA => "ja",
+            Pmod::JB => "jb",
+            Pmod::JC => "jc",
+            Pmod::JD => "jd",
+        }
+    }
+}
+
+pub fn pmod<D: Direction>(header: Pmod) -> Signal<D, Bits<8>> {
+    lvcmos33(&header.locations())
+}
+
+// The full set of pins available on the Arty A7.  Use with an [IOPlanner]
+// to claim pins by name instead of by pad location.
+pub fn pin_catalog() -> Vec<PinDefinition> {
+    let io = |name: &str, location: &str| {
+        PinDefinition::new(name, location).with_kind(SignalType::LowVoltageCMOS_3v3)
+    };
+    let mut ret = vec![
+        io("clock", "E3").clock_capable(),
+        io("reset_n", "C2"),
+        io("uart_rxd_out", "D10"),
+        io("uart_txd_in", "A9"),
+    ];
+    for (ndx, loc) in ["H5", "J5", "T9", "T10"].iter().enumerate() {
+        ret.push(io(&format!("led{}", ndx), loc));
+    }
+    for (ndx, loc) in ["A8", "C11", "C10", "A10"].iter().enumerate() {
+        ret.push(io(&format!("sw{}", ndx), loc));
+    }
+    for (ndx, loc) in ["D9", "C9", "B9", "B8"].iter().enumerate() {
+        ret.push(io(&format!("btn{}", ndx), loc));
+    }
+    for header in [Pmod::JA, Pmod::JB, Pmod::JC, Pmod::JD] {
+        for (ndx, loc) in header.locations().iter().enumerate() {
+            ret.push(io(&format!("{}_{}", header.name(), ndx), loc));
+        }
+    }
+    ret
+}
+
+pub fn io_planner() -> IOPlanner {
+    IOPlanner::new(&pin_catalog()).unwrap()
+}
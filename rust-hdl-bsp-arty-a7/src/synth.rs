@@ -0,0 +1,110 @@
+use rust_hdl::core::check_error::check_all;
+use rust_hdl::core::prelude::*;
+use rust_hdl::fpga::toolchains::vivado::generate_xdc;
+use std::fs::{copy, create_dir_all, remove_dir_all, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+// The Arty A7 is sold with either an XC7A35T or an XC7A100T.  Both are
+// in the same package, so the pinout is identical.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ArtyA7Variant {
+    A35T,
+    A100T,
+}
+
+impl ArtyA7Variant {
+    pub fn part(&self) -> &str {
+        match self {
+            ArtyA7Variant::A35T => "xc7a35ticsg324-1L",
+            ArtyA7Variant::A100T => "xc7a100tcsg324-1",
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct VivadoOptions {
+    pub vivado_path: String,
+    pub variant: ArtyA7Variant,
+    pub jobs: usize,
+}
+
+impl Default for VivadoOptions {
+    fn default() -> Self {
+        // Unlike the Opal Kelly boards, there are no vendor assets needed,
+        // so the location of vivado is picked up at run time.  If VIVADO_PATH
+        // is not set, vivado must be on the path.
+        let vivado_path = std::env::var("VIVADO_PATH")
+            .map(|x| format!("{}/vivado", x))
+            .unwrap_or_else(|_| "vivado".into());
+        Self {
+            vivado_path,
+            variant: ArtyA7Variant::A35T,
+            jobs: 8,
+        }
+    }
+}
+
+pub fn generate_tcl(options: &VivadoOptions) -> String {
+    format!(
+        r#"
+create_project top . -part {part} -force
+
+add_files {{top.v top.xdc}}
+
+update_compile_order
+
+launch_runs synth_1 -jobs {jobs}
+wait_on_run synth_1
+
+set status [ get_property STATUS [ get_runs synth_1 ] ]
+if {{ $status != "synth_design Complete!" }} {{
+ puts "Synthesis Failed"
+ exit
+}}
+
+launch_runs impl_1 -to_step write_bitstream -jobs {jobs}
+wait_on_run impl_1
+
+set status [ get_property STATUS [ get_runs impl_1 ] ]
+if {{ $status != "write_bitstream Complete!" }} {{
+ puts "Implementation Failed"
+ exit
+}}
+
+puts "Vivado Run Complete"
+exit
+"#,
+        part = options.variant.part(),
+        jobs = options.jobs
+    )
+}
+
+pub fn generate_bitstream<U: Block>(mut uut: U, prefix: &str, options: VivadoOptions) {
+    uut.connect_all();
+    check_all(&uut).unwrap(); // TODO - Change from panic to return an error
+    let verilog_text = filter_blackbox_directives(&generate_verilog(&uut));
+    let xdc_text = generate_xdc(&uut);
+    let dir = PathBuf::from(prefix);
+    let _ = remove_dir_all(&dir);
+    let _ = create_dir_all(&dir);
+    std::fs::write(dir.join("top.v"), verilog_text).unwrap();
+    std::fs::write(dir.join("top.xdc"), xdc_text).unwrap();
+    let mut tcl_file = File::create(dir.join("top.tcl")).unwrap();
+    write!(tcl_file, "{}", generate_tcl(&options)).unwrap();
+    let output = Command::new(&options.vivado_path)
+        .current_dir(dir.clone())
+        .arg("-mode")
+        .arg("tcl")
+        .arg("-source")
+        .arg("top.tcl")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    std::fs::write(dir.join("top.out"), &stdout).unwrap();
+    std::fs::write(dir.join("top.err"), &stderr).unwrap();
+    assert!(stdout.contains("Vivado Run Complete"));
+    copy(dir.join("top.runs/impl_1/top.bit"), dir.join("top.bit")).unwrap();
+}
@@ -0,0 +1,59 @@
+use rust_hdl::fpga::toolchains::vivado::generate_xdc;
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_arty_a7::pins;
+use rust_hdl_bsp_arty_a7::pins::Pmod;
+
+#[derive(LogicBlock)]
+pub struct ArtyA7Loopback {
+    clock: Signal<In, Clock>,
+    switches: Signal<In, Bits<4>>,
+    buttons: Signal<In, Bits<4>>,
+    ja: Signal<Out, Bits<8>>,
+    jd: Signal<Out, Bits<8>>,
+    uart_rxd_out: Signal<Out, Bit>,
+    uart_txd_in: Signal<In, Bit>,
+}
+
+impl Logic for ArtyA7Loopback {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.ja.next = bit_cast::<8, 4>(self.switches.val());
+        self.jd.next = bit_cast::<8, 4>(self.buttons.val());
+        self.uart_rxd_out.next = self.uart_txd_in.val();
+    }
+}
+
+impl Default for ArtyA7Loopback {
+    fn default() -> Self {
+        Self {
+            clock: pins::clock(),
+            switches: pins::switches(),
+            buttons: pins::buttons(),
+            ja: pins::pmod(Pmod::JA),
+            jd: pins::pmod(Pmod::JD),
+            uart_rxd_out: pins::uart_rxd_out(),
+            uart_txd_in: pins::uart_txd_in(),
+        }
+    }
+}
+
+#[test]
+fn test_arty_a7_xdc_maps_pmods() {
+    let mut uut = ArtyA7Loopback::default();
+    uut.connect_all();
+    let xdc = generate_xdc(&uut);
+    assert!(xdc.contains("set_property PACKAGE_PIN E3 [get_ports { clock }]"));
+    assert!(xdc.contains("set_property PACKAGE_PIN G13 [get_ports { ja[0] }]"));
+    assert!(xdc.contains("set_property PACKAGE_PIN G2 [get_ports { jd[7] }]"));
+    assert!(xdc.contains("set_property IOSTANDARD LVCMOS33 [get_ports { uart_rxd_out }]"));
+    assert!(xdc.contains("create_clock"));
+}
+
+#[test]
+fn test_arty_a7_io_planner_knows_headers() {
+    let mut planner = pins::io_planner();
+    let _clock = planner.request_clock("clock").unwrap();
+    let jb: Signal<Out, Bits<2>> = planner.request("jb", &["jb_0", "jb_7"]).unwrap();
+    assert_eq!(jb.constraints().len(), 4);
+    assert!(planner.request_pin::<In>("jb_0").is_err());
+}
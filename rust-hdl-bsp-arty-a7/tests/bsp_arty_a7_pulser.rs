@@ -0,0 +1,40 @@
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_arty_a7::pins::CLOCK_SPEED_100MHZ;
+use rust_hdl_bsp_arty_a7::{pins, synth};
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+pub struct ArtyA7Pulser {
+    pulser: Pulser,
+    clock: Signal<In, Clock>,
+    leds: Signal<Out, Bits<4>>,
+}
+
+impl Logic for ArtyA7Pulser {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pulser.enable.next = true;
+        clock!(self, clock, pulser);
+        self.leds.next = 0x00.into();
+        if self.pulser.pulse.val() {
+            self.leds.next = 0x0A.into();
+        }
+    }
+}
+
+impl Default for ArtyA7Pulser {
+    fn default() -> Self {
+        let pulser = Pulser::new(CLOCK_SPEED_100MHZ, 1.0, Duration::from_millis(250));
+        Self {
+            pulser,
+            clock: pins::clock(),
+            leds: pins::leds(),
+        }
+    }
+}
+
+#[test]
+fn synthesize_arty_a7_pulser() {
+    let uut = ArtyA7Pulser::default();
+    synth::generate_bitstream(uut, target_path!("arty_a7/pulser"), Default::default());
+}
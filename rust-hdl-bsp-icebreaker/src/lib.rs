@@ -0,0 +1,15 @@
+pub mod pins;
+pub mod synth;
+
+use rust_hdl::fpga::board::BoardMetadata;
+
+pub const METADATA: BoardMetadata = BoardMetadata {
+    name: "icebreaker",
+    description: "1BitSquared iCEBreaker (Lattice iCE40UP5K)",
+    crate_name: "rust-hdl-bsp-icebreaker",
+    clock_speed: "CLOCK_SPEED_12MHZ",
+    leds: "leds",
+    led_count: 5,
+    leds_active_low: false,
+    synth_args: &[],
+};
@@ -0,0 +1,96 @@
+use rust_hdl::core::prelude::*;
+use rust_hdl::fpga::io_planner::{IOPlanner, PinDefinition};
+
+pub const CLOCK_SPEED_12MHZ: u64 = 12_000_000;
+
+pub fn clock() -> Signal<In, Clock> {
+    let mut x = Signal::<In, _>::default();
+    x.add_location(0, "35");
+    x.connect();
+    x
+}
+
+// The red and green LEDs on the main board are active low.
+pub fn led_red_n() -> Signal<Out, Bit> {
+    let mut x = Signal::<Out, _>::default();
+    x.add_location(0, "11");
+    x
+}
+
+pub fn led_green_n() -> Signal<Out, Bit> {
+    let mut x = Signal::<Out, _>::default();
+    x.add_location(0, "37");
+    x
+}
+
+// The user button on the main board is active low.
+pub fn button_n() -> Signal<In, Bit> {
+    let mut x = Signal::<In, _>::default();
+    x.add_location(0, "10");
+    x.connect();
+    x
+}
+
+// The 5 LEDs on the snap-off PMOD2 breakout (active high).
+pub fn leds() -> Signal<Out, Bits<5>> {
+    let mut x = Signal::<Out, _>::default();
+    for (ndx, uname) in ["26", "27", "25", "23", "21"].iter().enumerate() {
+        x.add_location(ndx, uname);
+    }
+    x
+}
+
+// The 3 buttons on the snap-off PMOD2 breakout (active high).
+pub fn buttons() -> Signal<In, Bits<3>> {
+    let mut x = Signal::<In, _>::default();
+    for (ndx, uname) in ["20", "19", "18"].iter().enumerate() {
+        x.add_location(ndx, uname);
+    }
+    x.connect();
+    x
+}
+
+pub fn uart_rx() -> Signal<In, Bit> {
+    let mut x = Signal::<In, _>::default();
+    x.add_location(0, "6");
+    x.connect();
+    x
+}
+
+pub fn uart_tx() -> Signal<Out, Bit> {
+    let mut x = Signal::<Out, _>::default();
+    x.add_location(0, "9");
+    x
+}
+
+const PMOD1A: [&str; 8] = ["4", "2", "47", "45", "3", "48", "46", "44"];
+const PMOD1B: [&str; 8] = ["43", "38", "34", "31", "42", "36", "32", "28"];
+const PMOD2: [&str; 8] = ["27", "25", "21", "19", "26", "23", "20", "18"];
+
+// The full set of pins available on the iCEBreaker.  The PMOD2 header is
+// shared with the LEDs and buttons of the snap-off breakout, so the planner
+// lists them under their header names only.
+pub fn pin_catalog() -> Vec<PinDefinition> {
+    let mut ret = vec![
+        PinDefinition::new("clock", "35").clock_capable(),
+        PinDefinition::new("led_red_n", "11"),
+        PinDefinition::new("led_green_n", "37"),
+        PinDefinition::new("button_n", "10"),
+        PinDefinition::new("uart_rx", "6"),
+        PinDefinition::new("uart_tx", "9"),
+        PinDefinition::new("flash_sck", "15"),
+        PinDefinition::new("flash_ssb", "16"),
+        PinDefinition::new("flash_io0", "14"),
+        PinDefinition::new("flash_io1", "17"),
+    ];
+    for (header, pins) in [("pmod1a", PMOD1A), ("pmod1b", PMOD1B), ("pmod2", PMOD2)] {
+        for (ndx, pin) in pins.iter().enumerate() {
+            ret.push(PinDefinition::new(&format!("{}_{}", header, ndx), pin));
+        }
+    }
+    ret
+}
+
+pub fn io_planner() -> IOPlanner {
+    IOPlanner::new(&pin_catalog()).unwrap()
+}
@@ -0,0 +1,43 @@
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_icebreaker::pins::CLOCK_SPEED_12MHZ;
+use rust_hdl_bsp_icebreaker::{pins, synth};
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+pub struct ICEBreakerPulser {
+    pulser: Pulser,
+    clock: Signal<In, Clock>,
+    leds: Signal<Out, Bits<5>>,
+    led_red_n: Signal<Out, Bit>,
+}
+
+impl Logic for ICEBreakerPulser {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pulser.enable.next = true;
+        clock!(self, clock, pulser);
+        self.leds.next = 0x00.into();
+        if self.pulser.pulse.val() {
+            self.leds.next = 0x15.into();
+        }
+        self.led_red_n.next = !self.pulser.pulse.val();
+    }
+}
+
+impl Default for ICEBreakerPulser {
+    fn default() -> Self {
+        let pulser = Pulser::new(CLOCK_SPEED_12MHZ, 1.0, Duration::from_millis(250));
+        Self {
+            pulser,
+            clock: pins::clock(),
+            leds: pins::leds(),
+            led_red_n: pins::led_red_n(),
+        }
+    }
+}
+
+#[test]
+fn synthesize_icebreaker_pulser() {
+    let uut = ICEBreakerPulser::default();
+    synth::generate_bitstream(uut, target_path!("icebreaker/pulser"));
+}
@@ -0,0 +1,47 @@
+use rust_hdl::fpga::lattice::ice40::ice_pll::ICE40PLLPadBlock;
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_icebreaker::pins;
+use rust_hdl_bsp_icebreaker::pins::CLOCK_SPEED_12MHZ;
+use rust_hdl_bsp_icebreaker::synth::generate_bitstream;
+use std::time::Duration;
+
+const MHZ48: u64 = 48_000_000;
+
+#[derive(LogicBlock)]
+pub struct ICEBreakerPulserPLL {
+    pulser: Pulser,
+    clock: Signal<In, Clock>,
+    leds: Signal<Out, Bits<5>>,
+    pll: ICE40PLLPadBlock<CLOCK_SPEED_12MHZ, MHZ48>,
+}
+
+impl Logic for ICEBreakerPulserPLL {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pll.clock_in.next = self.clock.val();
+        self.pulser.enable.next = self.pll.locked.val();
+        self.pulser.clock.next = self.pll.clock_out.val();
+        self.leds.next = 0x00.into();
+        if self.pulser.pulse.val() {
+            self.leds.next = 0x0A.into();
+        }
+    }
+}
+
+impl Default for ICEBreakerPulserPLL {
+    fn default() -> Self {
+        let pulser = Pulser::new(MHZ48, 1.0, Duration::from_millis(100));
+        Self {
+            pulser,
+            clock: pins::clock(),
+            leds: pins::leds(),
+            pll: ICE40PLLPadBlock::default(),
+        }
+    }
+}
+
+#[test]
+fn synthesize_icebreaker_pulser_with_pll() {
+    let uut = ICEBreakerPulserPLL::default();
+    generate_bitstream(uut, target_path!("icebreaker/pulser_pll"));
+}
@@ -1,6 +1,46 @@
 use super::mcb_if::MCBInterface4GDDR3;
 use rust_hdl::prelude::*;
 
+// The native application interface of the MIG, as seen from the user
+// logic (controller) and the MIG (responder).  With a 16 bit DDR3 part,
+// a 4:1 PHY ratio and BL8, every command moves one 128 bit word, and the
+// (word) address advances by 8 for each burst.
+#[derive(LogicInterface, Default)]
+#[join = "MIG7NativeResponder"]
+pub struct MIG7NativeController {
+    pub address: Signal<Out, Bits<29>>,
+    pub command: Signal<Out, Bits<3>>,
+    pub enable: Signal<Out, Bit>,
+    pub write_data_in: Signal<Out, Bits<128>>,
+    pub write_data_end: Signal<Out, Bit>,
+    pub write_data_mask: Signal<Out, Bits<16>>,
+    pub write_enable: Signal<Out, Bit>,
+    pub read_data_out: Signal<In, Bits<128>>,
+    pub read_data_end: Signal<In, Bit>,
+    pub read_data_valid: Signal<In, Bit>,
+    pub ready: Signal<In, Bit>,
+    pub write_fifo_not_full: Signal<In, Bit>,
+    pub calib_done: Signal<In, Bit>,
+}
+
+#[derive(LogicInterface, Default)]
+#[join = "MIG7NativeController"]
+pub struct MIG7NativeResponder {
+    pub address: Signal<In, Bits<29>>,
+    pub command: Signal<In, Bits<3>>,
+    pub enable: Signal<In, Bit>,
+    pub write_data_in: Signal<In, Bits<128>>,
+    pub write_data_end: Signal<In, Bit>,
+    pub write_data_mask: Signal<In, Bits<16>>,
+    pub write_enable: Signal<In, Bit>,
+    pub read_data_out: Signal<Out, Bits<128>>,
+    pub read_data_end: Signal<Out, Bit>,
+    pub read_data_valid: Signal<Out, Bit>,
+    pub ready: Signal<Out, Bit>,
+    pub write_fifo_not_full: Signal<Out, Bit>,
+    pub calib_done: Signal<Out, Bit>,
+}
+
 #[derive(LogicBlock, Default)]
 pub struct MemoryInterfaceGenerator7Series {
     // Raw clock from the system - differential and raw
@@ -198,67 +238,98 @@ pub enum MIG7SimState {
     Idle,
 }
 
-/*
-// Just used for simulation...
+// A simulation stand-in for the MIG native interface.  It only models the
+// handshakes - calibration after reset, periodic back pressure on `ready`
+// and a fixed read latency - and backs 256 bursts with a RAM.  Writes must
+// present their data in the same cycle as the command (which is what the
+// [MIG7FIFOAdapter] does).
 #[derive(LogicBlock, Default)]
 pub struct MIG7Sim {
-    pub raw_pos_clock: Signal<In, Clock>,
-    pub raw_neg_clock: Signal<In, Clock>,
-    pub mcb: MCBInterface4GDDR3,
-    pub address: Signal<In, Bits<29>>,
-    pub command: Signal<In, Bits<3>>,
-    pub enable: Signal<In, Bit>,
-    pub write_data_in: Signal<In, Bits<128>>,
-    pub write_data_end: Signal<In, Bit>,
-    pub write_data_mask: Signal<In, Bits<16>>,
-    pub write_enable: Signal<In, Bit>,
-    pub read_data_out: Signal<Out, Bits<128>>,
-    pub read_data_end: Signal<Out, Bit>,
-    pub read_data_valid: Signal<Out, Bit>,
-    pub ready: Signal<Out, Bit>,
-    pub write_fifo_not_full: Signal<Out, Bit>,
-    pub calib_done: Signal<Out, Bit>,
-    pub reset: Signal<In, Reset>,
-    pub clock: Signal<Out, Clock>,
-    pub reset_out: Signal<Out, Bit>,
+    pub clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub app: MIG7NativeResponder,
     state: DFF<MIG7SimState>,
-    data_in_register: DFF<Bits<128>>,
-    data_out_register: DFF<Bits<128>>,
-    calib_flag: DFF<Bit>,
-    _sim: BTreeMap<Bits<29>, Bits<128>>,
+    boot: DFF<Bits<5>>,
+    tick: DFF<Bits<2>>,
+    read_pipe: DFF<Bits<4>>,
+    read_address_1: DFF<Bits<8>>,
+    read_address_2: DFF<Bits<8>>,
+    read_address_3: DFF<Bits<8>>,
+    issue_read: Signal<Local, Bit>,
+    mem: RAM<Bits<128>, 8>,
 }
 
-impl<D: Synth, const N: usize> Logic for MIG7Sim {
+impl Logic for MIG7Sim {
+    #[hdl_gen]
     fn update(&mut self) {
-        self.clock.next = self.raw_pos_clock.val();
-        self.state.clk.next = self.raw_pos_clock.val();
-        self.data_in_register.clk.next = self.raw_pos_clock.val();
-        self.data_out_register.clk.next = self.raw_pos_clock.val();
-        self.data_in_register.d.next = self.data_in_register.q.val();
-        self.data_out_register.d.next = self.data_out_register.q.val();
-        self.calib_flag.clk.next = self.raw_pos_clock.val();
-        self.calib_flag.d.next = self.calib_flag.q.val();
-        self.state.d.next = self.state.q.val();
+        dff_setup!(
+            self,
+            clock,
+            state,
+            boot,
+            tick,
+            read_pipe,
+            read_address_1,
+            read_address_2,
+            read_address_3
+        );
+        self.mem.read_clock.next = self.clock.val();
+        self.mem.write_clock.next = self.clock.val();
+        self.tick.d.next = self.tick.q.val() + 1;
+        self.app.calib_done.next = false;
+        self.app.ready.next = false;
+        self.app.write_fifo_not_full.next = false;
         match self.state.q.val() {
             MIG7SimState::Reset => {
-                self.calib_flag.d.next = false;
+                self.boot.d.next = 0.into();
                 if !self.reset.val() {
                     self.state.d.next = MIG7SimState::Calibrating;
                 }
             }
             MIG7SimState::Calibrating => {
-                self.calb_flag.d.next = true;
-                self.state.d.next = MIG7SimState::Idle;
+                self.boot.d.next = self.boot.q.val() + 1;
+                if self.boot.q.val().all() {
+                    self.state.d.next = MIG7SimState::Idle;
+                }
             }
             MIG7SimState::Idle => {
+                self.app.calib_done.next = true;
+                // Stall one cycle out of every four
+                self.app.ready.next = !self.tick.q.val().all();
+                self.app.write_fifo_not_full.next = true;
+            }
+            _ => {
+                self.state.d.next = MIG7SimState::Reset;
             }
         }
         if self.reset.val() {
             self.state.d.next = MIG7SimState::Reset;
         }
-        self.calib_done.next = self.calib_flag.val();
-        self.ready.next = self.state.q.val() == MIG7SimState::Idle;
+        // Writes
+        self.mem.write_address.next = self.app.address.val().get_bits::<8>(3);
+        self.mem.write_data.next = self.app.write_data_in.val();
+        self.mem.write_enable.next = self.app.enable.val()
+            & self.app.ready.val()
+            & (self.app.command.val() == 0)
+            & self.app.write_enable.val();
+        // Reads come back 4 clocks after they are accepted
+        self.issue_read.next =
+            self.app.enable.val() & self.app.ready.val() & (self.app.command.val() == 1);
+        self.read_address_1.d.next = self.app.address.val().get_bits::<8>(3);
+        self.read_address_2.d.next = self.read_address_1.q.val();
+        self.read_address_3.d.next = self.read_address_2.q.val();
+        self.mem.read_address.next = self.read_address_3.q.val();
+        self.read_pipe.d.next =
+            (self.read_pipe.q.val() << 1) | bit_cast::<4, 1>(self.issue_read.val().into());
+        self.app.read_data_out.next = self.mem.read_data.val();
+        self.app.read_data_valid.next = self.read_pipe.q.val().get_bit(3);
+        self.app.read_data_end.next = self.read_pipe.q.val().get_bit(3);
     }
 }
 
- */
+#[test]
+fn test_mig7_sim_synthesizes() {
+    let mut uut = MIG7Sim::default();
+    uut.connect_all();
+    yosys_validate("mig7_sim", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,243 @@
+use super::mcb_if::MCBInterface4GDDR3;
+#[cfg(test)]
+use super::mig7::MIG7Sim;
+use super::mig7::{MIG7NativeController, MemoryInterfaceGenerator7Series};
+use rust_hdl::prelude::*;
+
+// Turns the MIG native application interface into three HLS FIFO ports,
+// all in the MIG user clock domain:
+//
+//  - `command` takes one entry per burst.  Bits 28:0 are the MIG (word)
+//    address and bit 29 is set for a write and cleared for a read.
+//  - `write_data` supplies one 128 bit word for each write command, in
+//    the same order as the commands.
+//  - `read_data` returns one 128 bit word for each read command, in order.
+//
+// Reads are only issued when there is guaranteed space for the data in
+// the read FIFO, since the MIG has no way to stall its read data.
+#[derive(LogicBlock)]
+pub struct MIG7FIFOAdapter {
+    pub clock: Signal<In, Clock>,
+    pub app: MIG7NativeController,
+    pub command: FIFOWriteResponder<Bits<30>>,
+    pub write_data: FIFOWriteResponder<Bits<128>>,
+    pub read_data: FIFOReadResponder<Bits<128>>,
+    command_fifo: SyncFIFO<Bits<30>, 4, 5, 1>,
+    write_fifo: SyncFIFO<Bits<128>, 4, 5, 1>,
+    read_fifo: SyncFIFO<Bits<128>, 5, 6, 1>,
+    // Read slots in use - reads in flight plus words waiting in the read FIFO
+    reserved: DFF<Bits<6>>,
+    read_capacity: Constant<Bits<6>>,
+    is_write: Signal<Local, Bit>,
+    issue_read: Signal<Local, Bit>,
+    issue_write: Signal<Local, Bit>,
+    will_pop: Signal<Local, Bit>,
+}
+
+impl Default for MIG7FIFOAdapter {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            app: Default::default(),
+            command: Default::default(),
+            write_data: Default::default(),
+            read_data: Default::default(),
+            command_fifo: Default::default(),
+            write_fifo: Default::default(),
+            read_fifo: Default::default(),
+            reserved: Default::default(),
+            read_capacity: Constant::new(32.into()),
+            is_write: Default::default(),
+            issue_read: Default::default(),
+            issue_write: Default::default(),
+            will_pop: Default::default(),
+        }
+    }
+}
+
+impl Logic for MIG7FIFOAdapter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, command_fifo, write_fifo, read_fifo);
+        dff_setup!(self, clock, reserved);
+        FIFOWriteResponder::<Bits<30>>::link(&mut self.command, &mut self.command_fifo.bus_write);
+        FIFOWriteResponder::<Bits<128>>::link(&mut self.write_data, &mut self.write_fifo.bus_write);
+        FIFOReadResponder::<Bits<128>>::link(&mut self.read_data, &mut self.read_fifo.bus_read);
+        // Decide if the command at the head of the queue can go out this cycle
+        self.is_write.next = self.command_fifo.bus_read.data.val().get_bit(29);
+        self.issue_write.next = !self.command_fifo.bus_read.empty.val()
+            & self.is_write.val()
+            & !self.write_fifo.bus_read.empty.val()
+            & self.app.calib_done.val()
+            & self.app.ready.val()
+            & self.app.write_fifo_not_full.val();
+        self.issue_read.next = !self.command_fifo.bus_read.empty.val()
+            & !self.is_write.val()
+            & (self.reserved.q.val() < self.read_capacity.val())
+            & self.app.calib_done.val()
+            & self.app.ready.val();
+        // Drive the MIG - write data goes out with its command
+        self.app.address.next = self.command_fifo.bus_read.data.val().get_bits::<29>(0);
+        self.app.command.next = 1.into();
+        if self.is_write.val() {
+            self.app.command.next = 0.into();
+        }
+        self.app.enable.next = self.issue_read.val() | self.issue_write.val();
+        self.app.write_data_in.next = self.write_fifo.bus_read.data.val();
+        self.app.write_data_end.next = self.issue_write.val();
+        self.app.write_enable.next = self.issue_write.val();
+        self.app.write_data_mask.next = 0.into();
+        self.command_fifo.bus_read.read.next = self.issue_read.val() | self.issue_write.val();
+        self.write_fifo.bus_read.read.next = self.issue_write.val();
+        // Read data is always accepted - the slot was reserved when the read went out
+        self.read_fifo.bus_write.data.next = self.app.read_data_out.val();
+        self.read_fifo.bus_write.write.next = self.app.read_data_valid.val();
+        self.will_pop.next = self.read_data.read.val() & !self.read_fifo.bus_read.empty.val();
+        self.reserved.d.next = self.reserved.q.val()
+            + bit_cast::<6, 1>(self.issue_read.val().into())
+            - bit_cast::<6, 1>(self.will_pop.val().into());
+    }
+}
+
+#[test]
+fn test_mig7_fifo_adapter_synthesizes() {
+    let mut uut = MIG7FIFOAdapter::default();
+    uut.connect_all();
+    yosys_validate("mig7_fifo_adapter", &generate_verilog(&uut)).unwrap();
+}
+
+#[cfg(test)]
+#[derive(LogicBlock, Default)]
+struct MIG7AdapterTest {
+    clock: Signal<In, Clock>,
+    reset: Signal<In, Bit>,
+    command: FIFOWriteResponder<Bits<30>>,
+    write_data: FIFOWriteResponder<Bits<128>>,
+    read_data: FIFOReadResponder<Bits<128>>,
+    adapter: MIG7FIFOAdapter,
+    mig: MIG7Sim,
+}
+
+#[cfg(test)]
+impl Logic for MIG7AdapterTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, adapter, mig);
+        self.mig.reset.next = self.reset.val();
+        MIG7NativeController::join(&mut self.adapter.app, &mut self.mig.app);
+        FIFOWriteResponder::<Bits<30>>::link(&mut self.command, &mut self.adapter.command);
+        FIFOWriteResponder::<Bits<128>>::link(&mut self.write_data, &mut self.adapter.write_data);
+        FIFOReadResponder::<Bits<128>>::link(&mut self.read_data, &mut self.adapter.read_data);
+    }
+}
+
+#[test]
+fn test_mig7_fifo_adapter_round_trip() {
+    let mut uut = MIG7AdapterTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<MIG7AdapterTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    let pattern = |n: u64| -> Bits<128> {
+        (Bits::<128>::from(n * 0x0101_0101) << 64) | Bits::<128>::from(!n)
+    };
+    sim.add_testbench(move |mut sim: Sim<MIG7AdapterTest>| {
+        let mut x = sim.init()?;
+        x.reset.next = true;
+        wait_clock_cycles!(sim, clock, x, 4);
+        x.reset.next = false;
+        // Queue the writes, and then read everything back in reverse order
+        for n in 0..40_u64 {
+            x = sim.watch(|x| !x.command.full.val() && !x.write_data.full.val(), x)?;
+            x.command.data.next = ((1_u64 << 29) | (n * 8)).into();
+            x.command.write.next = true;
+            x.write_data.data.next = pattern(n);
+            x.write_data.write.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.command.write.next = false;
+            x.write_data.write.next = false;
+        }
+        for n in (0..40_u64).rev() {
+            x = sim.watch(|x| !x.command.full.val(), x)?;
+            x.command.data.next = (n * 8).into();
+            x.command.write.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.command.write.next = false;
+        }
+        for n in (0..40_u64).rev() {
+            x = sim.watch(|x| !x.read_data.empty.val(), x)?;
+            sim_assert_eq!(sim, x.read_data.data.val(), pattern(n), x);
+            x.read_data.read.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.read_data.read.next = false;
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
+
+// The MIG core and the FIFO adapter in one block.  The FIFO ports run on
+// the MIG user clock, which is exported as `clock`.
+#[derive(LogicBlock, Default)]
+pub struct MIG7BurstPort {
+    pub raw_pos_clock: Signal<In, Clock>,
+    pub raw_neg_clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub mcb: MCBInterface4GDDR3,
+    pub clock: Signal<Out, Clock>,
+    pub reset_out: Signal<Out, Bit>,
+    pub calib_done: Signal<Out, Bit>,
+    pub command: FIFOWriteResponder<Bits<30>>,
+    pub write_data: FIFOWriteResponder<Bits<128>>,
+    pub read_data: FIFOReadResponder<Bits<128>>,
+    mig: MemoryInterfaceGenerator7Series,
+    adapter: MIG7FIFOAdapter,
+}
+
+impl Logic for MIG7BurstPort {
+    #[hdl_gen]
+    fn update(&mut self) {
+        MCBInterface4GDDR3::link(&mut self.mcb, &mut self.mig.mcb);
+        FIFOWriteResponder::<Bits<30>>::link(&mut self.command, &mut self.adapter.command);
+        FIFOWriteResponder::<Bits<128>>::link(&mut self.write_data, &mut self.adapter.write_data);
+        FIFOReadResponder::<Bits<128>>::link(&mut self.read_data, &mut self.adapter.read_data);
+        self.mig.raw_pos_clock.next = self.raw_pos_clock.val();
+        self.mig.raw_neg_clock.next = self.raw_neg_clock.val();
+        self.mig.reset.next = self.reset.val();
+        self.clock.next = self.mig.clock.val();
+        self.reset_out.next = self.mig.reset_out.val();
+        self.calib_done.next = self.mig.calib_done.val();
+        self.adapter.clock.next = self.mig.clock.val();
+        // The MIG blackbox has a flat application interface
+        self.mig.address.next = self.adapter.app.address.val();
+        self.mig.command.next = self.adapter.app.command.val();
+        self.mig.enable.next = self.adapter.app.enable.val();
+        self.mig.write_data_in.next = self.adapter.app.write_data_in.val();
+        self.mig.write_data_end.next = self.adapter.app.write_data_end.val();
+        self.mig.write_data_mask.next = self.adapter.app.write_data_mask.val();
+        self.mig.write_enable.next = self.adapter.app.write_enable.val();
+        self.adapter.app.read_data_out.next = self.mig.read_data_out.val();
+        self.adapter.app.read_data_end.next = self.mig.read_data_end.val();
+        self.adapter.app.read_data_valid.next = self.mig.read_data_valid.val();
+        self.adapter.app.ready.next = self.mig.ready.val();
+        self.adapter.app.write_fifo_not_full.next = self.mig.write_fifo_not_full.val();
+        self.adapter.app.calib_done.next = self.mig.calib_done.val();
+    }
+}
+
+#[test]
+fn test_mig7_burst_port_gen() {
+    let mut uut = TopWrap::new(MIG7BurstPort::default());
+    uut.uut.raw_pos_clock.connect();
+    uut.uut.raw_neg_clock.connect();
+    uut.uut.reset.connect();
+    uut.uut.mcb.link_connect_dest();
+    uut.uut.command.data.connect();
+    uut.uut.command.write.connect();
+    uut.uut.write_data.data.connect();
+    uut.uut.write_data.write.connect();
+    uut.uut.read_data.read.connect();
+    uut.connect_all();
+    yosys_validate("mig7_burst_port", &generate_verilog(&uut)).unwrap();
+}
@@ -4,6 +4,7 @@ pub mod ddr_fifo7;
 pub mod download;
 pub mod mcb_if;
 pub mod mig7;
+pub mod mig7_adapter;
 pub mod pins;
 pub mod synth;
 pub mod sys_clock;
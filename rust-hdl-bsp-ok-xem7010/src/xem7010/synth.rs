@@ -1,6 +1,6 @@
 use std::fs::{copy, create_dir_all, remove_dir_all, File};
 use std::io::Write;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 
 use rust_hdl::prelude::*;
@@ -10,6 +10,9 @@ use rust_hdl_lib_ok_core::core::prelude::*;
 pub struct VivadoOptions {
     pub vivado_path: String,
     pub add_mig: bool,
+    // A MIG core built ahead of time with [generate_mig_ip_xem_7010].  When
+    // set, it is used in place of generating the core as part of the build.
+    pub mig_xci: Option<String>,
     pub assets: Vec<String>,
 }
 
@@ -18,6 +21,7 @@ impl Default for VivadoOptions {
         Self {
             vivado_path: env!("VIVADO_PATH", "Path to vivado executable").to_string(),
             add_mig: true,
+            mig_xci: None,
             assets: [
                 "okLibrary.v",
                 "okCoreHarness.v",
@@ -43,7 +47,7 @@ impl Default for VivadoOptions {
     }
 }
 
-pub fn add_mig_core_xem_7010(prefix: &str, _options: VivadoOptions) -> String {
+fn write_mig_project_xem_7010(prefix: &str) -> PathBuf {
     let mig_path = PathBuf::from(prefix).join("mig_a.prj");
     std::fs::write(&mig_path,
 r##"<?xml version='1.0' encoding='UTF-8'?>
@@ -176,9 +180,65 @@ r##"<?xml version='1.0' encoding='UTF-8'?>
     </Controller>
 </Project>
 "##).unwrap();
-    format!("create_ip  -vlnv xilinx.com:ip:mig_7series:4.* -module_name mig7
+    mig_path.canonicalize().unwrap()
+}
+
+fn mig_ip_tcl(mig_path: &Path, create_flags: &str) -> String {
+    format!("create_ip  -vlnv xilinx.com:ip:mig_7series:4.* -module_name mig7 {create_flags}
 set_property -dict [list CONFIG.XML_INPUT_FILE {{ {mig_path} }} CONFIG.RESET_BOARD_INTERFACE {{Custom}} CONFIG.MIG_DONT_TOUCH_PARAM {{Custom}} CONFIG.BOARD_MIG_PARAM {{Custom}}] [get_ips mig7]
-generate_target {{instantiation_template}} [get_files mig7.xci]", mig_path=mig_path.canonicalize().unwrap().to_string_lossy())
+", mig_path=mig_path.to_string_lossy(), create_flags=create_flags)
+}
+
+pub fn add_mig_core_xem_7010(prefix: &str, options: VivadoOptions) -> String {
+    if let Some(xci) = &options.mig_xci {
+        return format!("read_ip {{ {} }}", xci);
+    }
+    let mig_path = write_mig_project_xem_7010(prefix);
+    format!(
+        "{}generate_target {{instantiation_template}} [get_files mig7.xci]",
+        mig_ip_tcl(&mig_path, "")
+    )
+}
+
+// Generate and synthesize the MIG core on its own, under `prefix`.  This only
+// needs to be done once (per Vivado version) - pass the returned XCI file in
+// [VivadoOptions::mig_xci], and every design build reuses the same netlist
+// instead of regenerating the core.
+pub fn generate_mig_ip_xem_7010(prefix: &str, options: VivadoOptions) -> PathBuf {
+    let dir = PathBuf::from(prefix);
+    let _ = remove_dir_all(&dir);
+    let _ = create_dir_all(&dir);
+    let mig_path = write_mig_project_xem_7010(prefix);
+    std::fs::write(
+        dir.join("mig.tcl"),
+        format!(
+            r#"
+create_project -in_memory -part xc7a50tfgg484-1
+set_property target_language Verilog [current_project]
+{ip}
+generate_target all [get_ips mig7]
+synth_ip [get_ips mig7]
+puts "MIG Generation Complete"
+exit
+"#,
+            ip = mig_ip_tcl(&mig_path, "-dir .")
+        ),
+    )
+    .unwrap();
+    let output = Command::new(format!("{}/vivado", options.vivado_path))
+        .current_dir(dir.clone())
+        .arg("-mode")
+        .arg("batch")
+        .arg("-source")
+        .arg("mig.tcl")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    std::fs::write(dir.join("mig.out"), &stdout).unwrap();
+    std::fs::write(dir.join("mig.err"), &stderr).unwrap();
+    assert!(stdout.contains("MIG Generation Complete"));
+    dir.canonicalize().unwrap().join("mig7").join("mig7.xci")
 }
 
 pub fn generate_bitstream_xem_7010<U: Block>(mut uut: U, prefix: &str, options: VivadoOptions) {
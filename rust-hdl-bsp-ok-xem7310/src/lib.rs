@@ -0,0 +1 @@
+pub mod xem7310;
@@ -0,0 +1,30 @@
+use rust_hdl::prelude::*;
+
+pub mod pins;
+pub mod synth;
+
+use pins::*;
+use rust_hdl_lib_ok_core::core::prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct XEM7310 {}
+
+impl OpalKellyUSB3BSP for XEM7310 {
+    fn hi() -> OpalKellyUSB3HostInterface {
+        OpalKellyUSB3HostInterface::xem_7310()
+    }
+    fn ok_host() -> OpalKellyUSB3Host {
+        OpalKellyUSB3Host::xem_7310()
+    }
+
+    fn leds() -> Signal<Out, Bits<8>> {
+        xem_7310_leds()
+    }
+    fn clocks() -> Vec<Signal<In, Clock>> {
+        vec![xem_7310_pos_clock(), xem_7310_neg_clock()]
+    }
+
+    fn synth<U: Block>(uut: U, dir: &str) {
+        synth::synth_obj(uut, dir)
+    }
+}
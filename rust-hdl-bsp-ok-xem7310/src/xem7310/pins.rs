@@ -0,0 +1,38 @@
+use rust_hdl::prelude::*;
+
+// The LEDs are in a bank powered from 1.5V on the XEM7310.
+pub fn xem_7310_leds() -> Signal<Out, Bits<8>> {
+    let mut x = Signal::default();
+    for (ndx, name) in ["A13", "B13", "A14", "A15", "B15", "A16", "B16", "B17"]
+        .iter()
+        .enumerate()
+    {
+        x.add_location(ndx, name);
+        x.add_signal_type(ndx, SignalType::LowVoltageCMOS_1v5);
+    }
+    x
+}
+
+pub fn xem_7310_pos_clock() -> Signal<In, Clock> {
+    let mut x = Signal::default();
+    x.add_location(0, "W11");
+    x.add_signal_type(0, SignalType::LowVoltageDifferentialSignal_2v5);
+    x.connect();
+    x.add_constraint(PinConstraint {
+        index: 0,
+        constraint: Constraint::Timing(Timing::Periodic(PeriodicTiming {
+            net: "SystemClk".into(),
+            period_nanoseconds: 5.0,
+            duty_cycle: 50.0,
+        })),
+    });
+    x
+}
+
+pub fn xem_7310_neg_clock() -> Signal<In, Clock> {
+    let mut x = Signal::default();
+    x.add_location(0, "W12");
+    x.add_signal_type(0, SignalType::LowVoltageDifferentialSignal_2v5);
+    x.connect();
+    x
+}
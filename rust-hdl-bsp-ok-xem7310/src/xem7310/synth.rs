@@ -0,0 +1,136 @@
+use std::fs::{copy, create_dir_all, remove_dir_all, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use rust_hdl::prelude::*;
+use rust_hdl_lib_ok_core::core::prelude::*;
+
+#[derive(Clone, Debug)]
+pub struct VivadoOptions {
+    pub vivado_path: String,
+    pub assets: Vec<String>,
+}
+
+impl Default for VivadoOptions {
+    fn default() -> Self {
+        Self {
+            vivado_path: env!("VIVADO_PATH", "Path to vivado executable").to_string(),
+            assets: [
+                "okLibrary.v",
+                "okCoreHarness.v",
+                "okWireIn.v",
+                "okWireOut.v",
+                "okTriggerIn.v",
+                "okTriggerOut.v",
+                "okPipeIn.v",
+                "okPipeOut.v",
+                "okBTPipeIn.v",
+                "okBTPipeOut.v",
+            ]
+            .iter()
+            .map(|x| {
+                format!(
+                    "{}/XEM7310-A75/{}",
+                    env!("FP_PATH", "Path to FrontPanelHDL"),
+                    x
+                )
+            })
+            .collect(),
+        }
+    }
+}
+
+pub fn generate_bitstream_xem_7310<U: Block>(mut uut: U, prefix: &str, options: VivadoOptions) {
+    uut.connect_all();
+    let verilog_text = filter_blackbox_directives(&generate_verilog(&uut));
+    let xdc_text = rust_hdl::fpga::toolchains::vivado::generate_xdc(&uut);
+    let dir = PathBuf::from(prefix);
+    let out_file = dir.join("top.out");
+    if out_file.exists()
+        && String::from_utf8_lossy(&std::fs::read(out_file).unwrap())
+            .contains("Vivado Run Complete")
+    {
+        println!("Skipped synthesis!  Bitfile should exist");
+        return;
+    }
+    let _ = remove_dir_all(&dir);
+    let _ = create_dir_all(&dir);
+    let assets: Vec<String> = options.assets.clone();
+    std::fs::write(dir.clone().join("top.v"), verilog_text).unwrap();
+    std::fs::write(dir.clone().join("top.xdc"), xdc_text).unwrap();
+    for asset in &assets {
+        let src = PathBuf::from(asset);
+        let dest = dir.clone().join(src.file_name().unwrap());
+        println!("Copy from {:?} -> {:?}", asset, dest);
+        copy(asset, dest).unwrap();
+    }
+    let mut tcl_file = File::create(dir.clone().join("top.tcl")).unwrap();
+    write!(
+        tcl_file,
+        r#"
+create_project top . -part xc7a75tfgg484-1 -force
+
+add_files {{top.v top.xdc {assets} }}
+
+update_compile_order
+
+launch_runs synth_1 -jobs 8
+wait_on_run synth_1
+
+set status [ get_property STATUS [ get_runs synth_1 ] ]
+if {{ $status != "synth_design Complete!" }} {{
+ puts "Synthesis Failed"
+ exit
+}}
+
+launch_runs impl_1 -to_step write_bitstream -jobs 8
+wait_on_run impl_1
+
+set status [ get_property STATUS [ get_runs impl_1 ] ]
+if {{ $status != "write_bitstream Complete!" }} {{
+ puts "Implementation Failed"
+ exit
+}}
+
+puts "Vivado Run Complete"
+exit
+"#,
+        assets = assets
+            .iter()
+            .map(|x| PathBuf::from(x)
+                .file_name()
+                .unwrap()
+                .to_string_lossy()
+                .to_string())
+            .collect::<Vec<_>>()
+            .join(" "),
+    )
+    .unwrap();
+    let output = Command::new(format!("{}/vivado", options.vivado_path))
+        .current_dir(dir.clone())
+        .arg("-mode")
+        .arg("tcl")
+        .arg("-source")
+        .arg("top.tcl")
+        .output()
+        .unwrap();
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    std::fs::write(dir.clone().join("top.out"), &stdout).unwrap();
+    std::fs::write(dir.clone().join("top.err"), &stderr).unwrap();
+    assert!(stdout.contains("Vivado Run Complete"));
+    copy(
+        dir.clone().join("top.runs/impl_1/top.bit"),
+        dir.clone().join("top.bit"),
+    )
+    .unwrap();
+}
+
+pub fn synth_obj<U: Block>(uut: U, dir: &str) {
+    let vlog = generate_verilog(&uut);
+    find_ok_bus_collisions(&vlog);
+    let _xcd = rust_hdl::fpga::toolchains::vivado::generate_xdc(&uut);
+    yosys_validate(dir, &vlog).unwrap();
+    generate_bitstream_xem_7310(uut, dir, Default::default());
+}
@@ -0,0 +1,46 @@
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_ok_xem7310::xem7310::XEM7310;
+use rust_hdl_lib_ok_core::core::prelude::*;
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+pub struct OpalKellyUSB3Blinky {
+    pub hi: OpalKellyUSB3HostInterface,
+    pub ok_host: OpalKellyUSB3Host,
+    pub led: Signal<Out, Bits<8>>,
+    pub pulser: Pulser,
+}
+
+impl OpalKellyUSB3Blinky {
+    pub fn new<B: OpalKellyUSB3BSP>() -> Self {
+        Self {
+            hi: B::hi(),
+            ok_host: B::ok_host(),
+            led: B::leds(),
+            pulser: Pulser::new(MHZ100_8, 1.0, Duration::from_millis(500)),
+        }
+    }
+}
+
+impl Logic for OpalKellyUSB3Blinky {
+    #[hdl_gen]
+    fn update(&mut self) {
+        OpalKellyUSB3HostInterface::link(&mut self.hi, &mut self.ok_host.hi);
+        self.pulser.clock.next = self.ok_host.ok_clk.val();
+        self.pulser.enable.next = true;
+        // The LEDs are active low
+        if self.pulser.pulse.val() {
+            self.led.next = 0x00.into();
+        } else {
+            self.led.next = 0xFF.into();
+        }
+    }
+}
+
+#[test]
+fn test_opalkelly_xem_7310_synth_blinky() {
+    let mut uut = OpalKellyUSB3Blinky::new::<XEM7310>();
+    uut.hi.link_connect_dest();
+    uut.connect_all();
+    XEM7310::synth(uut, target_path!("xem_7310/blinky"));
+}
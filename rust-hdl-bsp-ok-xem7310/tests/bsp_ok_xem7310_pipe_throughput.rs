@@ -0,0 +1,125 @@
+use std::num::Wrapping;
+use std::time::Instant;
+
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_ok_xem7310::xem7310::XEM7310;
+use rust_hdl_lib_ok_core::core::prelude::*;
+use rust_hdl_lib_ok_core::test_common::tools::ok_test_prelude;
+use rust_hdl_lib_ok_frontpanel_sys::OkError;
+
+// Measures the throughput of the USB3 pipes in each direction.  Data
+// written to the pipe in (0x80) is summed, and the sum presented on a
+// wire out (0x20) so the host can verify that nothing was lost.  The
+// pipe out (0xA0) produces a 32 bit counter that advances on each read
+// strobe.  The pipe samples the data the cycle after the strobe, so the
+// first value the host sees is 1.
+#[derive(LogicBlock)]
+pub struct OpalKellyXEM7310PipeThroughput {
+    pub hi: OpalKellyUSB3HostInterface,
+    pub ok_host: OpalKellyUSB3Host,
+    pub accum: DFF<Bits<32>>,
+    pub counter: DFF<Bits<32>>,
+    pub o_wire: WireOutUSB3,
+    pub i_pipe: PipeInUSB3,
+    pub o_pipe: PipeOutUSB3,
+}
+
+impl OpalKellyXEM7310PipeThroughput {
+    pub fn new<B: OpalKellyUSB3BSP>() -> Self {
+        Self {
+            hi: B::hi(),
+            ok_host: B::ok_host(),
+            accum: Default::default(),
+            counter: Default::default(),
+            o_wire: WireOutUSB3::new(0x20),
+            i_pipe: PipeInUSB3::new(0x80),
+            o_pipe: PipeOutUSB3::new(0xA0),
+        }
+    }
+}
+
+impl Logic for OpalKellyXEM7310PipeThroughput {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Interface connections
+        OpalKellyUSB3HostInterface::link(&mut self.hi, &mut self.ok_host.hi);
+
+        // Clock connections
+        self.accum.clock.next = self.ok_host.ok_clk.val();
+        self.counter.clock.next = self.ok_host.ok_clk.val();
+
+        // Bus connections
+        self.o_wire.ok_he.next = self.ok_host.ok_he.val();
+        self.i_pipe.ok_he.next = self.ok_host.ok_he.val();
+        self.o_pipe.ok_he.next = self.ok_host.ok_he.val();
+        self.ok_host.ok_eh.next =
+            self.o_wire.ok_eh.val() | self.i_pipe.ok_eh.val() | self.o_pipe.ok_eh.val();
+
+        // Logic
+        self.accum.d.next = self.accum.q.val();
+        self.counter.d.next = self.counter.q.val();
+        if self.i_pipe.write.val() {
+            self.accum.d.next = self.accum.q.val() + self.i_pipe.dataout.val();
+        }
+        self.o_wire.datain.next = self.accum.q.val();
+        if self.o_pipe.read.val() {
+            self.counter.d.next = self.counter.q.val() + 1;
+        }
+        self.o_pipe.datain.next = self.counter.q.val();
+    }
+}
+
+#[test]
+fn test_opalkelly_xem_7310_synth_pipe_throughput() {
+    let mut uut = OpalKellyXEM7310PipeThroughput::new::<XEM7310>();
+    uut.hi.link_connect_dest();
+    uut.connect_all();
+    XEM7310::synth(uut, target_path!("xem_7310/pipe_throughput"));
+    test_opalkelly_xem_7310_pipe_throughput_runtime().unwrap();
+}
+
+#[cfg(test)]
+fn test_opalkelly_xem_7310_pipe_throughput_runtime() -> Result<(), OkError> {
+    let hnd = ok_test_prelude(
+        target_path!("xem_7310/pipe_throughput/top.bit"),
+        env!("XEM7310_SERIAL"),
+    )?;
+    // USB3 transfers must be a multiple of 16 bytes
+    let len = 64 * 1024 * 1024;
+    let data = (0..len).map(|_| rand::random::<u8>()).collect::<Vec<_>>();
+    let cpu_sum = data
+        .chunks(4)
+        .map(|x| Wrapping(u32::from_le_bytes([x[0], x[1], x[2], x[3]])))
+        .sum::<Wrapping<u32>>()
+        .0;
+    let start = Instant::now();
+    hnd.write_to_pipe_in(0x80, &data)?;
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "Pipe in: {} bytes in {:.3} s = {:.1} MB/s",
+        len,
+        elapsed,
+        len as f64 / elapsed / 1.0e6
+    );
+    hnd.update_wire_outs();
+    let fpga_sum = hnd.get_wire_out_u32(0x20);
+    println!("CPU sum {:x}, FPGA sum {:x}", cpu_sum, fpga_sum);
+    assert_eq!(cpu_sum, fpga_sum);
+    let mut data = vec![0_u8; len];
+    let start = Instant::now();
+    hnd.read_from_pipe_out(0xA0, &mut data)?;
+    let elapsed = start.elapsed().as_secs_f64();
+    println!(
+        "Pipe out: {} bytes in {:.3} s = {:.1} MB/s",
+        len,
+        elapsed,
+        len as f64 / elapsed / 1.0e6
+    );
+    for (ndx, val) in data.chunks(4).enumerate() {
+        assert_eq!(
+            u32::from_le_bytes([val[0], val[1], val[2], val[3]]),
+            ndx as u32 + 1
+        );
+    }
+    Ok(())
+}
@@ -0,0 +1,15 @@
+pub mod pins;
+pub mod synth;
+
+use rust_hdl::fpga::board::BoardMetadata;
+
+pub const METADATA: BoardMetadata = BoardMetadata {
+    name: "tang-nano-9k",
+    description: "Sipeed Tang Nano 9K (Gowin GW1NR-9)",
+    crate_name: "rust-hdl-bsp-tang-nano-9k",
+    clock_speed: "CLOCK_SPEED_27MHZ",
+    leds: "leds_n",
+    led_count: 6,
+    leds_active_low: true,
+    synth_args: &[],
+};
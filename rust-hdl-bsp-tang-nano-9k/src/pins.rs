@@ -0,0 +1,58 @@
+use rust_hdl::core::prelude::*;
+
+pub const CLOCK_SPEED_27MHZ: u64 = 27_000_000;
+
+// The 27 MHz crystal oscillator, on a 3.3V bank.
+pub fn clock() -> Signal<In, Clock> {
+    let mut x = Signal::<In, _>::default();
+    x.add_location(0, "52");
+    x.add_signal_type(0, SignalType::LowVoltageCMOS_3v3);
+    x.add_constraint(PinConstraint {
+        index: 0,
+        constraint: Constraint::Timing(Timing::Periodic(PeriodicTiming {
+            net: "sys_clk".into(),
+            period_nanoseconds: 37.037,
+            duty_cycle: 50.0,
+        })),
+    });
+    x.connect();
+    x
+}
+
+// The 6 LEDs are on a 1.8V bank, and are active low.
+pub fn leds_n() -> Signal<Out, Bits<6>> {
+    let mut x = Signal::<Out, _>::default();
+    for (ndx, uname) in ["10", "11", "13", "14", "15", "16"].iter().enumerate() {
+        x.add_location(ndx, uname);
+        x.add_signal_type(ndx, SignalType::LowVoltageCMOS_1v8);
+    }
+    x
+}
+
+// The two user buttons (S1 and S2) are also on the 1.8V bank, and are
+// active low.
+pub fn buttons_n() -> Signal<In, Bits<2>> {
+    let mut x = Signal::<In, _>::default();
+    for (ndx, uname) in ["4", "3"].iter().enumerate() {
+        x.add_location(ndx, uname);
+        x.add_signal_type(ndx, SignalType::LowVoltageCMOS_1v8);
+    }
+    x.connect();
+    x
+}
+
+// The UART lines go to the onboard BL702 USB bridge.
+pub fn uart_tx() -> Signal<Out, Bit> {
+    let mut x = Signal::<Out, _>::default();
+    x.add_location(0, "17");
+    x.add_signal_type(0, SignalType::LowVoltageCMOS_3v3);
+    x
+}
+
+pub fn uart_rx() -> Signal<In, Bit> {
+    let mut x = Signal::<In, _>::default();
+    x.add_location(0, "18");
+    x.add_signal_type(0, SignalType::LowVoltageCMOS_3v3);
+    x.connect();
+    x
+}
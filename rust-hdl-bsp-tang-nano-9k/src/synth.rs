@@ -0,0 +1,18 @@
+use rust_hdl::core::prelude::*;
+use rust_hdl::fpga::toolchains::apicula::{generate_bitstream_apicula, ApiculaOptions};
+
+use crate::pins::CLOCK_SPEED_27MHZ;
+
+// The Tang Nano 9K carries a GW1NR-LV9QN88PC6/I5, which is the default
+// target of the Apicula flow.  The bitstream ends up in `top.fs`, and can
+// be loaded with `openFPGALoader -b tangnano9k top.fs`.
+pub fn generate_bitstream<U: Block>(uut: U, prefix: &str) {
+    generate_bitstream_apicula(
+        uut,
+        prefix,
+        ApiculaOptions {
+            frequency_mhz: Some(CLOCK_SPEED_27MHZ as f64 / 1.0e6),
+            ..Default::default()
+        },
+    )
+}
@@ -0,0 +1,93 @@
+use rust_hdl::fpga::gowin::rpll::GowinRPLLBlock;
+use rust_hdl::fpga::toolchains::apicula::generate_cst;
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_tang_nano_9k::pins::CLOCK_SPEED_27MHZ;
+use rust_hdl_bsp_tang_nano_9k::{pins, synth};
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+pub struct TangNano9KPulser {
+    pulser: Pulser,
+    clock: Signal<In, Clock>,
+    leds_n: Signal<Out, Bits<6>>,
+}
+
+impl Logic for TangNano9KPulser {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pulser.enable.next = true;
+        clock!(self, clock, pulser);
+        self.leds_n.next = 0x3F.into();
+        if self.pulser.pulse.val() {
+            self.leds_n.next = 0x15.into();
+        }
+    }
+}
+
+impl Default for TangNano9KPulser {
+    fn default() -> Self {
+        let pulser = Pulser::new(CLOCK_SPEED_27MHZ, 1.0, Duration::from_millis(250));
+        Self {
+            pulser,
+            clock: pins::clock(),
+            leds_n: pins::leds_n(),
+        }
+    }
+}
+
+#[test]
+fn test_tang_nano_9k_cst() {
+    let mut uut = TangNano9KPulser::default();
+    uut.connect_all();
+    let cst = generate_cst(&uut);
+    assert!(cst.contains("IO_LOC \"clock\" 52;"));
+    assert!(cst.contains("IO_PORT \"clock\" IO_TYPE=LVCMOS33;"));
+    assert!(cst.contains("IO_LOC \"leds_n[5]\" 16;"));
+    assert!(cst.contains("IO_PORT \"leds_n[0]\" IO_TYPE=LVCMOS18;"));
+}
+
+#[test]
+fn synthesize_tang_nano_9k_pulser() {
+    let uut = TangNano9KPulser::default();
+    synth::generate_bitstream(uut, target_path!("tang_nano_9k/pulser"));
+}
+
+const CLOCK_SPEED_81MHZ: u64 = 81_000_000;
+
+#[derive(LogicBlock)]
+pub struct TangNano9KPulserPLL {
+    clock: Signal<In, Clock>,
+    leds_n: Signal<Out, Bits<6>>,
+    pll: GowinRPLLBlock<CLOCK_SPEED_27MHZ, CLOCK_SPEED_81MHZ>,
+    pulser: Pulser,
+}
+
+impl Logic for TangNano9KPulserPLL {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pll.clock_in.next = self.clock.val();
+        self.pulser.clock.next = self.pll.clock_out.val();
+        self.pulser.enable.next = self.pll.locked.val();
+        self.leds_n.next = 0x3F.into();
+        if self.pulser.pulse.val() {
+            self.leds_n.next = 0x2A.into();
+        }
+    }
+}
+
+impl Default for TangNano9KPulserPLL {
+    fn default() -> Self {
+        Self {
+            clock: pins::clock(),
+            leds_n: pins::leds_n(),
+            pll: Default::default(),
+            pulser: Pulser::new(CLOCK_SPEED_81MHZ, 1.0, Duration::from_millis(250)),
+        }
+    }
+}
+
+#[test]
+fn synthesize_tang_nano_9k_pulser_pll() {
+    let uut = TangNano9KPulserPLL::default();
+    synth::generate_bitstream(uut, target_path!("tang_nano_9k/pulser_pll"));
+}
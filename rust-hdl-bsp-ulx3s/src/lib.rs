@@ -0,0 +1,16 @@
+pub mod pins;
+pub mod sdram;
+pub mod synth;
+
+use rust_hdl::fpga::board::BoardMetadata;
+
+pub const METADATA: BoardMetadata = BoardMetadata {
+    name: "ulx3s",
+    description: "Radiona ULX3S (Lattice ECP5)",
+    crate_name: "rust-hdl-bsp-ulx3s",
+    clock_speed: "CLOCK_SPEED_25MHZ",
+    leds: "leds",
+    led_count: 8,
+    leds_active_low: false,
+    synth_args: &["synth::ULX3SVariant::LFE5U85F"],
+};
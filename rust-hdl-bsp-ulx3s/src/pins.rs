@@ -0,0 +1,112 @@
+use rust_hdl::core::prelude::*;
+
+pub const CLOCK_SPEED_25MHZ: u64 = 25_000_000;
+
+fn lvcmos33<D: Direction, T: Synth>(locations: &[&str]) -> Signal<D, T> {
+    let mut x = Signal::<D, T>::default();
+    for (ndx, name) in locations.iter().enumerate() {
+        x.add_location(ndx, name);
+        x.add_signal_type(ndx, SignalType::LowVoltageCMOS_3v3);
+    }
+    x
+}
+
+pub fn clock() -> Signal<In, Clock> {
+    let mut x = lvcmos33(&["G2"]);
+    x.add_constraint(PinConstraint {
+        index: 0,
+        constraint: Constraint::Timing(Timing::Periodic(PeriodicTiming {
+            net: "clk_25mhz".into(),
+            period_nanoseconds: 40.0,
+            duty_cycle: 50.0,
+        })),
+    });
+    x.connect();
+    x
+}
+
+pub fn leds() -> Signal<Out, Bits<8>> {
+    lvcmos33(&["B2", "C2", "C1", "D2", "D1", "E2", "E1", "H3"])
+}
+
+// Button 0 (PWR) is active low, the rest (F1, F2, up, down, left, right)
+// are active high.
+pub fn buttons() -> Signal<In, Bits<7>> {
+    let mut x = lvcmos33(&["D6", "R1", "T1", "R18", "V1", "U1", "H16"]);
+    x.connect();
+    x
+}
+
+pub fn ftdi_rxd() -> Signal<Out, Bit> {
+    lvcmos33(&["L4"])
+}
+
+pub fn ftdi_txd() -> Signal<In, Bit> {
+    let mut x = lvcmos33(&["M1"]);
+    x.connect();
+    x
+}
+
+// The GPDI (HDMI) connector.  Only the positive leg of each pair is
+// listed, the ECP5 generates the negative leg for LVCMOS33D outputs.
+pub fn gpdi_dp() -> Signal<Out, Bits<4>> {
+    let mut x = Signal::<Out, _>::default();
+    for (ndx, name) in ["A16", "A14", "A12", "A17"].iter().enumerate() {
+        x.add_location(ndx, name);
+        x.add_signal_type(ndx, SignalType::Custom("LVCMOS33D".into()));
+    }
+    x
+}
+
+#[derive(Clone, Debug, Default, LogicInterface)]
+pub struct ULX3SUSB {
+    pub dp: Signal<InOut, Bit>,
+    pub dn: Signal<InOut, Bit>,
+    pub pull_up_dp: Signal<Out, Bit>,
+    pub pull_up_dn: Signal<Out, Bit>,
+}
+
+// The US2 connector, which is wired directly to the FPGA.
+pub fn usb() -> ULX3SUSB {
+    ULX3SUSB {
+        dp: lvcmos33(&["E16"]),
+        dn: lvcmos33(&["F16"]),
+        pull_up_dp: lvcmos33(&["B12"]),
+        pull_up_dn: lvcmos33(&["C12"]),
+    }
+}
+
+#[derive(Clone, Debug, Default, LogicInterface)]
+pub struct ULX3SSDRAMPins {
+    pub clk: Signal<Out, Clock>,
+    pub cke: Signal<Out, Bit>,
+    pub cs_not: Signal<Out, Bit>,
+    pub we_not: Signal<Out, Bit>,
+    pub ras_not: Signal<Out, Bit>,
+    pub cas_not: Signal<Out, Bit>,
+    pub bank: Signal<Out, Bits<2>>,
+    pub address: Signal<Out, Bits<13>>,
+    pub dq: Signal<InOut, Bits<16>>,
+    pub dqm: Signal<Out, Bits<2>>,
+}
+
+pub fn sdram() -> ULX3SSDRAMPins {
+    ULX3SSDRAMPins {
+        clk: lvcmos33(&["F19"]),
+        cke: lvcmos33(&["F20"]),
+        cs_not: lvcmos33(&["P20"]),
+        we_not: lvcmos33(&["T20"]),
+        ras_not: lvcmos33(&["R20"]),
+        cas_not: lvcmos33(&["T19"]),
+        bank: lvcmos33(&["P19", "N20"]),
+        address: lvcmos33(&[
+            "M20", "M19", "L20", "L19", "K20", "K19", "K18", "J20", "J19", "H20", "N19", "G20",
+            "G19",
+        ]),
+        dq: lvcmos33(&[
+            "J16", "L18", "M18", "N18", "P18", "T18", "T17", "U20", "E19", "D20", "D19", "C20",
+            "E18", "F18", "J18", "J17",
+        ]),
+        dqm: lvcmos33(&["U19", "E20"]),
+    }
+}
@@ -0,0 +1,33 @@
+use crate::pins::ULX3SSDRAMPins;
+use rust_hdl::prelude::*;
+use rust_hdl::widgets::sdram::SDRAMDevice;
+
+// Connects one of the SDRAM controllers in the widgets crate to the
+// pins of the on-board SDRAM.  The data bus is shared between reads and
+// writes, so it goes through a tristate buffer.  The controllers do not
+// use the clock enable or the data masks, so those are tied off.
+#[derive(LogicBlock, Default)]
+pub struct ULX3SSDRAM {
+    pub sdram: SDRAMDevice<16>,
+    pub pins: ULX3SSDRAMPins,
+    buffer: TristateBuffer<Bits<16>>,
+}
+
+impl Logic for ULX3SSDRAM {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pins.clk.next = self.sdram.clk.val();
+        self.pins.cke.next = true;
+        self.pins.cs_not.next = self.sdram.cs_not.val();
+        self.pins.we_not.next = self.sdram.we_not.val();
+        self.pins.ras_not.next = self.sdram.ras_not.val();
+        self.pins.cas_not.next = self.sdram.cas_not.val();
+        self.pins.bank.next = self.sdram.bank.val();
+        self.pins.address.next = self.sdram.address.val();
+        self.pins.dqm.next = 0.into();
+        Signal::<InOut, Bits<16>>::link(&mut self.pins.dq, &mut self.buffer.bus);
+        self.buffer.write_enable.next = self.sdram.write_enable.val();
+        self.buffer.write_data.next = self.sdram.write_data.val();
+        self.sdram.read_data.next = self.buffer.read_data.val();
+    }
+}
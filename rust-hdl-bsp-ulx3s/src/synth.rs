@@ -0,0 +1,106 @@
+use rust_hdl::core::check_error::check_all;
+use rust_hdl::core::prelude::*;
+use rust_hdl::fpga::toolchains::ecp5::generate_lpf;
+use rust_hdl::fpga::toolchains::multiboot::ECP5DualBootLayout;
+use std::fs::{create_dir_all, read, remove_dir_all, write, File};
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::{Command, Output};
+use std::str::FromStr;
+
+// The ULX3S is sold with several sizes of ECP5.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ULX3SVariant {
+    LFE5U12F,
+    LFE5U25F,
+    LFE5U45F,
+    LFE5U85F,
+}
+
+impl ULX3SVariant {
+    fn nextpnr_flag(&self) -> &str {
+        match self {
+            ULX3SVariant::LFE5U12F => "--12k",
+            ULX3SVariant::LFE5U25F => "--25k",
+            ULX3SVariant::LFE5U45F => "--45k",
+            ULX3SVariant::LFE5U85F => "--85k",
+        }
+    }
+}
+
+fn save_stdout(output: Output, dir: &PathBuf, basename: &str) -> Result<(), std::io::Error> {
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let mut out_file = File::create(dir.clone().join(format!("{}.out", basename)))?;
+    write!(out_file, "{}", stdout)?;
+    let mut err_file = File::create(dir.clone().join(format!("{}.err", basename)))?;
+    write!(err_file, "{}", stderr)?;
+    Ok(())
+}
+
+pub fn generate_bitstream<U: Block>(mut uut: U, prefix: &str, variant: ULX3SVariant) {
+    uut.connect_all();
+    check_all(&uut).unwrap(); // TODO - Change from panic to return an error
+    let verilog_text = generate_verilog(&uut);
+    let lpf_text = generate_lpf(&uut);
+    let dir = PathBuf::from_str(prefix).unwrap();
+    let _ = remove_dir_all(&dir);
+    let _ = create_dir_all(&dir);
+    let mut v_file = File::create(dir.join("top.v")).unwrap();
+    write!(v_file, "{}", verilog_text).unwrap();
+    let mut lpf_file = File::create(dir.join("top.lpf")).unwrap();
+    write!(lpf_file, "{}", lpf_text).unwrap();
+    let output = Command::new("yosys")
+        .current_dir(dir.clone())
+        .arg(r#"-p synth_ecp5 -top top -json top.json"#)
+        .arg("top.v")
+        .output()
+        .unwrap();
+    save_stdout(output, &dir, "yosys_synth").unwrap();
+    let output = Command::new("nextpnr-ecp5")
+        .current_dir(dir.clone())
+        .args([
+            variant.nextpnr_flag(),
+            "--package",
+            "CABGA381",
+            "--json",
+            "top.json",
+            "--lpf",
+            "top.lpf",
+            "--textcfg",
+            "top.config",
+        ])
+        .output()
+        .unwrap();
+    save_stdout(output, &dir, "nextpnr").unwrap();
+    let output = Command::new("ecppack")
+        .current_dir(dir.clone())
+        .args(["--compress", "top.config", "top.bit"])
+        .output()
+        .unwrap();
+    save_stdout(output, &dir, "ecppack").unwrap();
+}
+
+// Build a dual boot flash image from two designs built with [generate_bitstream]
+// (in the given prefixes).  The golden design is packed again, so that it
+// boots the primary design from the flash.
+pub fn generate_dual_boot_image(
+    golden_prefix: &str,
+    primary_prefix: &str,
+    layout: ECP5DualBootLayout,
+    filename: &str,
+) {
+    let golden_dir = PathBuf::from_str(golden_prefix).unwrap();
+    let primary_dir = PathBuf::from_str(primary_prefix).unwrap();
+    let output = Command::new("ecppack")
+        .current_dir(golden_dir.clone())
+        .arg("--compress")
+        .args(layout.golden_ecppack_args())
+        .args(["top.config", "golden.bit"])
+        .output()
+        .unwrap();
+    save_stdout(output, &golden_dir, "ecppack_golden").unwrap();
+    let golden = read(golden_dir.join("golden.bit")).unwrap();
+    let primary = read(primary_dir.join("top.bit")).unwrap();
+    write(filename, layout.flash_image(&golden, &primary)).unwrap();
+}
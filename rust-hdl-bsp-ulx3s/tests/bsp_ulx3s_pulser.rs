@@ -0,0 +1,41 @@
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_ulx3s::pins::CLOCK_SPEED_25MHZ;
+use rust_hdl_bsp_ulx3s::synth::ULX3SVariant;
+use rust_hdl_bsp_ulx3s::{pins, synth};
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+pub struct ULX3SPulser {
+    pulser: Pulser,
+    clock: Signal<In, Clock>,
+    leds: Signal<Out, Bits<8>>,
+}
+
+impl Logic for ULX3SPulser {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pulser.enable.next = true;
+        clock!(self, clock, pulser);
+        self.leds.next = 0x00.into();
+        if self.pulser.pulse.val() {
+            self.leds.next = 0xAA.into();
+        }
+    }
+}
+
+impl Default for ULX3SPulser {
+    fn default() -> Self {
+        let pulser = Pulser::new(CLOCK_SPEED_25MHZ, 1.0, Duration::from_millis(250));
+        Self {
+            pulser,
+            clock: pins::clock(),
+            leds: pins::leds(),
+        }
+    }
+}
+
+#[test]
+fn synthesize_ulx3s_pulser() {
+    let uut = ULX3SPulser::default();
+    synth::generate_bitstream(uut, target_path!("ulx3s/pulser"), ULX3SVariant::LFE5U85F);
+}
@@ -0,0 +1,85 @@
+use rust_hdl::fpga::toolchains::ecp5::generate_lpf;
+use rust_hdl::prelude::*;
+use rust_hdl_bsp_ulx3s::pins::{ULX3SSDRAMPins, CLOCK_SPEED_25MHZ};
+use rust_hdl_bsp_ulx3s::sdram::ULX3SSDRAM;
+use rust_hdl_bsp_ulx3s::synth::ULX3SVariant;
+use rust_hdl_bsp_ulx3s::{pins, synth};
+
+// Streams a counter through the SDRAM backed FIFO, and checks that the
+// values come out in order.  The LEDs show the fill level of the FIFO,
+// and all of them light up if a mismatch is ever seen.
+#[derive(LogicBlock)]
+pub struct ULX3SSDRAMFIFOTest {
+    clock: Signal<In, Clock>,
+    leds: Signal<Out, Bits<8>>,
+    sdram_pins: ULX3SSDRAMPins,
+    pads: ULX3SSDRAM,
+    fifo: SDRAMFIFOController<13, 10, 4, 16, 25>,
+    counter: DFF<Bits<16>>,
+    expected: DFF<Bits<16>>,
+    error: DFF<Bit>,
+}
+
+impl Logic for ULX3SSDRAMFIFOTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, fifo, counter, expected, error);
+        self.fifo.ram_clock.next = self.clock.val();
+        SDRAMDriver::<16>::join(&mut self.fifo.sdram, &mut self.pads.sdram);
+        ULX3SSDRAMPins::link(&mut self.sdram_pins, &mut self.pads.pins);
+        // Write the counter into the FIFO whenever there is room
+        self.fifo.data_in.next = self.counter.q.val();
+        self.fifo.write.next = !self.fifo.full.val();
+        if !self.fifo.full.val() {
+            self.counter.d.next = self.counter.q.val() + 1;
+        }
+        // Drain the FIFO and check the sequence
+        self.fifo.read.next = !self.fifo.empty.val();
+        if !self.fifo.empty.val() {
+            if self.fifo.data_out.val() != self.expected.q.val() {
+                self.error.d.next = true;
+            }
+            self.expected.d.next = self.expected.q.val() + 1;
+        }
+        self.leds.next = self.fifo.status.val();
+        if self.error.q.val() {
+            self.leds.next = 0xFF.into();
+        }
+    }
+}
+
+impl Default for ULX3SSDRAMFIFOTest {
+    fn default() -> Self {
+        let timings = MemoryTimings::is42s16320f7(CLOCK_SPEED_25MHZ as f64);
+        Self {
+            clock: pins::clock(),
+            leds: pins::leds(),
+            sdram_pins: pins::sdram(),
+            pads: Default::default(),
+            fifo: SDRAMFIFOController::new(3, timings, OutputBuffer::Wired),
+            counter: Default::default(),
+            expected: Default::default(),
+            error: Default::default(),
+        }
+    }
+}
+
+#[test]
+fn test_ulx3s_sdram_fifo_constraints() {
+    let mut uut = ULX3SSDRAMFIFOTest::default();
+    uut.connect_all();
+    let lpf = generate_lpf(&uut);
+    assert!(lpf.contains("LOCATE COMP \"sdram_pins$dq[15]\" SITE \"J17\""));
+    assert!(lpf.contains("LOCATE COMP \"clock\" SITE \"G2\""));
+    assert!(lpf.contains("FREQUENCY PORT \"clock\" 25 MHz"));
+}
+
+#[test]
+fn synthesize_ulx3s_sdram_fifo() {
+    let uut = ULX3SSDRAMFIFOTest::default();
+    synth::generate_bitstream(
+        uut,
+        target_path!("ulx3s/sdram_fifo"),
+        ULX3SVariant::LFE5U85F,
+    );
+}
@@ -0,0 +1,107 @@
+//! Migration shims for designs written against the early RustHDL crates, where every
+//! signal carried its clock domain as a type parameter (e.g., `Signal<In, Bits<8>, Mhz1>`),
+//! and domains were declared with `make_domain!`.  The current library does not track
+//! domains in the type system (clock domain crossings are found by the checks that run
+//! before simulation instead), so the shims simply erase the domain parameter:
+//!
+//! ```rust
+//! use rust_hdl::compat::prelude::*;
+//!
+//! make_domain!(Mhz1, 1_000_000);
+//!
+//! #[derive(LogicBlock, Default)]
+//! struct Delay {
+//!     pub clock: Signal<In, Clock, Mhz1>,
+//!     pub data_in: Signal<In, Bits<8>, Mhz1>,
+//!     pub data_out: Signal<Out, Bits<8>, Mhz1>,
+//!     delay: DFF<Bits<8>, Mhz1>,
+//! }
+//!
+//! impl Logic for Delay {
+//!     #[hdl_gen]
+//!     fn update(&mut self) {
+//!         dff_setup!(self, clock, delay);
+//!         self.delay.d.next = self.data_in.val();
+//!         self.data_out.next = self.delay.q.val();
+//!     }
+//! }
+//! ```
+//!
+//! The compat prelude is the regular prelude, with the domain-typed names taking the place
+//! of the current ones, so a design can be moved over one file at a time by switching its
+//! `use` line.  A design that mixes the two will need to use the current names from
+//! [crate::prelude] explicitly (e.g., `prelude::Signal<In, Bit>`) in the files that still
+//! import the compat prelude.  Once a file no longer uses the domain parameters, it can go
+//! back to [crate::prelude].  Only the widgets that took a domain parameter have shims here.
+
+use crate::prelude as current;
+
+/// A clock domain, as declared by [make_domain!](crate::make_domain).  The frequency is
+/// kept, so that testbenches that derived their clock periods from the domain still work.
+pub trait Domain {
+    /// The frequency of the clock of the domain (in Hz), or 0 for the [Async] domain
+    const FREQUENCY: u64;
+    /// The half period of the clock of the domain (in picoseconds, the default time unit
+    /// of a [Simulation](current::Simulation)), for use with `add_clock`
+    fn half_period() -> u64 {
+        assert!(Self::FREQUENCY > 0, "The asynchronous domain has no clock");
+        (current::SIMULATION_TIME_ONE_SECOND / Self::FREQUENCY) / 2
+    }
+}
+
+/// The domain of signals that are not synchronous to any clock (e.g., inputs from pins).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct Async;
+
+impl Domain for Async {
+    const FREQUENCY: u64 = 0;
+}
+
+/// Declare a clock domain with the given name and frequency (in Hz).
+#[macro_export]
+macro_rules! make_domain {
+    ($name: ident, $frequency: expr) => {
+        #[derive(Copy, Clone, Debug, Default)]
+        pub struct $name;
+
+        impl $crate::compat::Domain for $name {
+            const FREQUENCY: u64 = $frequency;
+        }
+    };
+}
+
+/// Maps a domain-typed name onto the current type.  It is implemented for every
+/// [Domain], and exists only so that the aliases below make use of their domain
+/// parameters (an alias cannot simply drop one).
+pub trait EraseDomain<X> {
+    /// The current type
+    type Erased;
+}
+
+impl<X, C: Domain> EraseDomain<X> for C {
+    type Erased = X;
+}
+
+/// A [Signal](current::Signal) in the clock domain `C`
+pub type Signal<D, T, C> = <C as EraseDomain<current::Signal<D, T>>>::Erased;
+
+/// A [DFF](current::DFF) in the clock domain `C`
+pub type DFF<T, C> = <C as EraseDomain<current::DFF<T>>>::Erased;
+
+/// A [SynchronousFIFO](current::SynchronousFIFO) in the clock domain `C`
+pub type SyncFIFO<T, C, const N: usize, const NP1: usize, const BLOCK_SIZE: u32> =
+    <C as EraseDomain<current::SynchronousFIFO<T, N, NP1, BLOCK_SIZE>>>::Erased;
+
+/// An [AsynchronousFIFO](current::AsynchronousFIFO) from the domain `W` (written) to the
+/// domain `R` (read)
+pub type AsyncFIFO<T, W, R, const N: usize, const NP1: usize, const BLOCK_SIZE: u32> =
+    <W as EraseDomain<
+        <R as EraseDomain<current::AsynchronousFIFO<T, N, NP1, BLOCK_SIZE>>>::Erased,
+    >>::Erased;
+
+/// The regular prelude, with the domain-typed names in place of the current ones.
+pub mod prelude {
+    pub use super::{Async, AsyncFIFO, Domain, EraseDomain, Signal, SyncFIFO, DFF};
+    pub use crate::make_domain;
+    pub use crate::prelude::*;
+}
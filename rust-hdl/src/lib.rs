@@ -1973,10 +1973,14 @@
 
 #![warn(missing_docs)]
 
+///! Migration shims for designs written against the early, domain-typed RustHDL API.
+pub mod compat;
 ///! Tools for documenting RustHDL designs, including the generation of SVGs from simulation waveforms.
 pub mod docs;
 ///! A series of High Level Synthesis blocks used to build System-on-Chip designs quickly.
 pub use rust_hdl_lib_hls as hls;
+///! A runtime for the host side of HLS System-on-Chip designs, with transports for talking to them.
+pub use rust_hdl_lib_host as host;
 ///! Prelude module defines common symbols to make importing RustHDL easier.
 pub mod prelude;
 ///! The core RustHDL module.  Defines variable width bits, signals, logical blocks, etc.
@@ -0,0 +1,80 @@
+use rust_hdl::prelude::*;
+
+// Wide enough to need padding out to a power of two
+const WIDTH: usize = 9;
+
+#[derive(LogicBlock)]
+struct BitOpsTest {
+    clock: Signal<In, Clock>,
+    data_in: Signal<In, Bits<WIDTH>>,
+    encoder: PriorityEncoder<WIDTH, 4>,
+    zeros: LeadingZeroCounter<WIDTH, 4>,
+    ones: PopulationCount<WIDTH, 4>,
+}
+
+impl BitOpsTest {
+    fn new(registered: bool) -> Self {
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            encoder: PriorityEncoder::new(registered),
+            zeros: LeadingZeroCounter::new(registered),
+            ones: PopulationCount::new(registered),
+        }
+    }
+}
+
+impl Logic for BitOpsTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, encoder, zeros, ones);
+        self.encoder.data_in.next = self.data_in.val();
+        self.zeros.data_in.next = self.data_in.val();
+        self.ones.data_in.next = self.data_in.val();
+    }
+}
+
+fn test_bit_ops(registered: bool, name: &str) {
+    let mut uut = BitOpsTest::new(registered);
+    uut.clock.connect();
+    uut.data_in.connect();
+    uut.connect_all();
+    yosys_validate(name, &generate_verilog(&uut)).unwrap();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<BitOpsTest>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<BitOpsTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        for val in 0_u64..(1 << WIDTH) {
+            x.data_in.next = val.to_bits();
+            wait_clock_cycle!(sim, clock, x);
+            let lowest = if val == 0 {
+                0
+            } else {
+                val.trailing_zeros() as u64
+            };
+            sim_assert_eq!(sim, x.encoder.index.val(), lowest, x);
+            sim_assert_eq!(sim, x.encoder.valid.val(), val != 0, x);
+            sim_assert_eq!(
+                sim,
+                x.zeros.count.val(),
+                (val.leading_zeros() - (64 - WIDTH as u32)) as u64,
+                x
+            );
+            sim_assert_eq!(sim, x.ones.count.val(), val.count_ones() as u64, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 20_000, &vcd_path!(format!("{}.vcd", name)))
+        .unwrap();
+}
+
+#[test]
+fn test_bit_ops_combinational() {
+    test_bit_ops(false, "bit_ops_combinational");
+}
+
+#[test]
+fn test_bit_ops_registered() {
+    test_bit_ops(true, "bit_ops_registered");
+}
@@ -0,0 +1,108 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Channel {
+    pub clock: Signal<In, Clock>,
+    pub phase: Signal<In, Bits<8>>,
+    pub out: Signal<Out, Bits<8>>,
+    counter: DFF<Bits<8>>,
+}
+
+impl Logic for Channel {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.out.next = self.counter.q.val() + self.phase.val();
+    }
+}
+
+#[derive(LogicBlock)]
+struct Row<const C: usize> {
+    chans: [Channel; C],
+}
+
+impl<const C: usize> Default for Row<C> {
+    fn default() -> Self {
+        Self {
+            chans: array_init::array_init(|_| Default::default()),
+        }
+    }
+}
+
+impl<const C: usize> Logic for Row<C> {
+    #[hdl_gen]
+    fn update(&mut self) {}
+}
+
+#[derive(LogicBlock)]
+struct Grid<const R: usize, const C: usize> {
+    pub clock: Signal<In, Clock>,
+    pub outs: Signal<Out, Bits<32>>,
+    rows: [Row<C>; R],
+    phases: [[Constant<Bits<8>>; C]; R],
+}
+
+impl<const R: usize, const C: usize> Default for Grid<R, C> {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            outs: Default::default(),
+            rows: array_init::array_init(|_| Default::default()),
+            phases: array_init::array_init(|i| {
+                array_init::array_init(|j| Constant::new((((i * C + j) * 10) as u64).to_bits()))
+            }),
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> Logic for Grid<R, C> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.outs.next = 0.into();
+        for i in 0..R {
+            for j in 0..C {
+                self.rows[i].chans[j].clock.next = self.clock.val();
+                self.rows[i].chans[j].phase.next = self.phases[i][j].val();
+                self.outs.next = self.outs.val()
+                    | (bit_cast::<32, 8>(self.rows[i].chans[j].out.val())
+                        << (i * 16 + j * 8) as u64);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_block_array_verilog() {
+    let mut uut: Grid<2, 2> = Default::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("rows$1$chans$0$phase = phases$1$0;"));
+    assert!(vlog.contains("localparam  phases$1$1 = 8'h1e;"));
+    assert!(!vlog.contains("[i]") && !vlog.contains("[j]"));
+    yosys_validate("block_arrays", &vlog).unwrap();
+}
+
+#[test]
+fn test_block_array_simulates() {
+    let mut uut: Grid<2, 2> = Default::default();
+    uut.connect_all();
+    let mut sim = test_harness!(Grid<2, 2>, clocks: [clock => 100_000_000]);
+    sim.add_testbench(move |mut sim: Sim<Grid<2, 2>>| {
+        let mut x = sim.init()?;
+        for _ in 0..30 {
+            wait_clock_cycle!(sim, clock, x);
+            let lane0 = x.outs.val().get_bits::<8>(0);
+            for n in 1..4 {
+                sim_assert_eq!(
+                    sim,
+                    x.outs.val().get_bits::<8>(n * 8),
+                    lane0 + (n as u32 * 10).to_bits::<8>(),
+                    x
+                );
+            }
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000_000).unwrap();
+}
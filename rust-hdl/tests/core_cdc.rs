@@ -0,0 +1,249 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_hdl::prelude::*;
+use std::sync::{Arc, Mutex};
+
+// Send pulses as fast as the synchronizer allows (with random gaps), and
+// record when each was sent, when each arrived, and when busy cleared
+fn pulse_sync_run(in_half: u64, out_half: u64, count: usize) -> (Vec<u64>, Vec<u64>, Vec<u64>) {
+    let sent = Arc::new(Mutex::new(vec![]));
+    let cleared = Arc::new(Mutex::new(vec![]));
+    let received = Arc::new(Mutex::new(vec![]));
+    let mut uut = PulseSynchronizer::default();
+    uut.pulse_in.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(in_half, |x: &mut Box<PulseSynchronizer>| {
+        x.clock_in.next = !x.clock_in.val()
+    });
+    sim.add_clock(out_half, |x: &mut Box<PulseSynchronizer>| {
+        x.clock_out.next = !x.clock_out.val()
+    });
+    let sent_times = sent.clone();
+    let cleared_times = cleared.clone();
+    sim.add_testbench(move |mut sim: Sim<PulseSynchronizer>| {
+        let mut x = sim.init()?;
+        let mut rng = StdRng::seed_from_u64(0x5EED);
+        wait_clock_cycles!(sim, clock_in, x, 4);
+        for _ in 0..count {
+            x.pulse_in.next = true;
+            wait_clock_true!(sim, clock_in, x);
+            sent_times.lock().unwrap().push(sim.time());
+            wait_clock_false!(sim, clock_in, x);
+            sim_assert!(sim, x.busy.val(), x);
+            // A pulse sent while busy is dropped
+            x.pulse_in.next = rng.gen::<bool>();
+            while x.busy.val() {
+                wait_clock_true!(sim, clock_in, x);
+                x.pulse_in.next = false;
+                if !x.busy.val() {
+                    cleared_times.lock().unwrap().push(sim.time());
+                }
+                wait_clock_false!(sim, clock_in, x);
+            }
+            wait_clock_cycles!(sim, clock_in, x, rng.gen::<u8>() % 4);
+        }
+        wait_clock_cycles!(sim, clock_in, x, 20);
+        sim.done(x)
+    });
+    let received_times = received.clone();
+    sim.add_testbench(move |mut sim: Sim<PulseSynchronizer>| {
+        let mut x = sim.init()?;
+        // Keep watching for a while after the last pulse, to catch any extras
+        let mut quiet = 0;
+        while quiet < 100 {
+            wait_clock_true!(sim, clock_out, x);
+            quiet += 1;
+            if x.pulse_out.val() {
+                received_times.lock().unwrap().push(sim.time());
+                if received_times.lock().unwrap().len() < count {
+                    quiet = 0;
+                }
+            }
+            wait_clock_false!(sim, clock_out, x);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000_000).unwrap();
+    let sent = sent.lock().unwrap().clone();
+    let cleared = cleared.lock().unwrap().clone();
+    let received = received.lock().unwrap().clone();
+    (sent, cleared, received)
+}
+
+fn check_pulse_sync(in_half: u64, out_half: u64) {
+    let (sent, cleared, received) = pulse_sync_run(in_half, out_half, 40);
+    let (in_period, out_period) = (2 * in_half, 2 * out_half);
+    // The pulses sent while busy are dropped, so each of the others arrives exactly once
+    assert_eq!(sent.len(), 40);
+    assert_eq!(received.len(), 40);
+    for ((sent, received), cleared) in sent.iter().zip(&received).zip(&cleared) {
+        // The pulse is seen at the first rising clock_out edge after it fires
+        let fired = received - out_half;
+        assert!(fired > *sent && fired <= sent + 2 * out_period);
+        assert!(*cleared <= sent + 3 * out_period + 2 * in_period);
+    }
+}
+
+#[test]
+fn test_pulse_synchronizer_fast_to_slow() {
+    check_pulse_sync(5, 35);
+}
+
+#[test]
+fn test_pulse_synchronizer_slow_to_fast() {
+    check_pulse_sync(35, 5);
+}
+
+#[test]
+fn test_pulse_synchronizer_with_metastability() {
+    set_metastability_model(Some(MetastabilityModel {
+        probability: 0.3,
+        seed: 0xC0FFEE,
+    }));
+    let (sent, _, received) = pulse_sync_run(5, 35, 40);
+    set_metastability_model(None);
+    assert_eq!(sent.len(), received.len());
+}
+
+#[derive(Default)]
+struct HandshakeLog {
+    sent: Vec<(u64, u16)>,
+    acked: Vec<u64>,
+    cleared: Vec<u64>,
+    valid: Vec<u64>,
+    accepted: Vec<(u64, u16)>,
+}
+
+fn handshake_run(in_half: u64, out_half: u64, count: usize) -> HandshakeLog {
+    let log = Arc::new(Mutex::new(HandshakeLog::default()));
+    let mut uut = HandshakeSynchronizer::<Bits<16>>::default();
+    uut.data_in.connect();
+    uut.send.connect();
+    uut.accept.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(in_half, |x: &mut Box<HandshakeSynchronizer<Bits<16>>>| {
+        x.clock_in.next = !x.clock_in.val()
+    });
+    sim.add_clock(out_half, |x: &mut Box<HandshakeSynchronizer<Bits<16>>>| {
+        x.clock_out.next = !x.clock_out.val()
+    });
+    let source_log = log.clone();
+    sim.add_testbench(move |mut sim: Sim<HandshakeSynchronizer<Bits<16>>>| {
+        let mut x = sim.init()?;
+        let mut rng = StdRng::seed_from_u64(0xFACE);
+        wait_clock_cycles!(sim, clock_in, x, 4);
+        for _ in 0..count {
+            let value = rng.gen::<u16>();
+            x.data_in.next = value.to_bits();
+            x.send.next = true;
+            wait_clock_true!(sim, clock_in, x);
+            source_log.lock().unwrap().sent.push((sim.time(), value));
+            wait_clock_false!(sim, clock_in, x);
+            x.send.next = false;
+            x.data_in.next = rng.gen::<u16>().to_bits();
+            while x.busy.val() {
+                wait_clock_true!(sim, clock_in, x);
+                if x.ack.val() {
+                    source_log.lock().unwrap().acked.push(sim.time());
+                }
+                if !x.busy.val() {
+                    source_log.lock().unwrap().cleared.push(sim.time());
+                }
+                wait_clock_false!(sim, clock_in, x);
+            }
+            wait_clock_cycles!(sim, clock_in, x, rng.gen::<u8>() % 4);
+        }
+        sim.done(x)
+    });
+    let destination_log = log.clone();
+    sim.add_testbench(move |mut sim: Sim<HandshakeSynchronizer<Bits<16>>>| {
+        let mut x = sim.init()?;
+        let mut rng = StdRng::seed_from_u64(0xBEEF);
+        while destination_log.lock().unwrap().accepted.len() < count {
+            wait_clock_true!(sim, clock_out, x);
+            if x.valid.val() {
+                destination_log.lock().unwrap().valid.push(sim.time());
+                wait_clock_false!(sim, clock_out, x);
+                // Take a while to accept the value
+                wait_clock_cycles!(sim, clock_out, x, rng.gen::<u8>() % 8);
+                let value = x.data_out.val().index() as u16;
+                x.accept.next = true;
+                wait_clock_true!(sim, clock_out, x);
+                destination_log
+                    .lock()
+                    .unwrap()
+                    .accepted
+                    .push((sim.time(), value));
+                wait_clock_false!(sim, clock_out, x);
+                x.accept.next = false;
+            } else {
+                wait_clock_false!(sim, clock_out, x);
+            }
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000_000).unwrap();
+    let log = std::mem::take(&mut *log.lock().unwrap());
+    log
+}
+
+fn check_handshake(in_half: u64, out_half: u64) {
+    let log = handshake_run(in_half, out_half, 40);
+    let (in_period, out_period) = (2 * in_half, 2 * out_half);
+    assert_eq!(log.accepted.len(), 40);
+    assert_eq!(log.acked.len(), 40);
+    assert_eq!(log.cleared.len(), 40);
+    for i in 0..40 {
+        let (sent, value) = log.sent[i];
+        let (accepted, taken) = log.accepted[i];
+        assert_eq!(value, taken);
+        // valid is seen at the rising clock_out edge after it is asserted
+        let valid = log.valid[i] - out_period;
+        assert!(valid > sent && valid <= sent + 2 * out_period);
+        // ack is seen at the rising clock_in edge after it is strobed
+        let acked = log.acked[i] - in_period;
+        assert!(acked > accepted && acked <= accepted + 2 * in_period);
+        let cleared = log.cleared[i] - in_period;
+        assert!(cleared <= accepted + 5 * in_period + 3 * out_period);
+    }
+}
+
+#[test]
+fn test_handshake_synchronizer_fast_to_slow() {
+    check_handshake(5, 35);
+}
+
+#[test]
+fn test_handshake_synchronizer_slow_to_fast() {
+    check_handshake(35, 5);
+}
+
+#[test]
+fn test_handshake_synchronizer_with_metastability() {
+    set_metastability_model(Some(MetastabilityModel {
+        probability: 0.3,
+        seed: 0xD00D,
+    }));
+    let log = handshake_run(35, 5, 40);
+    set_metastability_model(None);
+    assert_eq!(log.accepted.len(), 40);
+    for (sent, accepted) in log.sent.iter().zip(&log.accepted) {
+        assert_eq!(sent.1, accepted.1);
+    }
+}
+
+#[test]
+fn test_cdc_widgets_synthesize() {
+    let mut uut = PulseSynchronizer::default();
+    uut.pulse_in.connect();
+    uut.connect_all();
+    yosys_validate("cdc_pulse", &generate_verilog(&uut)).unwrap();
+    let mut uut = HandshakeSynchronizer::<Bits<16>>::default();
+    uut.data_in.connect();
+    uut.send.connect();
+    uut.accept.connect();
+    uut.connect_all();
+    yosys_validate("cdc_handshake", &generate_verilog(&uut)).unwrap();
+}
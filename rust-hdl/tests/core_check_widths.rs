@@ -0,0 +1,83 @@
+use rust_hdl::core::check_error::CheckError;
+use rust_hdl::core::check_widths::{check_widths, WidthLintKind};
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct WrapBugs {
+    pub clock: Signal<In, Clock>,
+    pub a: Signal<In, Bits<8>>,
+    pub b: Signal<In, Bits<8>>,
+    pub wide: Signal<In, Bits<16>>,
+    pub expired: Signal<Out, Bit>,
+    pub same: Signal<Out, Bit>,
+    pub sum: Signal<Out, Bits<8>>,
+    pub low: Signal<Out, Bits<8>>,
+    counter: DFF<Bits<8>>,
+}
+
+impl Logic for WrapBugs {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.expired.next = self.counter.q.val() == 300;
+        self.same.next = bit_cast::<16, 8>(self.a.val()) == self.wide.val();
+        self.sum.next = self.a.val() + self.b.val();
+        self.low.next = bit_cast::<8, 16>(self.wide.val());
+        match self.counter.q.val().index() {
+            0x100 => self.low.next = 0.into(),
+            _ => {}
+        }
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct Wrapper {
+    pub clock: Signal<In, Clock>,
+    pub sum: Signal<Out, Bits<9>>,
+    inner: WrapBugs,
+}
+
+impl Logic for Wrapper {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, inner);
+        self.inner.a.next = 0.into();
+        self.inner.b.next = 0.into();
+        self.inner.wide.next = 0.into();
+        self.sum.next = bit_cast::<9, 8>(self.inner.sum.val()) + 1;
+    }
+}
+
+#[test]
+fn test_width_lints() {
+    let mut uut = Wrapper::default();
+    uut.connect_all();
+    let lints = match check_widths(&uut) {
+        Err(CheckError::SuspiciousWidths(lints)) => lints,
+        x => panic!("Expected width lints, got {:?}", x),
+    };
+    for lint in &lints {
+        println!("{}", lint);
+    }
+    let kinds = lints.iter().map(|x| x.kind).collect::<Vec<_>>();
+    assert_eq!(
+        kinds,
+        vec![
+            WidthLintKind::ConstantOutOfRange,
+            WidthLintKind::MixedWidthComparison,
+            WidthLintKind::AdditionWithoutCarry,
+            WidthLintKind::TruncatingAssignment,
+            WidthLintKind::ConstantOutOfRange,
+        ]
+    );
+    assert!(lints.iter().all(|x| x.path == "uut$inner"));
+    assert!(lints[0].description.contains("counter$q is 8 bits wide"));
+}
+
+#[test]
+fn test_width_lints_pass_on_clean_design() {
+    let mut uut = Strobe::<32>::new(1_000_000, 1000.0);
+    uut.connect_all();
+    assert!(check_widths(&uut).is_ok());
+}
@@ -0,0 +1,99 @@
+use rust_hdl::core::check_clock_gating::check_clock_gating;
+use rust_hdl::core::check_error::CheckError;
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct GatedCounters {
+    pub clock: Signal<In, Clock>,
+    pub enable: Signal<In, Bit>,
+    pub gated: Signal<Out, Bits<8>>,
+    pub enabled: Signal<Out, Bits<8>>,
+    gate: ClockGate,
+    gated_count: DFF<Bits<8>>,
+    enabled_count: DFFWithEnable<Bits<8>>,
+}
+
+impl Logic for GatedCounters {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.gate.clock.next = self.clock.val();
+        self.gate.enable.next = self.enable.val();
+        dff_setup!(self, clock, enabled_count);
+        self.gated_count.clock.next = self.gate.clock_out.val();
+        self.gated_count.d.next = self.gated_count.q.val() + 1;
+        self.enabled_count.d.next = self.enabled_count.q.val() + 1;
+        self.enabled_count.enable.next = self.enable.val();
+        self.gated.next = self.gated_count.q.val();
+        self.enabled.next = self.enabled_count.q.val();
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct HandGated {
+    pub tick: Signal<In, Bit>,
+    pub enable: Signal<In, Bit>,
+    pub count: Signal<Out, Bits<8>>,
+    counter: DFF<Bits<8>>,
+}
+
+impl Logic for HandGated {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.counter.clock.next = (self.tick.val() & self.enable.val()).into();
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.count.next = self.counter.q.val();
+    }
+}
+
+#[test]
+fn test_gated_counters_synthesize() {
+    let mut uut = GatedCounters::default();
+    uut.connect_all();
+    yosys_validate("clock_gate_counters", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_gated_counters_only_count_enabled_cycles() {
+    let mut uut = GatedCounters::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<GatedCounters>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<GatedCounters>| {
+        let mut x = sim.init()?;
+        let mut expected = 0;
+        for cycle in 0..40 {
+            // Change the enable just after a rising edge, like a register would
+            wait_clock_true!(sim, clock, x);
+            x = sim.wait(1, x)?;
+            x.enable.next = (cycle / 5) % 2 == 1;
+            wait_clock_cycle!(sim, clock, x);
+            if x.enable.val() {
+                expected += 1;
+            }
+            sim_assert_eq!(sim, x.gated.val(), expected, x);
+            sim_assert_eq!(sim, x.enabled.val(), expected, x);
+        }
+        sim_assert_eq!(sim, expected, 20, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10000, &vcd_path!("clock_gate.vcd"))
+        .unwrap()
+}
+
+#[test]
+fn test_check_clock_gating_flags_hand_gated_clocks() {
+    let mut uut = HandGated::default();
+    uut.connect_all();
+    match check_clock_gating(&uut) {
+        Err(CheckError::GatedClocks(clocks)) => {
+            assert_eq!(clocks.len(), 1);
+            assert_eq!(clocks[0].name, "counter$clock");
+        }
+        x => panic!("Expected the gated clock to be flagged, got {:?}", x),
+    }
+    let mut uut = GatedCounters::default();
+    uut.connect_all();
+    assert!(check_clock_gating(&uut).is_ok());
+}
@@ -0,0 +1,79 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct ClockMuxTest {
+    pub clock_0: Signal<In, Clock>,
+    pub clock_1: Signal<In, Clock>,
+    pub select: Signal<In, Bit>,
+    pub clock_out: Signal<Out, Clock>,
+    mux: ClockMux,
+}
+
+impl Logic for ClockMuxTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.mux.clock_0.next = self.clock_0.val();
+        self.mux.clock_1.next = self.clock_1.val();
+        self.mux.select.next = self.select.val();
+        self.clock_out.next = self.mux.clock_out.val();
+    }
+}
+
+#[test]
+fn test_clock_mux_test_synthesizes() {
+    let mut uut = ClockMuxTest::default();
+    uut.connect_all();
+    yosys_validate("clock_mux_test", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_clock_mux_switches_without_glitches() {
+    let mut uut = ClockMuxTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<ClockMuxTest>| {
+        x.clock_0.next = !x.clock_0.val()
+    });
+    sim.add_clock(3, |x: &mut Box<ClockMuxTest>| {
+        x.clock_1.next = !x.clock_1.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<ClockMuxTest>| {
+        let mut x = sim.init()?;
+        // Record the width of every pulse (high and low) of the output
+        let mut pulses = vec![];
+        let mut level = x.clock_out.val().clk;
+        let mut width = 0;
+        for time in 0..2000 {
+            // Switch back and forth, in the middle of the pulses
+            if time % 500 == 2 {
+                x.select.next = (time / 500) % 2 == 1;
+            }
+            x = sim.wait(1, x)?;
+            width += 1;
+            if x.clock_out.val().clk != level {
+                pulses.push((level, width));
+                level = x.clock_out.val().clk;
+                width = 0;
+            }
+            // Well after each switch, the output follows the selected clock
+            if time % 500 == 400 {
+                if x.select.val() {
+                    sim_assert_eq!(sim, x.clock_out.val(), x.clock_1.val(), x);
+                } else {
+                    sim_assert_eq!(sim, x.clock_out.val(), x.clock_0.val(), x);
+                }
+            }
+        }
+        // No high pulse is shorter than half a period of the faster clock,
+        // and neither is any low pulse
+        for (level, width) in pulses.into_iter().skip(1) {
+            sim_assert!(sim, width >= 3, x);
+            if level {
+                sim_assert!(sim, width == 3 || width == 5, x);
+            }
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10_000, &vcd_path!("clock_mux.vcd"))
+        .unwrap();
+}
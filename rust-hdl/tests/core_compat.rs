@@ -0,0 +1,100 @@
+use rust_hdl::compat::prelude::*;
+
+make_domain!(Mhz1, 1_000_000);
+make_domain!(Mhz2, 2_000_000);
+
+// A counter that is generic over its clock domain, as the early designs were written
+#[derive(LogicBlock, Default)]
+struct Counter<C: Domain> {
+    pub clock: Signal<In, Clock, C>,
+    pub count: Signal<Out, Bits<8>, C>,
+    counter: DFF<Bits<8>, C>,
+}
+
+impl<C: Domain> Logic for Counter<C> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.count.next = self.counter.q.val();
+    }
+}
+
+// Counts in one domain, and passes the count to the other through a FIFO
+#[derive(LogicBlock, Default)]
+struct Crossing {
+    pub write_clock: Signal<In, Clock, Mhz1>,
+    pub read_clock: Signal<In, Clock, Mhz2>,
+    pub data_out: Signal<Out, Bits<8>, Mhz2>,
+    pub empty: Signal<Out, Bit, Mhz2>,
+    pub read: Signal<In, Bit, Mhz2>,
+    counter: Counter<Mhz1>,
+    fifo: AsyncFIFO<Bits<8>, Mhz1, Mhz2, 4, 5, 1>,
+    buffer: SyncFIFO<Bits<8>, Mhz2, 2, 3, 1>,
+}
+
+impl Logic for Crossing {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.counter.clock.next = self.write_clock.val();
+        self.fifo.write_clock.next = self.write_clock.val();
+        self.fifo.read_clock.next = self.read_clock.val();
+        self.buffer.clock.next = self.read_clock.val();
+        self.fifo.data_in.next = self.counter.count.val();
+        self.fifo.write.next = !self.fifo.full.val();
+        self.buffer.data_in.next = self.fifo.data_out.val();
+        self.buffer.write.next = !self.fifo.empty.val() & !self.buffer.full.val();
+        self.fifo.read.next = !self.fifo.empty.val() & !self.buffer.full.val();
+        self.data_out.next = self.buffer.data_out.val();
+        self.empty.next = self.buffer.empty.val();
+        self.buffer.read.next = self.read.val();
+    }
+}
+
+#[test]
+fn test_domains_keep_their_frequencies() {
+    assert_eq!(Mhz1::FREQUENCY, 1_000_000);
+    assert_eq!(Mhz1::half_period(), 500_000);
+    assert_eq!(Mhz2::half_period(), 250_000);
+    assert_eq!(Async::FREQUENCY, 0);
+}
+
+#[test]
+fn test_domain_typed_design_synthesizes() {
+    let mut uut = Crossing::default();
+    uut.read.connect();
+    uut.connect_all();
+    yosys_validate("compat_crossing", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_domain_typed_design_simulates() {
+    let mut uut = Crossing::default();
+    uut.read.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(Mhz1::half_period(), |x: &mut Box<Crossing>| {
+        x.write_clock.next = !x.write_clock.val()
+    });
+    sim.add_clock(Mhz2::half_period(), |x: &mut Box<Crossing>| {
+        x.read_clock.next = !x.read_clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<Crossing>| {
+        let mut x = sim.init()?;
+        let mut last = None;
+        for _ in 0..50 {
+            x = sim.watch(|x| !x.empty.val(), x)?;
+            let value = x.data_out.val().index() as u64;
+            if let Some(last) = last {
+                // The counter runs while the FIFO is full, so values may be skipped
+                sim_assert!(sim, value != last, x);
+            }
+            last = Some(value);
+            x.read.next = true;
+            wait_clock_cycle!(sim, read_clock, x);
+            x.read.next = false;
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000_000_000).unwrap();
+}
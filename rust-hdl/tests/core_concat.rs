@@ -0,0 +1,55 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Packer {
+    pub a: Signal<In, Bits<8>>,
+    pub b: Signal<In, Bits<8>>,
+    pub swapped: Signal<Out, Bits<16>>,
+    pub packed: Signal<Out, Bits<12>>,
+    pub middle: Signal<Out, Bits<8>>,
+}
+
+impl Logic for Packer {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.swapped.next = self.b.val().concat(self.a.val());
+        self.packed.next = self
+            .a
+            .val()
+            .get_bits::<4>(4)
+            .concat::<4, 8>(bits::<4>(0xA))
+            .concat(self.b.val().get_bits::<4>(0));
+        self.middle.next = self.a.val().concat::<8, 16>(self.b.val()).get_bits::<8>(4);
+    }
+}
+
+#[test]
+fn test_concat_verilog() {
+    let mut uut = Packer::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("swapped = {b, a};"));
+    assert!(vlog.contains("packed = {{a[(32'h4)+:(4)], 4'ha}, b[(32'h0)+:(4)]};"));
+    assert!(vlog.contains("middle = (({a, b}) >> (32'h4)) & 8'hff;"));
+    yosys_validate("concat", &vlog).unwrap();
+}
+
+#[test]
+fn test_concat_simulates() {
+    let mut uut = Packer::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_testbench(move |mut sim: Sim<Packer>| {
+        let mut x = sim.init()?;
+        for (a, b) in [(0x12_u64, 0x34_u64), (0xFF, 0x00), (0x5A, 0xC3)] {
+            x.a.next = a.to_bits();
+            x.b.next = b.to_bits();
+            x = sim.wait(1, x)?;
+            sim_assert_eq!(sim, x.swapped.val(), (b << 8) | a, x);
+            sim_assert_eq!(sim, x.packed.val(), ((a >> 4) << 8) | 0xA0 | (b & 0xF), x);
+            sim_assert_eq!(sim, x.middle.val(), ((a << 8 | b) >> 4) & 0xFF, x);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000).unwrap();
+}
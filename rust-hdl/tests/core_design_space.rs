@@ -0,0 +1,75 @@
+use rust_hdl::prelude::*;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Arc;
+
+// Fill a FIFO, and report how many words it took, and how many clocks the
+// first word took to show up on the read side
+fn fill_fifo<const N: usize, const NP1: usize>() -> (Result<(), SimError>, u64, u64) {
+    let capacity = Arc::new(AtomicU64::new(0));
+    let latency = Arc::new(AtomicU64::new(0));
+    let mut uut = SynchronousFIFO::<Bits<8>, N, NP1, 1>::default();
+    uut.read.connect();
+    uut.write.connect();
+    uut.data_in.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SynchronousFIFO<Bits<8>, N, NP1, 1>>| {
+        x.clock.next = !x.clock.val()
+    });
+    let words = capacity.clone();
+    let clocks = latency.clone();
+    sim.add_testbench(move |mut sim: Sim<SynchronousFIFO<Bits<8>, N, NP1, 1>>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 4);
+        while !x.full.val() {
+            x.write.next = true;
+            x.data_in.next = (words.load(Ordering::SeqCst) & 0xFF).to_bits();
+            wait_clock_cycle!(sim, clock, x);
+            x.write.next = false;
+            words.fetch_add(1, Ordering::SeqCst);
+            if clocks.load(Ordering::SeqCst) == 0 {
+                let mut count = 1;
+                while x.empty.val() {
+                    wait_clock_cycle!(sim, clock, x);
+                    count += 1;
+                }
+                clocks.store(count, Ordering::SeqCst);
+            }
+        }
+        sim.done(x)
+    });
+    let result = sim.run(Box::new(uut), 100_000);
+    (
+        result,
+        capacity.load(Ordering::SeqCst),
+        latency.load(Ordering::SeqCst),
+    )
+}
+
+#[test]
+fn test_fifo_design_space() {
+    let mut space = DesignSpace::new("fifo_space");
+    design_points!(space, (N, NP1) in [(2, 3), (3, 4), (4, 5)], |point| {
+        point.estimate(SynchronousFIFO::<Bits<8>, N, NP1, 1>::default());
+        let (result, capacity, latency) = fill_fifo::<N, NP1>();
+        point
+            .check("simulation", result)
+            .measure("capacity", capacity as f64)
+            .measure("latency", latency as f64);
+    });
+    let csv = space.to_csv();
+    println!("{}", csv);
+    let lines = csv.lines().collect::<Vec<_>>();
+    assert_eq!(lines.len(), 4);
+    assert!(
+        lines[0].starts_with("N,NP1,cells,luts,flip_flops,logic_depth,fmax_mhz,capacity,latency")
+    );
+    for (point, depth) in space.points().iter().zip([2_u32, 3, 4]) {
+        assert_eq!(point.metric("capacity"), Some(2_u64.pow(depth) as f64));
+        // The write clock, and one more for the flag to clear
+        assert_eq!(point.metric("latency"), Some(2.0));
+        // The synthesis needs yosys, but the simulation does not
+        assert!(point.resources.is_some() ^ !point.errors.is_empty());
+        assert!(point.errors.iter().all(|x| x.starts_with("synthesis")));
+    }
+}
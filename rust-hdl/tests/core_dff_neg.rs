@@ -0,0 +1,58 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct EdgeCounters {
+    pub clock: Signal<In, Clock>,
+    pub rising: Signal<Out, Bits<8>>,
+    pub falling: Signal<Out, Bits<8>>,
+    pub both: Signal<Out, Bits<8>>,
+    rise_count: DFF<Bits<8>>,
+    fall_count: DFFNeg<Bits<8>>,
+    both_count: DualEdgeDFF<Bits<8>>,
+}
+
+impl Logic for EdgeCounters {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, rise_count, fall_count, both_count);
+        self.rise_count.d.next = self.rise_count.q.val() + 1;
+        self.fall_count.d.next = self.fall_count.q.val() + 1;
+        self.both_count.d.next = self.both_count.q.val() + 1;
+        self.rising.next = self.rise_count.q.val();
+        self.falling.next = self.fall_count.q.val();
+        self.both.next = self.both_count.q.val();
+    }
+}
+
+#[test]
+fn test_edge_counters_synthesize() {
+    let mut uut = EdgeCounters::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("always @(negedge clock)"));
+    yosys_validate("dff_neg", &vlog).unwrap();
+}
+
+#[test]
+fn test_edge_counters_count_their_edges() {
+    let mut uut = EdgeCounters::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<EdgeCounters>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<EdgeCounters>| {
+        let mut x = sim.init()?;
+        for i in 1..10 {
+            wait_clock_true!(sim, clock, x);
+            sim_assert_eq!(sim, x.rising.val(), i, x);
+            sim_assert_eq!(sim, x.falling.val(), i - 1, x);
+            sim_assert_eq!(sim, x.both.val(), 2 * i - 1, x);
+            wait_clock_false!(sim, clock, x);
+            sim_assert_eq!(sim, x.rising.val(), i, x);
+            sim_assert_eq!(sim, x.falling.val(), i, x);
+            sim_assert_eq!(sim, x.both.val(), 2 * i, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10000, &vcd_path!("dff_neg.vcd"))
+        .unwrap()
+}
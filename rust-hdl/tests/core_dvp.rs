@@ -0,0 +1,261 @@
+use rust_hdl::prelude::*;
+use rust_hdl::widgets::sdram::buffer::SDRAMOnChipBuffer;
+
+fn camera_config(bytes_per_pixel: usize) -> DVPCameraConfig {
+    DVPCameraConfig {
+        width: 8,
+        height: 4,
+        bytes_per_pixel,
+        h_blank: 6,
+        v_blank: 3,
+    }
+}
+
+// A camera sending RGB565 pixels to a capture block, with the pixel clock
+// a little slower than (and unrelated to) the system clock
+#[derive(LogicBlock)]
+struct DVPCaptureTest {
+    clock: Signal<In, Clock>,
+    xclk: Signal<In, Clock>,
+    camera: DVPCameraSimulator,
+    capture: DVPCapture,
+}
+
+impl Logic for DVPCaptureTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.camera.clock.next = self.xclk.val();
+        self.capture.clock.next = self.clock.val();
+        self.capture.pclk.next = self.camera.pclk.val();
+        DVPWiresCamera::join(&mut self.camera.wires, &mut self.capture.wires);
+    }
+}
+
+fn make_dvp_capture_test() -> DVPCaptureTest {
+    let mut uut = DVPCaptureTest {
+        clock: Default::default(),
+        xclk: Default::default(),
+        camera: DVPCameraSimulator::new(camera_config(2)),
+        capture: DVPCapture::new(2),
+    };
+    uut.capture.read.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_dvp_capture_test_synthesizes() {
+    let uut = make_dvp_capture_test();
+    yosys_validate("dvp_capture_test", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_dvp_capture_frames() {
+    let uut = make_dvp_capture_test();
+    let config = camera_config(2);
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<DVPCaptureTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_clock(7, |x: &mut Box<DVPCaptureTest>| x.xclk.next = !x.xclk.val());
+    sim.add_testbench(move |mut sim: Sim<DVPCaptureTest>| {
+        let mut x = sim.init()?;
+        for frame in 0..3 {
+            for y in 0..config.height {
+                for px in 0..config.width {
+                    x = sim.watch(|x| x.clock.val().clk && !x.capture.empty.val(), x)?;
+                    sim_assert_eq!(
+                        sim,
+                        x.capture.data_out.val(),
+                        dvp_test_pixel(frame, px, y).to_bits::<16>(),
+                        x
+                    );
+                    sim_assert_eq!(sim, x.capture.start_of_frame.val(), px == 0 && y == 0, x);
+                    sim_assert_eq!(sim, x.capture.start_of_line.val(), px == 0, x);
+                    x.capture.read.next = true;
+                    wait_clock_cycle!(sim, clock, x);
+                    x.capture.read.next = false;
+                }
+            }
+        }
+        sim_assert!(sim, !x.capture.overflow.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("dvp_capture.vcd"))
+        .unwrap();
+}
+
+// A camera sending raw Bayer pixels, decimated to RGB565 at half resolution
+#[derive(LogicBlock)]
+struct DVPBayerTest {
+    clock: Signal<In, Clock>,
+    xclk: Signal<In, Clock>,
+    camera: DVPCameraSimulator,
+    capture: DVPCapture,
+    bayer: BayerDecimator<4>,
+}
+
+impl Logic for DVPBayerTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.camera.clock.next = self.xclk.val();
+        clock!(self, clock, capture, bayer);
+        self.capture.pclk.next = self.camera.pclk.val();
+        DVPWiresCamera::join(&mut self.camera.wires, &mut self.capture.wires);
+        self.bayer.data_in.next = self.capture.data_out.val();
+        self.bayer.start_of_frame_in.next = self.capture.start_of_frame.val();
+        self.bayer.start_of_line_in.next = self.capture.start_of_line.val();
+        self.bayer.empty_in.next = self.capture.empty.val();
+        self.capture.read.next = self.bayer.read.val();
+    }
+}
+
+fn make_dvp_bayer_test() -> DVPBayerTest {
+    let mut uut = DVPBayerTest {
+        clock: Default::default(),
+        xclk: Default::default(),
+        camera: DVPCameraSimulator::new(camera_config(1)),
+        capture: DVPCapture::new(1),
+        bayer: Default::default(),
+    };
+    uut.bayer.full.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_dvp_bayer_test_synthesizes() {
+    let uut = make_dvp_bayer_test();
+    yosys_validate("dvp_bayer_test", &generate_verilog(&uut)).unwrap();
+}
+
+fn expected_rgb565(frame: usize, x: usize, y: usize) -> u16 {
+    let raw = |x: usize, y: usize| dvp_test_pixel(frame, x, y) & 0xFF;
+    let red = raw(2 * x, 2 * y);
+    let green = raw(2 * x + 1, 2 * y) + raw(2 * x, 2 * y + 1);
+    let blue = raw(2 * x + 1, 2 * y + 1);
+    ((red >> 3) << 11) | ((green >> 3) << 5) | (blue >> 3)
+}
+
+#[test]
+fn test_dvp_bayer_decimation() {
+    let uut = make_dvp_bayer_test();
+    let config = camera_config(1);
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<DVPBayerTest>| x.clock.next = !x.clock.val());
+    sim.add_clock(7, |x: &mut Box<DVPBayerTest>| x.xclk.next = !x.xclk.val());
+    sim.add_testbench(move |mut sim: Sim<DVPBayerTest>| {
+        let mut x = sim.init()?;
+        // Hold off the decimator for a while, to check it waits for room
+        x.bayer.full.next = true;
+        wait_clock_cycles!(sim, clock, x, 40);
+        x.bayer.full.next = false;
+        for frame in 0..2 {
+            for y in 0..config.height / 2 {
+                for px in 0..config.width / 2 {
+                    x = sim.watch(|x| x.clock.val().clk && x.bayer.write.val(), x)?;
+                    sim_assert_eq!(
+                        sim,
+                        x.bayer.data_out.val(),
+                        expected_rgb565(frame, px, y).to_bits::<16>(),
+                        x
+                    );
+                    sim_assert_eq!(sim, x.bayer.start_of_frame.val(), px == 0 && y == 0, x);
+                    sim_assert_eq!(sim, x.bayer.start_of_line.val(), px == 0, x);
+                    wait_clock_cycle!(sim, clock, x);
+                }
+            }
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("dvp_bayer.vcd"))
+        .unwrap();
+}
+
+// The capture feeding a frame buffer in SDRAM, through the SDRAM backed FIFO
+#[derive(LogicBlock)]
+struct DVPSDRAMTest {
+    clock: Signal<In, Clock>,
+    xclk: Signal<In, Clock>,
+    camera: DVPCameraSimulator,
+    capture: DVPCapture,
+    dram: SDRAMSimulator<6, 4, 10, 16>,
+    buffer: SDRAMOnChipBuffer<16>,
+    fifo: SDRAMFIFOController<6, 4, 16, 16, 12>,
+}
+
+impl Logic for DVPSDRAMTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.camera.clock.next = self.xclk.val();
+        clock!(self, clock, capture, fifo);
+        self.fifo.ram_clock.next = self.clock.val();
+        self.capture.pclk.next = self.camera.pclk.val();
+        DVPWiresCamera::join(&mut self.camera.wires, &mut self.capture.wires);
+        SDRAMDriver::<16>::join(&mut self.fifo.sdram, &mut self.buffer.buf_in);
+        SDRAMDriver::<16>::join(&mut self.buffer.buf_out, &mut self.dram.sdram);
+        self.fifo.data_in.next = self.capture.data_out.val();
+        self.fifo.write.next = !self.capture.empty.val() & !self.fifo.full.val();
+        self.capture.read.next = !self.capture.empty.val() & !self.fifo.full.val();
+    }
+}
+
+fn make_dvp_sdram_test() -> DVPSDRAMTest {
+    let timings = MemoryTimings::fast_boot_sim(100e6);
+    let mut uut = DVPSDRAMTest {
+        clock: Default::default(),
+        xclk: Default::default(),
+        camera: DVPCameraSimulator::new(camera_config(2)),
+        capture: DVPCapture::new(2),
+        dram: SDRAMSimulator::new(timings),
+        buffer: Default::default(),
+        fifo: SDRAMFIFOController::new(3, timings, OutputBuffer::DelayTwo),
+    };
+    uut.fifo.read.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_dvp_sdram_test_synthesizes() {
+    let uut = make_dvp_sdram_test();
+    yosys_validate("dvp_sdram_test", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_dvp_capture_to_sdram() {
+    let uut = make_dvp_sdram_test();
+    let config = camera_config(2);
+    let mut sim = Simulation::new();
+    sim.add_clock(5000, |x: &mut Box<DVPSDRAMTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_clock(20000, |x: &mut Box<DVPSDRAMTest>| {
+        x.xclk.next = !x.xclk.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<DVPSDRAMTest>| {
+        let mut x = sim.init()?;
+        // The capture starts at the beginning of a frame, so the SDRAM
+        // holds whole frames, one after the other
+        for frame in 0..2 {
+            for y in 0..config.height {
+                for px in 0..config.width {
+                    x = sim.watch(|x| x.clock.val().clk && !x.fifo.empty.val(), x)?;
+                    sim_assert_eq!(
+                        sim,
+                        x.fifo.data_out.val(),
+                        dvp_test_pixel(frame, px, y).to_bits::<16>(),
+                        x
+                    );
+                    x.fifo.read.next = true;
+                    wait_clock_cycle!(sim, clock, x);
+                    x.fifo.read.next = false;
+                }
+            }
+        }
+        sim_assert!(sim, !x.capture.overflow.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000_000, &vcd_path!("dvp_sdram.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,131 @@
+use rand::Rng;
+use rust_hdl::prelude::*;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+// A block that counts how many times it is updated
+#[derive(LogicBlock, Default)]
+struct Follower {
+    pub enable: Signal<In, Bit>,
+    pub active: Signal<Out, Bit>,
+    _updates: usize,
+}
+
+impl Logic for Follower {
+    fn update(&mut self) {
+        self._updates += 1;
+        self.active.next = self.enable.val();
+    }
+    fn connect(&mut self) {
+        self.active.connect();
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct Busy {
+    pub clock: Signal<In, Clock>,
+    pub enable: Signal<In, Bit>,
+    pub count: Signal<Out, Bits<8>>,
+    pub active: Signal<Out, Bit>,
+    counter: DFF<Bits<8>>,
+    follower: Follower,
+}
+
+impl Logic for Busy {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.count.next = self.counter.q.val();
+        self.follower.enable.next = self.enable.val();
+        self.active.next = self.follower.active.val();
+    }
+}
+
+// Returns the final count and the number of times the follower was updated
+fn run_busy(event_driven: bool) -> (u64, usize) {
+    let mut uut = Busy::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.set_event_driven(event_driven);
+    let updates = Arc::new(AtomicUsize::new(0));
+    let result = updates.clone();
+    let count = Arc::new(AtomicUsize::new(0));
+    let final_count = count.clone();
+    sim.add_clock(5, |x: &mut Box<Busy>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Busy>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        x.enable.next = true;
+        wait_clock_cycles!(sim, clock, x, 2);
+        sim_assert!(sim, x.active.val(), x);
+        x.enable.next = false;
+        wait_clock_cycles!(sim, clock, x, 100);
+        sim_assert!(sim, !x.active.val(), x);
+        count.store(x.count.val().index(), Ordering::SeqCst);
+        updates.store(x.follower._updates, Ordering::SeqCst);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000).unwrap();
+    (
+        final_count.load(Ordering::SeqCst) as u64,
+        result.load(Ordering::SeqCst),
+    )
+}
+
+#[test]
+fn test_event_driven_matches_full_update() {
+    let (full_count, full_updates) = run_busy(false);
+    let (event_count, event_updates) = run_busy(true);
+    assert_eq!(full_count, event_count);
+    // The follower only needs to run when its enable input changes
+    assert!(event_updates < 20);
+    assert!(full_updates > 10 * event_updates);
+}
+
+#[test]
+fn test_event_driven_sdram_fifo() {
+    let timings = MemoryTimings::fast_boot_sim(125e6);
+    #[derive(LogicBlock)]
+    struct FIFOTest {
+        fifo: SDRAMFIFO<5, 5, 4, 16, 12>,
+        sdram: SDRAMSimulator<5, 5, 10, 16>,
+        clock: Signal<In, Clock>,
+    }
+    impl Logic for FIFOTest {
+        #[hdl_gen]
+        fn update(&mut self) {
+            clock!(self, clock, fifo);
+            self.fifo.ram_clock.next = self.clock.val();
+            SDRAMDriver::<16>::join(&mut self.fifo.sdram, &mut self.sdram.sdram);
+        }
+    }
+    let mut uut = FIFOTest {
+        fifo: SDRAMFIFO::new(3, timings, OutputBuffer::Wired),
+        sdram: SDRAMSimulator::new(timings),
+        clock: Default::default(),
+    };
+    uut.fifo.bus_write.link_connect_dest();
+    uut.fifo.bus_read.link_connect_dest();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.set_event_driven(true);
+    let data = (0..256_u32)
+        .map(|x| ((x * 7919) & 0xFFFF) as u16)
+        .collect::<Vec<_>>();
+    let data2 = data.clone();
+    sim.add_clock(4000, |x: &mut Box<FIFOTest>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<FIFOTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 20);
+        hls_fifo_write_lazy!(sim, clock, x, fifo.bus_write, &data);
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<FIFOTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 20);
+        hls_fifo_read_lazy!(sim, clock, x, fifo.bus_read, &data2);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 200_000_000).unwrap();
+}
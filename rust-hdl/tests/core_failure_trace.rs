@@ -0,0 +1,80 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Counter {
+    pub clock: Signal<In, Clock>,
+    pub count: Signal<Out, Bits<16>>,
+    counter: DFF<Bits<16>>,
+}
+
+impl Logic for Counter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.count.next = self.counter.q.val();
+    }
+}
+
+fn counter_sim() -> Simulation<Counter> {
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Counter>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Counter>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 1000);
+        sim_assert_eq!(sim, x.count.val(), 999, x);
+        sim.done(x)
+    });
+    sim
+}
+
+#[test]
+fn test_failed_assertion_reports_values() {
+    let mut uut = Counter::default();
+    uut.connect_all();
+    let mut sim = counter_sim();
+    match sim.run(Box::new(uut), 100_000) {
+        Err(SimError::AssertionFailed { time, message }) => {
+            assert_eq!(time, 10_000);
+            assert!(message.contains("x.count.val() != 999"));
+            assert!(message.contains("1000"));
+        }
+        x => panic!("Expected an assertion failure, got {:?}", x),
+    }
+}
+
+#[test]
+fn test_failed_assertion_writes_trace_window() {
+    let mut uut = Counter::default();
+    uut.connect_all();
+    let mut sim = counter_sim();
+    let filename = vcd_path!("failure_trace.vcd");
+    let _ = std::fs::remove_file(&filename);
+    sim.set_failure_trace(100, &filename);
+    assert!(sim.run(Box::new(uut), 100_000).is_err());
+    let vcd = std::fs::read_to_string(&filename).unwrap();
+    // Only the last 100ps (10 clock cycles) are in the trace
+    let timestamps = vcd.lines().filter(|x| x.starts_with('#')).count();
+    assert!(timestamps <= 25);
+    assert!(vcd.contains("#9900"));
+    assert!(vcd.contains("#10000"));
+    assert!(!vcd.contains("#9000\n"));
+}
+
+#[test]
+fn test_no_trace_without_failure() {
+    let mut uut = Counter::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Counter>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Counter>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        sim.done(x)
+    });
+    let filename = vcd_path!("failure_trace_unused.vcd");
+    let _ = std::fs::remove_file(&filename);
+    sim.set_failure_trace(100, &filename);
+    sim.run(Box::new(uut), 100_000).unwrap();
+    assert!(std::fs::metadata(&filename).is_err());
+}
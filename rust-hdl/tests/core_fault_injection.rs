@@ -0,0 +1,131 @@
+use rust_hdl::prelude::*;
+
+// The same count is kept three ways - in a TMR counter (three copies and
+// a voter), in a plain register, and in a register with a parity bit that
+// reloads itself from the voted count when the parity is wrong.
+#[derive(LogicBlock, Default)]
+struct Upsettable {
+    pub clock: Signal<In, Clock>,
+    pub count: Signal<Out, Bits<8>>,
+    pub parity_error: Signal<Out, Bit>,
+    copies: [DFF<Bits<8>>; 3],
+    voter: MajorityVoter<8>,
+    plain: DFF<Bits<8>>,
+    checked: DFF<Bits<8>>,
+    parity: DFF<Bit>,
+}
+
+impl Logic for Upsettable {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, plain, checked, parity);
+        for i in 0..3 {
+            self.copies[i].clock.next = self.clock.val();
+            self.copies[i].d.next = self.voter.data_out.val() + 1;
+        }
+        self.voter.a.next = self.copies[0].q.val();
+        self.voter.b.next = self.copies[1].q.val();
+        self.voter.c.next = self.copies[2].q.val();
+        self.count.next = self.voter.data_out.val();
+        self.plain.d.next = self.plain.q.val() + 1;
+        self.parity_error.next = self.checked.q.val().xor() != self.parity.q.val();
+        self.checked.d.next = self.checked.q.val() + 1;
+        if self.parity_error.val() {
+            self.checked.d.next = self.voter.data_out.val() + 1;
+        }
+        self.parity.d.next = self.checked.d.val().xor();
+    }
+}
+
+fn upsettable_sim() -> Simulation<Upsettable> {
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Upsettable>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Upsettable>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 2000);
+        sim.done(x)
+    });
+    sim
+}
+
+fn random_campaign(targets: &[&str]) -> FaultCampaign {
+    let mut campaign = FaultCampaign::new(500);
+    campaign.inject_random(
+        &Upsettable::default(),
+        targets,
+        20,
+        1000..19_000,
+        0xDEAD_BEEF,
+    );
+    campaign
+}
+
+#[test]
+fn test_tmr_masks_single_upsets() {
+    let mut uut = Upsettable::default();
+    uut.connect_all();
+    let mut sim = upsettable_sim();
+    sim.add_fault_campaign(
+        random_campaign(&["uut$copies$0$q", "uut$copies$1$q", "uut$copies$2$q"]),
+        |_| false,
+        |x| x.count.val() != x.plain.q.val(),
+    );
+    sim.run(Box::new(uut), 100_000).unwrap();
+    let report = sim.fault_report().unwrap();
+    assert_eq!(report.records().len(), 20);
+    assert_eq!(report.count(FaultOutcome::Masked), 20);
+}
+
+#[test]
+fn test_unprotected_upsets_are_reported() {
+    let mut uut = Upsettable::default();
+    uut.connect_all();
+    let mut sim = upsettable_sim();
+    let mut campaign = FaultCampaign::new(500);
+    campaign.inject(2502, "uut$plain$q", 3);
+    sim.add_fault_campaign(campaign, |_| false, |x| x.count.val() != x.plain.q.val());
+    sim.run(Box::new(uut), 100_000).unwrap();
+    let report = sim.fault_report().unwrap();
+    let undetected = report.undetected();
+    assert_eq!(undetected.len(), 1);
+    assert_eq!(undetected[0].fault.path, "uut$plain$q");
+    assert_eq!(undetected[0].corrupted_after, Some(0));
+    assert!(report
+        .to_string()
+        .contains("undetected: bit 3 of uut$plain$q at 2502"));
+}
+
+#[test]
+fn test_parity_detects_upsets() {
+    let mut uut = Upsettable::default();
+    uut.connect_all();
+    let mut sim = upsettable_sim();
+    sim.add_fault_campaign(
+        random_campaign(&["uut$checked$q", "uut$parity$q"]),
+        |x| x.parity_error.val(),
+        |x| x.count.val() != x.checked.q.val(),
+    );
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("fault_parity.vcd"))
+        .unwrap();
+    let report = sim.fault_report().unwrap();
+    assert_eq!(report.count(FaultOutcome::Detected), 20);
+    // The parity check is combinational, so it flags the upset straight away
+    assert!(report.records().iter().all(|x| x.detected_after == Some(0)));
+}
+
+#[test]
+fn test_fault_on_missing_signal_fails() {
+    let mut uut = Upsettable::default();
+    uut.connect_all();
+    let mut sim = upsettable_sim();
+    let mut campaign = FaultCampaign::new(500);
+    campaign.inject(1000, "uut$nothing$q", 0);
+    sim.add_fault_campaign(campaign, |_| false, |_| false);
+    match sim.run(Box::new(uut), 100_000) {
+        Err(SimError::AssertionFailed { time, message }) => {
+            assert_eq!(time, 1000);
+            assert!(message.contains("uut$nothing$q"));
+        }
+        x => panic!("Expected an assertion failure, got {:?}", x),
+    }
+}
@@ -0,0 +1,113 @@
+use num_traits::ToPrimitive;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_hdl::prelude::*;
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+// A few tones, plus some noise, with the given peak amplitude
+fn test_signal(n: usize, amplitude: f64) -> Vec<(i64, i64)> {
+    let mut rng = StdRng::seed_from_u64(0xF_F7);
+    (0..n)
+        .map(|ndx| {
+            let t = ndx as f64 / n as f64;
+            let re = 0.5 * (2.0 * std::f64::consts::PI * 5.0 * t).cos()
+                + 0.3 * (2.0 * std::f64::consts::PI * 17.0 * t).sin()
+                + 0.1 * rng.gen_range(-1.0..1.0);
+            let im =
+                0.4 * (2.0 * std::f64::consts::PI * 5.0 * t).sin() + 0.1 * rng.gen_range(-1.0..1.0);
+            ((re * amplitude) as i64, (im * amplitude) as i64)
+        })
+        .collect()
+}
+
+// Run a block of samples through the FFT, and return the bins, scaled by the exponent
+fn run_fft<const B: usize>(samples: Vec<(i64, i64)>) -> Vec<Complex<f64>> {
+    let mut uut = FFT::<B>::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<FFT<B>>| x.clock.next = !x.clock.val());
+    let bins = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let report = bins.clone();
+    sim.add_testbench(move |mut sim: Sim<FFT<B>>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        x = sim.watch(|x| x.ready.val(), x)?;
+        for (re, im) in &samples {
+            x.re_in.next = re.to_signed_bits();
+            x.im_in.next = im.to_signed_bits();
+            x.strobe_in.next = true;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        x.strobe_in.next = false;
+        let mut out = vec![];
+        while out.len() < (1 << B) {
+            wait_clock_true!(sim, clock, x);
+            if x.strobe_out.val() {
+                let scale = (1_u64 << x.exponent.val().index()) as f64;
+                out.push(Complex::new(
+                    x.re_out.val().bigint().to_i64().unwrap() as f64 * scale,
+                    x.im_out.val().bigint().to_i64().unwrap() as f64 * scale,
+                ));
+            }
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim_assert!(sim, x.ready.val(), x);
+        *report.lock().unwrap() = out;
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000_000).unwrap();
+    let ret = bins.lock().unwrap().clone();
+    ret
+}
+
+// The signal to noise ratio (in dB) of the FFT against rustfft.  Each stage
+// rounds to 16 bits, and the block floating point leaves a bit or two of
+// headroom, so expect about 10 bits worth for the larger sizes.
+fn check_fft<const B: usize>(amplitude: f64) -> f64 {
+    let n = 1 << B;
+    let samples = test_signal(n, amplitude);
+    let mut reference = samples
+        .iter()
+        .map(|(re, im)| Complex::new(*re as f64, *im as f64))
+        .collect::<Vec<_>>();
+    FftPlanner::new()
+        .plan_fft_forward(n)
+        .process(&mut reference);
+    let bins = run_fft::<B>(samples);
+    let signal: f64 = reference.iter().map(|x| x.norm_sqr()).sum();
+    let noise: f64 = bins
+        .iter()
+        .zip(reference.iter())
+        .map(|(x, y)| (x - y).norm_sqr())
+        .sum();
+    let snr = 10.0 * (signal / noise).log10();
+    println!(
+        "FFT of {} points at amplitude {}: SNR {:.1} dB",
+        n, amplitude, snr
+    );
+    snr
+}
+
+#[test]
+fn test_fft_synthesizes() {
+    let mut uut = FFT256::default();
+    uut.connect_all();
+    yosys_validate("fft_256", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_fft_256_matches_rustfft() {
+    assert!(check_fft::<8>(30000.0) > 55.0);
+}
+
+#[test]
+fn test_fft_256_small_signal_matches_rustfft() {
+    // A small signal is not scaled down in the early stages, so it keeps its precision
+    assert!(check_fft::<8>(300.0) > 50.0);
+}
+
+#[test]
+fn test_fft_1024_matches_rustfft() {
+    assert!(check_fft::<10>(30000.0) > 50.0);
+}
@@ -0,0 +1,50 @@
+use rust_hdl::prelude::*;
+use std::time::Duration;
+
+// Measure a square wave with the given half period (in units of the
+// simulation time, where a clock cycle is 10).  The clock is nominally
+// 1 MHz, so the gate time of 1 ms is 1000 clock cycles.
+fn measure(half_period: u64) -> (u64, u64, u64) {
+    let mut uut = FrequencyCounter::<32>::new(1_000_000, Duration::from_millis(1));
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<FrequencyCounter<32>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_clock(half_period, |x: &mut Box<FrequencyCounter<32>>| {
+        x.pulses.next = !x.pulses.val()
+    });
+    let (tx, rx) = std::sync::mpsc::channel();
+    sim.add_testbench(move |mut sim: Sim<FrequencyCounter<32>>| {
+        let mut x = sim.init()?;
+        // Skip the first gate, which started before the input did
+        x = sim.watch(|x| x.edges_valid.val(), x)?;
+        wait_clock_cycle!(sim, clock, x);
+        x = sim.watch(|x| x.edges_valid.val(), x)?;
+        let edges = x.edges.val().to_u64();
+        x = sim.watch(|x| x.period_valid.val(), x)?;
+        tx.send((edges, x.period.val().to_u64(), x.high_time.val().to_u64()))
+            .unwrap();
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000_000).unwrap();
+    rx.recv().unwrap()
+}
+
+#[test]
+fn test_frequency_counter_fast_input() {
+    // A period of 7 clock cycles - about 142.86 kHz
+    let (edges, period, high_time) = measure(35);
+    assert!(edges == 142 || edges == 143);
+    assert_eq!(period, 7);
+    assert!(high_time == 3 || high_time == 4);
+}
+
+#[test]
+fn test_frequency_counter_slow_input() {
+    // A period of 2500 clock cycles (400 Hz), which is longer than the gate
+    let (edges, period, high_time) = measure(12_500);
+    assert!(edges <= 1);
+    assert_eq!(period, 2500);
+    assert_eq!(high_time, 1250);
+}
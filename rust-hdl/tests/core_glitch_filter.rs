@@ -0,0 +1,71 @@
+use rand::Rng;
+use rust_hdl::prelude::*;
+
+#[test]
+fn test_glitch_filter_matches_majority() {
+    let mut uut = GlitchFilter::<5>::default();
+    uut.connect_all();
+    yosys_validate("glitch_filter_5", &generate_verilog(&uut)).unwrap();
+    let samples = (0..2000)
+        .map(|_| rand::thread_rng().gen::<bool>())
+        .collect::<Vec<_>>();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<GlitchFilter<5>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<GlitchFilter<5>>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        for (ndx, sample) in samples.iter().enumerate() {
+            x.data_in.next = *sample;
+            wait_clock_cycle!(sim, clock, x);
+            // The window starts out full of zeros
+            let ones = samples[ndx.saturating_sub(4)..=ndx]
+                .iter()
+                .filter(|x| **x)
+                .count();
+            sim_assert_eq!(sim, x.data_out.val(), ones > 2, x);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
+
+#[test]
+fn test_glitch_filter_rejects_glitches() {
+    let mut uut = GlitchFilter::<7>::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<GlitchFilter<7>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<GlitchFilter<7>>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        let mut rng = rand::thread_rng();
+        let mut level = false;
+        for _ in 0..50 {
+            // Hold the level, with glitches of up to 3 clocks spaced at least 7 clocks apart
+            for _ in 0..rng.gen_range(1..5) {
+                x.data_in.next = !level;
+                wait_clock_cycles!(sim, clock, x, rng.gen_range(1..=3));
+                x.data_in.next = level;
+                for _ in 0..7 {
+                    wait_clock_cycle!(sim, clock, x);
+                    sim_assert_eq!(sim, x.data_out.val(), level, x);
+                }
+            }
+            // A real change appears on the output after 4 clocks
+            level = !level;
+            x.data_in.next = level;
+            wait_clock_cycles!(sim, clock, x, 3);
+            sim_assert_eq!(sim, x.data_out.val(), !level, x);
+            wait_clock_cycle!(sim, clock, x);
+            sim_assert_eq!(sim, x.data_out.val(), level, x);
+            wait_clock_cycles!(sim, clock, x, 7);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("glitch_filter.vcd"))
+        .unwrap();
+}
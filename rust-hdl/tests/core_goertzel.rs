@@ -0,0 +1,107 @@
+use num_traits::ToPrimitive;
+use rust_hdl::prelude::*;
+use rustfft::num_complex::Complex;
+use rustfft::FftPlanner;
+
+const LENGTH: usize = 256;
+const BIN: usize = 10;
+
+fn tone(cycles: f64, amplitude: f64, phase: f64) -> Vec<i64> {
+    (0..LENGTH)
+        .map(|ndx| {
+            let angle = 2.0 * std::f64::consts::PI * cycles * ndx as f64 / LENGTH as f64;
+            ((angle + phase).cos() * amplitude).round() as i64
+        })
+        .collect()
+}
+
+// Run blocks of samples through the detector, and collect the results
+fn run_goertzel(blocks: Vec<Vec<i64>>, threshold: u64) -> Vec<(Complex<f64>, u64, bool)> {
+    let count = blocks.len();
+    let mut uut = Goertzel::new(BIN, LENGTH);
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Goertzel>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Goertzel>| {
+        let mut x = sim.init()?;
+        x.threshold.next = threshold.to_bits();
+        wait_clock_true!(sim, clock, x);
+        for block in &blocks {
+            for sample in block {
+                x.data_in.next = sample.to_signed_bits();
+                x.strobe_in.next = true;
+                wait_clock_cycle!(sim, clock, x);
+                // Leave a gap between the samples, as a real ADC would
+                x.strobe_in.next = false;
+                wait_clock_cycle!(sim, clock, x);
+            }
+        }
+        sim.done(x)
+    });
+    let results = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let report = results.clone();
+    sim.add_testbench(move |mut sim: Sim<Goertzel>| {
+        let mut x = sim.init()?;
+        let mut out = vec![];
+        while out.len() < count {
+            x = sim.watch(|x| x.strobe_out.val(), x)?;
+            out.push((
+                Complex::new(
+                    x.real.val().bigint().to_i64().unwrap() as f64,
+                    x.imag.val().bigint().to_i64().unwrap() as f64,
+                ),
+                x.magnitude.val().to_u64(),
+                x.detected.val(),
+            ));
+            wait_clock_cycle!(sim, clock, x);
+        }
+        *report.lock().unwrap() = out;
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000_000).unwrap();
+    let ret = results.lock().unwrap().clone();
+    ret
+}
+
+// The bin from rustfft, rotated the way the detector leaves it
+fn reference(samples: &[i64]) -> Complex<f64> {
+    let mut spectrum = samples
+        .iter()
+        .map(|x| Complex::new(*x as f64, 0.0))
+        .collect::<Vec<_>>();
+    FftPlanner::new()
+        .plan_fft_forward(LENGTH)
+        .process(&mut spectrum);
+    let w = 2.0 * std::f64::consts::PI * BIN as f64 / LENGTH as f64;
+    spectrum[BIN] * Complex::from_polar(1.0, w * (LENGTH - 1) as f64)
+}
+
+#[test]
+fn test_goertzel_synthesizes() {
+    let mut uut = Goertzel::new(BIN, LENGTH);
+    uut.connect_all();
+    yosys_validate("goertzel_256", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_goertzel_matches_rustfft() {
+    let blocks = vec![
+        tone(BIN as f64, 20000.0, 0.3),
+        tone(BIN as f64 + 0.25, 30000.0, -1.0),
+        tone(3.0 * BIN as f64, 20000.0, 0.0),
+    ];
+    // Half of the magnitude of the first tone
+    let threshold = (20000 * LENGTH / 4) as u64;
+    let results = run_goertzel(blocks.clone(), threshold);
+    for (block, (bin, magnitude, _)) in blocks.iter().zip(results.iter()) {
+        let expected = reference(block);
+        println!("Goertzel {} rustfft {}", bin, expected);
+        // The error is dominated by the rounding of the coefficients
+        assert!((bin - expected).norm() < 1e-3 * (20000 * LENGTH) as f64);
+        let error = (*magnitude as f64 - bin.norm()) / bin.norm();
+        assert!(error.abs() < 0.07);
+    }
+    // The tone in the bin is detected, the one far from it is not
+    assert!(results[0].2);
+    assert!(!results[2].2);
+}
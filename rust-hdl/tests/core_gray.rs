@@ -0,0 +1,92 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct GrayRoundTrip<const N: usize> {
+    clock: Signal<In, Clock>,
+    data_in: Signal<In, Bits<N>>,
+    to_gray: BinaryToGray<N>,
+    to_binary: GrayToBinary<N>,
+}
+
+impl<const N: usize> Logic for GrayRoundTrip<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.to_gray.data_in.next = self.data_in.val();
+        self.to_binary.data_in.next = self.to_gray.data_out.val();
+    }
+}
+
+fn test_gray_round_trip<const N: usize>() {
+    let mut uut = GrayRoundTrip::<N>::default();
+    uut.clock.connect();
+    uut.data_in.connect();
+    uut.connect_all();
+    yosys_validate(&format!("gray_round_trip_{}", N), &generate_verilog(&uut)).unwrap();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<GrayRoundTrip<N>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<GrayRoundTrip<N>>| {
+        let mut x = sim.init()?;
+        let mut previous = x.to_gray.data_out.val();
+        for val in 0_u64..(1 << N) {
+            x.data_in.next = val.to_bits();
+            wait_clock_cycle!(sim, clock, x);
+            let gray = x.to_gray.data_out.val();
+            sim_assert_eq!(sim, gray, binary_to_gray::<N>(val.to_bits()), x);
+            sim_assert_eq!(sim, gray_to_binary(gray), val, x);
+            sim_assert_eq!(sim, x.to_binary.data_out.val(), val, x);
+            // Consecutive values differ in exactly one bit
+            if val != 0 {
+                sim_assert_eq!(sim, (gray ^ previous).to_u64().count_ones(), 1, x);
+            }
+            previous = gray;
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
+
+#[test]
+fn test_gray_round_trip_4() {
+    test_gray_round_trip::<4>();
+}
+
+#[test]
+fn test_gray_round_trip_10() {
+    test_gray_round_trip::<10>();
+}
+
+type GrayCounterTest = GrayCounter<6>;
+
+#[test]
+fn test_gray_counter() {
+    let mut uut = GrayCounterTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<GrayCounterTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<GrayCounterTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        let mut expected = 0_u64;
+        // Run through the count twice (so it wraps), pausing now and then
+        for ndx in 0..150 {
+            let previous = x.gray.val();
+            x.enable.next = ndx % 5 != 2;
+            wait_clock_cycle!(sim, clock, x);
+            if ndx % 5 != 2 {
+                expected = (expected + 1) % 64;
+                sim_assert_eq!(sim, (x.gray.val() ^ previous).to_u64().count_ones(), 1, x);
+            } else {
+                sim_assert_eq!(sim, x.gray.val(), previous, x);
+            }
+            sim_assert_eq!(sim, x.binary.val(), expected, x);
+            sim_assert_eq!(sim, gray_to_binary(x.gray.val()), expected, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10_000, &vcd_path!("gray_counter.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,167 @@
+use rand::Rng;
+use rust_hdl::prelude::*;
+
+// Bytes written into a FIFO are framed, sent over a UART, and deframed on
+// the other side.  The `corrupt` input inverts the serial line, to model
+// noise on the link.
+#[derive(LogicBlock)]
+struct HDLCLink {
+    clock: Signal<In, Clock>,
+    data_in: Signal<In, Bits<8>>,
+    write: Signal<In, Bit>,
+    full: Signal<Out, Bit>,
+    corrupt: Signal<In, Bit>,
+    fifo: SynchronousFIFO<Bits<8>, 6, 7, 1>,
+    framer: HDLCFramer,
+    tx: UARTTransmitter,
+    rx: UARTReceiver,
+    deframer: HDLCDeframer<8>,
+}
+
+impl Default for HDLCLink {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            write: Default::default(),
+            full: Default::default(),
+            corrupt: Default::default(),
+            fifo: Default::default(),
+            framer: HDLCFramer::new(32),
+            tx: UARTTransmitter::new(1_000_000, 125_000),
+            rx: UARTReceiver::new(1_000_000, 125_000),
+            deframer: Default::default(),
+        }
+    }
+}
+
+impl Logic for HDLCLink {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, fifo, framer, tx, rx, deframer);
+        self.fifo.data_in.next = self.data_in.val();
+        self.fifo.write.next = self.write.val();
+        self.full.next = self.fifo.full.val();
+        self.framer.data_in.next = self.fifo.data_out.val();
+        self.framer.empty_in.next = self.fifo.empty.val();
+        self.fifo.read.next = self.framer.read.val();
+        self.tx.data_in.next = self.framer.data_out.val();
+        self.tx.write.next = self.framer.write.val();
+        self.framer.full.next = self.tx.busy.val();
+        self.rx.rx.next = self.tx.tx.val() ^ self.corrupt.val();
+        self.deframer.data_in.next = self.rx.data_out.val();
+        self.deframer.strobe_in.next = self.rx.strobe_out.val();
+    }
+}
+
+fn make_link() -> HDLCLink {
+    let mut uut = HDLCLink::default();
+    uut.deframer.read.connect();
+    uut.connect_all();
+    uut
+}
+
+// Bytes with plenty of flags and escapes in them
+fn random_payload(count: usize) -> Vec<u8> {
+    let mut rng = rand::thread_rng();
+    (0..count)
+        .map(|_| match rng.gen_range(0..4) {
+            0 => HDLC_FLAG,
+            1 => HDLC_ESCAPE,
+            _ => rng.gen(),
+        })
+        .collect()
+}
+
+fn run_link(payload: Vec<u8>, glitch_at: Option<u64>, name: &str) -> (Vec<u8>, usize) {
+    let uut = make_link();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<HDLCLink>| x.clock.next = !x.clock.val());
+    let sent = payload.clone();
+    sim.add_testbench(move |mut sim: Sim<HDLCLink>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        for byte in &sent {
+            x = sim.watch(|x| !x.full.val(), x)?;
+            x.data_in.next = byte.to_bits();
+            x.write.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.write.next = false;
+        }
+        sim.done(x)
+    });
+    if let Some(delay) = glitch_at {
+        sim.add_testbench(move |mut sim: Sim<HDLCLink>| {
+            let mut x = sim.init()?;
+            wait_clock_true!(sim, clock, x);
+            wait_clock_cycles!(sim, clock, x, delay);
+            x.corrupt.next = true;
+            wait_clock_cycles!(sim, clock, x, 12);
+            x.corrupt.next = false;
+            sim.done(x)
+        });
+    }
+    let received = std::sync::Arc::new(std::sync::Mutex::new((vec![], 0)));
+    let result = received.clone();
+    sim.add_testbench(move |mut sim: Sim<HDLCLink>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        // Collect bytes until the link has been quiet for a while
+        let mut quiet = 0;
+        while quiet < 10_000 {
+            x.deframer.read.next = false;
+            if x.deframer.dropped.val() {
+                received.lock().unwrap().1 += 1;
+            }
+            if !x.deframer.empty.val() {
+                received
+                    .lock()
+                    .unwrap()
+                    .0
+                    .push(x.deframer.data_out.val().index() as u8);
+                x.deframer.read.next = true;
+                quiet = 0;
+            } else {
+                quiet += 1;
+            }
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000_000, &vcd_path!(name))
+        .unwrap();
+    let result = result.lock().unwrap().clone();
+    result
+}
+
+#[test]
+fn test_hdlc_link_synthesizes() {
+    let uut = make_link();
+    yosys_validate("hdlc_link", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_hdlc_link_delivers_payload() {
+    let payload = random_payload(200);
+    let (received, dropped) = run_link(payload.clone(), None, "hdlc_link.vcd");
+    assert_eq!(dropped, 0);
+    assert_eq!(received, payload);
+}
+
+#[test]
+fn test_hdlc_link_drops_corrupted_frames() {
+    let payload = random_payload(200);
+    // Hit the line in the middle of the transfer
+    let (received, dropped) = run_link(payload.clone(), Some(8_000), "hdlc_link_glitch.vcd");
+    assert!(dropped >= 1);
+    assert!(received.len() < payload.len());
+    // What remains is the payload, less the frames that were hit
+    let missing = payload.len() - received.len();
+    let start = received
+        .iter()
+        .zip(payload.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    assert_eq!(received[..start], payload[..start]);
+    assert_eq!(received[start..], payload[start + missing..]);
+}
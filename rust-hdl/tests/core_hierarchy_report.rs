@@ -0,0 +1,109 @@
+use rust_hdl::core::hierarchy_report::Connection;
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Counter {
+    pub clock: Signal<In, Clock>,
+    pub count: Signal<Out, Bits<8>>,
+    counter: DFF<Bits<8>>,
+}
+
+impl Logic for Counter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.count.next = self.counter.q.val();
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct TwoDomains {
+    pub fast_clock: Signal<In, Clock>,
+    pub slow_clock: Signal<In, Clock>,
+    pub sum: Signal<Out, Bits<8>>,
+    fast: Counter,
+    slow: [Counter; 2],
+}
+
+impl Logic for TwoDomains {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.fast.clock.next = self.fast_clock.val();
+        for i in 0..2 {
+            self.slow[i].clock.next = self.slow_clock.val();
+        }
+        self.sum.next = self.fast.count.val() + self.slow[1].count.val();
+    }
+}
+
+#[test]
+fn test_hierarchy_report_structure() {
+    let mut uut = TwoDomains::default();
+    uut.connect_all();
+    let report = hierarchy_report(&uut);
+    assert_eq!(report.path, "uut");
+    assert_eq!(report.signal("sum").unwrap().width, 8);
+    let names = report
+        .children
+        .iter()
+        .map(|x| x.name.as_str())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["fast", "slow$0", "slow$1"]);
+    let counter = report.find("uut$slow$1$counter").unwrap();
+    assert_eq!(counter.signal("q").unwrap().width, 8);
+    assert_eq!(counter.signal("clock").unwrap().type_name, "clock");
+    assert!(report.connections.contains(&Connection {
+        from: "slow_clock".into(),
+        to: "slow$1$clock".into()
+    }));
+    assert!(report.connections.contains(&Connection {
+        from: "slow$1$count".into(),
+        to: "sum".into()
+    }));
+    assert!(report
+        .find("uut$fast")
+        .unwrap()
+        .connections
+        .contains(&Connection {
+            from: "counter$q".into(),
+            to: "count".into()
+        }));
+}
+
+#[test]
+fn test_hierarchy_report_clock_domains() {
+    let mut uut = TwoDomains::default();
+    uut.connect_all();
+    let report = hierarchy_report(&uut);
+    assert_eq!(report.clock_domains, ["uut$fast_clock", "uut$slow_clock"]);
+    assert_eq!(
+        report.find("uut$fast$counter").unwrap().clock_domains,
+        ["uut$fast_clock"]
+    );
+    for slow in ["uut$slow$0$counter", "uut$slow$1$counter"] {
+        assert_eq!(
+            report
+                .find(slow)
+                .unwrap()
+                .signal("clock")
+                .unwrap()
+                .clock_domain,
+            Some("uut$slow_clock".to_string())
+        );
+    }
+}
+
+#[test]
+fn test_hierarchy_report_exports() {
+    let mut uut = TwoDomains::default();
+    uut.connect_all();
+    let report = hierarchy_report(&uut);
+    let json = report.to_json();
+    assert!(json.contains("{\"name\":\"sum\",\"kind\":\"OutputParameter\",\"width\":8"));
+    assert!(json.contains("\"path\":\"uut$slow$1$counter\""));
+    assert_eq!(json.matches('{').count(), json.matches('}').count());
+    let html = report.to_html();
+    assert!(html.starts_with("<!DOCTYPE html>"));
+    assert!(html.contains(&json));
+}
@@ -0,0 +1,80 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock)]
+struct BringUpTest {
+    bus: SoCBusController<16, 8>,
+    bringup: HLSBringUp<16, 8, 4, 3>,
+}
+
+impl Default for BringUpTest {
+    fn default() -> Self {
+        Self {
+            bus: Default::default(),
+            // Step every 10 clocks (at 100 MHz)
+            bringup: HLSBringUp::new(100_000_000, 10_000_000.0),
+        }
+    }
+}
+
+impl Logic for BringUpTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.bringup.upstream);
+    }
+}
+
+#[test]
+fn test_bringup_synthesizes() {
+    let mut uut = BringUpTest::default();
+    uut.bringup.inputs.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_bringup_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_bringup_works() {
+    let mut uut = BringUpTest::default();
+    uut.bringup.inputs.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<BringUpTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<BringUpTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, bus.clock, x);
+        // On its own, the one walks through all of the outputs (and wraps)
+        let mut seen = vec![];
+        for _ in 0..60 {
+            wait_clock_cycle!(sim, bus.clock, x);
+            let outputs = x.bringup.outputs.val().index();
+            sim_assert_eq!(sim, outputs.count_ones(), 1, x);
+            if seen.last() != Some(&outputs) {
+                seen.push(outputs);
+            }
+        }
+        sim_assert!(
+            sim,
+            seen.starts_with(&[0b0001, 0b0010, 0b0100, 0b1000, 0b0001]),
+            x
+        );
+        // Until the host takes over
+        bus_port_write!(sim, x, bus, 0, [2]);
+        wait_clock_cycles!(sim, bus.clock, x, 30);
+        sim_assert_eq!(sim, x.bringup.outputs.val(), 0b0100, x);
+        bus_port_write!(sim, x, bus, 0, [4]);
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert_eq!(sim, x.bringup.outputs.val(), 0, x);
+        // Sample each of the inputs
+        x.bringup.inputs.next = 0b101.into();
+        for (input, expected) in [(0, 1), (1, 0), (2, 1), (3, 0)] {
+            bus_port_write!(sim, x, bus, 1, [input]);
+            let val = bus_port_read!(sim, x, bus, 2);
+            sim_assert_eq!(sim, val, expected, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 20_000, &vcd_path!("hls_bringup.vcd"))
+        .unwrap();
+}
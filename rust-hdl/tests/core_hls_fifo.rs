@@ -137,6 +137,43 @@ fn test_feeder_works() {
     ret.unwrap();
 }
 
+#[test]
+fn test_feeder_constrained_random() {
+    let mut rng = StimulusRng::new();
+    let data = rng.bits_vec::<8>(256);
+    let mut uut = FIFOTestFixture {
+        feeder: LazyFIFOFeeder::new(
+            &data,
+            &rng.sleeps(data.len(), Gaps::Geometric { mean: 2.0 }),
+        ),
+        fifo: SyncFIFO::default(),
+        reader: LazyFIFOReader::new(
+            &data,
+            &rng.sleeps(data.len(), Gaps::Uniform { min: 0, max: 5 }),
+        ),
+        clock: Default::default(),
+    };
+    uut.feeder.start.connect();
+    uut.reader.start.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<FIFOTestFixture>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<FIFOTestFixture>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        x.feeder.start.next = true;
+        x.reader.start.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x = sim.watch(|x| x.feeder.done.val() & x.reader.done.val(), x)?;
+        sim_assert!(sim, !x.reader.error.val(), x);
+        wait_clock_cycle!(sim, clock, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
+
 #[derive(LogicBlock)]
 struct FIFOTestFixtureAsync {
     feeder: LazyFIFOFeeder<Bits<8>, 10>,
@@ -0,0 +1,78 @@
+use rust_hdl::prelude::*;
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+struct FrequencyCounterTest {
+    bus: SoCBusController<16, 8>,
+    counter: HLSFrequencyCounter<16, 8, 2>,
+}
+
+impl Default for FrequencyCounterTest {
+    fn default() -> Self {
+        Self {
+            bus: Default::default(),
+            // The gate is 1000 clock cycles
+            counter: HLSFrequencyCounter::new(1_000_000, Duration::from_millis(1)),
+        }
+    }
+}
+
+impl Logic for FrequencyCounterTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.counter.upstream);
+    }
+}
+
+// The measurements are 32 bits, so they take 2 reads
+
+fn make_test() -> FrequencyCounterTest {
+    let mut uut = FrequencyCounterTest::default();
+    for input in &mut uut.counter.inputs {
+        input.connect();
+    }
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_hls_frequency_counter_synthesizes() {
+    let uut = make_test();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_freq_counter_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_hls_frequency_counter_works() {
+    let uut = make_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<FrequencyCounterTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    // A period of 20 clock cycles (50 kHz) on channel 0
+    sim.add_clock(100, |x: &mut Box<FrequencyCounterTest>| {
+        x.counter.inputs[0].next = !x.counter.inputs[0].val()
+    });
+    // A period of 246 clock cycles on channel 1, with a 50% duty cycle
+    sim.add_clock(1230, |x: &mut Box<FrequencyCounterTest>| {
+        x.counter.inputs[1].next = !x.counter.inputs[1].val()
+    });
+    sim.add_testbench(move |mut sim: Sim<FrequencyCounterTest>| {
+        let mut x = sim.init()?;
+        // Let two full gates go by
+        wait_clock_cycles!(sim, bus.clock, x, 2100);
+        wait_clock_true!(sim, bus.clock, x);
+        bus_port_write!(sim, x, bus, 0, [0]);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 1, 2), 50, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 2, 2), 20, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 3, 2), 10, x);
+        bus_port_write!(sim, x, bus, 0, [1]);
+        let edges = bus_port_read!(sim, x, bus, 1, 2);
+        sim_assert!(sim, edges == 4 || edges == 5, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 2, 2), 246, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 3, 2), 123, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("hls_freq_counter.vcd"))
+        .unwrap();
+}
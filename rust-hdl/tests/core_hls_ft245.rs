@@ -0,0 +1,147 @@
+use rand::Rng;
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock)]
+struct FT245Test {
+    htf_feeder: LazyFIFOFeeder<Bits<8>, 10>,
+    htf_reader: LazyFIFOReader<Bits<8>, 10>,
+    fth_feeder: LazyFIFOFeeder<Bits<8>, 10>,
+    fth_reader: LazyFIFOReader<Bits<8>, 10>,
+    chip_to_bus_fifo: SyncFIFO<Bits<8>, 4, 5, 1>,
+    chip_from_bus_fifo: SyncFIFO<Bits<8>, 4, 5, 1>,
+    pub chip: FT245SimulatedChip,
+    pub ft: FT245SyncFIFO,
+    ft_from_bus_fifo: SyncFIFO<Bits<8>, 4, 5, 1>,
+    ft_to_bus_fifo: SyncFIFO<Bits<8>, 4, 5, 1>,
+    pub clock: Signal<In, Clock>,
+}
+
+impl Default for FT245Test {
+    fn default() -> Self {
+        let dlen = 256;
+        let data1 = (0..dlen)
+            .map(|_| rand::thread_rng().gen::<u8>().to_bits())
+            .collect::<Vec<_>>();
+        let data2 = (0..dlen)
+            .map(|_| rand::thread_rng().gen::<u8>().to_bits())
+            .collect::<Vec<_>>();
+        Self {
+            htf_feeder: LazyFIFOFeeder::new(&data1, &bursty_vec(data1.len())),
+            htf_reader: LazyFIFOReader::new(&data1, &bursty_vec(data1.len())),
+            fth_feeder: LazyFIFOFeeder::new(&data2, &bursty_vec(data2.len())),
+            fth_reader: LazyFIFOReader::new(&data2, &bursty_vec(data2.len())),
+            chip_to_bus_fifo: Default::default(),
+            chip_from_bus_fifo: Default::default(),
+            chip: Default::default(),
+            ft: Default::default(),
+            ft_from_bus_fifo: Default::default(),
+            ft_to_bus_fifo: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl Logic for FT245Test {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // The chip provides the clock for the FPGA side.  Everything else is
+        // clocked from the FPGA side too, so that it all sees the same edge.
+        self.chip.clock.next = self.clock.val();
+        self.htf_feeder.clock.next = self.ft.clock.val();
+        self.htf_reader.clock.next = self.ft.clock.val();
+        self.fth_feeder.clock.next = self.ft.clock.val();
+        self.fth_reader.clock.next = self.ft.clock.val();
+        self.chip_to_bus_fifo.clock.next = self.ft.clock.val();
+        self.chip_from_bus_fifo.clock.next = self.ft.clock.val();
+        self.ft_from_bus_fifo.clock.next = self.ft.clock.val();
+        self.ft_to_bus_fifo.clock.next = self.ft.clock.val();
+        FT245SyncBusChip::join(&mut self.chip.bus, &mut self.ft.bus);
+        // The host side
+        FIFOReadController::<Bits<8>>::join(
+            &mut self.chip.data_to_bus,
+            &mut self.chip_to_bus_fifo.bus_read,
+        );
+        FIFOWriteController::<Bits<8>>::join(
+            &mut self.chip.data_from_bus,
+            &mut self.chip_from_bus_fifo.bus_write,
+        );
+        FIFOWriteController::<Bits<8>>::join(
+            &mut self.htf_feeder.bus,
+            &mut self.chip_to_bus_fifo.bus_write,
+        );
+        FIFOReadController::<Bits<8>>::join(
+            &mut self.fth_reader.bus,
+            &mut self.chip_from_bus_fifo.bus_read,
+        );
+        // The FPGA side
+        FIFOReadController::<Bits<8>>::join(
+            &mut self.ft.data_to_bus,
+            &mut self.ft_to_bus_fifo.bus_read,
+        );
+        FIFOWriteController::<Bits<8>>::join(
+            &mut self.ft.data_from_bus,
+            &mut self.ft_from_bus_fifo.bus_write,
+        );
+        FIFOWriteController::<Bits<8>>::join(
+            &mut self.fth_feeder.bus,
+            &mut self.ft_to_bus_fifo.bus_write,
+        );
+        FIFOReadController::<Bits<8>>::join(
+            &mut self.htf_reader.bus,
+            &mut self.ft_from_bus_fifo.bus_read,
+        );
+    }
+}
+
+fn make_test() -> FT245Test {
+    let mut uut = FT245Test::default();
+    uut.htf_feeder.start.connect();
+    uut.htf_reader.start.connect();
+    uut.fth_feeder.start.connect();
+    uut.fth_reader.start.connect();
+    uut.clock.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_ft245_test_synthesizes() {
+    let uut = make_test();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("ft245_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_ft245_moves_data_both_ways() {
+    let uut = make_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<FT245Test>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<FT245Test>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        x.htf_feeder.start.next = true;
+        x.htf_reader.start.next = true;
+        x.fth_feeder.start.next = true;
+        x.fth_reader.start.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.htf_feeder.start.next = false;
+        x.htf_reader.start.next = false;
+        x.fth_feeder.start.next = false;
+        x.fth_reader.start.next = false;
+        x = sim.watch(
+            |x| {
+                x.htf_feeder.done.val()
+                    & x.htf_reader.done.val()
+                    & x.fth_feeder.done.val()
+                    & x.fth_reader.done.val()
+            },
+            x,
+        )?;
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert!(sim, !x.htf_reader.error.val(), x);
+        sim_assert!(sim, !x.fth_reader.error.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 500_000, &vcd_path!("ft245_stress.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,69 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct GPIOTest {
+    bus: SoCBusController<16, 8>,
+    gpio: HLSGPIO<16, 8, 4>,
+}
+
+impl Logic for GPIOTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.gpio.upstream);
+    }
+}
+
+#[test]
+fn test_gpio_synthesizes() {
+    let mut uut = GPIOTest::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_gpio_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_gpio_works() {
+    let mut uut = GPIOTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<GPIOTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<GPIOTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        wait_clock_true!(sim, bus.clock, x);
+        // Pins 0 and 1 are outputs, pins 2 and 3 are driven externally
+        x.gpio.pins[2].next = true;
+        x.gpio.pins[3].next = false;
+        bus_port_write!(sim, x, bus, 0, [0b0011]);
+        bus_port_write!(sim, x, bus, 1, [0b0001]);
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        sim_assert!(sim, x.gpio.pins[0].val(), x);
+        sim_assert!(sim, !x.gpio.pins[1].val(), x);
+        let val = bus_port_read!(sim, x, bus, 2);
+        sim_assert_eq!(sim, val, 0b0101, x);
+        // Enable the interrupt for pin 3, and toggle pins 2 and 3
+        bus_port_write!(sim, x, bus, 4, [0b1000]);
+        sim_assert!(sim, !x.gpio.irq.val(), x);
+        x.gpio.pins[2].next = false;
+        x.gpio.pins[3].next = true;
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        sim_assert!(sim, x.gpio.irq.val(), x);
+        let val = bus_port_read!(sim, x, bus, 5);
+        sim_assert_eq!(sim, val, 0b1000, x);
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert!(sim, !x.gpio.irq.val(), x);
+        // The pull register is passed through to the board layer
+        bus_port_write!(sim, x, bus, 3, [0b1100]);
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert_eq!(sim, x.gpio.pull_enable.val(), 0b1100, x);
+        sim.done(x)
+    });
+    sim.run_traced(
+        Box::new(uut),
+        10_000,
+        std::fs::File::create(vcd_path!("hls_gpio.vcd")).unwrap(),
+    )
+    .unwrap();
+}
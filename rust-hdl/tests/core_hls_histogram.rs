@@ -0,0 +1,157 @@
+use rust_hdl::prelude::*;
+
+// The ADC conversions (a ramp from the simulator) are fed to the histogram
+#[derive(LogicBlock)]
+struct HistogramTest {
+    bus: SoCBusController<16, 8>,
+    histogram: HLSHistogram<16, 8, 16, 4>,
+    master: SPIMaster<32>,
+    adc: ADS868XSimulator,
+}
+
+impl Default for HistogramTest {
+    fn default() -> Self {
+        Self {
+            bus: Default::default(),
+            histogram: Default::default(),
+            master: SPIMaster::new(ADS868XSimulator::spi_sw()),
+            adc: ADS868XSimulator::new(ADS868XSimulator::spi_sw()),
+        }
+    }
+}
+
+impl Logic for HistogramTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.histogram.upstream);
+        SPIWiresMaster::join(&mut self.master.wires, &mut self.adc.wires);
+        self.master.clock.next = self.bus.clock.val();
+        self.adc.clock.next = self.bus.clock.val();
+        self.master.continued_transaction.next = false;
+        self.master.bits_outbound.next = 32.into();
+        self.master.data_outbound.next = 0.into();
+        self.histogram.data_in.next = self.master.data_inbound.val().get_bits::<16>(16);
+        self.histogram.strobe_in.next = self.master.transfer_done.val();
+    }
+}
+
+fn make_test() -> HistogramTest {
+    let mut uut = HistogramTest::default();
+    uut.master.start_send.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_hls_histogram_synthesizes() {
+    let uut = make_test();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_histogram_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_hls_histogram_of_adc_samples() {
+    let uut = make_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<HistogramTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<HistogramTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        // The histogram is cleared after reset
+        while bus_port_read!(sim, x, bus, 8) != 0 {}
+        // 16 bins, each 2 wide, starting at 4
+        bus_port_write!(sim, x, bus, 1, [4]);
+        bus_port_write!(sim, x, bus, 2, [1]);
+        let mut samples = vec![];
+        for _ in 0..48 {
+            wait_clock_true!(sim, bus.clock, x);
+            x.master.start_send.next = true;
+            wait_clock_cycle!(sim, bus.clock, x);
+            x.master.start_send.next = false;
+            x = sim.watch(|x| x.master.transfer_done.val(), x)?;
+            samples.push(x.master.data_inbound.val().get_bits::<16>(16).index() as i64);
+            wait_clock_cycles!(sim, bus.clock, x, 50);
+        }
+        // Software reference
+        let mut bins = [0; 16];
+        let mut underflow = 0;
+        let mut overflow = 0;
+        for sample in &samples {
+            let bin = (sample - 4) >> 1;
+            if *sample < 4 {
+                underflow += 1;
+            } else if bin >= 16 {
+                overflow += 1;
+            } else {
+                bins[bin as usize] += 1;
+            }
+        }
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 5), samples.len() as u64, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 6), underflow, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 7), overflow, x);
+        bus_port_write!(sim, x, bus, 3, [0]);
+        for bin in bins {
+            sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 4), bin, x);
+        }
+        // Read out a single bin
+        bus_port_write!(sim, x, bus, 3, [5]);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 4), bins[5], x);
+        // Clear it
+        bus_port_write!(sim, x, bus, 0, [1]);
+        while bus_port_read!(sim, x, bus, 8) != 0 {}
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 5), 0, x);
+        bus_port_write!(sim, x, bus, 3, [0]);
+        for _ in 0..16 {
+            sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 4), 0, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 5_000_000, &vcd_path!("hls_histogram.vcd"))
+        .unwrap();
+}
+
+type SmallHistogram = Histogram<8, 3, 4>;
+
+#[test]
+fn test_histogram_counts_saturate() {
+    let mut uut = SmallHistogram::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SmallHistogram>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<SmallHistogram>| {
+        let mut x = sim.init()?;
+        x = sim.watch(|x| !x.busy.val(), x)?;
+        wait_clock_true!(sim, clock, x);
+        // Back to back samples into the same bin, then alternating bins
+        for _ in 0..20 {
+            x.data_in.next = 3.into();
+            x.strobe_in.next = true;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        for ndx in 0..6 {
+            x.data_in.next = (5 + ndx % 2).into();
+            x.strobe_in.next = true;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        x.data_in.next = 200.into();
+        wait_clock_cycle!(sim, clock, x);
+        x.strobe_in.next = false;
+        wait_clock_cycles!(sim, clock, x, 4);
+        let expected = [0, 0, 0, 15, 0, 3, 3, 0];
+        for (bin, count) in expected.iter().enumerate() {
+            x.read_address.next = bin.to_bits();
+            wait_clock_cycles!(sim, clock, x, 2);
+            sim_assert_eq!(sim, x.read_data.val(), *count as u64, x);
+        }
+        sim_assert_eq!(sim, x.total.val(), 15, x);
+        sim_assert_eq!(sim, x.overflow.val(), 1, x);
+        sim_assert_eq!(sim, x.underflow.val(), 0, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10_000, &vcd_path!("histogram_saturate.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,178 @@
+use rust_hdl::prelude::*;
+use rust_hdl::sim::sdr_sdram::chip::SDRAMSimulator;
+use rust_hdl::widgets::sdram::buffer::SDRAMOnChipBuffer;
+
+// The memory tester, with a fault on the data bus - the bits set in `stuck`
+// are stuck high on the way into the memory
+#[derive(LogicBlock)]
+struct MemoryTesterFixture {
+    pub stuck: Signal<In, Bits<16>>,
+    bus: SoCBusController<16, 8>,
+    tester: HLSMemoryTester<6, 4>,
+    buffer: SDRAMOnChipBuffer<16>,
+    dram: SDRAMSimulator<6, 4, 10, 16>,
+}
+
+impl Logic for MemoryTesterFixture {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.tester.upstream);
+        SDRAMDriver::<16>::join(&mut self.tester.dram, &mut self.buffer.buf_in);
+        self.dram.sdram.clk.next = self.buffer.buf_out.clk.val();
+        self.dram.sdram.we_not.next = self.buffer.buf_out.we_not.val();
+        self.dram.sdram.cas_not.next = self.buffer.buf_out.cas_not.val();
+        self.dram.sdram.ras_not.next = self.buffer.buf_out.ras_not.val();
+        self.dram.sdram.cs_not.next = self.buffer.buf_out.cs_not.val();
+        self.dram.sdram.bank.next = self.buffer.buf_out.bank.val();
+        self.dram.sdram.address.next = self.buffer.buf_out.address.val();
+        self.dram.sdram.write_data.next = self.buffer.buf_out.write_data.val() | self.stuck.val();
+        self.dram.sdram.write_enable.next = self.buffer.buf_out.write_enable.val();
+        self.buffer.buf_out.read_data.next = self.dram.sdram.read_data.val();
+    }
+}
+
+impl Default for MemoryTesterFixture {
+    fn default() -> Self {
+        let timings = MemoryTimings::fast_boot_sim(100e6);
+        Self {
+            stuck: Default::default(),
+            bus: Default::default(),
+            tester: HLSMemoryTester::new(3, timings, OutputBuffer::DelayTwo),
+            buffer: Default::default(),
+            dram: SDRAMSimulator::new(timings),
+        }
+    }
+}
+
+fn make_fixture() -> MemoryTesterFixture {
+    let mut uut = MemoryTesterFixture::default();
+    uut.stuck.connect();
+    uut.connect_all();
+    uut
+}
+
+#[derive(Clone, Debug, Default, PartialEq)]
+struct Results {
+    errors: u64,
+    first_error: u64,
+    last_error: u64,
+    error_bits: u64,
+    checked: u64,
+}
+
+// Runs each (pattern, seed) in turn over the first `length` addresses,
+// with the given bits stuck high, and collects the results
+fn run_memory_tests(stuck: u16, length: u32, tests: Vec<(u16, u32)>) -> Vec<Results> {
+    let results = std::sync::Arc::new(std::sync::Mutex::new(vec![]));
+    let mut sim = Simulation::new();
+    sim.add_clock(5000, |x: &mut Box<MemoryTesterFixture>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    let log = results.clone();
+    sim.add_testbench(move |mut sim: Sim<MemoryTesterFixture>| {
+        let mut x = sim.init()?;
+        x.stuck.next = stuck.to_bits();
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        bus_port_write!(sim, x, bus, 0, [length >> 16, length & 0xFFFF]);
+        for (pattern, seed) in tests.clone() {
+            bus_port_write!(sim, x, bus, 1, [seed >> 16, seed & 0xFFFF]);
+            bus_port_write!(sim, x, bus, 2, [pattern]);
+            loop {
+                let status = bus_port_read!(sim, x, bus, 3, 1);
+                if status & 3 == 2 {
+                    break;
+                }
+                wait_clock_cycles!(sim, bus.clock, x, 100);
+            }
+            let results = Results {
+                errors: bus_port_read!(sim, x, bus, 4, 2),
+                first_error: bus_port_read!(sim, x, bus, 5, 2),
+                last_error: bus_port_read!(sim, x, bus, 6, 2),
+                error_bits: bus_port_read!(sim, x, bus, 7, 4),
+                checked: bus_port_read!(sim, x, bus, 8, 2),
+            };
+            let status = bus_port_read!(sim, x, bus, 3, 1);
+            let flagged = status & 4 != 0;
+            let failed = results.errors != 0;
+            sim_assert_eq!(sim, flagged, failed, x);
+            log.lock().unwrap().push(results);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(make_fixture()), 500_000_000).unwrap();
+    let results = results.lock().unwrap().clone();
+    results
+}
+
+#[test]
+fn test_memory_tester_synthesizes() {
+    let uut = make_fixture();
+    yosys_validate("hls_memory_tester", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_memory_tester_passes_good_memory() {
+    let tests = vec![
+        (MemoryTestPattern::WalkingOnes as u16, 0),
+        (MemoryTestPattern::WalkingZeros as u16, 0),
+        (MemoryTestPattern::AddressInAddress as u16, 0),
+        (MemoryTestPattern::Random as u16, 0xDEAD_BEEF),
+    ];
+    let results = run_memory_tests(0, 512, tests);
+    assert_eq!(results.len(), 4);
+    for result in results {
+        assert_eq!(
+            result,
+            Results {
+                checked: 128,
+                ..Default::default()
+            }
+        );
+    }
+}
+
+#[test]
+fn test_memory_tester_finds_a_stuck_data_bit() {
+    let tests = vec![
+        (MemoryTestPattern::WalkingOnes as u16, 0),
+        (MemoryTestPattern::WalkingZeros as u16, 0),
+        (MemoryTestPattern::Random as u16, 42),
+    ];
+    // Bit 3 of the data bus shows up in bit 3 of each 16 bit word of a transfer
+    let stuck_bits = 0x0008_0008_0008_0008;
+    let results = run_memory_tests(0x0008, 256, tests);
+    // Walking ones fails everywhere (there is always a zero on one of the stuck bits)
+    assert_eq!(
+        results[0],
+        Results {
+            errors: 64,
+            first_error: 0,
+            last_error: 252,
+            error_bits: stuck_bits,
+            checked: 64,
+        }
+    );
+    // And walking zeros fails only where the zero is on a stuck bit
+    assert_eq!(
+        results[1],
+        Results {
+            errors: 4,
+            first_error: 3 * 4,
+            last_error: 51 * 4,
+            error_bits: stuck_bits,
+            checked: 64,
+        }
+    );
+    // The random pattern fails wherever it had a zero on a stuck bit
+    let expected = MemoryTestPattern::Random
+        .words(64, 42)
+        .into_iter()
+        .enumerate()
+        .filter(|(_, word)| (word | stuck_bits) != *word)
+        .map(|(ndx, _)| ndx as u64 * 4)
+        .collect::<Vec<_>>();
+    assert_eq!(results[2].errors, expected.len() as u64);
+    assert_eq!(results[2].first_error, expected[0]);
+    assert_eq!(results[2].last_error, *expected.last().unwrap());
+    assert_eq!(results[2].error_bits, stuck_bits);
+}
@@ -0,0 +1,90 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock)]
+struct ParallelBusTest {
+    mcu: ParallelBusSimulatedMaster<16, 8>,
+    slave: ParallelBusSlave<16, 8>,
+    bridge: Bridge<16, 8, 2>,
+    scratch: MOSIPort<16>,
+    readback: MISOPort<16>,
+    pub mcu_clock: Signal<In, Clock>,
+    pub fpga_clock: Signal<In, Clock>,
+}
+
+impl Default for ParallelBusTest {
+    fn default() -> Self {
+        Self {
+            mcu: Default::default(),
+            slave: Default::default(),
+            bridge: Bridge::new(["scratch", "readback"]),
+            scratch: Default::default(),
+            readback: Default::default(),
+            mcu_clock: Default::default(),
+            fpga_clock: Default::default(),
+        }
+    }
+}
+
+impl Logic for ParallelBusTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.mcu.clock.next = self.mcu_clock.val();
+        self.slave.clock.next = self.fpga_clock.val();
+        ParallelBusController::<16, 8>::join(&mut self.mcu.pins, &mut self.slave.pins);
+        SoCBusController::<16, 8>::join(&mut self.slave.bus, &mut self.bridge.upstream);
+        SoCPortController::<16>::join(&mut self.bridge.nodes[0], &mut self.scratch.bus);
+        SoCPortController::<16>::join(&mut self.bridge.nodes[1], &mut self.readback.bus);
+        self.scratch.ready.next = true;
+        // Read back the complement of what was written
+        self.readback.port_in.next = !self.scratch.port_out.val();
+        self.readback.ready_in.next = true;
+    }
+}
+
+fn make_test() -> ParallelBusTest {
+    let mut uut = ParallelBusTest::default();
+    uut.mcu.start.connect();
+    uut.mcu.write.connect();
+    uut.mcu.address.connect();
+    uut.mcu.write_data.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_parallel_bus_test_synthesizes() {
+    let uut = make_test();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("parallel_bus_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_parallel_bus_maps_mcu_accesses_onto_the_soc_bus() {
+    let uut = make_test();
+    let mut sim = Simulation::new();
+    // The MCU and the FPGA run from unrelated clocks
+    sim.add_clock(7, |x: &mut Box<ParallelBusTest>| {
+        x.mcu_clock.next = !x.mcu_clock.val()
+    });
+    sim.add_clock(5, |x: &mut Box<ParallelBusTest>| {
+        x.fpga_clock.next = !x.fpga_clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<ParallelBusTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, mcu_clock, x, 10);
+        for val in [0x1234_u16, 0xBEEF, 0x0000, 0x5A5A] {
+            parallel_bus_write!(sim, x, mcu, 0, val);
+            let readback = parallel_bus_read!(sim, x, mcu, 1);
+            sim_assert_eq!(sim, readback, !val as u64, x);
+            sim_assert_eq!(sim, x.scratch.port_out.val(), val as u64, x);
+        }
+        // Back to back writes are held off until the previous one is done
+        parallel_bus_write!(sim, x, mcu, 0, 0x1111);
+        parallel_bus_write!(sim, x, mcu, 0, 0x2222);
+        let readback = parallel_bus_read!(sim, x, mcu, 1);
+        sim_assert_eq!(sim, readback, !0x2222_u16 as u64, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("parallel_bus.vcd"))
+        .unwrap();
+}
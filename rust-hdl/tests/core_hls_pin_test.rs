@@ -0,0 +1,99 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock)]
+struct PinTestBench {
+    bus: SoCBusController<16, 8>,
+    selftest: HLSPinSelfTest<16, 8, 6>,
+}
+
+impl Default for PinTestBench {
+    fn default() -> Self {
+        Self {
+            bus: Default::default(),
+            // The jig wires pins 0-1 and 2-3 together, and leaves 4 and 5 alone
+            selftest: HLSPinSelfTest::new(&[(0, 1), (2, 3)], 4),
+        }
+    }
+}
+
+impl Logic for PinTestBench {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.selftest.upstream);
+    }
+}
+
+// Run the self-test against a jig with the given wires (which may differ
+// from the ones the block expects), and return the status and the result
+// of each pin.  Pins that are not driven by anything are pulled low.
+fn run_pin_test(wires: &'static [(usize, usize)]) -> (u64, Vec<u64>) {
+    let mut uut = PinTestBench::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<PinTestBench>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_custom_logic(move |x: &mut PinTestBench| {
+        let pins = &mut x.selftest.pins;
+        for i in 0..6 {
+            if pins[i].is_driving_tristate() {
+                continue;
+            }
+            let mut level = false;
+            for &(a, b) in wires {
+                for (from, to) in [(a, b), (b, a)] {
+                    if to == i && pins[from].is_driving_tristate() {
+                        level = pins[from].val();
+                    }
+                }
+            }
+            pins[i].next = level;
+        }
+    });
+    let results = std::sync::Arc::new(std::sync::Mutex::new((0, vec![])));
+    let report = results.clone();
+    sim.add_testbench(move |mut sim: Sim<PinTestBench>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        bus_port_write!(sim, x, bus, 0, [1]);
+        let mut status = bus_port_read!(sim, x, bus, 1);
+        sim_assert_eq!(sim, status & 1, 1, x);
+        while status & 1 != 0 {
+            status = bus_port_read!(sim, x, bus, 1);
+        }
+        let mut flags = vec![];
+        for pin in 0..6 {
+            bus_port_write!(sim, x, bus, 2, [pin]);
+            flags.push(bus_port_read!(sim, x, bus, 3));
+        }
+        *report.lock().unwrap() = (status, flags);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("hls_pin_test.vcd"))
+        .unwrap();
+    let ret = results.lock().unwrap().clone();
+    ret
+}
+
+#[test]
+fn test_pin_self_test_synthesizes() {
+    let mut uut = PinTestBench::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_pin_self_test_bench", &vlog).unwrap();
+}
+
+#[test]
+fn test_pin_self_test_passes_a_good_board() {
+    let (status, flags) = run_pin_test(&[(0, 1), (2, 3)]);
+    assert_eq!(status, 0b010);
+    assert_eq!(flags, vec![0; 6]);
+}
+
+#[test]
+fn test_pin_self_test_finds_opens_and_shorts() {
+    // The wire between 2 and 3 is broken, and 4 is shorted to 5
+    let (status, flags) = run_pin_test(&[(0, 1), (4, 5)]);
+    assert_eq!(status, 0b110);
+    assert_eq!(flags, vec![0, 0, 0b010, 0b010, 0b100, 0b100]);
+}
@@ -0,0 +1,74 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct RAMTest {
+    bus: SoCBusController<16, 8>,
+    ram: HLSRAM<16, 8, 6>,
+}
+
+impl Logic for RAMTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.ram.upstream);
+    }
+}
+
+#[test]
+fn test_ram_synthesizes() {
+    let mut uut = RAMTest::default();
+    uut.ram.read_address.connect();
+    uut.ram.write_address.connect();
+    uut.ram.write_data.connect();
+    uut.ram.write_enable.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_ram_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_ram_works() {
+    let mut uut = RAMTest::default();
+    uut.ram.read_address.connect();
+    uut.ram.write_address.connect();
+    uut.ram.write_data.connect();
+    uut.ram.write_enable.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<RAMTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<RAMTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, bus.clock, x);
+        // Load a table from the host, starting at word 4
+        bus_port_write!(sim, x, bus, 0, [4]);
+        for val in [0x10, 0x20, 0x30] {
+            bus_port_write!(sim, x, bus, 1, [val]);
+        }
+        // Which the design can then read
+        x.ram.read_address.next = 5.into();
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert_eq!(sim, x.ram.read_data.val(), 0x20, x);
+        // The design stores a value of its own
+        x.ram.write_address.next = 9.into();
+        x.ram.write_data.next = 0xBEEF.into();
+        x.ram.write_enable.next = true;
+        wait_clock_cycle!(sim, bus.clock, x);
+        x.ram.write_enable.next = false;
+        // And the host reads everything back
+        bus_port_write!(sim, x, bus, 0, [4]);
+        for expected in [0x10, 0x20, 0x30, 0, 0, 0xBEEF] {
+            let val = bus_port_read!(sim, x, bus, 2);
+            sim_assert_eq!(sim, val, expected, x);
+        }
+        // Random access is a write to the address before each read
+        for (address, expected) in [(9, 0xBEEF), (4, 0x10), (6, 0x30)] {
+            bus_port_write!(sim, x, bus, 0, [address]);
+            let val = bus_port_read!(sim, x, bus, 2);
+            sim_assert_eq!(sim, val, expected, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 50_000, &vcd_path!("hls_ram.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,57 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct RebootTest {
+    bus: SoCBusController<16, 8>,
+    reboot: HLSRebootController<16, 8>,
+}
+
+impl Logic for RebootTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.reboot.upstream);
+    }
+}
+
+#[test]
+fn test_hls_reboot_controller_synthesizes() {
+    let mut uut = RebootTest::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_reboot_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_hls_reboot_controller_needs_the_key() {
+    let mut uut = RebootTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<RebootTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<RebootTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        wait_clock_true!(sim, bus.clock, x);
+        bus_port_write!(sim, x, bus, 0, [2]);
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert_eq!(sim, x.reboot.image.val(), 2, x);
+        sim_assert!(sim, !x.reboot.boot.val(), x);
+        // Writing anything but the key does not reboot
+        bus_port_write!(sim, x, bus, 1, [0x1234]);
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        sim_assert!(sim, !x.reboot.boot.val(), x);
+        bus_port_write!(sim, x, bus, 1, [REBOOT_KEY]);
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert!(sim, x.reboot.boot.val(), x);
+        sim_assert_eq!(sim, x.reboot.image.val(), 2, x);
+        // Once triggered, the image is locked
+        bus_port_write!(sim, x, bus, 0, [1]);
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert_eq!(sim, x.reboot.image.val(), 2, x);
+        sim_assert!(sim, x.reboot.boot.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("hls_reboot.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,94 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock)]
+struct RTCTest {
+    bus: SoCBusController<16, 8>,
+    rtc: HLSRealTimeClock<16, 8, 10>,
+}
+
+impl Default for RTCTest {
+    fn default() -> Self {
+        Self {
+            bus: Default::default(),
+            // A second is 1000 clock cycles
+            rtc: HLSRealTimeClock::new(1000),
+        }
+    }
+}
+
+impl Logic for RTCTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.rtc.upstream);
+    }
+}
+
+// Read a 64 bit time, and convert it to clock cycles
+macro_rules! rtc_read_time {
+    ($sim: ident, $x: ident, $addr: expr) => {{
+        let val = bus_port_read!($sim, $x, bus, $addr, 4);
+        (val >> 32) * 1000 + (val & 0xFFFF_FFFF)
+    }};
+}
+
+#[test]
+fn test_hls_rtc_synthesizes() {
+    let mut uut = RTCTest::default();
+    uut.rtc.event.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_rtc_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_hls_rtc_works() {
+    let mut uut = RTCTest::default();
+    uut.rtc.event.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<RTCTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<RTCTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        wait_clock_true!(sim, bus.clock, x);
+        // Set the time, and let it run for a bit over a second
+        bus_port_write!(sim, x, bus, 0, [0x1234]);
+        bus_port_write!(sim, x, bus, 0, [0x5678]);
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        sim_assert_eq!(sim, x.rtc.seconds.val(), 0x1234_5678, x);
+        wait_clock_cycles!(sim, bus.clock, x, 1500);
+        sim_assert_eq!(sim, x.rtc.seconds.val(), 0x1234_5679, x);
+        // Take a snapshot of the time, and read it back
+        bus_port_write!(sim, x, bus, 1, [0]);
+        let now = rtc_read_time!(sim, x, 2);
+        sim_assert!(sim, now > 0x1234_5679 * 1000 + 500, x);
+        sim_assert!(sim, now < 0x1234_5679 * 1000 + 600, x);
+        // Queue up 3 events, 100 cycles apart
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 3), 0, x);
+        for _ in 0..3 {
+            x.rtc.event.next = true;
+            wait_clock_cycle!(sim, bus.clock, x);
+            x.rtc.event.next = false;
+            wait_clock_cycles!(sim, bus.clock, x, 99);
+        }
+        let mut times = vec![];
+        while bus_port_read!(sim, x, bus, 3) & 1 != 0 {
+            bus_port_write!(sim, x, bus, 4, [0]);
+            times.push(rtc_read_time!(sim, x, 5));
+        }
+        sim_assert_eq!(sim, times.len(), 3, x);
+        sim_assert_eq!(sim, times[1] - times[0], 100, x);
+        sim_assert_eq!(sim, times[2] - times[1], 100, x);
+        // Overflow the queue - the overflow is cleared when it is read
+        x.rtc.event.next = true;
+        wait_clock_cycles!(sim, bus.clock, x, 20);
+        x.rtc.event.next = false;
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 3), 0b11, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 3), 0b01, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("hls_rtc.vcd"))
+        .unwrap();
+}
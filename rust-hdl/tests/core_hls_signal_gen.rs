@@ -0,0 +1,103 @@
+use num_traits::ToPrimitive;
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct SignalGenTest {
+    bus: SoCBusController<16, 8>,
+    am: FIFOWriteController<Signed<16>>,
+    fm: FIFOWriteController<Signed<16>>,
+    pm: FIFOWriteController<Signed<16>>,
+    gen: HLSSignalGenerator<16, 8>,
+}
+
+impl Logic for SignalGenTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.gen.upstream);
+        FIFOWriteController::<Signed<16>>::join(&mut self.am, &mut self.gen.am);
+        FIFOWriteController::<Signed<16>>::join(&mut self.fm, &mut self.gen.fm);
+        FIFOWriteController::<Signed<16>>::join(&mut self.pm, &mut self.gen.pm);
+    }
+}
+
+#[test]
+fn test_signal_gen_synthesizes() {
+    let mut uut = SignalGenTest::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_signal_gen_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_signal_gen_works() {
+    let mut uut = SignalGenTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SignalGenTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<SignalGenTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        // A period of 64 clocks, at half of full scale
+        bus_port_write!(sim, x, bus, 1, [0x0400]);
+        bus_port_write!(sim, x, bus, 1, [0x0000]);
+        bus_port_write!(sim, x, bus, 3, [0x4000]);
+        bus_port_write!(sim, x, bus, 0, [0b10001]);
+        x = sim.watch(|x| x.gen.strobe_out.val(), x)?;
+        let mut sin = vec![];
+        let mut cos = vec![];
+        for _ in 0..128 {
+            wait_clock_cycle!(sim, bus.clock, x);
+            sin.push(x.gen.sin_out.val().bigint().to_i64().unwrap());
+            cos.push(x.gen.cos_out.val().bigint().to_i64().unwrap());
+        }
+        // The waveform repeats every 64 clocks, inverts every 32, and keeps
+        // the sine and cosine on a circle of the programmed amplitude
+        for ndx in 0..64 {
+            sim_assert!(sim, (sin[ndx] - sin[ndx + 64]).abs() <= 2, x);
+            sim_assert!(sim, (sin[ndx] + sin[ndx + 32]).abs() <= 2, x);
+            let radius = ((sin[ndx] * sin[ndx] + cos[ndx] * cos[ndx]) as f64).sqrt();
+            sim_assert!(sim, (radius - 16384.0).abs() < 16.0, x);
+        }
+        // Stop the generator, and move it with the modulation inputs
+        bus_port_write!(sim, x, bus, 0, [0b10000]);
+        bus_port_write!(sim, x, bus, 2, [0]);
+        bus_port_write!(sim, x, bus, 3, [0x7FFF]);
+        wait_clock_cycles!(sim, bus.clock, x, 8);
+        sim_assert!(
+            sim,
+            x.gen.sin_out.val().bigint().to_i64().unwrap().abs() < 200,
+            x
+        );
+        sim_assert!(
+            sim,
+            x.gen.cos_out.val().bigint().to_i64().unwrap() > 32500,
+            x
+        );
+        // A quarter turn of phase modulation, and half of the amplitude
+        // taken away
+        x.pm.data.next = 0x4000_i64.to_signed_bits();
+        x.pm.write.next = true;
+        x.am.data.next = (-0x4000_i64).to_signed_bits();
+        x.am.write.next = true;
+        wait_clock_cycle!(sim, bus.clock, x);
+        x.pm.write.next = false;
+        x.am.write.next = false;
+        bus_port_write!(sim, x, bus, 0, [0b01010]);
+        wait_clock_cycles!(sim, bus.clock, x, 8);
+        sim_assert!(
+            sim,
+            (x.gen.sin_out.val().bigint().to_i64().unwrap() - 16383).abs() < 100,
+            x
+        );
+        sim_assert!(
+            sim,
+            x.gen.cos_out.val().bigint().to_i64().unwrap().abs() < 200,
+            x
+        );
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("hls_signal_gen.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,121 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct StatisticsTest {
+    bus: SoCBusController<16, 8>,
+    data_in: Signal<In, Signed<16>>,
+    strobe_in: Signal<In, Bit>,
+    stats: HLSStatistics<16, 8>,
+}
+
+impl Logic for StatisticsTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.stats.upstream);
+        self.stats.data_in.next = self.data_in.val();
+        self.stats.strobe_in.next = self.strobe_in.val();
+    }
+}
+
+// Latches the results, and reads them back as
+// (mean, std_dev, rms, variance, mean_square)
+macro_rules! stats_results {
+    ($sim: ident, $x: ident) => {{
+        bus_port_write!($sim, $x, bus, 1, [0]);
+        let mean = bus_port_read!($sim, $x, bus, 3) as u16 as i16 as i64;
+        let std_dev = bus_port_read!($sim, $x, bus, 4);
+        let rms = bus_port_read!($sim, $x, bus, 5);
+        let variance = bus_port_read!($sim, $x, bus, 6, 2);
+        let mean_square = bus_port_read!($sim, $x, bus, 7, 2);
+        (mean, std_dev, rms, variance, mean_square)
+    }};
+}
+
+// A tone on top of a DC offset, plus some noise
+fn make_samples(count: usize) -> Vec<i64> {
+    let mut seed = 0x1234_5678_u32;
+    (0..count)
+        .map(|ndx| {
+            seed = seed.wrapping_mul(1664525).wrapping_add(1013904223);
+            let noise = ((seed >> 16) % 401) as i64 - 200;
+            let tone = 3000.0 * (2.0 * std::f64::consts::PI * ndx as f64 / 32.0).sin();
+            -1000 + tone.round() as i64 + noise
+        })
+        .collect()
+}
+
+#[test]
+fn test_hls_statistics_synthesizes() {
+    let mut uut = StatisticsTest::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_statistics_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_hls_statistics_works() {
+    let mut uut = StatisticsTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<StatisticsTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<StatisticsTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        // Windows of 256 samples
+        bus_port_write!(sim, x, bus, 0, [8]);
+        let samples = make_samples(256 * 3);
+        for (window, block) in samples.chunks(256).enumerate() {
+            for sample in block {
+                x.data_in.next = (*sample).to_signed_bits();
+                x.strobe_in.next = true;
+                wait_clock_cycle!(sim, bus.clock, x);
+                x.strobe_in.next = false;
+                wait_clock_cycles!(sim, bus.clock, x, 2);
+            }
+            while bus_port_read!(sim, x, bus, 2) != window as u64 + 1 {}
+            let (mean, std_dev, rms, variance, mean_square) = stats_results!(sim, x);
+            // Software reference
+            let n = block.len() as f64;
+            let ref_mean = block.iter().sum::<i64>() as f64 / n;
+            let ref_mean_square = block.iter().map(|x| (x * x) as f64).sum::<f64>() / n;
+            let ref_variance = ref_mean_square - ref_mean * ref_mean;
+            sim_assert_eq!(sim, mean, ref_mean.floor() as i64, x);
+            // The first two windows are measured against a mean of zero, so the
+            // rounding of the mean (of about 1000) costs a few thousand LSBs^2
+            let slack = if window < 2 { 3000.0 } else { 100.0 };
+            sim_assert!(sim, (variance as f64 - ref_variance).abs() < slack, x);
+            // The mean square adds the square of the rounded mean, which is off by
+            // up to twice the mean
+            sim_assert!(
+                sim,
+                (mean_square as f64 - ref_mean_square).abs() < 2.0 * ref_mean.abs() + 1.0,
+                x
+            );
+            sim_assert_eq!(sim, std_dev, (variance as f64).sqrt().floor() as u64, x);
+            sim_assert_eq!(sim, rms, (mean_square as f64).sqrt().floor() as u64, x);
+            sim_assert!(sim, (std_dev as f64 - ref_variance.sqrt()).abs() < 2.0, x);
+            sim_assert!(sim, (rms as f64 - ref_mean_square.sqrt()).abs() < 2.0, x);
+        }
+        // A constant signal, with windows of 16 samples, has no variance
+        bus_port_write!(sim, x, bus, 0, [4]);
+        for _ in 0..32 {
+            x.data_in.next = (-1234_i64).to_signed_bits();
+            x.strobe_in.next = true;
+            wait_clock_cycle!(sim, bus.clock, x);
+            x.strobe_in.next = false;
+        }
+        wait_clock_cycles!(sim, bus.clock, x, 40);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 2), 5, x);
+        let (mean, std_dev, rms, variance, mean_square) = stats_results!(sim, x);
+        sim_assert_eq!(sim, mean, -1234, x);
+        sim_assert_eq!(sim, variance, 0, x);
+        sim_assert_eq!(sim, std_dev, 0, x);
+        sim_assert_eq!(sim, mean_square, 1234 * 1234, x);
+        sim_assert_eq!(sim, rms, 1234, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("hls_statistics.vcd"))
+        .unwrap();
+}
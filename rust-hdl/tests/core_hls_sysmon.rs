@@ -0,0 +1,67 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct SystemMonitorTest {
+    bus: SoCBusController<16, 8>,
+    sysmon: HLSSystemMonitor<16, 8>,
+}
+
+impl Logic for SystemMonitorTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.sysmon.upstream);
+    }
+}
+
+fn make_test() -> SystemMonitorTest {
+    let mut uut = SystemMonitorTest::default();
+    uut.sysmon.sensor.temperature.connect();
+    uut.sysmon.sensor.vccint.connect();
+    uut.sysmon.sensor.vccaux.connect();
+    uut.sysmon.sensor.valid.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_hls_system_monitor_synthesizes() {
+    let uut = make_test();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_sysmon_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_hls_system_monitor_works() {
+    let uut = make_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SystemMonitorTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<SystemMonitorTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        wait_clock_true!(sim, bus.clock, x);
+        // 45 C, 0.95 V and 1.8 V
+        x.sysmon.sensor.temperature.next = (((45.0 + 273.15) * 16.0) as u64).into();
+        x.sysmon.sensor.vccint.next = 950.into();
+        x.sysmon.sensor.vccaux.next = 1800.into();
+        // The readings only count when they are valid
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 0), 0, x);
+        x.sysmon.sensor.valid.next = true;
+        wait_clock_cycle!(sim, bus.clock, x);
+        x.sysmon.sensor.valid.next = false;
+        x.sysmon.sensor.vccint.next = 0.into();
+        let temperature = bus_port_read!(sim, x, bus, 0) as u16;
+        sim_assert!(
+            sim,
+            (temperature_celsius(temperature) - 45.0).abs() < 0.1,
+            x
+        );
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 1), 950, x);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 2), 1800, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("hls_sysmon.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,76 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct TimerTest {
+    bus: SoCBusController<16, 8>,
+    capture: Signal<In, Bits<4>>,
+    timer: HLSTimer<16, 8>,
+}
+
+impl Logic for TimerTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.timer.upstream);
+        self.timer.capture.next = self.capture.val();
+    }
+}
+
+#[test]
+fn test_timer_synthesizes() {
+    let mut uut = TimerTest::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_timer_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_timer_works() {
+    let mut uut = TimerTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TimerTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<TimerTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        // Tick every other clock, and wrap every 10 ticks, with channel 0
+        // in PWM mode at 30%, and channel 1 capturing rising edges
+        bus_port_write!(sim, x, bus, 1, [1]);
+        bus_port_write!(sim, x, bus, 2, [9]);
+        bus_port_write!(sim, x, bus, 7, [3]);
+        bus_port_write!(sim, x, bus, 4, [0b1 | (0b10 << 4)]);
+        bus_port_write!(sim, x, bus, 5, [1]);
+        bus_port_write!(sim, x, bus, 0, [0b11]);
+        // Measure the duty cycle over 10 periods
+        let mut high = 0;
+        for _ in 0..200 {
+            wait_clock_cycle!(sim, bus.clock, x);
+            if x.timer.pwm.val().get_bit(0) {
+                high += 1;
+            }
+        }
+        sim_assert_eq!(sim, high, 60, x);
+        // The update interrupt is pending, and reading the status clears it
+        sim_assert!(sim, x.timer.irq.val(), x);
+        let status = bus_port_read!(sim, x, bus, 6);
+        sim_assert!(sim, status & 0b11 == 0b11, x);
+        // Freeze the counter, and capture it on channel 1
+        bus_port_write!(sim, x, bus, 0, [0]);
+        let count = bus_port_read!(sim, x, bus, 3);
+        let status = bus_port_read!(sim, x, bus, 6);
+        sim_assert!(sim, status & 0b100 == 0, x);
+        sim_assert!(sim, !x.timer.irq.val(), x);
+        x.capture.next = 0b10.into();
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        let captured = bus_port_read!(sim, x, bus, 12);
+        sim_assert_eq!(sim, captured, count, x);
+        let status = bus_port_read!(sim, x, bus, 6);
+        sim_assert!(sim, status & 0b100 != 0, x);
+        // Channel events are not enabled as interrupts
+        sim_assert!(sim, !x.timer.irq.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("hls_timer.vcd"))
+        .unwrap();
+}
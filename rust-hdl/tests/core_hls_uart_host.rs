@@ -0,0 +1,134 @@
+use rust_hdl::host::prelude::{hdlc_encode, HDLCDecoder};
+use rust_hdl::prelude::*;
+
+// A UARTHost with a couple of ports behind it.  The PC side of the serial
+// link is modelled with a second UART.
+#[derive(LogicBlock)]
+struct UARTHostTest {
+    clock: Signal<In, Clock>,
+    host: UARTHost<8>,
+    bridge: Bridge<16, 8, 2>,
+    port: MOSIPort<16>,
+    iport: MISOPort<16>,
+    pc_tx: UARTTransmitter,
+    pc_rx: UARTReceiver,
+}
+
+impl Default for UARTHostTest {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            host: UARTHost::new(WordOrder::MostSignificantFirst, 1_000_000, 125_000),
+            bridge: Bridge::new(["port", "iport"]),
+            port: Default::default(),
+            iport: Default::default(),
+            pc_tx: UARTTransmitter::new(1_000_000, 125_000),
+            pc_rx: UARTReceiver::new(1_000_000, 125_000),
+        }
+    }
+}
+
+impl Logic for UARTHostTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, host, pc_tx, pc_rx);
+        self.host.rx.next = self.pc_tx.tx.val();
+        self.pc_rx.rx.next = self.host.tx.val();
+        SoCBusController::<16, 8>::join(&mut self.host.bus, &mut self.bridge.upstream);
+        SoCPortController::<16>::join(&mut self.bridge.nodes[0], &mut self.port.bus);
+        SoCPortController::<16>::join(&mut self.bridge.nodes[1], &mut self.iport.bus);
+        self.port.ready.next = true;
+    }
+}
+
+fn make_uart_host_test() -> UARTHostTest {
+    let mut uut = UARTHostTest::default();
+    uut.iport.port_in.connect();
+    uut.iport.ready_in.connect();
+    uut.pc_tx.data_in.connect();
+    uut.pc_tx.write.connect();
+    uut.connect_all();
+    uut
+}
+
+fn words_to_frame(words: &[u16]) -> Vec<u8> {
+    hdlc_encode(
+        &words
+            .iter()
+            .flat_map(|word| word.to_be_bytes())
+            .collect::<Vec<_>>(),
+    )
+}
+
+#[test]
+fn test_uart_host_test_synthesizes() {
+    let uut = make_uart_host_test();
+    yosys_validate("uart_host_test", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_uart_host_carries_bus_commands() {
+    let uut = make_uart_host_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<UARTHostTest>| x.clock.next = !x.clock.val());
+    // The PC sends a corrupted ping (which must be ignored), a good ping, a
+    // write of 3 words (with flags and escapes in them), and a read
+    sim.add_testbench(move |mut sim: Sim<UARTHostTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 20);
+        let mut bad = words_to_frame(&[0x0155]);
+        bad[2] ^= 0x01;
+        let mut bytes = bad;
+        bytes.extend(words_to_frame(&[0x0167]));
+        bytes.extend(words_to_frame(&[0x0300, 3, 0x7E7D, 0x1234, 0xABCD]));
+        bytes.extend(words_to_frame(&[0x0201, 2]));
+        for byte in bytes {
+            x = sim.watch(|x| !x.pc_tx.busy.val(), x)?;
+            x.pc_tx.data_in.next = byte.to_bits();
+            x.pc_tx.write.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.pc_tx.write.next = false;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    // The PC decodes the replies - the ping echo, and the 2 words read
+    sim.add_testbench(move |mut sim: Sim<UARTHostTest>| {
+        let mut x = sim.init()?;
+        let mut decoder = HDLCDecoder::default();
+        let mut reply = vec![];
+        while reply.len() < 6 {
+            x = sim.watch(|x| x.pc_rx.strobe_out.val(), x)?;
+            if let Some(payload) = decoder.feed(x.pc_rx.data_out.val().index() as u8) {
+                sim_assert!(sim, payload.is_ok(), x);
+                reply.extend(payload.unwrap());
+            }
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim_assert_eq!(sim, reply, vec![0x01, 0x67, 0xBE, 0xE0, 0xBE, 0xE1], x);
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<UARTHostTest>| {
+        let mut x = sim.init()?;
+        for word in [0x7E7D_u16, 0x1234, 0xABCD] {
+            x = sim.watch(|x| x.port.strobe_out.val(), x)?;
+            sim_assert_eq!(sim, x.port.port_out.val(), word.to_bits::<16>(), x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<UARTHostTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        for word in [0xBEE0_u16, 0xBEE1] {
+            x.iport.port_in.next = word.to_bits();
+            x.iport.ready_in.next = true;
+            x = sim.watch(|x| x.iport.strobe_out.val(), x)?;
+            wait_clock_cycle!(sim, clock, x);
+            x.iport.ready_in.next = false;
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("uart_host.vcd"))
+        .unwrap();
+}
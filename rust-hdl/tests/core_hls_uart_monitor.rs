@@ -0,0 +1,202 @@
+use rust_hdl::host::prelude::{hdlc_crc, monitor_command};
+use rust_hdl::prelude::*;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+// A UARTMonitor in front of a write port (whose ready line the test
+// controls) and a read port that returns a count.  The PC side of the
+// serial link is modelled with a second UART.
+#[derive(LogicBlock)]
+struct UARTMonitorTest {
+    clock: Signal<In, Clock>,
+    port_ready: Signal<In, Bit>,
+    monitor: UARTMonitor<8>,
+    bridge: Bridge<16, 8, 2>,
+    port: MOSIPort<16>,
+    iport: MISOPort<16>,
+    counter: DFF<Bits<16>>,
+    pc_tx: UARTTransmitter,
+    pc_rx: UARTReceiver,
+}
+
+impl Default for UARTMonitorTest {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            port_ready: Default::default(),
+            monitor: UARTMonitor::new(1_000_000, 125_000, Duration::from_millis(1)),
+            bridge: Bridge::new(["port", "iport"]),
+            port: Default::default(),
+            iport: Default::default(),
+            counter: Default::default(),
+            pc_tx: UARTTransmitter::new(1_000_000, 125_000),
+            pc_rx: UARTReceiver::new(1_000_000, 125_000),
+        }
+    }
+}
+
+impl Logic for UARTMonitorTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        clock!(self, clock, monitor, pc_tx, pc_rx);
+        self.monitor.rx.next = self.pc_tx.tx.val();
+        self.pc_rx.rx.next = self.monitor.tx.val();
+        SoCBusController::<16, 8>::join(&mut self.monitor.bus, &mut self.bridge.upstream);
+        SoCPortController::<16>::join(&mut self.bridge.nodes[0], &mut self.port.bus);
+        SoCPortController::<16>::join(&mut self.bridge.nodes[1], &mut self.iport.bus);
+        self.port.ready.next = self.port_ready.val();
+        self.iport.port_in.next = self.counter.q.val();
+        self.iport.ready_in.next = true;
+        if self.iport.strobe_out.val() {
+            self.counter.d.next = self.counter.q.val() + 1;
+        }
+    }
+}
+
+fn make_uart_monitor_test() -> UARTMonitorTest {
+    let mut uut = UARTMonitorTest::default();
+    uut.pc_tx.data_in.connect();
+    uut.pc_tx.write.connect();
+    uut.connect_all();
+    uut
+}
+
+fn reply(status: u8, done: u16, data: &[u16]) -> Vec<u8> {
+    let mut reply = vec![status];
+    reply.extend(done.to_be_bytes());
+    reply.extend(data.iter().flat_map(|word| word.to_be_bytes()));
+    let crc = hdlc_crc(&reply);
+    reply.extend(crc.to_be_bytes());
+    reply
+}
+
+macro_rules! pc_send {
+    ($sim: ident, $x: ident, $bytes: expr) => {
+        for byte in $bytes {
+            $x = $sim.watch(|x| !x.pc_tx.busy.val(), $x)?;
+            $x.pc_tx.data_in.next = byte.to_bits();
+            $x.pc_tx.write.next = true;
+            wait_clock_cycle!($sim, clock, $x);
+            $x.pc_tx.write.next = false;
+            wait_clock_cycle!($sim, clock, $x);
+        }
+    };
+}
+
+macro_rules! pc_expect {
+    ($sim: ident, $x: ident, $received: ident, $reply: expr) => {
+        let expected = $reply;
+        while $received.lock().unwrap().len() < expected.len() {
+            wait_clock_cycle!($sim, clock, $x);
+        }
+        let got = $received
+            .lock()
+            .unwrap()
+            .drain(..expected.len())
+            .collect::<Vec<_>>();
+        sim_assert_eq!($sim, got, expected, $x);
+    };
+}
+
+#[test]
+fn test_uart_monitor_test_synthesizes() {
+    let uut = make_uart_monitor_test();
+    yosys_validate("uart_monitor_test", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_uart_monitor_commands() {
+    let uut = make_uart_monitor_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<UARTMonitorTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    // Everything the monitor sends back to the PC
+    let received = Arc::new(Mutex::new(Vec::<u8>::new()));
+    let log = received.clone();
+    let finished = Arc::new(AtomicBool::new(false));
+    let logging = finished.clone();
+    sim.add_testbench(move |mut sim: Sim<UARTMonitorTest>| {
+        let mut x = sim.init()?;
+        while !logging.load(Ordering::SeqCst) {
+            if x.pc_rx.strobe_out.val() {
+                log.lock()
+                    .unwrap()
+                    .push(x.pc_rx.data_out.val().index() as u8);
+            }
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<UARTMonitorTest>| {
+        let mut x = sim.init()?;
+        x.port_ready.next = true;
+        wait_clock_cycles!(sim, clock, x, 20);
+        // Poke and peek
+        pc_send!(
+            sim,
+            x,
+            monitor_command(MONITOR_POKE, 0, 2, &[0x7E7D, 0x1234])
+        );
+        pc_expect!(sim, x, received, reply(MONITOR_OK, 2, &[]));
+        pc_send!(sim, x, monitor_command(MONITOR_PEEK, 1, 3, &[]));
+        pc_expect!(sim, x, received, reply(MONITOR_OK, 3, &[0, 1, 2]));
+        // A corrupted poke is not carried out
+        let mut bad = monitor_command(MONITOR_POKE, 0, 1, &[0xDEAD]);
+        bad[4] ^= 0x01;
+        pc_send!(sim, x, bad);
+        pc_expect!(sim, x, received, reply(MONITOR_BAD_CRC, 0, &[]));
+        // Bad commands are rejected
+        pc_send!(sim, x, monitor_command(MONITOR_PEEK, 1, 0, &[]));
+        pc_expect!(sim, x, received, reply(MONITOR_BAD_COUNT, 0, &[]));
+        pc_send!(sim, x, monitor_command(MONITOR_PEEK, 1, 257, &[]));
+        pc_expect!(sim, x, received, reply(MONITOR_BAD_COUNT, 0, &[]));
+        pc_send!(sim, x, monitor_command(b'X', 1, 1, &[]));
+        pc_expect!(sim, x, received, reply(MONITOR_BAD_OPCODE, 0, &[]));
+        // A port that never becomes ready times out
+        x.port_ready.next = false;
+        pc_send!(
+            sim,
+            x,
+            monitor_command(MONITOR_POKE, 0, 2, &[0xBAD0, 0xBAD1])
+        );
+        pc_expect!(sim, x, received, reply(MONITOR_TIMEOUT, 0, &[]));
+        x.port_ready.next = true;
+        // So does a command that stops halfway
+        pc_send!(sim, x, [MONITOR_PEEK, 1]);
+        pc_expect!(sim, x, received, reply(MONITOR_TIMEOUT, 0, &[]));
+        pc_send!(sim, x, monitor_command(MONITOR_POKE, 0, 1, &[0x5555]));
+        pc_expect!(sim, x, received, reply(MONITOR_OK, 1, &[]));
+        // Stream blocks of 4 words until told to stop
+        pc_send!(sim, x, monitor_command(MONITOR_STREAM, 1, 4, &[]));
+        for block in 0..3 {
+            let words = (0..4).map(|n| 3 + block * 4 + n).collect::<Vec<_>>();
+            pc_expect!(sim, x, received, reply(MONITOR_OK, 4, &words));
+        }
+        pc_send!(sim, x, [0_u8]);
+        wait_clock_cycles!(sim, clock, x, 5000);
+        // The blocks in flight are still complete replies, and then the
+        // monitor goes back to taking commands
+        let tail = received.lock().unwrap().drain(..).collect::<Vec<_>>();
+        sim_assert!(sim, tail.len() % 13 == 0, x);
+        sim_assert!(sim, tail.len() <= 52, x);
+        let next = 15 + (tail.len() / 13) as u16 * 4;
+        pc_send!(sim, x, monitor_command(MONITOR_PEEK, 1, 1, &[]));
+        pc_expect!(sim, x, received, reply(MONITOR_OK, 1, &[next]));
+        finished.store(true, Ordering::SeqCst);
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<UARTMonitorTest>| {
+        let mut x = sim.init()?;
+        for word in [0x7E7D_u16, 0x1234, 0x5555] {
+            x = sim.watch(|x| x.port.strobe_out.val(), x)?;
+            sim_assert_eq!(sim, x.port.port_out.val(), word.to_bits::<16>(), x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("uart_monitor.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,74 @@
+use rust_hdl::prelude::*;
+use std::time::Duration;
+
+#[derive(LogicBlock)]
+struct WatchdogTest {
+    bus: SoCBusController<16, 8>,
+    bridge: Bridge<16, 8, 1>,
+    watchdog: HLSWatchdog<16, 8>,
+}
+
+impl Default for WatchdogTest {
+    fn default() -> Self {
+        Self {
+            bus: Default::default(),
+            bridge: Bridge::new(["watchdog"]),
+            // 50 clock cycles at 1 MHz
+            watchdog: HLSWatchdog::new(1_000_000, Duration::from_micros(50)),
+        }
+    }
+}
+
+impl Logic for WatchdogTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.bridge.upstream);
+        SoCPortController::<16>::join(&mut self.bridge.nodes[0], &mut self.watchdog.bus);
+    }
+}
+
+#[test]
+fn test_hls_watchdog_synthesizes() {
+    let mut uut = WatchdogTest::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_watchdog_test", &vlog).unwrap();
+}
+
+#[test]
+fn test_hls_watchdog_needs_the_host() {
+    let mut uut = WatchdogTest::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<WatchdogTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<WatchdogTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        wait_clock_true!(sim, bus.clock, x);
+        // Until the first kick, the watchdog is not armed
+        wait_clock_cycles!(sim, bus.clock, x, 200);
+        sim_assert!(sim, !x.watchdog.expired.val(), x);
+        // Writing anything but the key does not arm it
+        bus_port_write!(sim, x, bus, 0, [0x1234]);
+        wait_clock_cycles!(sim, bus.clock, x, 200);
+        sim_assert!(sim, !x.watchdog.expired.val(), x);
+        // A host that services it keeps it from expiring
+        for _ in 0..10 {
+            bus_port_write!(sim, x, bus, 0, [WATCHDOG_KICK_KEY]);
+            wait_clock_cycles!(sim, bus.clock, x, 30);
+            sim_assert!(sim, !x.watchdog.expired.val(), x);
+        }
+        // Writing the wrong value is not a kick
+        bus_port_write!(sim, x, bus, 0, [0x1234]);
+        wait_clock_cycles!(sim, bus.clock, x, 30);
+        sim_assert!(sim, x.watchdog.expired.val(), x);
+        bus_port_write!(sim, x, bus, 0, [WATCHDOG_KICK_KEY]);
+        wait_clock_cycles!(sim, bus.clock, x, 2);
+        sim_assert!(sim, !x.watchdog.expired.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("hls_watchdog.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,101 @@
+use rand::Rng;
+use rust_hdl::prelude::*;
+
+// A counter built three times, with the voted count fed back into each
+// copy.  The `upset` inputs are XORed into the next state of each copy,
+// which models a single event upset flipping bits of that register.
+#[derive(LogicBlock, Default)]
+struct TMRCounter {
+    clock: Signal<In, Clock>,
+    upset: [Signal<In, Bits<8>>; 3],
+    count: Signal<Out, Bits<8>>,
+    faulty: Signal<Out, Bits<3>>,
+    copies: [DFF<Bits<8>>; 3],
+    voter: MajorityVoter<8>,
+}
+
+impl Logic for TMRCounter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        for i in 0..3 {
+            self.copies[i].clock.next = self.clock.val();
+            self.copies[i].d.next = (self.voter.data_out.val() + 1) ^ self.upset[i].val();
+        }
+        self.voter.a.next = self.copies[0].q.val();
+        self.voter.b.next = self.copies[1].q.val();
+        self.voter.c.next = self.copies[2].q.val();
+        self.count.next = self.voter.data_out.val();
+        self.faulty.next = self.voter.faulty.val();
+    }
+}
+
+#[test]
+fn test_majority_voter_truth_table() {
+    let mut uut = MajorityVoter::<3>::default();
+    uut.connect_all();
+    yosys_validate("majority_voter_3", &generate_verilog(&uut)).unwrap();
+    for a in 0..8_u64 {
+        for b in 0..8_u64 {
+            for c in 0..8_u64 {
+                uut.a.next = a.to_bits();
+                uut.b.next = b.to_bits();
+                uut.c.next = c.to_bits();
+                simulate(&mut uut, 10);
+                let vote = (a & b) | (b & c) | (a & c);
+                assert_eq!(uut.data_out.val(), vote.to_bits::<3>());
+                let faulty =
+                    ((a != vote) as u64) | ((b != vote) as u64) << 1 | ((c != vote) as u64) << 2;
+                assert_eq!(uut.faulty.val(), faulty.to_bits::<3>());
+                assert_eq!(uut.mismatch.val(), faulty != 0);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tmr_counter_tolerates_single_upsets() {
+    let mut uut = TMRCounter::default();
+    uut.connect_all();
+    yosys_validate("tmr_counter", &generate_verilog(&uut)).unwrap();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TMRCounter>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<TMRCounter>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        wait_clock_cycle!(sim, clock, x);
+        let mut rng = rand::thread_rng();
+        let mut expected = x.count.val().index() as u8;
+        let mut upsets = 0;
+        for _ in 0..1000 {
+            // Upset any number of bits in (at most) one copy on each clock
+            let victim = rng.gen_range(0..4_usize);
+            let mask = rng.gen::<u8>();
+            for i in 0..3 {
+                x.upset[i].next = if i == victim { mask } else { 0 }.to_bits();
+            }
+            wait_clock_cycle!(sim, clock, x);
+            expected = expected.wrapping_add(1);
+            sim_assert_eq!(sim, x.count.val(), expected.to_bits::<8>(), x);
+            // The upset copy is caught by the voter
+            if victim < 3 && mask != 0 {
+                upsets += 1;
+                sim_assert_eq!(sim, x.faulty.val(), (1_u64 << victim).to_bits::<3>(), x);
+            } else {
+                sim_assert_eq!(sim, x.faulty.val(), 0, x);
+            }
+        }
+        sim_assert!(sim, upsets > 500, x);
+        // Upsetting the same bit in two copies defeats the voter, which then
+        // blames the good copy
+        x.upset[0].next = 0x10_u8.to_bits();
+        x.upset[1].next = 0x10_u8.to_bits();
+        x.upset[2].next = 0.into();
+        wait_clock_cycle!(sim, clock, x);
+        expected = expected.wrapping_add(1);
+        sim_assert_eq!(sim, x.count.val(), (expected ^ 0x10).to_bits::<8>(), x);
+        sim_assert_eq!(sim, x.faulty.val(), 0b100, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("tmr_counter.vcd"))
+        .unwrap();
+}
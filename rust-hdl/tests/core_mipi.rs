@@ -0,0 +1,200 @@
+#![cfg(feature = "mipi")]
+use rust_hdl::prelude::*;
+
+// Time for half a bit on the lanes (the system clock has a period of 10)
+const HALF_UI: u64 = 20;
+
+fn make_mipi_receiver<const L: usize>() -> MIPICSI2Receiver<L> {
+    let mut uut = MIPICSI2Receiver::<L>::default();
+    uut.clock_lane.connect();
+    uut.data_lanes.connect();
+    uut.hs_enable.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_mipi_receiver_synthesizes() {
+    let uut = make_mipi_receiver::<4>();
+    yosys_validate("mipi_receiver", &generate_verilog(&uut)).unwrap();
+}
+
+// Send one bit on each lane, with the clock lane edge in the middle of the bit
+fn send_bits<const L: usize>(
+    sim: &mut Sim<MIPICSI2Receiver<L>>,
+    mut x: Box<MIPICSI2Receiver<L>>,
+    bits: u64,
+) -> Result<Box<MIPICSI2Receiver<L>>, SimError> {
+    x.data_lanes.next = bits.to_bits();
+    x = sim.wait(HALF_UI, x)?;
+    x.clock_lane.next = !x.clock_lane.val();
+    sim.wait(HALF_UI, x)
+}
+
+// Send a packet in one high speed burst, with the bytes dealt out to the lanes
+fn send_burst<const L: usize>(
+    sim: &mut Sim<MIPICSI2Receiver<L>>,
+    mut x: Box<MIPICSI2Receiver<L>>,
+    packet: &[u8],
+) -> Result<Box<MIPICSI2Receiver<L>>, SimError> {
+    x.hs_enable.next = true;
+    let mut lane_bytes = vec![vec![0_u8; 2]; L];
+    for lane in lane_bytes.iter_mut() {
+        lane.push(MIPI_SYNC_BYTE);
+    }
+    for (ndx, byte) in packet.iter().enumerate() {
+        lane_bytes[ndx % L].push(*byte);
+    }
+    // The trail is the last bit, inverted, and held until the lanes go back to low power
+    let length = lane_bytes.iter().map(|x| x.len()).max().unwrap() + 2;
+    for lane in lane_bytes.iter_mut() {
+        let trail = if lane.last().unwrap() & 0x80 != 0 {
+            0x00
+        } else {
+            0xFF
+        };
+        lane.resize(length, trail);
+    }
+    for ndx in 0..length {
+        for bit in 0..8 {
+            let mut bits = 0;
+            for (lane, bytes) in lane_bytes.iter().enumerate() {
+                bits |= (((bytes[ndx] >> bit) & 1) as u64) << lane;
+            }
+            x = send_bits(sim, x, bits)?;
+        }
+    }
+    x.hs_enable.next = false;
+    for _ in 0..16 {
+        x = send_bits(sim, x, 0)?;
+    }
+    Ok(x)
+}
+
+fn raw8_line(line: usize) -> Vec<u8> {
+    (0..12).map(|x| (line * 40 + x * 7) as u8).collect()
+}
+
+fn raw10_pixels(line: usize) -> Vec<u16> {
+    (0..8)
+        .map(|x| ((line * 97 + x * 131) & 0x3FF) as u16)
+        .collect()
+}
+
+fn raw10_line(line: usize) -> Vec<u8> {
+    raw10_pixels(line)
+        .chunks(4)
+        .flat_map(|pixels| {
+            let mut bytes: Vec<u8> = pixels.iter().map(|p| (p >> 2) as u8).collect();
+            bytes.push(
+                pixels
+                    .iter()
+                    .enumerate()
+                    .fold(0, |a, (ndx, p)| a | (((p & 3) as u8) << (2 * ndx))),
+            );
+            bytes
+        })
+        .collect()
+}
+
+// A frame with RAW8 and RAW10 lines, a line with a bad checksum, and a packet with a
+// bad header (that should be ignored), all on virtual channel 1
+fn mipi_frame_test<const L: usize>(name: &str) {
+    let uut = make_mipi_receiver::<L>();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<MIPICSI2Receiver<L>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<MIPICSI2Receiver<L>>| {
+        let mut x = sim.init()?;
+        for _ in 0..16 {
+            x = send_bits(&mut sim, x, 0)?;
+        }
+        x = send_burst(&mut sim, x, &mipi_short_packet(1, MIPI_DT_FRAME_START, 7))?;
+        x = send_burst(
+            &mut sim,
+            x,
+            &mipi_long_packet(1, MIPI_DT_RAW8, &raw8_line(0)),
+        )?;
+        x = send_burst(
+            &mut sim,
+            x,
+            &mipi_long_packet(1, MIPI_DT_RAW10, &raw10_line(1)),
+        )?;
+        let mut bad_crc = mipi_long_packet(1, MIPI_DT_RAW8, &raw8_line(2));
+        *bad_crc.last_mut().unwrap() ^= 0x10;
+        x = send_burst(&mut sim, x, &bad_crc)?;
+        let mut bad_header = mipi_long_packet(1, MIPI_DT_RAW8, &raw8_line(3));
+        bad_header[1] ^= 0x04;
+        x = send_burst(&mut sim, x, &bad_header)?;
+        x = send_burst(
+            &mut sim,
+            x,
+            &mipi_long_packet(1, MIPI_DT_RAW8, &raw8_line(4)),
+        )?;
+        x = send_burst(&mut sim, x, &mipi_short_packet(1, MIPI_DT_FRAME_END, 7))?;
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<MIPICSI2Receiver<L>>| {
+        let mut x = sim.init()?;
+        let mut expected = vec![];
+        for line in [0, 1, 2, 4] {
+            let pixels: Vec<u16> = if line == 1 {
+                raw10_pixels(line)
+            } else {
+                raw8_line(line).iter().map(|x| *x as u16).collect()
+            };
+            for (ndx, pixel) in pixels.into_iter().enumerate() {
+                expected.push((pixel, ndx == 0));
+            }
+        }
+        let mut expected = expected.into_iter();
+        let mut frame_started = false;
+        let mut header_errors = 0;
+        let mut crc_errors = 0;
+        loop {
+            wait_clock_cycle!(sim, clock, x);
+            sim_assert!(sim, !x.overflow.val() && !x.lane_error.val(), x);
+            if x.frame_start.val() {
+                frame_started = true;
+            }
+            if x.header_error.val() {
+                header_errors += 1;
+            }
+            if x.crc_error.val() {
+                crc_errors += 1;
+            }
+            if x.pixel_valid.val() {
+                sim_assert!(sim, frame_started, x);
+                sim_assert_eq!(sim, x.virtual_channel.val(), 1, x);
+                let (pixel, start_of_line) = expected.next().unwrap();
+                sim_assert_eq!(sim, x.pixel.val(), pixel.to_bits::<10>(), x);
+                sim_assert_eq!(sim, x.start_of_line.val(), start_of_line, x);
+            }
+            if x.frame_end.val() {
+                break;
+            }
+        }
+        sim_assert!(sim, expected.next().is_none(), x);
+        sim_assert_eq!(sim, header_errors, 1, x);
+        sim_assert_eq!(sim, crc_errors, 1, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!(name))
+        .unwrap();
+}
+
+#[test]
+fn test_mipi_receiver_one_lane() {
+    mipi_frame_test::<1>("mipi_1_lane.vcd");
+}
+
+#[test]
+fn test_mipi_receiver_two_lanes() {
+    mipi_frame_test::<2>("mipi_2_lanes.vcd");
+}
+
+#[test]
+fn test_mipi_receiver_four_lanes() {
+    mipi_frame_test::<4>("mipi_4_lanes.vcd");
+}
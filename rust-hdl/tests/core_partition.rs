@@ -0,0 +1,74 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Domain {
+    pub clock: Signal<In, Clock>,
+    pub count: Signal<Out, Bits<16>>,
+    counter: DFF<Bits<16>>,
+}
+
+impl Logic for Domain {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.count.next = self.counter.q.val();
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct TwoDomains {
+    pub fast_clock: Signal<In, Clock>,
+    pub slow_clock: Signal<In, Clock>,
+    pub fast_count: Signal<Out, Bits<16>>,
+    pub slow_count: Signal<Out, Bits<16>>,
+    #[partition]
+    fast: Domain,
+    #[partition]
+    slow: Domain,
+}
+
+impl Logic for TwoDomains {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.fast.clock.next = self.fast_clock.val();
+        self.slow.clock.next = self.slow_clock.val();
+        self.fast_count.next = self.fast.count.val();
+        self.slow_count.next = self.slow.count.val();
+    }
+}
+
+#[test]
+fn test_partitioned_domains_simulate() {
+    let mut uut = TwoDomains::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TwoDomains>| {
+        x.fast_clock.next = !x.fast_clock.val()
+    });
+    sim.add_clock(35, |x: &mut Box<TwoDomains>| {
+        x.slow_clock.next = !x.slow_clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<TwoDomains>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, slow_clock, x, 100);
+        // The fast clock runs 7 times as often as the slow one.
+        sim_assert_eq!(
+            sim,
+            x.fast_count.val().index(),
+            x.slow_count.val().index() * 7,
+            x
+        );
+        sim_assert_eq!(sim, x.slow_count.val(), 100, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
+
+#[test]
+fn test_partitioned_domains_synthesize() {
+    let mut uut = TwoDomains::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("partition", &vlog).unwrap();
+}
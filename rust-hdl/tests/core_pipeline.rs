@@ -0,0 +1,63 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct PipelinedMultiplier {
+    pub clock: Signal<In, Clock>,
+    pub a: Signal<In, Bits<16>>,
+    pub b: Signal<In, Bits<16>>,
+    pub product: Signal<Out, Bits<32>>,
+    pub direct: Signal<Out, Bits<32>>,
+    stages: Pipeline<Bits<32>, 3>,
+    bypass: Pipeline<Bits<32>, 0>,
+}
+
+impl Logic for PipelinedMultiplier {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, stages, bypass);
+        self.stages.data_in.next = self.a.val() * self.b.val();
+        self.bypass.data_in.next = self.stages.data_in.val();
+        self.product.next = self.stages.data_out.val();
+        self.direct.next = self.bypass.data_out.val();
+    }
+}
+
+#[test]
+fn test_pipeline_synthesizes_with_retiming_attributes() {
+    let mut uut = PipelinedMultiplier::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("retiming_backward = 1"));
+    assert!(vlog.contains("stage_2"));
+    let mut uut: Pipeline<Bits<8>, 2> = Pipeline::new(Retiming::None);
+    uut.connect_all();
+    assert!(!generate_verilog(&uut).contains("retiming"));
+    yosys_validate("pipeline", &vlog).unwrap();
+}
+
+#[test]
+fn test_pipeline_delays_by_its_depth() {
+    let mut uut = PipelinedMultiplier::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<PipelinedMultiplier>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<PipelinedMultiplier>| {
+        let mut x = sim.init()?;
+        let products = (1..20_u64).map(|i| i * 1000 * (i + 7)).collect::<Vec<_>>();
+        for (i, product) in products.iter().enumerate() {
+            x.a.next = (1000 * (i as u64 + 1)).into();
+            x.b.next = (i as u64 + 8).into();
+            wait_clock_cycle!(sim, clock, x);
+            sim_assert_eq!(sim, x.direct.val(), *product, x);
+            // The edge that ends the wait is the first of the three
+            if i >= 2 {
+                sim_assert_eq!(sim, x.product.val(), products[i - 2], x);
+            }
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10000, &vcd_path!("pipeline.vcd"))
+        .unwrap()
+}
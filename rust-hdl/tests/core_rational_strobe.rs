@@ -0,0 +1,53 @@
+use rust_hdl::prelude::*;
+
+// Counts the strobes over `clocks` clock cycles, and checks that the
+// interval between them is always one of the two closest to the ideal.
+fn test_rational_strobe(numerator: u64, denominator: u64, clocks: u64) -> u64 {
+    let mut uut = RationalStrobe::<32>::with_ratio(numerator, denominator);
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<RationalStrobe<32>>| {
+        x.clock.next = !x.clock.val()
+    });
+    let (send, recv) = std::sync::mpsc::channel();
+    sim.add_testbench(move |mut sim: Sim<RationalStrobe<32>>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        x.enable.next = true;
+        let shortest = denominator / numerator;
+        let longest = denominator.div_ceil(numerator);
+        let mut count = 0;
+        let mut last = None;
+        for clock in 0..clocks {
+            wait_clock_false!(sim, clock, x);
+            if x.strobe.val() {
+                if let Some(last) = last {
+                    let interval = clock - last;
+                    sim_assert!(sim, (interval >= shortest) & (interval <= longest), x);
+                }
+                last = Some(clock);
+                count += 1;
+            }
+            wait_clock_true!(sim, clock, x);
+        }
+        send.send(count).unwrap();
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), clocks * 10 + 100).unwrap();
+    recv.recv().unwrap()
+}
+
+#[test]
+fn test_rational_strobe_small_ratio() {
+    assert_eq!(test_rational_strobe(3, 7, 7 * 300), 900);
+}
+
+#[test]
+fn test_rational_strobe_audio_sample_rate() {
+    // 44.1 kHz from a 12.288 MHz clock is 278.64 clocks per sample, which
+    // a Strobe would round to 279 (drifting by 0.13%).
+    assert_eq!(
+        test_rational_strobe(44_100, 12_288_000, 12_288_000 / 100),
+        441
+    );
+}
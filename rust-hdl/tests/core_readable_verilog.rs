@@ -0,0 +1,41 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct TwoCounters {
+    pub clock: Signal<In, Clock>,
+    pub sum: Signal<Out, Bits<8>>,
+    fast: DFF<Bits<8>>,
+    slow: DFF<Bits<8>>,
+}
+
+impl Logic for TwoCounters {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, fast, slow);
+        self.fast.d.next = self.fast.q.val() + 2;
+        self.slow.d.next = self.slow.q.val() + 1;
+        self.sum.next = self.fast.q.val() + self.slow.q.val();
+    }
+}
+
+#[test]
+fn test_readable_verilog() {
+    let mut uut = TwoCounters::default();
+    uut.connect_all();
+    let vlog = generate_verilog_with_options(&uut, VerilogOptions { readable: true });
+    yosys_validate("readable_verilog", &vlog).unwrap();
+    // Each statement points back to its source line
+    let source = format!("// {}:", file!());
+    assert_eq!(vlog.matches(&source).count(), 4);
+    // The stubs are grouped by sub-block
+    assert!(vlog.contains("// -- fast"));
+    assert!(vlog.contains("// -- slow"));
+    // And each instance keeps its own module
+    assert!(vlog.contains("top$fast fast("));
+    assert!(vlog.contains("top$slow slow("));
+    // None of which is present by default
+    let vlog = generate_verilog(&uut);
+    assert!(!vlog.contains(&source));
+    assert!(!vlog.contains("// -- fast"));
+    assert!(!vlog.contains("module top$slow"));
+}
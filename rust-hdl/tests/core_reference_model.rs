@@ -0,0 +1,117 @@
+use rust_hdl::prelude::*;
+
+// Multiplies each strobed input by 3, with one cycle of latency
+#[derive(LogicBlock, Default)]
+struct Tripler {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<16>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub data_out: Signal<Out, Bits<16>>,
+    pub strobe_out: Signal<Out, Bit>,
+    result: DFF<Bits<16>>,
+    valid: DFF<Bit>,
+}
+
+impl Logic for Tripler {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, result, valid);
+        self.valid.d.next = self.strobe_in.val();
+        if self.strobe_in.val() {
+            self.result.d.next = self.data_in.val() + (self.data_in.val() << 1);
+        }
+        self.data_out.next = self.result.q.val();
+        self.strobe_out.next = self.valid.q.val();
+    }
+}
+
+struct TriplerModel {
+    // Introduce a bug for inputs larger than this
+    broken_above: u64,
+}
+
+impl ReferenceModel for TriplerModel {
+    type Stimulus = u64;
+    type Response = u64;
+
+    fn name(&self) -> String {
+        "tripler".into()
+    }
+
+    fn consume(&mut self, stimulus: u64) -> Vec<u64> {
+        if stimulus > self.broken_above {
+            vec![stimulus * 3 + 1]
+        } else {
+            vec![stimulus * 3]
+        }
+    }
+}
+
+fn run_tripler(model: TriplerModel) -> Result<(), SimError> {
+    run_tripler_dropping(model, None)
+}
+
+// Runs the tripler, but loses any output with the given value on the way out
+fn run_tripler_dropping(model: TriplerModel, dropped: Option<u64>) -> Result<(), SimError> {
+    let mut uut = Tripler::default();
+    uut.connect_all();
+    let mut sim = test_harness!(Tripler, clocks: [clock => 100_000_000]);
+    sim.add_reference_model(
+        model,
+        |x| x.clock.val().clk,
+        |x| x.strobe_in.val().then(|| x.data_in.val().index() as u64),
+        move |x| {
+            let output = x.data_out.val().index() as u64;
+            (x.strobe_out.val() && Some(output) != dropped).then_some(output)
+        },
+    );
+    sim.add_testbench(move |mut sim: Sim<Tripler>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        for i in 0..100_u64 {
+            x.data_in.next = (i * 7).to_bits();
+            x.strobe_in.next = i % 3 != 0;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        x.strobe_in.next = false;
+        wait_clock_cycles!(sim, clock, x, 4);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000_000)
+}
+
+#[test]
+fn test_reference_model_matches() {
+    run_tripler(TriplerModel { broken_above: !0 }).unwrap();
+}
+
+#[test]
+fn test_reference_model_reports_first_divergence() {
+    match run_tripler(TriplerModel { broken_above: 300 }) {
+        Err(SimError::ProtocolViolation {
+            monitor, message, ..
+        }) => {
+            assert_eq!(monitor, "tripler");
+            // 301 is the first strobed input above 300 (43 * 7)
+            assert!(message.contains("was 903 but the model expected 904"));
+            assert!(message.contains("recent stimulus"));
+        }
+        x => panic!("Expected a divergence, got {:?}", x),
+    }
+}
+
+#[test]
+fn test_reference_model_reports_missing_responses() {
+    // The last strobed input is 98 * 7 = 686
+    match run_tripler_dropping(TriplerModel { broken_above: !0 }, Some(2058)) {
+        Err(SimError::ProtocolViolation {
+            monitor, message, ..
+        }) => {
+            assert_eq!(monitor, "tripler");
+            assert!(
+                message.contains("never produced 1 of the expected responses, the first was 2058")
+            );
+        }
+        x => panic!("Expected a missing response, got {:?}", x),
+    }
+}
@@ -0,0 +1,123 @@
+use rust_hdl::prelude::*;
+use std::time::Duration;
+
+type ResetTest = ResetController<2, 8>;
+
+// At 1 MHz, a hold of 20 clocks, and a debounce of 10 clocks, with an
+// active low button.
+fn make_controller() -> ResetTest {
+    let mut uut = ResetTest::new(
+        1_000_000,
+        Duration::from_micros(20),
+        Duration::from_micros(10),
+        true,
+    );
+    uut.button.connect();
+    uut.pll_locked.connect();
+    uut.watchdog_expired.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_reset_controller_sources() {
+    let uut = make_controller();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<ResetTest>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<ResetTest>| {
+        let mut x = sim.init()?;
+        x.button.next = true;
+        x.pll_locked.next = true;
+        // Power on reset
+        wait_clock_cycles!(sim, clock, x, 15);
+        sim_assert!(sim, x.reset.val(), x);
+        wait_clock_cycles!(sim, clock, x, 10);
+        sim_assert!(sim, !x.reset.val(), x);
+        // A bouncing button is ignored
+        for _ in 0..5 {
+            x.button.next = false;
+            wait_clock_cycles!(sim, clock, x, 4);
+            x.button.next = true;
+            wait_clock_cycles!(sim, clock, x, 2);
+        }
+        wait_clock_cycles!(sim, clock, x, 20);
+        sim_assert!(sim, !x.reset.val(), x);
+        // A held button resets, until the hold time after it is released
+        x.button.next = false;
+        wait_clock_cycles!(sim, clock, x, 20);
+        sim_assert!(sim, x.reset.val(), x);
+        x.button.next = true;
+        wait_clock_cycles!(sim, clock, x, 30);
+        sim_assert!(sim, x.reset.val(), x);
+        wait_clock_cycles!(sim, clock, x, 10);
+        sim_assert!(sim, !x.reset.val(), x);
+        // Losing PLL lock resets immediately
+        x.pll_locked.next = false;
+        wait_clock_cycles!(sim, clock, x, 2);
+        sim_assert!(sim, x.reset.val(), x);
+        x.pll_locked.next = true;
+        wait_clock_cycles!(sim, clock, x, 15);
+        sim_assert!(sim, x.reset.val(), x);
+        wait_clock_cycles!(sim, clock, x, 10);
+        sim_assert!(sim, !x.reset.val(), x);
+        // As does the watchdog
+        x.watchdog_expired.next = true;
+        wait_clock_cycles!(sim, clock, x, 2);
+        sim_assert!(sim, x.reset.val(), x);
+        x.watchdog_expired.next = false;
+        wait_clock_cycles!(sim, clock, x, 25);
+        sim_assert!(sim, !x.reset.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10_000, &vcd_path!("reset_controller.vcd"))
+        .unwrap();
+}
+
+#[test]
+fn test_reset_controller_domains() {
+    let uut = make_controller();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<ResetTest>| x.clock.next = !x.clock.val());
+    sim.add_clock(3, |x: &mut Box<ResetTest>| {
+        x.domain_clocks[0].next = !x.domain_clocks[0].val()
+    });
+    sim.add_clock(17, |x: &mut Box<ResetTest>| {
+        x.domain_clocks[1].next = !x.domain_clocks[1].val()
+    });
+    sim.add_testbench(move |mut sim: Sim<ResetTest>| {
+        let mut x = sim.init()?;
+        x.button.next = true;
+        x.pll_locked.next = true;
+        wait_clock_cycles!(sim, clock, x, 10);
+        x = sim.watch(|x| !x.reset.val(), x)?;
+        sim.done(x)
+    });
+    // Each domain reset is released on the second edge of its own clock
+    // after the main one
+    for domain in 0..2 {
+        sim.add_testbench(move |mut sim: Sim<ResetTest>| {
+            let mut x = sim.init()?;
+            x = sim.watch(move |x| x.domain_clocks[domain].val().clk, x)?;
+            sim_assert!(sim, x.domain_resets[domain].val(), x);
+            x = sim.watch(|x| !x.reset.val(), x)?;
+            sim_assert!(sim, x.domain_resets[domain].val(), x);
+            for edge in 0..2 {
+                x = sim.watch(move |x| !x.domain_clocks[domain].val().clk, x)?;
+                sim_assert!(sim, x.domain_resets[domain].val(), x);
+                x = sim.watch(move |x| x.domain_clocks[domain].val().clk, x)?;
+                if edge == 0 {
+                    sim_assert!(sim, x.domain_resets[domain].val(), x);
+                }
+            }
+            x = sim.watch(move |x| !x.domain_clocks[domain].val().clk, x)?;
+            sim_assert!(sim, !x.domain_resets[domain].val(), x);
+            sim.done(x)
+        });
+    }
+    sim.run_to_file(
+        Box::new(uut),
+        10_000,
+        &vcd_path!("reset_controller_domains.vcd"),
+    )
+    .unwrap();
+}
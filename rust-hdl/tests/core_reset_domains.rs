@@ -0,0 +1,189 @@
+use rust_hdl::core::check_error::CheckError;
+use rust_hdl::core::check_reset_domains::{check_reset_domains, ResetCrossing};
+use rust_hdl::prelude::*;
+
+// A counter in each of two clock domains, each reset by its own synchronizer
+#[derive(LogicBlock, Default)]
+struct TwoDomains {
+    pub fast_clock: Signal<In, Clock>,
+    pub slow_clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub fast_count: Signal<Out, Bits<8>>,
+    pub slow_count: Signal<Out, Bits<8>>,
+    fast_reset: ResetSynchronizer,
+    slow_reset: ResetSynchronizer,
+    fast_counter: DFF<Bits<8>>,
+    slow_counter: DFF<Bits<8>>,
+}
+
+impl Logic for TwoDomains {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, fast_clock, fast_reset, fast_counter);
+        clock!(self, slow_clock, slow_reset, slow_counter);
+        self.fast_reset.reset_in.next = self.reset.val();
+        self.slow_reset.reset_in.next = self.reset.val();
+        self.fast_counter.d.next = self.fast_counter.q.val() + 1;
+        if self.fast_reset.reset_out.val() {
+            self.fast_counter.d.next = 0.into();
+        }
+        self.slow_counter.d.next = self.slow_counter.q.val() + 1;
+        if self.slow_reset.reset_out.val() {
+            self.slow_counter.d.next = 0.into();
+        }
+        self.fast_count.next = self.fast_counter.q.val();
+        self.slow_count.next = self.slow_counter.q.val();
+    }
+}
+
+// The slow counter is (wrongly) reset by the reset of the fast domain
+#[derive(LogicBlock, Default)]
+struct CrossedReset {
+    pub fast_clock: Signal<In, Clock>,
+    pub slow_clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub slow_count: Signal<Out, Bits<8>>,
+    fast_reset: ResetSynchronizer,
+    slow_counter: DFF<Bits<8>>,
+}
+
+impl Logic for CrossedReset {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, fast_clock, fast_reset);
+        clock!(self, slow_clock, slow_counter);
+        self.fast_reset.reset_in.next = self.reset.val();
+        self.slow_counter.d.next = self.slow_counter.q.val() + 1;
+        if self.fast_reset.reset_out.val() {
+            self.slow_counter.d.next = 0.into();
+        }
+        self.slow_count.next = self.slow_counter.q.val();
+    }
+}
+
+// The counter samples the asynchronous reset directly, even though there
+// is a synchronizer for its domain
+#[derive(LogicBlock, Default)]
+struct RawReset {
+    pub clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub count: Signal<Out, Bits<8>>,
+    pub domain_reset: Signal<Out, Bit>,
+    sync: ResetSynchronizer,
+    counter: DFF<Bits<8>>,
+}
+
+impl Logic for RawReset {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, sync, counter);
+        self.sync.reset_in.next = self.reset.val();
+        self.domain_reset.next = self.sync.reset_out.val();
+        self.counter.d.next = self.counter.q.val() + 1;
+        if self.reset.val() {
+            self.counter.d.next = 0.into();
+        }
+        self.count.next = self.counter.q.val();
+    }
+}
+
+fn crossings(result: Result<(), CheckError>) -> Vec<ResetCrossing> {
+    match result {
+        Err(CheckError::ResetDomainCrossings(x)) => x,
+        x => panic!("Expected reset domain crossings, got {:?}", x),
+    }
+}
+
+#[test]
+fn test_synchronized_resets_pass_the_check() {
+    let mut uut = TwoDomains::default();
+    uut.connect_all();
+    assert!(check_reset_domains(&uut).is_ok());
+}
+
+#[test]
+fn test_reset_from_another_domain_is_reported() {
+    let mut uut = CrossedReset::default();
+    uut.connect_all();
+    let crossings = crossings(check_reset_domains(&uut));
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].reset, "uut$fast_reset$reset_out");
+    assert_eq!(crossings[0].reset_domain.as_deref(), Some("uut$fast_clock"));
+    assert_eq!(crossings[0].sampled_by.path, "uut$slow_counter");
+    assert_eq!(crossings[0].sampled_by.name, "d");
+    assert_eq!(crossings[0].domain, "uut$slow_clock");
+    assert_eq!(
+        crossings[0].to_string(),
+        "reset uut$fast_reset$reset_out (released in uut$fast_clock) is sampled by \
+         uut$slow_counter$d in uut$slow_clock"
+    );
+}
+
+#[test]
+fn test_asynchronous_reset_sampled_directly_is_reported() {
+    let mut uut = RawReset::default();
+    uut.connect_all();
+    let crossings = crossings(check_reset_domains(&uut));
+    assert_eq!(crossings.len(), 1);
+    assert_eq!(crossings[0].reset, "uut$reset");
+    assert_eq!(crossings[0].reset_domain, None);
+    assert_eq!(crossings[0].sampled_by.path, "uut$counter");
+    assert!(crossings[0]
+        .to_string()
+        .starts_with("asynchronous reset uut$reset is sampled by"));
+}
+
+#[test]
+fn test_reset_synchronizer_asserts_at_once_and_releases_on_the_clock() {
+    let mut uut = TwoDomains::default();
+    uut.reset.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TwoDomains>| {
+        x.fast_clock.next = !x.fast_clock.val()
+    });
+    sim.add_clock(35, |x: &mut Box<TwoDomains>| {
+        x.slow_clock.next = !x.slow_clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<TwoDomains>| {
+        let mut x = sim.init()?;
+        // The resets are asserted at power up, and released two clocks later
+        sim_assert!(sim, x.fast_reset.reset_out.val(), x);
+        wait_clock_cycles!(sim, fast_clock, x, 2);
+        sim_assert!(sim, !x.fast_reset.reset_out.val(), x);
+        wait_clock_cycles!(sim, slow_clock, x, 2);
+        sim_assert!(sim, !x.slow_reset.reset_out.val(), x);
+        wait_clock_cycle!(sim, slow_clock, x);
+        sim_assert!(sim, x.slow_count.val() != 0, x);
+        // Asserting the reset takes effect straight away, without a clock
+        x.reset.next = true;
+        x = sim.wait(1, x)?;
+        sim_assert!(sim, x.fast_reset.reset_out.val(), x);
+        sim_assert!(sim, x.slow_reset.reset_out.val(), x);
+        wait_clock_cycles!(sim, slow_clock, x, 2);
+        sim_assert_eq!(sim, x.slow_count.val(), 0, x);
+        // The release takes two clocks in each domain
+        x.reset.next = false;
+        wait_clock_cycle!(sim, fast_clock, x);
+        sim_assert!(sim, x.fast_reset.reset_out.val(), x);
+        wait_clock_cycle!(sim, fast_clock, x);
+        sim_assert!(sim, !x.fast_reset.reset_out.val(), x);
+        sim_assert!(sim, x.slow_reset.reset_out.val(), x);
+        x = sim.watch(|x| !x.slow_reset.reset_out.val(), x)?;
+        sim_assert_eq!(sim, x.slow_count.val(), 0, x);
+        wait_clock_cycles!(sim, slow_clock, x, 2);
+        sim_assert!(sim, x.slow_count.val() != 0, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
+
+#[test]
+fn test_reset_synchronizers_synthesize() {
+    let mut uut = TwoDomains::default();
+    uut.reset.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("always @(posedge clock or posedge reset_in)"));
+    yosys_validate("reset_domains", &vlog).unwrap();
+}
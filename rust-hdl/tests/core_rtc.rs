@@ -0,0 +1,67 @@
+use rust_hdl::prelude::*;
+
+#[test]
+fn test_rtc_counts_seconds() {
+    // A second is 100 clock cycles
+    let mut uut = RealTimeClock::<8>::new(100);
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<RealTimeClock<8>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<RealTimeClock<8>>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        x.set_seconds.next = 1000.into();
+        x.set.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.set.next = false;
+        sim_assert_eq!(sim, x.seconds.val(), 1000, x);
+        sim_assert_eq!(sim, x.ticks.val(), 0, x);
+        let mut pulses = 0;
+        for _ in 0..350 {
+            if x.pps.val() {
+                pulses += 1;
+            }
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim_assert_eq!(sim, pulses, 3, x);
+        sim_assert_eq!(sim, x.seconds.val(), 1003, x);
+        sim_assert_eq!(sim, x.ticks.val(), 50, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("rtc.vcd"))
+        .unwrap();
+}
+
+#[test]
+fn test_timestamp_counter_captures() {
+    let mut uut = TimestampCounter::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TimestampCounter>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<TimestampCounter>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        // Capture at known intervals, and check the differences
+        let mut last: Option<(u64, u64)> = None;
+        for gap in [7_u64, 30, 1, 5] {
+            x.capture.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.capture.next = false;
+            sim_assert!(sim, x.captured_strobe.val(), x);
+            let captured = x.captured.val().to_u64();
+            sim_assert!(sim, x.timestamp.val().to_u64() > captured, x);
+            if let Some((previous, previous_gap)) = last {
+                sim_assert_eq!(sim, captured - previous, previous_gap, x);
+            }
+            last = Some((captured, gap));
+            wait_clock_cycles!(sim, clock, x, gap - 1);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("timestamp.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,246 @@
+use rust_hdl::prelude::*;
+use std::sync::{Arc, Mutex};
+
+// Adds one to the data of each tagged word.  Words with even tags take the
+// fast lane (1 clock), and words with odd tags take the slow lane (3 clocks),
+// so the outputs come out of order.
+#[derive(LogicBlock, Default)]
+struct TwoLanes {
+    pub clock: Signal<In, Clock>,
+    pub tag_in: Signal<In, Bits<4>>,
+    pub data_in: Signal<In, Bits<8>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub fast_tag: Signal<Out, Bits<4>>,
+    pub fast_data: Signal<Out, Bits<8>>,
+    pub fast_strobe: Signal<Out, Bit>,
+    pub slow_tag: Signal<Out, Bits<4>>,
+    pub slow_data: Signal<Out, Bits<8>>,
+    pub slow_strobe: Signal<Out, Bit>,
+    fast_valid: DFF<Bit>,
+    fast_word: DFF<Bits<12>>,
+    slow_valid_0: DFF<Bit>,
+    slow_valid_1: DFF<Bit>,
+    slow_valid_2: DFF<Bit>,
+    slow_word_0: DFF<Bits<12>>,
+    slow_word_1: DFF<Bits<12>>,
+    slow_word_2: DFF<Bits<12>>,
+}
+
+impl Logic for TwoLanes {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            fast_valid,
+            fast_word,
+            slow_valid_0,
+            slow_valid_1,
+            slow_valid_2,
+            slow_word_0,
+            slow_word_1,
+            slow_word_2
+        );
+        self.fast_valid.d.next = self.strobe_in.val() & !self.tag_in.val().get_bit(0);
+        self.fast_word.d.next =
+            (bit_cast::<12, 4>(self.tag_in.val()) << 8) | bit_cast::<12, 8>(self.data_in.val() + 1);
+        self.slow_valid_0.d.next = self.strobe_in.val() & self.tag_in.val().get_bit(0);
+        self.slow_valid_1.d.next = self.slow_valid_0.q.val();
+        self.slow_valid_2.d.next = self.slow_valid_1.q.val();
+        self.slow_word_0.d.next = self.fast_word.d.val();
+        self.slow_word_1.d.next = self.slow_word_0.q.val();
+        self.slow_word_2.d.next = self.slow_word_1.q.val();
+        self.fast_strobe.next = self.fast_valid.q.val();
+        self.fast_tag.next = self.fast_word.q.val().get_bits::<4>(8);
+        self.fast_data.next = self.fast_word.q.val().get_bits::<8>(0);
+        self.slow_strobe.next = self.slow_valid_2.q.val();
+        self.slow_tag.next = self.slow_word_2.q.val().get_bits::<4>(8);
+        self.slow_data.next = self.slow_word_2.q.val().get_bits::<8>(0);
+    }
+}
+
+fn make_two_lanes() -> TwoLanes {
+    let mut uut = TwoLanes::default();
+    uut.tag_in.connect();
+    uut.data_in.connect();
+    uut.strobe_in.connect();
+    uut.connect_all();
+    uut
+}
+
+fn outputs(x: &TwoLanes) -> Vec<(u64, u64)> {
+    let mut ret = vec![];
+    if x.fast_strobe.val() {
+        ret.push((
+            x.fast_tag.val().index() as u64,
+            x.fast_data.val().index() as u64,
+        ));
+    }
+    if x.slow_strobe.val() {
+        ret.push((
+            x.slow_tag.val().index() as u64,
+            x.slow_data.val().index() as u64,
+        ));
+    }
+    ret
+}
+
+// Feeds `count` words into the lanes, reusing the tags, with an idle clock
+// after every fifth word
+fn add_driver(sim: &mut Simulation<TwoLanes>, count: u64) {
+    sim.add_clock(5, |x: &mut Box<TwoLanes>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<TwoLanes>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 2);
+        for ndx in 0..count {
+            x.tag_in.next = (ndx % 16).to_bits();
+            x.data_in.next = ((ndx * 37) & 0xFF).to_bits();
+            x.strobe_in.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.strobe_in.next = false;
+            if ndx % 5 == 4 {
+                wait_clock_cycle!(sim, clock, x);
+            }
+        }
+        wait_clock_cycles!(sim, clock, x, 10);
+        sim.done(x)
+    });
+}
+
+fn run_with_model<F: Fn(u64, u64) -> Option<u64> + 'static>(
+    count: u64,
+    model: F,
+) -> (Scoreboard<u64, u64>, Result<(), SimError>) {
+    let scoreboard = Scoreboard::new("two_lanes");
+    let mut sim = Simulation::new();
+    add_driver(&mut sim, count);
+    sim.add_scoreboard(
+        scoreboard.clone(),
+        |x: &TwoLanes| x.clock.val().clk,
+        move |x: &TwoLanes| {
+            let tag = x.tag_in.val().index() as u64;
+            let data = x.data_in.val().index() as u64;
+            match model(tag, data) {
+                Some(result) if x.strobe_in.val() => vec![(tag, result)],
+                _ => vec![],
+            }
+        },
+        outputs,
+    );
+    sim.set_log_quiet(true);
+    let result = sim.run(Box::new(make_two_lanes()), 10_000);
+    (scoreboard, result)
+}
+
+#[test]
+fn test_scoreboard_matches_out_of_order_outputs() {
+    let (scoreboard, result) = run_with_model(40, |_, data| Some((data + 1) & 0xFF));
+    assert!(result.is_ok());
+    assert!(scoreboard.finish().is_ok());
+    assert_eq!(scoreboard.matched(), 40);
+    // The words from the fast lane overtake the ones in the slow lane
+    assert!(scoreboard.max_overtaken() > 0);
+}
+
+#[test]
+fn test_scoreboard_catches_wrong_values() {
+    let (_, result) = run_with_model(40, |tag, data| {
+        Some((data + if tag == 5 { 2 } else { 1 }) & 0xFF)
+    });
+    match result {
+        Err(SimError::ProtocolViolation {
+            monitor, message, ..
+        }) => {
+            assert_eq!(monitor, "two_lanes");
+            // The first word with tag 5 is 5*37 = 0xB9
+            assert!(message.contains("observed 186 with key 5, but expected 187"));
+        }
+        x => panic!("Expected a violation, got {:?}", x),
+    }
+}
+
+#[test]
+fn test_scoreboard_catches_unexpected_and_missing_outputs() {
+    // Nothing is expected with tag 3, so the circuit should not produce it
+    let (scoreboard, result) =
+        run_with_model(40, |tag, data| (tag != 3).then_some((data + 1) & 0xFF));
+    match result {
+        Err(SimError::ProtocolViolation { message, .. }) => {
+            assert!(message.contains("with key 3, but nothing was expected"))
+        }
+        x => panic!("Expected a violation, got {:?}", x),
+    }
+    // And a word that is expected but never produced is reported at the end
+    let scoreboard_missing = Scoreboard::new("missing");
+    scoreboard_missing.expect(7_u64, 1_u64);
+    scoreboard_missing.expect(2, 3);
+    scoreboard_missing.expect(7, 2);
+    assert!(scoreboard_missing.observe(7, 1).is_ok());
+    assert_eq!(scoreboard_missing.outstanding(), vec![(2, 3), (7, 2)]);
+    let message = scoreboard_missing.finish().unwrap_err();
+    assert!(message.contains("2 transactions were never observed (1 matched)"));
+    assert!(scoreboard.matched() > 0);
+}
+
+#[test]
+fn test_scoreboard_keeps_the_order_within_a_key() {
+    let scoreboard = Scoreboard::new("reused");
+    for value in 0..4_u32 {
+        scoreboard.expect("a", value);
+        scoreboard.expect("b", value + 10);
+    }
+    // All of b overtakes a, but each key is in order
+    for value in 0..4 {
+        assert!(scoreboard.observe("b", value + 10).is_ok());
+    }
+    assert!(scoreboard.observe("a", 1).is_err());
+    for value in 1..4 {
+        assert!(scoreboard.observe("a", value).is_ok());
+    }
+    assert!(scoreboard.finish().is_ok());
+    assert_eq!(scoreboard.matched(), 7);
+    assert_eq!(scoreboard.max_overtaken(), 4);
+}
+
+#[test]
+fn test_scoreboard_shared_by_testbenches() {
+    // One testbench records what it drives, and another checks what comes out
+    let scoreboard = Scoreboard::new("testbenches");
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TwoLanes>| x.clock.next = !x.clock.val());
+    let expected = scoreboard.clone();
+    sim.add_testbench(move |mut sim: Sim<TwoLanes>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 2);
+        for ndx in 0..20_u64 {
+            x.tag_in.next = (ndx % 16).to_bits();
+            x.data_in.next = ndx.to_bits();
+            x.strobe_in.next = true;
+            expected.expect(ndx % 16, ndx + 1);
+            wait_clock_cycle!(sim, clock, x);
+            x.strobe_in.next = false;
+        }
+        sim.done(x)
+    });
+    let observed = scoreboard.clone();
+    let counts = Arc::new(Mutex::new(0));
+    let seen = counts.clone();
+    sim.add_testbench(move |mut sim: Sim<TwoLanes>| {
+        let mut x = sim.init()?;
+        for _ in 0..30 {
+            wait_clock_true!(sim, clock, x);
+            for (tag, data) in outputs(&x) {
+                if let Err(message) = observed.observe(tag, data) {
+                    return sim.fail(message, x);
+                }
+                *seen.lock().unwrap() += 1;
+            }
+            wait_clock_false!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.set_log_quiet(true);
+    sim.run(Box::new(make_two_lanes()), 10_000).unwrap();
+    assert!(scoreboard.finish().is_ok());
+    assert_eq!(*counts.lock().unwrap(), 20);
+}
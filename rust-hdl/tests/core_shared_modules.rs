@@ -0,0 +1,160 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Inverter {
+    pub sig_in: Signal<In, Bit>,
+    pub sig_out: Signal<Out, Bit>,
+}
+
+impl Logic for Inverter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.sig_out.next = !self.sig_in.val();
+    }
+}
+
+// A vendor buffer, declared as a black box
+#[derive(LogicBlock, Default)]
+struct VendorBuffer {
+    pub i: Signal<In, Bit>,
+    pub o: Signal<Out, Bit>,
+}
+
+impl Logic for VendorBuffer {
+    fn update(&mut self) {}
+
+    fn connect(&mut self) {
+        self.o.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Blackbox(BlackBox {
+            code: r#"
+(* blackbox *)
+module VENDOR_BUF(input i, output o);
+endmodule
+"#
+            .into(),
+            name: "VENDOR_BUF".into(),
+        })
+    }
+}
+
+// A vendor delay line, with a parameter that RustHDL does not know about
+#[derive(LogicBlock, Default)]
+struct VendorDelay {
+    pub sig_in: Signal<In, Bit>,
+    pub sig_out: Signal<Out, Bit>,
+}
+
+impl Logic for VendorDelay {
+    fn update(&mut self) {}
+
+    fn connect(&mut self) {
+        self.sig_out.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r#"
+VENDOR_DELAY #(.TAPS(3)) delay_inst(.I(sig_in), .O(sig_out));
+"#
+            .into(),
+            cores: r#"
+(* blackbox *)
+module VENDOR_DELAY(input I, output O);
+parameter TAPS = 1;
+endmodule
+"#
+            .into(),
+        })
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct DelayedInverter {
+    pub sig_in: Signal<In, Bit>,
+    pub sig_out: Signal<Out, Bit>,
+    delay: VendorDelay,
+    inv: Inverter,
+}
+
+impl Logic for DelayedInverter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.delay.sig_in.next = self.sig_in.val();
+        self.inv.sig_in.next = self.delay.sig_out.val();
+        self.sig_out.next = self.inv.sig_out.val();
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct Chain {
+    pub sig_in: Signal<In, Bit>,
+    pub sig_out: Signal<Out, Bit>,
+    inv_1: Inverter,
+    inv_2: Inverter,
+    buf_1: VendorBuffer,
+    buf_2: VendorBuffer,
+    delay_1: VendorDelay,
+    delay_2: VendorDelay,
+    stage_1: DelayedInverter,
+    stage_2: DelayedInverter,
+}
+
+impl Logic for Chain {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.inv_1.sig_in.next = self.sig_in.val();
+        self.inv_2.sig_in.next = self.inv_1.sig_out.val();
+        self.buf_1.i.next = self.inv_2.sig_out.val();
+        self.buf_2.i.next = self.buf_1.o.val();
+        self.delay_1.sig_in.next = self.buf_2.o.val();
+        self.delay_2.sig_in.next = self.delay_1.sig_out.val();
+        self.stage_1.sig_in.next = self.delay_2.sig_out.val();
+        self.stage_2.sig_in.next = self.stage_1.sig_out.val();
+        self.sig_out.next = self.stage_2.sig_out.val();
+    }
+}
+
+fn chain_verilog() -> String {
+    let mut uut = Chain::default();
+    uut.sig_in.connect();
+    uut.connect_all();
+    generate_verilog(&uut)
+}
+
+#[test]
+fn test_identical_modules_are_emitted_once() {
+    let vlog = chain_verilog();
+    // All four inverters share the module of the first one found
+    assert!(vlog.contains("top$stage_1$inv inv_1"));
+    assert!(vlog.contains("top$stage_1$inv inv_2"));
+    assert!(vlog.contains("top$stage_1$inv inv("));
+    assert!(!vlog.contains("module top$inv_1"));
+    assert!(!vlog.contains("module top$inv_2"));
+    assert!(!vlog.contains("module top$stage_2$inv"));
+}
+
+#[test]
+fn test_black_boxes_are_declared_once() {
+    let vlog = chain_verilog();
+    assert!(vlog.contains("VENDOR_BUF buf_1"));
+    assert!(vlog.contains("VENDOR_BUF buf_2"));
+    assert_eq!(vlog.matches("module VENDOR_BUF").count(), 1);
+}
+
+#[test]
+fn test_wrappers_are_never_merged() {
+    let vlog = chain_verilog();
+    // Each wrapper keeps its own module, even though the Verilog is the same
+    assert!(vlog.contains("module top$delay_1"));
+    assert!(vlog.contains("module top$delay_2"));
+    assert!(vlog.contains("top$delay_2 delay_2"));
+    // and so do the blocks that contain them
+    assert!(vlog.contains("module top$stage_1$delay"));
+    assert!(vlog.contains("module top$stage_2$delay"));
+    assert!(vlog.contains("top$stage_2 stage_2"));
+    // The cores are declared once
+    assert_eq!(vlog.matches("module VENDOR_DELAY").count(), 1);
+}
@@ -0,0 +1,89 @@
+use rust_hdl::prelude::*;
+
+// A stand-in for a vendor PLL primitive, which has no simulation model
+#[derive(LogicBlock, Default)]
+struct VendorPLL {
+    pub clock_in: Signal<In, Clock>,
+    pub clock_out: Signal<Out, Clock>,
+    pub locked: Signal<Out, Bit>,
+}
+
+impl Logic for VendorPLL {
+    fn update(&mut self) {}
+
+    fn connect(&mut self) {
+        self.clock_out.connect();
+        self.locked.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r#"
+VENDOR_PLL #(.MULT(4)) pll_inst(.CLKIN(clock_in), .CLKOUT(clock_out), .LOCKED(locked));
+"#
+            .into(),
+            cores: r#"
+(* blackbox *)
+module VENDOR_PLL(input CLKIN, output CLKOUT, output LOCKED);
+parameter MULT = 1;
+endmodule
+"#
+            .into(),
+        })
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct PLLCounter {
+    pub clock: Signal<In, Clock>,
+    pub count: Signal<Out, Bits<16>>,
+    pll: VendorPLL,
+    counter: DFF<Bits<16>>,
+}
+
+impl Logic for PLLCounter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pll.clock_in.next = self.clock.val();
+        self.counter.clock.next = self.pll.clock_out.val();
+        self.counter.d.next = self.counter.q.val();
+        if self.pll.locked.val() {
+            self.counter.d.next = self.counter.q.val() + 1;
+        }
+        self.count.next = self.counter.q.val();
+    }
+}
+
+#[test]
+fn test_pll_counter_is_synthesizable() {
+    let mut uut = PLLCounter::default();
+    uut.connect_all();
+    yosys_validate("pll_behavior", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_behavior_models_a_wrapped_pll() {
+    let mut uut = PLLCounter::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(20, |x: &mut Box<PLLCounter>| x.clock.next = !x.clock.val());
+    // The PLL multiplies the clock by 4, and locks at 1000 ps
+    sim.add_clock(5, |x: &mut Box<PLLCounter>| {
+        x.pll.clock_out.next = !x.pll.clock_out.val()
+    });
+    sim.add_behavior(
+        |x: &mut PLLCounter| &mut x.pll,
+        |pll, time| pll.locked.next = time >= 1000,
+    );
+    sim.add_testbench(move |mut sim: Sim<PLLCounter>| {
+        let mut x = sim.init()?;
+        x = sim.wait(1000, x)?;
+        sim_assert_eq!(sim, x.count.val(), 0, x);
+        // 40 input clocks are 160 PLL clocks
+        x = sim.wait(1600, x)?;
+        sim_assert_eq!(sim, x.count.val(), 160, x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10_000, &vcd_path!("pll_behavior.vcd"))
+        .unwrap();
+}
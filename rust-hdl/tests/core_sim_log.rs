@@ -0,0 +1,145 @@
+use rust_hdl::prelude::*;
+
+// Counts clocks, and talks about it
+#[derive(LogicBlock, Default)]
+struct Chatty {
+    pub clock: Signal<In, Clock>,
+    pub count: Signal<Out, Bits<8>>,
+    counter: DFF<Bits<8>>,
+}
+
+impl Logic for Chatty {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.count.next = self.counter.q.val();
+        sim_log!(self, Debug, "count {}", self.counter.q.val().index());
+        if self.counter.q.val() == 3 {
+            sim_log!(self, Warn, "The count is {}", 3);
+        }
+    }
+}
+
+fn run_chatty<F: Fn(&mut Simulation<Chatty>)>(setup: F) -> (Vec<LogRecord>, Result<(), SimError>) {
+    let mut uut = Chatty::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Chatty>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Chatty>| {
+        let mut x = sim.init()?;
+        sim_log!("testbench", Info, "start");
+        wait_clock_cycles!(sim, clock, x, 6);
+        sim_log!("testbench", Info, "count is {}", x.count.val().index());
+        sim_assert_eq!(sim, x.count.val(), 6, x);
+        sim.done(x)
+    });
+    setup(&mut sim);
+    let result = sim.run(Box::new(uut), 1000);
+    (sim.log_records(), result)
+}
+
+fn messages(records: &[LogRecord]) -> Vec<(u64, String, String)> {
+    records
+        .iter()
+        .map(|x| (x.time, x.source.clone(), x.message.clone()))
+        .collect()
+}
+
+#[test]
+fn test_log_level_and_timestamps() {
+    let (records, result) = run_chatty(|sim| sim.set_log_quiet(true));
+    assert!(result.is_ok());
+    // The clock rises at 5, 15, 25...  The warning is logged once, even
+    // though the count is 3 for two time steps
+    assert_eq!(
+        messages(&records),
+        vec![
+            (0, "testbench".into(), "start".into()),
+            (25, "Chatty".into(), "The count is 3".into()),
+            (60, "testbench".into(), "count is 6".into()),
+        ]
+    );
+    assert_eq!(records[1].level, LogLevel::Warn);
+    assert_eq!(records[1].to_string(), "@25 WARN  Chatty: The count is 3");
+}
+
+#[test]
+fn test_log_per_block() {
+    let (records, _) = run_chatty(|sim| {
+        sim.set_log_quiet(true);
+        sim.set_log_level(LogLevel::Error);
+        sim.set_block_log_level("Chatty", Some(LogLevel::Debug));
+    });
+    // Each count is logged once, when it changes
+    let counts = records
+        .iter()
+        .filter(|x| x.level == LogLevel::Debug)
+        .map(|x| (x.time, x.message.clone()))
+        .collect::<Vec<_>>();
+    assert_eq!(counts[0], (0, "count 0".into()));
+    assert_eq!(counts[1], (5, "count 1".into()));
+    assert_eq!(counts[3], (25, "count 3".into()));
+    assert!(records.iter().all(|x| x.source == "Chatty"));
+    // And a block can be silenced
+    let (records, _) = run_chatty(|sim| {
+        sim.set_log_quiet(true);
+        sim.set_block_log_level("Chatty", None);
+    });
+    assert!(records.iter().all(|x| x.source == "testbench"));
+}
+
+#[test]
+fn test_log_repeat_limit() {
+    let (records, _) = run_chatty(|sim| {
+        sim.set_log_quiet(true);
+        sim.set_log_level(LogLevel::Debug);
+        sim.set_log_repeat_limit(2);
+    });
+    let chatty = records
+        .iter()
+        .filter(|x| x.source == "Chatty")
+        .map(|x| x.message.clone())
+        .collect::<Vec<_>>();
+    assert_eq!(chatty.len(), 4);
+    assert_eq!(chatty[0], "count 0");
+    assert_eq!(chatty[1], "count 1");
+    assert_eq!(chatty[2], "The count is 3");
+    assert!(chatty[3].starts_with("5 more messages from"));
+    assert_eq!(records.last().unwrap().level, LogLevel::Warn);
+}
+
+#[test]
+fn test_failed_assertions_are_logged() {
+    let mut uut = Chatty::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Chatty>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Chatty>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 4);
+        sim_assert_eq!(sim, x.count.val(), 3, x);
+        sim.done(x)
+    });
+    sim.set_log_quiet(true);
+    let result = sim.run(Box::new(uut), 1000);
+    let failure = sim
+        .log_records()
+        .into_iter()
+        .find(|x| x.source == "sim_assert")
+        .unwrap();
+    assert_eq!(failure.level, LogLevel::Error);
+    assert!(failure.message.starts_with("HALT x.count.val() != 3"));
+    match result {
+        Err(SimError::AssertionFailed { time, .. }) => assert_eq!(time, failure.time),
+        x => panic!("Expected a failed assertion, got {:?}", x),
+    }
+}
+
+#[test]
+fn test_log_is_a_comment_in_hdl() {
+    let mut uut = Chatty::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("Warn: The count is {}"));
+}
@@ -0,0 +1,72 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Stage {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<8>>,
+    pub data_out: Signal<Out, Bits<8>>,
+    hold: DFF<Bits<8>>,
+}
+
+impl Logic for Stage {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, hold);
+        self.hold.d.next = self.data_in.val() + 1;
+        self.data_out.next = self.hold.q.val();
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct Pipeline {
+    pub clock: Signal<In, Clock>,
+    pub data_out: Signal<Out, Bits<8>>,
+    stages: [Stage; 3],
+}
+
+impl Logic for Pipeline {
+    #[hdl_gen]
+    fn update(&mut self) {
+        for i in 0..3 {
+            self.stages[i].clock.next = self.clock.val();
+        }
+        self.stages[0].data_in.next = 0.into();
+        self.stages[1].data_in.next = self.stages[0].data_out.val();
+        self.stages[2].data_in.next = self.stages[1].data_out.val();
+        self.data_out.next = self.stages[2].data_out.val();
+    }
+}
+
+#[test]
+fn test_profile_lists_every_block() {
+    let mut uut = Pipeline::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.set_profiling(true);
+    sim.add_clock(5, |x: &mut Box<Pipeline>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Pipeline>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        sim_assert_eq!(sim, x.data_out.val(), 3, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1000).unwrap();
+    let profile = sim.profile().unwrap();
+    let entries = profile.entries();
+    let paths = entries.iter().map(|x| x.path.as_str()).collect::<Vec<_>>();
+    for path in ["uut", "uut.stages$0", "uut.stages$2", "uut.stages$1.hold"] {
+        assert!(
+            paths.contains(&path),
+            "{} is missing from {:?}",
+            path,
+            paths
+        );
+    }
+    assert_eq!(entries.len(), 7);
+    // Every block is updated the same number of times
+    assert!(entries[0].calls > 0);
+    assert!(entries.iter().all(|x| x.calls == entries[0].calls));
+    // With the most expensive first
+    assert!(entries.windows(2).all(|x| x[0].time >= x[1].time));
+    assert!(profile.to_string().contains("uut.stages$1.hold"));
+}
@@ -0,0 +1,149 @@
+use num_traits::ToPrimitive;
+use rust_hdl::prelude::*;
+
+const PHASE: usize = 12;
+const TABLE: usize = 7;
+const AMP: usize = 12;
+
+type SinCosTest = SinCosROM<PHASE, TABLE, AMP>;
+
+// Software model of the ROM, for bit exact comparison
+fn model_sin(phase: i64, interpolate: bool) -> i64 {
+    let table = quarter_wave_table::<TABLE, AMP>();
+    let frac_bits = PHASE - TABLE - 2;
+    let size = 1_i64 << TABLE;
+    let lookup = |wave: i64| -> i64 {
+        let wave = wave.rem_euclid(4 * size);
+        let ndx = wave % size;
+        let val = if (wave / size) % 2 == 1 {
+            table[(size - 1 - ndx) as usize]
+        } else {
+            table[ndx as usize]
+        };
+        if wave >= 2 * size {
+            -val
+        } else {
+            val
+        }
+    };
+    if !interpolate {
+        return lookup(phase >> frac_bits);
+    }
+    let phase = phase - (1 << (frac_bits - 1));
+    let wave = phase.rem_euclid(1 << PHASE) >> frac_bits;
+    let frac = phase & ((1 << frac_bits) - 1);
+    let a = lookup(wave);
+    let b = lookup(wave + 1);
+    a + (((b - a) * frac + (1 << (frac_bits - 1))) >> frac_bits)
+}
+
+// Total harmonic distortion (in dB) of one period of a sampled sinusoid
+fn thd_db(samples: &[f64]) -> f64 {
+    let n = samples.len() as f64;
+    let power = |k: usize| {
+        let (re, im) = samples
+            .iter()
+            .enumerate()
+            .fold((0.0, 0.0), |(re, im), (ndx, y)| {
+                let angle = 2.0 * std::f64::consts::PI * (k * ndx) as f64 / n;
+                (re + y * angle.cos(), im - y * angle.sin())
+            });
+        re * re + im * im
+    };
+    let harmonics: f64 = (2..=16).map(power).sum();
+    10.0 * (harmonics / power(1)).log10()
+}
+
+fn test_sin_cos_rom(interpolate: bool, name: &str) {
+    let mut uut = SinCosTest::new(interpolate);
+    uut.connect_all();
+    yosys_validate(name, &generate_verilog(&uut)).unwrap();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SinCosTest>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<SinCosTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        for phase in 0..(1_u64 << PHASE) {
+            x.phase_in.next = phase.to_bits();
+            x.strobe_in.next = true;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        x.strobe_in.next = false;
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<SinCosTest>| {
+        let mut x = sim.init()?;
+        let mut sines = vec![];
+        let mut cosines = vec![];
+        while sines.len() < (1 << PHASE) {
+            wait_clock_true!(sim, clock, x);
+            if x.strobe_out.val() {
+                let phase = sines.len() as i64;
+                let sin = x.sin_out.val().bigint().to_i64().unwrap();
+                let cos = x.cos_out.val().bigint().to_i64().unwrap();
+                sim_assert_eq!(sim, sin, model_sin(phase, interpolate), x);
+                sim_assert_eq!(
+                    sim,
+                    cos,
+                    model_sin(phase + (1 << (PHASE - 2)), interpolate),
+                    x
+                );
+                sines.push(sin as f64);
+                cosines.push(cos as f64);
+            }
+            wait_clock_cycle!(sim, clock, x);
+        }
+        // Compare against an ideal sinusoid rounded to the same number of bits
+        let amplitude = ((1 << (AMP - 1)) - 1) as f64;
+        let reference = (0..(1 << PHASE))
+            .map(|ndx| {
+                let angle = 2.0 * std::f64::consts::PI * ndx as f64 / (1 << PHASE) as f64;
+                (angle.sin() * amplitude).round()
+            })
+            .collect::<Vec<_>>();
+        let thd_reference = thd_db(&reference);
+        let thd_sin = thd_db(&sines);
+        let thd_cos = thd_db(&cosines);
+        println!(
+            "THD reference {:.1} dB, sin {:.1} dB, cos {:.1} dB",
+            thd_reference, thd_sin, thd_cos
+        );
+        sim_assert!(sim, thd_sin < thd_reference + 15.0, x);
+        sim_assert!(sim, thd_cos < thd_reference + 15.0, x);
+        // Half an LSB each from rounding the table and the output, plus the error of the
+        // chord between table points.  Without interpolation, the phase error can be up
+        // to half a table step.
+        let step = 2.0 * std::f64::consts::PI / (4 << TABLE) as f64;
+        let max_error = if interpolate {
+            1.0 + amplitude * step * step / 8.0
+        } else {
+            1.0 + amplitude * step / 2.0
+        };
+        for ndx in 0..(1 << PHASE) {
+            let angle = 2.0 * std::f64::consts::PI * ndx as f64 / (1 << PHASE) as f64;
+            sim_assert!(
+                sim,
+                (sines[ndx] - angle.sin() * amplitude).abs() <= max_error,
+                x
+            );
+            sim_assert!(
+                sim,
+                (cosines[ndx] - angle.cos() * amplitude).abs() <= max_error,
+                x
+            );
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 200_000, &vcd_path!(format!("{}.vcd", name)))
+        .unwrap();
+}
+
+#[test]
+fn test_sin_cos_rom_interpolated() {
+    test_sin_cos_rom(true, "sincos_interp");
+}
+
+#[test]
+fn test_sin_cos_rom_table_only() {
+    test_sin_cos_rom(false, "sincos_table");
+}
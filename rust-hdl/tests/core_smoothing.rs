@@ -0,0 +1,144 @@
+use num_traits::ToPrimitive;
+use rust_hdl::prelude::*;
+
+const DATA: usize = 12;
+const LOG_LENGTH: usize = 3;
+const WIDTH: usize = DATA + LOG_LENGTH;
+
+type BoxcarTest = MovingAverage<DATA, LOG_LENGTH, WIDTH>;
+type IIRTest = ExponentialSmoother<DATA, LOG_LENGTH, WIDTH>;
+
+// A noisy step from near the bottom of the range to near the top and back again,
+// so that the filters are exercised over the full range of the input.
+fn make_samples() -> Vec<i64> {
+    let mut state = 0x1234_u32;
+    let max = (1_i64 << (DATA - 1)) - 1;
+    (0..300)
+        .map(|ndx| {
+            state = state.wrapping_mul(1103515245).wrapping_add(12345);
+            let noise = ((state >> 16) % 256) as i64 - 128;
+            let level = if (ndx / 100) % 2 == 1 {
+                max - 128
+            } else {
+                128 - max
+            };
+            level + noise
+        })
+        .collect()
+}
+
+fn round_shift(x: i64, shift: usize, round: bool) -> i64 {
+    if round {
+        (x + (1 << (shift - 1))) >> shift
+    } else {
+        x >> shift
+    }
+}
+
+fn model_boxcar(samples: &[i64], round: bool) -> Vec<i64> {
+    let length = 1 << LOG_LENGTH;
+    (0..samples.len())
+        .map(|ndx| {
+            let sum: i64 = samples[ndx.saturating_sub(length - 1)..=ndx].iter().sum();
+            round_shift(sum, LOG_LENGTH, round)
+        })
+        .collect()
+}
+
+fn model_iir(samples: &[i64], round: bool) -> Vec<i64> {
+    let mut state = 0_i64;
+    samples
+        .iter()
+        .map(|x| {
+            state += x - (state >> LOG_LENGTH);
+            round_shift(state, LOG_LENGTH, round)
+        })
+        .collect()
+}
+
+// Macro, since the two filters have the same interface but no common trait
+macro_rules! test_smoothing_filter {
+    ($uut: ty, $uut_new: expr, $model: ident, $round: expr, $name: expr) => {{
+        let round = $round;
+        let mut uut: $uut = $uut_new;
+        uut.connect_all();
+        let samples = make_samples();
+        let expected = $model(&samples, round);
+        let mut sim = Simulation::new();
+        sim.add_clock(5, |x: &mut Box<$uut>| x.clock.next = !x.clock.val());
+        sim.add_testbench(move |mut sim: Sim<$uut>| {
+            let mut x = sim.init()?;
+            wait_clock_true!(sim, clock, x);
+            // Mostly back to back, with an occasional gap
+            for (ndx, sample) in samples.iter().enumerate() {
+                x.data_in.next = sample.to_signed_bits();
+                x.strobe_in.next = true;
+                wait_clock_cycle!(sim, clock, x);
+                if ndx % 7 == 3 {
+                    x.strobe_in.next = false;
+                    wait_clock_cycles!(sim, clock, x, ndx % 3 + 1);
+                }
+            }
+            x.strobe_in.next = false;
+            sim.done(x)
+        });
+        sim.add_testbench(move |mut sim: Sim<$uut>| {
+            let mut x = sim.init()?;
+            let mut count = 0;
+            while count < expected.len() {
+                wait_clock_true!(sim, clock, x);
+                if x.strobe_out.val() {
+                    sim_assert_eq!(
+                        sim,
+                        x.data_out.val().bigint().to_i64().unwrap(),
+                        expected[count],
+                        x
+                    );
+                    count += 1;
+                }
+                wait_clock_cycle!(sim, clock, x);
+            }
+            sim.done(x)
+        });
+        sim.run_to_file(Box::new(uut), 20_000, &vcd_path!(format!("{}.vcd", $name)))
+            .unwrap();
+    }};
+}
+
+#[test]
+fn test_moving_average_rounded() {
+    test_smoothing_filter!(
+        BoxcarTest,
+        BoxcarTest::new(true),
+        model_boxcar,
+        true,
+        "moving_average_rounded"
+    );
+}
+
+#[test]
+fn test_moving_average_truncated() {
+    test_smoothing_filter!(
+        BoxcarTest,
+        BoxcarTest::new(false),
+        model_boxcar,
+        false,
+        "moving_average_truncated"
+    );
+}
+
+#[test]
+fn test_exponential_smoother_rounded() {
+    test_smoothing_filter!(IIRTest, IIRTest::new(true), model_iir, true, "iir_rounded");
+}
+
+#[test]
+fn test_exponential_smoother_truncated() {
+    test_smoothing_filter!(
+        IIRTest,
+        IIRTest::new(false),
+        model_iir,
+        false,
+        "iir_truncated"
+    );
+}
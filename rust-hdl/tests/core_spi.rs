@@ -237,3 +237,99 @@ fn test_spi_xchange(config: SPIConfig, name: &str) {
     .unwrap();
     //sim.run(Box::new(uut), 1_000_000).unwrap();
 }
+
+#[derive(LogicBlock)]
+struct SPITestDynamicBaud {
+    clock: Signal<In, Clock>,
+    master: SPIMasterDynamicBaud<32>,
+    slave: SPISlave<32>,
+}
+
+impl SPITestDynamicBaud {
+    pub fn new(config: SPIConfig) -> Self {
+        Self {
+            clock: Default::default(),
+            master: SPIMasterDynamicBaud::new(config),
+            slave: SPISlave::new(SPIConfig {
+                speed_hz: 200_000,
+                ..config
+            }),
+        }
+    }
+}
+
+impl Logic for SPITestDynamicBaud {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, master, slave);
+        SPIWiresMaster::join(&mut self.master.wires, &mut self.slave.wires);
+    }
+}
+
+#[test]
+fn test_spi_dynamic_baud() {
+    let mut uut = SPITestDynamicBaud::new(mk_spi_config([true, false, true, false]));
+    uut.master.continued_transaction.connect();
+    uut.master.start_send.connect();
+    uut.master.data_outbound.connect();
+    uut.master.bits_outbound.connect();
+    uut.master.divisor.connect();
+    uut.slave.data_outbound.connect();
+    uut.slave.start_send.connect();
+    uut.slave.continued_transaction.connect();
+    uut.slave.disabled.connect();
+    uut.slave.bits.connect();
+    uut.connect_all();
+    yosys_validate("spi_dynamic_baud", &generate_verilog(&uut)).unwrap();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SPITestDynamicBaud>| {
+        x.clock.next = !x.clock.val()
+    });
+    // The configured speed (a divisor of 40), 4 times slower, the configured
+    // speed again with a new divisor arriving mid-transfer, and then about as
+    // fast as the slave can follow.  The master strobes every quarter
+    // of the divisor.
+    sim.add_testbench(move |mut sim: Sim<SPITestDynamicBaud>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 16);
+        for (divisor, late_divisor, quarter) in
+            [(0, 0, 10), (160, 160, 40), (0, 80, 10), (32, 32, 8)]
+        {
+            wait_clock_true!(sim, clock, x);
+            x.master.divisor.next = (divisor as u16).to_bits();
+            wait_clock_cycle!(sim, clock, x);
+            x.master.data_outbound.next = 0xDEADBEEF.into();
+            x.master.bits_outbound.next = 32.into();
+            x.master.start_send.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.master.start_send.next = false;
+            x.master.divisor.next = (late_divisor as u16).to_bits();
+            let mut elapsed = 1;
+            while !x.master.transfer_done.val() {
+                wait_clock_cycle!(sim, clock, x);
+                elapsed += 1;
+            }
+            // 2 strobes for each of the 32 bits, plus a dwell and a finish
+            sim_assert_eq!(sim, elapsed, 66 * quarter + 1, x);
+            sim_assert_eq!(sim, x.master.data_inbound.val(), 0xCAFEBABE_u64, x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<SPITestDynamicBaud>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 16);
+        for _ in 0..4 {
+            wait_clock_true!(sim, clock, x);
+            x.slave.data_outbound.next = 0xCAFEBABE.into();
+            x.slave.bits.next = 32.into();
+            x.slave.start_send.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x = sim.watch(|x| x.slave.transfer_done.val().into(), x)?;
+            sim_assert_eq!(sim, x.slave.data_inbound.val(), 0xDEADBEEF_u64, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("spi_dynamic_baud.vcd"))
+        .unwrap();
+}
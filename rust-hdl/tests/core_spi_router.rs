@@ -0,0 +1,143 @@
+use rust_hdl::prelude::*;
+
+// An SPIRouter with two targets.  Target 0 takes its mode from the
+// transaction, target 1 is slower, and is pinned to mode 3.
+#[derive(LogicBlock)]
+struct SPIRouterTest {
+    clock: Signal<In, Clock>,
+    router: SPIRouter<32, 2>,
+    slaves: [SPISlave<32>; 2],
+}
+
+fn slave_config(speed_hz: u64, cs_off: bool, cpha: bool, cpol: bool) -> SPIConfig {
+    SPIConfig {
+        clock_speed: 48_000_000,
+        cs_off,
+        mosi_off: false,
+        speed_hz,
+        cpha,
+        cpol,
+    }
+}
+
+impl Default for SPIRouterTest {
+    fn default() -> Self {
+        let fast = SPIRouterTarget {
+            cs_off: true,
+            mosi_off: false,
+            speed_hz: 1_000_000,
+            fixed_mode: false,
+            cpha: false,
+            cpol: false,
+        };
+        let slow = SPIRouterTarget {
+            cs_off: false,
+            mosi_off: false,
+            speed_hz: 400_000,
+            fixed_mode: true,
+            cpha: true,
+            cpol: true,
+        };
+        Self {
+            clock: Default::default(),
+            router: SPIRouter::new(48_000_000, [fast, slow]),
+            slaves: [
+                SPISlave::new(slave_config(1_000_000, true, false, true)),
+                SPISlave::new(slave_config(400_000, false, true, true)),
+            ],
+        }
+    }
+}
+
+impl Logic for SPIRouterTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, router);
+        for i in 0..2 {
+            self.slaves[i].clock.next = self.clock.val();
+            SPIWiresMaster::join(&mut self.router.wires[i], &mut self.slaves[i].wires);
+        }
+    }
+}
+
+fn make_spi_router_test() -> SPIRouterTest {
+    let mut uut = SPIRouterTest::default();
+    uut.router.bits_outbound.connect();
+    uut.router.data_outbound.connect();
+    uut.router.start_send.connect();
+    uut.router.continued_transaction.connect();
+    for i in 0..2 {
+        uut.slaves[i].data_outbound.connect();
+        uut.slaves[i].start_send.connect();
+        uut.slaves[i].continued_transaction.connect();
+        uut.slaves[i].disabled.connect();
+        uut.slaves[i].bits.connect();
+    }
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_spi_router_test_synthesizes() {
+    let uut = make_spi_router_test();
+    yosys_validate("spi_router_test", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_spi_router_addresses_targets() {
+    let uut = make_spi_router_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SPIRouterTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<SPIRouterTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 16);
+        // Target 0 in mode 1 (cpol set), then target 1 with no mode bits
+        // (its override applies), twice over
+        for (bits, word, reply) in [
+            (0x0120_u16, 0xDEADBEEF_u32, 0xCAFEBABE_u32),
+            (0x0420, 0x12345678, 0x8765_4321),
+            (0x0120, 0xDEADBEEF, 0xCAFEBABE),
+            (0x0420, 0x12345678, 0x8765_4321),
+        ] {
+            wait_clock_true!(sim, clock, x);
+            x.router.data_outbound.next = word.to_bits();
+            x.router.bits_outbound.next = bits.to_bits();
+            x.router.start_send.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.router.start_send.next = false;
+            x = sim.watch(|x| x.router.transfer_done.val(), x)?;
+            sim_assert_eq!(sim, x.router.data_inbound.val(), reply.to_bits::<32>(), x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    for (target, expect, reply) in [
+        (0, 0xDEADBEEF_u32, 0xCAFEBABE_u32),
+        (1, 0x12345678, 0x8765_4321),
+    ] {
+        sim.add_testbench(move |mut sim: Sim<SPIRouterTest>| {
+            let mut x = sim.init()?;
+            wait_clock_cycles!(sim, clock, x, 16);
+            for _ in 0..2 {
+                wait_clock_true!(sim, clock, x);
+                x.slaves[target].data_outbound.next = reply.to_bits();
+                x.slaves[target].bits.next = 32.into();
+                x.slaves[target].start_send.next = true;
+                wait_clock_cycle!(sim, clock, x);
+                x.slaves[target].start_send.next = false;
+                x = sim.watch(move |x| x.slaves[target].transfer_done.val(), x)?;
+                sim_assert_eq!(
+                    sim,
+                    x.slaves[target].data_inbound.val(),
+                    expect.to_bits::<32>(),
+                    x
+                );
+            }
+            sim.done(x)
+        });
+    }
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("spi_router.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,135 @@
+use rust_hdl::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, LogicState)]
+enum LoaderState {
+    Idle,
+    Load,
+    Shift,
+    Done,
+}
+
+#[derive(LogicBlock, Default)]
+struct Loader {
+    pub clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub start: Signal<In, Bit>,
+    pub busy: Signal<Out, Bit>,
+    state: DFF<LoaderState>,
+    count: DFF<Bits<4>>,
+}
+
+impl Logic for Loader {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state, count);
+        self.busy.next = true;
+        match self.state.q.val() {
+            LoaderState::Idle => {
+                self.busy.next = false;
+                if self.start.val() {
+                    self.state.d.next = LoaderState::Load;
+                }
+            }
+            LoaderState::Load => {
+                self.count.d.next = 0.into();
+                self.state.d.next = LoaderState::Shift;
+            }
+            LoaderState::Shift => {
+                self.count.d.next = self.count.q.val() + 1;
+                if self.count.q.val() == 7 {
+                    self.state.d.next = LoaderState::Done;
+                }
+            }
+            LoaderState::Done => {
+                self.state.d.next = LoaderState::Idle;
+            }
+            _ => {
+                self.state.d.next = LoaderState::Idle;
+            }
+        }
+        if self.reset.val() {
+            self.state.d.next = LoaderState::Idle;
+        }
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct TwoLoaders {
+    pub clock: Signal<In, Clock>,
+    left: Loader,
+    right: Loader,
+}
+
+impl Logic for TwoLoaders {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, left, right);
+        self.left.reset.next = false;
+        self.left.start.next = true;
+        self.right.reset.next = false;
+        self.right.start.next = self.left.busy.val();
+    }
+}
+
+#[test]
+fn test_extract_state_machine() {
+    let mut uut = TwoLoaders::default();
+    uut.connect_all();
+    let machines = extract_state_machines(&uut);
+    assert_eq!(machines.len(), 2);
+    assert_eq!(machines[0].path, "uut$left");
+    assert_eq!(machines[1].path, "uut$right");
+    let fsm = &machines[0];
+    assert_eq!(fsm.register, "state");
+    assert_eq!(fsm.states, vec!["Idle", "Load", "Shift", "Done"]);
+    let edge = |from: Option<&str>, to: &str, condition: &str| StateTransition {
+        from: from.map(|x| x.to_string()),
+        to: to.to_string(),
+        condition: condition.to_string(),
+    };
+    assert_eq!(
+        fsm.transitions,
+        vec![
+            edge(Some("Idle"), "Load", "start"),
+            edge(Some("Load"), "Shift", ""),
+            edge(Some("Shift"), "Done", "count$q == 32'h7"),
+            edge(Some("Done"), "Idle", ""),
+            edge(None, "Idle", ""),
+            edge(None, "Idle", "reset"),
+        ]
+    );
+}
+
+#[test]
+fn test_state_machine_diagrams() {
+    let mut uut = Loader::default();
+    uut.connect_all();
+    let fsm = &extract_state_machines(&uut)[0];
+    let dot = fsm.dot();
+    assert!(dot.starts_with("digraph \"uut$state\" {"));
+    assert!(dot.contains("\"Idle\" [shape=doublecircle];"));
+    assert!(dot.contains("\"Idle\" -> \"Load\" [label=\"start\"];"));
+    assert!(dot.contains("\"*\" -> \"Idle\" [label=\"reset\"];"));
+    let mermaid = fsm.mermaid();
+    assert!(mermaid.starts_with("stateDiagram-v2\n    [*] --> Idle\n"));
+    assert!(mermaid.contains("    Shift --> Done : count$q == 32'h7\n"));
+    assert!(mermaid.contains("    Load --> Shift\n"));
+    assert!(mermaid.contains("    any_state --> Idle : reset\n"));
+}
+
+#[test]
+fn test_spi_slave_state_machine() {
+    let mut uut = SPISlave::<64>::new(SPIConfig {
+        clock_speed: 48_000_000,
+        cs_off: true,
+        mosi_off: true,
+        speed_hz: 1_000_000,
+        cpha: true,
+        cpol: false,
+    });
+    uut.connect_all();
+    let machines = extract_state_machines(&uut);
+    assert_eq!(machines.len(), 1);
+    assert_eq!(machines[0].states.len(), 10);
+    assert!(machines[0].transitions.len() > 10);
+}
@@ -0,0 +1,153 @@
+use rust_hdl::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, LogicState)]
+enum Mode {
+    Add,
+    Sub,
+}
+
+#[derive(LogicBlock, Default)]
+struct Accumulator {
+    pub clock: Signal<In, Clock>,
+    pub enable: Signal<In, Bit>,
+    pub mode: Signal<In, Mode>,
+    pub data: Signal<In, Bits<8>>,
+    pub sum: Signal<Out, Bits<16>>,
+    total: DFF<Bits<16>>,
+}
+
+impl Logic for Accumulator {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, total);
+        if self.enable.val() {
+            match self.mode.val() {
+                Mode::Add => {
+                    self.total.d.next = self.total.q.val() + bit_cast::<16, 8>(self.data.val())
+                }
+                Mode::Sub => {
+                    self.total.d.next = self.total.q.val() - bit_cast::<16, 8>(self.data.val())
+                }
+            }
+        }
+        self.sum.next = self.total.q.val();
+    }
+}
+
+// The same accumulator, but with the enable renamed
+#[derive(LogicBlock, Default)]
+struct RenamedAccumulator {
+    pub clock: Signal<In, Clock>,
+    pub strobe: Signal<In, Bit>,
+    pub mode: Signal<In, Mode>,
+    pub data: Signal<In, Bits<8>>,
+    pub sum: Signal<Out, Bits<16>>,
+}
+
+impl Logic for RenamedAccumulator {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.sum.next = 0.into();
+    }
+}
+
+const CYCLES: u64 = 40;
+
+fn expected_sum() -> u64 {
+    let mut sum = 0_u64;
+    for i in 0..CYCLES {
+        if i % 3 != 0 {
+            if i % 4 == 0 {
+                sum = sum.wrapping_sub(i * 5);
+            } else {
+                sum = sum.wrapping_add(i * 5);
+            }
+        }
+    }
+    sum & 0xFFFF
+}
+
+fn recorded_stimulus(name: &str) -> String {
+    let filename = vcd_path!(name);
+    let mut uut = Accumulator::default();
+    uut.connect_all();
+    let mut sim = test_harness!(Accumulator, clocks: [clock => 100_000_000]);
+    sim.add_testbench(move |mut sim: Sim<Accumulator>| {
+        let mut x = sim.init()?;
+        for i in 0..CYCLES {
+            x.enable.next = i % 3 != 0;
+            x.mode.next = if i % 4 == 0 { Mode::Sub } else { Mode::Add };
+            x.data.next = (i * 5).to_bits();
+            wait_clock_cycle!(sim, clock, x);
+        }
+        x.enable.next = false;
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert_eq!(sim, x.sum.val(), expected_sum(), x);
+        sim.done(x)
+    });
+    sim.record_stimulus(&filename);
+    sim.run(Box::new(uut), 1_000_000).unwrap();
+    filename
+}
+
+#[test]
+fn test_stimulus_is_recorded() {
+    let filename = recorded_stimulus("stimulus_recorded.txt");
+    let stimulus = Stimulus::load(&filename).unwrap();
+    let first = &stimulus.steps()[0];
+    assert_eq!(first.time, 0);
+    assert!(first.changes.iter().any(|x| x.0 == "mode"));
+    // The clock toggles every 5ns, and is recorded with everything else
+    assert!(stimulus
+        .steps()
+        .iter()
+        .any(|x| x.time == 5_000 && x.changes.iter().any(|x| x.0 == "clock")));
+    assert!(stimulus
+        .steps()
+        .iter()
+        .all(|x| x.changes.iter().all(|x| x.0 != "sum")));
+    assert_eq!(Stimulus::parse(&stimulus.to_string()), Some(stimulus));
+}
+
+#[test]
+fn test_stimulus_replays_without_testbench() {
+    let filename = recorded_stimulus("stimulus_replay.txt");
+    let stimulus = Stimulus::load(&filename).unwrap();
+    let end = stimulus.steps().last().unwrap().time;
+    let mut uut = Accumulator::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_stimulus(stimulus);
+    sim.add_testbench(move |mut sim: Sim<Accumulator>| {
+        let mut x = sim.init()?;
+        x = sim.wait(end + 1, x)?;
+        sim_assert_eq!(sim, x.sum.val(), expected_sum(), x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000_000).unwrap();
+}
+
+#[test]
+fn test_stimulus_replay_reports_missing_inputs() {
+    let filename = recorded_stimulus("stimulus_missing.txt");
+    let mut uut = RenamedAccumulator::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_stimulus(Stimulus::load(&filename).unwrap());
+    match sim.run(Box::new(uut), 1_000_000) {
+        Err(SimError::AssertionFailed { message, .. }) => {
+            assert!(message.contains("enable"));
+        }
+        x => panic!("Expected the replay to fail, got {:?}", x),
+    }
+}
+
+#[test]
+fn test_stimulus_parse_values() {
+    let text = "#0\nclock 0\ndata b0101\nmode sSub\npair {b01,1}\n#10\nclock 1\n";
+    let stimulus = Stimulus::parse(text).unwrap();
+    assert_eq!(stimulus.steps().len(), 2);
+    assert_eq!(stimulus.to_string(), text);
+    assert!(Stimulus::parse("clock 0\n").is_none());
+    assert!(Stimulus::parse("#0\ndata b012\n").is_none());
+}
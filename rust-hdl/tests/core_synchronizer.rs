@@ -0,0 +1,105 @@
+use rust_hdl::prelude::*;
+use std::sync::{Arc, Mutex};
+
+// Toggle the input of a synchronizer a number of times, and record how many
+// clocks each change took to reach the output
+fn synchronizer_latencies<const STAGES: usize>(toggles: usize) -> Vec<u64> {
+    let latencies = Arc::new(Mutex::new(vec![]));
+    let mut uut = BitSynchronizer::<STAGES>::default();
+    uut.sig_in.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<BitSynchronizer<STAGES>>| {
+        x.clock.next = !x.clock.val()
+    });
+    let record = latencies.clone();
+    sim.add_testbench(move |mut sim: Sim<BitSynchronizer<STAGES>>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 4);
+        let mut level = false;
+        for _ in 0..toggles {
+            level = !level;
+            x.sig_in.next = level;
+            let mut count = 0;
+            while x.sig_out.val() != level {
+                wait_clock_cycle!(sim, clock, x);
+                count += 1;
+            }
+            record.lock().unwrap().push(count);
+            wait_clock_cycles!(sim, clock, x, 2);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000_000).unwrap();
+    let latencies = latencies.lock().unwrap().clone();
+    latencies
+}
+
+#[test]
+fn test_synchronizer_latency_matches_stages() {
+    assert!(synchronizer_latencies::<2>(10).iter().all(|x| *x == 2));
+    assert!(synchronizer_latencies::<3>(10).iter().all(|x| *x == 3));
+    assert!(synchronizer_latencies::<5>(10).iter().all(|x| *x == 5));
+}
+
+#[test]
+fn test_metastability_delays_some_changes() {
+    set_metastability_model(Some(MetastabilityModel {
+        probability: 0.5,
+        seed: 0x1234_5678,
+    }));
+    let latencies = synchronizer_latencies::<3>(100);
+    set_metastability_model(None);
+    // A missed capture is caught at the next clock, so a change is late by at most one clock
+    assert!(latencies.iter().all(|x| *x == 3 || *x == 4));
+    let late = latencies.iter().filter(|x| **x == 4).count();
+    assert!(late > 25 && late < 75);
+}
+
+#[test]
+fn test_metastability_with_no_chance_changes_nothing() {
+    set_metastability_model(Some(MetastabilityModel {
+        probability: 0.0,
+        seed: 42,
+    }));
+    let latencies = synchronizer_latencies::<2>(20);
+    set_metastability_model(None);
+    assert!(latencies.iter().all(|x| *x == 2));
+}
+
+#[test]
+fn test_three_stage_synchronizer_synthesizes() {
+    let mut uut = BitSynchronizer::<3>::default();
+    uut.sig_in.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("(* ASYNC_REG = \"TRUE\" *) reg [2:0] sync_stages;"));
+    yosys_validate("sync_3_stages", &vlog).unwrap();
+}
+
+#[test]
+fn test_mtbf_grows_with_stages() {
+    let flops = FlipFlopCharacteristics::default();
+    let two = synchronizer_mtbf(100e6, 10e6, 2, &flops);
+    let three = synchronizer_mtbf(100e6, 10e6, 3, &flops);
+    assert!(three > two * 1e100);
+    // A faster clock leaves less time to settle
+    assert!(synchronizer_mtbf(400e6, 10e6, 2, &flops) < two);
+    // A clock period shorter than the overhead leaves none at all
+    assert_eq!(synchronizer_mtbf(4e9, 10e6, 3, &flops), 0.0);
+}
+
+#[test]
+fn test_stages_for_mtbf() {
+    let flops = FlipFlopCharacteristics::default();
+    let year = 365.0 * 24.0 * 3600.0;
+    assert_eq!(
+        synchronizer_stages_for_mtbf(100e6, 10e6, 1000.0 * year, &flops),
+        Some(2)
+    );
+    let stages = synchronizer_stages_for_mtbf(800e6, 100e6, 1000.0 * year, &flops).unwrap();
+    assert!(stages > 2);
+    assert!(synchronizer_mtbf(800e6, 100e6, stages, &flops) >= 1000.0 * year);
+    assert!(synchronizer_mtbf(800e6, 100e6, stages - 1, &flops) < 1000.0 * year);
+    assert_eq!(synchronizer_stages_for_mtbf(4e9, 1e6, year, &flops), None);
+}
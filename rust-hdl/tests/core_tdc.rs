@@ -0,0 +1,189 @@
+use rust_hdl::prelude::*;
+
+fn make_counter_tdc() -> CounterTDC<16> {
+    let mut uut = CounterTDC::<16>::new(200);
+    uut.start.connect();
+    uut.stop.connect();
+    uut.read.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_counter_tdc_synthesizes() {
+    let uut = make_counter_tdc();
+    yosys_validate("counter_tdc", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_counter_tdc_times_echoes() {
+    let uut = make_counter_tdc();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<CounterTDC<16>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<CounterTDC<16>>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        // A start with two echoes (off the clock edges)
+        x = sim.wait(3, x)?;
+        x.start.next = true;
+        x = sim.wait(30, x)?;
+        x.start.next = false;
+        x = sim.wait(340, x)?;
+        x.stop.next = true;
+        x = sim.wait(30, x)?;
+        x.stop.next = false;
+        x = sim.wait(830, x)?;
+        x.stop.next = true;
+        x = sim.wait(30, x)?;
+        x.stop.next = false;
+        x = sim.watch(|x| !x.busy.val(), x)?;
+        sim_assert!(sim, !x.timeout.val(), x);
+        for expected in [37_u64, 123] {
+            sim_assert!(sim, !x.empty.val(), x);
+            sim_assert_eq!(sim, x.data.val(), expected, x);
+            x.read.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.read.next = false;
+        }
+        sim_assert!(sim, x.empty.val(), x);
+        // A start with no echo times out
+        x.start.next = true;
+        x = sim.wait(30, x)?;
+        x.start.next = false;
+        x = sim.watch(|x| x.timeout.val(), x)?;
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert!(sim, x.empty.val() & !x.busy.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 100_000, &vcd_path!("counter_tdc.vcd"))
+        .unwrap();
+}
+
+// A delay line with uneven taps, that are between 0.5 and 1.5 of a 24th of a
+// clock long (so that 32 of them are longer than a clock)
+struct DelayLineModel {
+    taps: Vec<f64>,
+}
+
+impl DelayLineModel {
+    fn new(seed: u64) -> Self {
+        let mut rng = Rng(seed);
+        let mut delay = 0.0;
+        let taps = (0..32)
+            .map(|_| {
+                delay += (0.5 + rng.next()) / 24.0;
+                delay
+            })
+            .collect();
+        Self { taps }
+    }
+    // The taps sampled on the clock edges after a hit that arrived `phase` clocks
+    // before the first of them, then the line is cleared
+    fn words(&self, phase: f64) -> Vec<u64> {
+        (0..3)
+            .map(|edge| {
+                self.taps
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, delay)| **delay <= phase + edge as f64)
+                    .fold(0, |acc, (ndx, _)| acc | (1 << ndx))
+            })
+            .chain([0])
+            .collect()
+    }
+}
+
+struct Rng(u64);
+
+impl Rng {
+    fn next(&mut self) -> f64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        (self.0 >> 11) as f64 / (1_u64 << 53) as f64
+    }
+}
+
+// The start and stop phases and the stop delay (in clocks) of each measurement
+fn measurements() -> Vec<(f64, f64, usize)> {
+    let mut rng = Rng(0x1234_5678);
+    (0..40)
+        .map(|_| (rng.next(), rng.next(), 5 + (rng.next() * 60.0) as usize))
+        .collect()
+}
+
+fn make_interval_tdc() -> IntervalTDC<32, 6> {
+    let mut uut = IntervalTDC::<32, 6>::new(12, 1000);
+    uut.start_taps.connect();
+    uut.stop_taps.connect();
+    uut.calibrate.connect();
+    uut.read.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_interval_tdc_synthesizes() {
+    let uut = make_interval_tdc();
+    yosys_validate("interval_tdc", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_interval_tdc_calibrates_and_measures() {
+    let uut = make_interval_tdc();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<IntervalTDC<32, 6>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<IntervalTDC<32, 6>>| {
+        let mut x = sim.init()?;
+        let start_line = DelayLineModel::new(0xDEAD_BEEF);
+        let stop_line = DelayLineModel::new(0xFEED_F00D);
+        let mut rng = Rng(0xC0FFEE);
+        wait_clock_cycles!(sim, clock, x, 100);
+        x.calibrate.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.calibrate.next = false;
+        // Random hits on both channels for the code density test
+        while x.calibrating.val() {
+            let start = start_line.words(rng.next());
+            let stop = stop_line.words(rng.next());
+            for (start, stop) in start.into_iter().zip(stop) {
+                x.start_taps.next = start.to_bits();
+                x.stop_taps.next = stop.to_bits();
+                wait_clock_cycle!(sim, clock, x);
+            }
+        }
+        for (start_phase, stop_phase, delay) in measurements() {
+            let mut start = start_line.words(start_phase);
+            let mut stop = stop_line.words(stop_phase);
+            start.resize(delay + stop.len(), 0);
+            stop.splice(0..0, vec![0; delay]);
+            for (start, stop) in start.into_iter().zip(stop) {
+                x.start_taps.next = start.to_bits();
+                x.stop_taps.next = stop.to_bits();
+                wait_clock_cycle!(sim, clock, x);
+            }
+            wait_clock_cycles!(sim, clock, x, 10);
+        }
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<IntervalTDC<32, 6>>| {
+        let mut x = sim.init()?;
+        for (start_phase, stop_phase, delay) in measurements() {
+            x = sim.watch(|x| !x.empty.val(), x)?;
+            let expected = delay as f64 + start_phase - stop_phase;
+            let measured = x.data.val().index() as f64 / 65536.0;
+            sim_assert!(sim, (measured - expected).abs() < 0.07, x);
+            sim_assert!(sim, !x.timeout.val() & !x.overflow.val(), x);
+            x.read.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.read.next = false;
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("interval_tdc.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,168 @@
+use rust_hdl::prelude::*;
+
+// A TDM link looped back from a serializer to a deserializer.  The bit clock,
+// and the clocks on either side of the FIFOs, are all unrelated.
+#[derive(LogicBlock)]
+struct TDMLoopback {
+    bit_clock: Signal<In, Clock>,
+    write_clock: Signal<In, Clock>,
+    read_clock: Signal<In, Clock>,
+    tx: TDMSerializer<16, 4>,
+    rx: TDMDeserializer<16, 4>,
+}
+
+impl TDMLoopback {
+    fn new(config: TDMConfig) -> Self {
+        Self {
+            bit_clock: Default::default(),
+            write_clock: Default::default(),
+            read_clock: Default::default(),
+            tx: TDMSerializer::new(config),
+            rx: TDMDeserializer::new(config),
+        }
+    }
+}
+
+impl Logic for TDMLoopback {
+    #[hdl_gen]
+    fn update(&mut self) {
+        TDMWiresTransmitter::join(&mut self.tx.wires, &mut self.rx.wires);
+        self.tx.bit_clock.next = self.bit_clock.val();
+        self.rx.bit_clock.next = self.bit_clock.val();
+        self.tx.clock.next = self.write_clock.val();
+        self.rx.clock.next = self.read_clock.val();
+    }
+}
+
+const SAMPLES: usize = 12;
+
+fn make_loopback(config: TDMConfig) -> TDMLoopback {
+    let mut uut = TDMLoopback::new(config);
+    for channel in 0..4 {
+        uut.tx.data_in[channel].connect();
+        uut.tx.write[channel].connect();
+        uut.rx.read[channel].connect();
+    }
+    uut.connect_all();
+    uut
+}
+
+fn sample(channel: usize, index: usize) -> u16 {
+    (((channel + 1) << 12) | (index + 1)) as u16
+}
+
+fn run_loopback(config: TDMConfig, periods: [u64; 3], name: &str) {
+    let uut = make_loopback(config);
+    let mut sim = Simulation::new();
+    sim.add_clock(periods[0], |x: &mut Box<TDMLoopback>| {
+        x.bit_clock.next = !x.bit_clock.val()
+    });
+    sim.add_clock(periods[1], |x: &mut Box<TDMLoopback>| {
+        x.write_clock.next = !x.write_clock.val()
+    });
+    sim.add_clock(periods[2], |x: &mut Box<TDMLoopback>| {
+        x.read_clock.next = !x.read_clock.val()
+    });
+    // Keep the transmit FIFOs topped up
+    sim.add_testbench(move |mut sim: Sim<TDMLoopback>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, write_clock, x);
+        let mut written = [0; 4];
+        while written.iter().any(|n| *n < SAMPLES) {
+            for (channel, count) in written.iter_mut().enumerate() {
+                x.tx.write[channel].next = false;
+                if !x.tx.full[channel].val() && *count < SAMPLES {
+                    x.tx.data_in[channel].next = sample(channel, *count).to_bits();
+                    x.tx.write[channel].next = true;
+                    *count += 1;
+                }
+            }
+            wait_clock_cycle!(sim, write_clock, x);
+        }
+        for channel in 0..4 {
+            x.tx.write[channel].next = false;
+        }
+        sim.done(x)
+    });
+    // Drain the receive FIFOs, and check the samples of each channel
+    sim.add_testbench(move |mut sim: Sim<TDMLoopback>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, read_clock, x);
+        let mut received = vec![vec![]; 4];
+        while received.iter().any(|r| r.len() < SAMPLES) {
+            for (channel, samples) in received.iter_mut().enumerate() {
+                x.rx.read[channel].next = false;
+                if !x.rx.empty[channel].val() {
+                    let value = x.rx.data_out[channel].val().index() as u16;
+                    // Slots are sent as zeros until the first samples arrive
+                    if value != 0 || !samples.is_empty() {
+                        samples.push(value);
+                    }
+                    x.rx.read[channel].next = true;
+                }
+            }
+            wait_clock_cycle!(sim, read_clock, x);
+        }
+        for (channel, samples) in received.iter().enumerate() {
+            for (index, value) in samples.iter().take(SAMPLES).enumerate() {
+                sim_assert_eq!(sim, *value, sample(channel, index), x);
+            }
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 5_000_000, &vcd_path!(name))
+        .unwrap();
+}
+
+#[test]
+fn test_tdm_loopback_synthesizes() {
+    let uut = make_loopback(TDMConfig {
+        slots: 8,
+        slot_width: 32,
+        sync_active_high: true,
+        sync_offset: 1,
+    });
+    yosys_validate("tdm_loopback", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_tdm_loopback_dsp_mode_a() {
+    run_loopback(
+        TDMConfig {
+            slots: 6,
+            slot_width: 24,
+            sync_active_high: true,
+            sync_offset: 1,
+        },
+        [7, 5, 11],
+        "tdm_dsp_mode_a.vcd",
+    );
+}
+
+#[test]
+fn test_tdm_loopback_short_slots_active_low_sync() {
+    run_loopback(
+        TDMConfig {
+            slots: 5,
+            slot_width: 18,
+            sync_active_high: false,
+            sync_offset: 0,
+        },
+        [5, 13, 3],
+        "tdm_short_slots.vcd",
+    );
+}
+
+#[test]
+fn test_tdm_loopback_late_sync() {
+    run_loopback(
+        TDMConfig {
+            slots: 4,
+            slot_width: 16,
+            sync_active_high: true,
+            sync_offset: 19,
+        },
+        [3, 4, 9],
+        "tdm_late_sync.vcd",
+    );
+}
@@ -0,0 +1,74 @@
+use rust_hdl::prelude::*;
+
+#[derive(LogicBlock, Default)]
+struct Resettable {
+    pub clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub count: Signal<Out, Bits<8>>,
+    counter: DFF<Bits<8>>,
+}
+
+impl Logic for Resettable {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter);
+        if self.reset.val() {
+            self.counter.d.next = 0.into();
+        } else {
+            self.counter.d.next = self.counter.q.val() + 1;
+        }
+        self.count.next = self.counter.q.val();
+    }
+}
+
+#[derive(LogicBlock, Default)]
+struct TwoClocks {
+    pub fast: Resettable,
+    pub slow: Resettable,
+}
+
+impl Logic for TwoClocks {
+    #[hdl_gen]
+    fn update(&mut self) {}
+}
+
+#[test]
+fn test_harness_with_reset() {
+    let mut uut = Resettable::default();
+    uut.connect_all();
+    let mut sim = test_harness!(Resettable, clocks: [clock => 100_000_000], reset: reset, 4);
+    sim.add_testbench(move |mut sim: Sim<Resettable>| {
+        let mut x = sim.init()?;
+        sim_assert!(sim, x.reset.val(), x);
+        x = sim.watch(|x| !x.reset.val(), x)?;
+        sim_assert_eq!(sim, x.count.val(), 0, x);
+        wait_clock_cycles!(sim, clock, x, 10);
+        sim_assert_eq!(sim, x.count.val(), 10, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000_000).unwrap();
+}
+
+#[test]
+fn test_harness_with_multiple_clocks() {
+    let mut uut = TwoClocks::default();
+    uut.fast.clock.connect();
+    uut.fast.reset.connect();
+    uut.slow.clock.connect();
+    uut.slow.reset.connect();
+    uut.connect_all();
+    let mut sim =
+        test_harness!(TwoClocks, clocks: [fast.clock => 100_000_000, slow.clock => 25_000_000]);
+    sim.add_testbench(move |mut sim: Sim<TwoClocks>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, slow.clock, x, 20);
+        sim_assert_eq!(
+            sim,
+            x.fast.count.val().index(),
+            x.slow.count.val().index() * 4,
+            x
+        );
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000_000).unwrap();
+}
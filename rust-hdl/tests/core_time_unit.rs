@@ -0,0 +1,71 @@
+use rust_hdl::prelude::*;
+use std::time::Duration;
+
+#[derive(LogicBlock, Default)]
+struct Toggler {
+    pub clock: Signal<In, Clock>,
+    pub flag: Signal<Out, Bit>,
+    state: DFF<Bit>,
+}
+
+impl Logic for Toggler {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state);
+        self.state.d.next = !self.state.q.val();
+        self.flag.next = self.state.q.val();
+    }
+}
+
+fn run_toggler(unit: TimeUnit) -> (Vec<u64>, String) {
+    let mut uut = Toggler::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.set_time_unit(unit);
+    // A 100 MHz clock, whatever the time unit
+    sim.add_clock(Duration::from_nanos(5), |x: &mut Box<Toggler>| {
+        x.clock.next = !x.clock.val()
+    });
+    let (send, recv) = std::sync::mpsc::channel();
+    sim.add_testbench(move |mut sim: Sim<Toggler>| {
+        let mut x = sim.init()?;
+        x = sim.wait(Duration::from_micros(1), x)?;
+        send.send(sim.time()).unwrap();
+        sim_assert_eq!(sim, sim.now(), Duration::from_micros(1), x);
+        wait_clock_true!(sim, clock, x);
+        send.send(sim.time()).unwrap();
+        sim.done(x)
+    });
+    let mut vcd = vec![];
+    sim.run_traced(Box::new(uut), Duration::from_micros(2), &mut vcd)
+        .unwrap();
+    (recv.try_iter().collect(), String::from_utf8(vcd).unwrap())
+}
+
+#[test]
+fn test_time_unit_sets_ticks_and_vcd_timescale() {
+    let (times, vcd) = run_toggler(TimeUnit::Picoseconds);
+    assert_eq!(times, vec![1_000_000, 1_005_000]);
+    assert!(vcd.contains("$timescale 1 ps $end"));
+    let (times, vcd) = run_toggler(TimeUnit::Nanoseconds);
+    assert_eq!(times, vec![1_000, 1_005]);
+    assert!(vcd.contains("$timescale 1 ns $end"));
+    assert!(vcd.contains("\n#1005\n"));
+    let (times, _) = run_toggler(TimeUnit::Femtoseconds);
+    assert_eq!(times, vec![1_000_000_000, 1_005_000_000]);
+}
+
+#[test]
+fn test_time_unit_converts_durations() {
+    assert_eq!(TimeUnit::Picoseconds.ticks(Duration::from_nanos(3)), 3_000);
+    assert_eq!(TimeUnit::Microseconds.ticks(Duration::from_nanos(2_500)), 2);
+    assert_eq!(
+        TimeUnit::Femtoseconds.duration(2_000_000),
+        Duration::from_nanos(2)
+    );
+    assert_eq!(
+        Duration::from_micros(3).to_ticks(TimeUnit::Nanoseconds),
+        3_000
+    );
+    assert_eq!(42_u64.to_ticks(TimeUnit::Microseconds), 42);
+}
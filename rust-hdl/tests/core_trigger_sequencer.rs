@@ -0,0 +1,265 @@
+use rust_hdl::prelude::*;
+use rust_hdl::sim::sdr_sdram::chip::SDRAMSimulator;
+use rust_hdl::widgets::sdram::buffer::SDRAMOnChipBuffer;
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone)]
+struct Stage {
+    mask: u16,
+    value: u16,
+    count: u16,
+    window: u16,
+}
+
+impl Stage {
+    fn new(mask: u16, value: u16) -> Self {
+        Self {
+            mask,
+            value,
+            count: 1,
+            window: 0,
+        }
+    }
+    fn count(self, count: u16) -> Self {
+        Self { count, ..self }
+    }
+    fn window(self, window: u16) -> Self {
+        Self { window, ..self }
+    }
+}
+
+// Configures the sequencer with the stages, and feeds it the samples (with
+// `gap` idle clocks between them).  Returns the samples that fired the
+// trigger, and the stage the sequencer was in after each sample.
+fn run_sequencer(
+    stages: Vec<Stage>,
+    depth: u64,
+    samples: Vec<u16>,
+    gap: usize,
+) -> (Vec<usize>, Vec<u64>) {
+    let fired = Arc::new(Mutex::new(vec![]));
+    let states = Arc::new(Mutex::new(vec![]));
+    let mut uut = TriggerSequencer::<16, 2>::default();
+    uut.data_in.connect();
+    uut.strobe_in.connect();
+    uut.arm.connect();
+    uut.depth.connect();
+    uut.config_stage.connect();
+    uut.config_mask.connect();
+    uut.config_value.connect();
+    uut.config_count.connect();
+    uut.config_window.connect();
+    uut.config_write.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TriggerSequencer<16, 2>>| {
+        x.clock.next = !x.clock.val()
+    });
+    let fired_log = fired.clone();
+    let states_log = states.clone();
+    sim.add_testbench(move |mut sim: Sim<TriggerSequencer<16, 2>>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 2);
+        for (ndx, stage) in stages.iter().enumerate() {
+            x.config_stage.next = (ndx as u64).to_bits();
+            x.config_mask.next = (stage.mask as u64).to_bits();
+            x.config_value.next = (stage.value as u64).to_bits();
+            x.config_count.next = (stage.count as u64).to_bits();
+            x.config_window.next = (stage.window as u64).to_bits();
+            x.config_write.next = true;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        x.config_write.next = false;
+        x.depth.next = depth.to_bits();
+        x.arm.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.arm.next = false;
+        for (ndx, sample) in samples.iter().enumerate() {
+            x.data_in.next = (*sample as u64).to_bits();
+            x.strobe_in.next = true;
+            x = sim.wait(1, x)?;
+            if x.trigger.val() {
+                fired_log.lock().unwrap().push(ndx);
+            }
+            wait_clock_cycle!(sim, clock, x);
+            x.strobe_in.next = false;
+            x.data_in.next = 0xFFFF.into();
+            wait_clock_cycles!(sim, clock, x, gap);
+            states_log
+                .lock()
+                .unwrap()
+                .push(x.stage.val().index() as u64);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000_000).unwrap();
+    let fired = fired.lock().unwrap().clone();
+    let states = states.lock().unwrap().clone();
+    (fired, states)
+}
+
+#[test]
+fn test_sequencer_a_then_b_within_a_window() {
+    // A is 0xA5 in the upper byte, and B is 0xB6 in the upper byte, within
+    // 4 samples of A.  The lower byte is a don't care.
+    let stages = vec![
+        Stage::new(0xFF00, 0xA500),
+        Stage::new(0xFF00, 0xB600).window(4),
+    ];
+    let samples = vec![
+        0xA512, 0, 0, 0, 0, 0xB600, // B is too late
+        0xB6FF, // B without an A
+        0xA5FF, 0x1234, 0xB6AB, // B right after A
+        0xA500, 0, 0, 0, 0xB642, // B on the last sample of the window
+    ];
+    for gap in [0, 3] {
+        let (fired, states) = run_sequencer(stages.clone(), 2, samples.clone(), gap);
+        assert_eq!(fired, vec![9, 14]);
+        assert_eq!(states, vec![1, 1, 1, 1, 0, 0, 0, 1, 1, 0, 1, 1, 1, 1, 0]);
+    }
+}
+
+#[test]
+fn test_sequencer_counts_matches() {
+    // The third odd sample, and then an exact 0x00FF twice
+    let stages = vec![
+        Stage::new(0x0001, 0x0001).count(3),
+        Stage::new(0xFFFF, 0x00FF).count(2),
+    ];
+    let samples = vec![1, 2, 0xFF, 4, 5, 0xFF, 6, 0xFF, 0xFF, 0xFF];
+    let (fired, states) = run_sequencer(stages, 2, samples, 1);
+    assert_eq!(fired, vec![7]);
+    assert_eq!(states, vec![0, 0, 0, 0, 1, 1, 1, 0, 0, 0]);
+}
+
+#[test]
+fn test_sequencer_depth() {
+    let stages = vec![Stage::new(0x00F0, 0x0030), Stage::new(0xFFFF, 0x1111)];
+    let samples = vec![0x30, 0x31, 0x40, 0x1111, 0x3F];
+    // A depth of 1 fires on every match of the first stage
+    let (fired, _) = run_sequencer(stages.clone(), 1, samples.clone(), 0);
+    assert_eq!(fired, vec![0, 1, 4]);
+    let (fired, _) = run_sequencer(stages.clone(), 2, samples.clone(), 0);
+    assert_eq!(fired, vec![3]);
+    // And a depth of 0 turns the sequencer off
+    let (fired, states) = run_sequencer(stages, 0, samples, 0);
+    assert!(fired.is_empty());
+    assert!(states.iter().all(|x| *x == 0));
+}
+
+#[derive(LogicBlock)]
+struct HLSCaptureTest {
+    bus: SoCBusController<16, 8>,
+    dram: SDRAMSimulator<6, 4, 10, 16>,
+    buffer: SDRAMOnChipBuffer<16>,
+    capture: HLSCapture<6, 4, 16, 16, 12, 8, 8>,
+}
+
+impl Logic for HLSCaptureTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusController::<16, 8>::join(&mut self.bus, &mut self.capture.upstream);
+        SDRAMDriver::<16>::join(&mut self.capture.sdram, &mut self.buffer.buf_in);
+        SDRAMDriver::<16>::join(&mut self.buffer.buf_out, &mut self.dram.sdram);
+        self.capture.ram_clock.next = self.bus.clock.val();
+    }
+}
+
+impl Default for HLSCaptureTest {
+    fn default() -> Self {
+        let timings = MemoryTimings::fast_boot_sim(100e6);
+        Self {
+            bus: Default::default(),
+            dram: SDRAMSimulator::new(timings),
+            buffer: Default::default(),
+            capture: HLSCapture::new(3, timings, OutputBuffer::DelayTwo),
+        }
+    }
+}
+
+fn make_capture_test() -> HLSCaptureTest {
+    let mut uut = HLSCaptureTest::default();
+    uut.capture.data_in.connect();
+    uut.capture.strobe_in.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_hls_capture_with_sequencer_synthesizes() {
+    let uut = make_capture_test();
+    yosys_validate("hls_capture_sequencer", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_hls_capture_with_a_multi_stage_trigger() {
+    let uut = make_capture_test();
+    let mut sim = Simulation::new();
+    sim.add_clock(5000, |x: &mut Box<HLSCaptureTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    // A counter on the input, with a sample every other clock
+    sim.add_testbench(move |mut sim: Sim<HLSCaptureTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 1000);
+        for counter in 0..1_200_u64 {
+            x.capture.data_in.next = counter.to_bits();
+            x.capture.strobe_in.next = true;
+            wait_clock_cycle!(sim, bus.clock, x);
+            x.capture.strobe_in.next = false;
+            wait_clock_cycle!(sim, bus.clock, x);
+        }
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<HLSCaptureTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 10);
+        // The third sample with 0x02 in the upper byte, then a sample ending
+        // in 4 within 8 samples, and then 0x0206 within 2 samples.  That is
+        // the sample 0x206, at address 0x103 (as every other one is kept).
+        let stages = [
+            (0xFF00, 0x0200, 3, 0),
+            (0x000F, 0x0004, 1, 8),
+            (0xFFFF, 0x0206, 1, 2),
+        ];
+        for (ndx, (mask, value, count, window)) in stages.into_iter().enumerate() {
+            bus_port_write!(sim, x, bus, 12, [mask]);
+            bus_port_write!(sim, x, bus, 13, [value]);
+            bus_port_write!(sim, x, bus, 14, [count]);
+            bus_port_write!(sim, x, bus, 15, [window]);
+            bus_port_write!(sim, x, bus, 16, [ndx]);
+        }
+        bus_port_write!(sim, x, bus, 17, [3]);
+        bus_port_write!(sim, x, bus, 1, [1]);
+        bus_port_write!(sim, x, bus, 2, [0]);
+        bus_port_write!(sim, x, bus, 2, [40]);
+        bus_port_write!(sim, x, bus, 0, [1]);
+        sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 18), 0, x);
+        loop {
+            let status = bus_port_read!(sim, x, bus, 7);
+            if status & 4 != 0 {
+                sim_assert_eq!(sim, status & 0xA, 2, x);
+                break;
+            }
+            wait_clock_cycles!(sim, bus.clock, x, 100);
+        }
+        let address = ((bus_port_read!(sim, x, bus, 8) as u32) << 16)
+            | (bus_port_read!(sim, x, bus, 8) as u32);
+        sim_assert_eq!(sim, address, 0x103, x);
+        // Read out the line before the trigger, and the one with it
+        bus_port_write!(sim, x, bus, 9, [0]);
+        bus_port_write!(sim, x, bus, 9, [0xF0]);
+        bus_port_write!(sim, x, bus, 10, [0]);
+        bus_port_write!(sim, x, bus, 10, [32]);
+        for ndx in 0..32 {
+            sim_assert_eq!(sim, bus_port_read!(sim, x, bus, 11), 0x1E0 + 2 * ndx, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(
+        Box::new(uut),
+        100_000_000,
+        &vcd_path!("hls_capture_sequencer.vcd"),
+    )
+    .unwrap();
+}
@@ -0,0 +1,42 @@
+use rust_hdl::prelude::*;
+use std::time::Duration;
+
+#[test]
+fn test_watchdog_expires_without_kick() {
+    // 20 clock cycles at 1 MHz
+    let mut uut = Watchdog::<8>::new(1_000_000, Duration::from_micros(20));
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Watchdog<8>>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Watchdog<8>>| {
+        let mut x = sim.init()?;
+        // Disabled, it never expires
+        wait_clock_cycles!(sim, clock, x, 50);
+        sim_assert!(sim, !x.expired.val(), x);
+        x.enable.next = true;
+        // Kicked often enough, it never expires
+        for _ in 0..10 {
+            wait_clock_cycles!(sim, clock, x, 15);
+            sim_assert!(sim, !x.expired.val(), x);
+            x.kick.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.kick.next = false;
+        }
+        // Without a kick, it expires and stays expired
+        wait_clock_cycles!(sim, clock, x, 19);
+        sim_assert!(sim, !x.expired.val(), x);
+        wait_clock_cycles!(sim, clock, x, 4);
+        sim_assert!(sim, x.expired.val(), x);
+        wait_clock_cycles!(sim, clock, x, 100);
+        sim_assert!(sim, x.expired.val(), x);
+        // A kick clears it
+        x.kick.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.kick.next = false;
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert!(sim, !x.expired.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 10_000, &vcd_path!("watchdog.vcd"))
+        .unwrap();
+}
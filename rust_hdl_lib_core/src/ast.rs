@@ -233,9 +233,9 @@ use std::fmt::{Display, Formatter, LowerHex};
 /// name by automatically namespacing them.  That means that if you have a
 /// module that is used in two different places in your code, it will get
 /// two different names.  This is because of the parametric nature of the
-/// generated code.  RustHDL does not assume (or know) that your two modules
-/// will generate identical Verilog.  So it assumes they will be different
-/// and creates two different named instances.
+/// generated code - the two uses may generate different Verilog.  When they
+/// do not, RustHDL only emits the first of them, and instantiates it in
+/// both places.
 ///
 /// To see how that works, let's create a minimum example.  For test, we will
 /// use a single bit inverter.
@@ -284,10 +284,12 @@ use std::fmt::{Display, Formatter, LowerHex};
 /// x.connect_all();
 /// let v = generate_verilog(&x);
 /// // If you examine the generated code, you will see it contains
-/// // two instances of modules, one named `top$knot_1` and the
+/// // two instances of the module named `top$knot_1`, since both
+/// // inverters generate the same Verilog.
 /// assert!(v.contains("top$knot_1 knot_1"));
-/// // and the second is `top$knot_2`.
-/// assert!(v.contains("top$knot_2 knot_2"));
+/// assert!(v.contains("top$knot_1 knot_2"));
+/// // The module `top$knot_2` is not emitted at all.
+/// assert!(!v.contains("module top$knot_2"));
 /// ```
 /// The problem arises when you use a [BlackBox] Verilog declaration.
 /// In particular, RustHDL does not wrap your declaration (the Verilog is
@@ -296,7 +298,9 @@ use std::fmt::{Display, Formatter, LowerHex};
 /// A classic case is in the case of a parameterized blackbox IP core.
 /// In that case, it is up to you to rename the different IP cores so that
 /// they do not conflict.  A better way around this is to use the [Wrapper]
-/// variant, since that is easier to use in most cases.
+/// variant, since that is easier to use in most cases.  Wrappers are never
+/// merged, even when their Verilog is the same, so each one keeps its own
+/// module.
 #[derive(Debug, Clone)]
 pub struct BlackBox {
     /// The Verilog code to create the black box in your firmware
@@ -600,6 +604,8 @@ pub enum VerilogStatement {
     Match(VerilogMatch),
     Loop(VerilogLoop),
     Comment(String),
+    // The Rust source location (file:line) of the statements that follow
+    Source(String),
     Link(Vec<VerilogLink>),
     Macro(VerilogBlock),
 }
@@ -675,6 +681,10 @@ pub struct VerilogLiteral {
 }
 
 impl VerilogLiteral {
+    /// The number of bits needed to hold the value of the literal
+    pub fn significant_bits(&self) -> usize {
+        self.val.bits() as usize
+    }
     pub fn as_usize(&self) -> usize {
         let m = self.val.to_u32_digits();
         assert!(m.0 != Sign::Minus);
@@ -780,6 +790,7 @@ pub enum VerilogExpression {
         Box<VerilogExpression>,
         Box<VerilogExpression>,
     ),
+    Concat(Vec<VerilogExpression>),
 }
 
 #[doc(hidden)]
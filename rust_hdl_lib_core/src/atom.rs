@@ -42,6 +42,12 @@ pub trait Atom {
     fn id(&self) -> usize;
     fn verilog(&self) -> VerilogLiteral;
     fn constraints(&self) -> Vec<PinConstraint>;
+    /// Drive the atom with a value given as a [VCDValue].  Returns `false` if
+    /// the atom can not be driven, or the value does not fit it.  Atoms can not
+    /// be driven unless they say otherwise.
+    fn set_vcd(&mut self, _value: &VCDValue) -> bool {
+        false
+    }
 }
 
 pub fn is_atom_an_enum(atom: &dyn Atom) -> bool {
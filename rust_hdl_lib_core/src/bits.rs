@@ -957,6 +957,32 @@ impl<const N: usize> Bits<N> {
         *self = masked | replace
     }
 
+    #[inline(always)]
+    /// Concatenate two [Bits] values, with `self` in the most significant
+    /// bits and `rhs` in the least significant bits.  This is the same as
+    /// `{self, rhs}` in Verilog.  The width of the result (the argument [O])
+    /// must be the sum of the widths of the two values, and is usually
+    /// inferred from where the result is used.  When concatenations are
+    /// chained, the widths of the intermediate results have to be given.
+    /// ```
+    /// # use rust_hdl_lib_core::prelude::*;
+    /// let x: Bits<8> = bits(0xDE);
+    /// let y: Bits<4> = bits(0xA);
+    /// let z: Bits<12> = x.concat(y);
+    /// assert_eq!(z, bits(0xDEA));
+    /// let w: Bits<16> = x.concat::<4, 12>(y).concat(bits::<4>(0xD));
+    /// assert_eq!(w, bits(0xDEAD));
+    /// ```
+    pub fn concat<const M: usize, const O: usize>(&self, rhs: Bits<M>) -> Bits<O> {
+        assert_eq!(
+            O,
+            N + M,
+            "The result of a concatenation must have exactly {} bits",
+            N + M
+        );
+        (bit_cast::<O, N>(*self) << M as LiteralType) | bit_cast::<O, M>(rhs)
+    }
+
     #[inline(always)]
     /// Returns a [Bits] value that contains [N] ones.
     /// ```
@@ -1,5 +1,8 @@
 use crate::logic::Logic;
-use crate::probe::Probe;
+use crate::probe::{Probe, ProbeMut};
+use rayon::prelude::*;
+use std::fmt::{Display, Formatter};
+use std::time::Duration;
 
 /// The [Block] trait is required for all circuitry that
 /// can be simulated by RustHDL.  If you want to be able
@@ -14,8 +17,272 @@ pub trait Block: Logic {
     fn update_all(&mut self);
     /// Returns `true` if anything in the circuit has changed (outputs or internal state)
     fn has_changed(&self) -> bool;
+    /// Like [update_all](Block::update_all), but skips the `update` of any block in which
+    /// nothing has changed for the last two delta cycles, since it would see exactly the
+    /// same inputs (and edges) as it did the last time.  If, in addition, the owner of
+    /// the block was not updated (so nothing can have written to its inputs), the block
+    /// is skipped entirely.  `owner_updated` is set if the block that contains this one
+    /// was updated in this pass, and `ports_written` if that block's own inputs may
+    /// have been written.  Returns `true` if the circuit is settled after the update.
+    /// The default is to update everything and never report the block as settled,
+    /// which is always safe.
+    fn update_changed(
+        &mut self,
+        _state: &mut EventState,
+        _owner_updated: bool,
+        _ports_written: bool,
+    ) -> bool {
+        self.update_all();
+        false
+    }
+    /// Like [update_all](Block::update_all), but counts the calls to `update`, and the
+    /// time spent in them, for each block in the circuit (see [SimProfile]).  `name` is
+    /// the name of the block in its owner.  The default updates everything without
+    /// recording anything, which is right for signals and constants.
+    fn update_profiled(&mut self, _name: &str, _profile: &mut SimProfile) {
+        self.update_all();
+    }
     /// The visitor pattern - allows a circuit to be probed by a [Probe] struct.
     fn accept(&self, name: &str, probe: &mut dyn Probe);
+    /// Like [accept](Block::accept), but the [ProbeMut] can change the signals it visits.
+    /// The default visits nothing.
+    fn accept_mut(&mut self, _name: &str, _probe: &mut dyn ProbeMut) {}
+}
+
+#[derive(Clone, Copy, Debug, Default)]
+struct Slot {
+    settled: bool,
+    end: usize,
+    split_end: usize,
+}
+
+/// The bookkeeping for [Block::update_changed].  It records for each block if
+/// it was settled (nothing in it changed in the last two delta cycles) at the
+/// end of the previous update.  The blocks are visited in the same order every
+/// time, so each one simply takes the next slot, and a block that is skipped
+/// entirely jumps over the slots of everything inside it.  Blocks that are
+/// updated in parallel get their own [EventState].
+#[derive(Clone, Debug, Default)]
+pub struct EventState {
+    slots: Vec<Slot>,
+    next: usize,
+    splits: Vec<Vec<EventState>>,
+    next_split: usize,
+    external: bool,
+}
+
+impl EventState {
+    /// Start a new pass over the circuit.  Set `external` if something other than
+    /// the circuit itself (e.g., a testbench) may have written to its signals.
+    pub fn rewind(&mut self, external: bool) {
+        self.next = 0;
+        self.next_split = 0;
+        self.external = external;
+    }
+    /// Returns `true` if this pass follows writes from outside the circuit.
+    pub fn external(&self) -> bool {
+        self.external
+    }
+    /// Claim the slot for a block.  Returns the slot and whether the block was settled.
+    pub fn enter(&mut self) -> (usize, bool) {
+        let slot = self.next;
+        self.next += 1;
+        if slot == self.slots.len() {
+            self.slots.push(Slot::default());
+        }
+        (slot, self.slots[slot].settled)
+    }
+    /// Skip over everything inside the block in the given slot.
+    pub fn skip(&mut self, slot: usize) {
+        self.next = self.slots[slot].end;
+        self.next_split = self.slots[slot].split_end;
+    }
+    /// Record if the block in the given slot is settled after the update.
+    pub fn leave(&mut self, slot: usize, settled: bool) {
+        self.slots[slot] = Slot {
+            settled,
+            end: self.next,
+            split_end: self.next_split,
+        };
+    }
+    /// Get a separate state for each of `count` blocks that are updated in parallel.
+    pub fn split(&mut self, count: usize) -> &mut [EventState] {
+        let ndx = self.next_split;
+        self.next_split += 1;
+        if ndx == self.splits.len() {
+            self.splits.push(vec![EventState::default(); count]);
+        }
+        let external = self.external;
+        let states = &mut self.splits[ndx];
+        states.iter_mut().for_each(|x| x.rewind(external));
+        states
+    }
+}
+
+/// The time spent in the `update` of one block of the circuit (not counting
+/// the blocks inside it), and the number of times it was called.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ProfileEntry {
+    pub path: String,
+    pub calls: u64,
+    pub time: Duration,
+}
+
+/// The bookkeeping for [Block::update_profiled].  Like [EventState], each block
+/// takes the next slot as the circuit is visited, so the path of a block is only
+/// worked out the first time through.  The blocks of an array or [Vec] are named
+/// `name$index`, as they are in a VCD file.
+#[derive(Clone, Debug, Default)]
+pub struct SimProfile {
+    entries: Vec<ProfileEntry>,
+    next: usize,
+    stack: Vec<usize>,
+    index: Option<usize>,
+}
+
+impl SimProfile {
+    /// Start a new pass over the circuit.
+    pub fn rewind(&mut self) {
+        self.next = 0;
+        self.stack.clear();
+    }
+    /// The next block to enter is element `index` of an array.
+    pub fn set_index(&mut self, index: usize) {
+        self.index = Some(index);
+    }
+    /// Claim the slot for the block `name` (inside the block entered last).
+    pub fn enter(&mut self, name: &str) -> usize {
+        let slot = self.next;
+        self.next += 1;
+        let index = self.index.take();
+        if slot == self.entries.len() {
+            let mut path = match self.stack.last() {
+                Some(owner) => format!("{}.{}", self.entries[*owner].path, name),
+                None => name.to_string(),
+            };
+            if let Some(index) = index {
+                path += &format!("${}", index);
+            }
+            self.entries.push(ProfileEntry {
+                path,
+                ..Default::default()
+            });
+        }
+        self.stack.push(slot);
+        slot
+    }
+    /// Record a call to the `update` of the block in the given slot.
+    pub fn record(&mut self, slot: usize, time: Duration) {
+        self.entries[slot].calls += 1;
+        self.entries[slot].time += time;
+    }
+    /// Done with the block entered last (and everything inside it).
+    pub fn leave(&mut self) {
+        self.stack.pop();
+    }
+    /// The total time spent updating the circuit.
+    pub fn total_time(&self) -> Duration {
+        self.entries.iter().map(|x| x.time).sum()
+    }
+    /// The blocks of the circuit, with the most expensive first.
+    pub fn entries(&self) -> Vec<ProfileEntry> {
+        let mut entries = self.entries.clone();
+        entries.sort_by(|a, b| b.time.cmp(&a.time).then_with(|| a.path.cmp(&b.path)));
+        entries
+    }
+}
+
+impl Display for SimProfile {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let total = self.total_time().as_secs_f64().max(f64::MIN_POSITIVE);
+        writeln!(
+            f,
+            "{:>12} {:>7} {:>12} {:>9}  block",
+            "time (ms)", "share", "calls", "ns/call"
+        )?;
+        for entry in self.entries() {
+            let time = entry.time.as_secs_f64();
+            writeln!(
+                f,
+                "{:>12.3} {:>6.1}% {:>12} {:>9.0}  {}",
+                time * 1e3,
+                time / total * 100.0,
+                entry.calls,
+                time * 1e9 / entry.calls.max(1) as f64,
+                entry.path
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Update a set of independent blocks in parallel.  This is used by the
+/// `#[derive(LogicBlock)]` code for fields marked with `#[partition]`, e.g.,
+/// ```rust
+/// # use rust_hdl_lib_core::prelude::*;
+/// #[derive(LogicBlock, Default)]
+/// struct Domain {
+///     pub clock: Signal<In, Clock>,
+/// }
+/// # impl Logic for Domain {
+/// #   #[hdl_gen]
+/// #   fn update(&mut self) {}
+/// # }
+///
+/// #[derive(LogicBlock, Default)]
+/// struct TwoDomains {
+///     #[partition]
+///     fast: Domain,
+///     #[partition]
+///     slow: Domain,
+/// }
+/// # impl Logic for TwoDomains {
+/// #   #[hdl_gen]
+/// #   fn update(&mut self) {}
+/// # }
+/// ```
+/// Because a block only sees its own state when it is updated, the
+/// result is identical to updating the blocks one after the other.  Spreading
+/// the work across threads costs a few microseconds per delta cycle, so it only
+/// pays off when each partition is large (e.g., a whole clock domain with
+/// its own FIFOs and memory controllers).  Set `RAYON_NUM_THREADS=1` to
+/// run everything on a single thread.
+///
+/// The partitions are whatever fields are marked - nothing looks for clock
+/// domain crossings (synchronizers or FIFOs) to find the boundaries, and the
+/// partitions still meet at every delta cycle rather than running freely
+/// between crossings.  Whether it is faster depends on the design and the
+/// machine, so measure it before relying on it.
+pub fn update_all_parallel(blocks: &mut [&mut (dyn Block + Send)]) {
+    if rayon::current_num_threads() == 1 {
+        blocks.iter_mut().for_each(|x| x.update_all());
+    } else {
+        blocks.par_iter_mut().for_each(|x| x.update_all());
+    }
+}
+
+/// The event driven counterpart of [update_all_parallel].  Returns `true` if all
+/// of the blocks are settled.
+pub fn update_changed_parallel(
+    blocks: &mut [&mut (dyn Block + Send)],
+    states: &mut [EventState],
+    owner_updated: bool,
+    ports_written: bool,
+) -> bool {
+    if rayon::current_num_threads() == 1 {
+        blocks
+            .iter_mut()
+            .zip(states.iter_mut())
+            .fold(true, |settled, (x, state)| {
+                x.update_changed(state, owner_updated, ports_written) & settled
+            })
+    } else {
+        blocks
+            .par_iter_mut()
+            .zip(states.par_iter_mut())
+            .map(|(x, state)| x.update_changed(state, owner_updated, ports_written))
+            .reduce(|| true, |a, b| a & b)
+    }
 }
 
 impl<B: Block> Block for Vec<B> {
@@ -40,12 +307,40 @@ impl<B: Block> Block for Vec<B> {
         false
     }
 
+    fn update_changed(
+        &mut self,
+        state: &mut EventState,
+        owner_updated: bool,
+        ports_written: bool,
+    ) -> bool {
+        let mut settled = true;
+        for x in self {
+            settled &= x.update_changed(state, owner_updated, ports_written);
+        }
+        settled
+    }
+
+    fn update_profiled(&mut self, name: &str, profile: &mut SimProfile) {
+        for x in self.iter_mut().enumerate() {
+            profile.set_index(x.0);
+            x.1.update_profiled(name, profile);
+            profile.index = None;
+        }
+    }
+
     fn accept(&self, name: &str, probe: &mut dyn Probe) {
         for x in self.iter().enumerate() {
             let name = format!("{}${}", name, x.0);
             x.1.accept(&name, probe);
         }
     }
+
+    fn accept_mut(&mut self, name: &str, probe: &mut dyn ProbeMut) {
+        for x in self.iter_mut().enumerate() {
+            let name = format!("{}${}", name, x.0);
+            x.1.accept_mut(&name, probe);
+        }
+    }
 }
 
 impl<B: Block, const P: usize> Block for [B; P] {
@@ -70,10 +365,38 @@ impl<B: Block, const P: usize> Block for [B; P] {
         false
     }
 
+    fn update_changed(
+        &mut self,
+        state: &mut EventState,
+        owner_updated: bool,
+        ports_written: bool,
+    ) -> bool {
+        let mut settled = true;
+        for x in self {
+            settled &= x.update_changed(state, owner_updated, ports_written);
+        }
+        settled
+    }
+
+    fn update_profiled(&mut self, name: &str, profile: &mut SimProfile) {
+        for x in self.iter_mut().enumerate() {
+            profile.set_index(x.0);
+            x.1.update_profiled(name, profile);
+            profile.index = None;
+        }
+    }
+
     fn accept(&self, name: &str, probe: &mut dyn Probe) {
         for x in self.iter().enumerate() {
             let name = format!("{}${}", name, x.0);
             x.1.accept(&name, probe);
         }
     }
+
+    fn accept_mut(&mut self, name: &str, probe: &mut dyn ProbeMut) {
+        for x in self.iter_mut().enumerate() {
+            let name = format!("{}${}", name, x.0);
+            x.1.accept_mut(&name, probe);
+        }
+    }
 }
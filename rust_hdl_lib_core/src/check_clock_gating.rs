@@ -0,0 +1,133 @@
+use crate::ast::{Verilog, VerilogExpression};
+use crate::atom::{get_atom_typename, Atom};
+use crate::block::Block;
+use crate::check_error::{CheckError, PathedName, PathedNameList};
+use crate::probe::Probe;
+use crate::probe_path::ProbePath;
+use crate::verilog_visitor::VerilogVisitor;
+use std::collections::HashSet;
+
+// A clock may be passed along as is, but anything else (an AND with an
+// enable, a mux between clocks, an inversion) puts logic in the clock path.
+fn is_plain_copy(e: &VerilogExpression) -> bool {
+    match e {
+        VerilogExpression::Signal(_) => true,
+        VerilogExpression::Paren(x) => is_plain_copy(x),
+        _ => false,
+    }
+}
+
+struct GatedClockFinder<'a> {
+    path: String,
+    clocks: &'a HashSet<String>,
+    failures: PathedNameList,
+}
+
+impl VerilogVisitor for GatedClockFinder<'_> {
+    fn visit_assignment(&mut self, l: &VerilogExpression, r: &VerilogExpression) {
+        if let VerilogExpression::Signal(name) = l {
+            let name = name.trim_end_matches("$next");
+            if self.clocks.contains(name) && !is_plain_copy(r) {
+                self.failures.push(PathedName {
+                    path: self.path.clone(),
+                    name: name.to_owned(),
+                });
+            }
+        }
+    }
+}
+
+#[derive(Default)]
+struct GatedClockScanner {
+    path: ProbePath,
+    clocks: Vec<HashSet<String>>,
+    failures: PathedNameList,
+}
+
+impl Probe for GatedClockScanner {
+    fn visit_start_scope(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_scope(name);
+        self.clocks.push(Default::default());
+    }
+
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_namespace(name);
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        if get_atom_typename(signal) != "clock" {
+            return;
+        }
+        let depth = self.clocks.len();
+        if let Some(port) = self.path.parent_name(name) {
+            self.clocks[depth - 2].insert(port);
+        }
+        self.clocks[depth - 1].insert(self.path.local_name(name));
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.end_namespace();
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, node: &dyn Block) {
+        if let Verilog::Combinatorial(code) = &node.hdl() {
+            let mut finder = GatedClockFinder {
+                path: self.path.block(),
+                clocks: self.clocks.last().unwrap(),
+                failures: vec![],
+            };
+            finder.visit_block(code);
+            self.failures.extend(finder.failures);
+        }
+        self.clocks.pop();
+        self.path.end_scope();
+    }
+}
+
+/// Check a circuit for clocks that are generated with logic, like a clock
+/// that is AND'ed with an enable to turn it off.  RustHDL will happily
+/// simulate these, but in an FPGA, the gate can glitch when the enable
+/// changes while the clock is high, the gated clock leaves the dedicated
+/// clock network, and the tools can no longer work out its timing.  Each
+/// clock that is driven by anything other than another clock is reported.
+/// Use a flip-flop with an enable (like `DFFWithEnable`) to stop a register,
+/// or a clock gate (like `ClockGate`, or one of the vendor specific ones in
+/// the FPGA support library) to stop the clock itself.  Blocks that are
+/// written in Verilog directly are not checked.  Because some designs do
+/// need to forward an inverted clock (to a pin, for example), this check is
+/// not part of [check_all](crate::check_error::check_all).
+/// ```rust
+/// use rust_hdl_lib_core::prelude::*;
+/// use rust_hdl_lib_core::check_error::CheckError;
+/// use rust_hdl_lib_core::check_clock_gating::check_clock_gating;
+///
+/// #[derive(LogicBlock, Default)]
+/// struct PowerSaver {
+///    pub clock: Signal<In, Clock>,
+///    pub tick: Signal<In, Bit>,
+///    pub enable: Signal<In, Bit>,
+///    pub slow_clock: Signal<Out, Clock>,
+/// }
+///
+/// impl Logic for PowerSaver {
+///     #[hdl_gen]
+///     fn update(&mut self) {
+///         self.slow_clock.next = (self.tick.val() & self.enable.val()).into(); // <-- gated clock
+///     }
+/// }
+///
+/// let mut uut = PowerSaver::default(); uut.connect_all();
+/// match check_clock_gating(&uut) {
+///     Err(CheckError::GatedClocks(clocks)) => assert_eq!(clocks[0].name, "slow_clock"),
+///     _ => panic!("The gated clock should have been flagged"),
+/// }
+/// ```
+pub fn check_clock_gating(uut: &dyn Block) -> Result<(), CheckError> {
+    let mut visitor = GatedClockScanner::default();
+    uut.accept("uut", &mut visitor);
+    if visitor.failures.is_empty() {
+        Ok(())
+    } else {
+        Err(CheckError::GatedClocks(visitor.failures))
+    }
+}
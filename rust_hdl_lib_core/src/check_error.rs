@@ -1,6 +1,8 @@
 use crate::block::Block;
 use crate::check_connected::check_connected;
 use crate::check_logic_loops::check_logic_loops;
+use crate::check_reset_domains::ResetCrossing;
+use crate::check_widths::WidthLint;
 use crate::check_write_inputs::check_inputs_not_written;
 
 use std::collections::HashMap;
@@ -30,6 +32,15 @@ pub enum CheckError {
     LogicLoops(PathedNameList),
     /// The circuit attempts to write to the inputs, which is not allowed in RustHDL.
     WritesToInputs(PathedNameList),
+    /// The circuit uses signal widths in a way that is likely to wrap silently (see
+    /// [check_widths](crate::check_widths::check_widths))
+    SuspiciousWidths(Vec<WidthLint>),
+    /// The circuit drives clocks with logic, like an enable AND'ed into the clock (see
+    /// [check_clock_gating](crate::check_clock_gating::check_clock_gating))
+    GatedClocks(PathedNameList),
+    /// The circuit samples resets in the wrong clock domain (see
+    /// [check_reset_domains](crate::check_reset_domains::check_reset_domains))
+    ResetDomainCrossings(Vec<ResetCrossing>),
 }
 
 /// This is a helper function used to check a [Block] for connection, loops, and
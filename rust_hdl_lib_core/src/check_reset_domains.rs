@@ -0,0 +1,196 @@
+use crate::ast::Verilog;
+use crate::block::Block;
+use crate::check_error::{CheckError, PathedName};
+use crate::probe::Probe;
+use crate::probe_path::ProbePath;
+use crate::signal_drivers::SignalDrivers;
+use crate::verilog_gen::verilog_signal_flow_with_conditions;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt::{Display, Formatter};
+
+/// A reset that is sampled in the wrong clock domain, as found by [check_reset_domains].
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResetCrossing {
+    /// The reset (e.g., `uut$reset`, or `uut$fast_reset$reset_out`)
+    pub reset: String,
+    /// The clock domain the reset is released in, or `None` if it is asynchronous
+    pub reset_domain: Option<String>,
+    /// The input of the register that samples the reset (e.g., `d` of `uut$counter`)
+    pub sampled_by: PathedName,
+    /// The clock domain of that register
+    pub domain: String,
+}
+
+impl Display for ResetCrossing {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match &self.reset_domain {
+            Some(domain) => write!(f, "reset {} (released in {})", self.reset, domain)?,
+            None => write!(f, "asynchronous reset {}", self.reset)?,
+        }
+        write!(
+            f,
+            " is sampled by {}${} in {}",
+            self.sampled_by.path, self.sampled_by.name, self.domain
+        )
+    }
+}
+
+// A register input (or output), and the clock it is sampled with
+struct Register {
+    signal: PathedName,
+    clock: String,
+}
+
+impl Register {
+    fn full_name(&self) -> String {
+        format!("{}${}", self.signal.path, self.signal.name)
+    }
+}
+
+#[derive(Default)]
+struct ResetScanner {
+    path: ProbePath,
+    fanout: HashMap<String, Vec<String>>,
+    drivers: SignalDrivers,
+    register_inputs: Vec<Register>,
+    register_outputs: Vec<Register>,
+    // The clock, input and output of each reset synchronizer
+    synchronizers: Vec<(String, String, String)>,
+}
+
+impl Probe for ResetScanner {
+    fn visit_start_scope(&mut self, name: &str, node: &dyn Block) {
+        self.path.start_scope(name);
+        let path = self.path.block();
+        if let Verilog::Combinatorial(code) = &node.hdl() {
+            for (from, to) in verilog_signal_flow_with_conditions(code) {
+                let (from, to) = (self.path.full_name(&from), self.path.full_name(&to));
+                self.fanout
+                    .entry(from.clone())
+                    .or_default()
+                    .push(to.clone());
+                self.drivers.add(from, to);
+            }
+        }
+        for info in node.timing() {
+            let clock = self.path.full_name(&info.clock);
+            let register = |name: &String| Register {
+                signal: PathedName {
+                    path: path.clone(),
+                    name: name.clone(),
+                },
+                clock: clock.clone(),
+            };
+            self.register_inputs
+                .extend(info.inputs.iter().map(register));
+            self.register_outputs
+                .extend(info.outputs.iter().map(register));
+        }
+        for info in node.resets() {
+            self.synchronizers.push((
+                self.path.full_name(&info.clock),
+                self.path.full_name(&info.reset_in),
+                self.path.full_name(&info.reset_out),
+            ));
+        }
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.end_scope();
+    }
+}
+
+impl ResetScanner {
+    // Everything the signal flows into through logic
+    fn reach(&self, signal: &str) -> HashSet<String> {
+        let mut seen = HashSet::new();
+        let mut pending = vec![signal.to_string()];
+        while let Some(signal) = pending.pop() {
+            if seen.insert(signal.clone()) {
+                if let Some(targets) = self.fanout.get(&signal) {
+                    pending.extend(targets.iter().cloned());
+                }
+            }
+        }
+        seen
+    }
+    // The resets in the circuit, and the domains they are released in.  The output
+    // of a synchronizer is a reset in the domain of its clock.  Whatever drives the
+    // input of a synchronizer is also a reset - in the domain of the register that
+    // drives it, or asynchronous if it comes from outside of the circuit.
+    fn resets(&self) -> BTreeMap<String, Option<String>> {
+        let mut resets = BTreeMap::new();
+        for (clock, _, reset_out) in &self.synchronizers {
+            resets.insert(reset_out.clone(), Some(self.drivers.clock_source(clock)));
+        }
+        for (_, reset_in, _) in &self.synchronizers {
+            for root in self.drivers.roots(reset_in) {
+                if resets.contains_key(&root) {
+                    continue;
+                }
+                let domain = self
+                    .register_outputs
+                    .iter()
+                    .find(|x| x.full_name() == root)
+                    .map(|x| self.drivers.clock_source(&x.clock));
+                resets.insert(root, domain);
+            }
+        }
+        resets
+    }
+}
+
+/// Check a circuit for resets that are sampled in the wrong clock domain.  A reset
+/// that comes from outside of the circuit (like a button, or a PLL lock indicator) is
+/// asynchronous to every clock.  If a register samples it directly, the release of the
+/// reset can make the register metastable, and registers that are meant to leave
+/// reset together can do so a clock apart.  The same goes for a reset that is released
+/// in one clock domain, and sampled in another.  Each clock domain should have its own
+/// reset synchronizer (like `ResetSynchronizer`), which asserts its reset as soon as
+/// the input reset is asserted, but releases it in step with the clock of the domain.
+///
+/// The reset synchronizers are what tells this check which signals are resets.  The
+/// output of each one is a reset in the domain of its clock, and whatever drives its
+/// input is an asynchronous reset (or a reset in the domain of the register that drives
+/// it).  Every register that samples a reset (in the logic that computes its input, or
+/// in the conditions that decide it) in a different clock domain is reported.  Clocks
+/// that are driven from the same source are in the same domain, as they are in
+/// [hierarchy_report](crate::hierarchy_report::hierarchy_report).  Blocks that are
+/// written in Verilog directly are only seen through their timing information.  As
+/// with [check_clock_gating](crate::check_clock_gating::check_clock_gating), this check
+/// is not part of [check_all](crate::check_error::check_all).
+/// ```rust,ignore
+/// let mut uut = TwoDomains::default(); uut.connect_all();
+/// if let Err(CheckError::ResetDomainCrossings(crossings)) = check_reset_domains(&uut) {
+///     for crossing in crossings {
+///         println!("{}", crossing);
+///     }
+/// }
+/// ```
+pub fn check_reset_domains(uut: &dyn Block) -> Result<(), CheckError> {
+    let mut scanner = ResetScanner::default();
+    uut.accept("uut", &mut scanner);
+    let mut crossings = vec![];
+    for (reset, reset_domain) in scanner.resets() {
+        let reach = scanner.reach(&reset);
+        for register in &scanner.register_inputs {
+            if !reach.contains(&register.full_name()) {
+                continue;
+            }
+            let domain = scanner.drivers.clock_source(&register.clock);
+            if reset_domain.as_ref() != Some(&domain) {
+                crossings.push(ResetCrossing {
+                    reset: reset.clone(),
+                    reset_domain: reset_domain.clone(),
+                    sampled_by: register.signal.clone(),
+                    domain,
+                });
+            }
+        }
+    }
+    if crossings.is_empty() {
+        Ok(())
+    } else {
+        Err(CheckError::ResetDomainCrossings(crossings))
+    }
+}
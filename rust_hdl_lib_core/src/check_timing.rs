@@ -331,8 +331,8 @@ impl Probe for TimingChecker {
         }
         let descriptor = &signal.descriptor();
         if let TypeKind::Enum(x) = &descriptor.kind {
-            for label in x {
-                let label = label.replace("::", "$");
+            for variant in x {
+                let label = variant.name.replace("::", "$");
                 let my_id = self.graph.add_signal_node(&SignalNode {
                     name: format!("{}${}", module_path, label),
                     kind: SignalNodeKind::Normal,
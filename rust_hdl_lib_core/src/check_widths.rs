@@ -0,0 +1,305 @@
+use crate::ast::{
+    Verilog, VerilogExpression, VerilogLiteral, VerilogMatch, VerilogOp, VerilogOpUnary,
+};
+use crate::atom::Atom;
+use crate::block::Block;
+use crate::check_error::CheckError;
+use crate::probe::Probe;
+use crate::probe_path::ProbePath;
+use crate::verilog_gen::verilog_expression;
+use crate::verilog_visitor::{walk_binop, walk_match, VerilogVisitor};
+use regex::Regex;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::OnceLock;
+
+/// The kinds of suspicious width usage found by [check_widths].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum WidthLintKind {
+    /// Two values of different widths are compared.
+    MixedWidthComparison,
+    /// A constant is compared with (or assigned to, or matched against) a signal
+    /// that is too narrow to ever hold it - e.g., an 8 bit counter that is
+    /// compared with 300 will wrap before it gets there.
+    ConstantOutOfRange,
+    /// A value is assigned to a narrower signal, and the upper bits are lost.
+    TruncatingAssignment,
+    /// Two signals are added and the sum is stored in a signal that is no
+    /// wider than they are, so the carry is lost.
+    AdditionWithoutCarry,
+}
+
+/// A single finding from [check_widths].
+#[derive(Clone, Debug, PartialEq)]
+pub struct WidthLint {
+    /// The path to the block with the suspicious code (e.g., `uut$counter`)
+    pub path: String,
+    /// What kind of problem was found
+    pub kind: WidthLintKind,
+    /// A human readable description of the problem
+    pub description: String,
+}
+
+impl Display for WidthLint {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} in {}: {}", self.kind, self.path, self.description)
+    }
+}
+
+fn literal(e: &VerilogExpression) -> Option<&VerilogLiteral> {
+    match e {
+        VerilogExpression::Literal(x) => Some(x),
+        VerilogExpression::Paren(x) => literal(x),
+        _ => None,
+    }
+}
+
+fn is_comparison(op: &VerilogOp) -> bool {
+    matches!(
+        op,
+        VerilogOp::Eq
+            | VerilogOp::Ne
+            | VerilogOp::Lt
+            | VerilogOp::Le
+            | VerilogOp::Gt
+            | VerilogOp::Ge
+    )
+}
+
+struct WidthLinter<'a> {
+    path: String,
+    widths: &'a HashMap<String, usize>,
+    lints: Vec<WidthLint>,
+}
+
+impl WidthLinter<'_> {
+    fn lint(&mut self, kind: WidthLintKind, description: String) {
+        self.lints.push(WidthLint {
+            path: self.path.clone(),
+            kind,
+            description,
+        });
+    }
+    fn signal_width(&self, name: &str) -> Option<usize> {
+        // All of the elements of an array have the same widths, so the first
+        // one stands in for whatever a loop indexes.
+        static INDEX: OnceLock<Regex> = OnceLock::new();
+        let re = INDEX.get_or_init(|| Regex::new(r"\[[^\]]*\]").unwrap());
+        let name = re.replace_all(name.trim_end_matches("$next"), "$$0");
+        self.widths.get(name.as_ref()).copied()
+    }
+    // The width of an expression, if it can be worked out.  Literals take
+    // the width of whatever they are combined with, so they have none.
+    fn width(&self, e: &VerilogExpression) -> Option<usize> {
+        match e {
+            VerilogExpression::Signal(x) => self.signal_width(x),
+            VerilogExpression::Literal(_) => None,
+            VerilogExpression::Cast(_, bits) => Some(*bits),
+            VerilogExpression::Signed(x)
+            | VerilogExpression::Unsigned(x)
+            | VerilogExpression::Paren(x) => self.width(x),
+            VerilogExpression::Binary(l, op, r) => match op {
+                VerilogOp::Shl | VerilogOp::Shr => self.width(l),
+                VerilogOp::LogicalAnd | VerilogOp::LogicalOr => Some(1),
+                _ if is_comparison(op) => Some(1),
+                _ => match (self.width(l), self.width(r)) {
+                    (Some(l), Some(r)) => Some(l.max(r)),
+                    (l, r) => l.or(r),
+                },
+            },
+            VerilogExpression::Unary(op, x) => match op {
+                VerilogOpUnary::Not | VerilogOpUnary::Neg => self.width(x),
+                _ => Some(1),
+            },
+            VerilogExpression::Index(_, _) => Some(1),
+            VerilogExpression::Slice(_, width, _) => Some(*width),
+            VerilogExpression::IndexReplace(x, _, _) => self.width(x),
+            VerilogExpression::Concat(x) => x.iter().map(|x| self.width(x)).sum(),
+        }
+    }
+    fn check_constant(&mut self, e: &VerilogExpression, width: usize, constant: &str, bits: usize) {
+        if bits > width {
+            self.lint(
+                WidthLintKind::ConstantOutOfRange,
+                format!(
+                    "{} is {} bits wide, but is used with {}, which needs {} bits",
+                    verilog_expression(e),
+                    width,
+                    constant,
+                    bits
+                ),
+            );
+        }
+    }
+    fn check_literal(&mut self, e: &VerilogExpression, width: usize, constant: &VerilogLiteral) {
+        self.check_constant(e, width, &constant.to_string(), constant.significant_bits());
+    }
+}
+
+impl VerilogVisitor for WidthLinter<'_> {
+    fn visit_assignment(&mut self, l: &VerilogExpression, r: &VerilogExpression) {
+        self.visit_expression(r);
+        let target = match self.width(l) {
+            Some(x) => x,
+            None => return,
+        };
+        if let Some(constant) = literal(r) {
+            self.check_literal(l, target, constant);
+            return;
+        }
+        if let Some(value) = self.width(r) {
+            if value > target {
+                self.lint(
+                    WidthLintKind::TruncatingAssignment,
+                    format!(
+                        "{} bits are assigned to {}, which is only {} bits wide",
+                        value,
+                        verilog_expression(l),
+                        target
+                    ),
+                );
+            }
+        }
+        if let VerilogExpression::Binary(a, VerilogOp::Add, b) = r {
+            if let (Some(a_width), Some(b_width)) = (self.width(a), self.width(b)) {
+                // Adding a single bit is an increment, like a counter
+                if a_width.min(b_width) > 1 && target <= a_width.max(b_width) {
+                    self.lint(
+                        WidthLintKind::AdditionWithoutCarry,
+                        format!(
+                            "the sum {} is stored in {} bits, so the carry is lost",
+                            verilog_expression(r),
+                            target
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    fn visit_binop(&mut self, l: &VerilogExpression, o: &VerilogOp, r: &VerilogExpression) {
+        if is_comparison(o) {
+            match (self.width(l), self.width(r), literal(l), literal(r)) {
+                (Some(l_width), Some(r_width), _, _) if l_width != r_width => {
+                    self.lint(
+                        WidthLintKind::MixedWidthComparison,
+                        format!(
+                            "{} ({} bits) is compared with {} ({} bits)",
+                            verilog_expression(l),
+                            l_width,
+                            verilog_expression(r),
+                            r_width
+                        ),
+                    );
+                }
+                (Some(width), None, _, Some(constant)) => self.check_literal(l, width, constant),
+                (None, Some(width), Some(constant), _) => self.check_literal(r, width, constant),
+                _ => {}
+            }
+        }
+        walk_binop(self, l, o, r);
+    }
+
+    fn visit_match(&mut self, m: &VerilogMatch) {
+        if let Some(width) = self.width(&m.test) {
+            for case in &m.cases {
+                for label in case.condition.split(", ") {
+                    if let Ok(value) = label.parse::<u128>() {
+                        let bits = (u128::BITS - value.leading_zeros()) as usize;
+                        self.check_constant(&m.test, width, label, bits);
+                    }
+                }
+            }
+        }
+        walk_match(self, m);
+    }
+}
+
+#[derive(Default)]
+struct WidthScanner {
+    path: ProbePath,
+    widths: Vec<HashMap<String, usize>>,
+    lints: Vec<WidthLint>,
+}
+
+impl Probe for WidthScanner {
+    fn visit_start_scope(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_scope(name);
+        self.widths.push(Default::default());
+    }
+
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_namespace(name);
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        let depth = self.widths.len();
+        if let Some(port) = self.path.parent_name(name) {
+            self.widths[depth - 2].insert(port, signal.bits());
+        }
+        self.widths[depth - 1].insert(self.path.local_name(name), signal.bits());
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.end_namespace();
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, node: &dyn Block) {
+        if let Verilog::Combinatorial(code) = &node.hdl() {
+            let mut linter = WidthLinter {
+                path: self.path.block(),
+                widths: self.widths.last().unwrap(),
+                lints: vec![],
+            };
+            linter.visit_block(code);
+            self.lints.extend(linter.lints);
+        }
+        self.widths.pop();
+        self.path.end_scope();
+    }
+}
+
+/// Check a circuit for suspicious uses of signal widths.  Because the
+/// widths of [Bits](crate::bits::Bits) are part of their types, most width problems
+/// are caught by the compiler.  The ones that are not usually involve
+/// a wrap around that happens silently - a counter that can never reach
+/// the constant it is compared with, a `bit_cast` that drops the upper bits,
+/// or a sum that has no room for the carry.  This check looks for those,
+/// and reports each one as a [WidthLint].  Some of them may be intentional
+/// (a counter that is meant to wrap, for example), so this check is not
+/// part of [check_all](crate::check_error::check_all).
+/// ```rust
+/// use rust_hdl_lib_core::prelude::*;
+/// use rust_hdl_lib_core::check_error::CheckError;
+/// use rust_hdl_lib_core::check_widths::{check_widths, WidthLintKind};
+///
+/// #[derive(LogicBlock, Default)]
+/// struct Timeout {
+///    pub count: Signal<In, Bits<8>>,
+///    pub expired: Signal<Out, Bit>,
+/// }
+///
+/// impl Logic for Timeout {
+///     #[hdl_gen]
+///     fn update(&mut self) {
+///         self.expired.next = self.count.val() == 300; // <-- never true
+///     }
+/// }
+///
+/// let mut uut = Timeout::default(); uut.connect_all();
+/// match check_widths(&uut) {
+///     Err(CheckError::SuspiciousWidths(lints)) => {
+///         assert_eq!(lints[0].kind, WidthLintKind::ConstantOutOfRange)
+///     }
+///     _ => panic!("The counter should have been flagged"),
+/// }
+/// ```
+pub fn check_widths(uut: &dyn Block) -> Result<(), CheckError> {
+    let mut visitor = WidthScanner::default();
+    uut.accept("uut", &mut visitor);
+    if visitor.lints.is_empty() {
+        Ok(())
+    } else {
+        Err(CheckError::SuspiciousWidths(visitor.lints))
+    }
+}
@@ -1,10 +1,10 @@
 use crate::ast::VerilogLiteral;
 use crate::atom::{Atom, AtomKind};
 use crate::bits::Bits;
-use crate::block::Block;
+use crate::block::{Block, EventState};
 use crate::constraint::PinConstraint;
 use crate::logic::Logic;
-use crate::probe::Probe;
+use crate::probe::{Probe, ProbeMut};
 use crate::signal::{get_signal_id, Signal};
 use crate::sim_assert_eq;
 use crate::simulate::{Sim, Simulation};
@@ -136,7 +136,20 @@ impl<T: Synth> Block for Constant<T> {
         false
     }
 
+    fn update_changed(
+        &mut self,
+        _state: &mut EventState,
+        _owner_updated: bool,
+        _ports_written: bool,
+    ) -> bool {
+        true
+    }
+
     fn accept(&self, name: &str, probe: &mut dyn Probe) {
         probe.visit_atom(name, self);
     }
+
+    fn accept_mut(&mut self, name: &str, probe: &mut dyn ProbeMut) {
+        probe.visit_atom(name, self);
+    }
 }
@@ -93,6 +93,14 @@ pub struct OutputTimingConstraint {
     pub to_signal_bit: Option<usize>,
 }
 
+// Relaxes the paths through a signal to `cycles` periods of `clock` (the net
+// named in its periodic constraint).
+#[derive(Clone, Debug)]
+pub struct MulticycleTiming {
+    pub clock: String,
+    pub cycles: usize,
+}
+
 #[derive(Clone, Debug)]
 pub enum Timing {
     Periodic(PeriodicTiming),
@@ -102,6 +110,8 @@ pub enum Timing {
     VivadoOutputTiming(VivadoOutputTimingConstraint),
     VivadoClockGroup(Vec<Vec<String>>),
     VivadoFalsePath(FalsePathRegexp),
+    FalsePath,
+    Multicycle(MulticycleTiming),
     Custom(String),
 }
 
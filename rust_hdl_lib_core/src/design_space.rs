@@ -0,0 +1,245 @@
+use crate::block::Block;
+use crate::module_defines::generate_verilog;
+use crate::yosys::{yosys_estimate, ResourceEstimate};
+use std::fmt::{Debug, Display};
+
+/// One point of a [DesignSpace] - the values of the parameters, and whatever was
+/// measured at them.  Each measurement is optional, so a point can be estimated,
+/// simulated, or both.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DesignPoint {
+    /// The names and values of the parameters of the point
+    pub parameters: Vec<(String, String)>,
+    /// The size and depth of the design (see [DesignPoint::estimate])
+    pub resources: Option<ResourceEstimate>,
+    /// Other measurements (like the latency seen in a simulation), by name
+    pub metrics: Vec<(String, f64)>,
+    /// Anything that went wrong at this point (a failed synthesis or simulation)
+    pub errors: Vec<String>,
+    prefix: String,
+}
+
+impl DesignPoint {
+    /// Synthesize the design with yosys, and record its size and logic depth (see
+    /// [yosys_estimate]).  If the synthesis fails, the error is recorded instead.
+    pub fn estimate<U: Block>(&mut self, mut uut: U) -> &mut Self {
+        uut.connect_all();
+        match yosys_estimate(&self.prefix, &generate_verilog(&uut)) {
+            Ok(resources) => self.resources = Some(resources),
+            Err(e) => self.errors.push(format!("synthesis failed: {:?}", e)),
+        }
+        self
+    }
+    /// Record a measurement
+    pub fn measure(&mut self, name: &str, value: f64) -> &mut Self {
+        self.metrics.push((name.into(), value));
+        self
+    }
+    /// Record the result of a check (e.g., a simulation run).  An error is kept
+    /// (under the given name), so that the point is flagged in the report.
+    pub fn check<E: Debug>(&mut self, name: &str, result: Result<(), E>) -> &mut Self {
+        if let Err(e) = result {
+            self.errors.push(format!("{} failed: {:?}", name, e));
+        }
+        self
+    }
+    /// The value of a parameter
+    pub fn parameter(&self, name: &str) -> Option<&str> {
+        lookup(&self.parameters, name).map(|x| x.as_str())
+    }
+    /// The value of a measurement
+    pub fn metric(&self, name: &str) -> Option<f64> {
+        lookup(&self.metrics, name).copied()
+    }
+}
+
+fn lookup<'a, T>(list: &'a [(String, T)], name: &str) -> Option<&'a T> {
+    list.iter().find(|x| x.0 == name).map(|x| &x.1)
+}
+
+// Add the names from the list that are not already in the columns
+fn add_columns<T>(columns: &mut Vec<String>, list: &[(String, T)]) {
+    for (name, _) in list {
+        if !columns.contains(name) {
+            columns.push(name.clone());
+        }
+    }
+}
+
+fn csv_field(x: &str) -> String {
+    if x.contains([',', '"', '\n']) {
+        format!("\"{}\"", x.replace('"', "\"\""))
+    } else {
+        x.to_string()
+    }
+}
+
+/// Most widgets are sized by their const generic parameters (the depth of a FIFO,
+/// the width of a counter, the CAS delay of an SDRAM), and picking them is a matter
+/// of trading area for speed or latency.  A [DesignSpace] collects the measurements
+/// of a design at a number of points, and writes them out as a CSV file (one row
+/// per point) for comparison.
+///
+/// Const generic parameters have to be known at compile time, so each point is a
+/// separate instantiation of the design.  The [design_points!](crate::design_points)
+/// macro takes care of that, by running the same code for each point of a grid, with
+/// the parameters defined as constants:
+///
+/// ```rust,ignore
+/// let mut space = DesignSpace::new("fifo");
+/// design_points!(space, (N, NP1) in [(4, 5), (6, 7), (8, 9)], |point| {
+///     point.estimate(SynchronousFIFO::<Bits<8>, N, NP1, 1>::default());
+///     point.measure("words", (1 << N) as f64);
+/// });
+/// space.save_csv("fifo.csv").unwrap();
+/// ```
+///
+/// The CSV has a column for each parameter, then the resource estimate (cells, LUTs,
+/// flip flops, logic depth and the estimated maximum frequency), then a column for
+/// each measurement, and finally any errors.  A value that was not measured at a
+/// point is left empty.
+#[derive(Clone, Debug)]
+pub struct DesignSpace {
+    name: String,
+    level_delay_nanoseconds: f64,
+    points: Vec<DesignPoint>,
+}
+
+impl DesignSpace {
+    /// Start an empty design space.  The `name` is used to name the directories
+    /// that the synthesis of each point runs in.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            level_delay_nanoseconds: 1.0,
+            points: vec![],
+        }
+    }
+    /// Set the delay of one level of logic (a LUT and its routing), which is used to
+    /// estimate the maximum frequency from the logic depth.  The default of 1ns is
+    /// about right for an ECP5 or an Artix 7.
+    pub fn set_level_delay(&mut self, nanoseconds: f64) {
+        self.level_delay_nanoseconds = nanoseconds;
+    }
+    /// Add a point with the given parameter values, and return it so that it can be
+    /// measured.
+    pub fn point(&mut self, parameters: &[(&str, &dyn Display)]) -> &mut DesignPoint {
+        let prefix = format!("{}_{}", self.name, self.points.len());
+        self.points.push(DesignPoint {
+            parameters: parameters
+                .iter()
+                .map(|(name, value)| (name.to_string(), value.to_string()))
+                .collect(),
+            prefix,
+            ..Default::default()
+        });
+        self.points.last_mut().unwrap()
+    }
+    /// The points, in the order they were added
+    pub fn points(&self) -> &[DesignPoint] {
+        &self.points
+    }
+    /// Write the points out as CSV (with a header row)
+    pub fn to_csv(&self) -> String {
+        let mut parameters = vec![];
+        let mut metrics = vec![];
+        for point in &self.points {
+            add_columns(&mut parameters, &point.parameters);
+            add_columns(&mut metrics, &point.metrics);
+        }
+        let mut header = parameters.clone();
+        header.extend(
+            ["cells", "luts", "flip_flops", "logic_depth", "fmax_mhz"]
+                .iter()
+                .map(|x| x.to_string()),
+        );
+        header.extend(metrics.iter().cloned());
+        header.push("errors".into());
+        let mut rows = vec![header
+            .iter()
+            .map(|x| csv_field(x))
+            .collect::<Vec<_>>()
+            .join(",")];
+        for point in &self.points {
+            let mut row = parameters
+                .iter()
+                .map(|x| point.parameter(x).unwrap_or_default().to_string())
+                .collect::<Vec<_>>();
+            match &point.resources {
+                Some(x) => row.extend([
+                    x.cells.to_string(),
+                    x.luts.to_string(),
+                    x.flip_flops.to_string(),
+                    x.logic_depth.to_string(),
+                    format!("{:.1}", x.fmax_mhz(self.level_delay_nanoseconds)),
+                ]),
+                None => row.extend(vec![String::new(); 5]),
+            }
+            row.extend(
+                metrics
+                    .iter()
+                    .map(|x| point.metric(x).map(|x| x.to_string()).unwrap_or_default()),
+            );
+            row.push(point.errors.join("; "));
+            rows.push(
+                row.iter()
+                    .map(|x| csv_field(x))
+                    .collect::<Vec<_>>()
+                    .join(","),
+            );
+        }
+        rows.join("\n") + "\n"
+    }
+    /// Write the CSV to a file
+    pub fn save_csv(&self, filename: &str) -> std::io::Result<()> {
+        std::fs::write(filename, self.to_csv())
+    }
+}
+
+/// Run the same code for each point of a grid of const generic parameters, adding
+/// a point to a [DesignSpace](crate::design_space::DesignSpace) for each one.  The
+/// parameters are defined as `usize` constants with the given names, so they can be
+/// used as generic arguments, and the point is bound to the name given in the
+/// closure-like body, e.g.,
+///
+/// ```rust,ignore
+/// design_points!(space, (W, D) in [(8, 4), (8, 6), (16, 4)], |point| {
+///     point.estimate(MyWidget::<W, D>::default());
+/// });
+/// ```
+#[macro_export]
+macro_rules! design_points {
+    ($space: expr, ($($param: ident),+) in [], |$point: ident| $body: block) => {};
+    ($space: expr, ($($param: ident),+) in [($($value: expr),+) $(, $rest: tt)*], |$point: ident| $body: block) => {
+        {
+            $(const $param: usize = $value;)+
+            let $point = $space.point(&[$((stringify!($param), &$param as &dyn std::fmt::Display)),+]);
+            $body
+        }
+        $crate::design_points!($space, ($($param),+) in [$($rest),*], |$point| $body);
+    };
+}
+
+#[test]
+fn test_design_space_csv() {
+    let mut space = DesignSpace::new("csv_test");
+    design_points!(space, (N, M) in [(4, 5), (6, 7)], |point| {
+        point.measure("latency", (N + M) as f64);
+        if N == 6 {
+            point.check("simulation", Err::<(), _>("bad, \"really\""));
+        }
+    });
+    space.points[0].resources = Some(ResourceEstimate {
+        cells: 10,
+        luts: 6,
+        flip_flops: 4,
+        logic_depth: 2,
+    });
+    assert_eq!(space.points()[1].parameter("M"), Some("7"));
+    assert_eq!(
+        space.to_csv(),
+        "N,M,cells,luts,flip_flops,logic_depth,fmax_mhz,latency,errors\n\
+         4,5,10,6,4,2,500.0,9,\n\
+         6,7,,,,,,13,\"simulation failed: \"\"bad, \\\"\"really\\\"\"\"\"\"\n"
+    );
+}
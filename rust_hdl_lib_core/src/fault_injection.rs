@@ -0,0 +1,381 @@
+use crate::atom::{Atom, AtomKind};
+use crate::block::Block;
+use crate::probe::{Probe, ProbeMut};
+use crate::probe_path::ProbePath;
+use crate::synth::VCDValue;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::fmt::{Display, Formatter};
+use std::ops::Range;
+
+/// A single event upset - one bit of one signal in the circuit is flipped at a
+/// given time.  The signal is named by its path in the circuit, as in a
+/// [hierarchy report](crate::hierarchy_report::hierarchy_report), e.g.,
+/// `uut$counter$q` for the output of the `counter` flip flop at the top level.
+///
+/// The flip changes the value the signal holds, not the logic that drives it.
+/// A flip flop output keeps the flipped value until the next clock edge loads
+/// it, so flipping the `q` of a flip flop is the way to model an upset in a
+/// register.  Combinational signals are recomputed straight away, so flipping
+/// one of those has no lasting effect.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Fault {
+    /// The simulation time of the upset (in ticks - see [crate::simulate::sim_time::TimeUnit])
+    pub time: u64,
+    /// The path of the signal to upset
+    pub path: String,
+    /// The bit of the signal to flip (0 is the least significant bit)
+    pub bit: usize,
+}
+
+fn flip(value: &VCDValue, bit: usize) -> Option<VCDValue> {
+    let invert = |x: &vcd::Value| match x {
+        vcd::Value::V0 => Some(vcd::Value::V1),
+        vcd::Value::V1 => Some(vcd::Value::V0),
+        _ => None,
+    };
+    match value {
+        VCDValue::Single(x) if bit == 0 => invert(x).map(VCDValue::Single),
+        VCDValue::Vector(x) if bit < x.len() => {
+            let mut x = x.clone();
+            let ndx = x.len() - 1 - bit;
+            x[ndx] = invert(&x[ndx])?;
+            Some(VCDValue::Vector(x))
+        }
+        _ => None,
+    }
+}
+
+// Lists the widths of the signals in a circuit, by path
+#[derive(Default)]
+struct SignalScanner {
+    path: ProbePath,
+    signals: Vec<(String, usize)>,
+}
+
+impl Probe for SignalScanner {
+    fn visit_start_scope(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_scope(name);
+    }
+
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_namespace(name);
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        if signal.kind() != AtomKind::Constant {
+            self.signals
+                .push((self.path.full_name(name), signal.bits()));
+        }
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.end_namespace();
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.end_scope();
+    }
+}
+
+struct FaultFlipper<'a> {
+    path: ProbePath,
+    fault: &'a Fault,
+    found: bool,
+    flipped: bool,
+}
+
+impl ProbeMut for FaultFlipper<'_> {
+    fn visit_start_scope(&mut self, name: &str) {
+        self.path.start_scope(name);
+    }
+
+    fn visit_start_namespace(&mut self, name: &str) {
+        self.path.start_namespace(name);
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &mut dyn Atom) {
+        if self.found || self.path.full_name(name) != self.fault.path {
+            return;
+        }
+        self.found = true;
+        if let Some(value) = flip(&signal.vcd(), self.fault.bit) {
+            self.flipped = signal.set_vcd(&value);
+        }
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str) {
+        self.path.end_namespace();
+    }
+
+    fn visit_end_scope(&mut self, _name: &str) {
+        self.path.end_scope();
+    }
+}
+
+impl Fault {
+    /// Flip the bit in the circuit.  Fails (with a description of the problem)
+    /// if the signal does not exist, or the bit can not be flipped (because the
+    /// signal is a constant, is too narrow, or holds a value that is not a plain
+    /// bit vector, like an enum or an undriven bus).
+    pub fn apply(&self, uut: &mut dyn Block) -> Result<(), String> {
+        let mut flipper = FaultFlipper {
+            path: Default::default(),
+            fault: self,
+            found: false,
+            flipped: false,
+        };
+        uut.accept_mut("uut", &mut flipper);
+        if !flipper.found {
+            return Err(format!(
+                "Fault at {} targets a signal the circuit does not have: {}",
+                self.time, self.path
+            ));
+        }
+        if !flipper.flipped {
+            return Err(format!(
+                "Fault at {} can not flip bit {} of {}",
+                self.time, self.bit, self.path
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// A list of [Fault]s to inject into a circuit as it is simulated, for testing the
+/// logic that is supposed to cope with them (watchdogs, TMR, parity and CRC checks,
+/// and so on).  Attach it to a [Simulation](crate::simulate::Simulation) with
+/// [add_fault_campaign](crate::simulate::Simulation::add_fault_campaign), which
+/// explains how the effect of each fault is judged.
+///
+/// After each fault, the simulation watches the circuit for the `window` of the
+/// campaign (or until the next fault, if that comes first), so the faults should be
+/// spaced far enough apart for the circuit to respond to each one, and to recover
+/// from it before the next.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaultCampaign {
+    faults: Vec<Fault>,
+    window: u64,
+}
+
+impl FaultCampaign {
+    /// Start an empty campaign.  The `window` is how long (in ticks) to watch the
+    /// circuit after each fault.
+    pub fn new(window: u64) -> Self {
+        assert!(window > 0);
+        Self {
+            faults: vec![],
+            window,
+        }
+    }
+    /// Flip `bit` of the signal at `path` at `time` (in ticks)
+    pub fn inject(&mut self, time: u64, path: &str, bit: usize) {
+        self.faults.push(Fault {
+            time,
+            path: path.into(),
+            bit,
+        });
+        self.faults.sort_by_key(|x| x.time);
+    }
+    /// Add `count` faults at random, in a random bit of a random one of the `targets`,
+    /// at a random time in `times`.  The widths of the targets are looked up in `uut`,
+    /// and the faults are spaced at least a `window` apart (so `times` must be long
+    /// enough to hold them).  The same `seed` gives the same faults, so a failing
+    /// campaign can be repeated.
+    pub fn inject_random(
+        &mut self,
+        uut: &dyn Block,
+        targets: &[&str],
+        count: usize,
+        times: Range<u64>,
+        seed: u64,
+    ) {
+        let mut scanner = SignalScanner::default();
+        uut.accept("uut", &mut scanner);
+        let targets = targets
+            .iter()
+            .map(|target| {
+                scanner
+                    .signals
+                    .iter()
+                    .find(|(path, _)| path == target)
+                    .cloned()
+                    .unwrap_or_else(|| panic!("The circuit has no signal {}", target))
+            })
+            .collect::<Vec<_>>();
+        assert!(!targets.is_empty());
+        let slots = (times.end - times.start) / self.window;
+        assert!(
+            slots >= count as u64,
+            "Cannot fit {} faults a window apart in the given times",
+            count
+        );
+        let mut rng = StdRng::seed_from_u64(seed);
+        let mut slots = rand::seq::index::sample(&mut rng, slots as usize, count).into_vec();
+        slots.sort_unstable();
+        for slot in slots {
+            let (path, width) = &targets[rng.gen_range(0..targets.len())];
+            let time = times.start + slot as u64 * self.window + rng.gen_range(0..self.window);
+            let bit = rng.gen_range(0..*width);
+            self.inject(time, path, bit);
+        }
+    }
+    /// The faults, in the order they will be injected
+    pub fn faults(&self) -> &[Fault] {
+        &self.faults
+    }
+    /// How long (in ticks) the circuit is watched after each fault
+    pub fn window(&self) -> u64 {
+        self.window
+    }
+}
+
+/// What became of a [Fault] (see [FaultRecord::outcome]).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum FaultOutcome {
+    /// The error detection logic flagged the fault
+    Detected,
+    /// The fault had no visible effect (it was outvoted, corrected, or overwritten)
+    Masked,
+    /// The fault corrupted the circuit, and nothing flagged it
+    Undetected,
+}
+
+/// The effect of one [Fault] on the circuit.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FaultRecord {
+    /// The fault that was injected
+    pub fault: Fault,
+    /// How long (in ticks) after the fault it was detected (if it was)
+    pub detected_after: Option<u64>,
+    /// How long (in ticks) after the fault the circuit was first seen to be corrupted (if it was)
+    pub corrupted_after: Option<u64>,
+}
+
+impl FaultRecord {
+    /// Classify the fault.  A fault that was detected counts as detected, whether
+    /// or not it also corrupted the circuit.
+    pub fn outcome(&self) -> FaultOutcome {
+        match (self.detected_after, self.corrupted_after) {
+            (Some(_), _) => FaultOutcome::Detected,
+            (None, None) => FaultOutcome::Masked,
+            (None, Some(_)) => FaultOutcome::Undetected,
+        }
+    }
+}
+
+/// The results of a [FaultCampaign], one [FaultRecord] for each fault that was
+/// injected before the simulation ended.  The report prints as a summary, followed
+/// by the undetected faults (the ones that need attention).
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct FaultReport {
+    records: Vec<FaultRecord>,
+}
+
+impl FaultReport {
+    /// The faults that were injected, in order, with their effects
+    pub fn records(&self) -> &[FaultRecord] {
+        &self.records
+    }
+    /// The number of faults with the given outcome
+    pub fn count(&self, outcome: FaultOutcome) -> usize {
+        self.records
+            .iter()
+            .filter(|x| x.outcome() == outcome)
+            .count()
+    }
+    /// The faults that corrupted the circuit without being detected
+    pub fn undetected(&self) -> Vec<&FaultRecord> {
+        self.records
+            .iter()
+            .filter(|x| x.outcome() == FaultOutcome::Undetected)
+            .collect()
+    }
+}
+
+impl Display for FaultReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(
+            f,
+            "{} faults injected: {} detected, {} masked, {} undetected",
+            self.records.len(),
+            self.count(FaultOutcome::Detected),
+            self.count(FaultOutcome::Masked),
+            self.count(FaultOutcome::Undetected)
+        )?;
+        for record in self.undetected() {
+            writeln!(
+                f,
+                "  undetected: bit {} of {} at {} (corrupted after {})",
+                record.fault.bit,
+                record.fault.path,
+                record.fault.time,
+                record.corrupted_after.unwrap()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Injects the faults of a campaign, and watches the circuit after each one.
+pub(crate) struct FaultInjector<T> {
+    campaign: FaultCampaign,
+    next: usize,
+    detected: Box<dyn Fn(&T) -> bool>,
+    corrupted: Box<dyn Fn(&T) -> bool>,
+    report: FaultReport,
+}
+
+impl<T: Block> FaultInjector<T> {
+    pub(crate) fn new(
+        campaign: FaultCampaign,
+        detected: Box<dyn Fn(&T) -> bool>,
+        corrupted: Box<dyn Fn(&T) -> bool>,
+    ) -> Self {
+        Self {
+            campaign,
+            next: 0,
+            detected,
+            corrupted,
+            report: Default::default(),
+        }
+    }
+    pub(crate) fn clear(&mut self) {
+        self.next = 0;
+        self.report.records.clear();
+    }
+    /// The time of the next fault (if there is one)
+    pub(crate) fn next_time(&self) -> Option<u64> {
+        self.campaign.faults.get(self.next).map(|x| x.time)
+    }
+    /// Inject the next fault
+    pub(crate) fn inject(&mut self, x: &mut T) -> Result<(), String> {
+        let fault = &self.campaign.faults[self.next];
+        self.next += 1;
+        fault.apply(x)?;
+        self.report.records.push(FaultRecord {
+            fault: fault.clone(),
+            detected_after: None,
+            corrupted_after: None,
+        });
+        Ok(())
+    }
+    /// Check the circuit for the effects of the last fault
+    pub(crate) fn observe(&mut self, x: &T, time: u64) {
+        let window = self.campaign.window;
+        let record = match self.report.records.last_mut() {
+            Some(record) if time < record.fault.time + window => record,
+            _ => return,
+        };
+        let elapsed = time - record.fault.time;
+        if record.detected_after.is_none() && (self.detected)(x) {
+            record.detected_after = Some(elapsed);
+        }
+        if record.corrupted_after.is_none() && (self.corrupted)(x) {
+            record.corrupted_after = Some(elapsed);
+        }
+    }
+    pub(crate) fn report(&self) -> &FaultReport {
+        &self.report
+    }
+}
@@ -0,0 +1,336 @@
+use crate::ast::Verilog;
+use crate::atom::Atom;
+use crate::block::Block;
+use crate::probe::Probe;
+use crate::probe_path::ProbePath;
+use crate::signal_drivers::SignalDrivers;
+use crate::verilog_gen::verilog_signal_flow;
+use std::collections::{BTreeSet, HashMap};
+
+/// A signal (or constant) that belongs to a block in a [BlockReport].
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignalReport {
+    /// The name of the signal within the block (e.g., `counter$d`)
+    pub name: String,
+    /// The kind of the signal (e.g., `InputParameter` or `LocalSignal`)
+    pub kind: String,
+    /// The width of the signal in bits
+    pub width: usize,
+    /// The name of the type of the signal (e.g., `Bits::<8>`)
+    pub type_name: String,
+    /// For clocks, the clock domain it belongs to.  This is the full
+    /// name of the signal that drives the clock (e.g., `uut$clock`).
+    pub clock_domain: Option<String>,
+}
+
+/// A connection inside a block that involves the ports of one of its children.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Connection {
+    /// The signal that drives the connection (e.g., `clock` or `counter$q`)
+    pub from: String,
+    /// The signal that is driven (e.g., `counter$clock`)
+    pub to: String,
+}
+
+/// The report on one block of a circuit, as generated by [hierarchy_report].
+#[derive(Clone, Debug, PartialEq)]
+pub struct BlockReport {
+    /// The name of the block in its parent
+    pub name: String,
+    /// The full path to the block (e.g., `uut$counter`)
+    pub path: String,
+    /// The signals that belong to the block
+    pub signals: Vec<SignalReport>,
+    /// The connections between the block and its children, and between the children
+    pub connections: Vec<Connection>,
+    /// The children of the block
+    pub children: Vec<BlockReport>,
+    /// The clock domains used by the block
+    pub clock_domains: Vec<String>,
+}
+
+fn json_string(x: &str) -> String {
+    let mut ret = String::from("\"");
+    for c in x.chars() {
+        match c {
+            '"' => ret += "\\\"",
+            '\\' => ret += "\\\\",
+            '\n' => ret += "\\n",
+            '\r' => ret += "\\r",
+            '\t' => ret += "\\t",
+            '<' => ret += "\\u003c",
+            '>' => ret += "\\u003e",
+            '&' => ret += "\\u0026",
+            c if (c as u32) < 0x20 => ret += &format!("\\u{:04x}", c as u32),
+            c => ret.push(c),
+        }
+    }
+    ret.push('"');
+    ret
+}
+
+fn json_list<T>(x: &[T], f: impl Fn(&T) -> String) -> String {
+    format!("[{}]", x.iter().map(f).collect::<Vec<_>>().join(","))
+}
+
+impl SignalReport {
+    fn to_json(&self) -> String {
+        format!(
+            "{{\"name\":{},\"kind\":{},\"width\":{},\"type\":{},\"clock_domain\":{}}}",
+            json_string(&self.name),
+            json_string(&self.kind),
+            self.width,
+            json_string(&self.type_name),
+            self.clock_domain
+                .as_deref()
+                .map(json_string)
+                .unwrap_or_else(|| "null".into())
+        )
+    }
+}
+
+impl BlockReport {
+    /// Find the report for a block by its full path (e.g., `uut$counter`).
+    pub fn find(&self, path: &str) -> Option<&BlockReport> {
+        if self.path == path {
+            return Some(self);
+        }
+        self.children.iter().find_map(|x| x.find(path))
+    }
+    /// Find a signal in this block by name.
+    pub fn signal(&self, name: &str) -> Option<&SignalReport> {
+        self.signals.iter().find(|x| x.name == name)
+    }
+    /// The report (including all of the children) as a JSON document.
+    pub fn to_json(&self) -> String {
+        format!(
+            "{{\"name\":{},\"path\":{},\"clock_domains\":{},\"signals\":{},\"connections\":{},\"children\":{}}}",
+            json_string(&self.name),
+            json_string(&self.path),
+            json_list(&self.clock_domains, |x| json_string(x)),
+            json_list(&self.signals, |x| x.to_json()),
+            json_list(&self.connections, |x| format!(
+                "{{\"from\":{},\"to\":{}}}",
+                json_string(&x.from),
+                json_string(&x.to)
+            )),
+            json_list(&self.children, |x| x.to_json())
+        )
+    }
+    /// The report as a self-contained HTML page, with a collapsible view
+    /// of the hierarchy and a search box.  It needs nothing but a browser.
+    pub fn to_html(&self) -> String {
+        HTML_TEMPLATE
+            .replace("@TITLE@", &self.path)
+            .replace("@REPORT@", &self.to_json())
+    }
+    fn assign_clock_domains(&mut self, domains: &HashMap<String, String>) {
+        let mut used = BTreeSet::new();
+        for signal in &mut self.signals {
+            signal.clock_domain = domains
+                .get(&format!("{}${}", self.path, signal.name))
+                .cloned();
+            if let Some(domain) = &signal.clock_domain {
+                used.insert(domain.clone());
+            }
+        }
+        self.clock_domains = used.into_iter().collect();
+        for child in &mut self.children {
+            child.assign_clock_domains(domains);
+        }
+    }
+}
+
+#[derive(Default)]
+struct HierarchyScanner {
+    path: ProbePath,
+    blocks: Vec<BlockReport>,
+    root: Option<BlockReport>,
+    clocks: Vec<String>,
+    drivers: SignalDrivers,
+}
+
+impl Probe for HierarchyScanner {
+    fn visit_start_scope(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_scope(name);
+        self.blocks.push(BlockReport {
+            name: name.to_string(),
+            path: self.path.block(),
+            signals: vec![],
+            connections: vec![],
+            children: vec![],
+            clock_domains: vec![],
+        });
+    }
+
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_namespace(name);
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        let descriptor = signal.descriptor();
+        if descriptor.name == "clock" {
+            self.clocks.push(self.path.full_name(name));
+        }
+        self.blocks.last_mut().unwrap().signals.push(SignalReport {
+            name: self.path.local_name(name),
+            kind: format!("{:?}", signal.kind()),
+            width: signal.bits(),
+            type_name: descriptor.name,
+            clock_domain: None,
+        });
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.end_namespace();
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, node: &dyn Block) {
+        let mut block = self.blocks.pop().unwrap();
+        if let Verilog::Combinatorial(code) = &node.hdl() {
+            // The names that are visible in this block - its own signals,
+            // and the ports of its children.
+            let mut known = block
+                .signals
+                .iter()
+                .map(|x| x.name.clone())
+                .collect::<BTreeSet<_>>();
+            for child in &block.children {
+                for signal in &child.signals {
+                    known.insert(format!("{}${}", child.name, signal.name));
+                }
+            }
+            let is_child_port = |x: &str| {
+                block
+                    .children
+                    .iter()
+                    .any(|c| x.starts_with(&format!("{}$", c.name)))
+            };
+            for (from, to) in verilog_signal_flow(code) {
+                if !known.contains(&from) || !known.contains(&to) {
+                    continue;
+                }
+                self.drivers.add(
+                    format!("{}${}", block.path, from),
+                    format!("{}${}", block.path, to),
+                );
+                if is_child_port(&from) || is_child_port(&to) {
+                    block.connections.push(Connection { from, to });
+                }
+            }
+        }
+        match self.blocks.last_mut() {
+            Some(parent) => parent.children.push(block),
+            None => self.root = Some(block),
+        }
+        self.path.end_scope();
+    }
+}
+
+/// Generate a report on the structure of a circuit.  The report
+/// covers the whole hierarchy of blocks, with the width and type of each
+/// signal, the connections between each block and its children, and
+/// the clock domain of each clock signal.  Clocks that are driven from
+/// the same source are in the same domain, which is named after the source.
+/// The report can be saved with [BlockReport::to_json] for other tools, or
+/// with [BlockReport::to_html] as a page that can be browsed.
+/// ```rust
+/// use rust_hdl_lib_core::prelude::*;
+///
+/// #[derive(LogicBlock, Default)]
+/// struct Inverter {
+///    pub clock: Signal<In, Clock>,
+///    pub a: Signal<In, Bits<4>>,
+///    pub y: Signal<Out, Bits<4>>,
+/// }
+///
+/// impl Logic for Inverter {
+///     #[hdl_gen]
+///     fn update(&mut self) {
+///         self.y.next = !self.a.val();
+///     }
+/// }
+///
+/// let mut uut = Inverter::default(); uut.connect_all();
+/// let report = hierarchy_report(&uut);
+/// assert_eq!(report.signal("y").unwrap().width, 4);
+/// assert_eq!(report.clock_domains, vec!["uut$clock"]);
+/// assert!(report.to_json().starts_with("{\"name\":\"uut\""));
+/// ```
+pub fn hierarchy_report(uut: &dyn Block) -> BlockReport {
+    let mut visitor = HierarchyScanner::default();
+    uut.accept("uut", &mut visitor);
+    let mut root = visitor.root.take().unwrap();
+    let domains = visitor
+        .clocks
+        .iter()
+        .map(|x| (x.clone(), visitor.drivers.clock_source(x)))
+        .collect();
+    root.assign_clock_domains(&domains);
+    root
+}
+
+const HTML_TEMPLATE: &str = r#"<!DOCTYPE html>
+<html>
+<head>
+<meta charset="utf-8">
+<title>@TITLE@</title>
+<style>
+body { font-family: sans-serif; margin: 1em; }
+details { margin-left: 1.5em; }
+summary { cursor: pointer; font-weight: bold; }
+table { border-collapse: collapse; margin: 0.3em 0 0.3em 1.5em; font-size: 90%; }
+td, th { border: 1px solid #ccc; padding: 2px 6px; text-align: left; }
+.hit { background: #ff8; }
+.domain { color: #06c; font-weight: normal; }
+</style>
+</head>
+<body>
+<input id="search" type="search" placeholder="Search blocks and signals" size="40">
+<div id="tree"></div>
+<script>
+const report = @REPORT@;
+function table(headers, rows) {
+  const t = document.createElement("table");
+  const h = t.insertRow();
+  headers.forEach(x => { const th = document.createElement("th"); th.textContent = x; h.appendChild(th); });
+  rows.forEach(r => { const row = t.insertRow(); r.forEach(x => { row.insertCell().textContent = x; }); });
+  return t;
+}
+function render(block) {
+  const d = document.createElement("details");
+  d.open = true;
+  d.dataset.path = block.path;
+  const s = document.createElement("summary");
+  s.textContent = block.name + " ";
+  const dom = document.createElement("span");
+  dom.className = "domain";
+  dom.textContent = block.clock_domains.join(", ");
+  s.appendChild(dom);
+  d.appendChild(s);
+  if (block.signals.length) {
+    d.appendChild(table(["signal", "kind", "width", "type", "clock domain"],
+      block.signals.map(x => [x.name, x.kind, x.width, x.type, x.clock_domain || ""])));
+  }
+  if (block.connections.length) {
+    d.appendChild(table(["from", "to"], block.connections.map(x => [x.from, x.to])));
+  }
+  block.children.forEach(c => d.appendChild(render(c)));
+  return d;
+}
+document.getElementById("tree").appendChild(render(report));
+document.getElementById("search").addEventListener("input", e => {
+  const q = e.target.value.toLowerCase();
+  document.querySelectorAll("td, summary").forEach(x => {
+    x.classList.toggle("hit", q.length > 0 && x.textContent.toLowerCase().includes(q));
+  });
+  if (q.length > 0) {
+    document.querySelectorAll("details").forEach(x => {
+      x.open = x.querySelector(".hit") !== null;
+    });
+  }
+});
+</script>
+</body>
+</html>
+"#;
@@ -6,27 +6,42 @@ pub mod bits;
 #[doc(hidden)]
 pub mod bitvec;
 pub mod block;
+pub mod check_clock_gating;
 pub mod check_connected;
 pub mod check_error;
 pub mod check_logic_loops;
+pub mod check_reset_domains;
 pub mod check_timing;
+pub mod check_widths;
 pub mod check_write_inputs;
 pub mod clock;
 pub mod code_writer;
 pub mod constant;
 pub mod constraint;
+pub mod design_space;
 pub mod direction;
+pub mod fault_injection;
+pub mod hierarchy_report;
+pub mod logging;
 pub mod logic;
 pub mod module_defines;
+pub mod monitor;
 pub mod named_path;
+pub mod parameter;
 pub mod path_tools;
 pub mod prelude;
 pub mod probe;
+pub mod probe_path;
+pub mod reference_model;
+pub mod scoreboard;
 #[doc(hidden)]
 pub mod short_bit_vec;
 pub mod signal;
+pub mod signal_drivers;
 pub mod signed;
 pub mod simulate;
+pub mod state_machines;
+pub mod stimulus;
 pub mod synth;
 pub mod timing;
 pub mod top_wrap;
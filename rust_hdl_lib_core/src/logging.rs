@@ -0,0 +1,275 @@
+use crate::block::Block;
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+/// How important a message logged with [sim_log!](crate::sim_log) is, from the most
+/// important to the least.  A [Simulation](crate::simulate::Simulation) keeps the messages
+/// at [LogLevel::Info] and above, unless told otherwise with
+/// [set_log_level](crate::simulate::Simulation::set_log_level).
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+pub enum LogLevel {
+    Error,
+    Warn,
+    #[default]
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Display for LogLevel {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            LogLevel::Error => "ERROR",
+            LogLevel::Warn => "WARN",
+            LogLevel::Info => "INFO",
+            LogLevel::Debug => "DEBUG",
+            LogLevel::Trace => "TRACE",
+        };
+        f.pad(name)
+    }
+}
+
+/// A message logged during a simulation (see [sim_log!](crate::sim_log))
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogRecord {
+    /// The simulation time (in ticks) the message was logged at
+    pub time: u64,
+    pub level: LogLevel,
+    /// Where the message came from - the name of the type of the block that logged
+    /// it (like `AD7193Simulator`), or the name given by a testbench
+    pub source: String,
+    pub message: String,
+}
+
+impl Display for LogRecord {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "@{} {:<5} {}: {}",
+            self.time, self.level, self.source, self.message
+        )
+    }
+}
+
+/// Anything that can be named as the source of a message.  A block is named by its
+/// type (without the module path or the generic parameters), so that `sim_log!(self, ...)`
+/// in the `update` of an `AD7193Simulator` comes from `AD7193Simulator`.  Testbenches
+/// (which are not blocks) give a name as a string.
+pub trait LogSource {
+    fn log_source(&self) -> String;
+}
+
+impl LogSource for str {
+    fn log_source(&self) -> String {
+        self.to_string()
+    }
+}
+
+impl LogSource for String {
+    fn log_source(&self) -> String {
+        self.clone()
+    }
+}
+
+impl<B: Block> LogSource for B {
+    fn log_source(&self) -> String {
+        let name = std::any::type_name::<B>();
+        let name = name.split('<').next().unwrap_or(name);
+        name.rsplit("::").next().unwrap_or(name).to_string()
+    }
+}
+
+#[derive(Default)]
+struct LogState {
+    time: u64,
+    previous_time: u64,
+    level: LogLevel,
+    sources: HashMap<String, Option<LogLevel>>,
+    repeat_limit: Option<usize>,
+    repeats: HashMap<(String, &'static str), usize>,
+    // The last message from each source and call site, and when it was logged
+    last: HashMap<(String, &'static str), (u64, String)>,
+    quiet: bool,
+    records: Vec<LogRecord>,
+}
+
+impl LogState {
+    fn enabled(&self, source: &str, level: LogLevel) -> bool {
+        match self.sources.get(source) {
+            Some(limit) => limit.map(|x| level <= x).unwrap_or(false),
+            None => level <= self.level,
+        }
+    }
+    fn push(&mut self, record: LogRecord) {
+        if !self.quiet {
+            println!("{}", record);
+        }
+        self.records.push(record);
+    }
+}
+
+// The log of a simulation.  It is shared by the thread that runs the simulation,
+// and the threads of the testbenches (only one of which runs at any time).
+#[derive(Clone, Default)]
+pub(crate) struct SimLogger(Arc<Mutex<LogState>>);
+
+thread_local! {
+    static CURRENT: RefCell<Option<SimLogger>> = const { RefCell::new(None) };
+}
+
+// Sends the messages logged on this thread to a logger, until dropped
+pub(crate) struct LogGuard(Option<SimLogger>);
+
+impl Drop for LogGuard {
+    fn drop(&mut self) {
+        let previous = self.0.take();
+        CURRENT.with(|x| *x.borrow_mut() = previous);
+    }
+}
+
+impl SimLogger {
+    fn state(&self) -> std::sync::MutexGuard<'_, LogState> {
+        self.0.lock().unwrap_or_else(|x| x.into_inner())
+    }
+    pub(crate) fn install(&self) -> LogGuard {
+        LogGuard(CURRENT.with(|x| x.borrow_mut().replace(self.clone())))
+    }
+    pub(crate) fn set_time(&self, time: u64) {
+        let mut state = self.state();
+        if time != state.time {
+            state.previous_time = state.time;
+            state.time = time;
+        }
+    }
+    pub(crate) fn set_level(&self, level: LogLevel) {
+        self.state().level = level;
+    }
+    pub(crate) fn set_source_level(&self, source: &str, level: Option<LogLevel>) {
+        self.state().sources.insert(source.to_string(), level);
+    }
+    pub(crate) fn set_repeat_limit(&self, limit: Option<usize>) {
+        self.state().repeat_limit = limit;
+    }
+    pub(crate) fn set_quiet(&self, quiet: bool) {
+        self.state().quiet = quiet;
+    }
+    pub(crate) fn records(&self) -> Vec<LogRecord> {
+        self.state().records.clone()
+    }
+    pub(crate) fn start(&self, time: u64) {
+        let mut state = self.state();
+        state.time = time;
+        state.previous_time = time;
+        state.repeats.clear();
+        state.last.clear();
+        state.records.clear();
+    }
+    // Report the messages that were held back by the repeat limit
+    pub(crate) fn finish(&self) {
+        let mut state = self.state();
+        let Some(limit) = state.repeat_limit else {
+            return;
+        };
+        let mut held: Vec<_> = state
+            .repeats
+            .iter()
+            .filter(|(_, count)| **count > limit)
+            .map(|((source, site), count)| (source.clone(), *site, count - limit))
+            .collect();
+        held.sort();
+        for (source, site, count) in held {
+            let record = LogRecord {
+                time: state.time,
+                level: LogLevel::Warn,
+                source,
+                message: format!("{} more messages from {} were not shown", count, site),
+            };
+            state.push(record);
+        }
+    }
+    fn log(&self, source: String, level: LogLevel, site: &'static str, message: String) {
+        let mut state = self.state();
+        // A block is updated many times as the circuit settles, and a message that depends
+        // on a level (rather than an edge) is repeated on every update while the level holds.
+        // So the same message from the same place is only logged again once it has not been
+        // logged for a time step.
+        let time = state.time;
+        let previous_time = state.previous_time;
+        let key = (source.clone(), site);
+        let repeated = matches!(state.last.get(&key),
+            Some((last, text)) if *text == message && (*last == time || *last == previous_time));
+        state.last.insert(key, (time, message.clone()));
+        if repeated {
+            return;
+        }
+        if let Some(limit) = state.repeat_limit {
+            let count = state.repeats.entry((source.clone(), site)).or_default();
+            *count += 1;
+            if *count > limit {
+                return;
+            }
+        }
+        state.push(LogRecord {
+            time,
+            level,
+            source,
+            message,
+        });
+    }
+    fn enabled(&self, source: &str, level: LogLevel) -> bool {
+        self.state().enabled(source, level)
+    }
+}
+
+#[doc(hidden)]
+pub fn log<F: FnOnce() -> String>(source: String, level: LogLevel, site: &'static str, message: F) {
+    let logger = CURRENT.with(|x| x.borrow().clone());
+    match logger {
+        Some(logger) => {
+            // The message is only formatted if it is going to be kept
+            if logger.enabled(&source, level) {
+                logger.log(source, level, site, message());
+            }
+        }
+        None => {
+            if level <= LogLevel::Info {
+                println!("{:<5} {}: {}", level, source, message());
+            }
+        }
+    }
+}
+
+/// Log a message from inside a simulation - from the `update` of a block, or from a
+/// testbench.  Unlike a `println!`, the message is stamped with the simulation time,
+/// and has a [LogLevel], so that the chatter of a simulation model can be left in, and
+/// only shown when it is wanted.  The first argument is the source of the message
+/// (see [LogSource]) - `self` in a block, or a name in a testbench:
+/// ```rust,ignore
+/// sim_log!(self, Debug, "Reset encountered");
+/// sim_log!("testbench", Info, "Read {:x} from register {}", value, index);
+/// ```
+/// A [Simulation](crate::simulate::Simulation) decides which messages are kept (with
+/// [set_log_level](crate::simulate::Simulation::set_log_level) for all of them, and
+/// [set_block_log_level](crate::simulate::Simulation::set_block_log_level) for those of
+/// one source), and how many times the same `sim_log!` can repeat itself (with
+/// [set_log_repeat_limit](crate::simulate::Simulation::set_log_repeat_limit)).  The kept
+/// messages are printed, and can be read back with
+/// [log_records](crate::simulate::Simulation::log_records).  Failed `sim_assert!`s are
+/// logged as errors (from `sim_assert`).  Since `update` is called over and over, a
+/// message that is repeated (from the same `sim_log!`) in consecutive time steps is only
+/// logged the first time.  Outside of a simulation, messages at [LogLevel::Info] and
+/// above are printed (without a time).  In HDL, the message becomes a comment.
+#[macro_export]
+macro_rules! sim_log {
+    ($source: expr, $level: ident, $($arg: tt)+) => {{
+        use $crate::logging::LogSource as _;
+        $crate::logging::log(
+            ($source).log_source(),
+            $crate::logging::LogLevel::$level,
+            concat!(file!(), ":", line!()),
+            || format!($($arg)+),
+        )
+    }};
+}
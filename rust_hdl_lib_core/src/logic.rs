@@ -1,5 +1,6 @@
 use crate::ast::{Verilog, VerilogLink};
-use crate::timing::TimingInfo;
+use crate::parameter::ModuleParameter;
+use crate::timing::{ResetInfo, TimingInfo};
 
 pub trait Logic {
     fn update(&mut self);
@@ -10,6 +11,12 @@ pub trait Logic {
     fn timing(&self) -> Vec<TimingInfo> {
         vec![]
     }
+    fn resets(&self) -> Vec<ResetInfo> {
+        vec![]
+    }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![]
+    }
 }
 
 pub fn logic_connect_fn<L: Logic>(x: &mut L) {
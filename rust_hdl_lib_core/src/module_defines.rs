@@ -5,10 +5,11 @@ use crate::block::Block;
 use crate::check_error::check_all;
 use crate::code_writer::CodeWriter;
 use crate::named_path::NamedPath;
+use crate::parameter::ModuleParameter;
 use crate::probe::Probe;
 use crate::type_descriptor::{TypeDescriptor, TypeKind};
 use crate::verilog_gen::{verilog_combinatorial, verilog_link_extraction};
-use std::collections::BTreeMap;
+use std::collections::{BTreeMap, BTreeSet};
 
 #[derive(Clone, Debug, Default)]
 struct SubModuleInvocation {
@@ -21,6 +22,7 @@ struct ModuleDetails {
     atoms: Vec<AtomDetails>,
     sub_modules: Vec<SubModuleInvocation>,
     enums: Vec<EnumDefinition>,
+    parameters: Vec<ModuleParameter>,
     code: Verilog,
     links: Vec<VerilogLink>,
 }
@@ -79,14 +81,32 @@ fn decl(x: &AtomDetails) -> String {
     }
 }
 
+/// Options that control the Verilog emitted by [generate_verilog_with_options].
+#[derive(Clone, Copy, Debug, Default)]
+pub struct VerilogOptions {
+    /// Generate Verilog that is easier to review and debug in vendor tools.  Each
+    /// statement is annotated with the Rust source line it came from, the stub signals
+    /// are grouped by the sub-block they connect to, and every instance gets its own
+    /// module (named for its path in the design), instead of sharing the module of
+    /// an identical instance.
+    pub readable: bool,
+}
+
 #[derive(Default)]
 pub struct ModuleDefines {
     path: NamedPath,
     namespace: NamedPath,
     details: BTreeMap<String, ModuleDetails>,
+    options: VerilogOptions,
 }
 
 impl ModuleDefines {
+    pub fn new(options: VerilogOptions) -> Self {
+        Self {
+            options,
+            ..Default::default()
+        }
+    }
     fn add_atom(&mut self, module: &str, atom: AtomDetails) {
         let entry = self.details.entry(module.into()).or_default();
         entry.atoms.push(atom)
@@ -103,11 +123,11 @@ impl ModuleDefines {
         let enum_name = descriptor.name.clone();
         match &descriptor.kind {
             TypeKind::Enum(x) => {
-                for (ndx, label) in x.iter().enumerate() {
+                for variant in x {
                     let def = EnumDefinition {
                         type_name: enum_name.clone(),
-                        discriminant: label.into(),
-                        value: ndx,
+                        discriminant: variant.name.clone(),
+                        value: variant.value,
                     };
                     if !entry.enums.contains(&def) {
                         entry.enums.push(def);
@@ -132,6 +152,10 @@ impl ModuleDefines {
         };
         entry.code = code;
     }
+    fn add_parameters(&mut self, module: &str, parameters: Vec<ModuleParameter>) {
+        let entry = self.details.entry(module.into()).or_default();
+        entry.parameters = parameters;
+    }
 }
 
 impl Probe for ModuleDefines {
@@ -141,6 +165,7 @@ impl Probe for ModuleDefines {
         self.namespace.reset();
         self.add_submodule(&top_level, name, &self.path.to_string());
         self.add_code(&self.path.to_string(), node.hdl());
+        self.add_parameters(&self.path.to_string(), node.parameters());
     }
 
     fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
@@ -193,7 +218,7 @@ impl Probe for ModuleDefines {
     }
 }
 
-fn get_link_equivalence(link: &VerilogLink) -> (String, String) {
+pub(crate) fn get_link_equivalence(link: &VerilogLink) -> (String, String) {
     match link {
         VerilogLink::Forward(link) => (
             format!("{}${}", link.other_name, link.my_name),
@@ -222,11 +247,12 @@ impl ModuleDefines {
         module_details: &ModuleDetails,
         child: &SubModuleInvocation,
         io: &mut CodeWriter,
+        names: &BTreeMap<String, String>,
     ) {
         let entry = self.details.get(&child.kind).unwrap();
         let submodule_kind = match &entry.code {
             Verilog::Blackbox(b) => &b.name,
-            _ => &child.kind,
+            _ => names.get(&child.kind).unwrap_or(&child.kind),
         };
         let child_args = entry
             .atoms
@@ -316,6 +342,7 @@ impl ModuleDefines {
         module_name: &str,
         module_details: &ModuleDetails,
         io: &mut CodeWriter,
+        names: &BTreeMap<String, String>,
     ) {
         // Remap the output parameters to pass through (net type) in case we have a wrapper
         let atoms_passthrough = &module_details
@@ -380,6 +407,13 @@ impl ModuleDefines {
             });
         }
         let submodules = &module_details.sub_modules;
+        if !module_details.parameters.is_empty() {
+            io.add("\n// Parameters");
+            module_details
+                .parameters
+                .iter()
+                .for_each(|x| io.add(x.to_string()));
+        }
         if !consts.is_empty() {
             io.add("\n// Constant declarations");
             consts.iter().for_each(|x| io.add(decl(x)));
@@ -396,11 +430,25 @@ impl ModuleDefines {
         }
         if !stubs.is_empty() & !wrapper_mode {
             io.add("\n// Stub signals");
-            stubs.iter().for_each(|x| {
-                if !self.stub_is_linked_to_module_argument(module_details, &x.name) {
-                    io.add(decl(x))
+            let stubs = stubs
+                .into_iter()
+                .filter(|x| !self.stub_is_linked_to_module_argument(module_details, &x.name))
+                .collect::<Vec<_>>();
+            if self.options.readable {
+                for child in &module_details.sub_modules {
+                    let prefix = format!("{}$", child.name);
+                    let child_stubs = stubs
+                        .iter()
+                        .filter(|x| x.name.starts_with(&prefix))
+                        .collect::<Vec<_>>();
+                    if !child_stubs.is_empty() {
+                        io.add(format!("// -- {}", child.name));
+                        child_stubs.iter().for_each(|x| io.add(decl(x)));
+                    }
                 }
-            });
+            } else {
+                stubs.iter().for_each(|x| io.add(decl(x)));
+            }
         }
         if !locals.is_empty() & !wrapper_mode {
             io.add("\n// Local signals");
@@ -409,13 +457,13 @@ impl ModuleDefines {
         if !submodules.is_empty() & !wrapper_mode {
             io.add("\n// Sub module instances");
             for child in submodules {
-                self.sub_module_invocation(module_details, child, io);
+                self.sub_module_invocation(module_details, child, io, names);
             }
         }
         match &module_details.code {
             Verilog::Combinatorial(code) => {
                 io.add("\n// Update code");
-                io.add(verilog_combinatorial(code));
+                io.add(verilog_combinatorial(code, self.options.readable));
             }
             Verilog::Custom(code) => {
                 io.add("\n// Update code (custom)");
@@ -470,21 +518,66 @@ impl ModuleDefines {
         io.add(format!("endmodule // {}", module_name));
     }
 
+    // Identical sub-modules (like an array of the same widget) generate identical
+    // module bodies.  To emit each body only once, the modules are rendered from the
+    // leaves up, with their children already renamed to the first module that had the
+    // same body.  The result maps each module to the name it should be emitted under.
+    // The top level module always keeps its own name, and so does every wrapper, since
+    // the IP it wraps may be set up by parameters that RustHDL can not see.
+    fn canonical_names(&self) -> BTreeMap<String, String> {
+        let mut names = BTreeMap::new();
+        let mut bodies = BTreeMap::new();
+        let mut modules = self
+            .details
+            .iter()
+            .filter(|x| !x.0.is_empty())
+            .filter(|x| !matches!(x.1.code, Verilog::Blackbox(_)))
+            .collect::<Vec<_>>();
+        modules.sort_by_key(|x| std::cmp::Reverse(x.0.matches('$').count()));
+        for (module_name, module_details) in modules {
+            if !module_name.contains('$')
+                || self.options.readable
+                || matches!(module_details.code, Verilog::Wrapper(_))
+            {
+                names.insert(module_name.clone(), module_name.clone());
+                continue;
+            }
+            let mut io = CodeWriter::default();
+            self.process_module("", module_details, &mut io, &names);
+            let canonical = bodies
+                .entry(io.to_string())
+                .or_insert_with(|| module_name.clone())
+                .clone();
+            names.insert(module_name.clone(), canonical);
+        }
+        names
+    }
+
     pub fn defines(&self) -> String {
         let mut io = CodeWriter::default();
+        let names = self.canonical_names();
         self.details
             .iter()
-            .filter(|x| x.0.len() != 0)
+            .filter(|x| !x.0.is_empty())
             .filter(|x| !matches!(x.1.code, Verilog::Blackbox(_)))
+            .filter(|x| names.get(x.0) == Some(x.0))
             .for_each(|k| {
                 let module_name = k.0;
                 let module_details = k.1;
-                self.process_module(module_name, module_details, &mut io);
+                self.process_module(module_name, module_details, &mut io, &names);
             });
-        self.details.iter().for_each(|x| match &x.1.code {
-            Verilog::Blackbox(b) => io.add(&b.code),
-            Verilog::Wrapper(w) => io.add(&w.cores),
-            _ => {}
+        // Black boxes and cores only need to be declared once, no matter how
+        // many times they are instantiated.
+        let mut cores = BTreeSet::new();
+        self.details.iter().for_each(|x| {
+            let code = match &x.1.code {
+                Verilog::Blackbox(b) => &b.code,
+                Verilog::Wrapper(w) => &w.cores,
+                _ => return,
+            };
+            if cores.insert(code.clone()) {
+                io.add(code);
+            }
         });
         io.to_string()
     }
@@ -497,6 +590,15 @@ pub fn generate_verilog<U: Block>(uut: &U) -> String {
     defines.defines()
 }
 
+/// Generate Verilog for a circuit, as [generate_verilog] does, but with control over
+/// the style of the output (e.g., to make it readable).
+pub fn generate_verilog_with_options<U: Block>(uut: &U, options: VerilogOptions) -> String {
+    let mut defines = ModuleDefines::new(options);
+    check_all(uut).unwrap();
+    uut.accept("top", &mut defines);
+    defines.defines()
+}
+
 pub fn generate_verilog_unchecked<U: Block>(uut: &U) -> String {
     let mut defines = ModuleDefines::default();
     uut.accept("top", &mut defines);
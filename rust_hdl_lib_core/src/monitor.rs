@@ -0,0 +1,69 @@
+/// A [Monitor] is a passive observer that can be attached to a
+/// [Simulation](crate::simulate::Simulation) with
+/// [add_monitor](crate::simulate::Simulation::add_monitor).  After every
+/// simulation event, the monitor is handed a [Sample](Monitor::Sample) of
+/// the signals it watches (extracted from the circuit by a closure),
+/// along with the current simulation time.  If the monitor decides that
+/// a protocol rule has been broken, it returns a description of the
+/// problem, and the simulation stops with a
+/// [SimError::ProtocolViolation](crate::simulate::SimError::ProtocolViolation).
+///
+/// Monitors never drive signals, so they can be attached to any existing
+/// testbench without changing the behavior of the circuit.
+pub trait Monitor {
+    /// The values of the signals the monitor needs to see.
+    type Sample;
+    /// A name for the monitor, used when reporting violations.
+    fn name(&self) -> String;
+    /// Check the latest sample.  Return an `Err` with a description of
+    /// the problem if a rule was violated.
+    fn check(&mut self, sample: Self::Sample, time: u64) -> Result<(), String>;
+    /// Called once when the simulation ends (for whatever reason).  Monitors
+    /// that collect statistics rather than check rules can report them here.
+    /// Monitors that are still waiting for something (like a response that
+    /// never came) can return an `Err`, which fails a simulation that would
+    /// otherwise have passed.
+    fn finish(&mut self, _time: u64) -> Result<(), String> {
+        Ok(())
+    }
+}
+
+/// Tracks the rising edges of a clock in a stream of samples.  Because a
+/// monitor sees the circuit after each event has been processed, the
+/// values that were registered at a rising edge are the ones from the
+/// previous sample.  `EdgeTracker` keeps that previous sample around.
+#[derive(Clone, Debug)]
+pub struct EdgeTracker<S: Clone> {
+    previous: Option<S>,
+    cycles: u64,
+}
+
+impl<S: Clone> Default for EdgeTracker<S> {
+    fn default() -> Self {
+        Self {
+            previous: None,
+            cycles: 0,
+        }
+    }
+}
+
+impl<S: Clone> EdgeTracker<S> {
+    /// Feed the latest sample (and the clock level in that sample).  If
+    /// a rising edge occurred, the sample from just before the edge is
+    /// returned.
+    pub fn update(&mut self, sample: &S, clock: impl Fn(&S) -> bool) -> Option<S> {
+        let ret = match &self.previous {
+            Some(prev) if !clock(prev) && clock(sample) => {
+                self.cycles += 1;
+                Some(prev.clone())
+            }
+            _ => None,
+        };
+        self.previous = Some(sample.clone());
+        ret
+    }
+    /// The number of rising edges seen so far.
+    pub fn cycles(&self) -> u64 {
+        self.cycles
+    }
+}
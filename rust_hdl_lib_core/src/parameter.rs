@@ -0,0 +1,87 @@
+use std::fmt::{Display, Formatter};
+
+/// A [ModuleParameter] records the name and value of a (typically const generic)
+/// parameter of a block, so that it can be surfaced in the generated Verilog.
+/// Once the Verilog is generated, `FIFOReducerN<32, 4>` is just another module,
+/// and the numbers that define it are buried in the constants and widths of the
+/// signals.  By overriding [Logic::parameters](crate::logic::Logic::parameters),
+/// a block can list them explicitly:
+/// ```rust
+/// # use rust_hdl_lib_core::prelude::*;
+/// #[derive(LogicBlock, Default)]
+/// struct Adder<const N: usize> {
+///     pub a: Signal<In, Bits<N>>,
+///     pub b: Signal<In, Bits<N>>,
+///     pub sum: Signal<Out, Bits<N>>,
+/// }
+///
+/// impl<const N: usize> Logic for Adder<N> {
+///     #[hdl_gen]
+///     fn update(&mut self) {
+///         self.sum.next = self.a.val() + self.b.val();
+///     }
+///     fn parameters(&self) -> Vec<ModuleParameter> {
+///         vec![ModuleParameter::new("N", N, "Width of the operands")]
+///     }
+/// }
+/// ```
+/// Each parameter is emitted as a `localparam` (with the description as a
+/// trailing comment) at the top of the generated module.  The names must
+/// not collide with any of the signals or constants in the block.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ModuleParameter {
+    pub name: String,
+    pub value: u64,
+    pub description: String,
+}
+
+impl ModuleParameter {
+    pub fn new<T: Into<ParameterValue>>(name: &str, value: T, description: &str) -> Self {
+        Self {
+            name: name.into(),
+            value: value.into().0,
+            description: description.into(),
+        }
+    }
+}
+
+impl Display for ModuleParameter {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        if self.description.is_empty() {
+            write!(f, "localparam {} = {};", self.name, self.value)
+        } else {
+            write!(
+                f,
+                "localparam {} = {}; // {}",
+                self.name, self.value, self.description
+            )
+        }
+    }
+}
+
+#[doc(hidden)]
+pub struct ParameterValue(u64);
+
+impl From<usize> for ParameterValue {
+    fn from(x: usize) -> Self {
+        ParameterValue(x as u64)
+    }
+}
+
+impl From<u64> for ParameterValue {
+    fn from(x: u64) -> Self {
+        ParameterValue(x)
+    }
+}
+
+impl From<u32> for ParameterValue {
+    fn from(x: u32) -> Self {
+        ParameterValue(x as u64)
+    }
+}
+
+impl From<bool> for ParameterValue {
+    fn from(x: bool) -> Self {
+        ParameterValue(x as u64)
+    }
+}
@@ -22,16 +22,27 @@ pub use crate::clock::NANOS_PER_FEMTO;
 pub use crate::constant::Constant;
 pub use crate::constraint::Timing::*;
 pub use crate::constraint::*;
+pub use crate::design_points;
+pub use crate::design_space::{DesignPoint, DesignSpace};
 pub use crate::direction::{Direction, In, InOut, Local, Out};
+pub use crate::fault_injection::{Fault, FaultCampaign, FaultOutcome, FaultRecord, FaultReport};
+pub use crate::hierarchy_report::{hierarchy_report, BlockReport};
+pub use crate::logging::{LogLevel, LogRecord};
 pub use crate::logic;
 pub use crate::logic::Logic;
 pub use crate::logic::LogicJoin;
 pub use crate::logic::LogicLink;
-pub use crate::module_defines::ModuleDefines;
-pub use crate::module_defines::{generate_verilog, generate_verilog_unchecked};
+pub use crate::module_defines::{
+    generate_verilog, generate_verilog_unchecked, generate_verilog_with_options,
+};
+pub use crate::module_defines::{ModuleDefines, VerilogOptions};
+pub use crate::monitor::{EdgeTracker, Monitor};
 pub use crate::named_path::NamedPath;
+pub use crate::parameter::ModuleParameter;
 pub use crate::probe;
 pub use crate::probe::Probe;
+pub use crate::reference_model::{EquivalenceChecker, ModelSample, ReferenceModel};
+pub use crate::scoreboard::{Scoreboard, ScoreboardMonitor, ScoreboardSample};
 pub use crate::signal::Signal;
 pub use crate::signed::ToSignedBits;
 pub use crate::signed::{
@@ -39,21 +50,28 @@ pub use crate::signed::{
 };
 pub use crate::sim_assert;
 pub use crate::sim_assert_eq;
+pub use crate::sim_log;
 pub use crate::simple_sim;
 pub use crate::simulate::sim_time;
+pub use crate::simulate::sim_time::{SimDuration, TimeUnit};
 pub use crate::simulate::simulate;
 pub use crate::simulate::SIMULATION_TIME_ONE_SECOND;
 pub use crate::simulate::{Sim, SimError, Simulation};
+pub use crate::state_machines::{extract_state_machines, StateMachine, StateTransition};
+pub use crate::stimulus::Stimulus;
 pub use crate::synth;
 pub use crate::synth::Synth;
 pub use crate::synth::VCDValue;
 pub use crate::target_path;
-pub use crate::timing::TimingInfo;
+pub use crate::test_harness;
+pub use crate::timing::{ClockEdge, ResetInfo, TimingInfo};
 pub use crate::top_wrap::TopWrap;
 pub use crate::type_descriptor;
-pub use crate::type_descriptor::{TypeDescriptor, TypeField, TypeKind};
+pub use crate::type_descriptor::{EnumVariant, TypeDescriptor, TypeField, TypeKind};
 pub use crate::vcd_path;
-pub use crate::vcd_probe::{write_vcd_change, write_vcd_dump, write_vcd_header};
+pub use crate::vcd_probe::{
+    write_vcd_change, write_vcd_dump, write_vcd_header, write_vcd_header_with_unit,
+};
 pub use crate::verilog_gen::filter_blackbox_directives;
 pub use crate::verilog_visitor::VerilogVisitor;
 pub use crate::wait_clock_cycle;
@@ -8,3 +8,13 @@ pub trait Probe {
     fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {}
     fn visit_end_scope(&mut self, _name: &str, _node: &dyn Block) {}
 }
+
+/// Like [Probe], but with mutable access to the signals, so that a probe
+/// can change them (e.g., to drive the inputs of a circuit from a recording).
+pub trait ProbeMut {
+    fn visit_start_scope(&mut self, _name: &str) {}
+    fn visit_start_namespace(&mut self, _name: &str) {}
+    fn visit_atom(&mut self, _name: &str, _signal: &mut dyn Atom) {}
+    fn visit_end_namespace(&mut self, _name: &str) {}
+    fn visit_end_scope(&mut self, _name: &str) {}
+}
@@ -0,0 +1,63 @@
+use crate::named_path::NamedPath;
+
+/// Tracks where a [Probe](crate::probe::Probe) (or [ProbeMut](crate::probe::ProbeMut))
+/// is in a circuit, so that it can name the signals it visits the way the
+/// generated Verilog does.  Call the `start_` and `end_` methods from the
+/// matching `visit_` methods of the probe.
+#[derive(Clone, Debug, Default)]
+pub struct ProbePath {
+    path: NamedPath,
+    namespace: NamedPath,
+    saved_namespaces: Vec<NamedPath>,
+}
+
+impl ProbePath {
+    pub fn start_scope(&mut self, name: &str) {
+        self.path.push(name);
+        self.saved_namespaces.push(self.namespace.clone());
+        self.namespace.reset();
+    }
+
+    pub fn start_namespace(&mut self, name: &str) {
+        self.namespace.push(name);
+    }
+
+    pub fn end_namespace(&mut self) {
+        self.namespace.pop();
+    }
+
+    pub fn end_scope(&mut self) {
+        self.namespace = self.saved_namespaces.pop().unwrap();
+        self.path.pop();
+    }
+
+    /// The path of the current block (e.g., `uut$counter`)
+    pub fn block(&self) -> String {
+        self.path.to_string()
+    }
+
+    /// The name of a signal inside the current block (e.g., `count$d`)
+    pub fn local_name(&self, name: &str) -> String {
+        if self.namespace.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}${}", self.namespace.flat("$"), name)
+        }
+    }
+
+    /// The name the parent block uses for a port of the current block, which is
+    /// `<block>$<port>` (e.g., `count$d` is seen as `counter$count$d`).  Returns
+    /// `None` at the top of the circuit, where there is no parent.
+    pub fn parent_name(&self, name: &str) -> Option<String> {
+        if self.path.len() > 1 {
+            Some(format!("{}${}", self.path.last(), self.local_name(name)))
+        } else {
+            None
+        }
+    }
+
+    /// The full path of a signal (e.g., `uut$counter$count$d`)
+    pub fn full_name(&self, name: &str) -> String {
+        format!("{}${}", self.block(), self.local_name(name))
+    }
+}
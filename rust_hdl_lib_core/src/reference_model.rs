@@ -0,0 +1,130 @@
+use crate::monitor::{EdgeTracker, Monitor};
+use std::collections::VecDeque;
+use std::fmt::Debug;
+
+/// A [ReferenceModel] is a pure Rust description of what a circuit is
+/// supposed to do.  It is handed each stimulus the circuit receives, and
+/// returns the responses the circuit should produce as a result.  Attach
+/// it to a [Simulation](crate::simulate::Simulation) with
+/// [add_reference_model](crate::simulate::Simulation::add_reference_model),
+/// and the simulation will check the circuit against the model as it runs.
+///
+/// A stimulus can be whatever is convenient - the inputs for a single clock
+/// cycle (for a cycle-by-cycle comparison), or a whole transaction.  The
+/// model can return any number of responses for each stimulus (including
+/// none), and the circuit is allowed to take as many cycles as it needs to
+/// produce them.  Only the order and the values have to match.
+pub trait ReferenceModel {
+    /// The input to the model
+    type Stimulus: Clone + Debug;
+    /// The output of the model (and of the circuit)
+    type Response: Clone + Debug + PartialEq;
+    /// A name for the model, used when reporting a divergence.
+    fn name(&self) -> String;
+    /// Consume a stimulus, and return the responses the circuit is expected to produce.
+    fn consume(&mut self, stimulus: Self::Stimulus) -> Vec<Self::Response>;
+}
+
+/// What an [EquivalenceChecker] sees of the circuit after each simulation event.
+#[derive(Clone, Debug)]
+pub struct ModelSample<S, R> {
+    /// The level of the clock the stimulus and response are registered on
+    pub clock: bool,
+    /// The stimulus being applied to the circuit (if any)
+    pub stimulus: Option<S>,
+    /// The response being produced by the circuit (if any)
+    pub response: Option<R>,
+}
+
+const HISTORY_LENGTH: usize = 8;
+
+/// A [Monitor] that drives a [ReferenceModel] with the stimulus applied to the circuit,
+/// and compares the responses of the circuit with those of the model.  Both are sampled
+/// on the rising edge of the clock.  The first response that does not match stops the
+/// simulation, and the violation reports which response it was, what was expected, and
+/// the stimulus that led up to it.  Responses the model expected but the circuit never
+/// produced fail the simulation when it ends.
+pub struct EquivalenceChecker<M: ReferenceModel> {
+    model: M,
+    edges: EdgeTracker<ModelSample<M::Stimulus, M::Response>>,
+    expected: VecDeque<M::Response>,
+    history: VecDeque<String>,
+    matched: usize,
+}
+
+impl<M: ReferenceModel> EquivalenceChecker<M> {
+    /// Check a circuit against the given model.
+    pub fn new(model: M) -> Self {
+        Self {
+            model,
+            edges: Default::default(),
+            expected: Default::default(),
+            history: Default::default(),
+            matched: 0,
+        }
+    }
+    fn context(&self) -> String {
+        format!(
+            "cycle {}, after {} matching responses, recent stimulus: [{}]",
+            self.edges.cycles(),
+            self.matched,
+            self.history.iter().cloned().collect::<Vec<_>>().join(", ")
+        )
+    }
+}
+
+impl<M: ReferenceModel> Monitor for EquivalenceChecker<M> {
+    type Sample = ModelSample<M::Stimulus, M::Response>;
+
+    fn name(&self) -> String {
+        self.model.name()
+    }
+
+    fn check(&mut self, sample: Self::Sample, _time: u64) -> Result<(), String> {
+        let sample = match self.edges.update(&sample, |x| x.clock) {
+            Some(sample) => sample,
+            None => return Ok(()),
+        };
+        if let Some(stimulus) = sample.stimulus {
+            if self.history.len() == HISTORY_LENGTH {
+                self.history.pop_front();
+            }
+            self.history.push_back(format!("{:?}", stimulus));
+            self.expected.extend(self.model.consume(stimulus));
+        }
+        if let Some(actual) = sample.response {
+            match self.expected.pop_front() {
+                None => {
+                    return Err(format!(
+                        "circuit produced {:?} but the model expected nothing ({})",
+                        actual,
+                        self.context()
+                    ))
+                }
+                Some(expected) if expected != actual => {
+                    return Err(format!(
+                        "response {} was {:?} but the model expected {:?} ({})",
+                        self.matched,
+                        actual,
+                        expected,
+                        self.context()
+                    ))
+                }
+                Some(_) => self.matched += 1,
+            }
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, _time: u64) -> Result<(), String> {
+        match self.expected.front() {
+            None => Ok(()),
+            Some(expected) => Err(format!(
+                "the circuit never produced {} of the expected responses, the first was {:?} ({})",
+                self.expected.len(),
+                expected,
+                self.context()
+            )),
+        }
+    }
+}
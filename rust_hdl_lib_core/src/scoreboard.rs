@@ -0,0 +1,232 @@
+use crate::monitor::{EdgeTracker, Monitor};
+use std::collections::{BTreeMap, HashMap, VecDeque};
+use std::fmt::Debug;
+use std::hash::Hash;
+use std::sync::{Arc, Mutex, MutexGuard};
+
+/// A [Scoreboard] checks the transactions produced by a circuit against the ones it is
+/// expected to produce, without caring about the order they come out in.  Each transaction
+/// carries a key (a tag, an ID, a sequence number, a destination port...) and a transaction
+/// matches the expected one with the same key.  That makes it the tool for checking
+/// arbiters, crossbars and anything else that is allowed to reorder its outputs - unlike
+/// an [EquivalenceChecker](crate::reference_model::EquivalenceChecker) or a `LazyFIFOReader`,
+/// which both expect the outputs in order.
+///
+/// Transactions that share a key must come out in the order they were expected (a tag
+/// that is reused once it has been retired is fine).  A scoreboard is cheap to clone, and
+/// all the clones share the same state, so one can be filled in by a testbench that drives
+/// the circuit, checked by another that reads the outputs (or attached to the simulation
+/// with [add_scoreboard](crate::simulate::Simulation::add_scoreboard)), and then inspected
+/// once the simulation is over:
+/// ```rust,ignore
+/// let scoreboard = Scoreboard::new("crossbar");
+/// // In the testbench driving the inputs
+/// scoreboard.expect(tag, value);
+/// // In the testbench reading the outputs
+/// if let Err(message) = scoreboard.observe(tag, value) {
+///     return sim.fail(message, x);
+/// }
+/// // And at the end, check that nothing went missing
+/// scoreboard.finish().unwrap();
+/// ```
+pub struct Scoreboard<K, T> {
+    name: String,
+    state: Arc<Mutex<ScoreboardState<K, T>>>,
+}
+
+impl<K, T> Clone for Scoreboard<K, T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: self.name.clone(),
+            state: self.state.clone(),
+        }
+    }
+}
+
+struct ScoreboardState<K, T> {
+    // The outstanding transactions for each key, with their sequence numbers
+    pending: HashMap<K, VecDeque<(u64, T)>>,
+    // The keys of the outstanding transactions, by sequence number
+    order: BTreeMap<u64, K>,
+    expected: u64,
+    matched: u64,
+    max_overtaken: usize,
+}
+
+impl<K: Eq + Hash + Clone + Debug, T: Clone + Debug + PartialEq> Scoreboard<K, T> {
+    /// Create an empty scoreboard.  The name is used in the error messages.
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            state: Arc::new(Mutex::new(ScoreboardState {
+                pending: Default::default(),
+                order: Default::default(),
+                expected: 0,
+                matched: 0,
+                max_overtaken: 0,
+            })),
+        }
+    }
+    fn state(&self) -> MutexGuard<'_, ScoreboardState<K, T>> {
+        // A testbench that panics while holding the lock should not hide the
+        // contents of the scoreboard from the others
+        self.state.lock().unwrap_or_else(|x| x.into_inner())
+    }
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+    /// Record a transaction the circuit is expected to produce
+    pub fn expect(&self, key: K, value: T) {
+        let mut state = self.state();
+        let sequence = state.expected;
+        state.expected += 1;
+        state
+            .pending
+            .entry(key.clone())
+            .or_default()
+            .push_back((sequence, value));
+        state.order.insert(sequence, key);
+    }
+    /// Check a transaction produced by the circuit against the oldest outstanding one
+    /// with the same key.  Fails if there is no such transaction, or if the values differ.
+    /// Either way, the expected transaction (if any) is retired.
+    pub fn observe(&self, key: K, value: T) -> Result<(), String> {
+        let mut state = self.state();
+        let entry = state.pending.get_mut(&key).and_then(|x| x.pop_front());
+        if state.pending.get(&key).map(|x| x.is_empty()) == Some(true) {
+            state.pending.remove(&key);
+        }
+        let (sequence, expected) = match entry {
+            Some(entry) => entry,
+            None => {
+                return Err(format!(
+                    "{}: observed {:?} with key {:?}, but nothing was expected with that key ({} matched so far)",
+                    self.name, value, key, state.matched
+                ))
+            }
+        };
+        // How many transactions that were expected earlier are still outstanding
+        let overtaken = state.order.range(..sequence).count();
+        state.order.remove(&sequence);
+        state.max_overtaken = state.max_overtaken.max(overtaken);
+        if expected != value {
+            return Err(format!(
+                "{}: observed {:?} with key {:?}, but expected {:?} (transaction {}, {} matched so far)",
+                self.name, value, key, expected, sequence, state.matched
+            ));
+        }
+        state.matched += 1;
+        Ok(())
+    }
+    /// The transactions that have been expected, but not yet observed, oldest first
+    pub fn outstanding(&self) -> Vec<(K, T)> {
+        let state = self.state();
+        let mut ret: Vec<(u64, K, T)> = state
+            .pending
+            .iter()
+            .flat_map(|(key, entries)| {
+                entries
+                    .iter()
+                    .map(move |(sequence, value)| (*sequence, key.clone(), value.clone()))
+            })
+            .collect();
+        ret.sort_by_key(|x| x.0);
+        ret.into_iter()
+            .map(|(_, key, value)| (key, value))
+            .collect()
+    }
+    /// The number of transactions that were observed and matched
+    pub fn matched(&self) -> u64 {
+        self.state().matched
+    }
+    /// The largest number of earlier transactions that one transaction overtook.  A circuit
+    /// that keeps its outputs in order scores 0.
+    pub fn max_overtaken(&self) -> usize {
+        self.state().max_overtaken
+    }
+    /// Check that every expected transaction has been observed.  Call this once the
+    /// simulation is over.
+    pub fn finish(&self) -> Result<(), String> {
+        let outstanding = self.outstanding();
+        if outstanding.is_empty() {
+            return Ok(());
+        }
+        let shown = outstanding
+            .iter()
+            .take(8)
+            .map(|(key, value)| format!("{:?} => {:?}", key, value))
+            .collect::<Vec<_>>()
+            .join(", ");
+        Err(format!(
+            "{}: {} transactions were never observed ({} matched): [{}{}]",
+            self.name,
+            outstanding.len(),
+            self.matched(),
+            shown,
+            if outstanding.len() > 8 { ", ..." } else { "" }
+        ))
+    }
+}
+
+/// What a [ScoreboardMonitor] sees of the circuit after each simulation event.
+#[derive(Clone, Debug)]
+pub struct ScoreboardSample<K, T> {
+    /// The level of the clock the transactions are registered on
+    pub clock: bool,
+    /// The transactions the circuit was given (and is expected to produce)
+    pub expected: Vec<(K, T)>,
+    /// The transactions the circuit produced
+    pub observed: Vec<(K, T)>,
+}
+
+/// A [Monitor] that fills in a [Scoreboard], and checks against it, on the rising edges
+/// of a clock.  The transactions expected on an edge are recorded before the ones observed
+/// on the same edge are checked, so a circuit with no latency can be checked too.  The
+/// monitor reports the transactions that were never observed when the simulation ends,
+/// but it does not fail the simulation for them - use [Scoreboard::finish] for that.
+pub struct ScoreboardMonitor<K: Clone, T: Clone> {
+    scoreboard: Scoreboard<K, T>,
+    edges: EdgeTracker<ScoreboardSample<K, T>>,
+}
+
+impl<K: Clone, T: Clone> ScoreboardMonitor<K, T> {
+    pub fn new(scoreboard: Scoreboard<K, T>) -> Self {
+        Self {
+            scoreboard,
+            edges: Default::default(),
+        }
+    }
+}
+
+impl<K, T> Monitor for ScoreboardMonitor<K, T>
+where
+    K: Eq + Hash + Clone + Debug,
+    T: Clone + Debug + PartialEq,
+{
+    type Sample = ScoreboardSample<K, T>;
+
+    fn name(&self) -> String {
+        self.scoreboard.name().to_string()
+    }
+
+    fn check(&mut self, sample: Self::Sample, _time: u64) -> Result<(), String> {
+        let sample = match self.edges.update(&sample, |x| x.clock) {
+            Some(sample) => sample,
+            None => return Ok(()),
+        };
+        for (key, value) in sample.expected {
+            self.scoreboard.expect(key, value);
+        }
+        for (key, value) in sample.observed {
+            self.scoreboard.observe(key, value)?;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, _time: u64) -> Result<(), String> {
+        if let Err(message) = self.scoreboard.finish() {
+            crate::sim_log!(self.name(), Warn, "{}", message);
+        }
+        Ok(())
+    }
+}
@@ -4,12 +4,12 @@ use std::sync::atomic::{AtomicUsize, Ordering};
 use crate::ast::{VerilogLink, VerilogLinkDetails, VerilogLiteral};
 use crate::atom::{Atom, AtomKind};
 use crate::bits::Bit;
-use crate::block::Block;
+use crate::block::{Block, EventState};
 use crate::clock::Clock;
-use crate::constraint::{Constraint, PinConstraint, SignalType};
+use crate::constraint::{Constraint, MulticycleTiming, PinConstraint, SignalType, Timing};
 use crate::direction::{Direction, In, InOut, Local, Out};
 use crate::logic::{Logic, LogicJoin, LogicLink};
-use crate::probe::Probe;
+use crate::probe::{Probe, ProbeMut};
 use crate::synth::{Synth, VCDValue};
 use crate::type_descriptor::TypeDescriptor;
 
@@ -25,6 +25,7 @@ pub struct Signal<D: Direction, T: Synth> {
     val: T,
     prev: T,
     pub changed: bool,
+    was_changed: bool,
     claimed: bool,
     id: usize,
     tristate_is_output: bool,
@@ -166,6 +167,23 @@ impl<D: Direction, T: Synth> Signal<D, T> {
             constraint: Constraint::Kind(signal),
         });
     }
+    // Timing exceptions cover the paths through every bit of the signal
+    pub fn add_false_path(&mut self) {
+        self.constraints.push(PinConstraint {
+            index: 0,
+            constraint: Constraint::Timing(Timing::FalsePath),
+        });
+    }
+    pub fn add_multicycle_path(&mut self, clock: &str, cycles: usize) {
+        assert!(cycles > 1);
+        self.constraints.push(PinConstraint {
+            index: 0,
+            constraint: Constraint::Timing(Timing::Multicycle(MulticycleTiming {
+                clock: clock.to_owned(),
+                cycles,
+            })),
+        });
+    }
 }
 
 impl<D: Direction, T: Synth> Atom for Signal<D, T> {
@@ -208,6 +226,16 @@ impl<D: Direction, T: Synth> Atom for Signal<D, T> {
     fn constraints(&self) -> Vec<PinConstraint> {
         self.constraints.clone()
     }
+
+    fn set_vcd(&mut self, value: &VCDValue) -> bool {
+        match T::from_vcd(value) {
+            Some(x) => {
+                self.next = x;
+                true
+            }
+            None => false,
+        }
+    }
 }
 
 impl<D: Direction, T: Synth> Logic for Signal<D, T> {
@@ -221,6 +249,7 @@ impl<D: Direction, T: Synth> Block for Signal<D, T> {
     fn connect_all(&mut self) {}
 
     fn update_all(&mut self) {
+        self.was_changed = self.changed;
         self.changed = self.val != self.next;
         if self.changed {
             self.prev = self.val;
@@ -232,9 +261,23 @@ impl<D: Direction, T: Synth> Block for Signal<D, T> {
         self.changed
     }
 
+    fn update_changed(
+        &mut self,
+        _state: &mut EventState,
+        _owner_updated: bool,
+        _ports_written: bool,
+    ) -> bool {
+        self.update_all();
+        !self.changed && !self.was_changed
+    }
+
     fn accept(&self, name: &str, probe: &mut dyn Probe) {
         probe.visit_atom(name, self);
     }
+
+    fn accept_mut(&mut self, name: &str, probe: &mut dyn ProbeMut) {
+        probe.visit_atom(name, self);
+    }
 }
 
 impl Signal<In, Clock> {
@@ -255,6 +298,7 @@ impl<T: Synth> Signal<Out, T> {
             val: init,
             prev: init,
             changed: false,
+            was_changed: false,
             claimed: false,
             id: get_signal_id(),
             tristate_is_output: false,
@@ -281,6 +325,7 @@ impl<D: Direction, T: Synth> Default for Signal<D, T> {
             val: T::default(),
             prev: T::default(),
             changed: false,
+            was_changed: false,
             claimed: false,
             id: get_signal_id(),
             tristate_is_output: false,
@@ -0,0 +1,50 @@
+use std::collections::{HashMap, HashSet};
+
+/// The drivers of the signals in a circuit, as found in the Verilog of each block.
+/// Signals are named by their full path (e.g., `uut$counter$clock`).  Used by the
+/// probes that need to follow a signal back to where it comes from, like
+/// [hierarchy_report](crate::hierarchy_report::hierarchy_report) and
+/// [check_reset_domains](crate::check_reset_domains::check_reset_domains).
+#[derive(Clone, Debug, Default)]
+pub struct SignalDrivers {
+    drivers: HashMap<String, Vec<String>>,
+}
+
+impl SignalDrivers {
+    /// Record that `from` drives `to`
+    pub fn add(&mut self, from: String, to: String) {
+        self.drivers.entry(to).or_default().push(from);
+    }
+
+    /// Follow the (first) drivers of a clock back to where it comes from.  Clocks
+    /// that come from the same source are in the same clock domain.
+    pub fn clock_source(&self, clock: &str) -> String {
+        let mut source = clock.to_string();
+        let mut seen = HashSet::new();
+        while let Some(driver) = self.drivers.get(&source).and_then(|x| x.first()) {
+            if !seen.insert(source.clone()) {
+                break;
+            }
+            source = driver.clone();
+        }
+        source
+    }
+
+    /// The signals that (eventually) drive this one, but are not driven by logic
+    /// themselves (the inputs of the circuit, and the outputs of registers)
+    pub fn roots(&self, signal: &str) -> Vec<String> {
+        let mut roots = vec![];
+        let mut seen = HashSet::new();
+        let mut pending = vec![signal.to_string()];
+        while let Some(signal) = pending.pop() {
+            if !seen.insert(signal.clone()) {
+                continue;
+            }
+            match self.drivers.get(&signal) {
+                Some(drivers) => pending.extend(drivers.iter().cloned()),
+                None => roots.push(signal),
+            }
+        }
+        roots
+    }
+}
@@ -9,7 +9,7 @@ pub type SignedLiteralType = i64;
 pub const SIGNED_LITERAL_BITS: usize = 64;
 
 #[derive(Clone, Debug, Copy, PartialEq, Default)]
-pub struct Signed<const N: usize>(Bits<N>);
+pub struct Signed<const N: usize>(pub(crate) Bits<N>);
 
 pub trait ToSignedBits {
     fn to_signed_bits<const N: usize>(self) -> Signed<N>;
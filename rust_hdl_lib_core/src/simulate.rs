@@ -1,9 +1,16 @@
 use crossbeam::channel::{bounded, Receiver, Sender};
 use crossbeam::channel::{RecvError, SendError};
 
-use crate::block::Block;
+use crate::block::{Block, EventState, SimProfile};
 use crate::check_error::{check_all, CheckError};
-use crate::vcd_probe::{write_vcd_change, write_vcd_dump, write_vcd_header};
+use crate::fault_injection::{FaultCampaign, FaultInjector, FaultReport};
+use crate::logging::{LogLevel, LogRecord, SimLogger};
+use crate::monitor::Monitor;
+use crate::reference_model::{EquivalenceChecker, ModelSample, ReferenceModel};
+use crate::scoreboard::{Scoreboard, ScoreboardMonitor, ScoreboardSample};
+use crate::simulate::sim_time::{SimDuration, TimeUnit};
+use crate::stimulus::{Stimulus, StimulusRecorder};
+use crate::vcd_probe::{write_vcd_change, write_vcd_dump, write_vcd_header_with_unit, VCDWindow};
 use std::io::Write;
 use std::thread::JoinHandle;
 
@@ -33,14 +40,23 @@ pub enum SimError {
     SimTerminated,
     /// The simulation reached the maximum allowed time for the simulation
     MaxTimeReached,
-    /// The simulation halted - usually this means an assertion failed
+    /// The simulation halted (see [Sim::halt])
     SimHalted,
+    /// A `sim_assert!` or `sim_assert_eq!` failed at the given time.  The message
+    /// includes the failing expression and the values it had.
+    AssertionFailed { time: u64, message: String },
     /// The circuit failed to converge.  This means the logic has some issue (like an oscillation).
     FailedToConverge,
     /// Something went wrong with the circuit check (either a missing connection or other issue, like a latching write).
     Check(CheckError),
     /// The simulation panicked.  This usually means `.unwrap` was called on a result in the testbench.
     SimPanic,
+    /// A [Monitor] attached to the simulation detected a protocol violation at the given time.
+    ProtocolViolation {
+        time: u64,
+        monitor: String,
+        message: String,
+    },
 }
 
 impl From<CheckError> for SimError {
@@ -70,6 +86,7 @@ enum TriggerType<T> {
     Function(Box<dyn Fn(&T) -> bool + Send>),
     Clock(u64),
     Halt,
+    Fail(String),
 }
 
 struct Message<T> {
@@ -93,6 +110,48 @@ struct Worker<T> {
 /// are otherwise difficult or impossible to model.
 pub type CustomLogicFn<T> = Box<dyn Fn(&mut T) -> ()>;
 
+/// The [BehaviorFn] is a boxed function that stands in for the simulation
+/// model of a block (see [Simulation::add_behavior]).  It is given the
+/// circuit and the current simulation time.
+pub type BehaviorFn<T> = Box<dyn FnMut(&mut T, u64)>;
+
+// A [Monitor] attached to a simulation, along with the probe that samples
+// the circuit for it.
+struct ProbedMonitor<M, F> {
+    monitor: M,
+    probe: F,
+}
+
+trait AttachedMonitor<T> {
+    fn check(&mut self, x: &T, time: u64) -> Result<()>;
+    fn finish(&mut self, time: u64) -> Result<()>;
+}
+
+impl<T, M: Monitor, F: Fn(&T) -> M::Sample> AttachedMonitor<T> for ProbedMonitor<M, F> {
+    fn check(&mut self, x: &T, time: u64) -> Result<()> {
+        let monitor = &mut self.monitor;
+        monitor.check((self.probe)(x), time).map_err(|message| {
+            crate::sim_log!(monitor.name(), Error, "VIOLATION {}", message);
+            SimError::ProtocolViolation {
+                time,
+                monitor: monitor.name(),
+                message,
+            }
+        })
+    }
+    fn finish(&mut self, time: u64) -> Result<()> {
+        let monitor = &mut self.monitor;
+        monitor.finish(time).map_err(|message| {
+            crate::sim_log!(monitor.name(), Error, "VIOLATION {}", message);
+            SimError::ProtocolViolation {
+                time,
+                monitor: monitor.name(),
+                message,
+            }
+        })
+    }
+}
+
 /// This type represents a simulation over a circuit `T`.   To simulate
 /// a circuit, you will need to construct one of these structs.
 pub struct Simulation<T> {
@@ -100,8 +159,28 @@ pub struct Simulation<T> {
     recv: Receiver<MessageOrPanic<T>>,
     channel_to_sim: Sender<MessageOrPanic<T>>,
     time: u64,
+    time_unit: TimeUnit,
     testbenches: Vec<JoinHandle<Result<()>>>,
     custom_logic: Vec<CustomLogicFn<T>>,
+    behaviors: Vec<BehaviorFn<T>>,
+    monitors: Vec<Box<dyn AttachedMonitor<T>>>,
+    event_driven: bool,
+    event_state: EventState,
+    profile: Option<SimProfile>,
+    failure_trace: Option<FailureTrace>,
+    stimulus_recording: Option<StimulusRecording>,
+    fault_injector: Option<FaultInjector<T>>,
+    log: SimLogger,
+}
+
+struct StimulusRecording {
+    filename: String,
+    recorder: StimulusRecorder,
+}
+
+struct FailureTrace {
+    filename: String,
+    window: VCDWindow,
 }
 
 /// The `Sim` struct is used to communicate with a simulation.  Every testbench
@@ -109,6 +188,7 @@ pub struct Simulation<T> {
 /// with the core simulation.
 pub struct Sim<T> {
     time: u64,
+    time_unit: TimeUnit,
     to_sim: Sender<MessageOrPanic<T>>,
     from_sim: Receiver<Message<T>>,
 }
@@ -135,15 +215,284 @@ impl<T: Send + 'static + Block> Simulation<T> {
             recv,
             channel_to_sim: send,
             time: 0,
+            time_unit: Default::default(),
             testbenches: vec![],
             custom_logic: vec![],
+            behaviors: vec![],
+            monitors: vec![],
+            event_driven: false,
+            event_state: Default::default(),
+            profile: None,
+            failure_trace: None,
+            stimulus_recording: None,
+            fault_injector: None,
+            log: Default::default(),
+        }
+    }
+    /// Set the length of one tick of simulation time
+    ///
+    /// # Arguments
+    ///
+    /// * `unit` - the [TimeUnit] of every time in the simulation (a picosecond by default)
+    ///
+    /// Every time in the simulation (clock intervals, waits, the maximum time of a run, and
+    /// the times in a VCD file) is a whole number of ticks.  A picosecond is fine enough for
+    /// any clock you are likely to see, and leaves room for about 200 days of simulation.
+    /// Use femtoseconds to model finer delays, or nanoseconds and microseconds when all you
+    /// care about is slow timing.  The VCD timescale follows the unit.  Times given as a
+    /// [std::time::Duration] are converted to ticks when they are passed in, so set the unit
+    /// before adding any clocks or testbenches.
+    pub fn set_time_unit(&mut self, unit: TimeUnit) {
+        assert!(
+            self.workers.is_empty() && self.failure_trace.is_none(),
+            "The time unit must be set before adding clocks, testbenches or a failure trace"
+        );
+        self.time_unit = unit;
+    }
+    /// The length of one tick of simulation time (see [Simulation::set_time_unit])
+    pub fn time_unit(&self) -> TimeUnit {
+        self.time_unit
+    }
+    /// Switch the simulation to event driven updates
+    ///
+    /// # Arguments
+    ///
+    /// * `enable` - if `true`, blocks are only updated when something they contain has changed
+    ///
+    /// Normally, every block in the circuit is updated on every delta cycle.  In an event
+    /// driven simulation, a block whose signals (and those of its children) have not changed
+    /// in the last two delta cycles is not updated, since updating it again cannot change
+    /// anything.  If the block that contains it was not updated either, nothing can have
+    /// written to its inputs, and the whole block is skipped.  Most of a large design is
+    /// idle most of the time (think of the refresh logic in an SDRAM controller, or a FIFO
+    /// nobody is reading), so this saves a lot of work.
+    ///
+    /// This assumes that `update` depends only on the signals of the block, and writes
+    /// only to its own signals and to the inputs of its immediate children.  That is
+    /// always true of `#[hdl_gen]` code.  A hand written `update` that does something else
+    /// (e.g., a model whose internal state is changed by the testbench) may not be updated
+    /// when you expect, so this is off by default.
+    pub fn set_event_driven(&mut self, enable: bool) {
+        self.event_driven = enable;
+    }
+    /// Count the updates of each block in the circuit, and the time they take
+    ///
+    /// # Arguments
+    ///
+    /// * `enable` - if `true`, the simulation keeps a [SimProfile], and prints it at the end
+    ///
+    /// When a long simulation is slow, the profile shows where the time goes.  Each block
+    /// is listed by its path in the circuit (as in a VCD file), with the number of times
+    /// its `update` was called, and the time spent in it (not counting the blocks inside
+    /// it), with the most expensive blocks first.  Timing every update slows the simulation
+    /// down a bit, and every block is updated on every delta cycle (even in an event driven
+    /// simulation), so use it to find what to optimize, and then turn it off.
+    pub fn set_profiling(&mut self, enable: bool) {
+        self.profile = enable.then(SimProfile::default);
+    }
+    /// The profile of the last run (if profiling is enabled - see [Simulation::set_profiling])
+    pub fn profile(&self) -> Option<&SimProfile> {
+        self.profile.as_ref()
+    }
+    /// Keep the messages logged with `sim_log!` at `level` and above (the default is
+    /// [LogLevel::Info])
+    ///
+    /// Simulation models log what they are doing at [LogLevel::Debug], so a regression with
+    /// many of them stays readable.  Turn the level up to see what they do, or down to
+    /// [LogLevel::Error] to see only the failures.
+    pub fn set_log_level(&mut self, level: LogLevel) {
+        self.log.set_level(level);
+    }
+    /// Set the level of the messages kept from one source (instead of the level set with
+    /// [Simulation::set_log_level])
+    ///
+    /// # Arguments
+    ///
+    /// * `source` - the name of the type of a block (like `AD7193Simulator`), or the name a
+    ///   testbench logs as (failed assertions are logged by `sim_assert`)
+    /// * `level` - the least important messages to keep, or `None` to drop all of them
+    pub fn set_block_log_level(&mut self, source: &str, level: Option<LogLevel>) {
+        self.log.set_source_level(source, level);
+    }
+    /// Keep at most `limit` messages from each `sim_log!` (and each source)
+    ///
+    /// A model that logs on every clock can bury everything else.  With a limit, the
+    /// messages past it are counted instead, and the counts are logged (as warnings) when
+    /// the run ends.
+    pub fn set_log_repeat_limit(&mut self, limit: usize) {
+        self.log.set_repeat_limit(Some(limit));
+    }
+    /// Keep the log messages without printing them (they can still be read with
+    /// [Simulation::log_records])
+    pub fn set_log_quiet(&mut self, quiet: bool) {
+        self.log.set_quiet(quiet);
+    }
+    /// The messages kept during the last run
+    pub fn log_records(&self) -> Vec<LogRecord> {
+        self.log.records()
+    }
+    /// Keep the last part of the simulation in memory, and write it to a VCD file if the
+    /// simulation fails
+    ///
+    /// # Arguments
+    ///
+    /// * `window` - how much of the simulation to keep (in ticks, or as a [std::time::Duration])
+    /// * `filename` - the name of the VCD file to write
+    ///
+    /// Long simulations are usually run with [Simulation::run], since tracing every
+    /// signal for the whole run produces enormous VCD files.  But when an assertion fails
+    /// deep into the run, you then have to run it all again with tracing on to see what
+    /// happened.  With a failure trace, [Simulation::run] keeps the signal changes for the
+    /// `window` of the simulation, and if a `sim_assert!` or `sim_assert_eq!` fails (or a
+    /// [Monitor] reports a violation), it writes them out to `filename`.  The file covers
+    /// just the time leading up to the failure, so it is quick to open and look at.
+    pub fn set_failure_trace(&mut self, window: impl SimDuration, filename: &str) {
+        self.failure_trace = Some(FailureTrace {
+            filename: filename.into(),
+            window: VCDWindow::new(window.to_ticks(self.time_unit)),
+        });
+    }
+    fn record_failure_trace(&mut self, x: &T) {
+        if let Some(trace) = &mut self.failure_trace {
+            trace.window.record(self.time, x);
         }
     }
+    fn write_failure_trace(&self, x: &T) {
+        if let Some(trace) = &self.failure_trace {
+            let mut vcd = vec![];
+            trace.window.write(&mut vcd, x, self.time_unit);
+            std::fs::write(&trace.filename, vcd).unwrap();
+            println!("Failure trace written to {}", trace.filename);
+        }
+    }
+    /// Record the stimulus applied to the circuit to a file
+    ///
+    /// # Arguments
+    ///
+    /// * `filename` - the name of the file to write the [Stimulus] to
+    ///
+    /// Every change to the top level inputs of the circuit (including the clocks) is
+    /// recorded as the simulation runs, and written out to `filename` when it ends.
+    /// The recording can then be played back with [Simulation::add_stimulus] against
+    /// a modified version of the circuit, without the testbenches that produced it.
+    /// Inputs of child blocks that are driven directly by a testbench are not
+    /// recorded (see [Stimulus]).
+    pub fn record_stimulus(&mut self, filename: &str) {
+        self.stimulus_recording = Some(StimulusRecording {
+            filename: filename.into(),
+            recorder: Default::default(),
+        });
+    }
+    fn record_stimulus_step(&mut self, x: &T) {
+        if let Some(recording) = &mut self.stimulus_recording {
+            recording.recorder.record(self.time, x);
+        }
+    }
+    fn write_stimulus(&self) {
+        if let Some(recording) = &self.stimulus_recording {
+            recording
+                .recorder
+                .stimulus()
+                .save(&recording.filename)
+                .unwrap();
+        }
+    }
+    /// Play back a recorded [Stimulus]
+    ///
+    /// # Arguments
+    ///
+    /// * `stimulus` - the recording to play back (see [Simulation::record_stimulus])
+    ///
+    /// The steps of the recording are applied to the top level inputs of the circuit
+    /// at the times they were recorded.  Since the recording includes the clocks, the
+    /// simulation needs no clocks of its own, but testbenches that check the outputs
+    /// can be added as usual.  If the circuit does not have one of the recorded inputs,
+    /// the simulation fails with a [SimError::AssertionFailed].
+    pub fn add_stimulus(&mut self, stimulus: Stimulus) {
+        self.add_testbench(move |mut sim: Sim<T>| {
+            let mut x = sim.init()?;
+            for step in stimulus.steps() {
+                x = sim.wait(step.time - sim.time(), x)?;
+                if let Err(message) = step.apply(x.as_mut()) {
+                    return sim.fail(message, x);
+                }
+            }
+            sim.done(x)
+        });
+    }
+    /// Inject faults into the circuit as it runs, and check how it copes with them
+    ///
+    /// # Arguments
+    ///
+    /// * `campaign` - the faults to inject (see [FaultCampaign])
+    /// * `detected` - a closure that returns `true` when the circuit has flagged an error
+    /// * `corrupted` - a closure that returns `true` when the circuit is producing wrong results
+    ///
+    /// Each fault is injected at its time (between the events of the testbenches and
+    /// clocks), and the circuit is then watched for the window of the campaign.  If
+    /// `detected` returns `true` in that window, the fault was detected.  Otherwise, if
+    /// `corrupted` returns `true`, the fault got through unnoticed, which is usually what
+    /// the campaign is looking for.  What counts as corrupted is up to the testbench - an
+    /// output that differs from a model, a TMR voter that no longer agrees with a spare
+    /// copy, a state machine in the wrong state, and so on.  The detection flag must be
+    /// clear again before the next fault, or the next fault will be counted as detected
+    /// too.  Faults that fall after the end of the simulation are not injected.
+    ///
+    /// The results are in the [FaultReport] (see [Simulation::fault_report]), which is
+    /// also printed at the end of the run.  If a fault names a signal the circuit does
+    /// not have, the simulation fails with a [SimError::AssertionFailed].
+    pub fn add_fault_campaign<D, C>(&mut self, campaign: FaultCampaign, detected: D, corrupted: C)
+    where
+        D: Fn(&T) -> bool + 'static,
+        C: Fn(&T) -> bool + 'static,
+    {
+        self.fault_injector = Some(FaultInjector::new(
+            campaign,
+            Box::new(detected),
+            Box::new(corrupted),
+        ));
+    }
+    /// The results of the fault campaign of the last run (see [Simulation::add_fault_campaign])
+    pub fn fault_report(&self) -> Option<&FaultReport> {
+        self.fault_injector.as_ref().map(|x| x.report())
+    }
+    // The time of the next fault, if it is due no later than `time`
+    fn fault_due(&self, time: u64) -> Option<u64> {
+        self.fault_injector
+            .as_ref()
+            .and_then(|x| x.next_time())
+            .filter(|t| *t <= time)
+    }
+    fn inject_fault(&mut self, mut x: Box<T>, time: u64) -> Result<Box<T>> {
+        self.time = self.time.max(time);
+        let injector = self.fault_injector.as_mut().unwrap();
+        self.log.set_time(self.time);
+        if let Err(message) = injector.inject(&mut x) {
+            crate::sim_log!("fault_injection", Error, "FAULT INJECTION {}", message);
+            self.terminate();
+            return Err(SimError::AssertionFailed {
+                time: self.time,
+                message,
+            });
+        }
+        self.settle(x)
+    }
+    fn observe_faults(&mut self, x: &T) {
+        if let Some(injector) = &mut self.fault_injector {
+            injector.observe(x, self.time);
+        }
+    }
+    fn failure_message(&self) -> Option<String> {
+        self.workers.iter().find_map(|worker| match &worker.kind {
+            TriggerType::Fail(message) => Some(message.clone()),
+            _ => None,
+        })
+    }
     /// Add a clock function to the simulation
     ///
     /// # Arguments
     ///
-    /// * `interval` - the number of picoseconds between calls to the clock closure
+    /// * `interval` - the time between calls to the clock closure (in ticks, or as a [std::time::Duration])
     /// * `clock_fn` - a closure to change the clock state of the circuit
     ///
     /// # Example
@@ -166,10 +515,11 @@ impl<T: Send + 'static + Block> Simulation<T> {
     /// sim.add_clock(5, |x| x.clock.next = !x.clock.val()); // Toggles the clock every 5 picoseconds.
     /// ```
     ///
-    pub fn add_clock<F>(&mut self, interval: u64, clock_fn: F)
+    pub fn add_clock<F>(&mut self, interval: impl SimDuration, clock_fn: F)
     where
         F: Fn(&mut Box<T>) -> () + Send + 'static + std::panic::RefUnwindSafe,
     {
+        let interval = interval.to_ticks(self.time_unit);
         self.add_testbench(move |mut ep: Sim<T>| {
             let mut x = ep.init()?;
             loop {
@@ -186,8 +536,8 @@ impl<T: Send + 'static + Block> Simulation<T> {
     ///
     /// # Arguments
     ///
-    /// * `interval` - the delay in picoseconds between the clock function being called
-    /// * `phase_delay` - the number of picoseconds to wait before the clock starts being toggled
+    /// * `interval` - the delay (in ticks, or as a [std::time::Duration]) between the clock function being called
+    /// * `phase_delay` - the time to wait before the clock starts being toggled
     /// * `clock_fn` - the function that toggles the actual clock.
     ///
     /// # Example
@@ -211,10 +561,16 @@ impl<T: Send + 'static + Block> Simulation<T> {
     /// sim.add_phased_clock(5, 15, |x| x.clock.next = !x.clock.val());
     /// ```
     ///
-    pub fn add_phased_clock<F>(&mut self, interval: u64, phase_delay: u64, clock_fn: F)
-    where
+    pub fn add_phased_clock<F>(
+        &mut self,
+        interval: impl SimDuration,
+        phase_delay: impl SimDuration,
+        clock_fn: F,
+    ) where
         F: Fn(&mut Box<T>) -> () + Send + 'static + std::panic::RefUnwindSafe,
     {
+        let interval = interval.to_ticks(self.time_unit);
+        let phase_delay = phase_delay.to_ticks(self.time_unit);
         self.add_testbench(move |mut ep: Sim<T>| {
             let mut x = ep.init()?;
             x = ep.wait(phase_delay, x)?;
@@ -239,7 +595,9 @@ impl<T: Send + 'static + Block> Simulation<T> {
         F: Fn(Sim<T>) -> Result<()> + Send + 'static + std::panic::RefUnwindSafe,
     {
         let ep = self.endpoint();
+        let log = self.log.clone();
         self.testbenches.push(std::thread::spawn(move || {
+            let _log = log.install();
             let ep_panic = ep.to_sim.clone();
             let result = std::panic::catch_unwind(|| testbench(ep));
             match result {
@@ -257,6 +615,127 @@ impl<T: Send + 'static + Block> Simulation<T> {
     {
         self.custom_logic.push(Box::new(logic));
     }
+    /// Attach a behavioral model to a block that cannot otherwise be simulated
+    ///
+    /// # Arguments
+    ///
+    /// * `select` - a closure that picks the block out of the circuit
+    /// * `behavior` - a closure that updates the block, given the current time (in ticks of the
+    ///   simulation [TimeUnit])
+    ///
+    /// Blocks that wrap external IP with a [Wrapper](crate::ast::Wrapper) or
+    /// [BlackBox](crate::ast::BlackBox) (PLLs, DDR input buffers, memory controllers) have
+    /// no simulation model of their own, so their outputs never change, and a simulation of
+    /// the whole system stalls at them.  The behavior is run along with the circuit
+    /// updates, and can drive the outputs of the block from its inputs, from state it keeps
+    /// itself, or from the time.  For example, a PLL that locks after 2 microseconds:
+    ///
+    /// ```rust,ignore
+    /// sim.add_behavior(|x| &mut x.pll, |pll, time| pll.locked.next = time >= 2_000_000);
+    /// ```
+    ///
+    /// Periodic outputs (like the PLL output clock) are better left to the clock engine,
+    /// with [Simulation::add_clock] toggling the output of the block directly.
+    pub fn add_behavior<B, S, F>(&mut self, select: S, mut behavior: F)
+    where
+        B: Block,
+        S: Fn(&mut T) -> &mut B + 'static,
+        F: FnMut(&mut B, u64) + 'static,
+    {
+        self.behaviors.push(Box::new(move |x: &mut T, time: u64| {
+            behavior(select(x), time)
+        }));
+    }
+    /// Attach a [Monitor] to the simulation
+    ///
+    /// # Arguments
+    ///
+    /// * `monitor` - the monitor that checks the protocol rules
+    /// * `probe` - a closure that extracts the monitor's sample from the circuit
+    ///
+    /// The monitor is run after every simulation event.  The first violation
+    /// it reports ends the simulation with a [SimError::ProtocolViolation].
+    pub fn add_monitor<M, F>(&mut self, monitor: M, probe: F)
+    where
+        M: Monitor + 'static,
+        F: Fn(&T) -> M::Sample + 'static,
+    {
+        self.monitors
+            .push(Box::new(ProbedMonitor { monitor, probe }));
+    }
+    /// Check the circuit against a [ReferenceModel] as it runs
+    ///
+    /// # Arguments
+    ///
+    /// * `model` - the reference model of the circuit
+    /// * `clock` - a closure that returns the level of the clock to sample on
+    /// * `stimulus` - a closure that returns the stimulus being applied to the circuit (if any)
+    /// * `response` - a closure that returns the response the circuit is producing (if any)
+    ///
+    /// At each rising edge of the clock, any stimulus is fed to the model, and any
+    /// response is compared with the next one the model produced.  The first one that
+    /// differs ends the simulation with a [SimError::ProtocolViolation] that describes the
+    /// divergence.  The testbenches still drive the circuit as usual - the model only sees
+    /// what they do.
+    pub fn add_reference_model<M, C, S, R>(&mut self, model: M, clock: C, stimulus: S, response: R)
+    where
+        M: ReferenceModel + 'static,
+        C: Fn(&T) -> bool + 'static,
+        S: Fn(&T) -> Option<M::Stimulus> + 'static,
+        R: Fn(&T) -> Option<M::Response> + 'static,
+    {
+        self.add_monitor(EquivalenceChecker::new(model), move |x: &T| ModelSample {
+            clock: clock(x),
+            stimulus: stimulus(x),
+            response: response(x),
+        });
+    }
+    /// Check the transactions produced by the circuit against a [Scoreboard] as it runs
+    ///
+    /// # Arguments
+    ///
+    /// * `scoreboard` - the scoreboard to fill in and check against (keep a clone to
+    ///   inspect it after the simulation)
+    /// * `clock` - a closure that returns the level of the clock to sample on
+    /// * `expected` - a closure that returns the transactions the circuit is being given
+    /// * `observed` - a closure that returns the transactions the circuit is producing
+    ///
+    /// At each rising edge of the clock, the expected transactions are added to the
+    /// scoreboard, and the observed ones are matched against it (by key, in any order).
+    /// The first one that does not match ends the simulation with a
+    /// [SimError::ProtocolViolation].  Transactions that never came out are not an error
+    /// until [Scoreboard::finish] is called.
+    pub fn add_scoreboard<K, V, C, E, O>(
+        &mut self,
+        scoreboard: Scoreboard<K, V>,
+        clock: C,
+        expected: E,
+        observed: O,
+    ) where
+        K: Eq + std::hash::Hash + Clone + std::fmt::Debug + 'static,
+        V: Clone + std::fmt::Debug + PartialEq + 'static,
+        C: Fn(&T) -> bool + 'static,
+        E: Fn(&T) -> Vec<(K, V)> + 'static,
+        O: Fn(&T) -> Vec<(K, V)> + 'static,
+    {
+        self.add_monitor(ScoreboardMonitor::new(scoreboard), move |x: &T| {
+            ScoreboardSample {
+                clock: clock(x),
+                expected: expected(x),
+                observed: observed(x),
+            }
+        });
+    }
+    fn check_monitors(&mut self, x: &T) -> Result<()> {
+        let time = self.time;
+        for monitor in &mut self.monitors {
+            if let Err(e) = monitor.check(x, time) {
+                self.terminate();
+                return Err(e);
+            }
+        }
+        Ok(())
+    }
     pub fn endpoint(&mut self) -> Sim<T> {
         let (send_to_worker, recv_from_sim_to_worker) = bounded(0);
         let id = self.workers.len();
@@ -270,6 +749,7 @@ impl<T: Send + 'static + Block> Simulation<T> {
             to_sim: self.channel_to_sim.clone(),
             from_sim: recv_from_sim_to_worker,
             time: 0,
+            time_unit: self.time_unit,
         }
     }
     fn dispatch(&mut self, idx: usize, x: Box<T>) -> Result<Box<T>> {
@@ -279,21 +759,38 @@ impl<T: Send + 'static + Block> Simulation<T> {
             circuit: x,
         })?;
         let x = self.recv.recv()?;
-        let mut x = match x {
+        let x = match x {
             MessageOrPanic::Message(x) => x,
             MessageOrPanic::Panic => {
                 return Err(SimError::SimPanic);
             }
         };
         worker.kind = x.kind;
-        // Update the circuit
+        self.settle(x.circuit)
+    }
+    // Update the circuit until it stops changing
+    fn settle(&mut self, mut x: Box<T>) -> Result<Box<T>> {
         let mut converged = false;
-        for _ in 0..100 {
+        for iteration in 0..100 {
             for l in &self.custom_logic {
-                l(&mut x.circuit);
+                l(&mut x);
+            }
+            for b in &mut self.behaviors {
+                b(&mut x, self.time);
+            }
+            if let Some(profile) = &mut self.profile {
+                profile.rewind();
+                x.update_profiled("uut", profile);
+            } else if self.event_driven {
+                // The worker (and any custom logic) may have written to any signal
+                let external =
+                    iteration == 0 || !self.custom_logic.is_empty() || !self.behaviors.is_empty();
+                self.event_state.rewind(external);
+                x.update_changed(&mut self.event_state, false, false);
+            } else {
+                x.update_all();
             }
-            x.circuit.update_all();
-            if !x.circuit.has_changed() {
+            if !x.has_changed() {
                 converged = true;
                 break;
             }
@@ -301,7 +798,7 @@ impl<T: Send + 'static + Block> Simulation<T> {
         if !converged {
             Err(SimError::FailedToConverge)
         } else {
-            Ok(x.circuit)
+            Ok(x)
         }
     }
     fn scan_workers(&self, x: &T) -> NextTime {
@@ -310,7 +807,7 @@ impl<T: Send + 'static + Block> Simulation<T> {
         let mut only_clock_waiters = true;
         for worker in self.workers.iter() {
             match &worker.kind {
-                TriggerType::Halt => {
+                TriggerType::Halt | TriggerType::Fail(_) => {
                     return NextTime {
                         halted: true,
                         time: !0,
@@ -349,18 +846,65 @@ impl<T: Send + 'static + Block> Simulation<T> {
             halted: false,
         }
     }
-    fn terminate(&mut self) {
+    // Returns the first violation reported by a monitor as it finishes
+    fn terminate(&mut self) -> Option<SimError> {
+        let time = self.time;
+        let mut violation = None;
+        for monitor in &mut self.monitors {
+            if let Err(e) = monitor.finish(time) {
+                violation.get_or_insert(e);
+            }
+        }
+        if let Some(injector) = &self.fault_injector {
+            println!("Fault injection report\n{}", injector.report());
+        }
+        if let Some(profile) = &self.profile {
+            println!(
+                "Simulation profile at {} {}\n{}",
+                time,
+                self.time_unit.suffix(),
+                profile
+            );
+        }
         self.workers.clear();
         for handle in std::mem::take(&mut self.testbenches) {
             let _ = handle.join().unwrap();
         }
+        violation
     }
-    pub fn run(&mut self, mut x: Box<T>, max_time: u64) -> Result<()> {
+    pub fn run(&mut self, x: Box<T>, max_time: impl SimDuration) -> Result<()> {
+        let _log = self.log.install();
+        self.log.start(self.time);
+        let result = self.run_untraced(x, max_time.to_ticks(self.time_unit));
+        self.write_stimulus();
+        self.log.finish();
+        result
+    }
+    fn run_untraced(&mut self, mut x: Box<T>, max_time: u64) -> Result<()> {
         x.as_mut().connect_all();
         check_all(x.as_mut())?;
+        self.event_state = Default::default();
+        if let Some(profile) = &mut self.profile {
+            *profile = Default::default();
+        }
+        if let Some(recording) = &mut self.stimulus_recording {
+            recording.recorder.clear();
+        }
+        if let Some(trace) = &mut self.failure_trace {
+            trace.window.clear();
+        }
+        if let Some(injector) = &mut self.fault_injector {
+            injector.clear();
+        }
         // First initialize the workers.
         for id in 0..self.workers.len() {
             x = self.dispatch(id, x)?;
+            self.record_stimulus_step(&x);
+        }
+        self.record_failure_trace(&x);
+        if let Err(e) = self.check_monitors(&x) {
+            self.write_failure_trace(&x);
+            return Err(e);
         }
         // Next run until we have no one else waiting
         let mut halted = false;
@@ -370,32 +914,81 @@ impl<T: Send + 'static + Block> Simulation<T> {
                 halted = next.halted;
                 break;
             }
-            self.time = next.time;
-            x = self.dispatch(next.idx, x)?;
+            if let Some(time) = self.fault_due(next.time) {
+                x = self.inject_fault(x, time)?;
+            } else {
+                self.time = next.time;
+                self.log.set_time(self.time);
+                x = self.dispatch(next.idx, x)?;
+                self.record_stimulus_step(&x);
+            }
+            self.observe_faults(&x);
+            self.record_failure_trace(&x);
+            if let Err(e) = self.check_monitors(&x) {
+                self.write_failure_trace(&x);
+                return Err(e);
+            }
         }
-        self.terminate();
+        let failure = self.failure_message();
+        let violation = self.terminate();
         if self.time >= max_time {
             return Err(SimError::MaxTimeReached);
         }
+        if let Some(message) = failure {
+            self.write_failure_trace(&x);
+            return Err(SimError::AssertionFailed {
+                time: self.time,
+                message,
+            });
+        }
+        if let Some(e) = violation {
+            self.write_failure_trace(&x);
+            return Err(e);
+        }
         if halted {
             return Err(SimError::SimHalted);
         }
         Ok(())
     }
-    pub fn run_to_file(&mut self, x: Box<T>, max_time: u64, name: &str) -> Result<()> {
+    pub fn run_to_file(&mut self, x: Box<T>, max_time: impl SimDuration, name: &str) -> Result<()> {
         let mut vcd = vec![];
         let result = self.run_traced(x, max_time, &mut vcd);
         std::fs::write(name, vcd).unwrap();
         result
     }
-    pub fn run_traced<W: Write>(&mut self, mut x: Box<T>, max_time: u64, trace: W) -> Result<()> {
+    pub fn run_traced<W: Write>(
+        &mut self,
+        x: Box<T>,
+        max_time: impl SimDuration,
+        trace: W,
+    ) -> Result<()> {
+        let _log = self.log.install();
+        self.log.start(self.time);
+        let result = self.run_with_trace(x, max_time.to_ticks(self.time_unit), trace);
+        self.write_stimulus();
+        self.log.finish();
+        result
+    }
+    fn run_with_trace<W: Write>(&mut self, mut x: Box<T>, max_time: u64, trace: W) -> Result<()> {
         x.as_mut().connect_all();
         check_all(x.as_mut())?;
-        let mut vcd = write_vcd_header(trace, x.as_ref());
+        self.event_state = Default::default();
+        if let Some(profile) = &mut self.profile {
+            *profile = Default::default();
+        }
+        if let Some(recording) = &mut self.stimulus_recording {
+            recording.recorder.clear();
+        }
+        if let Some(injector) = &mut self.fault_injector {
+            injector.clear();
+        }
+        let mut vcd = write_vcd_header_with_unit(trace, x.as_ref(), self.time_unit);
         // First initialize the workers.
         for id in 0..self.workers.len() {
             x = self.dispatch(id, x)?;
+            self.record_stimulus_step(&x);
         }
+        self.check_monitors(&x)?;
         vcd = write_vcd_dump(vcd, x.as_ref());
         let mut halted = false;
         // Next run until we have no one else waiting
@@ -405,15 +998,33 @@ impl<T: Send + 'static + Block> Simulation<T> {
                 halted = next.halted;
                 break;
             }
-            self.time = next.time;
-            x = self.dispatch(next.idx, x)?;
-            vcd.timestamp(next.time).unwrap();
+            if let Some(time) = self.fault_due(next.time) {
+                x = self.inject_fault(x, time)?;
+            } else {
+                self.time = next.time;
+                self.log.set_time(self.time);
+                x = self.dispatch(next.idx, x)?;
+                self.record_stimulus_step(&x);
+            }
+            self.observe_faults(&x);
+            vcd.timestamp(self.time).unwrap();
             vcd = write_vcd_change(vcd, x.as_ref());
+            self.check_monitors(&x)?;
         }
-        self.terminate();
+        let failure = self.failure_message();
+        let violation = self.terminate();
         if self.time >= max_time {
             return Err(SimError::MaxTimeReached);
         }
+        if let Some(message) = failure {
+            return Err(SimError::AssertionFailed {
+                time: self.time,
+                message,
+            });
+        }
+        if let Some(e) = violation {
+            return Err(e);
+        }
         if halted {
             return Err(SimError::SimHalted);
         }
@@ -422,11 +1033,100 @@ impl<T: Send + 'static + Block> Simulation<T> {
 }
 
 pub mod sim_time {
-    pub const ONE_PICOSECOND: u64 = 1;
-    pub const ONE_NANOSECOND: u64 = 1000 * ONE_PICOSECOND;
-    pub const ONE_MICROSECOND: u64 = 1000 * ONE_NANOSECOND;
+    use std::time::Duration;
+
+    /// The [TimeUnit] a simulation uses unless it is given another one
+    pub const DEFAULT_TIME_UNIT: TimeUnit = TimeUnit::Picoseconds;
+
+    // These are in ticks of the default time unit.  A simulation with another
+    // time unit should use a Duration (or TimeUnit::ticks) instead.
+    pub const ONE_PICOSECOND: u64 = DEFAULT_TIME_UNIT.ticks_in_femtoseconds(1_000);
+    pub const ONE_NANOSECOND: u64 = DEFAULT_TIME_UNIT.ticks_in_femtoseconds(1_000_000);
+    pub const ONE_MICROSECOND: u64 = DEFAULT_TIME_UNIT.ticks_in_femtoseconds(1_000_000_000);
     pub const ONE_MILLISECOND: u64 = 1000 * ONE_MICROSECOND;
     pub const ONE_SEC: u64 = 1000 * ONE_MILLISECOND;
+
+    /// The length of one tick of simulation time (see [Simulation::set_time_unit]).
+    ///
+    /// [Simulation::set_time_unit]: crate::simulate::Simulation::set_time_unit
+    #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+    pub enum TimeUnit {
+        Femtoseconds,
+        Picoseconds,
+        Nanoseconds,
+        Microseconds,
+    }
+
+    impl Default for TimeUnit {
+        fn default() -> Self {
+            DEFAULT_TIME_UNIT
+        }
+    }
+
+    impl TimeUnit {
+        /// The length of one tick in femtoseconds
+        pub const fn femtoseconds(self) -> u64 {
+            match self {
+                TimeUnit::Femtoseconds => 1,
+                TimeUnit::Picoseconds => 1_000,
+                TimeUnit::Nanoseconds => 1_000_000,
+                TimeUnit::Microseconds => 1_000_000_000,
+            }
+        }
+        /// The number of (whole) ticks in the given number of femtoseconds
+        pub const fn ticks_in_femtoseconds(self, femtoseconds: u64) -> u64 {
+            femtoseconds / self.femtoseconds()
+        }
+        /// The number of (whole) ticks in the given [Duration]
+        pub fn ticks(self, duration: Duration) -> u64 {
+            (duration.as_nanos() * 1_000_000 / self.femtoseconds() as u128) as u64
+        }
+        /// The [Duration] of the given number of ticks (to the nearest nanosecond below)
+        pub fn duration(self, ticks: u64) -> Duration {
+            Duration::from_nanos((ticks as u128 * self.femtoseconds() as u128 / 1_000_000) as u64)
+        }
+        /// The number of (whole) ticks in half a period of a clock running at `freq_hz`
+        pub const fn half_period(self, freq_hz: u64) -> u64 {
+            self.ticks_in_femtoseconds(1_000_000_000_000_000 / (2 * freq_hz))
+        }
+        /// The abbreviation of the unit, as used in VCD files
+        pub fn suffix(self) -> &'static str {
+            match self {
+                TimeUnit::Femtoseconds => "fs",
+                TimeUnit::Picoseconds => "ps",
+                TimeUnit::Nanoseconds => "ns",
+                TimeUnit::Microseconds => "us",
+            }
+        }
+        pub(crate) fn vcd_timescale(self) -> vcd::TimescaleUnit {
+            match self {
+                TimeUnit::Femtoseconds => vcd::TimescaleUnit::FS,
+                TimeUnit::Picoseconds => vcd::TimescaleUnit::PS,
+                TimeUnit::Nanoseconds => vcd::TimescaleUnit::NS,
+                TimeUnit::Microseconds => vcd::TimescaleUnit::US,
+            }
+        }
+    }
+
+    /// An amount of simulation time.  A `u64` is a raw number of ticks (in whatever
+    /// [TimeUnit] the simulation uses), while a [Duration] is converted to ticks, so that
+    /// `sim.wait(Duration::from_micros(100), x)` means the same thing in any simulation.
+    /// A [Duration] cannot be shorter than a nanosecond, so use ticks for anything finer.
+    pub trait SimDuration {
+        fn to_ticks(self, unit: TimeUnit) -> u64;
+    }
+
+    impl SimDuration for u64 {
+        fn to_ticks(self, _unit: TimeUnit) -> u64 {
+            self
+        }
+    }
+
+    impl SimDuration for Duration {
+        fn to_ticks(self, unit: TimeUnit) -> u64 {
+            unit.ticks(self)
+        }
+    }
 }
 
 impl<T> Sim<T> {
@@ -447,9 +1147,9 @@ impl<T> Sim<T> {
         }
         Ok(t.circuit)
     }
-    pub fn clock(&mut self, delta: u64, x: Box<T>) -> Result<Box<T>> {
+    pub fn clock(&mut self, delta: impl SimDuration, x: Box<T>) -> Result<Box<T>> {
         self.to_sim.send(MessageOrPanic::Message(Message {
-            kind: TriggerType::Clock(delta + self.time),
+            kind: TriggerType::Clock(delta.to_ticks(self.time_unit) + self.time),
             circuit: x,
         }))?;
         let t = self.from_sim.recv()?;
@@ -458,9 +1158,10 @@ impl<T> Sim<T> {
         }
         Ok(t.circuit)
     }
-    pub fn wait(&mut self, delta: u64, x: Box<T>) -> Result<Box<T>> {
+    /// Wait for `delta` (in ticks, or as a [std::time::Duration]) of simulation time
+    pub fn wait(&mut self, delta: impl SimDuration, x: Box<T>) -> Result<Box<T>> {
         self.to_sim.send(MessageOrPanic::Message(Message {
-            kind: TriggerType::Time(delta + self.time),
+            kind: TriggerType::Time(delta.to_ticks(self.time_unit) + self.time),
             circuit: x,
         }))?;
         let t = self.from_sim.recv()?;
@@ -483,9 +1184,30 @@ impl<T> Sim<T> {
         }))?;
         Err(SimError::SimHalted)
     }
+    /// Stop the simulation because a check failed.  This is what `sim_assert!` and
+    /// `sim_assert_eq!` use - the message ends up in the [SimError::AssertionFailed]
+    /// returned by the simulation.
+    pub fn fail(&self, message: String, x: Box<T>) -> Result<()> {
+        self.to_sim.send(MessageOrPanic::Message(Message {
+            kind: TriggerType::Fail(message.clone()),
+            circuit: x,
+        }))?;
+        Err(SimError::AssertionFailed {
+            time: self.time,
+            message,
+        })
+    }
     pub fn time(&self) -> u64 {
         self.time
     }
+    /// The current simulation time as a [std::time::Duration]
+    pub fn now(&self) -> std::time::Duration {
+        self.time_unit.duration(self.time)
+    }
+    /// The length of one tick of simulation time (see [Simulation::set_time_unit])
+    pub fn time_unit(&self) -> TimeUnit {
+        self.time_unit
+    }
 }
 
 #[macro_export]
@@ -533,8 +1255,9 @@ macro_rules! wait_clock_cycles {
 macro_rules! sim_assert {
     ($sim: ident, $test: expr, $circuit: ident) => {
         if !($test) {
-            println!("HALT {}", stringify!($test));
-            return $sim.halt($circuit);
+            let message = stringify!($test).to_string();
+            $crate::sim_log!("sim_assert", Error, "HALT {}", message);
+            return $sim.fail(message, $circuit);
         }
     };
 }
@@ -543,14 +1266,15 @@ macro_rules! sim_assert {
 macro_rules! sim_assert_eq {
     ($sim: ident, $lhs: expr, $rhs: expr, $circuit: ident) => {
         if !($lhs == $rhs) {
-            println!(
-                "HALT {} != {},  {:?} != {:?}",
+            let message = format!(
+                "{} != {},  {:?} != {:?}",
                 stringify!($lhs),
                 stringify!($rhs),
                 $lhs,
                 $rhs
             );
-            return $sim.halt($circuit);
+            $crate::sim_log!("sim_assert", Error, "HALT {}", message);
+            return $sim.fail(message, $circuit);
         }
     };
 }
@@ -560,7 +1284,7 @@ macro_rules! simple_sim {
     ($kind: ty, $($clock: ident).+, $clock_speed_hz: expr, $fixture: ident, $testbench: expr) => {
         {
             let mut sim = Simulation::new();
-            let half_period = 1_000_000_000_000 / (2 * $clock_speed_hz);
+            let half_period = sim.time_unit().half_period($clock_speed_hz);
             sim.add_clock(half_period, |x: &mut Box<$kind>| x.$($clock).+.next = !x.$($clock).+.val());
             sim.add_testbench(move |mut $fixture: Sim<$kind>| {
                 $testbench
@@ -570,4 +1294,45 @@ macro_rules! simple_sim {
     }
 }
 
-pub const SIMULATION_TIME_ONE_SECOND: u64 = 1_000_000_000_000;
+/// Build a [Simulation] with its clocks (and optionally a reset) already set up
+///
+/// The first form takes the type of the circuit, and a list of clocks with their
+/// frequencies in Hz:
+/// ```ignore
+/// let mut sim = test_harness!(MyCircuit, clocks: [clock => 100_000_000, link.clock => 48_000_000]);
+/// ```
+/// The second form also holds a reset input (a `Signal<In, Bit>`) high for the given number
+/// of cycles of the first clock, before releasing it:
+/// ```ignore
+/// let mut sim = test_harness!(MyCircuit, clocks: [clock => 100_000_000], reset: reset, 4);
+/// ```
+/// The reset is driven by a testbench that is added before any of yours, so a testbench
+/// can wait for it to finish with `x = sim.watch(|x| !x.reset.val(), x)?`.
+#[macro_export]
+macro_rules! test_harness {
+    ($kind: ty, clocks: [$($($clock: ident).+ => $clock_speed_hz: expr),+ $(,)?]) => {
+        {
+            let mut sim = Simulation::new();
+            $(
+                let half_period = sim.time_unit().half_period($clock_speed_hz);
+                sim.add_clock(half_period, |x: &mut Box<$kind>| x.$($clock).+.next = !x.$($clock).+.val());
+            )+
+            sim
+        }
+    };
+    ($kind: ty, clocks: [$($first: ident).+ => $first_speed_hz: expr $(, $($clock: ident).+ => $clock_speed_hz: expr)* $(,)?], reset: $($reset: ident).+, $cycles: expr) => {
+        {
+            let mut sim = test_harness!($kind, clocks: [$($first).+ => $first_speed_hz $(, $($clock).+ => $clock_speed_hz)*]);
+            sim.add_testbench(move |mut sim: Sim<$kind>| {
+                let mut x = sim.init()?;
+                x.$($reset).+.next = true;
+                wait_clock_cycles!(sim, $($first).+, x, $cycles);
+                x.$($reset).+.next = false;
+                sim.done(x)
+            });
+            sim
+        }
+    };
+}
+
+pub const SIMULATION_TIME_ONE_SECOND: u64 = sim_time::ONE_SEC;
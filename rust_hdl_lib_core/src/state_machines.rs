@@ -0,0 +1,263 @@
+use crate::ast::{
+    Verilog, VerilogBlock, VerilogBlockOrConditional, VerilogExpression, VerilogStatement,
+};
+use crate::atom::Atom;
+use crate::block::Block;
+use crate::probe::Probe;
+use crate::probe_path::ProbePath;
+use crate::type_descriptor::TypeKind;
+use crate::verilog_gen::verilog_expression;
+use std::collections::HashMap;
+
+/// A transition between two states of a [StateMachine].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateTransition {
+    /// The state the transition starts from, or `None` if the transition
+    /// can happen from any state (like a reset).
+    pub from: Option<String>,
+    /// The state the transition leads to.
+    pub to: String,
+    /// The condition under which the transition happens, as a Verilog
+    /// expression.  It is empty if the transition is unconditional.
+    pub condition: String,
+}
+
+/// A state machine recognized in a circuit by [extract_state_machines].
+#[derive(Clone, Debug, PartialEq)]
+pub struct StateMachine {
+    /// The path to the block that contains the state machine (e.g., `uut$controller`)
+    pub path: String,
+    /// The name of the register that holds the state (e.g., `state`)
+    pub register: String,
+    /// The states, in the order they are declared.  The first one is the initial state.
+    pub states: Vec<String>,
+    /// The transitions found in the HDL code
+    pub transitions: Vec<StateTransition>,
+}
+
+fn quoted(x: &str) -> String {
+    x.replace('"', "\\\"")
+}
+
+impl StateMachine {
+    /// The state transition diagram in Graphviz format, e.g., for `dot -Tsvg`.
+    pub fn dot(&self) -> String {
+        let mut ret = format!("digraph \"{}${}\" {{\n", self.path, self.register);
+        for (ndx, state) in self.states.iter().enumerate() {
+            let shape = if ndx == 0 { "doublecircle" } else { "circle" };
+            ret += &format!("    \"{}\" [shape={}];\n", state, shape);
+        }
+        if self.transitions.iter().any(|x| x.from.is_none()) {
+            ret += "    \"*\" [label=\"any\", shape=plaintext];\n";
+        }
+        for transition in &self.transitions {
+            ret += &format!(
+                "    \"{}\" -> \"{}\" [label=\"{}\"];\n",
+                transition.from.as_deref().unwrap_or("*"),
+                transition.to,
+                quoted(&transition.condition)
+            );
+        }
+        ret += "}\n";
+        ret
+    }
+    /// The state transition diagram as a Mermaid state diagram, which can
+    /// be embedded directly in Markdown documentation.
+    pub fn mermaid(&self) -> String {
+        let mut ret = "stateDiagram-v2\n".to_string();
+        if let Some(initial) = self.states.first() {
+            ret += &format!("    [*] --> {}\n", initial);
+        }
+        if self.transitions.iter().any(|x| x.from.is_none()) {
+            ret += "    state \"any\" as any_state\n";
+        }
+        for transition in &self.transitions {
+            let from = transition.from.as_deref().unwrap_or("any_state");
+            if transition.condition.is_empty() {
+                ret += &format!("    {} --> {}\n", from, transition.to);
+            } else {
+                // A colon would end the label early
+                ret += &format!(
+                    "    {} --> {} : {}\n",
+                    from,
+                    transition.to,
+                    transition.condition.replace(':', "#58;")
+                );
+            }
+        }
+        ret
+    }
+}
+
+// The name of a state from the name of the enum variant (MyState$Idle or MyState::Idle)
+fn state_name(x: &str) -> String {
+    x.rsplit(['$', ':']).next().unwrap_or(x).to_string()
+}
+
+struct TransitionFinder<'a> {
+    register: &'a str,
+    states: &'a [String],
+    conditions: Vec<String>,
+    current: Option<String>,
+    transitions: Vec<StateTransition>,
+}
+
+impl TransitionFinder<'_> {
+    fn is_state(&self, e: &VerilogExpression) -> Option<String> {
+        if let VerilogExpression::Signal(x) = e {
+            let name = state_name(x);
+            if x.contains('$') && self.states.contains(&name) {
+                return Some(name);
+            }
+        }
+        None
+    }
+    fn with_condition(&mut self, condition: String, block: &VerilogBlock) {
+        self.conditions.push(condition);
+        self.walk_block(block);
+        self.conditions.pop();
+    }
+    fn walk_block(&mut self, block: &VerilogBlock) {
+        for statement in block {
+            self.walk_statement(statement);
+        }
+    }
+    fn walk_statement(&mut self, statement: &VerilogStatement) {
+        match statement {
+            VerilogStatement::Assignment(VerilogExpression::Signal(target), value) => {
+                let target = target.trim_end_matches("$next");
+                if target == format!("{}$d", self.register) {
+                    if let Some(to) = self.is_state(value) {
+                        let transition = StateTransition {
+                            from: self.current.clone(),
+                            to,
+                            condition: self.conditions.join(" && "),
+                        };
+                        if !self.transitions.contains(&transition) {
+                            self.transitions.push(transition);
+                        }
+                    }
+                }
+            }
+            VerilogStatement::If(c) => {
+                let test = verilog_expression(&c.test);
+                self.with_condition(test.clone(), &c.then);
+                match &c.otherwise {
+                    VerilogBlockOrConditional::Block(b) => {
+                        self.with_condition(format!("!({})", test), b)
+                    }
+                    VerilogBlockOrConditional::Conditional(s) => {
+                        self.conditions.push(format!("!({})", test));
+                        self.walk_statement(s);
+                        self.conditions.pop();
+                    }
+                    VerilogBlockOrConditional::None => {}
+                }
+            }
+            VerilogStatement::Match(m) => {
+                let test = verilog_expression(&m.test);
+                let is_fsm = test == format!("{}$q", self.register) && self.current.is_none();
+                for case in &m.cases {
+                    if is_fsm {
+                        self.current = if case.condition == "default" {
+                            None
+                        } else {
+                            Some(state_name(&case.condition))
+                        };
+                        self.walk_block(&case.block);
+                        self.current = None;
+                    } else if case.condition == "default" {
+                        self.with_condition(format!("{}: default", test), &case.block);
+                    } else {
+                        self.with_condition(format!("{} == {}", test, case.condition), &case.block);
+                    }
+                }
+            }
+            VerilogStatement::Loop(l) => self.walk_block(&l.block),
+            VerilogStatement::Macro(m) => self.walk_block(m),
+            _ => {}
+        }
+    }
+}
+
+#[derive(Default)]
+struct StateMachineScanner {
+    path: ProbePath,
+    enums: Vec<HashMap<String, Vec<String>>>,
+    machines: Vec<StateMachine>,
+}
+
+impl Probe for StateMachineScanner {
+    fn visit_start_scope(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_scope(name);
+        self.enums.push(Default::default());
+    }
+
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        self.path.start_namespace(name);
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        if let TypeKind::Enum(variants) = signal.descriptor().kind {
+            let variants = variants
+                .iter()
+                .map(|x| state_name(&x.name))
+                .collect::<Vec<_>>();
+            let depth = self.enums.len();
+            if let Some(port) = self.path.parent_name(name) {
+                self.enums[depth - 2].insert(port, variants);
+            }
+        }
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.end_namespace();
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, node: &dyn Block) {
+        if let Verilog::Combinatorial(code) = &node.hdl() {
+            let enums = self.enums.last().unwrap();
+            let mut registers = enums
+                .keys()
+                .filter_map(|x| x.strip_suffix("$q"))
+                .filter(|x| enums.contains_key(&format!("{}$d", x)))
+                .collect::<Vec<_>>();
+            registers.sort_unstable();
+            for register in registers {
+                let states = &enums[&format!("{}$q", register)];
+                let mut finder = TransitionFinder {
+                    register,
+                    states,
+                    conditions: vec![],
+                    current: None,
+                    transitions: vec![],
+                };
+                finder.walk_block(code);
+                if !finder.transitions.is_empty() {
+                    self.machines.push(StateMachine {
+                        path: self.path.block(),
+                        register: register.to_string(),
+                        states: states.clone(),
+                        transitions: finder.transitions,
+                    });
+                }
+            }
+        }
+        self.enums.pop();
+        self.path.end_scope();
+    }
+}
+
+/// Find the state machines in a circuit.  A state machine is the usual
+/// RustHDL pattern of a `DFF` that holds a [LogicState](crate::prelude::LogicState)
+/// enum, and a `match` on its output that selects the next state.
+/// The transitions (and the conditions under which they happen) are read
+/// from the HDL code, so the resulting [StateMachine] can be drawn with
+/// [StateMachine::dot] or [StateMachine::mermaid] to document the design.
+/// Assignments to the next state outside of the `match` (like a reset)
+/// become transitions from any state.
+pub fn extract_state_machines(uut: &dyn Block) -> Vec<StateMachine> {
+    let mut visitor = StateMachineScanner::default();
+    uut.accept("uut", &mut visitor);
+    visitor.machines
+}
@@ -0,0 +1,318 @@
+use crate::atom::{Atom, AtomKind};
+use crate::block::Block;
+use crate::named_path::NamedPath;
+use crate::probe::{Probe, ProbeMut};
+use crate::synth::VCDValue;
+use std::collections::HashMap;
+use std::fmt::{Display, Formatter};
+
+/// The changes to the inputs of a circuit at one step of a simulation.
+#[derive(Clone, Debug, PartialEq)]
+pub struct StimulusStep {
+    /// The simulation time of the step (in ticks - see [crate::simulate::sim_time::TimeUnit])
+    pub time: u64,
+    /// The inputs that changed, and their new values
+    pub changes: Vec<(String, VCDValue)>,
+}
+
+/// A recording of everything that was applied to the top level inputs of a
+/// circuit during a simulation (including the clocks).  Record one with
+/// [Simulation::record_stimulus](crate::simulate::Simulation::record_stimulus),
+/// and play it back with [Simulation::add_stimulus](crate::simulate::Simulation::add_stimulus).
+/// The recording is a simple text file, with a `#<time>` line for each step,
+/// followed by a `<input> <value>` line for each input that changed, e.g.,
+/// ```text
+/// #0
+/// clock 0
+/// enable 1
+/// data b00001111
+/// #5000
+/// clock 1
+/// ```
+/// Only the inputs of the top level block are recorded.  A testbench that drives
+/// the input of a child block directly (e.g., `x.child.enable.next = true` after
+/// connecting it by hand) will not have that input captured, and the stimulus
+/// will not replay the run faithfully.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Stimulus {
+    steps: Vec<StimulusStep>,
+}
+
+fn value_char(x: &vcd::Value) -> char {
+    match x {
+        vcd::Value::V0 => '0',
+        vcd::Value::V1 => '1',
+        vcd::Value::X => 'x',
+        vcd::Value::Z => 'z',
+    }
+}
+
+fn char_value(x: char) -> Option<vcd::Value> {
+    match x {
+        '0' => Some(vcd::Value::V0),
+        '1' => Some(vcd::Value::V1),
+        'x' => Some(vcd::Value::X),
+        'z' => Some(vcd::Value::Z),
+        _ => None,
+    }
+}
+
+fn format_value(x: &VCDValue) -> String {
+    match x {
+        VCDValue::Single(x) => value_char(x).to_string(),
+        VCDValue::Vector(x) => format!("b{}", x.iter().map(value_char).collect::<String>()),
+        VCDValue::String(x) => format!("s{}", x),
+        VCDValue::Composite(x) => format!(
+            "{{{}}}",
+            x.iter()
+                .map(|x| format_value(x))
+                .collect::<Vec<_>>()
+                .join(",")
+        ),
+    }
+}
+
+fn parse_value(x: &str) -> Option<VCDValue> {
+    let mut chars = x.chars();
+    match chars.next()? {
+        'b' => chars
+            .map(char_value)
+            .collect::<Option<Vec<_>>>()
+            .map(VCDValue::Vector),
+        's' => Some(VCDValue::String(chars.as_str().to_string())),
+        '{' => {
+            let inner = x.strip_prefix('{')?.strip_suffix('}')?;
+            let mut fields = vec![];
+            let mut depth = 0;
+            let mut start = 0;
+            for (ndx, c) in inner.char_indices() {
+                match c {
+                    '{' => depth += 1,
+                    '}' => depth -= 1,
+                    ',' if depth == 0 => {
+                        fields.push(Box::new(parse_value(&inner[start..ndx])?));
+                        start = ndx + 1;
+                    }
+                    _ => {}
+                }
+            }
+            if !inner.is_empty() {
+                fields.push(Box::new(parse_value(&inner[start..])?));
+            }
+            Some(VCDValue::Composite(fields))
+        }
+        c if x.len() == 1 => char_value(c).map(VCDValue::Single),
+        _ => None,
+    }
+}
+
+impl Display for Stimulus {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for step in &self.steps {
+            writeln!(f, "#{}", step.time)?;
+            for (name, value) in &step.changes {
+                writeln!(f, "{} {}", name, format_value(value))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl Stimulus {
+    /// The steps of the recording, in the order they were applied
+    pub fn steps(&self) -> &[StimulusStep] {
+        &self.steps
+    }
+    /// Parse a recording from its text form.  Returns `None` if the text is not valid.
+    pub fn parse(text: &str) -> Option<Stimulus> {
+        let mut steps: Vec<StimulusStep> = vec![];
+        for line in text.lines().map(|x| x.trim()).filter(|x| !x.is_empty()) {
+            if let Some(time) = line.strip_prefix('#') {
+                steps.push(StimulusStep {
+                    time: time.parse().ok()?,
+                    changes: vec![],
+                });
+            } else {
+                let (name, value) = line.split_once(' ')?;
+                steps
+                    .last_mut()?
+                    .changes
+                    .push((name.to_string(), parse_value(value)?));
+            }
+        }
+        Some(Stimulus { steps })
+    }
+    /// Write the recording to a file
+    pub fn save(&self, filename: &str) -> std::io::Result<()> {
+        std::fs::write(filename, self.to_string())
+    }
+    /// Read a recording from a file
+    pub fn load(filename: &str) -> std::io::Result<Stimulus> {
+        let text = std::fs::read_to_string(filename)?;
+        Stimulus::parse(&text).ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::InvalidData,
+                format!("{} is not a valid stimulus recording", filename),
+            )
+        })
+    }
+}
+
+// Finds the inputs of the top level of the circuit
+#[derive(Default)]
+struct InputScanner {
+    depth: usize,
+    namespace: NamedPath,
+    inputs: Vec<(String, VCDValue)>,
+}
+
+impl InputScanner {
+    fn name(&self, name: &str) -> String {
+        if self.namespace.is_empty() {
+            name.to_owned()
+        } else {
+            format!("{}${}", self.namespace.flat("$"), name)
+        }
+    }
+}
+
+impl Probe for InputScanner {
+    fn visit_start_scope(&mut self, _name: &str, _node: &dyn Block) {
+        self.depth += 1;
+    }
+
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        if self.depth == 1 {
+            self.namespace.push(name);
+        }
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        if self.depth == 1 && signal.kind() == AtomKind::InputParameter {
+            self.inputs.push((self.name(name), signal.vcd()));
+        }
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        if self.depth == 1 {
+            self.namespace.pop();
+        }
+    }
+
+    fn visit_end_scope(&mut self, _name: &str, _node: &dyn Block) {
+        self.depth -= 1;
+    }
+}
+
+struct InputDriver<'a> {
+    depth: usize,
+    namespace: NamedPath,
+    changes: HashMap<&'a str, &'a VCDValue>,
+    failed: Vec<String>,
+}
+
+impl ProbeMut for InputDriver<'_> {
+    fn visit_start_scope(&mut self, _name: &str) {
+        self.depth += 1;
+    }
+
+    fn visit_start_namespace(&mut self, name: &str) {
+        if self.depth == 1 {
+            self.namespace.push(name);
+        }
+    }
+
+    fn visit_atom(&mut self, name: &str, signal: &mut dyn Atom) {
+        if self.depth == 1 && signal.kind() == AtomKind::InputParameter {
+            let name = if self.namespace.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{}${}", self.namespace.flat("$"), name)
+            };
+            if let Some(value) = self.changes.remove(name.as_str()) {
+                if !signal.set_vcd(value) {
+                    self.failed.push(name);
+                }
+            }
+        }
+    }
+
+    fn visit_end_namespace(&mut self, _name: &str) {
+        if self.depth == 1 {
+            self.namespace.pop();
+        }
+    }
+
+    fn visit_end_scope(&mut self, _name: &str) {
+        self.depth -= 1;
+    }
+}
+
+impl StimulusStep {
+    /// Drive the inputs of the circuit with the values in this step.  Fails
+    /// (with a description of the problem) if an input does not exist, or
+    /// the value does not fit it.
+    pub fn apply(&self, uut: &mut dyn Block) -> Result<(), String> {
+        let mut driver = InputDriver {
+            depth: 0,
+            namespace: Default::default(),
+            changes: self
+                .changes
+                .iter()
+                .map(|(name, value)| (name.as_str(), value))
+                .collect(),
+            failed: vec![],
+        };
+        uut.accept_mut("uut", &mut driver);
+        let mut missing = driver.changes.into_keys().collect::<Vec<_>>();
+        missing.sort_unstable();
+        if !missing.is_empty() {
+            return Err(format!(
+                "Stimulus at {} drives inputs the circuit does not have: {}",
+                self.time,
+                missing.join(", ")
+            ));
+        }
+        if !driver.failed.is_empty() {
+            return Err(format!(
+                "Stimulus at {} does not fit the inputs: {}",
+                self.time,
+                driver.failed.join(", ")
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// Records the changes to the top level inputs of a circuit as it is simulated.
+#[derive(Default)]
+pub(crate) struct StimulusRecorder {
+    last: HashMap<String, VCDValue>,
+    stimulus: Stimulus,
+}
+
+impl StimulusRecorder {
+    pub(crate) fn clear(&mut self) {
+        self.last.clear();
+        self.stimulus.steps.clear();
+    }
+    pub(crate) fn record(&mut self, time: u64, uut: &dyn Block) {
+        let mut scanner = InputScanner::default();
+        uut.accept("uut", &mut scanner);
+        let changes = scanner
+            .inputs
+            .into_iter()
+            .filter(|(name, value)| self.last.get(name) != Some(value))
+            .collect::<Vec<_>>();
+        if changes.is_empty() {
+            return;
+        }
+        for (name, value) in &changes {
+            self.last.insert(name.clone(), value.clone());
+        }
+        self.stimulus.steps.push(StimulusStep { time, changes });
+    }
+    pub(crate) fn stimulus(&self) -> &Stimulus {
+        &self.stimulus
+    }
+}
@@ -29,6 +29,21 @@ pub trait Synth: Default + Copy + PartialEq + Debug {
     fn descriptor() -> TypeDescriptor;
     fn vcd(self) -> VCDValue;
     fn verilog(self) -> VerilogLiteral;
+    /// The inverse of [Synth::vcd] - rebuild a value from its VCD representation.
+    /// Returns `None` if the value does not fit the type (or the type does not
+    /// support it, which is the default).
+    fn from_vcd(_value: &VCDValue) -> Option<Self> {
+        None
+    }
+}
+
+// The value of a single bit (x and z are not valid values)
+fn vcd_bit(x: &vcd::Value) -> Option<bool> {
+    match x {
+        vcd::Value::V0 => Some(false),
+        vcd::Value::V1 => Some(true),
+        _ => None,
+    }
 }
 
 impl<const N: usize> Synth for Bits<N> {
@@ -48,6 +63,20 @@ impl<const N: usize> Synth for Bits<N> {
     fn verilog(self) -> VerilogLiteral {
         self.into()
     }
+
+    fn from_vcd(value: &VCDValue) -> Option<Self> {
+        match value {
+            VCDValue::Single(x) if N == 1 => Some(Bits::default().replace_bit(0, vcd_bit(x)?)),
+            VCDValue::Vector(x) if x.len() == N => {
+                let mut ret = Bits::default();
+                for (ndx, bit) in x.iter().rev().enumerate() {
+                    ret = ret.replace_bit(ndx, vcd_bit(bit)?);
+                }
+                Some(ret)
+            }
+            _ => None,
+        }
+    }
 }
 
 impl Synth for Bit {
@@ -71,6 +100,13 @@ impl Synth for Bit {
     fn verilog(self) -> VerilogLiteral {
         self.into()
     }
+
+    fn from_vcd(value: &VCDValue) -> Option<Self> {
+        match value {
+            VCDValue::Single(x) => vcd_bit(x),
+            _ => None,
+        }
+    }
 }
 
 impl Synth for Clock {
@@ -90,6 +126,10 @@ impl Synth for Clock {
     fn verilog(self) -> VerilogLiteral {
         self.clk.into()
     }
+
+    fn from_vcd(value: &VCDValue) -> Option<Self> {
+        Bit::from_vcd(value).map(|clk| Clock { clk })
+    }
 }
 
 impl<const N: usize> Synth for Signed<N> {
@@ -106,4 +146,7 @@ impl<const N: usize> Synth for Signed<N> {
     fn verilog(self) -> VerilogLiteral {
         self.inner().into()
     }
+    fn from_vcd(value: &VCDValue) -> Option<Self> {
+        Bits::from_vcd(value).map(Signed)
+    }
 }
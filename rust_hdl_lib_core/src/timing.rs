@@ -1,7 +1,28 @@
+/// The clock edge (or edges) a register samples its inputs on
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum ClockEdge {
+    #[default]
+    Rising,
+    Falling,
+    Both,
+}
+
 #[derive(Clone, Debug)]
 pub struct TimingInfo {
     pub name: String,
     pub clock: String,
+    pub edge: ClockEdge,
     pub inputs: Vec<String>,
     pub outputs: Vec<String>,
 }
+
+/// Describes a reset synchronizer - a block that asserts its output as soon as the
+/// (asynchronous) input reset is asserted, but releases it synchronously to a clock.
+/// The output is a reset in the domain of that clock (see
+/// [check_reset_domains](crate::check_reset_domains::check_reset_domains)).
+#[derive(Clone, Debug)]
+pub struct ResetInfo {
+    pub clock: String,
+    pub reset_in: String,
+    pub reset_out: String,
+}
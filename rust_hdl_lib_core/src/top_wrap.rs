@@ -1,4 +1,10 @@
-use crate::{ast::Verilog, block::Block, logic::Logic, probe::Probe, timing::TimingInfo};
+use crate::{
+    ast::Verilog,
+    block::{Block, EventState, SimProfile},
+    logic::Logic,
+    probe::{Probe, ProbeMut},
+    timing::TimingInfo,
+};
 
 pub struct TopWrap<U: Block> {
     pub uut: U,
@@ -26,9 +32,25 @@ impl<U: Block> Block for TopWrap<U> {
     fn has_changed(&self) -> bool {
         self.uut.has_changed()
     }
+    fn update_changed(
+        &mut self,
+        state: &mut EventState,
+        owner_updated: bool,
+        ports_written: bool,
+    ) -> bool {
+        self.uut.update_changed(state, owner_updated, ports_written)
+    }
+    fn update_profiled(&mut self, _name: &str, profile: &mut SimProfile) {
+        self.uut.update_profiled("uut", profile);
+    }
     fn accept(&self, name: &str, probe: &mut dyn Probe) {
         probe.visit_start_scope(name, self);
         self.uut.accept("uut", probe);
         probe.visit_end_scope(name, self);
     }
+    fn accept_mut(&mut self, name: &str, probe: &mut dyn ProbeMut) {
+        probe.visit_start_scope(name);
+        self.uut.accept_mut("uut", probe);
+        probe.visit_end_scope(name);
+    }
 }
@@ -14,6 +14,13 @@ pub struct TypeField {
 pub enum TypeKind {
     Bits(usize),
     Signed(usize),
-    Enum(Vec<String>),
+    Enum(Vec<EnumVariant>),
     Composite(Vec<Box<TypeField>>),
 }
+
+/// A variant of an enum, along with the value that encodes it in hardware
+#[derive(Clone, Debug)]
+pub struct EnumVariant {
+    pub name: String,
+    pub value: usize,
+}
@@ -1,10 +1,11 @@
 use crate::atom::Atom;
 use crate::block::Block;
 use crate::probe::Probe;
+use crate::simulate::sim_time::TimeUnit;
 use crate::synth::VCDValue;
 use crate::type_descriptor::TypeDescriptor;
 use crate::type_descriptor::TypeKind;
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
 use std::io::Write;
 
 #[derive(Clone, Debug)]
@@ -83,8 +84,17 @@ impl<W: Write> Probe for VCDHeader<W> {
 }
 
 pub fn write_vcd_header<W: Write>(writer: W, uut: &dyn Block) -> VCDProbe<W> {
+    write_vcd_header_with_unit(writer, uut, TimeUnit::Picoseconds)
+}
+
+/// Like [write_vcd_header], but with a timescale of one `unit` (instead of a picosecond)
+pub fn write_vcd_header_with_unit<W: Write>(
+    writer: W,
+    uut: &dyn Block,
+    unit: TimeUnit,
+) -> VCDProbe<W> {
     let mut visitor = VCDHeader(VCDProbe::new(writer));
-    visitor.0.vcd.timescale(1, vcd::TimescaleUnit::PS).unwrap();
+    visitor.0.vcd.timescale(1, unit.vcd_timescale()).unwrap();
     uut.accept("uut", &mut visitor);
     visitor.0.vcd.enddefinitions().unwrap();
     visitor.0
@@ -193,3 +203,92 @@ pub fn write_vcd_dump<W: Write>(vcd: VCDProbe<W>, uut: &dyn Block) -> VCDProbe<W
     visitor.0.vcd.end().unwrap();
     visitor.0
 }
+
+struct VCDSample(Vec<(usize, VCDValue)>);
+
+impl Probe for VCDSample {
+    fn visit_atom(&mut self, _name: &str, signal: &dyn Atom) {
+        self.0.push((signal.id(), signal.vcd()));
+    }
+}
+
+/// Keeps the signal changes for the last `window` ticks of a simulation in
+/// memory, so that a short VCD can be written out if something goes wrong.
+pub(crate) struct VCDWindow {
+    window: u64,
+    start: u64,
+    ids: Vec<usize>,
+    base: Vec<VCDValue>,
+    current: Vec<VCDValue>,
+    changes: VecDeque<(u64, Vec<(usize, VCDValue)>)>,
+}
+
+impl VCDWindow {
+    pub(crate) fn new(window: u64) -> VCDWindow {
+        Self {
+            window,
+            start: 0,
+            ids: vec![],
+            base: vec![],
+            current: vec![],
+            changes: Default::default(),
+        }
+    }
+
+    pub(crate) fn clear(&mut self) {
+        *self = VCDWindow::new(self.window);
+    }
+
+    pub(crate) fn record(&mut self, time: u64, uut: &dyn Block) {
+        let mut sample = VCDSample(vec![]);
+        uut.accept("uut", &mut sample);
+        if self.ids.is_empty() {
+            self.start = time;
+            self.ids = sample.0.iter().map(|x| x.0).collect();
+            self.base = sample.0.into_iter().map(|x| x.1).collect();
+            self.current = self.base.clone();
+            return;
+        }
+        let mut changed = vec![];
+        for (ndx, (_, val)) in sample.0.into_iter().enumerate() {
+            if self.current[ndx] != val {
+                self.current[ndx] = val.clone();
+                changed.push((ndx, val));
+            }
+        }
+        if !changed.is_empty() {
+            self.changes.push_back((time, changed));
+        }
+        // Fold anything that has fallen out of the window into the starting values
+        while let Some((old_time, _)) = self.changes.front() {
+            if old_time.saturating_add(self.window) >= time {
+                break;
+            }
+            let (old_time, old_changes) = self.changes.pop_front().unwrap();
+            for (ndx, val) in old_changes {
+                self.base[ndx] = val;
+            }
+            self.start = old_time;
+        }
+    }
+
+    pub(crate) fn write<W: Write>(&self, writer: W, uut: &dyn Block, unit: TimeUnit) {
+        let mut vcd = write_vcd_header_with_unit(writer, uut, unit);
+        vcd.timestamp(self.start).unwrap();
+        vcd.vcd.begin(vcd::SimulationCommand::Dumpvars).unwrap();
+        for (ndx, val) in self.base.iter().enumerate() {
+            if let Some(idc) = vcd.id_map.get(&self.ids[ndx]) {
+                do_vcd_change(&mut vcd.val_map, &mut vcd.vcd, idc, val, true);
+            }
+        }
+        vcd.vcd.end().unwrap();
+        for (time, changes) in &self.changes {
+            vcd.timestamp(*time).unwrap();
+            for (ndx, val) in changes {
+                if let Some(idc) = vcd.id_map.get(&self.ids[*ndx]) {
+                    do_vcd_change(&mut vcd.val_map, &mut vcd.vcd, idc, val, false);
+                }
+            }
+        }
+    }
+}
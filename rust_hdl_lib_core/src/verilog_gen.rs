@@ -8,7 +8,7 @@ use crate::ast::{
     VerilogOpUnary,
 };
 use crate::code_writer::CodeWriter;
-use crate::verilog_visitor::{walk_block, VerilogVisitor};
+use crate::verilog_visitor::{walk_block, walk_conditional, walk_match, VerilogVisitor};
 
 struct LoopVariable {
     variable: String,
@@ -20,6 +20,7 @@ struct VerilogCodeGenerator {
     io: CodeWriter,
     loops: Vec<LoopVariable>,
     links: Vec<VerilogLink>,
+    readable: bool,
 }
 
 impl VerilogCodeGenerator {
@@ -29,15 +30,13 @@ impl VerilogCodeGenerator {
         for lvar in &self.loops {
             let _ = context.set_value(lvar.variable.clone(), (lvar.value as i64).into());
         }
-        if let Some(x) = re.captures(a) {
-            if x.len() == 2 {
-                if let Some(txt) = x.get(1) {
-                    let arg = evalexpr::eval_with_context(txt.as_str(), &context).unwrap();
-                    return re.replace(a, format!("$${}", arg)).to_string();
-                }
-            }
-        }
-        a.to_string()
+        // Every index in the path is replaced, so that nested arrays of blocks
+        // (e.g., self.rows[i].cells[j]) resolve to a single signal name.
+        re.replace_all(a, |x: &regex::Captures| {
+            let arg = evalexpr::eval_with_context(&x[1], &context).unwrap();
+            format!("${}", arg)
+        })
+        .to_string()
     }
 
     fn link_fixup(&self, x: &VerilogLinkDetails) -> VerilogLinkDetails {
@@ -82,8 +81,135 @@ pub fn verilog_link_extraction(code: &VerilogBlock) -> Vec<VerilogLink> {
     gen.links
 }
 
-pub fn verilog_combinatorial(code: &VerilogBlock) -> String {
+pub fn verilog_expression(e: &VerilogExpression) -> String {
     let mut gen = VerilogCodeGenerator::default();
+    gen.visit_expression(e);
+    gen.io.flush();
+    gen.to_string().trim_end().to_string()
+}
+
+// Collects the flow of signals through a block of code, as (source, target)
+// pairs, with loops unrolled and links resolved to the signals they join.
+// If `conditions` is set, the signals tested by an `if` or `match` also flow
+// into everything assigned under it.
+#[derive(Default)]
+struct SignalFlow {
+    gen: VerilogCodeGenerator,
+    reads: Vec<String>,
+    flow: Vec<(String, String)>,
+    conditions: Option<Vec<Vec<String>>>,
+}
+
+impl SignalFlow {
+    fn add_flow(&mut self, targets: &[String], sources: &[String]) {
+        let tested = self.conditions.iter().flatten().flatten();
+        for target in targets {
+            for source in sources.iter().chain(tested.clone()) {
+                let pair = (source.clone(), target.clone());
+                if !self.flow.contains(&pair) {
+                    self.flow.push(pair);
+                }
+            }
+        }
+    }
+}
+
+impl VerilogVisitor for SignalFlow {
+    fn visit_loop(&mut self, a: &VerilogLoop) {
+        for i in a.from.as_usize()..a.to.as_usize() {
+            self.gen.loops.push(LoopVariable {
+                variable: a.index.clone(),
+                value: i,
+            });
+            walk_block(self, &a.block);
+            self.gen.loops.pop();
+        }
+    }
+
+    fn visit_slice_assignment(
+        &mut self,
+        base: &VerilogExpression,
+        _width: &usize,
+        _offset: &VerilogExpression,
+        replacement: &VerilogExpression,
+    ) {
+        self.visit_assignment(base, replacement);
+    }
+
+    fn visit_signal(&mut self, sig: &str) {
+        self.reads.push(self.gen.ident_fixup(sig));
+    }
+
+    fn visit_conditional(&mut self, c: &VerilogConditional) {
+        if self.conditions.is_none() {
+            return walk_conditional(self, c);
+        }
+        self.reads.clear();
+        self.visit_expression(&c.test);
+        let tested = std::mem::take(&mut self.reads);
+        self.conditions.as_mut().unwrap().push(tested);
+        self.visit_block(&c.then);
+        self.visit_block_or_conditional(&c.otherwise);
+        self.conditions.as_mut().unwrap().pop();
+    }
+
+    fn visit_match(&mut self, m: &VerilogMatch) {
+        if self.conditions.is_none() {
+            return walk_match(self, m);
+        }
+        self.reads.clear();
+        self.visit_expression(&m.test);
+        let tested = std::mem::take(&mut self.reads);
+        self.conditions.as_mut().unwrap().push(tested);
+        for case in &m.cases {
+            self.visit_case(case);
+        }
+        self.conditions.as_mut().unwrap().pop();
+    }
+
+    fn visit_link(&mut self, l: &[VerilogLink]) {
+        self.gen.visit_link(l);
+        for link in std::mem::take(&mut self.gen.links) {
+            // The equivalence is (driven, driver)
+            let (target, source) = crate::module_defines::get_link_equivalence(&link);
+            self.add_flow(&[target], &[source]);
+        }
+    }
+
+    fn visit_assignment(&mut self, l: &VerilogExpression, r: &VerilogExpression) {
+        self.reads.clear();
+        self.visit_expression(r);
+        let sources = std::mem::take(&mut self.reads);
+        self.visit_expression(l);
+        let targets = std::mem::take(&mut self.reads);
+        self.add_flow(&targets, &sources);
+    }
+}
+
+pub(crate) fn verilog_signal_flow(code: &VerilogBlock) -> Vec<(String, String)> {
+    let mut flow = SignalFlow::default();
+    flow.visit_block(code);
+    flow.flow
+}
+
+// As [verilog_signal_flow], but a signal that is tested (in an `if` or a
+// `match`) also flows into the signals that are assigned depending on it.
+pub(crate) fn verilog_signal_flow_with_conditions(code: &VerilogBlock) -> Vec<(String, String)> {
+    let mut flow = SignalFlow {
+        conditions: Some(vec![]),
+        ..Default::default()
+    };
+    flow.visit_block(code);
+    flow.flow
+}
+
+// In readable mode, each statement is preceded by a comment with the
+// Rust source line it came from.
+pub fn verilog_combinatorial(code: &VerilogBlock, readable: bool) -> String {
+    let mut gen = VerilogCodeGenerator {
+        readable,
+        ..Default::default()
+    };
     gen.visit_block(code);
     format!("always @(*) {}\n", gen.to_string())
 }
@@ -161,6 +287,12 @@ impl VerilogVisitor for VerilogCodeGenerator {
         self.io.add(format!("// {}", x));
     }
 
+    fn visit_source(&mut self, x: &str) {
+        if self.readable {
+            self.io.add(format!("// {}", x));
+        }
+    }
+
     fn visit_signal(&mut self, sig: &str) {
         self.io.write(self.ident_fixup(sig));
     }
@@ -263,10 +395,32 @@ impl VerilogVisitor for VerilogCodeGenerator {
     }
 
     fn visit_slice(&mut self, sig: &VerilogExpression, width: &usize, offset: &VerilogExpression) {
-        self.visit_expression(sig);
-        self.io.write("[(");
-        self.visit_expression(offset);
-        self.io.write(format!(")+:({})]", width));
+        if let VerilogExpression::Signal(_) = sig {
+            self.visit_expression(sig);
+            self.io.write("[(");
+            self.visit_expression(offset);
+            self.io.write(format!(")+:({})]", width));
+        } else {
+            // Verilog only allows a part select of a signal, so slices of
+            // other expressions (like a concatenation) are shifted and masked
+            self.io.write("((");
+            self.visit_expression(sig);
+            self.io.write(") >> (");
+            self.visit_expression(offset);
+            let mask = (BigUint::from(1_u32) << width) - 1_u32;
+            self.io.write(format!(")) & {}'h{:x}", width, mask));
+        }
+    }
+
+    fn visit_concat(&mut self, a: &[VerilogExpression]) {
+        self.io.write("{");
+        for (ndx, x) in a.iter().enumerate() {
+            if ndx != 0 {
+                self.io.write(", ");
+            }
+            self.visit_expression(x);
+        }
+        self.io.write("}");
     }
 
     fn visit_index_replace(
@@ -47,6 +47,10 @@ pub trait VerilogVisitor {
         // Terminal
     }
 
+    fn visit_source(&mut self, _s: &str) {
+        // Terminal
+    }
+
     fn visit_signal(&mut self, _c: &str) {
         // Terminal
     }
@@ -125,6 +129,16 @@ pub trait VerilogVisitor {
     ) {
         walk_index_replacement(self, a, b, c);
     }
+
+    fn visit_concat(&mut self, a: &[VerilogExpression]) {
+        walk_concat(self, a);
+    }
+}
+
+pub fn walk_concat<V: VerilogVisitor + ?Sized>(visitor: &mut V, a: &[VerilogExpression]) {
+    for x in a {
+        visitor.visit_expression(x);
+    }
 }
 
 pub fn walk_index_replacement<V: VerilogVisitor + ?Sized>(
@@ -240,6 +254,9 @@ pub fn walk_statement<V: VerilogVisitor + ?Sized>(visitor: &mut V, s: &VerilogSt
         VerilogStatement::Comment(x) => {
             visitor.visit_comment(x);
         }
+        VerilogStatement::Source(x) => {
+            visitor.visit_source(x);
+        }
         VerilogStatement::Loop(l) => {
             visitor.visit_loop(l);
         }
@@ -354,5 +371,8 @@ pub fn walk_expression<V: VerilogVisitor + ?Sized>(visitor: &mut V, e: &VerilogE
         VerilogExpression::Unsigned(a) => {
             visitor.visit_unsigned(a);
         }
+        VerilogExpression::Concat(a) => {
+            visitor.visit_concat(a);
+        }
     }
 }
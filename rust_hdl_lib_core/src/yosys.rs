@@ -1,7 +1,10 @@
+use std::collections::{BTreeSet, HashMap, HashSet};
 use std::env::temp_dir;
 use std::fs::{create_dir_all, remove_dir_all, File};
 use std::io::{Error, Write};
+use std::path::PathBuf;
 use std::process::Command;
+use std::sync::OnceLock;
 
 #[derive(Debug)]
 pub enum SynthError {
@@ -20,7 +23,275 @@ impl From<std::io::Error> for SynthError {
     }
 }
 
+const YOSYS_VALIDATE_SCRIPT: &str = "-p read -vlog95 top.v; hierarchy -check -top top; proc";
+
+// Running yosys is by far the slowest part of checking a design, and most of a design
+// does not change from one test run to the next.  Each module of the generated Verilog
+// that passes validation is recorded in a cache directory, keyed by a hash of its text,
+// the keys of the modules it instantiates, the validation script and the yosys version.
+// A change to a module therefore invalidates it and the modules above it, but nothing
+// else.  When some modules are missing from the cache, only those are handed to yosys,
+// with the cached modules they instantiate replaced by empty stubs that just declare
+// their ports.  Failures are never cached.  Set RUST_HDL_NO_YOSYS_CACHE to force yosys
+// to check the whole design every time.
+fn yosys_cache_dir() -> PathBuf {
+    temp_dir().as_path().join("rust_hdl_yosys_cache")
+}
+
+fn yosys_cache_enabled() -> bool {
+    std::env::var_os("RUST_HDL_NO_YOSYS_CACHE").is_none()
+}
+
+fn yosys_version() -> &'static str {
+    static VERSION: OnceLock<String> = OnceLock::new();
+    VERSION.get_or_init(|| {
+        Command::new("yosys")
+            .arg("-V")
+            .output()
+            .map(|x| String::from_utf8_lossy(&x.stdout).to_string())
+            .unwrap_or_default()
+    })
+}
+
+// A 64 bit FNV-1a hash.  Unlike the hashers in std, its output is fixed, so
+// the keys stay valid from one build (and one version of Rust) to the next.
+struct StableHasher(u64);
+
+impl StableHasher {
+    fn new() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+    fn write(&mut self, text: &str) {
+        // Each field ends with a byte that cannot appear in UTF-8, so that
+        // moving text from one field to the next changes the hash
+        for byte in text.bytes().chain(std::iter::once(0xff)) {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x0000_0100_0000_01b3);
+        }
+    }
+    fn key(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+struct VerilogModule<'a> {
+    name: &'a str,
+    text: String,
+    // The names of the modules it instantiates
+    children: BTreeSet<&'a str>,
+}
+
+impl<'a> VerilogModule<'a> {
+    // An empty module with the same ports
+    fn stub(&self) -> Option<String> {
+        let header = self.text.lines().next()?;
+        let arguments = match (header.find('('), header.rfind(')')) {
+            (Some(start), Some(end)) if start < end => header[start + 1..end]
+                .split(',')
+                .filter(|x| !x.trim().is_empty())
+                .count(),
+            _ => 0,
+        };
+        let ports = self
+            .text
+            .lines()
+            .filter(|line| {
+                let line = line.trim_start();
+                line.starts_with("input ")
+                    || line.starts_with("output ")
+                    || line.starts_with("inout ")
+            })
+            .collect::<Vec<_>>();
+        // Anything unusual (like a port declared over several lines) is not stubbed
+        if ports.len() != arguments {
+            return None;
+        }
+        Some(format!(
+            "{}\n{}\nendmodule // {}\n",
+            header,
+            ports.join("\n"),
+            self.name
+        ))
+    }
+}
+
+// Split the generated Verilog into its modules, and whatever text sits
+// between them.  Returns None if the text does not look like the output
+// of generate_verilog (with a single `top` module).
+fn split_modules(translation: &str) -> Option<(String, Vec<VerilogModule<'_>>)> {
+    let mut shared = String::new();
+    let mut modules = vec![];
+    let mut current: Option<(&str, Vec<&str>)> = None;
+    for line in translation.lines() {
+        if let Some(header) = line.strip_prefix("module ") {
+            if current.is_some() {
+                return None;
+            }
+            let name = header
+                .split(|c: char| c == '(' || c == ';' || c.is_whitespace())
+                .next()
+                .filter(|x| !x.is_empty())?;
+            current = Some((name, vec![line]));
+        } else if let Some((name, mut lines)) = current.take() {
+            lines.push(line);
+            if line.starts_with("endmodule") {
+                modules.push(VerilogModule {
+                    name,
+                    text: lines.join("\n") + "\n",
+                    children: Default::default(),
+                });
+            } else {
+                current = Some((name, lines));
+            }
+        } else {
+            shared += line;
+            shared += "\n";
+        }
+    }
+    let names = modules.iter().map(|x| x.name).collect::<HashSet<_>>();
+    if current.is_some() || names.len() != modules.len() || !names.contains("top") {
+        return None;
+    }
+    for module in &mut modules {
+        // Sub module instances look like `    top$child child(`
+        let children = module
+            .text
+            .lines()
+            .skip(1)
+            .filter(|line| line.starts_with(' ') && line.ends_with('('))
+            .filter_map(|line| line.split_whitespace().next())
+            .filter_map(|name| names.get(name).copied())
+            .collect();
+        module.children = children;
+    }
+    Some((shared, modules))
+}
+
+// What needs to be handed to yosys to validate a design, given the contents of the cache
+struct YosysCachePlan {
+    // The Verilog to validate, or None if the whole design is already in the cache
+    design: Option<String>,
+    // The cache keys to record if the validation passes
+    keys: Vec<String>,
+}
+
+fn yosys_cache_plan(translation: &str) -> YosysCachePlan {
+    let cached = |key: &str| yosys_cache_dir().join(key).exists();
+    let mut common = StableHasher::new();
+    common.write(yosys_version());
+    common.write(YOSYS_VALIDATE_SCRIPT);
+    let (shared, modules) = match split_modules(translation) {
+        Some(x) => x,
+        None => {
+            // Fall back to caching the design as a whole
+            common.write(translation);
+            let key = common.key();
+            return YosysCachePlan {
+                design: (!cached(&key)).then(|| translation.to_string()),
+                keys: vec![key],
+            };
+        }
+    };
+    common.write(&shared);
+    let by_name = modules
+        .iter()
+        .map(|x| (x.name, x))
+        .collect::<HashMap<_, _>>();
+    fn key_of<'a>(
+        name: &'a str,
+        by_name: &HashMap<&'a str, &VerilogModule<'a>>,
+        common: &StableHasher,
+        keys: &mut HashMap<&'a str, String>,
+    ) -> String {
+        if let Some(key) = keys.get(name) {
+            return key.clone();
+        }
+        let module = by_name[name];
+        let mut hasher = StableHasher(common.0);
+        hasher.write(&module.text);
+        for child in &module.children {
+            hasher.write(&key_of(child, by_name, common, keys));
+        }
+        let key = hasher.key();
+        keys.insert(name, key.clone());
+        key
+    }
+    // Only the modules under top are checked by yosys, so only those are cached
+    let mut keys = HashMap::new();
+    let mut missed = HashSet::new();
+    let mut pending = vec!["top"];
+    let mut seen = HashSet::new();
+    while let Some(name) = pending.pop() {
+        if !seen.insert(name) {
+            continue;
+        }
+        if !cached(&key_of(name, &by_name, &common, &mut keys)) {
+            missed.insert(name);
+            pending.extend(by_name[name].children.iter().copied());
+        }
+    }
+    if missed.is_empty() {
+        return YosysCachePlan {
+            design: None,
+            keys: vec![],
+        };
+    }
+    // The missed modules go in whole.  The cached modules they instantiate go in as
+    // stubs, unless they cannot be stubbed, in which case they go in whole as well.
+    let mut whole = missed.clone();
+    let mut stubs = HashMap::new();
+    let mut pending = missed.iter().copied().collect::<Vec<_>>();
+    while let Some(name) = pending.pop() {
+        for child in &by_name[name].children {
+            if whole.contains(child) || stubs.contains_key(child) {
+                continue;
+            }
+            match by_name[child].stub() {
+                Some(stub) => {
+                    stubs.insert(*child, stub);
+                }
+                None => {
+                    whole.insert(child);
+                    pending.push(child);
+                }
+            }
+        }
+    }
+    let mut design = shared;
+    for module in &modules {
+        if whole.contains(module.name) {
+            design += &module.text;
+        } else if let Some(stub) = stubs.get(module.name) {
+            design += stub;
+        }
+    }
+    YosysCachePlan {
+        design: Some(design),
+        keys: missed.iter().map(|x| keys[x].clone()).collect(),
+    }
+}
+
+/// Remove all of the cached yosys validation results.
+pub fn yosys_clear_cache() {
+    let _ = remove_dir_all(yosys_cache_dir());
+}
+
 pub fn yosys_validate(prefix: &str, translation: &str) -> Result<(), SynthError> {
+    if !yosys_cache_enabled() {
+        return yosys_validate_uncached(prefix, translation);
+    }
+    let plan = yosys_cache_plan(translation);
+    if let Some(design) = plan.design {
+        yosys_validate_uncached(prefix, &design)?;
+        let _ = create_dir_all(yosys_cache_dir());
+        for key in plan.keys {
+            let _ = File::create(yosys_cache_dir().join(key));
+        }
+    }
+    Ok(())
+}
+
+fn yosys_validate_uncached(prefix: &str, translation: &str) -> Result<(), SynthError> {
     let dir = temp_dir().as_path().join(prefix);
     let _ = remove_dir_all(&dir);
     let _ = create_dir_all(&dir);
@@ -28,9 +299,7 @@ pub fn yosys_validate(prefix: &str, translation: &str) -> Result<(), SynthError>
     write!(v_file, "{}", translation).unwrap();
     let output = Command::new("yosys")
         .current_dir(dir.clone())
-        .arg(format!(
-            "-p read -vlog95 top.v; hierarchy -check -top top; proc",
-        ))
+        .arg(YOSYS_VALIDATE_SCRIPT)
         .output()
         .unwrap();
     let stdout = String::from_utf8(output.stdout).unwrap();
@@ -87,3 +356,226 @@ pub fn yosys_validate(prefix: &str, translation: &str) -> Result<(), SynthError>
     }
     Ok(())
 }
+
+/// A rough estimate of the size and speed of a design, from a generic synthesis
+/// with yosys (see [yosys_estimate]).  The design is mapped to 4-input LUTs and
+/// flip flops, which is close to what most FPGA families will need, but does not
+/// use any of the features of a particular family (carry chains, block RAMs, DSP
+/// slices), so treat the numbers as a way to compare designs, not as a prediction
+/// of the utilization report.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct ResourceEstimate {
+    /// The total number of cells after mapping
+    pub cells: usize,
+    /// The number of 4-input LUTs
+    pub luts: usize,
+    /// The number of flip flops
+    pub flip_flops: usize,
+    /// The number of LUTs on the longest path between flip flops (or ports)
+    pub logic_depth: usize,
+}
+
+impl ResourceEstimate {
+    /// Estimate the maximum clock frequency (in MHz), given the delay of each level
+    /// of logic (a LUT and its routing), e.g., about 1ns for an ECP5 or an Artix 7.
+    pub fn fmax_mhz(&self, level_delay_nanoseconds: f64) -> f64 {
+        1000.0 / (self.logic_depth.max(1) as f64 * level_delay_nanoseconds)
+    }
+}
+
+const YOSYS_ESTIMATE_SCRIPT: &str = "-p read -vlog95 top.v; hierarchy -check -top top; \
+    synth -flatten -lut 4 -top top; tee -q -o ltp.txt ltp -noff; tee -q -o stat.json stat -json";
+
+// Pull the counts out of the outputs of `stat -json` and `ltp`
+fn parse_estimate(stat: &str, ltp: &str) -> Option<ResourceEstimate> {
+    let count = |reg_exp: &str, text: &str| -> Vec<(String, usize)> {
+        regex::Regex::new(reg_exp)
+            .unwrap()
+            .captures_iter(text)
+            .map(|x| (x[1].to_string(), x[2].parse().unwrap_or(0)))
+            .collect()
+    };
+    // The totals for the whole design come after the per-module counts
+    let stat = stat.rfind("\"design\"").map_or(stat, |x| &stat[x..]);
+    let cells = count(r#""(num_cells)":\s*(\d+)"#, stat).first()?.1;
+    let by_type = count(r#""(\$[^"]+)":\s*(\d+)"#, stat);
+    let luts = by_type
+        .iter()
+        .filter(|(kind, _)| kind == "$lut")
+        .map(|x| x.1)
+        .sum();
+    let flip_flops = by_type
+        .iter()
+        .filter(|(kind, _)| kind.contains("DFF"))
+        .map(|x| x.1)
+        .sum();
+    let logic_depth = count(r#"(Longest) topological path .*?\(length=(\d+)\)"#, ltp)
+        .first()
+        .map(|x| x.1)
+        .unwrap_or(0);
+    Some(ResourceEstimate {
+        cells,
+        luts,
+        flip_flops,
+        logic_depth,
+    })
+}
+
+/// Synthesize a design with yosys (mapping it to generic 4-input LUTs), and
+/// report how big and how deep it is.  Unlike [yosys_validate], the results
+/// are not cached.
+pub fn yosys_estimate(prefix: &str, translation: &str) -> Result<ResourceEstimate, SynthError> {
+    let dir = temp_dir().as_path().join(prefix);
+    let _ = remove_dir_all(&dir);
+    create_dir_all(&dir)?;
+    let mut v_file = File::create(dir.join("top.v"))?;
+    write!(v_file, "{}", translation)?;
+    let output = Command::new("yosys")
+        .current_dir(dir.clone())
+        .arg(YOSYS_ESTIMATE_SCRIPT)
+        .output()?;
+    let stdout = String::from_utf8_lossy(&output.stdout).to_string();
+    let stderr = String::from_utf8_lossy(&output.stderr).to_string();
+    let stat = std::fs::read_to_string(dir.join("stat.json")).unwrap_or_default();
+    let ltp = std::fs::read_to_string(dir.join("ltp.txt")).unwrap_or_default();
+    match parse_estimate(&stat, &ltp) {
+        Some(estimate) if stdout.contains("End of script.") => Ok(estimate),
+        _ => Err(SynthError::SynthesisFailed { stdout, stderr }),
+    }
+}
+
+#[cfg(test)]
+fn cache_test_design(tag: &str, leaf_body: &str) -> String {
+    // A top with two children, each with a port declaration and some logic
+    format!(
+        r#"
+module top(clock,a,b);
+    input wire  clock;
+    output wire  a;
+    output wire  b;
+    top$left left(
+        .clock(clock),
+        .q(a)
+    );
+    top$right right(
+        .clock(clock),
+        .q(b)
+    );
+endmodule // top
+
+module top$left(clock,q);
+    input wire  clock;
+    output reg  q;
+    always @(posedge clock) q <= {};
+endmodule // top$left
+
+module top$right(clock,q);
+    input wire  clock;
+    output reg  q;
+    always @(posedge clock) q <= ~q; // {}
+endmodule // top$right
+"#,
+        leaf_body, tag
+    )
+}
+
+#[cfg(test)]
+fn cache_test_record(plan: &YosysCachePlan) {
+    let _ = create_dir_all(yosys_cache_dir());
+    for key in &plan.keys {
+        File::create(yosys_cache_dir().join(key)).unwrap();
+    }
+}
+
+#[cfg(test)]
+fn cache_test_forget(plan: &YosysCachePlan) {
+    for key in &plan.keys {
+        let _ = std::fs::remove_file(yosys_cache_dir().join(key));
+    }
+}
+
+#[test]
+fn test_yosys_cache_misses_then_hits() {
+    let tag = format!("hit {:?}", std::time::SystemTime::now());
+    let design = cache_test_design(&tag, "~q");
+    // Nothing is cached, so the whole design has to be validated
+    let plan = yosys_cache_plan(&design);
+    assert_eq!(plan.keys.len(), 3);
+    let text = plan.design.clone().unwrap();
+    assert!(text.contains("q <= ~q; // hit"));
+    assert!(text.contains("always @(posedge clock) q <= ~q;\n"));
+    cache_test_record(&plan);
+    // And once it has passed, there is nothing left to do
+    let again = yosys_cache_plan(&design);
+    assert!(again.design.is_none());
+    assert!(again.keys.is_empty());
+    assert!(yosys_validate("yosys_cache", &design).is_ok());
+    cache_test_forget(&plan);
+}
+
+#[test]
+fn test_yosys_cache_invalidates_changed_modules() {
+    let tag = format!("changed {:?}", std::time::SystemTime::now());
+    let plan = yosys_cache_plan(&cache_test_design(&tag, "~q"));
+    cache_test_record(&plan);
+    // Change one of the children.  It and the top need to be checked again, but
+    // the other child is only needed for its ports.
+    let changed = yosys_cache_plan(&cache_test_design(&tag, "q"));
+    assert_eq!(changed.keys.len(), 2);
+    let text = changed.design.clone().unwrap();
+    assert!(text.contains("module top(clock,a,b);"));
+    assert!(text.contains("always @(posedge clock) q <= q;"));
+    assert!(text.contains(
+        "module top$right(clock,q);\n    input wire  clock;\n    output reg  q;\nendmodule"
+    ));
+    assert!(!text.contains("// changed"));
+    cache_test_forget(&plan);
+    cache_test_forget(&changed);
+}
+
+#[test]
+fn test_yosys_cache_keys_are_stable() {
+    let mut hasher = StableHasher::new();
+    hasher.write("module top(); endmodule");
+    assert_eq!(hasher.key(), "4cf16d3ae4bc88fc");
+    // Text that is not split into modules is cached as a whole
+    let plan = yosys_cache_plan("// not a design");
+    assert_eq!(plan.keys.len(), 1);
+}
+
+#[test]
+fn test_yosys_estimate_is_parsed() {
+    let stat = r#"{
+  "modules": {
+    "\\top": {
+      "num_cells": 21,
+      "num_cells_by_type": {
+        "$_DFF_P_": 8,
+        "$_SDFF_PP0_": 2,
+        "$lut": 11
+      }
+    }
+  },
+  "design": {
+    "num_cells": 21,
+    "num_cells_by_type": {
+      "$_DFF_P_": 8,
+      "$_SDFF_PP0_": 2,
+      "$lut": 11
+    }
+  }
+}"#;
+    let ltp = "Longest topological path in top (length=3):\n    0: clock\n";
+    let estimate = parse_estimate(stat, ltp).unwrap();
+    assert_eq!(
+        estimate,
+        ResourceEstimate {
+            cells: 21,
+            luts: 11,
+            flip_flops: 10,
+            logic_depth: 3
+        }
+    );
+    assert_eq!(estimate.fmax_mhz(1.0).round(), 333.0);
+    assert!(parse_estimate("", ltp).is_none());
+}
@@ -0,0 +1,26 @@
+// A description of a board support package, detailed enough for tools
+// (like the project generator) to write code against it.  The names refer
+// to items in the `pins` and `synth` modules of the BSP crate.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BoardMetadata {
+    // The short name of the board, as used on the command line
+    pub name: &'static str,
+    pub description: &'static str,
+    // The name of the BSP crate (as in Cargo.toml)
+    pub crate_name: &'static str,
+    // The constant in `pins` with the frequency of the clock from `pins::clock()`
+    pub clock_speed: &'static str,
+    // The function in `pins` that returns the LEDs, and how many there are
+    pub leds: &'static str,
+    pub led_count: usize,
+    pub leds_active_low: bool,
+    // The arguments to `synth::generate_bitstream` after the design and the prefix
+    pub synth_args: &'static [&'static str],
+}
+
+impl BoardMetadata {
+    // The name of the BSP crate, as used in Rust code
+    pub fn crate_ident(&self) -> String {
+        self.crate_name.replace('-', "_")
+    }
+}
@@ -0,0 +1,451 @@
+use crate::gowin::rpll;
+use crate::lattice::ice40::ice_pll;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+use std::fmt::{Display, Formatter};
+
+// A clock planner works out how to get a set of rates (a system clock, an
+// audio sample rate, a baud rate...) from the oscillator on a board.  It
+// searches every output of the PLL of the family (and the oscillator
+// itself, with no PLL at all) for a clock from which each of the requested
+// rates can be made with a [RationalStrobe], within the tolerance given for
+// that rate.  Of the clocks that work, the slowest one is used, as it is the
+// easiest one to close timing on.  The plan can then be turned into a
+// [ClockChain], which holds the PLL and all of the strobes.
+
+// The largest denominator of a strobe (so that it fits a RationalStrobe<32>)
+const MAX_DENOMINATOR: u64 = 1 << 30;
+
+// The families with a PLL the planner knows how to set up
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum PLLFamily {
+    NoPLL,
+    ICE40,
+    Gowin,
+}
+
+impl PLLFamily {
+    // The clocks (in Hz) the PLL can produce from `input_hz`
+    fn output_frequencies(&self, input_hz: f64) -> Vec<f64> {
+        let input_mhz = input_hz / 1_000_000.0;
+        match self {
+            PLLFamily::NoPLL => vec![],
+            PLLFamily::ICE40 => ice_pll::output_frequencies(input_mhz),
+            PLLFamily::Gowin => rpll::output_frequencies(input_mhz),
+        }
+        .into_iter()
+        .map(|x| x * 1_000_000.0)
+        .collect()
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClockRequest {
+    pub name: String,
+    pub frequency_hz: f64,
+    pub tolerance_ppm: f64,
+}
+
+impl ClockRequest {
+    pub fn new(name: &str, frequency_hz: f64, tolerance_ppm: f64) -> Self {
+        Self {
+            name: name.into(),
+            frequency_hz,
+            tolerance_ppm,
+        }
+    }
+}
+
+// A strobe that fires `numerator` times every `denominator` clocks
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlannedStrobe {
+    pub name: String,
+    pub requested_hz: f64,
+    pub frequency_hz: f64,
+    pub numerator: u64,
+    pub denominator: u64,
+    pub error_ppm: f64,
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClockPlan {
+    pub family: PLLFamily,
+    pub input_hz: f64,
+    // The output of the PLL, or None if the input clock is used as is
+    pub pll_hz: Option<f64>,
+    pub strobes: Vec<PlannedStrobe>,
+}
+
+impl ClockPlan {
+    // The clock that drives the strobes
+    pub fn clock_hz(&self) -> f64 {
+        self.pll_hz.unwrap_or(self.input_hz)
+    }
+}
+
+impl Display for ClockPlan {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "{:<16} {:>16.3} Hz", "input", self.input_hz)?;
+        match self.pll_hz {
+            Some(hz) => writeln!(f, "{:<16} {:>16.3} Hz ({:?} PLL)", "clock", hz, self.family)?,
+            None => writeln!(f, "{:<16} {:>16.3} Hz (no PLL)", "clock", self.clock_hz())?,
+        }
+        for strobe in &self.strobes {
+            writeln!(
+                f,
+                "{:<16} {:>16.3} Hz = clock * {}/{} ({:.3} ppm)",
+                strobe.name,
+                strobe.frequency_hz,
+                strobe.numerator,
+                strobe.denominator,
+                strobe.error_ppm
+            )?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum ClockPlanError {
+    // No clock the family can produce gets this rate within its tolerance
+    Unreachable(String),
+    // Each of the rates can be reached, but not all of them from one clock
+    NoCommonClock,
+}
+
+impl Display for ClockPlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ClockPlanError::Unreachable(name) => write!(
+                f,
+                "Clock {} cannot be reached within its tolerance from any available clock",
+                name
+            ),
+            ClockPlanError::NoCommonClock => write!(
+                f,
+                "No single clock reaches all of the requested clocks within their tolerances"
+            ),
+        }
+    }
+}
+
+impl std::error::Error for ClockPlanError {}
+
+// The fraction n/d (no more than 1, and with d at most MAX_DENOMINATOR) with
+// the smallest denominator that is within `tolerance` (relative) of `ratio`,
+// from the convergents of the continued fraction of `ratio`.  If none of them
+// is close enough, the closest is returned.
+fn approximate_ratio(ratio: f64, tolerance: f64) -> Option<(u64, u64)> {
+    if !(ratio > 0.0 && ratio <= 1.0) {
+        return None;
+    }
+    let (mut h0, mut h1) = (0_u64, 1_u64);
+    let (mut k0, mut k1) = (1_u64, 0_u64);
+    let mut best: Option<(u64, u64, f64)> = None;
+    let mut x = ratio;
+    loop {
+        let a = x.floor().min(MAX_DENOMINATOR as f64) as u64;
+        let h = a.saturating_mul(h1).saturating_add(h0);
+        let k = a.saturating_mul(k1).saturating_add(k0);
+        if k > MAX_DENOMINATOR {
+            break;
+        }
+        (h0, h1) = (h1, h);
+        (k0, k1) = (k1, k);
+        if h > 0 {
+            let error = ((h as f64 / k as f64) - ratio).abs() / ratio;
+            if best.is_none_or(|(_, _, e)| error < e) {
+                best = Some((h, k, error));
+            }
+            if error <= tolerance {
+                break;
+            }
+        }
+        let fraction = x - x.floor();
+        if fraction < 1e-12 {
+            break;
+        }
+        x = 1.0 / fraction;
+    }
+    best.map(|(h, k, _)| (h, k))
+}
+
+// The strobe for `request` from a clock of `clock_hz`, if it can be made
+// within its tolerance
+fn plan_strobe(request: &ClockRequest, clock_hz: f64) -> Option<PlannedStrobe> {
+    let tolerance = request.tolerance_ppm * 1e-6;
+    let (numerator, denominator) = approximate_ratio(request.frequency_hz / clock_hz, tolerance)?;
+    let frequency_hz = clock_hz * numerator as f64 / denominator as f64;
+    let error_ppm = (frequency_hz - request.frequency_hz).abs() / request.frequency_hz * 1e6;
+    if error_ppm > request.tolerance_ppm + 1e-6 {
+        return None;
+    }
+    Some(PlannedStrobe {
+        name: request.name.clone(),
+        requested_hz: request.frequency_hz,
+        frequency_hz,
+        numerator,
+        denominator,
+        error_ppm,
+    })
+}
+
+/// Plan the clocks of a design.  The `requests` are met by strobes (in the
+/// order they are given) running on a single clock, that either comes from the
+/// PLL of the `family`, or is the input clock itself.
+pub fn plan_clocks(
+    family: PLLFamily,
+    input_hz: f64,
+    requests: &[ClockRequest],
+) -> Result<ClockPlan, ClockPlanError> {
+    let candidates = std::iter::once(None)
+        .chain(family.output_frequencies(input_hz).into_iter().map(Some))
+        .collect::<Vec<_>>();
+    let mut best: Option<ClockPlan> = None;
+    for pll_hz in &candidates {
+        let clock_hz = pll_hz.unwrap_or(input_hz);
+        if best.as_ref().is_some_and(|b| b.clock_hz() <= clock_hz) {
+            continue;
+        }
+        let strobes = requests
+            .iter()
+            .map(|request| plan_strobe(request, clock_hz))
+            .collect::<Option<Vec<_>>>();
+        if let Some(strobes) = strobes {
+            best = Some(ClockPlan {
+                family,
+                input_hz,
+                pll_hz: *pll_hz,
+                strobes,
+            });
+        }
+    }
+    if let Some(plan) = best {
+        return Ok(plan);
+    }
+    for request in requests {
+        if !candidates
+            .iter()
+            .any(|pll_hz| plan_strobe(request, pll_hz.unwrap_or(input_hz)).is_some())
+        {
+            return Err(ClockPlanError::Unreachable(request.name.clone()));
+        }
+    }
+    Err(ClockPlanError::NoCommonClock)
+}
+
+// The PLL of a plan (or a plain wire if the plan has no PLL).  There is no
+// simulation model of the PLL itself, so in simulation, drive `clock_out` and
+// `locked` with [Simulation::add_clock] and [Simulation::add_behavior].
+#[derive(LogicBlock)]
+struct PlannedPLL {
+    pub clock_in: Signal<In, Clock>,
+    pub clock_out: Signal<Out, Clock>,
+    pub locked: Signal<Out, Bit>,
+    _family: PLLFamily,
+    _input_hz: f64,
+    _pll_hz: Option<f64>,
+}
+
+impl Logic for PlannedPLL {
+    fn update(&mut self) {
+        if self._pll_hz.is_none() {
+            self.clock_out.next = self.clock_in.val();
+            self.locked.next = true;
+        }
+    }
+
+    fn connect(&mut self) {
+        self.clock_out.connect();
+        self.locked.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        let input_mhz = self._input_hz / 1_000_000.0;
+        match (self._family, self._pll_hz) {
+            (PLLFamily::ICE40, Some(hz)) => ice_pll::core_wrapper(input_mhz, hz / 1_000_000.0),
+            (PLLFamily::Gowin, Some(hz)) => rpll::core_wrapper(input_mhz, hz / 1_000_000.0),
+            _ => Verilog::Custom(
+                "\
+always @(*) clock_out = clock_in;
+always @(*) locked = 1'b1;"
+                    .into(),
+            ),
+        }
+    }
+}
+
+// The clock chain of a [ClockPlan] with `N` strobes.  `clock_out` is the
+// clock of the plan, and the strobes (in the order of the requests) are
+// synchronous to it, and held off until the PLL locks.
+#[derive(LogicBlock)]
+pub struct ClockChain<const N: usize> {
+    pub clock_in: Signal<In, Clock>,
+    pub clock_out: Signal<Out, Clock>,
+    pub locked: Signal<Out, Bit>,
+    pub strobes: [Signal<Out, Bit>; N],
+    pll: PlannedPLL,
+    dividers: [RationalStrobe<32>; N],
+}
+
+impl<const N: usize> ClockChain<N> {
+    pub fn new(plan: &ClockPlan) -> Self {
+        assert_eq!(plan.strobes.len(), N);
+        Self {
+            clock_in: Default::default(),
+            clock_out: Default::default(),
+            locked: Default::default(),
+            strobes: array_init::array_init(|_| Default::default()),
+            pll: PlannedPLL {
+                clock_in: Default::default(),
+                clock_out: Default::default(),
+                locked: Default::default(),
+                _family: plan.family,
+                _input_hz: plan.input_hz,
+                _pll_hz: plan.pll_hz,
+            },
+            dividers: array_init::array_init(|ndx| {
+                RationalStrobe::with_ratio(
+                    plan.strobes[ndx].numerator,
+                    plan.strobes[ndx].denominator,
+                )
+            }),
+        }
+    }
+}
+
+impl<const N: usize> Logic for ClockChain<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.pll.clock_in.next = self.clock_in.val();
+        self.clock_out.next = self.pll.clock_out.val();
+        self.locked.next = self.pll.locked.val();
+        for i in 0..N {
+            self.dividers[i].clock.next = self.pll.clock_out.val();
+            self.dividers[i].enable.next = self.pll.locked.val();
+            self.strobes[i].next = self.dividers[i].strobe.val();
+        }
+    }
+}
+
+#[test]
+fn test_audio_rate_needs_no_pll() {
+    let plan = plan_clocks(
+        PLLFamily::ICE40,
+        12_000_000.0,
+        &[ClockRequest::new("audio", 48_000.0, 0.0)],
+    )
+    .unwrap();
+    assert_eq!(plan.pll_hz, None);
+    assert_eq!(plan.strobes[0].numerator, 1);
+    assert_eq!(plan.strobes[0].denominator, 250);
+}
+
+#[test]
+fn test_plan_uses_the_slowest_clock_that_works() {
+    let plan = plan_clocks(
+        PLLFamily::ICE40,
+        12_000_000.0,
+        &[
+            ClockRequest::new("system", 48_000_000.0, 100.0),
+            ClockRequest::new("audio", 48_000.0, 0.0),
+            ClockRequest::new("baud", 115_200.0, 1000.0),
+        ],
+    )
+    .unwrap();
+    assert_eq!(plan.pll_hz, Some(48_000_000.0));
+    let ratios = plan
+        .strobes
+        .iter()
+        .map(|x| (x.numerator, x.denominator))
+        .collect::<Vec<_>>();
+    // The baud rate takes the first ratio that is within 1000 ppm
+    assert_eq!(ratios, vec![(1, 1), (1, 1000), (1, 417)]);
+    assert!(plan.strobes[2].error_ppm < 1000.0);
+    assert!(plan.to_string().contains("clock * 1/1000"));
+    // The Gowin rPLL gets the same rates from the 27 MHz oscillator of the
+    // Tang Nano 9K (where the audio rate alone would be a 2/1125 strobe)
+    let plan = plan_clocks(
+        PLLFamily::Gowin,
+        27_000_000.0,
+        &[
+            ClockRequest::new("system", 48_000_000.0, 100.0),
+            ClockRequest::new("audio", 48_000.0, 0.0),
+        ],
+    )
+    .unwrap();
+    assert_eq!(plan.pll_hz, Some(48_000_000.0));
+    assert_eq!(plan.strobes[1].denominator, 1000);
+}
+
+#[test]
+fn test_plan_reports_unreachable_clocks() {
+    let too_fast = [ClockRequest::new("fast", 500_000_000.0, 100.0)];
+    assert_eq!(
+        plan_clocks(PLLFamily::ICE40, 12_000_000.0, &too_fast),
+        Err(ClockPlanError::Unreachable("fast".into()))
+    );
+    // The only clock fast enough for the first one is 270 MHz, which is too
+    // fast for a RationalStrobe<32> to reach the second one
+    let apart = [
+        ClockRequest::new("fast", 270_000_000.0, 0.0),
+        ClockRequest::new("slow", 0.2, 0.0),
+    ];
+    assert!(plan_clocks(PLLFamily::ICE40, 12_000_000.0, &apart[1..]).is_ok());
+    assert_eq!(
+        plan_clocks(PLLFamily::ICE40, 12_000_000.0, &apart),
+        Err(ClockPlanError::NoCommonClock)
+    );
+}
+
+#[test]
+fn test_clock_chain_is_synthesizable() {
+    let plan = plan_clocks(
+        PLLFamily::ICE40,
+        12_000_000.0,
+        &[
+            ClockRequest::new("system", 48_000_000.0, 100.0),
+            ClockRequest::new("audio", 48_000.0, 0.0),
+        ],
+    )
+    .unwrap();
+    let mut uut = ClockChain::<2>::new(&plan);
+    uut.connect_all();
+    yosys_validate("clock_chain", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_clock_chain_strobes_with_no_pll() {
+    // 2.5 MHz and 4 MHz from 10 MHz
+    let plan = plan_clocks(
+        PLLFamily::NoPLL,
+        10_000_000.0,
+        &[
+            ClockRequest::new("slow", 2_500_000.0, 0.0),
+            ClockRequest::new("fast", 4_000_000.0, 0.0),
+        ],
+    )
+    .unwrap();
+    let mut uut = ClockChain::<2>::new(&plan);
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    // 10 MHz is a 100000 ps period
+    sim.add_clock(50_000, |x: &mut Box<ClockChain<2>>| {
+        x.clock_in.next = !x.clock_in.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<ClockChain<2>>| {
+        let mut x = sim.init()?;
+        let mut counts = [0, 0];
+        for _ in 0..100 {
+            wait_clock_cycle!(sim, clock_out, x);
+            for (ndx, count) in counts.iter_mut().enumerate() {
+                if x.strobes[ndx].val() {
+                    *count += 1;
+                }
+            }
+        }
+        sim_assert_eq!(sim, counts, [25, 40], x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 20_000_000).unwrap();
+}
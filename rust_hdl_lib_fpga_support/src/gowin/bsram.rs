@@ -0,0 +1,213 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_core::timing::TimingInfo;
+
+// The block RAMs (BSRAM) of the Gowin parts are 18 Kbit each, and yosys
+// (synth_gowin) will map a memory onto them as long as the Verilog follows
+// the template it expects: a single clock, a registered read with a read
+// enable, and no reset on the read data.  The [RAM] widget uses separate
+// read and write clocks, which does not map, and ends up in LUTs.  These
+// templates keep to the inferable subset.
+
+// Semi dual port RAM - one write port and one read port on the same clock.
+#[derive(LogicBlock)]
+pub struct GowinSDPRAM<D: Synth, const N: usize> {
+    pub clock: Signal<In, Clock>,
+    pub read_address: Signal<In, Bits<N>>,
+    pub read_enable: Signal<In, Bit>,
+    pub read_data: Signal<Out, D>,
+    pub write_address: Signal<In, Bits<N>>,
+    pub write_data: Signal<In, D>,
+    pub write_enable: Signal<In, Bit>,
+    _sim: Vec<D>,
+}
+
+impl<D: Synth, const N: usize> Default for GowinSDPRAM<D, N> {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            read_address: Default::default(),
+            read_enable: Default::default(),
+            read_data: Default::default(),
+            write_address: Default::default(),
+            write_data: Default::default(),
+            write_enable: Default::default(),
+            _sim: vec![D::default(); 1 << N],
+        }
+    }
+}
+
+impl<D: Synth, const N: usize> Logic for GowinSDPRAM<D, N> {
+    fn update(&mut self) {
+        if self.clock.pos_edge() {
+            if self.read_enable.val() {
+                self.read_data.next = self._sim[self.read_address.val().index()];
+            }
+            if self.write_enable.val() {
+                self._sim[self.write_address.val().index()] = self.write_data.val();
+            }
+        }
+    }
+
+    fn connect(&mut self) {
+        self.read_data.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+(* syn_ramstyle = \"block_ram\" *)
+reg[{D}:0] mem[{Acount}:0];
+
+always @(posedge clock) begin
+   if (read_enable) begin
+      read_data <= mem[read_address];
+   end
+   if (write_enable) begin
+      mem[write_address] <= write_data;
+   end
+end
+            ",
+            D = D::BITS - 1,
+            Acount = (1 << N) - 1,
+        ))
+    }
+
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "bsram_sdp".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Rising,
+            inputs: vec![
+                "read_address".into(),
+                "read_enable".into(),
+                "write_address".into(),
+                "write_data".into(),
+                "write_enable".into(),
+            ],
+            outputs: vec!["read_data".into()],
+        }]
+    }
+}
+
+// Single port RAM.  A write does not update the read data (read first), which
+// is the mode the BSRAM supports natively.
+#[derive(LogicBlock)]
+pub struct GowinSPRAM<D: Synth, const N: usize> {
+    pub clock: Signal<In, Clock>,
+    pub address: Signal<In, Bits<N>>,
+    pub enable: Signal<In, Bit>,
+    pub write_enable: Signal<In, Bit>,
+    pub data_in: Signal<In, D>,
+    pub data_out: Signal<Out, D>,
+    _sim: Vec<D>,
+}
+
+impl<D: Synth, const N: usize> Default for GowinSPRAM<D, N> {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            address: Default::default(),
+            enable: Default::default(),
+            write_enable: Default::default(),
+            data_in: Default::default(),
+            data_out: Default::default(),
+            _sim: vec![D::default(); 1 << N],
+        }
+    }
+}
+
+impl<D: Synth, const N: usize> Logic for GowinSPRAM<D, N> {
+    fn update(&mut self) {
+        if self.clock.pos_edge() && self.enable.val() {
+            let address = self.address.val().index();
+            self.data_out.next = self._sim[address];
+            if self.write_enable.val() {
+                self._sim[address] = self.data_in.val();
+            }
+        }
+    }
+
+    fn connect(&mut self) {
+        self.data_out.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+(* syn_ramstyle = \"block_ram\" *)
+reg[{D}:0] mem[{Acount}:0];
+
+always @(posedge clock) begin
+   if (enable) begin
+      data_out <= mem[address];
+      if (write_enable) begin
+         mem[address] <= data_in;
+      end
+   end
+end
+            ",
+            D = D::BITS - 1,
+            Acount = (1 << N) - 1,
+        ))
+    }
+
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "bsram_sp".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Rising,
+            inputs: vec![
+                "address".into(),
+                "enable".into(),
+                "write_enable".into(),
+                "data_in".into(),
+            ],
+            outputs: vec!["data_out".into()],
+        }]
+    }
+}
+
+#[test]
+fn test_bsram_synthesizes() {
+    let mut uut = GowinSDPRAM::<Bits<16>, 10>::default();
+    uut.connect_all();
+    yosys_validate("gowin_sdpram", &generate_verilog(&uut)).unwrap();
+    let mut uut = GowinSPRAM::<Bits<8>, 11>::default();
+    uut.connect_all();
+    yosys_validate("gowin_spram", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_sdpram_read_enable_holds_data() {
+    let mut uut = GowinSDPRAM::<Bits<16>, 8>::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<GowinSDPRAM<Bits<16>, 8>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<GowinSDPRAM<Bits<16>, 8>>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        x.write_enable.next = true;
+        x.write_address.next = 3.into();
+        x.write_data.next = 0xCAFE.into();
+        wait_clock_cycle!(sim, clock, x);
+        x.write_address.next = 4.into();
+        x.write_data.next = 0xBEEF.into();
+        wait_clock_cycle!(sim, clock, x);
+        x.write_enable.next = false;
+        x.read_enable.next = true;
+        x.read_address.next = 3.into();
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert_eq!(sim, x.read_data.val(), 0xCAFE, x);
+        x.read_enable.next = false;
+        x.read_address.next = 4.into();
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert_eq!(sim, x.read_data.val(), 0xCAFE, x);
+        x.read_enable.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert_eq!(sim, x.read_data.val(), 0xBEEF, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1000).unwrap();
+}
@@ -0,0 +1,3 @@
+pub mod bsram;
+pub mod osc;
+pub mod rpll;
@@ -0,0 +1,86 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The GW1N(R)-9 internal oscillator runs at a nominal 250 MHz, and is
+// divided down by an even divider (FREQ_DIV) between 2 and 128.
+const OSC_BASE_HZ: f64 = 250.0e6;
+
+fn frequency_divider(freq_hz: u64) -> u32 {
+    let best = (2..=128)
+        .step_by(2)
+        .min_by(|a: &u32, b: &u32| {
+            let da = (OSC_BASE_HZ / *a as f64 - freq_hz as f64).abs();
+            let db = (OSC_BASE_HZ / *b as f64 - freq_hz as f64).abs();
+            da.partial_cmp(&db).unwrap()
+        })
+        .unwrap();
+    let err = (OSC_BASE_HZ / best as f64 - freq_hz as f64).abs() / freq_hz as f64;
+    assert!(
+        err < 0.01,
+        "Requested oscillator frequency of {} Hz cannot be generated (closest is {} Hz)",
+        freq_hz,
+        OSC_BASE_HZ / best as f64
+    );
+    best
+}
+
+// The internal oscillator (OSC) of the GW1N(R)-9.  There is no simulation
+// model, so in simulation, drive `clock` with [Simulation::add_clock].
+#[derive(LogicBlock)]
+pub struct GowinOSC {
+    pub clock: Signal<Out, Clock>,
+    _freq_div: u32,
+    _device: &'static str,
+}
+
+impl GowinOSC {
+    pub fn new(freq_hz: u64, device: &'static str) -> Self {
+        Self {
+            clock: Default::default(),
+            _freq_div: frequency_divider(freq_hz),
+            _device: device,
+        }
+    }
+    pub fn frequency_hz(&self) -> f64 {
+        OSC_BASE_HZ / self._freq_div as f64
+    }
+}
+
+impl Logic for GowinOSC {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.clock.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: format!(
+                r##"
+OSC #(.FREQ_DIV({div}), .DEVICE("{device}")) inst_OSC(.OSCOUT(clock));
+"##,
+                div = self._freq_div,
+                device = self._device
+            ),
+            cores: r##"
+(* blackbox *)
+module OSC(output OSCOUT);
+parameter FREQ_DIV = 100;
+parameter DEVICE = "GW1N-4";
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_osc_divider() {
+    assert_eq!(frequency_divider(125_000_000), 2);
+    assert_eq!(frequency_divider(2_500_000), 100);
+    assert!(std::panic::catch_unwind(|| frequency_divider(100_000_000)).is_err());
+}
+
+#[test]
+fn test_osc_synthesizes() {
+    let mut uut = GowinOSC::new(2_500_000, "GW1NR-9C");
+    uut.connect_all();
+    yosys_validate("gowin_osc", &generate_verilog(&uut)).unwrap();
+}
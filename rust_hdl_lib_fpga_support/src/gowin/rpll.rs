@@ -0,0 +1,272 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The rPLL of the GW1N(R) family generates
+//
+//   CLKOUT = CLKIN * (FBDIV_SEL + 1) / (IDIV_SEL + 1)
+//
+// with the VCO running at CLKOUT * ODIV_SEL.  The limits below are the
+// ones from the GW1NR-9 datasheet (C6/I5 speed grade).
+const ODIV_VALUES: [u32; 11] = [2, 4, 8, 16, 32, 48, 64, 80, 96, 112, 128];
+
+#[derive(Clone, Default, Debug)]
+struct GowinRPLLSettings {
+    f_clkin: f64,
+    fout: f64,
+    idiv: u32,
+    fbdiv: u32,
+    odiv: u32,
+}
+
+fn analyze(f_clkin: f64, f_clkout: f64) -> Option<GowinRPLLSettings> {
+    if !(3.0..=400.0).contains(&f_clkin) {
+        panic!(
+            "Error: PLL input frequency {} MHz is outside range 3 MHz - 400 MHz!\n",
+            f_clkin
+        );
+    }
+    if !(3.125..=600.0).contains(&f_clkout) {
+        panic!(
+            "Error: PLL output frequency {} MHz is outside range 3.125 MHz - 600 MHz!\n",
+            f_clkout
+        );
+    }
+    let mut best: Option<GowinRPLLSettings> = None;
+    for idiv in 0..=63 {
+        let f_pfd = f_clkin / (idiv as f64 + 1.);
+        if !(3.0..=400.0).contains(&f_pfd) {
+            continue;
+        }
+        for fbdiv in 0..=63 {
+            let fout = f_pfd * (fbdiv as f64 + 1.);
+            if !(3.125..=600.0).contains(&fout) {
+                continue;
+            }
+            for odiv in ODIV_VALUES {
+                let f_vco = fout * odiv as f64;
+                if !(400.0..=1200.0).contains(&f_vco) {
+                    continue;
+                }
+                let better = match &best {
+                    None => true,
+                    Some(b) => f64::abs(fout - f_clkout) < f64::abs(b.fout - f_clkout),
+                };
+                if better {
+                    best = Some(GowinRPLLSettings {
+                        f_clkin,
+                        fout,
+                        idiv,
+                        fbdiv,
+                        odiv,
+                    });
+                }
+            }
+        }
+    }
+    best
+}
+
+impl GowinRPLLSettings {
+    // The rPLL instance for these settings, fed from `clock_in`
+    fn core_instance(&self) -> String {
+        format!(
+            "\
+rPLL #(
+                .FCLKIN(\"{FCLKIN}\"),
+                .IDIV_SEL({IDIV}),
+                .FBDIV_SEL({FBDIV}),
+                .ODIV_SEL({ODIV}),
+                .DYN_IDIV_SEL(\"false\"),
+                .DYN_FBDIV_SEL(\"false\"),
+                .DYN_ODIV_SEL(\"false\"),
+                .CLKOUT_BYPASS(\"false\"),
+                .CLKOUTP_BYPASS(\"false\"),
+                .CLKOUTD_BYPASS(\"false\")
+               ) uut (
+                .CLKOUT(clock_out),
+                .LOCK(locked),
+                .CLKOUTP(),
+                .CLKOUTD(),
+                .CLKOUTD3(),
+                .RESET(1'b0),
+                .RESET_P(1'b0),
+                .CLKIN(clock_in),
+                .CLKFB(1'b0),
+                .FBDSEL(6'b0),
+                .IDSEL(6'b0),
+                .ODSEL(6'b0),
+                .PSDA(4'b0),
+                .DUTYDA(4'b0),
+                .FDLY(4'b0));
+",
+            FCLKIN = self.f_clkin,
+            IDIV = self.idiv,
+            FBDIV = self.fbdiv,
+            ODIV = self.odiv,
+        )
+    }
+}
+
+// Every output frequency (in MHz) the rPLL can reach from `f_clkin`, in
+// increasing order.  Unlike [analyze], an input that is out of range is
+// not an error - it just reaches nothing.
+pub(crate) fn output_frequencies(f_clkin: f64) -> Vec<f64> {
+    let mut ret = vec![];
+    if !(3.0..=400.0).contains(&f_clkin) {
+        return ret;
+    }
+    for idiv in 0..=63 {
+        let f_pfd = f_clkin / (idiv as f64 + 1.);
+        if !(3.0..=400.0).contains(&f_pfd) {
+            continue;
+        }
+        for fbdiv in 0..=63 {
+            let fout = f_pfd * (fbdiv as f64 + 1.);
+            if !(3.125..=600.0).contains(&fout) {
+                continue;
+            }
+            if ODIV_VALUES
+                .iter()
+                .any(|odiv| (400.0..=1200.0).contains(&(fout * *odiv as f64)))
+            {
+                ret.push(fout);
+            }
+        }
+    }
+    ret.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ret.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+    ret
+}
+
+// A wrapper for the rPLL that produces `f_clkout` from `f_clkin` (both in
+// MHz), with the ports `clock_in`, `clock_out` and `locked`.
+pub(crate) fn core_wrapper(f_clkin: f64, f_clkout: f64) -> Verilog {
+    let cores = match GowinRPLLCore::new().hdl() {
+        Verilog::Blackbox(b) => b.code,
+        _ => unreachable!(),
+    };
+    Verilog::Wrapper(Wrapper {
+        code: analyze(f_clkin, f_clkout).unwrap().core_instance(),
+        cores,
+    })
+}
+
+#[test]
+fn test_rpll_gen() {
+    // The Tang Nano 9K has a 27 MHz oscillator
+    let x = analyze(27., 81.).unwrap();
+    assert!((x.fout - 81.).abs() < 1e-6);
+    let f_vco = x.fout * x.odiv as f64;
+    assert!((400.0..=1200.0).contains(&f_vco));
+    // The PFD limit of 3 MHz rules out 27 * 37 / 30 = 33.3 MHz, so the
+    // closest we can get is 27 * 11 / 9
+    let x = analyze(27., 33.333).unwrap();
+    assert!((x.fout - 33.0).abs() < 1e-6);
+}
+
+// A PLL block built on the rPLL primitive.  The input and output
+// frequencies are given in Hz.  There is no simulation model, so in
+// simulation, drive `clock_out` with [Simulation::add_clock].
+#[derive(LogicBlock)]
+pub struct GowinRPLLBlock<const FIN_FREQ: u64, const FOUT_FREQ: u64> {
+    pub clock_in: Signal<In, Clock>,
+    pub clock_out: Signal<Out, Clock>,
+    pub locked: Signal<Out, Bit>,
+    core: GowinRPLLCore,
+    _settings: GowinRPLLSettings,
+}
+
+impl<const FIN_FREQ: u64, const FOUT_FREQ: u64> Default for GowinRPLLBlock<FIN_FREQ, FOUT_FREQ> {
+    fn default() -> Self {
+        let freq_in_mhz = (FIN_FREQ as f64) / (1_000_000.0);
+        let freq_out_mhz = (FOUT_FREQ as f64) / (1_000_000.0);
+        Self {
+            clock_in: Signal::default(),
+            clock_out: Signal::new_with_default(Clock::default()),
+            locked: Signal::new_with_default(false),
+            core: GowinRPLLCore::new(),
+            _settings: analyze(freq_in_mhz, freq_out_mhz).unwrap(),
+        }
+    }
+}
+
+impl<const FIN_FREQ: u64, const FOUT_FREQ: u64> GowinRPLLBlock<FIN_FREQ, FOUT_FREQ> {
+    pub fn frequency_hz(&self) -> f64 {
+        self._settings.fout * 1_000_000.0
+    }
+}
+
+impl<const FIN_FREQ: u64, const FOUT_FREQ: u64> Logic for GowinRPLLBlock<FIN_FREQ, FOUT_FREQ> {
+    fn update(&mut self) {}
+
+    fn connect(&mut self) {
+        self.clock_out.connect();
+        self.locked.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(self._settings.core_instance())
+    }
+}
+
+#[derive(LogicBlock, Default)]
+pub struct GowinRPLLCore {}
+
+impl GowinRPLLCore {
+    pub fn new() -> GowinRPLLCore {
+        Self {}
+    }
+}
+
+impl Logic for GowinRPLLCore {
+    fn update(&mut self) {}
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Blackbox(BlackBox {
+            code: r#"
+(* blackbox *)
+module rPLL (
+    output CLKOUT,
+    output LOCK,
+    output CLKOUTP,
+    output CLKOUTD,
+    output CLKOUTD3,
+    input  RESET,
+    input  RESET_P,
+    input  CLKIN,
+    input  CLKFB,
+    input  [5:0] FBDSEL,
+    input  [5:0] IDSEL,
+    input  [5:0] ODSEL,
+    input  [3:0] PSDA,
+    input  [3:0] DUTYDA,
+    input  [3:0] FDLY
+);
+parameter FCLKIN = "100.0";
+parameter DYN_IDIV_SEL = "false";
+parameter IDIV_SEL = 0;
+parameter DYN_FBDIV_SEL = "false";
+parameter FBDIV_SEL = 0;
+parameter DYN_ODIV_SEL = "false";
+parameter ODIV_SEL = 8;
+parameter PSDA_SEL = "0000";
+parameter DYN_DA_EN = "false";
+parameter DUTYDA_SEL = "1000";
+parameter CLKOUT_FT_DIR = 1'b1;
+parameter CLKOUTP_FT_DIR = 1'b1;
+parameter CLKOUT_DLY_STEP = 0;
+parameter CLKOUTP_DLY_STEP = 0;
+parameter CLKFB_SEL = "internal";
+parameter CLKOUT_BYPASS = "false";
+parameter CLKOUTP_BYPASS = "false";
+parameter CLKOUTD_BYPASS = "false";
+parameter DYN_SDIV_SEL = 2;
+parameter CLKOUTD_SRC = "CLKOUT";
+parameter CLKOUTD3_SRC = "CLKOUT";
+parameter DEVICE = "GW1N-1";
+endmodule
+            "#
+            .into(),
+            name: "rPLL".into(),
+        })
+    }
+}
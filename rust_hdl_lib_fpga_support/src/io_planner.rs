@@ -0,0 +1,371 @@
+use rust_hdl_lib_core::prelude::*;
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter};
+
+// An IO planner lets a board describe every pin it has in one place (the
+// catalog), and lets a design claim those pins by name.  The planner
+// attaches the location and IO standard to the signals it hands out, so
+// the UCF/XDC/PCF generators in the toolchains module pick them up with
+// no further work.  Claiming the same pin twice is reported as an error
+// when the design is built, rather than as a confusing place and route
+// failure later on.
+//
+// Note that the conflicts are found at run time (when the pins are
+// requested, i.e., when the design is constructed), and not at compile
+// time.  Pins are requested by name, so the compiler cannot check them.
+
+#[derive(Clone, Debug)]
+pub struct PinDefinition {
+    pub name: String,
+    pub location: String,
+    pub kind: Option<SignalType>,
+    pub clock_capable: bool,
+}
+
+impl PinDefinition {
+    pub fn new(name: &str, location: &str) -> Self {
+        Self {
+            name: name.into(),
+            location: location.into(),
+            kind: None,
+            clock_capable: false,
+        }
+    }
+    pub fn with_kind(self, kind: SignalType) -> Self {
+        Self {
+            kind: Some(kind),
+            ..self
+        }
+    }
+    pub fn clock_capable(self) -> Self {
+        Self {
+            clock_capable: true,
+            ..self
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum IOPlanError {
+    UnknownPin(String),
+    DuplicatePin(String),
+    DuplicateLocation {
+        location: String,
+        first: String,
+        second: String,
+    },
+    PinConflict {
+        pin: String,
+        owner: String,
+        requested_by: String,
+    },
+    NotClockCapable(String),
+    WidthMismatch {
+        owner: String,
+        expected: usize,
+        requested: usize,
+    },
+}
+
+impl Display for IOPlanError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            IOPlanError::UnknownPin(p) => write!(f, "Pin {} is not in the board catalog", p),
+            IOPlanError::DuplicatePin(p) => write!(f, "Pin {} is defined twice in the catalog", p),
+            IOPlanError::DuplicateLocation {
+                location,
+                first,
+                second,
+            } => write!(
+                f,
+                "Pins {} and {} are both at location {} in the catalog",
+                first, second, location
+            ),
+            IOPlanError::PinConflict {
+                pin,
+                owner,
+                requested_by,
+            } => write!(
+                f,
+                "Pin {} requested by {} is already claimed by {}",
+                pin, requested_by, owner
+            ),
+            IOPlanError::NotClockCapable(p) => {
+                write!(f, "Pin {} cannot be used as a clock input", p)
+            }
+            IOPlanError::WidthMismatch {
+                owner,
+                expected,
+                requested,
+            } => write!(
+                f,
+                "Signal {} is {} bits wide, but {} pins were requested",
+                owner, expected, requested
+            ),
+        }
+    }
+}
+
+impl std::error::Error for IOPlanError {}
+
+// The outputs and inputs of a bring-up test
+pub type BringUpSignals<const O: usize, const I: usize> =
+    (Signal<Out, Bits<O>>, Signal<In, Bits<I>>);
+
+#[derive(Clone, Debug)]
+pub struct IOPlanner {
+    catalog: BTreeMap<String, PinDefinition>,
+    claims: BTreeMap<String, String>,
+}
+
+impl IOPlanner {
+    pub fn new(catalog: &[PinDefinition]) -> Result<Self, IOPlanError> {
+        let mut map = BTreeMap::new();
+        let mut locations = BTreeMap::new();
+        for pin in catalog {
+            if map.insert(pin.name.clone(), pin.clone()).is_some() {
+                return Err(IOPlanError::DuplicatePin(pin.name.clone()));
+            }
+            if let Some(first) = locations.insert(pin.location.clone(), pin.name.clone()) {
+                return Err(IOPlanError::DuplicateLocation {
+                    location: pin.location.clone(),
+                    first,
+                    second: pin.name.clone(),
+                });
+            }
+        }
+        Ok(Self {
+            catalog: map,
+            claims: Default::default(),
+        })
+    }
+
+    pub fn pin(&self, name: &str) -> Option<&PinDefinition> {
+        self.catalog.get(name)
+    }
+
+    fn claim(&mut self, owner: &str, pins: &[&str]) -> Result<Vec<PinDefinition>, IOPlanError> {
+        let mut ret = vec![];
+        for pin in pins {
+            let def = self
+                .catalog
+                .get(*pin)
+                .ok_or_else(|| IOPlanError::UnknownPin(pin.to_string()))?;
+            if let Some(current) = self.claims.get(*pin) {
+                return Err(IOPlanError::PinConflict {
+                    pin: pin.to_string(),
+                    owner: current.clone(),
+                    requested_by: owner.into(),
+                });
+            }
+            if ret.iter().any(|x: &PinDefinition| x.name == def.name) {
+                return Err(IOPlanError::PinConflict {
+                    pin: pin.to_string(),
+                    owner: owner.into(),
+                    requested_by: owner.into(),
+                });
+            }
+            ret.push(def.clone());
+        }
+        for pin in pins {
+            self.claims.insert(pin.to_string(), owner.into());
+        }
+        Ok(ret)
+    }
+
+    /// Claim a set of pins for a signal named `owner`.  Bit `i` of the
+    /// signal is mapped to `pins[i]`.
+    pub fn request<D: Direction, T: Synth>(
+        &mut self,
+        owner: &str,
+        pins: &[&str],
+    ) -> Result<Signal<D, T>, IOPlanError> {
+        if pins.len() != T::BITS {
+            return Err(IOPlanError::WidthMismatch {
+                owner: owner.into(),
+                expected: T::BITS,
+                requested: pins.len(),
+            });
+        }
+        let defs = self.claim(owner, pins)?;
+        let mut x = Signal::<D, T>::default();
+        for (ndx, def) in defs.iter().enumerate() {
+            x.add_location(ndx, &def.location);
+            if let Some(kind) = &def.kind {
+                x.add_signal_type(ndx, kind.clone());
+            }
+        }
+        Ok(x)
+    }
+
+    pub fn request_pin<D: Direction>(&mut self, pin: &str) -> Result<Signal<D, Bit>, IOPlanError> {
+        self.request(pin, &[pin])
+    }
+
+    /// Claim a clock input.  The pin must be marked as clock capable in
+    /// the catalog.
+    pub fn request_clock(&mut self, pin: &str) -> Result<Signal<In, Clock>, IOPlanError> {
+        match self.catalog.get(pin) {
+            None => Err(IOPlanError::UnknownPin(pin.into())),
+            Some(def) if !def.clock_capable => Err(IOPlanError::NotClockCapable(pin.into())),
+            Some(_) => {
+                let mut x: Signal<In, Clock> = self.request(pin, &[pin])?;
+                x.connect();
+                Ok(x)
+            }
+        }
+    }
+
+    /// Claim the pins for a bring-up test (e.g., an `HLSBringUp` block), with
+    /// `outputs[i]` on bit `i` of the outputs, and `inputs[i]` on bit `i` of the
+    /// inputs.  The host side plan lists the same pins, in the same order.
+    pub fn request_bring_up<const O: usize, const I: usize>(
+        &mut self,
+        outputs: &[&str],
+        inputs: &[&str],
+    ) -> Result<BringUpSignals<O, I>, IOPlanError> {
+        let outs = self.request("bringup_outputs", outputs)?;
+        let mut ins: Signal<In, Bits<I>> = self.request("bringup_inputs", inputs)?;
+        ins.connect();
+        Ok((outs, ins))
+    }
+
+    /// Claim the pins for a pin self-test (e.g., an `HLSPinSelfTest` block),
+    /// with `pins[i]` on element `i` of the returned array.  The loopback
+    /// pairs of the jig refer to the pins by their position in `pins`.
+    pub fn request_self_test<const N: usize>(
+        &mut self,
+        pins: &[&str],
+    ) -> Result<[Signal<InOut, Bit>; N], IOPlanError> {
+        if pins.len() != N {
+            return Err(IOPlanError::WidthMismatch {
+                owner: "selftest_pins".into(),
+                expected: N,
+                requested: pins.len(),
+            });
+        }
+        let defs = self.claim("selftest_pins", pins)?;
+        Ok(array_init::array_init(|ndx| {
+            let mut x = Signal::<InOut, Bit>::default();
+            x.add_location(0, &defs[ndx].location);
+            if let Some(kind) = &defs[ndx].kind {
+                x.add_signal_type(0, kind.clone());
+            }
+            x
+        }))
+    }
+
+    /// The pins claimed so far, and who claimed them.
+    pub fn claims(&self) -> &BTreeMap<String, String> {
+        &self.claims
+    }
+
+    /// A plain text report of the pin assignment, one pin per line.
+    pub fn report(&self) -> String {
+        let mut ret = String::new();
+        for (name, def) in &self.catalog {
+            let owner = self.claims.get(name).map(|x| x.as_str()).unwrap_or("-");
+            ret += &format!("{:<16} {:<8} {}\n", name, def.location, owner);
+        }
+        ret
+    }
+}
+
+#[cfg(test)]
+fn test_catalog() -> Vec<PinDefinition> {
+    vec![
+        PinDefinition::new("clk", "P7").clock_capable(),
+        PinDefinition::new("led0", "J11").with_kind(SignalType::LowVoltageCMOS_3v3),
+        PinDefinition::new("led1", "K11").with_kind(SignalType::LowVoltageCMOS_3v3),
+        PinDefinition::new("btn", "P8"),
+    ]
+}
+
+#[test]
+fn test_io_planner_assigns_locations() {
+    let mut planner = IOPlanner::new(&test_catalog()).unwrap();
+    let leds: Signal<Out, Bits<2>> = planner.request("leds", &["led0", "led1"]).unwrap();
+    let constraints = leds.constraints();
+    assert_eq!(constraints.len(), 4);
+    assert!(matches!(&constraints[0].constraint, Constraint::Location(l) if l == "J11"));
+    assert!(matches!(&constraints[2].constraint, Constraint::Location(l) if l == "K11"));
+    assert_eq!(planner.claims()["led1"], "leds");
+    let _clock = planner.request_clock("clk").unwrap();
+    assert!(planner.report().contains("led0"));
+}
+
+#[test]
+fn test_io_planner_claims_bring_up_pins() {
+    let mut planner = IOPlanner::new(&test_catalog()).unwrap();
+    let (outputs, inputs) = planner
+        .request_bring_up::<2, 1>(&["led0", "led1"], &["btn"])
+        .unwrap();
+    assert!(matches!(&outputs.constraints()[2].constraint, Constraint::Location(l) if l == "K11"));
+    assert!(matches!(&inputs.constraints()[0].constraint, Constraint::Location(l) if l == "P8"));
+    assert_eq!(planner.claims()["btn"], "bringup_inputs");
+}
+
+#[test]
+fn test_io_planner_claims_self_test_pins() {
+    let mut planner = IOPlanner::new(&test_catalog()).unwrap();
+    let pins = planner
+        .request_self_test::<3>(&["led0", "led1", "btn"])
+        .unwrap();
+    assert!(matches!(&pins[1].constraints()[0].constraint, Constraint::Location(l) if l == "K11"));
+    assert!(matches!(&pins[2].constraints()[0].constraint, Constraint::Location(l) if l == "P8"));
+    assert_eq!(planner.claims()["btn"], "selftest_pins");
+    assert!(matches!(
+        planner.request_self_test::<2>(&["clk"]),
+        Err(IOPlanError::WidthMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_io_planner_detects_conflicts() {
+    let mut planner = IOPlanner::new(&test_catalog()).unwrap();
+    let _btn = planner.request_pin::<In>("btn").unwrap();
+    assert_eq!(
+        planner
+            .request::<Out, Bits<2>>("leds", &["led0", "btn"])
+            .err(),
+        Some(IOPlanError::PinConflict {
+            pin: "btn".into(),
+            owner: "btn".into(),
+            requested_by: "leds".into()
+        })
+    );
+    // The failed request must not leave led0 claimed
+    assert!(planner.request_pin::<Out>("led0").is_ok());
+    assert_eq!(
+        planner.request_clock("btn").err(),
+        Some(IOPlanError::NotClockCapable("btn".into()))
+    );
+    assert_eq!(
+        planner.request_pin::<In>("nope").err(),
+        Some(IOPlanError::UnknownPin("nope".into()))
+    );
+    assert!(matches!(
+        planner.request::<Out, Bits<3>>("x", &["led1"]),
+        Err(IOPlanError::WidthMismatch { .. })
+    ));
+}
+
+#[test]
+fn test_io_planner_checks_the_catalog() {
+    let mut catalog = test_catalog();
+    catalog.push(PinDefinition::new("led0", "A1"));
+    assert_eq!(
+        IOPlanner::new(&catalog).err(),
+        Some(IOPlanError::DuplicatePin("led0".into()))
+    );
+    let mut catalog = test_catalog();
+    catalog.push(PinDefinition::new("led2", "K11"));
+    assert_eq!(
+        IOPlanner::new(&catalog).err(),
+        Some(IOPlanError::DuplicateLocation {
+            location: "K11".into(),
+            first: "led1".into(),
+            second: "led2".into()
+        })
+    );
+}
@@ -0,0 +1,84 @@
+use rust_hdl_lib_core::prelude::*;
+
+// A tapped delay line for a [FineTDC], built on a chain of CCU2C carry cells
+// of the ECP5 (two taps per cell).  The cells are set up to propagate the
+// carry, so a rising edge on `hit` ripples up the chain, and the taps (the
+// inverted sum outputs) are sampled by `clock`.  The chain must be placed in a
+// single column to be of any use, and it should be longer than a clock (each
+// tap is roughly 20-30 ps).  In simulation, the line has no delay at all, so
+// the taps are all set or all clear.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct ECP5CarryChainDelayLine<const T: usize> {
+    pub clock: Signal<In, Clock>,
+    pub hit: Signal<In, Bit>,
+    pub taps: Signal<Out, Bits<T>>,
+}
+
+impl<const T: usize> Logic for ECP5CarryChainDelayLine<T> {
+    fn update(&mut self) {
+        assert_eq!(T % 2, 0);
+        if self.clock.pos_edge() {
+            self.taps.next = if self.hit.val() {
+                !Bits::<T>::default()
+            } else {
+                Bits::<T>::default()
+            };
+        }
+    }
+    fn connect(&mut self) {
+        self.taps.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let cells = (0..T / 2)
+            .map(|x| {
+                format!(
+                    r##"
+(* keep *)
+CCU2C #(.INIT0(16'b1001011010101010), .INIT1(16'b1001011010101010), .INJECT1_0("NO"), .INJECT1_1("NO"))
+    tdc_cell_{x}(.A0(1'b1), .B0(1'b0), .C0(1'b0), .D0(1'b1), .A1(1'b1), .B1(1'b0), .C1(1'b0), .D1(1'b1),
+    .CIN(tdc_carry[{x}]), .S0(tdc_sum[{lo}]), .S1(tdc_sum[{hi}]), .COUT(tdc_carry[{next}]));
+"##,
+                    x = x,
+                    lo = 2 * x,
+                    hi = 2 * x + 1,
+                    next = x + 1
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Verilog::Wrapper(Wrapper {
+            code: format!(
+                r##"
+wire [{cells}:0] tdc_carry;
+wire [{msb}:0] tdc_sum;
+reg [{msb}:0] tdc_taps;
+assign tdc_carry[0] = hit;
+{cells_code}
+always @(posedge clock) tdc_taps <= ~tdc_sum;
+assign taps = tdc_taps;
+"##,
+                cells = T / 2,
+                msb = T - 1,
+                cells_code = cells
+            ),
+            cores: r##"
+(* blackbox *)
+module CCU2C(input CIN, input A0, input B0, input C0, input D0, input A1, input B1, input C1, input D1,
+    output S0, output S1, output COUT);
+parameter [15:0] INIT0 = 16'h0000;
+parameter [15:0] INIT1 = 16'h0000;
+parameter INJECT1_0 = "YES";
+parameter INJECT1_1 = "YES";
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_ecp5_carry_chain_synthesizes() {
+    let mut uut = ECP5CarryChainDelayLine::<64>::default();
+    uut.connect_all();
+    yosys_validate("ecp5_carry_chain", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,44 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A glitch-free clock gate built on the DCCA (dynamic clock control) of the
+// ECP5, which sits at the entry to the primary clock network.  The ports
+// match those of the fabric [ClockGate], so the two can be swapped freely.
+// The clock is stopped (low) while `enable` is low.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct ECP5ClockGate {
+    pub clock: Signal<In, Clock>,
+    pub enable: Signal<In, Bit>,
+    pub clock_out: Signal<Out, Clock>,
+    _model: ClockGateModel,
+}
+
+impl Logic for ECP5ClockGate {
+    fn update(&mut self) {
+        self.clock_out.next = self._model.update(&self.clock, self.enable.val());
+    }
+    fn connect(&mut self) {
+        self.clock_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r##"
+DCCA dcca_inst(.CLKI(clock), .CE(enable), .CLKO(clock_out));
+            "##
+            .into(),
+            cores: r##"
+(* blackbox *)
+module DCCA(input CLKI, input CE, output CLKO);
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_ecp5_clock_gate_synthesizes() {
+    let mut uut = ECP5ClockGate::default();
+    uut.connect_all();
+    yosys_validate("ecp5_clock_gate", &generate_verilog(&uut)).unwrap();
+}
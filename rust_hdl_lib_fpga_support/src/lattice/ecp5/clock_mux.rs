@@ -0,0 +1,48 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A glitch-free clock multiplexer built on the DCSC (dynamic clock select)
+// of the ECP5, in its glitchless mode (MODESEL low).  The ports match those
+// of the fabric [ClockMux], so the two can be swapped freely.  `select`
+// picks `clock_1` when high.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct ECP5ClockMux {
+    pub clock_0: Signal<In, Clock>,
+    pub clock_1: Signal<In, Clock>,
+    pub select: Signal<In, Bit>,
+    pub clock_out: Signal<Out, Clock>,
+    _model: ClockMuxModel,
+}
+
+impl Logic for ECP5ClockMux {
+    fn update(&mut self) {
+        self.clock_out.next = self
+            ._model
+            .update(&self.clock_0, &self.clock_1, self.select.val());
+    }
+    fn connect(&mut self) {
+        self.clock_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r##"
+DCSC #(.DCSMODE("POS")) dcsc_inst(.CLK0(clock_0), .CLK1(clock_1), .SEL({select, ~select}), .MODESEL(1'b0), .DCSOUT(clock_out));
+            "##
+            .into(),
+            cores: r##"
+(* blackbox *)
+module DCSC(input CLK0, input CLK1, input [1:0] SEL, input MODESEL, output DCSOUT);
+parameter DCSMODE = "POS";
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_ecp5_clock_mux_synthesizes() {
+    let mut uut = ECP5ClockMux::default();
+    uut.connect_all();
+    yosys_validate("ecp5_clock_mux", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,369 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The ECP5-5G (and ECP5UM) parts have one or two dual channel SERDES blocks
+// (DCUs).  The DCU has a hard PCS with its own 8b/10b codec and word
+// aligner, but this wrapper bypasses all of that (the "10BSER" mode), so
+// that the fabric sees raw 10 bit words.  Use the `Encoder8b10b`,
+// `Decoder8b10b` and `CommaAligner` widgets to build a link on top.  The
+// fabric interface is 10 bits wide (1:1 gearing) and runs at the line
+// rate / 10, which tops out around 3.2 Gb/s.
+//
+// There is no simulation model for the DCU.
+
+const REFCK_MULTIPLIERS: [(u32, &str); 5] = [
+    (8, "0b011"),
+    (10, "0b001"),
+    (16, "0b010"),
+    (20, "0b000"),
+    (25, "0b100"),
+];
+
+const RATE_DIVIDERS: [(u32, &str); 6] = [
+    (1, "0b000"),
+    (2, "0b010"),
+    (4, "0b100"),
+    (8, "0b101"),
+    (16, "0b110"),
+    (32, "0b111"),
+];
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct DCUConfig {
+    pub dcu: usize,
+    pub channel: usize,
+    pub refclk_mhz: f64,
+    pub line_rate_mbps: f64,
+    pub tx_amplitude_mv: u32,
+    pub rx_loss_of_signal_detect: bool,
+}
+
+impl DCUConfig {
+    pub fn new(line_rate_mbps: f64, refclk_mhz: f64) -> Self {
+        Self {
+            dcu: 0,
+            channel: 0,
+            refclk_mhz,
+            line_rate_mbps,
+            tx_amplitude_mv: 1000,
+            rx_loss_of_signal_detect: true,
+        }
+    }
+    pub fn dcu(self, dcu: usize) -> Self {
+        assert!(dcu < 2, "ECP5 parts have at most 2 DCUs");
+        Self { dcu, ..self }
+    }
+    pub fn channel(self, channel: usize) -> Self {
+        assert!(channel < 2, "Each DCU has 2 channels");
+        Self { channel, ..self }
+    }
+    pub fn tx_amplitude_mv(self, tx_amplitude_mv: u32) -> Self {
+        assert!(
+            (100..=1300).contains(&tx_amplitude_mv),
+            "TX amplitude must be between 100 and 1300 mV"
+        );
+        Self {
+            tx_amplitude_mv,
+            ..self
+        }
+    }
+    pub fn rx_loss_of_signal_detect(self, enable: bool) -> Self {
+        Self {
+            rx_loss_of_signal_detect: enable,
+            ..self
+        }
+    }
+    // Find the reference clock multiplier and rate divider that hit the
+    // requested line rate.  The PLL runs at refclk * multiplier, which must
+    // be in the 1-3.2 GHz range (up to 5 GHz on the -5G parts).
+    fn clocking(&self) -> (&'static str, &'static str) {
+        for (div, div_code) in RATE_DIVIDERS {
+            for (mult, mult_code) in REFCK_MULTIPLIERS {
+                let vco = self.refclk_mhz * mult as f64;
+                let rate = vco / div as f64;
+                if (1000.0..=5000.0).contains(&vco)
+                    && ((rate - self.line_rate_mbps) / self.line_rate_mbps).abs() < 1e-3
+                {
+                    return (mult_code, div_code);
+                }
+            }
+        }
+        panic!(
+            "Cannot generate a line rate of {} Mb/s from a {} MHz reference clock",
+            self.line_rate_mbps, self.refclk_mhz
+        )
+    }
+    /// The parameters for the DCUA primitive, as (name, value) pairs.
+    pub fn parameters(&self) -> Vec<(String, String)> {
+        let (mult_code, div_code) = self.clocking();
+        let max_rate = format!("{:.2}", self.line_rate_mbps / 1000.0);
+        let mut ret: Vec<(String, String)> = [
+            ("D_MACROPDB", "0b1"),
+            ("D_IB_PWDNB", "0b1"),
+            ("D_XGE_MODE", "0b0"),
+            ("D_TXPLL_PWDNB", "0b1"),
+            ("D_REFCK_MODE", mult_code),
+            ("D_TX_MAX_RATE", &max_rate),
+            ("D_TX_VCO_CK_DIV", div_code),
+            ("D_BITCLK_LOCAL_EN", "0b1"),
+            ("D_SYNC_LOCAL_EN", "0b1"),
+            ("D_CDR_LOL_SET", "0b11"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let tx_amplitude = format!("0d{}", self.tx_amplitude_mv);
+        let los = if self.rx_loss_of_signal_detect {
+            "0b1"
+        } else {
+            "0b0"
+        };
+        let channel: Vec<(&str, &str)> = vec![
+            ("PROTOCOL", "10BSER"),
+            ("UC_MODE", "0b1"),
+            ("ENC_BYPASS", "0b1"),
+            ("DEC_BYPASS", "0b1"),
+            ("WA_BYPASS", "0b1"),
+            ("RX_GEAR_MODE", "0b0"),
+            ("TX_GEAR_MODE", "0b0"),
+            ("FF_RX_H_CLK_EN", "0b0"),
+            ("FF_RX_F_CLK_DIS", "0b0"),
+            ("FF_TX_H_CLK_EN", "0b0"),
+            ("FF_TX_F_CLK_DIS", "0b0"),
+            ("CDR_MAX_RATE", &max_rate),
+            ("RX_DCO_CK_DIV", div_code),
+            ("RPWDNB", "0b1"),
+            ("TPWDNB", "0b1"),
+            ("TXAMPLITUDE", &tx_amplitude),
+            ("RTERM_RX", "0d22"),
+            ("RTERM_TX", "0d19"),
+            ("RXTERM_CM", "0b11"),
+            ("RXIN_CM", "0b11"),
+            ("RX_LOS_EN", los),
+        ];
+        for (k, v) in channel {
+            ret.push((format!("CH{}_{}", self.channel, k), v.to_string()));
+        }
+        ret
+    }
+}
+
+#[derive(LogicBlock)]
+pub struct ECP5DCUChannel {
+    // Dedicated reference clock pins of the DCU
+    pub refclk_p: Signal<In, Clock>,
+    pub refclk_n: Signal<In, Clock>,
+    // High speed pins
+    pub rx_p: Signal<In, Bit>,
+    pub rx_n: Signal<In, Bit>,
+    pub tx_p: Signal<Out, Bit>,
+    pub tx_n: Signal<Out, Bit>,
+    // Fabric side - tx_data is sampled on tx_clock, and rx_data changes on
+    // rx_clock (the recovered clock).  Bit 0 is the first bit on the wire.
+    pub tx_clock: Signal<Out, Clock>,
+    pub tx_data: Signal<In, Bits<10>>,
+    pub rx_clock: Signal<Out, Clock>,
+    pub rx_data: Signal<Out, Bits<10>>,
+    // Resets and status
+    pub pll_reset: Signal<In, Bit>,
+    pub tx_reset: Signal<In, Bit>,
+    pub rx_reset: Signal<In, Bit>,
+    pub tx_pll_locked: Signal<Out, Bit>,
+    pub rx_cdr_locked: Signal<Out, Bit>,
+    pub rx_signal_lost: Signal<Out, Bit>,
+    _config: DCUConfig,
+}
+
+impl ECP5DCUChannel {
+    pub fn new(config: DCUConfig) -> Self {
+        // Check the clocking early, rather than at HDL generation time
+        let _ = config.clocking();
+        Self {
+            refclk_p: Default::default(),
+            refclk_n: Default::default(),
+            rx_p: Default::default(),
+            rx_n: Default::default(),
+            tx_p: Default::default(),
+            tx_n: Default::default(),
+            tx_clock: Default::default(),
+            tx_data: Default::default(),
+            rx_clock: Default::default(),
+            rx_data: Default::default(),
+            pll_reset: Default::default(),
+            tx_reset: Default::default(),
+            rx_reset: Default::default(),
+            tx_pll_locked: Default::default(),
+            rx_cdr_locked: Default::default(),
+            rx_signal_lost: Default::default(),
+            _config: config,
+        }
+    }
+}
+
+fn dcu_ports(ch: usize) -> Vec<(String, &'static str)> {
+    let mut ret: Vec<(String, &'static str)> = vec![
+        ("D_REFCLKI".into(), "input"),
+        ("D_FFC_MACROPDB".into(), "input"),
+        ("D_FFC_MACRO_RST".into(), "input"),
+        ("D_FFC_DUAL_RST".into(), "input"),
+        ("D_FFC_TRST".into(), "input"),
+        ("D_FFS_PLOL".into(), "output"),
+        (format!("CH{}_HDINP", ch), "input"),
+        (format!("CH{}_HDINN", ch), "input"),
+        (format!("CH{}_HDOUTP", ch), "output"),
+        (format!("CH{}_HDOUTN", ch), "output"),
+        (format!("CH{}_FF_TXI_CLK", ch), "input"),
+        (format!("CH{}_FF_TX_PCLK", ch), "output"),
+        (format!("CH{}_FF_RXI_CLK", ch), "input"),
+        (format!("CH{}_FF_RX_PCLK", ch), "output"),
+        (format!("CH{}_FFC_RXPWDNB", ch), "input"),
+        (format!("CH{}_FFC_TXPWDNB", ch), "input"),
+        (format!("CH{}_FFC_RRST", ch), "input"),
+        (format!("CH{}_FFC_LANE_TX_RST", ch), "input"),
+        (format!("CH{}_FFC_LANE_RX_RST", ch), "input"),
+        (format!("CH{}_FFS_RLOS", ch), "output"),
+        (format!("CH{}_FFS_RLOL", ch), "output"),
+    ];
+    for bit in 0..24 {
+        ret.push((format!("CH{}_FF_TX_D_{}", ch, bit), "input"));
+        ret.push((format!("CH{}_FF_RX_D_{}", ch, bit), "output"));
+    }
+    ret
+}
+
+impl Logic for ECP5DCUChannel {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.tx_p.connect();
+        self.tx_n.connect();
+        self.tx_clock.connect();
+        self.rx_clock.connect();
+        self.rx_data.connect();
+        self.tx_pll_locked.connect();
+        self.rx_cdr_locked.connect();
+        self.rx_signal_lost.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let ch = self._config.channel;
+        let params = self
+            ._config
+            .parameters()
+            .iter()
+            .map(|(k, v)| format!("    .{}(\"{}\")", k, v))
+            .collect::<Vec<_>>()
+            .join(",\n");
+        let mut ports = vec![
+            "    .D_REFCLKI(refclk)".to_string(),
+            "    .D_FFC_MACROPDB(1'b1)".to_string(),
+            "    .D_FFC_MACRO_RST(pll_reset)".to_string(),
+            "    .D_FFC_DUAL_RST(pll_reset)".to_string(),
+            "    .D_FFC_TRST(tx_reset)".to_string(),
+            "    .D_FFS_PLOL(pll_lol)".to_string(),
+            format!("    .CH{}_HDINP(rx_p)", ch),
+            format!("    .CH{}_HDINN(rx_n)", ch),
+            format!("    .CH{}_HDOUTP(tx_p)", ch),
+            format!("    .CH{}_HDOUTN(tx_n)", ch),
+            format!("    .CH{}_FF_TXI_CLK(tx_clock)", ch),
+            format!("    .CH{}_FF_TX_PCLK(tx_clock)", ch),
+            format!("    .CH{}_FF_RXI_CLK(rx_clock)", ch),
+            format!("    .CH{}_FF_RX_PCLK(rx_clock)", ch),
+            format!("    .CH{}_FFC_RXPWDNB(1'b1)", ch),
+            format!("    .CH{}_FFC_TXPWDNB(1'b1)", ch),
+            format!("    .CH{}_FFC_RRST(rx_reset)", ch),
+            format!("    .CH{}_FFC_LANE_TX_RST(tx_reset)", ch),
+            format!("    .CH{}_FFC_LANE_RX_RST(rx_reset)", ch),
+            format!("    .CH{}_FFS_RLOS(rx_signal_lost)", ch),
+            format!("    .CH{}_FFS_RLOL(cdr_lol)", ch),
+        ];
+        for bit in 0..10 {
+            ports.push(format!("    .CH{}_FF_TX_D_{}(tx_data[{}])", ch, bit, bit));
+            ports.push(format!("    .CH{}_FF_RX_D_{}(rx_data[{}])", ch, bit, bit));
+        }
+        for bit in 10..24 {
+            ports.push(format!("    .CH{}_FF_TX_D_{}(1'b0)", ch, bit));
+        }
+        let code = format!(
+            r##"
+wire refclk;
+wire pll_lol;
+wire cdr_lol;
+assign tx_pll_locked = ~pll_lol;
+assign rx_cdr_locked = ~cdr_lol;
+
+(* LOC="EXTREF{dcu}" *)
+EXTREFB #(
+    .REFCK_PWDNB("0b1"),
+    .REFCK_RTERM("0b1"),
+    .REFCK_DCBIAS_EN("0b0")
+) extref_inst (
+    .REFCLKP(refclk_p),
+    .REFCLKN(refclk_n),
+    .REFCLKO(refclk)
+);
+
+(* LOC="DCU{dcu}" *)
+DCUA #(
+{params}
+) dcu_inst (
+{ports}
+);
+"##,
+            dcu = self._config.dcu,
+            params = params,
+            ports = ports.join(",\n")
+        );
+        let cores = format!(
+            r##"
+(* blackbox *)
+module EXTREFB(input REFCLKP, input REFCLKN, output REFCLKO);
+parameter REFCK_PWDNB = "0b0";
+parameter REFCK_RTERM = "0b0";
+parameter REFCK_DCBIAS_EN = "0b0";
+endmodule
+
+(* blackbox *)
+module DCUA(
+{ports}
+);
+{params}
+endmodule
+"##,
+            ports = dcu_ports(ch)
+                .iter()
+                .map(|(name, dir)| format!("    {} {}", dir, name))
+                .collect::<Vec<_>>()
+                .join(",\n"),
+            params = self
+                ._config
+                .parameters()
+                .iter()
+                .map(|(k, _)| format!("parameter {} = \"0b0\";", k))
+                .collect::<Vec<_>>()
+                .join("\n")
+        );
+        Verilog::Wrapper(Wrapper { code, cores })
+    }
+}
+
+#[test]
+fn test_dcu_clocking() {
+    // 1.25 Gb/s (SGMII) from a 125 MHz reference
+    let config = DCUConfig::new(1250.0, 125.0);
+    assert_eq!(config.clocking(), ("0b001", "0b000"));
+    // 3 Gb/s (SATA II) from 150 MHz
+    let config = DCUConfig::new(3000.0, 150.0).channel(1);
+    assert_eq!(config.clocking(), ("0b000", "0b000"));
+    assert!(config
+        .parameters()
+        .contains(&("CH1_PROTOCOL".to_string(), "10BSER".to_string())));
+    // 625 Mb/s from 125 MHz needs the divider
+    let config = DCUConfig::new(625.0, 125.0);
+    assert_eq!(config.clocking(), ("0b001", "0b010"));
+    assert!(std::panic::catch_unwind(|| DCUConfig::new(1234.0, 100.0).clocking()).is_err());
+}
+
+#[test]
+fn test_dcu_synthesizes() {
+    let mut uut = ECP5DCUChannel::new(DCUConfig::new(1250.0, 125.0).channel(1));
+    uut.connect_all();
+    yosys_validate("ecp5_dcu", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,165 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// The temperature (in C) for a 6 bit code from the DTR.  The DTR covers
+// -58 C to 85 C in 64 steps.  This treats the steps as even, which is
+// good to within a few degrees - the DTR is not a precision sensor.
+pub fn dtr_celsius(code: u8) -> f64 {
+    -58.0 + (code & 0x3F) as f64 * (85.0 + 58.0) / 63.0
+}
+
+// The die temperature readout (DTR) of the ECP5.  A pulse on `start`
+// begins a conversion, and the result is presented on `code` - the
+// temperature is in the lower 6 bits (see [dtr_celsius]).  The simulation
+// model reports the temperature given to [ECP5DTR::set_simulated_temperature]
+// once the first conversion has started.
+#[derive(LogicBlock)]
+pub struct ECP5DTR {
+    pub start: Signal<In, Bit>,
+    pub code: Signal<Out, Bits<8>>,
+    _sim_code: u8,
+    _sim_started: bool,
+}
+
+impl Default for ECP5DTR {
+    fn default() -> Self {
+        let mut ret = Self {
+            start: Default::default(),
+            code: Default::default(),
+            _sim_code: 0,
+            _sim_started: false,
+        };
+        ret.set_simulated_temperature(25.0);
+        ret
+    }
+}
+
+impl ECP5DTR {
+    pub fn set_simulated_temperature(&mut self, temperature: f64) {
+        self._sim_code = (0..64_u8)
+            .min_by(|a, b| {
+                let da = (dtr_celsius(*a) - temperature).abs();
+                let db = (dtr_celsius(*b) - temperature).abs();
+                da.partial_cmp(&db).unwrap()
+            })
+            .unwrap();
+    }
+}
+
+impl Logic for ECP5DTR {
+    fn update(&mut self) {
+        if self.start.val() {
+            self._sim_started = true;
+        }
+        if self._sim_started {
+            self.code.next = (self._sim_code as u64).into();
+        }
+    }
+    fn connect(&mut self) {
+        self.code.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r##"
+DTR inst_DTR(.STARTPULSE(start),
+    .DTROUT7(code[7]), .DTROUT6(code[6]), .DTROUT5(code[5]), .DTROUT4(code[4]),
+    .DTROUT3(code[3]), .DTROUT2(code[2]), .DTROUT1(code[1]), .DTROUT0(code[0]));
+            "##
+            .into(),
+            cores: r##"
+(* blackbox *)
+module DTR(input STARTPULSE, output DTROUT7, output DTROUT6, output DTROUT5, output DTROUT4,
+    output DTROUT3, output DTROUT2, output DTROUT1, output DTROUT0);
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+// Runs a conversion of the DTR at regular intervals, and presents the
+// result as a [SystemMonitor].  The ECP5 cannot measure its supply
+// voltages, so those readings are always 0.
+#[derive(LogicBlock)]
+pub struct ECP5SystemMonitor {
+    pub clock: Signal<In, Clock>,
+    pub monitor: SystemMonitor,
+    pub dtr: ECP5DTR,
+    interval: Strobe<32>,
+    // The temperature (in 1/16 K) for each code
+    table: ROM<Bits<16>, 6>,
+    start: DFF<Bit>,
+    temperature: DFF<Bits<16>>,
+    valid: DFF<Bit>,
+}
+
+impl ECP5SystemMonitor {
+    // Each conversion takes a millisecond (the result is read just before the next one starts)
+    pub fn new(frequency: u64) -> Self {
+        Self {
+            clock: Default::default(),
+            monitor: Default::default(),
+            dtr: Default::default(),
+            interval: Strobe::new(frequency, 1000.0),
+            table: (0..64_u8)
+                .map(|code| (((dtr_celsius(code) + 273.15) * 16.0).round() as u64).to_bits())
+                .into(),
+            start: Default::default(),
+            temperature: Default::default(),
+            valid: Default::default(),
+        }
+    }
+}
+
+impl Logic for ECP5SystemMonitor {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.interval.clock.next = self.clock.val();
+        dff_setup!(self, clock, start, temperature, valid);
+        self.interval.enable.next = true;
+        // Read the result of the last conversion, and start the next one
+        self.start.d.next = self.interval.strobe.val();
+        self.dtr.start.next = self.start.q.val();
+        self.table.address.next = self.dtr.code.val().get_bits::<6>(0);
+        self.valid.d.next = false;
+        if self.interval.strobe.val() {
+            self.temperature.d.next = self.table.data.val();
+            self.valid.d.next = true;
+        }
+        self.monitor.temperature.next = self.temperature.q.val();
+        self.monitor.vccint.next = 0.into();
+        self.monitor.vccaux.next = 0.into();
+        self.monitor.valid.next = self.valid.q.val();
+    }
+}
+
+#[test]
+fn test_ecp5_system_monitor_synthesizes() {
+    let mut uut = ECP5SystemMonitor::new(25_000_000);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("ecp5_sysmon", &vlog).unwrap();
+}
+
+#[test]
+fn test_ecp5_system_monitor_reports_temperature() {
+    let mut uut = ECP5SystemMonitor::new(1_000_000);
+    uut.dtr.set_simulated_temperature(60.0);
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<ECP5SystemMonitor>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<ECP5SystemMonitor>| {
+        let mut x = sim.init()?;
+        // The first reading is from before any conversion was done
+        x = sim.watch(|x| x.monitor.valid.val(), x)?;
+        wait_clock_cycle!(sim, clock, x);
+        x = sim.watch(|x| x.monitor.valid.val(), x)?;
+        let celsius = temperature_celsius(x.monitor.temperature.val().index() as u16);
+        sim_assert!(sim, (celsius - 60.0).abs() < 1.5, x);
+        sim_assert_eq!(sim, x.monitor.vccint.val(), 0, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1_000_000).unwrap();
+}
@@ -1,3 +1,8 @@
+pub mod carry_chain;
+pub mod clock_gate;
+pub mod clock_mux;
+pub mod dcu;
+pub mod dtr;
 pub mod edge_flip_flop;
 pub mod edge_tristate_buffer;
 pub mod edge_tristate_buffer_delayed;
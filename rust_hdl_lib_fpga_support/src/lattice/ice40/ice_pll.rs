@@ -46,6 +46,74 @@ impl ICE40PLLSettings {
         };
         filter_range
     }
+    // The SB_PLL40_CORE instance for these settings, fed from `clock_in`
+    fn core_instance(&self) -> String {
+        format!(
+            "\
+SB_PLL40_CORE #(
+                .FEEDBACK_PATH(\"{feedback}\"),
+                .DIVR({DIVR}),
+                .DIVF({DIVF}),
+                .DIVQ({DIVQ}),
+                .FILTER_RANGE({FILTER_RANGE})
+               ) uut (
+                .LOCK(locked),
+                .RESETB(1'b1),
+                .BYPASS(1'b0),
+                .REFERENCECLK(clock_in),
+                .PLLOUTCORE(clock_out));
+",
+            feedback = if self.simple { "SIMPLE" } else { "NON_SIMPLE" },
+            DIVR = VerilogLiteral::from(self.divr as u32),
+            DIVF = VerilogLiteral::from(self.divf as u32),
+            DIVQ = VerilogLiteral::from(self.divq as u32),
+            FILTER_RANGE = VerilogLiteral::from(self.filter_range())
+        )
+    }
+}
+
+// Every output frequency (in MHz) the PLL can reach from `f_pllin` in
+// SIMPLE feedback mode, in increasing order.  Unlike [analyze], an input
+// that is out of range is not an error - it just reaches nothing.
+pub(crate) fn output_frequencies(f_pllin: f64) -> Vec<f64> {
+    let mut ret = vec![];
+    if !(10. ..=133.).contains(&f_pllin) {
+        return ret;
+    }
+    for divr in 0..=15 {
+        let f_pfd = f_pllin / (divr as f64 + 1.);
+        if !(10. ..=133.).contains(&f_pfd) {
+            continue;
+        }
+        for divf in 0..=127 {
+            let f_vco = f_pfd * (divf as f64 + 1.);
+            if !(533. ..=1066.).contains(&f_vco) {
+                continue;
+            }
+            for divq in 1..=6 {
+                let fout = f_vco * f64::exp2(-divq as f64);
+                if (16. ..=275.).contains(&fout) {
+                    ret.push(fout);
+                }
+            }
+        }
+    }
+    ret.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    ret.dedup_by(|a, b| (*a - *b).abs() < 1e-9);
+    ret
+}
+
+// A wrapper for the SB_PLL40_CORE that produces `f_pllout` from `f_pllin`
+// (both in MHz), with the ports `clock_in`, `clock_out` and `locked`.
+pub(crate) fn core_wrapper(f_pllin: f64, f_pllout: f64) -> Verilog {
+    let cores = match ICEPLL40Core::new().hdl() {
+        Verilog::Blackbox(b) => b.code,
+        _ => unreachable!(),
+    };
+    Verilog::Wrapper(Wrapper {
+        code: analyze(true, f_pllin, f_pllout).unwrap().core_instance(),
+        cores,
+    })
 }
 
 fn analyze(simple_feedback: bool, f_pllin: f64, f_pllout: f64) -> Option<ICE40PLLSettings> {
@@ -162,10 +230,50 @@ impl<const FIN_FREQ: u64, const FOUT_FREQ: u64> Logic for ICE40PLLBlock<FIN_FREQ
         self.locked.connect();
     }
 
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(self._settings.core_instance())
+    }
+}
+
+// Same as the [ICE40PLLBlock], but driven directly from a PLL capable
+// package pin (SB_PLL40_PAD).  The `clock_in` signal must be a top level
+// pin of the design.  This is the usual way to clock the UP5K parts, where
+// the global buffer feeding an SB_PLL40_CORE is often already taken.
+#[derive(LogicBlock)]
+pub struct ICE40PLLPadBlock<const FIN_FREQ: u64, const FOUT_FREQ: u64> {
+    pub clock_in: Signal<In, Clock>,
+    pub clock_out: Signal<Out, Clock>,
+    pub locked: Signal<Out, Bit>,
+    core: ICEPLL40Pad,
+    _settings: ICE40PLLSettings,
+}
+
+impl<const FIN_FREQ: u64, const FOUT_FREQ: u64> Default for ICE40PLLPadBlock<FIN_FREQ, FOUT_FREQ> {
+    fn default() -> Self {
+        let freq_in_mhz = (FIN_FREQ as f64) / (1_000_000.0);
+        let freq_out_mhz = (FOUT_FREQ as f64) / (1_000_000.0);
+        Self {
+            clock_in: Signal::default(),
+            clock_out: Signal::new_with_default(Clock::default()),
+            locked: Signal::new_with_default(false),
+            core: ICEPLL40Pad::new(),
+            _settings: analyze(true, freq_in_mhz, freq_out_mhz).unwrap(),
+        }
+    }
+}
+
+impl<const FIN_FREQ: u64, const FOUT_FREQ: u64> Logic for ICE40PLLPadBlock<FIN_FREQ, FOUT_FREQ> {
+    fn update(&mut self) {}
+
+    fn connect(&mut self) {
+        self.clock_out.connect();
+        self.locked.connect();
+    }
+
     fn hdl(&self) -> Verilog {
         Verilog::Custom(format!(
             "\
-SB_PLL40_CORE #(
+SB_PLL40_PAD #(
                 .FEEDBACK_PATH(\"{feedback}\"),
                 .DIVR({DIVR}),
                 .DIVF({DIVF}),
@@ -175,8 +283,8 @@ SB_PLL40_CORE #(
                 .LOCK(locked),
                 .RESETB(1'b1),
                 .BYPASS(1'b0),
-                .REFERENCECLK(clock_in),
-                .PLLOUTCORE(clock_out));
+                .PACKAGEPIN(clock_in),
+                .PLLOUTGLOBAL(clock_out));
 ",
             feedback = if self._settings.simple {
                 "SIMPLE"
@@ -242,3 +350,55 @@ endmodule
         })
     }
 }
+
+#[derive(LogicBlock, Default)]
+pub struct ICEPLL40Pad {}
+
+impl ICEPLL40Pad {
+    pub fn new() -> ICEPLL40Pad {
+        Self {}
+    }
+}
+
+impl Logic for ICEPLL40Pad {
+    fn update(&mut self) {}
+
+    fn hdl(&self) -> Verilog {
+        Verilog::Blackbox(BlackBox {
+            code: r#"
+(* blackbox *)
+module SB_PLL40_PAD (
+    input   PACKAGEPIN,
+    output  PLLOUTCORE,
+    output  PLLOUTGLOBAL,
+    input   EXTFEEDBACK,
+    input   [7:0] DYNAMICDELAY,
+    output  LOCK,
+    input   BYPASS,
+    input   RESETB,
+    input   LATCHINPUTVALUE,
+    output  SDO,
+    input   SDI,
+    input   SCLK
+);
+parameter FEEDBACK_PATH = "SIMPLE";
+parameter DELAY_ADJUSTMENT_MODE_FEEDBACK = "FIXED";
+parameter DELAY_ADJUSTMENT_MODE_RELATIVE = "FIXED";
+parameter SHIFTREG_DIV_MODE = 1'b0;
+parameter FDA_FEEDBACK = 4'b0000;
+parameter FDA_RELATIVE = 4'b0000;
+parameter PLLOUT_SELECT = "GENCLK";
+parameter DIVR = 4'b0000;
+parameter DIVF = 7'b0000000;
+parameter DIVQ = 3'b000;
+parameter FILTER_RANGE = 3'b000;
+parameter ENABLE_ICEGATE = 1'b0;
+parameter TEST_MODE = 1'b0;
+parameter EXTERNAL_DIVIDE_FACTOR = 1;
+endmodule
+            "#
+            .into(),
+            name: "SB_PLL40_PAD".into(),
+        })
+    }
+}
@@ -1 +1,3 @@
 pub mod ice_pll;
+pub mod spram;
+pub mod warmboot;
@@ -0,0 +1,120 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The UP5K parts have 4 single port RAMs (SPRAM) of 16K x 16 bits each.
+// They cannot be inferred by yosys, so they have to be instantiated
+// directly.  The write mask has one bit per nibble of the data word.
+#[derive(LogicBlock)]
+pub struct ICE40SPRAM {
+    pub clock: Signal<In, Clock>,
+    pub address: Signal<In, Bits<14>>,
+    pub data_in: Signal<In, Bits<16>>,
+    pub mask_write: Signal<In, Bits<4>>,
+    pub write_enable: Signal<In, Bit>,
+    pub chip_select: Signal<In, Bit>,
+    pub data_out: Signal<Out, Bits<16>>,
+    _mem: Vec<Bits<16>>,
+}
+
+impl Default for ICE40SPRAM {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            address: Default::default(),
+            data_in: Default::default(),
+            mask_write: Default::default(),
+            write_enable: Default::default(),
+            chip_select: Default::default(),
+            data_out: Default::default(),
+            _mem: vec![0.into(); 1 << 14],
+        }
+    }
+}
+
+impl Logic for ICE40SPRAM {
+    fn update(&mut self) {
+        if self.clock.pos_edge() && self.chip_select.val() {
+            let address = self.address.val().index();
+            if self.write_enable.val() {
+                let mut mask: Bits<16> = 0.into();
+                for nibble in 0..4 {
+                    if self.mask_write.val().get_bit(nibble) {
+                        mask = mask | (Bits::<16>::from(0xF) << (nibble as LiteralType * 4));
+                    }
+                }
+                self._mem[address] = (self._mem[address] & !mask) | (self.data_in.val() & mask);
+            } else {
+                self.data_out.next = self._mem[address];
+            }
+        }
+    }
+    fn connect(&mut self) {
+        self.data_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r##"
+SB_SPRAM256KA inst_SB_SPRAM256KA(
+    .ADDRESS(address),
+    .DATAIN(data_in),
+    .MASKWREN(mask_write),
+    .WREN(write_enable),
+    .CHIPSELECT(chip_select),
+    .CLOCK(clock),
+    .STANDBY(1'b0),
+    .SLEEP(1'b0),
+    .POWEROFF(1'b1),
+    .DATAOUT(data_out));
+            "##
+            .into(),
+            cores: r##"
+(* blackbox *)
+module SB_SPRAM256KA(
+    input [13:0] ADDRESS,
+    input [15:0] DATAIN,
+    input [3:0] MASKWREN,
+    input WREN,
+    input CHIPSELECT,
+    input CLOCK,
+    input STANDBY,
+    input SLEEP,
+    input POWEROFF,
+    output [15:0] DATAOUT);
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_spram_synthesizes() {
+    let mut uut = ICE40SPRAM::default();
+    uut.connect_all();
+    yosys_validate("spram", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_spram_masked_write() {
+    let mut uut = ICE40SPRAM::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<ICE40SPRAM>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<ICE40SPRAM>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        x.chip_select.next = true;
+        x.write_enable.next = true;
+        x.address.next = 42.into();
+        x.data_in.next = 0xDEAD.into();
+        x.mask_write.next = 0xF.into();
+        wait_clock_cycle!(sim, clock, x);
+        x.data_in.next = 0xBEEF.into();
+        x.mask_write.next = 0b0101.into();
+        wait_clock_cycle!(sim, clock, x);
+        x.write_enable.next = false;
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert_eq!(sim, x.data_out.val(), 0xDEAF, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 1000).unwrap();
+}
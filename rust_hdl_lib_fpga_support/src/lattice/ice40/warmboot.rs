@@ -0,0 +1,50 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The warm boot primitive (SB_WARMBOOT) of the iCE40.  Raising `boot`
+// reconfigures the FPGA from the image numbered `image` in a multiboot
+// flash image (see [crate::toolchains::multiboot::ice40_multiboot_image]).
+// The design is gone as soon as the reboot starts, so `image` must be
+// stable before `boot` is raised.  The simulation model records the
+// image that was booted, which can be checked with [ICE40WarmBoot::booted_image].
+#[derive(LogicBlock, Default)]
+pub struct ICE40WarmBoot {
+    pub boot: Signal<In, Bit>,
+    pub image: Signal<In, Bits<2>>,
+    _sim_booted: Option<usize>,
+}
+
+impl ICE40WarmBoot {
+    pub fn booted_image(&self) -> Option<usize> {
+        self._sim_booted
+    }
+}
+
+impl Logic for ICE40WarmBoot {
+    fn update(&mut self) {
+        if self.boot.val() && self._sim_booted.is_none() {
+            self._sim_booted = Some(self.image.val().index());
+        }
+    }
+    fn connect(&mut self) {}
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r##"
+SB_WARMBOOT inst_SB_WARMBOOT(.BOOT(boot), .S1(image[1]), .S0(image[0]));
+            "##
+            .into(),
+            cores: r##"
+(* blackbox *)
+module SB_WARMBOOT(input BOOT, input S1, input S0);
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_warmboot_synthesizes() {
+    let mut uut = ICE40WarmBoot::default();
+    uut.connect_all();
+    yosys_validate("ice40_warmboot", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,164 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The embedded function block (EFB) of the MachXO2/MachXO3 holds the hard
+// I2C and SPI cores, a timer/counter, and the interface to the
+// configuration logic (and hence to the user flash memory, UFM).  All of
+// them are reached through an 8 bit WISHBONE bus.  This wrapper only
+// brings out the WISHBONE bus and the UFM interrupt - the hard I2C/SPI/
+// timer cores are left disabled.
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "EFBWishboneTarget"]
+pub struct EFBWishboneController {
+    pub cyc: Signal<Out, Bit>,
+    pub stb: Signal<Out, Bit>,
+    pub we: Signal<Out, Bit>,
+    pub address: Signal<Out, Bits<8>>,
+    pub to_efb: Signal<Out, Bits<8>>,
+    pub from_efb: Signal<In, Bits<8>>,
+    pub ack: Signal<In, Bit>,
+}
+
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "EFBWishboneController"]
+pub struct EFBWishboneTarget {
+    pub cyc: Signal<In, Bit>,
+    pub stb: Signal<In, Bit>,
+    pub we: Signal<In, Bit>,
+    pub address: Signal<In, Bits<8>>,
+    pub to_efb: Signal<In, Bits<8>>,
+    pub from_efb: Signal<Out, Bits<8>>,
+    pub ack: Signal<Out, Bit>,
+}
+
+// Register addresses of the configuration logic on the EFB WISHBONE bus
+pub const EFB_CFGCR: u8 = 0x70;
+pub const EFB_CFGTXDR: u8 = 0x71;
+pub const EFB_CFGSR: u8 = 0x72;
+pub const EFB_CFGRXDR: u8 = 0x73;
+
+#[derive(LogicBlock)]
+pub struct MachXO2EFB {
+    pub clock: Signal<In, Clock>,
+    pub reset: Signal<In, Bit>,
+    pub wishbone: EFBWishboneTarget,
+    pub ufm_irq: Signal<Out, Bit>,
+    _wb_clock_mhz: f64,
+    _dev_density: &'static str,
+}
+
+impl MachXO2EFB {
+    // The device density is needed by the EFB to locate the UFM, and
+    // must match the part (e.g., "7000L" for an LCMXO2-7000HE).
+    pub fn new(wb_clock_hz: u64, dev_density: &'static str) -> Self {
+        Self {
+            clock: Default::default(),
+            reset: Default::default(),
+            wishbone: Default::default(),
+            ufm_irq: Default::default(),
+            _wb_clock_mhz: wb_clock_hz as f64 / 1.0e6,
+            _dev_density: dev_density,
+        }
+    }
+}
+
+impl Logic for MachXO2EFB {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.wishbone.from_efb.connect();
+        self.wishbone.ack.connect();
+        self.ufm_irq.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let adr = (0..8)
+            .map(|i| format!(".WBADRI{i}(wishbone$address[{i}])", i = i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let dat_in = (0..8)
+            .map(|i| format!(".WBDATI{i}(wishbone$to_efb[{i}])", i = i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let dat_out = (0..8)
+            .map(|i| format!(".WBDATO{i}(wishbone$from_efb[{i}])", i = i))
+            .collect::<Vec<_>>()
+            .join(", ");
+        let ports = |prefix: &str, dir: &str| {
+            (0..8)
+                .map(|i| format!("{} {}{}", dir, prefix, i))
+                .collect::<Vec<_>>()
+                .join(", ")
+        };
+        Verilog::Wrapper(Wrapper {
+            code: format!(
+                r##"
+EFB #(
+    .EFB_I2C1("DISABLED"),
+    .EFB_I2C2("DISABLED"),
+    .EFB_SPI("DISABLED"),
+    .EFB_TC("DISABLED"),
+    .EFB_TC_PORTMODE("WB"),
+    .EFB_UFM("ENABLED"),
+    .EFB_WB_CLK_FREQ("{freq:.1}"),
+    .DEV_DENSITY("{density}"),
+    .UFM_INIT_PAGES(0),
+    .UFM_INIT_START_PAGE(0),
+    .UFM_INIT_ALL_ZEROS("ENABLED"),
+    .UFM_INIT_FILE_NAME("NONE"),
+    .UFM_INIT_FILE_FORMAT("HEX")
+) inst_EFB(
+    .WBCLKI(clock), .WBRSTI(reset),
+    .WBCYCI(wishbone$cyc), .WBSTBI(wishbone$stb), .WBWEI(wishbone$we),
+    {adr},
+    {dat_in},
+    {dat_out},
+    .WBACKO(wishbone$ack),
+    .WBCUFMIRQ(ufm_irq),
+    .UFMSN(1'b1));
+"##,
+                freq = self._wb_clock_mhz,
+                density = self._dev_density,
+                adr = adr,
+                dat_in = dat_in,
+                dat_out = dat_out
+            ),
+            cores: format!(
+                r##"
+(* blackbox *)
+module EFB(input WBCLKI, input WBRSTI, input WBCYCI, input WBSTBI, input WBWEI,
+    {adr}, {dat_in}, {dat_out},
+    output WBACKO, output WBCUFMIRQ, input UFMSN);
+parameter EFB_I2C1 = "DISABLED";
+parameter EFB_I2C2 = "DISABLED";
+parameter EFB_SPI = "DISABLED";
+parameter EFB_TC = "DISABLED";
+parameter EFB_TC_PORTMODE = "WB";
+parameter EFB_UFM = "DISABLED";
+parameter EFB_WB_CLK_FREQ = "50.0";
+parameter DEV_DENSITY = "7000L";
+parameter UFM_INIT_PAGES = 0;
+parameter UFM_INIT_START_PAGE = 0;
+parameter UFM_INIT_ALL_ZEROS = "ENABLED";
+parameter UFM_INIT_FILE_NAME = "NONE";
+parameter UFM_INIT_FILE_FORMAT = "HEX";
+endmodule
+"##,
+                adr = ports("WBADRI", "input"),
+                dat_in = ports("WBDATI", "input"),
+                dat_out = ports("WBDATO", "output")
+            ),
+        })
+    }
+}
+
+#[test]
+fn test_efb_synthesizes() {
+    let mut uut = MachXO2EFB::new(50_000_000, "7000L");
+    uut.clock.connect();
+    uut.reset.connect();
+    uut.wishbone.cyc.connect();
+    uut.wishbone.stb.connect();
+    uut.wishbone.we.connect();
+    uut.wishbone.address.connect();
+    uut.wishbone.to_efb.connect();
+    uut.connect_all();
+    yosys_validate("efb", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,3 @@
+pub mod efb;
+pub mod osch;
+pub mod ufm;
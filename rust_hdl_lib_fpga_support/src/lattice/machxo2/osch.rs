@@ -0,0 +1,98 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The nominal frequencies (in MHz) that the internal oscillator of the
+// MachXO2/MachXO3 can be set to.  The oscillator is only accurate to
+// about +/- 5%, so it is not a substitute for a crystal.
+const OSCH_FREQUENCIES: [&str; 63] = [
+    "2.08", "2.15", "2.22", "2.29", "2.38", "2.46", "2.56", "2.66", "2.77", "2.89", "3.02", "3.17",
+    "3.33", "3.50", "3.69", "3.91", "4.16", "4.29", "4.43", "4.59", "4.75", "4.93", "5.12", "5.32",
+    "5.54", "5.78", "6.05", "6.33", "6.65", "7.00", "7.39", "7.82", "8.31", "8.58", "8.87", "9.17",
+    "9.50", "9.85", "10.23", "10.64", "11.08", "11.57", "12.09", "12.67", "13.30", "14.00",
+    "14.78", "15.65", "16.63", "17.73", "19.00", "20.46", "22.17", "24.18", "26.60", "29.56",
+    "33.25", "38.00", "44.33", "53.20", "66.50", "88.67", "133.00",
+];
+
+// Pick the supported nominal frequency closest to the requested one.
+fn nominal_frequency(freq_hz: u64) -> &'static str {
+    let freq_mhz = freq_hz as f64 / 1.0e6;
+    let best = OSCH_FREQUENCIES
+        .iter()
+        .min_by(|a, b| {
+            let da = (a.parse::<f64>().unwrap() - freq_mhz).abs();
+            let db = (b.parse::<f64>().unwrap() - freq_mhz).abs();
+            da.partial_cmp(&db).unwrap()
+        })
+        .unwrap();
+    let err = (best.parse::<f64>().unwrap() - freq_mhz).abs() / freq_mhz;
+    assert!(
+        err < 0.01,
+        "Requested OSCH frequency of {} MHz is not supported (closest is {} MHz)",
+        freq_mhz,
+        best
+    );
+    best
+}
+
+// The internal oscillator (OSCH) of the MachXO2/MachXO3.  There is no
+// simulation model - the oscillator is not derived from any other clock,
+// so in simulation, drive `clock` with [Simulation::add_clock] instead.
+#[derive(LogicBlock)]
+pub struct MachXO2OSCH {
+    pub standby: Signal<In, Bit>,
+    pub clock: Signal<Out, Clock>,
+    _nom_freq: &'static str,
+}
+
+impl MachXO2OSCH {
+    pub fn new(freq_hz: u64) -> Self {
+        Self {
+            standby: Default::default(),
+            clock: Default::default(),
+            _nom_freq: nominal_frequency(freq_hz),
+        }
+    }
+    pub fn nominal_frequency_mhz(&self) -> f64 {
+        self._nom_freq.parse().unwrap()
+    }
+}
+
+impl Logic for MachXO2OSCH {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.clock.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: format!(
+                r##"
+OSCH #(.NOM_FREQ("{freq}")) inst_OSCH(.STDBY(standby), .OSC(clock), .SEDSTDBY());
+"##,
+                freq = self._nom_freq
+            ),
+            cores: r##"
+(* blackbox *)
+module OSCH(input STDBY, output OSC, output SEDSTDBY);
+parameter NOM_FREQ = "2.08";
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_osch_frequency_selection() {
+    assert_eq!(nominal_frequency(12_090_000), "12.09");
+    assert_eq!(nominal_frequency(133_000_000), "133.00");
+    assert!(std::panic::catch_unwind(|| nominal_frequency(50_000_000)).is_err());
+}
+
+#[test]
+fn test_osch_synthesizes() {
+    let mut uut = MachXO2OSCH::new(26_600_000);
+    uut.standby.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains("NOM_FREQ(\"26.60\")"));
+    yosys_validate("osch", &vlog).unwrap();
+}
@@ -0,0 +1,220 @@
+use super::efb::{EFBWishboneController, EFB_CFGCR, EFB_CFGRXDR, EFB_CFGTXDR};
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// Reads one 16 byte page of the user flash memory (UFM) through the EFB.
+// The UFM is accessed by sending configuration commands over the EFB
+// WISHBONE bus.  The command sequence (see Lattice TN1246) is stored as a
+// small program in a ROM, with each step encoded as
+//
+//   [17:16] - operation (0 = write, 1 = write page high, 2 = write page low, 3 = read)
+//   [15:8]  - WISHBONE register address
+//   [7:0]   - data to write
+//
+// Each byte read back from the UFM is presented on `data` with a one
+// cycle `data_valid` strobe.
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum State {
+    Idle,
+    Cycle,
+    Gap,
+}
+
+const OP_WRITE: u64 = 0;
+const OP_PAGE_HI: u64 = 1;
+const OP_PAGE_LO: u64 = 2;
+const OP_READ: u64 = 3;
+
+fn step(op: u64, address: u8, data: u8) -> Bits<18> {
+    ((op << 16) | ((address as u64) << 8) | (data as u64)).into()
+}
+
+fn frame(body: &[Bits<18>]) -> Vec<Bits<18>> {
+    let mut ret = vec![step(OP_WRITE, EFB_CFGCR, 0x80)];
+    ret.extend_from_slice(body);
+    ret.push(step(OP_WRITE, EFB_CFGCR, 0x00));
+    ret
+}
+
+fn command(bytes: &[u8]) -> Vec<Bits<18>> {
+    bytes
+        .iter()
+        .map(|x| step(OP_WRITE, EFB_CFGTXDR, *x))
+        .collect()
+}
+
+pub(crate) fn ufm_read_program() -> Vec<Bits<18>> {
+    let mut ret = vec![];
+    // Enable the configuration interface (transparent mode)
+    ret.extend(frame(&command(&[0x74, 0x08, 0x00, 0x00])));
+    // Set the address to the requested page of the UFM sector
+    let mut address = command(&[0xB4, 0x00, 0x00, 0x00, 0x40, 0x00]);
+    address.push(step(OP_PAGE_HI, EFB_CFGTXDR, 0));
+    address.push(step(OP_PAGE_LO, EFB_CFGTXDR, 0));
+    ret.extend(frame(&address));
+    // Read a single page
+    let mut read = command(&[0xCA, 0x10, 0x00, 0x01]);
+    for _ in 0..16 {
+        read.push(step(OP_READ, EFB_CFGRXDR, 0));
+    }
+    ret.extend(frame(&read));
+    // Disable the configuration interface, and send the bypass command
+    ret.extend(frame(&command(&[0x26, 0x00, 0x00])));
+    ret.extend(frame(&command(&[0xFF, 0xFF, 0xFF, 0xFF])));
+    ret
+}
+
+#[derive(LogicBlock)]
+pub struct MachXO2UFMReader {
+    pub clock: Signal<In, Clock>,
+    pub start: Signal<In, Bit>,
+    pub page: Signal<In, Bits<14>>,
+    pub data: Signal<Out, Bits<8>>,
+    pub data_valid: Signal<Out, Bit>,
+    pub busy: Signal<Out, Bit>,
+    pub wishbone: EFBWishboneController,
+    program: ROM<Bits<18>, 6>,
+    last: Constant<Bits<6>>,
+    pc: DFF<Bits<6>>,
+    page_latch: DFF<Bits<14>>,
+    state: DFF<State>,
+    op: Signal<Local, Bits<2>>,
+}
+
+impl Default for MachXO2UFMReader {
+    fn default() -> Self {
+        let program = ufm_read_program();
+        Self {
+            clock: Default::default(),
+            start: Default::default(),
+            page: Default::default(),
+            data: Default::default(),
+            data_valid: Default::default(),
+            busy: Default::default(),
+            wishbone: Default::default(),
+            last: Constant::new((program.len() - 1).to_bits()),
+            program: program.into_iter().into(),
+            pc: Default::default(),
+            page_latch: Default::default(),
+            state: Default::default(),
+            op: Default::default(),
+        }
+    }
+}
+
+impl Logic for MachXO2UFMReader {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, pc, page_latch, state);
+        // Decode the current step of the program
+        self.program.address.next = self.pc.q.val();
+        self.op.next = self.program.data.val().get_bits::<2>(16);
+        self.wishbone.address.next = self.program.data.val().get_bits::<8>(8);
+        self.wishbone.to_efb.next = self.program.data.val().get_bits::<8>(0);
+        if self.op.val() == 1 {
+            self.wishbone.to_efb.next = bit_cast::<8, 6>(self.page_latch.q.val().get_bits::<6>(8));
+        }
+        if self.op.val() == 2 {
+            self.wishbone.to_efb.next = self.page_latch.q.val().get_bits::<8>(0);
+        }
+        self.wishbone.we.next = self.op.val() != 3;
+        self.wishbone.cyc.next = false;
+        self.wishbone.stb.next = false;
+        self.data.next = self.wishbone.from_efb.val();
+        self.data_valid.next = false;
+        self.busy.next = self.state.q.val() != State::Idle;
+        match self.state.q.val() {
+            State::Idle => {
+                if self.start.val() {
+                    self.pc.d.next = 0.into();
+                    self.page_latch.d.next = self.page.val();
+                    self.state.d.next = State::Cycle;
+                }
+            }
+            State::Cycle => {
+                self.wishbone.cyc.next = true;
+                self.wishbone.stb.next = true;
+                if self.wishbone.ack.val() {
+                    self.data_valid.next = self.op.val() == 3;
+                    if self.pc.q.val() == self.last.val() {
+                        self.state.d.next = State::Idle;
+                    } else {
+                        self.pc.d.next = self.pc.q.val() + 1;
+                        self.state.d.next = State::Gap;
+                    }
+                }
+            }
+            State::Gap => {
+                self.state.d.next = State::Cycle;
+            }
+            _ => {
+                self.state.d.next = State::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ufm_reader_synthesizes() {
+    let mut uut = MachXO2UFMReader::default();
+    uut.connect_all();
+    yosys_validate("ufm_reader", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_ufm_reader_sequence() {
+    let mut uut = MachXO2UFMReader::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<MachXO2UFMReader>| {
+        x.clock.next = !x.clock.val()
+    });
+    // Act as the EFB - ack every cycle, and record the bytes written to
+    // the transmit register.  Reads return an incrementing count.
+    sim.add_testbench(move |mut sim: Sim<MachXO2UFMReader>| {
+        let mut x = sim.init()?;
+        let mut sent = vec![];
+        let mut counter = 0_u8;
+        wait_clock_true!(sim, clock, x);
+        x.page.next = 0x1234.into();
+        x.start.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.start.next = false;
+        loop {
+            x.wishbone.ack.next = false;
+            if x.wishbone.stb.val() {
+                x.wishbone.ack.next = true;
+                if x.wishbone.we.val() {
+                    if x.wishbone.address.val().index() == EFB_CFGTXDR as usize {
+                        sent.push(x.wishbone.to_efb.val().index() as u8);
+                    }
+                } else {
+                    x.wishbone.from_efb.next = (counter as u64).into();
+                    counter += 1;
+                }
+            }
+            wait_clock_cycle!(sim, clock, x);
+            if !x.busy.val() {
+                break;
+            }
+        }
+        sim_assert_eq!(sim, counter, 16, x);
+        sim_assert_eq!(
+            sim,
+            &sent[4..14],
+            &[0xB4, 0x00, 0x00, 0x00, 0x40, 0x00, 0x12, 0x34, 0xCA, 0x10],
+            x
+        );
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<MachXO2UFMReader>| {
+        let mut x = sim.init()?;
+        for expected in 0..16_u64 {
+            x = sim.watch(|x| x.data_valid.val(), x)?;
+            sim_assert_eq!(sim, x.data.val(), expected, x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000).unwrap();
+}
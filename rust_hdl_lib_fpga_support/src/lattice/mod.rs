@@ -1,2 +1,3 @@
 pub mod ecp5;
 pub mod ice40;
+pub mod machxo2;
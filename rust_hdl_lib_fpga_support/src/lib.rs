@@ -1,2 +1,7 @@
+pub mod board;
+pub mod clock_planner;
+pub mod gowin;
+pub mod io_planner;
 pub mod lattice;
 pub mod toolchains;
+pub mod xilinx;
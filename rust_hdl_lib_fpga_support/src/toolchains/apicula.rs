@@ -0,0 +1,148 @@
+// Covers the Gowin parts via the open source flow - yosys, nextpnr (the
+// himbaechel Gowin backend) and gowin_pack from Project Apicula.
+use rust_hdl_lib_core::check_error::check_all;
+use rust_hdl_lib_core::prelude::*;
+use std::fs::{create_dir_all, remove_dir_all, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use super::map_signal_type_to_gowin_string;
+
+#[derive(Default)]
+struct CSTGenerator {
+    path: NamedPath,
+    namespace: NamedPath,
+    cst: Vec<String>,
+}
+
+impl Probe for CSTGenerator {
+    fn visit_start_scope(&mut self, name: &str, _node: &dyn Block) {
+        self.path.push(name);
+        self.namespace.reset();
+    }
+    fn visit_start_namespace(&mut self, name: &str, _node: &dyn Block) {
+        self.namespace.push(name);
+    }
+    fn visit_atom(&mut self, name: &str, signal: &dyn Atom) {
+        if self.path.len() == 1 {
+            let namespace = self.namespace.flat("$");
+            let name = if namespace.is_empty() {
+                name.to_owned()
+            } else {
+                format!("{}${}", namespace, name)
+            };
+            for pin in &signal.constraints() {
+                let prefix = if signal.bits() == 1 {
+                    name.clone()
+                } else {
+                    format!("{}[{}]", name, pin.index)
+                };
+                match &pin.constraint {
+                    Constraint::Location(l) => {
+                        self.cst.push(format!("IO_LOC \"{}\" {};", prefix, l))
+                    }
+                    Constraint::Kind(k) => self.cst.push(format!(
+                        "IO_PORT \"{}\" IO_TYPE={};",
+                        prefix,
+                        map_signal_type_to_gowin_string(k)
+                    )),
+                    Constraint::Slew(_) => {
+                        // Apicula does not support drive strength/slew settings yet
+                    }
+                    // Clock constraints are not part of the CST file, and are
+                    // passed to nextpnr on the command line instead.
+                    Constraint::Timing(_) => {}
+                    Constraint::Custom(s) => self.cst.push(s.clone()),
+                }
+            }
+        }
+    }
+    fn visit_end_namespace(&mut self, _name: &str, _node: &dyn Block) {
+        self.namespace.pop();
+    }
+    fn visit_end_scope(&mut self, _name: &str, _node: &dyn Block) {
+        self.path.pop();
+    }
+}
+
+pub fn generate_cst<U: Block>(uut: &U) -> String {
+    let mut cst = CSTGenerator::default();
+    uut.accept("top", &mut cst);
+    cst.cst.join("\n") + "\n"
+}
+
+#[derive(Clone, Debug)]
+pub struct ApiculaOptions {
+    // Full part number, e.g., GW1NR-LV9QN88PC6/I5
+    pub device: String,
+    // The family name used by nextpnr and gowin_pack, e.g., GW1N-9C
+    pub family: String,
+    // Target clock frequency for place and route (MHz)
+    pub frequency_mhz: Option<f64>,
+}
+
+impl Default for ApiculaOptions {
+    fn default() -> Self {
+        Self {
+            device: "GW1NR-LV9QN88PC6/I5".into(),
+            family: "GW1N-9C".into(),
+            frequency_mhz: None,
+        }
+    }
+}
+
+fn save_stdout(output: Output, dir: &Path, basename: &str) -> Result<(), std::io::Error> {
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let mut out_file = File::create(dir.join(format!("{}.out", basename)))?;
+    write!(out_file, "{}", stdout)?;
+    let mut err_file = File::create(dir.join(format!("{}.err", basename)))?;
+    write!(err_file, "{}", stderr)?;
+    Ok(())
+}
+
+pub fn generate_bitstream_apicula<U: Block>(mut uut: U, prefix: &str, options: ApiculaOptions) {
+    uut.connect_all();
+    check_all(&uut).unwrap(); // TODO - Change from panic to return an error
+    let verilog_text = generate_verilog(&uut);
+    let cst_text = generate_cst(&uut);
+    let dir = PathBuf::from(prefix);
+    let _ = remove_dir_all(&dir);
+    let _ = create_dir_all(&dir);
+    let mut v_file = File::create(dir.join("top.v")).unwrap();
+    write!(v_file, "{}", verilog_text).unwrap();
+    let mut cst_file = File::create(dir.join("top.cst")).unwrap();
+    write!(cst_file, "{}", cst_text).unwrap();
+    let output = Command::new("yosys")
+        .current_dir(dir.clone())
+        .args(["-p", "synth_gowin -top top -json top.json"])
+        .arg("top.v")
+        .output()
+        .unwrap();
+    save_stdout(output, &dir, "yosys_synth").unwrap();
+    let mut pnr = Command::new("nextpnr-himbaechel");
+    pnr.current_dir(dir.clone()).args([
+        "--json",
+        "top.json",
+        "--write",
+        "pnr.json",
+        "--device",
+        &options.device,
+        "--vopt",
+        &format!("family={}", options.family),
+        "--vopt",
+        "cst=top.cst",
+    ]);
+    if let Some(freq) = options.frequency_mhz {
+        pnr.args(["--freq", &format!("{}", freq)]);
+    }
+    let output = pnr.output().unwrap();
+    save_stdout(output, &dir, "nextpnr").unwrap();
+    let output = Command::new("gowin_pack")
+        .current_dir(dir.clone())
+        .args(["-d", &options.family, "-o", "top.fs", "pnr.json"])
+        .output()
+        .unwrap();
+    save_stdout(output, &dir, "gowin_pack").unwrap();
+}
@@ -2,7 +2,7 @@
 use rust_hdl_lib_core::prelude::*;
 use std::collections::HashMap;
 
-use super::map_signal_type_to_lattice_string;
+use super::{hierarchical_net_name, map_signal_type_to_lattice_string};
 
 #[derive(Default)]
 struct LPFGenerator {
@@ -56,6 +56,19 @@ impl Probe for LPFGenerator {
                             )
                         }
                         Timing::Custom(c) => c.to_string(),
+                        Timing::FalsePath => {
+                            format!(
+                                "BLOCK NET \"{}\"",
+                                hierarchical_net_name(&self.path, &name, signal.bits(), "[*]")
+                            )
+                        }
+                        Timing::Multicycle(m) => {
+                            format!(
+                                "MULTICYCLE NET \"{}\" {} X",
+                                hierarchical_net_name(&self.path, &name, signal.bits(), "[*]"),
+                                m.cycles
+                            )
+                        }
                         _ => unimplemented!("Unknown timing constraint for ECP5 generation"),
                     };
                     if !timing.is_empty() {
@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use crate::toolchains::map_signal_type_to_xilinx_string;
+use crate::toolchains::{hierarchical_net_name, map_signal_type_to_xilinx_string};
 use rust_hdl_lib_core::prelude::*;
 
 #[derive(Default)]
@@ -121,6 +121,25 @@ impl Probe for UCFGenerator {
                         }
                         Timing::Custom(c) => c.to_string(),
                         Timing::VivadoFalsePath(_) => "".to_string(),
+                        Timing::FalsePath => {
+                            format!(
+                                "NET \"{net}\" TIG",
+                                net =
+                                    hierarchical_net_name(&self.path, &name, signal.bits(), "<*>")
+                            )
+                        }
+                        Timing::Multicycle(m) => {
+                            let group = format!(
+                                "TP_{}",
+                                hierarchical_net_name(&self.path, &name, 1, "")
+                                    .replace(['/', '$'], "_")
+                            );
+                            format!("NET \"{net}\" TPTHRU = {group}; TIMESPEC TS_{group} = FROM FFS THRU {group} TO FFS TS_{clock} * {cycles}",
+                                    net = hierarchical_net_name(&self.path, &name, signal.bits(), "<*>"),
+                                    group = group,
+                                    clock = m.clock,
+                                    cycles = m.cycles)
+                        }
                         _ => unimplemented!("Unknown timing constraint for ISE/UCF generation"),
                     };
                     if !timing.is_empty() {
@@ -0,0 +1,122 @@
+// Covers the MachXO2/MachXO3 via either the open source flow (yosys,
+// nextpnr-machxo2 and ecppack from Project Trellis) or Lattice Diamond.
+// Both flows take the same LPF constraints as the ECP5.
+use rust_hdl_lib_core::check_error::check_all;
+use rust_hdl_lib_core::prelude::*;
+use std::fs::{create_dir_all, remove_dir_all, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::{Command, Output};
+
+use super::ecp5::generate_lpf;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum MachXO2Flow {
+    Yosys,
+    Diamond { diamond_path: String },
+}
+
+#[derive(Clone, Debug)]
+pub struct MachXO2Options {
+    // Full part number, e.g., LCMXO2-7000HE-4TG144C
+    pub device: String,
+    pub flow: MachXO2Flow,
+}
+
+impl Default for MachXO2Options {
+    fn default() -> Self {
+        Self {
+            device: "LCMXO2-7000HE-4TG144C".into(),
+            flow: MachXO2Flow::Yosys,
+        }
+    }
+}
+
+fn save_stdout(output: Output, dir: &Path, basename: &str) -> Result<(), std::io::Error> {
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    let mut out_file = File::create(dir.join(format!("{}.out", basename)))?;
+    write!(out_file, "{}", stdout)?;
+    let mut err_file = File::create(dir.join(format!("{}.err", basename)))?;
+    write!(err_file, "{}", stderr)?;
+    Ok(())
+}
+
+pub fn generate_diamond_tcl(options: &MachXO2Options) -> String {
+    format!(
+        r#"
+prj_project new -name top -impl impl1 -dev {device} -synthesis "lse"
+prj_src add top.v
+prj_src add top.lpf
+prj_impl option top top
+prj_project save
+prj_run Synthesis -impl impl1
+prj_run Translate -impl impl1
+prj_run Map -impl impl1
+prj_run PAR -impl impl1
+prj_run Export -impl impl1 -task Bitgen
+prj_run Export -impl impl1 -task Jedecgen
+prj_project close
+"#,
+        device = options.device
+    )
+}
+
+pub fn generate_bitstream_machxo2<U: Block>(mut uut: U, prefix: &str, options: MachXO2Options) {
+    uut.connect_all();
+    check_all(&uut).unwrap(); // TODO - Change from panic to return an error
+    let verilog_text = generate_verilog(&uut);
+    let lpf_text = generate_lpf(&uut);
+    let dir = PathBuf::from(prefix);
+    let _ = remove_dir_all(&dir);
+    let _ = create_dir_all(&dir);
+    let mut lpf_file = File::create(dir.join("top.lpf")).unwrap();
+    write!(lpf_file, "{}", lpf_text).unwrap();
+    match &options.flow {
+        MachXO2Flow::Yosys => {
+            let mut v_file = File::create(dir.join("top.v")).unwrap();
+            write!(v_file, "{}", verilog_text).unwrap();
+            let output = Command::new("yosys")
+                .current_dir(dir.clone())
+                .args(["-p", "synth_lattice -family xo2 -top top -json top.json"])
+                .arg("top.v")
+                .output()
+                .unwrap();
+            save_stdout(output, &dir, "yosys_synth").unwrap();
+            let output = Command::new("nextpnr-machxo2")
+                .current_dir(dir.clone())
+                .args([
+                    "--device",
+                    &options.device,
+                    "--json",
+                    "top.json",
+                    "--lpf",
+                    "top.lpf",
+                    "--textcfg",
+                    "top.config",
+                ])
+                .output()
+                .unwrap();
+            save_stdout(output, &dir, "nextpnr").unwrap();
+            let output = Command::new("ecppack")
+                .current_dir(dir.clone())
+                .args(["--compress", "top.config", "top.bit"])
+                .output()
+                .unwrap();
+            save_stdout(output, &dir, "ecppack").unwrap();
+        }
+        MachXO2Flow::Diamond { diamond_path } => {
+            // Diamond brings its own models of the primitives
+            let mut v_file = File::create(dir.join("top.v")).unwrap();
+            write!(v_file, "{}", filter_blackbox_directives(&verilog_text)).unwrap();
+            let mut tcl_file = File::create(dir.join("top.tcl")).unwrap();
+            write!(tcl_file, "{}", generate_diamond_tcl(&options)).unwrap();
+            let output = Command::new(format!("{}/diamondc", diamond_path))
+                .current_dir(dir.clone())
+                .arg("top.tcl")
+                .output()
+                .unwrap();
+            save_stdout(output, &dir, "diamond").unwrap();
+        }
+    }
+}
@@ -3,6 +3,7 @@ use rust_hdl_lib_core::prelude::*;
 pub fn map_signal_type_to_lattice_string(k: &SignalType) -> &str {
     match k {
         SignalType::LowVoltageCMOS_3v3 => "LVCMOS33",
+        SignalType::Custom(c) => c,
         _ => panic!(
             "Unsupported mapping for signal type {:?} in Lattice mapping",
             k
@@ -10,6 +11,20 @@ pub fn map_signal_type_to_lattice_string(k: &SignalType) -> &str {
     }
 }
 
+pub fn map_signal_type_to_gowin_string(k: &SignalType) -> &str {
+    match k {
+        SignalType::LowVoltageCMOS_3v3 => "LVCMOS33",
+        SignalType::LowVoltageCMOS_1v8 => "LVCMOS18",
+        SignalType::LowVoltageCMOS_1v5 => "LVCMOS15",
+        SignalType::LowVoltageDifferentialSignal_2v5 => "LVDS25",
+        SignalType::Custom(c) => c,
+        _ => panic!(
+            "Unsupported mapping for signal type {:?} in Gowin mapping",
+            k
+        ),
+    }
+}
+
 pub fn map_signal_type_to_xilinx_string(k: &SignalType) -> &str {
     match k {
         SignalType::LowVoltageCMOS_1v8 => "LVCMOS18",
@@ -28,7 +43,84 @@ pub fn map_signal_type_to_xilinx_string(k: &SignalType) -> &str {
     }
 }
 
+// The name of a net in the generated Verilog, as the vendor tools see it
+// after elaboration (the top module is not part of the hierarchy).  For
+// a vector, `bus` is used to select all of the bits (e.g., `foo[*]`).
+pub fn hierarchical_net_name(path: &NamedPath, name: &str, bits: usize, bus: &str) -> String {
+    let scope = path.flat("/");
+    let name = match scope.split_once('/') {
+        Some((_top, rest)) => format!("{}/{}", rest, name),
+        None => name.to_owned(),
+    };
+    if bits == 1 {
+        name
+    } else {
+        format!("{}{}", name, bus)
+    }
+}
+
+pub mod apicula;
 pub mod ecp5;
 pub mod icestorm;
 pub mod ise;
+pub mod machxo2;
+pub mod multiboot;
 pub mod vivado;
+
+#[cfg(test)]
+mod tests {
+    use rust_hdl_lib_core::prelude::*;
+    use rust_hdl_lib_widgets::prelude::*;
+
+    #[derive(LogicBlock, Default)]
+    struct TimingExceptions {
+        clock: Signal<In, Clock>,
+        sig_in: Signal<In, Bits<4>>,
+        sig_out: Signal<Out, Bits<4>>,
+        slow: DFF<Bits<4>>,
+        flag: DFF<Bit>,
+    }
+
+    impl Logic for TimingExceptions {
+        #[hdl_gen]
+        fn update(&mut self) {
+            dff_setup!(self, clock, slow, flag);
+            self.slow.d.next = self.sig_in.val();
+            self.flag.d.next = self.slow.q.val().any();
+            self.sig_out.next = self.slow.q.val();
+        }
+    }
+
+    fn make_uut() -> TimingExceptions {
+        let mut uut = TimingExceptions::default();
+        uut.clock.add_constraint(PinConstraint {
+            index: 0,
+            constraint: Constraint::Timing(Timing::Periodic(PeriodicTiming {
+                net: "sys_clk".into(),
+                period_nanoseconds: 10.0,
+                duty_cycle: 50.0,
+            })),
+        });
+        uut.slow.q.add_multicycle_path("sys_clk", 3);
+        uut.flag.d.add_false_path();
+        uut.connect_all();
+        uut
+    }
+
+    #[test]
+    fn test_timing_exceptions_are_emitted() {
+        let uut = make_uut();
+        let xdc = super::vivado::generate_xdc(&uut);
+        assert!(xdc.contains("set_multicycle_path -setup 3 -through [get_nets { slow/q[*] }]"));
+        assert!(xdc.contains("set_multicycle_path -hold 2 -through [get_nets { slow/q[*] }]"));
+        assert!(xdc.contains("set_false_path -through [get_nets { flag/d }]"));
+        let ucf = super::ise::generate_ucf(&uut);
+        assert!(ucf.contains(
+            "NET \"slow/q<*>\" TPTHRU = TP_slow_q; TIMESPEC TS_TP_slow_q = FROM FFS THRU TP_slow_q TO FFS TS_sys_clk * 3"
+        ));
+        assert!(ucf.contains("NET \"flag/d\" TIG"));
+        let lpf = super::ecp5::generate_lpf(&uut);
+        assert!(lpf.contains("MULTICYCLE NET \"slow/q[*]\" 3 X"));
+        assert!(lpf.contains("BLOCK NET \"flag/d\""));
+    }
+}
@@ -0,0 +1,124 @@
+// Flash images for designs that can reboot into another bitstream.  This
+// allows a deployed design to be updated in the field - a known good
+// (golden) image stays in the flash, and the host only ever rewrites the
+// other images, so a failed or interrupted update cannot brick the board.
+
+// Images in the flash are aligned to the size of an erase block, so that
+// each one can be rewritten without touching the others.
+pub const FLASH_SECTOR_SIZE: usize = 0x1_0000;
+
+const ICE40_HEADER_SIZE: usize = 32;
+const ICE40_HEADER_COUNT: usize = 5;
+
+fn align_to_sector(offset: usize) -> usize {
+    offset.div_ceil(FLASH_SECTOR_SIZE) * FLASH_SECTOR_SIZE
+}
+
+fn ice40_header(offset: usize, cold_boot: bool) -> Vec<u8> {
+    let mut header = vec![
+        // Preamble
+        0x7E,
+        0xAA,
+        0x99,
+        0x7E,
+        // Boot mode
+        0x92,
+        0x00,
+        if cold_boot { 0x10 } else { 0x00 },
+        // Boot address
+        0x44,
+        0x03,
+        (offset >> 16) as u8,
+        (offset >> 8) as u8,
+        offset as u8,
+        // Bank offset
+        0x82,
+        0x00,
+        0x00,
+        // Reboot
+        0x01,
+        0x08,
+    ];
+    header.resize(ICE40_HEADER_SIZE, 0);
+    header
+}
+
+// Combine up to 4 iCE40 bitstreams into a single flash image (like `icemulti`
+// does).  The image starts with a table of headers - the first one is used
+// at power on, and boots the image numbered `power_on`.  The others are used
+// by the warm boot primitive (see [crate::lattice::ice40::warmboot::ICE40WarmBoot]),
+// and boot the image with the same number.  If there are less than 4 images,
+// the unused entries boot the first image.  With `cold_boot` set, the
+// state of the CBSEL pins selects the image at power on instead.
+pub fn ice40_multiboot_image(images: &[Vec<u8>], power_on: usize, cold_boot: bool) -> Vec<u8> {
+    assert!(!images.is_empty() && images.len() <= 4);
+    assert!(power_on < images.len());
+    let mut offsets = vec![];
+    let mut offset = align_to_sector(ICE40_HEADER_SIZE * ICE40_HEADER_COUNT);
+    for image in images {
+        offsets.push(offset);
+        offset = align_to_sector(offset + image.len());
+    }
+    let mut flash = ice40_header(offsets[power_on], cold_boot);
+    for i in 0..4 {
+        flash.extend(ice40_header(*offsets.get(i).unwrap_or(&offsets[0]), false));
+    }
+    for (image, offset) in images.iter().zip(offsets) {
+        flash.resize(offset, 0xFF);
+        flash.extend(image);
+    }
+    flash
+}
+
+// The layout of a dual boot flash for the ECP5.  The golden image is at
+// the start of the flash, and is packed (by `ecppack`) with the address
+// of the primary image as its boot address.  At power on (or when PROGRAMN
+// is pulsed) the ECP5 follows that address and loads the primary image.  If
+// the primary image is missing or corrupt, it falls back to the golden image.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ECP5DualBootLayout {
+    pub primary_offset: usize,
+}
+
+impl ECP5DualBootLayout {
+    pub fn new(primary_offset: usize) -> Self {
+        assert_eq!(primary_offset % FLASH_SECTOR_SIZE, 0);
+        assert!(primary_offset > 0 && primary_offset < (1 << 24));
+        Self { primary_offset }
+    }
+    // The extra arguments for `ecppack` when packing the golden image
+    pub fn golden_ecppack_args(&self) -> Vec<String> {
+        vec![
+            "--bootaddr".to_string(),
+            format!("0x{:06X}", self.primary_offset),
+        ]
+    }
+    // Place the golden and primary bitstreams into a single flash image
+    pub fn flash_image(&self, golden: &[u8], primary: &[u8]) -> Vec<u8> {
+        assert!(
+            golden.len() <= self.primary_offset,
+            "The golden image ({} bytes) does not fit below the primary image at 0x{:06X}",
+            golden.len(),
+            self.primary_offset
+        );
+        let mut flash = golden.to_vec();
+        flash.resize(self.primary_offset, 0xFF);
+        flash.extend(primary);
+        flash
+    }
+}
+
+#[test]
+fn test_ice40_multiboot_layout() {
+    let images = vec![vec![0x11; 100], vec![0x22; FLASH_SECTOR_SIZE + 1]];
+    let flash = ice40_multiboot_image(&images, 1, false);
+    // Image 0 is in the first sector after the headers, image 1 needs two sectors
+    assert_eq!(flash.len(), 3 * FLASH_SECTOR_SIZE + 1);
+    assert_eq!(flash[FLASH_SECTOR_SIZE], 0x11);
+    assert_eq!(flash[2 * FLASH_SECTOR_SIZE], 0x22);
+    // The power on header boots image 1, unused headers boot image 0
+    assert_eq!(flash[9..12], [0x02, 0x00, 0x00]);
+    assert_eq!(flash[32 + 9..32 + 12], [0x01, 0x00, 0x00]);
+    assert_eq!(flash[64 + 9..64 + 12], [0x02, 0x00, 0x00]);
+    assert_eq!(flash[96 + 9..96 + 12], [0x01, 0x00, 0x00]);
+}
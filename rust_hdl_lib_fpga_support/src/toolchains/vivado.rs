@@ -1,4 +1,4 @@
-use crate::toolchains::map_signal_type_to_xilinx_string;
+use crate::toolchains::{hierarchical_net_name, map_signal_type_to_xilinx_string};
 use rust_hdl_lib_core::prelude::*;
 
 #[derive(Default)]
@@ -89,6 +89,22 @@ set_multicycle_path -setup -from [get_ports {{ {prefix} }}] {cycles}",
                                     .join(" ")
                             )
                         }
+                        Timing::FalsePath => {
+                            format!(
+                                "set_false_path -through [get_nets {{ {net} }}]",
+                                net =
+                                    hierarchical_net_name(&self.path, &name, signal.bits(), "[*]")
+                            )
+                        }
+                        Timing::Multicycle(m) => {
+                            format!(
+                                "set_multicycle_path -setup {cycles} -through [get_nets {{ {net} }}]
+set_multicycle_path -hold {hold} -through [get_nets {{ {net} }}]",
+                                cycles = m.cycles,
+                                hold = m.cycles - 1,
+                                net = hierarchical_net_name(&self.path, &name, signal.bits(), "[*]")
+                            )
+                        }
                         VivadoFalsePath(p) => {
                             format!(
                                 "set_false_path -from [get_pins -hierarchical -regexp {from}] -to [get_pins -hierarchical -regexp {to}]",
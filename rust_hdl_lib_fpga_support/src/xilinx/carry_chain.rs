@@ -0,0 +1,82 @@
+use rust_hdl_lib_core::prelude::*;
+
+// A tapped delay line for a [FineTDC], built on a chain of CARRY4 cells of
+// the Xilinx 7 series (four taps per cell).  Every select input is set, so a
+// rising edge on `hit` ripples up the chain, and the carry outputs (the taps)
+// are sampled by `clock`.  The chain must be placed in a single column to be
+// of any use, and it should be longer than a clock (each tap is roughly
+// 10-20 ps).  In simulation, the line has no delay at all, so the taps are
+// all set or all clear.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct XilinxCarryChainDelayLine<const T: usize> {
+    pub clock: Signal<In, Clock>,
+    pub hit: Signal<In, Bit>,
+    pub taps: Signal<Out, Bits<T>>,
+}
+
+impl<const T: usize> Logic for XilinxCarryChainDelayLine<T> {
+    fn update(&mut self) {
+        assert_eq!(T % 4, 0);
+        if self.clock.pos_edge() {
+            self.taps.next = if self.hit.val() {
+                !Bits::<T>::default()
+            } else {
+                Bits::<T>::default()
+            };
+        }
+    }
+    fn connect(&mut self) {
+        self.taps.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let cells = (0..T / 4)
+            .map(|x| {
+                // The first cell takes the hit on CYINIT, the rest chain through CI
+                let (ci, cyinit) = if x == 0 {
+                    ("1'b0".to_string(), "hit".to_string())
+                } else {
+                    (format!("tdc_carry[{}]", 4 * x - 1), "1'b0".to_string())
+                };
+                format!(
+                    r##"
+(* keep *)
+CARRY4 tdc_cell_{x}(.CI({ci}), .CYINIT({cyinit}), .DI(4'b0000), .S(4'b1111),
+    .CO(tdc_carry[{hi}:{lo}]), .O());
+"##,
+                    x = x,
+                    ci = ci,
+                    cyinit = cyinit,
+                    hi = 4 * x + 3,
+                    lo = 4 * x
+                )
+            })
+            .collect::<Vec<_>>()
+            .join("\n");
+        Verilog::Wrapper(Wrapper {
+            code: format!(
+                r##"
+wire [{msb}:0] tdc_carry;
+reg [{msb}:0] tdc_taps;
+{cells_code}
+always @(posedge clock) tdc_taps <= tdc_carry;
+assign taps = tdc_taps;
+"##,
+                msb = T - 1,
+                cells_code = cells
+            ),
+            cores: r##"
+(* blackbox *)
+module CARRY4(input CI, input CYINIT, input [3:0] DI, input [3:0] S, output [3:0] CO, output [3:0] O);
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_xilinx_carry_chain_synthesizes() {
+    let mut uut = XilinxCarryChainDelayLine::<64>::default();
+    uut.connect_all();
+    yosys_validate("xilinx_carry_chain", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,44 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A glitch-free clock gate built on the BUFGCE of the Xilinx 7 series,
+// which also drives the output onto a global clock net.  The ports match
+// those of the fabric [ClockGate], so the two can be swapped freely.  The
+// clock is stopped (low) while `enable` is low.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct XilinxClockGate {
+    pub clock: Signal<In, Clock>,
+    pub enable: Signal<In, Bit>,
+    pub clock_out: Signal<Out, Clock>,
+    _model: ClockGateModel,
+}
+
+impl Logic for XilinxClockGate {
+    fn update(&mut self) {
+        self.clock_out.next = self._model.update(&self.clock, self.enable.val());
+    }
+    fn connect(&mut self) {
+        self.clock_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r##"
+BUFGCE bufgce_inst(.I(clock), .CE(enable), .O(clock_out));
+            "##
+            .into(),
+            cores: r##"
+(* blackbox *)
+module BUFGCE(input I, input CE, output O);
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_xilinx_clock_gate_synthesizes() {
+    let mut uut = XilinxClockGate::default();
+    uut.connect_all();
+    yosys_validate("xilinx_clock_gate", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,48 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A glitch-free clock multiplexer built on the BUFGMUX of the Xilinx 7
+// series, which also drives the output onto a global clock net.  The ports
+// match those of the fabric [ClockMux], so the two can be swapped freely.
+// `select` picks `clock_1` when high.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct XilinxClockMux {
+    pub clock_0: Signal<In, Clock>,
+    pub clock_1: Signal<In, Clock>,
+    pub select: Signal<In, Bit>,
+    pub clock_out: Signal<Out, Clock>,
+    _model: ClockMuxModel,
+}
+
+impl Logic for XilinxClockMux {
+    fn update(&mut self) {
+        self.clock_out.next = self
+            ._model
+            .update(&self.clock_0, &self.clock_1, self.select.val());
+    }
+    fn connect(&mut self) {
+        self.clock_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: r##"
+BUFGMUX #(.CLK_SEL_TYPE("SYNC")) bufgmux_inst(.I0(clock_0), .I1(clock_1), .S(select), .O(clock_out));
+            "##
+            .into(),
+            cores: r##"
+(* blackbox *)
+module BUFGMUX(input I0, input I1, input S, output O);
+parameter CLK_SEL_TYPE = "SYNC";
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[test]
+fn test_xilinx_clock_mux_synthesizes() {
+    let mut uut = XilinxClockMux::default();
+    uut.connect_all();
+    yosys_validate("xilinx_clock_mux", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,4 @@
+pub mod carry_chain;
+pub mod clock_gate;
+pub mod clock_mux;
+pub mod xadc;
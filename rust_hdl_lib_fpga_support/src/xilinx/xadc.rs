@@ -0,0 +1,290 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// The DRP addresses of the status registers of the XADC
+const XADC_TEMPERATURE: u64 = 0x00;
+const XADC_VCCINT: u64 = 0x01;
+const XADC_VCCAUX: u64 = 0x02;
+// The simulation model ends a sequence this often (in clock cycles)
+const XADC_SIM_SEQUENCE_CYCLES: u32 = 100;
+
+// Convert a reading into the 16 bit code that the XADC reports
+// (the 12 bit result of the conversion is in the upper bits).
+fn xadc_code(value: f64) -> u16 {
+    ((value.clamp(0.0, 1.0) * 4095.0).round() as u16) << 4
+}
+
+// The XADC of the Xilinx 7 series, set up to measure the die temperature,
+// VCCINT and VCCAUX over and over again (continuous sequence mode).  The
+// results are read through the dynamic reconfiguration port (DRP).  `eos`
+// fires at the end of each sequence, when a new set of results is ready.
+// The simulation model ends a sequence every 100 clock cycles, and answers
+// reads of the status registers with the readings given to [XADC::set_simulated_readings].
+#[derive(LogicBlock)]
+pub struct XADC {
+    pub dclk: Signal<In, Clock>,
+    pub den: Signal<In, Bit>,
+    pub daddr: Signal<In, Bits<7>>,
+    pub data_out: Signal<Out, Bits<16>>,
+    pub drdy: Signal<Out, Bit>,
+    pub eos: Signal<Out, Bit>,
+    _clock_divider: u64,
+    _sim_codes: [u16; 3],
+    _sim_cycles: u32,
+    _sim_pending: Option<u16>,
+}
+
+impl XADC {
+    // The ADC clock is derived from the DRP clock, and must be no more than 26 MHz
+    pub fn new(dclk_frequency: u64) -> Self {
+        let divider = dclk_frequency.div_ceil(26_000_000).max(2);
+        assert!(divider < 256);
+        let mut ret = Self {
+            dclk: Default::default(),
+            den: Default::default(),
+            daddr: Default::default(),
+            data_out: Default::default(),
+            drdy: Default::default(),
+            eos: Default::default(),
+            _clock_divider: divider,
+            _sim_codes: [0; 3],
+            _sim_cycles: 0,
+            _sim_pending: None,
+        };
+        ret.set_simulated_readings(25.0, 1.0, 1.8);
+        ret
+    }
+    // Set the temperature (in C) and the supply voltages (in V) reported in simulation
+    pub fn set_simulated_readings(&mut self, temperature: f64, vccint: f64, vccaux: f64) {
+        self._sim_codes = [
+            xadc_code((temperature + 273.15) / 503.975),
+            xadc_code(vccint / 3.0),
+            xadc_code(vccaux / 3.0),
+        ];
+    }
+}
+
+impl Logic for XADC {
+    fn update(&mut self) {
+        if self.dclk.pos_edge() {
+            self.drdy.next = false;
+            self.eos.next = false;
+            if let Some(code) = self._sim_pending.take() {
+                self.data_out.next = (code as u64).into();
+                self.drdy.next = true;
+            }
+            if self.den.val() {
+                self._sim_pending = Some(
+                    self._sim_codes
+                        .get(self.daddr.val().index())
+                        .copied()
+                        .unwrap_or_default(),
+                );
+            }
+            self._sim_cycles += 1;
+            if self._sim_cycles == XADC_SIM_SEQUENCE_CYCLES {
+                self._sim_cycles = 0;
+                self.eos.next = true;
+            }
+        }
+    }
+    fn connect(&mut self) {
+        self.data_out.connect();
+        self.drdy.connect();
+        self.eos.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Wrapper(Wrapper {
+            code: format!(
+                r##"
+XADC #(
+    .INIT_40(16'h0000),
+    .INIT_41(16'h2FFF),
+    .INIT_42(16'h{divider:02X}00),
+    .INIT_48(16'h0701),
+    .INIT_49(16'h0000)
+) inst_XADC (
+    .DCLK(dclk),
+    .DEN(den),
+    .DADDR(daddr),
+    .DWE(1'b0),
+    .DI(16'h0000),
+    .DO(data_out),
+    .DRDY(drdy),
+    .EOS(eos),
+    .RESET(1'b0),
+    .CONVST(1'b0),
+    .CONVSTCLK(1'b0),
+    .VP(1'b0),
+    .VN(1'b0),
+    .VAUXP(16'h0000),
+    .VAUXN(16'h0000),
+    .ALM(),
+    .OT(),
+    .BUSY(),
+    .CHANNEL(),
+    .EOC(),
+    .JTAGBUSY(),
+    .JTAGLOCKED(),
+    .JTAGMODIFIED(),
+    .MUXADDR()
+);
+"##,
+                divider = self._clock_divider
+            ),
+            cores: r##"
+(* blackbox *)
+module XADC(input DCLK, input DEN, input [6:0] DADDR, input DWE, input [15:0] DI,
+    output [15:0] DO, output DRDY, output EOS, input RESET, input CONVST, input CONVSTCLK,
+    input VP, input VN, input [15:0] VAUXP, input [15:0] VAUXN, output [7:0] ALM, output OT,
+    output BUSY, output [4:0] CHANNEL, output EOC, output JTAGBUSY, output JTAGLOCKED,
+    output JTAGMODIFIED, output [4:0] MUXADDR);
+parameter INIT_40 = 16'h0000;
+parameter INIT_41 = 16'h0000;
+parameter INIT_42 = 16'h0800;
+parameter INIT_48 = 16'h0000;
+parameter INIT_49 = 16'h0000;
+endmodule
+            "##
+            .into(),
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, LogicState)]
+enum XADCMonitorState {
+    Idle,
+    ReadTemperature,
+    ReadVccInt,
+    ReadVccAux,
+}
+
+// Reads the temperature and supply voltages from the XADC at the end of
+// each sequence, and presents them as a [SystemMonitor].
+#[derive(LogicBlock)]
+pub struct XADCMonitor {
+    pub clock: Signal<In, Clock>,
+    pub monitor: SystemMonitor,
+    pub xadc: XADC,
+    state: DFF<XADCMonitorState>,
+    temperature: DFF<Bits<16>>,
+    vccint: DFF<Bits<16>>,
+    vccaux: DFF<Bits<16>>,
+    valid: DFF<Bit>,
+    code: Signal<Local, Bits<16>>,
+    // (code * temperature_scale) >> 14 is the temperature in 1/16 K
+    temperature_scale: Constant<Bits<16>>,
+    // (code * voltage_scale) >> 16 is the voltage in mV
+    voltage_scale: Constant<Bits<16>>,
+    addr_temperature: Constant<Bits<7>>,
+    addr_vccint: Constant<Bits<7>>,
+    addr_vccaux: Constant<Bits<7>>,
+}
+
+impl XADCMonitor {
+    pub fn new(frequency: u64) -> Self {
+        Self {
+            clock: Default::default(),
+            monitor: Default::default(),
+            xadc: XADC::new(frequency),
+            state: Default::default(),
+            temperature: Default::default(),
+            vccint: Default::default(),
+            vccaux: Default::default(),
+            valid: Default::default(),
+            code: Default::default(),
+            // 503.975 K full scale, in 1/16 K, over 4096 codes
+            temperature_scale: Constant::new(32251.into()),
+            // 3000 mV full scale over 4096 codes
+            voltage_scale: Constant::new(48000.into()),
+            addr_temperature: Constant::new(XADC_TEMPERATURE.into()),
+            addr_vccint: Constant::new(XADC_VCCINT.into()),
+            addr_vccaux: Constant::new(XADC_VCCAUX.into()),
+        }
+    }
+}
+
+impl Logic for XADCMonitor {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.xadc.dclk.next = self.clock.val();
+        dff_setup!(self, clock, state, temperature, vccint, vccaux, valid);
+        self.xadc.den.next = false;
+        self.xadc.daddr.next = self.addr_temperature.val();
+        self.valid.d.next = false;
+        // The 12 bit result of the conversion, in the lower bits
+        self.code.next = self.xadc.data_out.val() >> 4;
+        match self.state.q.val() {
+            XADCMonitorState::Idle => {
+                if self.xadc.eos.val() {
+                    self.xadc.den.next = true;
+                    self.xadc.daddr.next = self.addr_temperature.val();
+                    self.state.d.next = XADCMonitorState::ReadTemperature;
+                }
+            }
+            XADCMonitorState::ReadTemperature => {
+                if self.xadc.drdy.val() {
+                    self.temperature.d.next =
+                        (self.code.val() * self.temperature_scale.val()).get_bits::<16>(14);
+                    self.xadc.den.next = true;
+                    self.xadc.daddr.next = self.addr_vccint.val();
+                    self.state.d.next = XADCMonitorState::ReadVccInt;
+                }
+            }
+            XADCMonitorState::ReadVccInt => {
+                if self.xadc.drdy.val() {
+                    self.vccint.d.next =
+                        (self.code.val() * self.voltage_scale.val()).get_bits::<16>(16);
+                    self.xadc.den.next = true;
+                    self.xadc.daddr.next = self.addr_vccaux.val();
+                    self.state.d.next = XADCMonitorState::ReadVccAux;
+                }
+            }
+            XADCMonitorState::ReadVccAux => {
+                if self.xadc.drdy.val() {
+                    self.vccaux.d.next =
+                        (self.code.val() * self.voltage_scale.val()).get_bits::<16>(16);
+                    self.valid.d.next = true;
+                    self.state.d.next = XADCMonitorState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = XADCMonitorState::Idle;
+            }
+        }
+        self.monitor.temperature.next = self.temperature.q.val();
+        self.monitor.vccint.next = self.vccint.q.val();
+        self.monitor.vccaux.next = self.vccaux.q.val();
+        self.monitor.valid.next = self.valid.q.val();
+    }
+}
+
+#[test]
+fn test_xadc_monitor_synthesizes() {
+    let mut uut = XADCMonitor::new(100_000_000);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    assert!(vlog.contains(".INIT_42(16'h0400)"));
+    yosys_validate("xadc_monitor", &vlog).unwrap();
+}
+
+#[test]
+fn test_xadc_monitor_reports_readings() {
+    let mut uut = XADCMonitor::new(100_000_000);
+    uut.xadc.set_simulated_readings(45.0, 0.95, 1.8);
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<XADCMonitor>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<XADCMonitor>| {
+        let mut x = sim.init()?;
+        x = sim.watch(|x| x.monitor.valid.val(), x)?;
+        let celsius = temperature_celsius(x.monitor.temperature.val().index() as u16);
+        sim_assert!(sim, (celsius - 45.0).abs() < 0.25, x);
+        let vccint = x.monitor.vccint.val().index() as i64;
+        let vccaux = x.monitor.vccaux.val().index() as i64;
+        sim_assert!(sim, (vccint - 950).abs() <= 1, x);
+        sim_assert!(sim, (vccaux - 1800).abs() <= 1, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
@@ -0,0 +1,132 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A board bring-up block, for checking the pins of a new board port.  The
+// `outputs` are driven with a walking one - exactly one of them is high at
+// a time.  Out of configuration, the one steps through the outputs on its
+// own (at `step_hz`), so that LEDs and scope probes show the pin order with
+// no host attached.  The first write to the walk register hands the walk over
+// to the host, which can then drive each output in turn, and sample each of
+// the `inputs` (synchronized to the bus clock) along the way.  The host side
+// of the test is in the bringup module of the host runtime.
+//
+// HLS ports
+// 0 - walk (write only) - the output to drive high (outputs past the last one clear them all)
+// 1 - probe (write only) - the input to sample
+// 2 - sample (read only) - bit 0 is the current value of the probed input
+#[derive(LogicBlock)]
+pub struct HLSBringUp<const D: usize, const A: usize, const O: usize, const I: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub outputs: Signal<Out, Bits<O>>,
+    pub inputs: Signal<In, Bits<I>>,
+    bridge: Bridge<D, A, 3>,
+    walk_reg: MOSIPort<D>,
+    probe_reg: MOSIPort<D>,
+    sample_reg: MISOPort<D>,
+    step: Strobe<32>,
+    index: DFF<Bits<D>>,
+    host: DFF<Bit>,
+    probe: DFF<Bits<D>>,
+    sync_0: DFF<Bits<I>>,
+    sync_1: DFF<Bits<I>>,
+    sample: Signal<Local, Bit>,
+    one: Constant<Bits<O>>,
+    output_count: Constant<Bits<D>>,
+    input_count: Constant<Bits<D>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize, const O: usize, const I: usize> HLSNamedPorts
+    for HLSBringUp<D, A, O, I>
+{
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize, const O: usize, const I: usize> HLSBringUp<D, A, O, I> {
+    pub fn new(frequency: u64, step_hz: f64) -> Self {
+        assert!(D < 64);
+        assert!((O as u64) < (1 << D));
+        assert!((I as u64) < (1 << D));
+        Self {
+            upstream: Default::default(),
+            outputs: Default::default(),
+            inputs: Default::default(),
+            bridge: Bridge::new(["walk", "probe", "sample"]),
+            walk_reg: Default::default(),
+            probe_reg: Default::default(),
+            sample_reg: Default::default(),
+            step: Strobe::new(frequency, step_hz),
+            index: Default::default(),
+            host: Default::default(),
+            probe: Default::default(),
+            sync_0: Default::default(),
+            sync_1: Default::default(),
+            sample: Default::default(),
+            one: Constant::new(1.into()),
+            output_count: Constant::new(O.to_bits()),
+            input_count: Constant::new(I.to_bits()),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize, const O: usize, const I: usize> Logic
+    for HLSBringUp<D, A, O, I>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.walk_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.probe_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.sample_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        clock!(self, clock, step);
+        dff_setup!(self, clock, index, host, probe, sync_0, sync_1);
+        self.walk_reg.ready.next = true;
+        self.probe_reg.ready.next = true;
+        self.sample_reg.ready_in.next = true;
+        // Step through the outputs on our own, until the host takes over
+        self.step.enable.next = !self.host.q.val();
+        if self.step.strobe.val() {
+            self.index.d.next = self.index.q.val() + 1;
+            if self.index.q.val() + 1 >= self.output_count.val() {
+                self.index.d.next = 0.into();
+            }
+        }
+        if self.walk_reg.strobe_out.val() {
+            self.index.d.next = self.walk_reg.port_out.val();
+            self.host.d.next = true;
+        }
+        self.outputs.next = 0.into();
+        if self.index.q.val() < self.output_count.val() {
+            self.outputs.next = self.one.val() << self.index.q.val();
+        }
+        // Sample the probed input
+        self.sync_0.d.next = self.inputs.val();
+        self.sync_1.d.next = self.sync_0.q.val();
+        if self.probe_reg.strobe_out.val() {
+            self.probe.d.next = self.probe_reg.port_out.val();
+        }
+        self.sample.next = false;
+        if self.probe.q.val() < self.input_count.val() {
+            self.sample.next = self.sync_1.q.val().get_bit(self.probe.q.val().index());
+        }
+        self.sample_reg.port_in.next = bit_cast::<D, 1>(self.sample.val().into());
+    }
+}
+
+#[test]
+fn test_hls_bringup_is_synthesizable() {
+    let mut uut = HLSBringUp::<16, 8, 12, 5>::new(100_000_000, 4.0);
+    uut.upstream.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_bringup", &vlog).unwrap();
+}
@@ -0,0 +1,541 @@
+use crate::bus::{
+    FIFOReadController, FIFOReadResponder, FIFOWriteController, FIFOWriteResponder,
+    SoCBusController, SoCBusResponder, SoCPortController, SoCPortResponder,
+};
+use rust_hdl_lib_core::prelude::*;
+
+// Passive protocol checkers for the buses of the HLS library.  Each one
+// checks the handshake that was registered at each rising edge of the bus
+// clock, and reports the first broken rule along with the number of the
+// clock cycle it happened in (the rising edges of the clock are counted
+// from the start of the simulation, so the first one is cycle 1).
+//
+// SoCBusMonitor checks the SoC bus:
+//   - strobe may only be asserted while the responder signals ready
+//   - strobe and address_strobe may not be asserted in the same cycle
+//   - strobe may not be asserted before any address has been selected
+// Attach it with
+//   sim.add_monitor(SoCBusMonitor::default(), |x: &T| (&x.bus).into());
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SoCBusSample {
+    pub clock: bool,
+    pub address: usize,
+    pub address_strobe: bool,
+    pub strobe: bool,
+    pub ready: bool,
+}
+
+impl<const D: usize, const A: usize> From<&SoCBusController<D, A>> for SoCBusSample {
+    fn from(x: &SoCBusController<D, A>) -> Self {
+        Self {
+            clock: x.clock.val().clk,
+            address: x.address.val().index(),
+            address_strobe: x.address_strobe.val(),
+            strobe: x.strobe.val(),
+            ready: x.ready.val(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize> From<&SoCBusResponder<D, A>> for SoCBusSample {
+    fn from(x: &SoCBusResponder<D, A>) -> Self {
+        Self {
+            clock: x.clock.val().clk,
+            address: x.address.val().index(),
+            address_strobe: x.address_strobe.val(),
+            strobe: x.strobe.val(),
+            ready: x.ready.val(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SoCBusMonitor {
+    edges: EdgeTracker<SoCBusSample>,
+    address: Option<usize>,
+}
+
+impl Monitor for SoCBusMonitor {
+    type Sample = SoCBusSample;
+
+    fn name(&self) -> String {
+        "SoCBus".into()
+    }
+
+    fn check(&mut self, sample: SoCBusSample, _time: u64) -> Result<(), String> {
+        let cycle = match self.edges.update(&sample, |x| x.clock) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let count = self.edges.cycles();
+        if cycle.strobe && cycle.address_strobe {
+            return Err(format!(
+                "cycle {}: strobe and address_strobe asserted together (address {})",
+                count, cycle.address
+            ));
+        }
+        if cycle.address_strobe {
+            self.address = Some(cycle.address);
+        }
+        if cycle.strobe {
+            match self.address {
+                None => {
+                    return Err(format!(
+                        "cycle {}: strobe asserted before any address was selected",
+                        count
+                    ))
+                }
+                Some(address) if !cycle.ready => {
+                    return Err(format!(
+                        "cycle {}: strobe asserted while address {} was not ready",
+                        count, address
+                    ))
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+// SoCPortMonitor checks a single port of the SoC bus (i.e., the bus as a
+// port sees it, after the address has been decoded):
+//   - strobe may only be asserted while the port is selected
+//   - strobe may only be asserted while the port signals ready
+// Attach it with
+//   sim.add_monitor(SoCPortMonitor::default(), |x: &T| (&x.port).into());
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SoCPortSample {
+    pub clock: bool,
+    pub select: bool,
+    pub strobe: bool,
+    pub ready: bool,
+}
+
+impl<const D: usize> From<&SoCPortController<D>> for SoCPortSample {
+    fn from(x: &SoCPortController<D>) -> Self {
+        Self {
+            clock: x.clock.val().clk,
+            select: x.select.val(),
+            strobe: x.strobe.val(),
+            ready: x.ready.val(),
+        }
+    }
+}
+
+impl<const D: usize> From<&SoCPortResponder<D>> for SoCPortSample {
+    fn from(x: &SoCPortResponder<D>) -> Self {
+        Self {
+            clock: x.clock.val().clk,
+            select: x.select.val(),
+            strobe: x.strobe.val(),
+            ready: x.ready.val(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SoCPortMonitor {
+    edges: EdgeTracker<SoCPortSample>,
+}
+
+impl Monitor for SoCPortMonitor {
+    type Sample = SoCPortSample;
+
+    fn name(&self) -> String {
+        "SoCPort".into()
+    }
+
+    fn check(&mut self, sample: SoCPortSample, _time: u64) -> Result<(), String> {
+        let cycle = match self.edges.update(&sample, |x| x.clock) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let count = self.edges.cycles();
+        if cycle.strobe && !cycle.select {
+            return Err(format!(
+                "cycle {}: strobe asserted while the port was not selected",
+                count
+            ));
+        }
+        if cycle.strobe && !cycle.ready {
+            return Err(format!(
+                "cycle {}: strobe asserted while the port was not ready",
+                count
+            ));
+        }
+        Ok(())
+    }
+}
+
+// FIFOBusMonitor checks either side of a FIFO bus:
+//   - write may not be asserted while the FIFO is full
+//   - read may not be asserted while the FIFO is empty
+// Either would be ignored by the FIFO, and the data silently lost (or
+// made up).  The FIFO buses do not carry a clock, so it is passed along
+// with the bus:
+//   sim.add_monitor(FIFOBusMonitor::default(), |x: &T| (x.clock.val(), &x.fifo.bus_write).into());
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FIFOBusSample {
+    pub clock: bool,
+    pub write: bool,
+    pub full: bool,
+    pub read: bool,
+    pub empty: bool,
+}
+
+impl<T: Synth> From<(Clock, &FIFOWriteController<T>)> for FIFOBusSample {
+    fn from(x: (Clock, &FIFOWriteController<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            write: x.1.write.val(),
+            full: x.1.full.val(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Synth> From<(Clock, &FIFOWriteResponder<T>)> for FIFOBusSample {
+    fn from(x: (Clock, &FIFOWriteResponder<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            write: x.1.write.val(),
+            full: x.1.full.val(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Synth> From<(Clock, &FIFOReadController<T>)> for FIFOBusSample {
+    fn from(x: (Clock, &FIFOReadController<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            read: x.1.read.val(),
+            empty: x.1.empty.val(),
+            ..Default::default()
+        }
+    }
+}
+
+impl<T: Synth> From<(Clock, &FIFOReadResponder<T>)> for FIFOBusSample {
+    fn from(x: (Clock, &FIFOReadResponder<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            read: x.1.read.val(),
+            empty: x.1.empty.val(),
+            ..Default::default()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct FIFOBusMonitor {
+    edges: EdgeTracker<FIFOBusSample>,
+}
+
+impl Monitor for FIFOBusMonitor {
+    type Sample = FIFOBusSample;
+
+    fn name(&self) -> String {
+        "FIFOBus".into()
+    }
+
+    fn check(&mut self, sample: FIFOBusSample, _time: u64) -> Result<(), String> {
+        let cycle = match self.edges.update(&sample, |x| x.clock) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let count = self.edges.cycles();
+        if cycle.write && cycle.full {
+            return Err(format!("cycle {}: write to a full FIFO", count));
+        }
+        if cycle.read && cycle.empty {
+            return Err(format!("cycle {}: read from an empty FIFO", count));
+        }
+        Ok(())
+    }
+}
+
+// HandshakeMonitor checks a generic valid/ready channel, like the channels
+// of AXI (and AXI stream), or an Avalon streaming interface.  There are no
+// such buses in the HLS library, so the sample is filled in by hand:
+//   sim.add_monitor(HandshakeMonitor::new("AXI write address"), |x: &T| HandshakeSample {
+//       clock: x.clock.val().clk,
+//       valid: x.awvalid.val(),
+//       ready: x.awready.val(),
+//       data: x.awaddr.val().index() as u128,
+//   });
+// The data may pack together as many of the channel signals as fit.  It
+// flags
+//   - valid dropped before the transfer took place (valid && ready)
+//   - data changed while valid was asserted and ready was not
+// A ready that waits for valid (or not) is allowed either way.
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct HandshakeSample {
+    pub clock: bool,
+    pub valid: bool,
+    pub ready: bool,
+    pub data: u128,
+}
+
+pub struct HandshakeMonitor {
+    name: String,
+    edges: EdgeTracker<HandshakeSample>,
+    pending: Option<u128>,
+}
+
+impl HandshakeMonitor {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.into(),
+            edges: Default::default(),
+            pending: None,
+        }
+    }
+}
+
+impl Monitor for HandshakeMonitor {
+    type Sample = HandshakeSample;
+
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn check(&mut self, sample: HandshakeSample, _time: u64) -> Result<(), String> {
+        let cycle = match self.edges.update(&sample, |x| x.clock) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let count = self.edges.cycles();
+        if let Some(data) = self.pending {
+            if !cycle.valid {
+                return Err(format!(
+                    "cycle {}: valid dropped before the transfer of {:x} took place",
+                    count, data
+                ));
+            }
+            if cycle.data != data {
+                return Err(format!(
+                    "cycle {}: data changed from {:x} to {:x} while waiting for ready",
+                    count, data, cycle.data
+                ));
+            }
+        }
+        self.pending = if cycle.valid && !cycle.ready {
+            Some(cycle.data)
+        } else {
+            None
+        };
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[derive(LogicBlock, Default)]
+struct SoCBusMonitorTest {
+    bus: SoCBusResponder<16, 4>,
+}
+
+#[cfg(test)]
+impl Logic for SoCBusMonitorTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.bus.to_controller.next = 0.into();
+        self.bus.ready.next = self.bus.address.val() == 3;
+    }
+}
+
+#[cfg(test)]
+fn run_soc_bus_monitor(address: u32, strobe_with_address: bool) -> Result<(), SimError> {
+    let mut uut = SoCBusMonitorTest::default();
+    uut.bus.address.connect();
+    uut.bus.address_strobe.connect();
+    uut.bus.from_controller.connect();
+    uut.bus.strobe.connect();
+    uut.bus.clock.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SoCBusMonitorTest>| {
+        x.bus.clock.next = !x.bus.clock.val()
+    });
+    sim.add_monitor(SoCBusMonitor::default(), |x: &SoCBusMonitorTest| {
+        (&x.bus).into()
+    });
+    sim.add_testbench(move |mut sim: Sim<SoCBusMonitorTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        x.bus.address.next = address.to_bits();
+        x.bus.address_strobe.next = true;
+        x.bus.strobe.next = strobe_with_address;
+        wait_clock_cycle!(sim, bus.clock, x);
+        x.bus.address_strobe.next = false;
+        x.bus.strobe.next = true;
+        wait_clock_cycle!(sim, bus.clock, x);
+        x.bus.strobe.next = false;
+        wait_clock_cycles!(sim, bus.clock, x, 4);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000)
+}
+
+#[test]
+fn test_soc_bus_monitor() {
+    assert!(run_soc_bus_monitor(3, false).is_ok());
+    assert!(matches!(
+        run_soc_bus_monitor(2, false),
+        Err(SimError::ProtocolViolation { .. })
+    ));
+    assert!(matches!(
+        run_soc_bus_monitor(3, true),
+        Err(SimError::ProtocolViolation { .. })
+    ));
+}
+
+#[test]
+fn test_soc_bus_monitor_reports_the_cycle() {
+    match run_soc_bus_monitor(2, false) {
+        Err(SimError::ProtocolViolation { message, .. }) => {
+            assert_eq!(
+                message,
+                "cycle 6: strobe asserted while address 2 was not ready"
+            )
+        }
+        x => panic!("Expected a protocol violation, got {:?}", x),
+    }
+}
+
+#[cfg(test)]
+#[derive(LogicBlock, Default)]
+struct SoCPortMonitorTest {
+    port: SoCPortResponder<16>,
+    busy: Signal<In, Bit>,
+}
+
+#[cfg(test)]
+impl Logic for SoCPortMonitorTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.port.to_controller.next = 0.into();
+        self.port.ready.next = !self.busy.val();
+    }
+}
+
+#[cfg(test)]
+fn run_soc_port_monitor(select: bool, busy: bool) -> Result<(), SimError> {
+    let mut uut = SoCPortMonitorTest::default();
+    uut.port.select.connect();
+    uut.port.from_controller.connect();
+    uut.port.strobe.connect();
+    uut.port.clock.connect();
+    uut.busy.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SoCPortMonitorTest>| {
+        x.port.clock.next = !x.port.clock.val()
+    });
+    sim.add_monitor(SoCPortMonitor::default(), |x: &SoCPortMonitorTest| {
+        (&x.port).into()
+    });
+    sim.add_testbench(move |mut sim: Sim<SoCPortMonitorTest>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, port.clock, x, 4);
+        x.port.select.next = select;
+        x.busy.next = busy;
+        wait_clock_cycle!(sim, port.clock, x);
+        x.port.strobe.next = true;
+        wait_clock_cycle!(sim, port.clock, x);
+        x.port.strobe.next = false;
+        wait_clock_cycles!(sim, port.clock, x, 4);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000)
+}
+
+#[test]
+fn test_soc_port_monitor_reports_the_cycle() {
+    assert!(run_soc_port_monitor(true, false).is_ok());
+    match run_soc_port_monitor(false, false) {
+        Err(SimError::ProtocolViolation { message, .. }) => {
+            assert_eq!(
+                message,
+                "cycle 6: strobe asserted while the port was not selected"
+            )
+        }
+        x => panic!("Expected a protocol violation, got {:?}", x),
+    }
+    match run_soc_port_monitor(true, true) {
+        Err(SimError::ProtocolViolation { message, .. }) => {
+            assert_eq!(
+                message,
+                "cycle 6: strobe asserted while the port was not ready"
+            )
+        }
+        x => panic!("Expected a protocol violation, got {:?}", x),
+    }
+}
+
+// Feed a list of (valid, ready, data) cycles through the handshake monitor,
+// one rising edge per cycle.
+#[cfg(test)]
+fn run_handshake_monitor(cycles: &[(bool, bool, u128)]) -> Result<(), String> {
+    let mut monitor = HandshakeMonitor::new("handshake");
+    for (time, &(valid, ready, data)) in cycles.iter().enumerate() {
+        for clock in [false, true] {
+            monitor.check(
+                HandshakeSample {
+                    clock,
+                    valid,
+                    ready,
+                    data,
+                },
+                time as u64,
+            )?;
+        }
+    }
+    Ok(())
+}
+
+#[test]
+fn test_handshake_monitor() {
+    // Ready after a couple of cycles, with the data held
+    assert!(run_handshake_monitor(&[
+        (false, false, 0),
+        (true, false, 5),
+        (true, false, 5),
+        (true, true, 5),
+        (true, true, 6),
+        (false, true, 0),
+    ])
+    .is_ok());
+    assert_eq!(
+        run_handshake_monitor(&[(false, false, 0), (true, false, 5), (false, false, 5)]),
+        Err("cycle 3: valid dropped before the transfer of 5 took place".into())
+    );
+    assert_eq!(
+        run_handshake_monitor(&[(false, false, 0), (true, false, 5), (true, true, 6)]),
+        Err("cycle 3: data changed from 5 to 6 while waiting for ready".into())
+    );
+}
+
+#[test]
+fn test_fifo_bus_monitor() {
+    let mut monitor = FIFOBusMonitor::default();
+    let cycles = [(false, true), (true, false), (true, true)];
+    let mut result = Ok(());
+    for (time, &(write, full)) in cycles.iter().enumerate() {
+        for clock in [false, true] {
+            let sample = FIFOBusSample {
+                clock,
+                write,
+                full,
+                ..Default::default()
+            };
+            result = result.and_then(|_| monitor.check(sample, time as u64));
+        }
+    }
+    assert_eq!(result, Err("cycle 3: write to a full FIFO".into()));
+}
@@ -0,0 +1,278 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::miso_wide_port::MISOWidePort;
+use crate::mosi_port::MOSIPort;
+use crate::mosi_wide_port::MOSIWidePort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A scope/logic analyzer front end for the host.  The samples on `data_in`
+// (D bits each, packing D/W channels of W bits) are decimated and written
+// into the SDRAM as a ring buffer (see [SDRAMCapture]), until the level
+// trigger on the selected channel or the trigger sequencer (see
+// [TriggerSequencer]) fires.  The engine then collects the samples after the
+// trigger, and freezes.  To read out a window of the capture, write the start
+// address (usually the line of the trigger address less the number of samples
+// wanted before the trigger) and the count of samples (both multiples of the
+// line size L), and then read them in bursts from the data port.  Wide values are written and read most significant
+// word first.  The samples are in the bus clock domain.
+//
+// HLS ports
+// 0 - arm (write only) - any write clears the trigger, and starts a new capture
+// 1 - decimate (write only) - keep one sample in every decimate + 1
+// 2 - post trigger (write only, 32 bits) - samples to keep after the trigger
+// 3 - channel (write only) - the channel the trigger watches
+// 4 - level (write only) - the level the channel must cross (a level of 0
+//     never fires, for captures that only use the sequencer)
+// 5 - edge (write only) - 0 for a rising crossing, 1 for a falling one
+// 6 - force (write only) - any write triggers right away
+// 7 - status (read only) - bit 0 = armed, bit 1 = triggered, bit 2 = done,
+//     bit 3 = overflow, bit 4 = readout starting.  Reading the status also
+//     loads the trigger address into port 8
+// 8 - trigger address (read only, 32 bits)
+// 9 - read start (write only, 32 bits)
+// 10 - read count (write only, 32 bits) - writing the count starts the readout
+// 11 - data (read only) - the samples of the readout, in order
+// 12 - sequence mask (write only) - the bits of the sample a stage looks at
+// 13 - sequence value (write only) - the value those bits must have
+// 14 - sequence count (write only) - the number of matches to complete a stage
+// 15 - sequence window (write only) - the samples a stage has to complete in
+//      (0 for no limit)
+// 16 - sequence stage (write only) - stores the mask, value, count and window
+//      in the stage written (0 to 3)
+// 17 - sequence depth (write only) - the number of stages in use, or 0 to
+//      turn the sequencer off.  Arming also restarts the sequencer
+// 18 - sequence state (read only) - the stage the sequencer is waiting on
+#[derive(LogicBlock)]
+pub struct HLSCapture<
+    const R: usize, // Number of rows in the SDRAM
+    const C: usize, // Number of columns in the SDRAM
+    const L: u32,   // Line size of the SDRAM bursts
+    const D: usize, // Number of bits in the SDRAM interface, in each sample, and on the bus
+    const A: usize, // Number of address bits in the SDRAM
+    const B: usize, // Number of address bits on the bus
+    const W: usize, // Number of bits in each channel
+> {
+    pub upstream: SoCBusResponder<D, B>,
+    pub sdram: SDRAMDriver<D>,
+    pub ram_clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<D>>,
+    pub strobe_in: Signal<In, Bit>,
+    bridge: Bridge<D, B, 19>,
+    arm_reg: MOSIPort<D>,
+    decimate_reg: MOSIPort<D>,
+    post_reg: MOSIWidePort<32, D>,
+    channel_reg: MOSIPort<D>,
+    level_reg: MOSIPort<D>,
+    edge_reg: MOSIPort<D>,
+    force_reg: MOSIPort<D>,
+    status_reg: MISOPort<D>,
+    address_reg: MISOWidePort<32, D>,
+    start_reg: MOSIWidePort<32, D>,
+    count_reg: MOSIWidePort<32, D>,
+    data_reg: MISOPort<D>,
+    seq_mask_reg: MOSIPort<D>,
+    seq_value_reg: MOSIPort<D>,
+    seq_count_reg: MOSIPort<D>,
+    seq_window_reg: MOSIPort<D>,
+    seq_stage_reg: MOSIPort<D>,
+    seq_depth_reg: MOSIPort<D>,
+    seq_state_reg: MISOPort<D>,
+    trigger: LevelTrigger<D, W>,
+    sequencer: TriggerSequencer<D, 2>,
+    capture: SDRAMCapture<R, C, L, D, A>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<
+        const R: usize,
+        const C: usize,
+        const L: u32,
+        const D: usize,
+        const A: usize,
+        const B: usize,
+        const W: usize,
+    > HLSNamedPorts for HLSCapture<R, C, L, D, A, B, W>
+{
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<
+        const R: usize,
+        const C: usize,
+        const L: u32,
+        const D: usize,
+        const A: usize,
+        const B: usize,
+        const W: usize,
+    > HLSCapture<R, C, L, D, A, B, W>
+{
+    pub fn new(cas_delay: u32, timings: MemoryTimings, buffer: OutputBuffer) -> Self {
+        assert!(A <= 32);
+        assert!(W <= D);
+        Self {
+            upstream: Default::default(),
+            sdram: Default::default(),
+            ram_clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            bridge: Bridge::new([
+                "arm",
+                "decimate",
+                "post_trigger",
+                "channel",
+                "level",
+                "edge",
+                "force",
+                "status",
+                "trigger_address",
+                "read_start",
+                "read_count",
+                "data",
+                "sequence_mask",
+                "sequence_value",
+                "sequence_count",
+                "sequence_window",
+                "sequence_stage",
+                "sequence_depth",
+                "sequence_state",
+            ]),
+            arm_reg: Default::default(),
+            decimate_reg: Default::default(),
+            post_reg: Default::default(),
+            channel_reg: Default::default(),
+            level_reg: Default::default(),
+            edge_reg: Default::default(),
+            force_reg: Default::default(),
+            status_reg: Default::default(),
+            address_reg: Default::default(),
+            start_reg: Default::default(),
+            count_reg: Default::default(),
+            data_reg: Default::default(),
+            seq_mask_reg: Default::default(),
+            seq_value_reg: Default::default(),
+            seq_count_reg: Default::default(),
+            seq_window_reg: Default::default(),
+            seq_stage_reg: Default::default(),
+            seq_depth_reg: Default::default(),
+            seq_state_reg: Default::default(),
+            trigger: Default::default(),
+            sequencer: Default::default(),
+            capture: SDRAMCapture::new(cas_delay, timings, buffer),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<
+        const R: usize,
+        const C: usize,
+        const L: u32,
+        const D: usize,
+        const A: usize,
+        const B: usize,
+        const W: usize,
+    > Logic for HLSCapture<R, C, L, D, A, B, W>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, B>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.arm_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.decimate_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.post_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.channel_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[4], &mut self.level_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[5], &mut self.edge_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[6], &mut self.force_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[7], &mut self.status_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[8], &mut self.address_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[9], &mut self.start_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[10], &mut self.count_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[11], &mut self.data_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[12], &mut self.seq_mask_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[13], &mut self.seq_value_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[14], &mut self.seq_count_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[15], &mut self.seq_window_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[16], &mut self.seq_stage_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[17], &mut self.seq_depth_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[18], &mut self.seq_state_reg.bus);
+        SDRAMDriver::<D>::link(&mut self.sdram, &mut self.capture.sdram);
+        self.clock.next = self.bridge.clock_out.val();
+        self.trigger.clock.next = self.clock.val();
+        self.sequencer.clock.next = self.clock.val();
+        self.capture.clock.next = self.clock.val();
+        self.capture.ram_clock.next = self.ram_clock.val();
+        self.arm_reg.ready.next = true;
+        self.decimate_reg.ready.next = true;
+        self.channel_reg.ready.next = true;
+        self.level_reg.ready.next = true;
+        self.edge_reg.ready.next = true;
+        self.force_reg.ready.next = true;
+        self.status_reg.ready_in.next = true;
+        self.seq_mask_reg.ready.next = true;
+        self.seq_value_reg.ready.next = true;
+        self.seq_count_reg.ready.next = true;
+        self.seq_window_reg.ready.next = true;
+        self.seq_stage_reg.ready.next = true;
+        self.seq_depth_reg.ready.next = true;
+        self.seq_state_reg.ready_in.next = true;
+        // The triggers watch the same samples that go into the capture
+        self.trigger.data_in.next = self.data_in.val();
+        self.trigger.strobe_in.next = self.strobe_in.val();
+        self.trigger.channel.next = bit_cast::<8, D>(self.channel_reg.port_out.val());
+        self.trigger.level.next = bit_cast::<W, D>(self.level_reg.port_out.val());
+        self.trigger.falling.next = self.edge_reg.port_out.val().any();
+        self.trigger.force.next = self.force_reg.strobe_out.val();
+        self.sequencer.data_in.next = self.data_in.val();
+        self.sequencer.strobe_in.next = self.strobe_in.val();
+        self.sequencer.arm.next = self.arm_reg.strobe_out.val();
+        self.sequencer.depth.next = bit_cast::<8, D>(self.seq_depth_reg.port_out.val());
+        self.sequencer.config_mask.next = self.seq_mask_reg.port_out.val();
+        self.sequencer.config_value.next = self.seq_value_reg.port_out.val();
+        self.sequencer.config_count.next = bit_cast::<16, D>(self.seq_count_reg.port_out.val());
+        self.sequencer.config_window.next = bit_cast::<16, D>(self.seq_window_reg.port_out.val());
+        self.sequencer.config_stage.next = bit_cast::<2, D>(self.seq_stage_reg.port_out.val());
+        self.sequencer.config_write.next = self.seq_stage_reg.strobe_out.val();
+        self.seq_state_reg.port_in.next = bit_cast::<D, 2>(self.sequencer.stage.val());
+        self.capture.data_in.next = self.data_in.val();
+        self.capture.strobe_in.next = self.strobe_in.val();
+        self.capture.trigger.next = self.trigger.trigger.val() | self.sequencer.trigger.val();
+        self.capture.decimate.next = bit_cast::<16, D>(self.decimate_reg.port_out.val());
+        self.capture.arm.next = self.arm_reg.strobe_out.val();
+        self.capture.post_trigger.next = bit_cast::<A, 32>(self.post_reg.port_out.val());
+        // Status
+        self.status_reg.port_in.next = bit_cast::<D, 1>(self.capture.armed.val().into())
+            | (bit_cast::<D, 1>(self.capture.triggered.val().into()) << 1)
+            | (bit_cast::<D, 1>(self.capture.done.val().into()) << 2)
+            | (bit_cast::<D, 1>(self.capture.overflow.val().into()) << 3)
+            | (bit_cast::<D, 1>(self.capture.read_busy.val().into()) << 4);
+        self.address_reg.port_in.next = bit_cast::<32, A>(self.capture.trigger_address.val());
+        self.address_reg.strobe_in.next = self.status_reg.strobe_out.val();
+        // Readout
+        self.capture.read_start.next = bit_cast::<A, 32>(self.start_reg.port_out.val());
+        self.capture.read_count.next = bit_cast::<A, 32>(self.count_reg.port_out.val());
+        self.capture.read_go.next = self.count_reg.strobe_out.val();
+        self.data_reg.port_in.next = self.capture.data_out.val();
+        self.data_reg.ready_in.next = !self.capture.empty.val();
+        self.capture.read.next = self.data_reg.strobe_out.val();
+    }
+}
+
+#[test]
+fn test_hls_capture_is_synthesizable() {
+    let mut uut = HLSCapture::<6, 4, 4, 16, 12, 8, 4>::new(
+        3,
+        MemoryTimings::fast_boot_sim(125e6),
+        OutputBuffer::Wired,
+    );
+    uut.upstream.link_connect_dest();
+    uut.data_in.connect();
+    uut.strobe_in.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_capture", &vlog).unwrap();
+}
@@ -0,0 +1,276 @@
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use rust_hdl_lib_core::prelude::*;
+
+// A small constrained random framework for testbenches.
+//
+// All of the randomness comes from a `StimulusRng`, which is seeded either
+// from the `RUST_HDL_SEED` environment variable or (if that is not set)
+// from the thread RNG.  The seed is printed if the test panics while the
+// `StimulusRng` is alive, so a failure can be reproduced with
+//   RUST_HDL_SEED=<seed> cargo test <the test>
+//
+// The generators cover the common needs of the FIFO style tests:
+//   - weighted choices between alternatives
+//   - inter-arrival gaps with a choice of distributions (these plug straight
+//     into the sleeps argument of LazyFIFOFeeder/LazyFIFOReader)
+//   - sequences of operations that must respect a protocol, like never
+//     reading from an empty FIFO, where each candidate is checked against a
+//     model of the state before it is accepted.
+
+pub const SEED_VARIABLE: &str = "RUST_HDL_SEED";
+
+pub struct StimulusRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl Default for StimulusRng {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl StimulusRng {
+    pub fn new() -> StimulusRng {
+        let seed = std::env::var(SEED_VARIABLE)
+            .ok()
+            .and_then(|x| x.parse::<u64>().ok())
+            .unwrap_or_else(|| rand::thread_rng().gen());
+        Self::from_seed(seed)
+    }
+    pub fn from_seed(seed: u64) -> StimulusRng {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+    pub fn seed(&self) -> u64 {
+        self.seed
+    }
+    pub fn rng(&mut self) -> &mut StdRng {
+        &mut self.rng
+    }
+    pub fn bits<const N: usize>(&mut self) -> Bits<N> {
+        (0..N).fold(Bits::<N>::default(), |acc, ndx| {
+            acc.replace_bit(ndx, self.rng.gen())
+        })
+    }
+    pub fn bits_vec<const N: usize>(&mut self, len: usize) -> Vec<Bits<N>> {
+        (0..len).map(|_| self.bits()).collect()
+    }
+    pub fn chance(&mut self, probability: f64) -> bool {
+        self.rng.gen::<f64>() < probability
+    }
+    // Pick one of the choices, with probability proportional to its weight
+    pub fn choose<'a, T>(&mut self, choices: &'a [(u32, T)]) -> &'a T {
+        let total = choices.iter().map(|x| x.0 as u64).sum::<u64>();
+        assert!(total > 0, "At least one choice must have a non-zero weight");
+        let mut pick = self.rng.gen_range(0..total);
+        for (weight, choice) in choices {
+            if pick < *weight as u64 {
+                return choice;
+            }
+            pick -= *weight as u64;
+        }
+        unreachable!()
+    }
+    pub fn gap(&mut self, gaps: Gaps) -> u32 {
+        match gaps {
+            Gaps::None => 0,
+            Gaps::Fixed(n) => n,
+            Gaps::Uniform { min, max } => self.rng.gen_range(min..=max),
+            Gaps::Bursty { probability, max } => {
+                if self.chance(probability) {
+                    self.rng.gen_range(0..=max)
+                } else {
+                    0
+                }
+            }
+            Gaps::Geometric { mean } => {
+                // Number of failures before the first success
+                let p = 1.0 / (1.0 + mean);
+                let mut count = 0;
+                while !self.chance(p) {
+                    count += 1;
+                }
+                count
+            }
+        }
+    }
+    // A list of gaps, in the form LazyFIFOFeeder and LazyFIFOReader expect
+    pub fn sleeps(&mut self, len: usize, gaps: Gaps) -> Vec<Bits<32>> {
+        (0..len).map(|_| self.gap(gaps).to_bits()).collect()
+    }
+    // Generate a sequence of `len` items.  Each candidate from `generate` is
+    // checked with `legal` against the current state, and only accepted (and
+    // applied to the state with `advance`) if it is legal.
+    pub fn constrained<S, T, G, L, A>(
+        &mut self,
+        len: usize,
+        mut state: S,
+        mut generate: G,
+        legal: L,
+        advance: A,
+    ) -> Vec<T>
+    where
+        G: FnMut(&mut StimulusRng) -> T,
+        L: Fn(&S, &T) -> bool,
+        A: Fn(&mut S, &T),
+    {
+        let mut ret = Vec::with_capacity(len);
+        while ret.len() < len {
+            let mut tries = 0;
+            let candidate = loop {
+                let candidate = generate(self);
+                if legal(&state, &candidate) {
+                    break candidate;
+                }
+                tries += 1;
+                assert!(
+                    tries < 1000,
+                    "Unable to generate a legal item after {} tries",
+                    tries
+                );
+            };
+            advance(&mut state, &candidate);
+            ret.push(candidate);
+        }
+        ret
+    }
+    // A sequence of operations on a FIFO of the given depth that never writes
+    // to it when full, and never reads from it when empty.
+    pub fn fifo_ops<const N: usize>(
+        &mut self,
+        len: usize,
+        depth: usize,
+        weights: FIFOOpWeights,
+    ) -> Vec<FIFOOp<Bits<N>>> {
+        self.constrained(
+            len,
+            0_usize,
+            |rng| match rng.choose(&[(weights.write, 0), (weights.read, 1), (weights.idle, 2)]) {
+                0 => FIFOOp::Write(rng.bits()),
+                1 => FIFOOp::Read,
+                _ => FIFOOp::Idle,
+            },
+            |count, op| match op {
+                FIFOOp::Write(_) => *count < depth,
+                FIFOOp::Read => *count > 0,
+                FIFOOp::Idle => true,
+            },
+            |count, op| match op {
+                FIFOOp::Write(_) => *count += 1,
+                FIFOOp::Read => *count -= 1,
+                FIFOOp::Idle => {}
+            },
+        )
+    }
+}
+
+impl Drop for StimulusRng {
+    fn drop(&mut self) {
+        if std::thread::panicking() {
+            println!(
+                "Random stimulus used seed {} (rerun with {}={} to reproduce)",
+                self.seed, SEED_VARIABLE, self.seed
+            );
+        }
+    }
+}
+
+// How long to wait between items
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Gaps {
+    None,
+    Fixed(u32),
+    Uniform { min: u32, max: u32 },
+    // Usually back to back, but with a gap of up to `max` with the given probability
+    Bursty { probability: f64, max: u32 },
+    Geometric { mean: f64 },
+}
+
+impl Gaps {
+    // The same distribution as `bursty_rand`
+    pub fn bursty() -> Gaps {
+        Gaps::Bursty {
+            probability: 0.1,
+            max: 39,
+        }
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FIFOOp<T> {
+    Write(T),
+    Read,
+    Idle,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FIFOOpWeights {
+    pub write: u32,
+    pub read: u32,
+    pub idle: u32,
+}
+
+impl Default for FIFOOpWeights {
+    fn default() -> Self {
+        Self {
+            write: 4,
+            read: 4,
+            idle: 1,
+        }
+    }
+}
+
+#[test]
+fn test_same_seed_same_stimulus() {
+    let mut a = StimulusRng::from_seed(42);
+    let mut b = StimulusRng::from_seed(42);
+    assert_eq!(a.bits_vec::<16>(100), b.bits_vec::<16>(100));
+    assert_eq!(a.sleeps(100, Gaps::bursty()), b.sleeps(100, Gaps::bursty()));
+}
+
+#[test]
+fn test_weighted_choice() {
+    let mut rng = StimulusRng::from_seed(1);
+    let choices = [(1, 'a'), (0, 'b'), (9, 'c')];
+    let picks = (0..10000)
+        .map(|_| *rng.choose(&choices))
+        .collect::<Vec<_>>();
+    let count_a = picks.iter().filter(|x| **x == 'a').count();
+    assert!(!picks.contains(&'b'));
+    assert!(count_a > 800 && count_a < 1200);
+}
+
+#[test]
+fn test_gaps_in_range() {
+    let mut rng = StimulusRng::from_seed(2);
+    for _ in 0..1000 {
+        let gap = rng.gap(Gaps::Uniform { min: 3, max: 7 });
+        assert!((3..=7).contains(&gap));
+        assert!(rng.gap(Gaps::bursty()) < 40);
+    }
+    let mean = (0..10000)
+        .map(|_| rng.gap(Gaps::Geometric { mean: 4.0 }) as f64)
+        .sum::<f64>()
+        / 10000.0;
+    assert!((mean - 4.0).abs() < 0.3);
+}
+
+#[test]
+fn test_fifo_ops_are_legal() {
+    let mut rng = StimulusRng::from_seed(3);
+    let ops = rng.fifo_ops::<8>(10000, 4, FIFOOpWeights::default());
+    let mut count = 0;
+    for op in &ops {
+        match op {
+            FIFOOp::Write(_) => count += 1,
+            FIFOOp::Read => count -= 1,
+            FIFOOp::Idle => {}
+        }
+        assert!((0..=4).contains(&count));
+    }
+    assert!(ops.iter().any(|x| matches!(x, FIFOOp::Read)));
+}
@@ -0,0 +1,317 @@
+use crate::bus::{FIFOReadController, FIFOReadResponder, FIFOWriteController, FIFOWriteResponder};
+use rust_hdl_lib_core::prelude::*;
+use std::collections::VecDeque;
+use std::fmt::{Display, Formatter};
+use std::sync::{Arc, Mutex};
+
+/// What a [FIFOInstrument] sees of one FIFO bus.  Build it from a clock and any of
+/// the FIFO bus types with `(clock, &bus).into()`.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FIFOPortSample {
+    /// The level of the clock the bus is registered on
+    pub clock: bool,
+    /// True if a token crosses the bus on this clock edge
+    pub transfer: bool,
+}
+
+impl<T: Synth> From<(Clock, &FIFOWriteController<T>)> for FIFOPortSample {
+    fn from(x: (Clock, &FIFOWriteController<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            transfer: x.1.write.val() && !x.1.full.val(),
+        }
+    }
+}
+
+impl<T: Synth> From<(Clock, &FIFOWriteResponder<T>)> for FIFOPortSample {
+    fn from(x: (Clock, &FIFOWriteResponder<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            transfer: x.1.write.val() && !x.1.full.val(),
+        }
+    }
+}
+
+impl<T: Synth> From<(Clock, &FIFOReadController<T>)> for FIFOPortSample {
+    fn from(x: (Clock, &FIFOReadController<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            transfer: x.1.read.val() && !x.1.empty.val(),
+        }
+    }
+}
+
+impl<T: Synth> From<(Clock, &FIFOReadResponder<T>)> for FIFOPortSample {
+    fn from(x: (Clock, &FIFOReadResponder<T>)) -> Self {
+        Self {
+            clock: x.0.clk,
+            transfer: x.1.read.val() && !x.1.empty.val(),
+        }
+    }
+}
+
+/// What a [FIFOInstrument] sees of the path after each simulation event.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct FIFOInstrumentSample {
+    /// The write bus going into the path
+    pub write: FIFOPortSample,
+    /// The read bus coming out of the path
+    pub read: FIFOPortSample,
+}
+
+/// The numbers collected by a [FIFOInstrument].
+#[derive(Clone, Debug, Default)]
+pub struct FIFOStatistics {
+    pub name: String,
+    /// Number of tokens written into the path
+    pub writes: u64,
+    /// Number of tokens read out of the path
+    pub reads: u64,
+    /// Number of rising edges of the read clock (while the path was in use)
+    pub read_cycles: u64,
+    /// Latency (in picoseconds) of each token read out, in order
+    pub latencies: Vec<u64>,
+    /// `occupancy[n]` is the number of write clock cycles with n tokens in the path
+    pub occupancy: Vec<u64>,
+    /// The time of the first token written into the path
+    pub first_write: Option<u64>,
+    /// The time of the last token read out of the path
+    pub last_read: Option<u64>,
+}
+
+impl FIFOStatistics {
+    /// The latency (in picoseconds) below which the given percentage of tokens fall
+    pub fn latency_percentile(&self, percent: f64) -> Option<u64> {
+        if self.latencies.is_empty() {
+            return None;
+        }
+        let mut sorted = self.latencies.clone();
+        sorted.sort_unstable();
+        let ndx = ((percent / 100.0) * (sorted.len() - 1) as f64).round() as usize;
+        Some(sorted[ndx.min(sorted.len() - 1)])
+    }
+    /// Tokens per second delivered by the path, from the first token in to the last token out
+    pub fn throughput(&self) -> f64 {
+        match (self.first_write, self.last_read) {
+            (Some(start), Some(end)) if end > start => {
+                self.reads as f64 * SIMULATION_TIME_ONE_SECOND as f64 / (end - start) as f64
+            }
+            _ => 0.0,
+        }
+    }
+    /// Fraction of read clock cycles in which a token came out of the path
+    pub fn utilization(&self) -> f64 {
+        if self.read_cycles == 0 {
+            0.0
+        } else {
+            self.reads as f64 / self.read_cycles as f64
+        }
+    }
+    /// The largest number of tokens that were in the path at once
+    pub fn max_occupancy(&self) -> usize {
+        self.occupancy.len().saturating_sub(1)
+    }
+}
+
+impl Display for FIFOStatistics {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "FIFO statistics for {}", self.name)?;
+        writeln!(f, "  tokens in/out   : {} / {}", self.writes, self.reads)?;
+        writeln!(
+            f,
+            "  throughput      : {:.3} Mtokens/s ({:.1}% of read cycles)",
+            self.throughput() / 1.0e6,
+            self.utilization() * 100.0
+        )?;
+        let ns = |x: Option<u64>| x.map(|x| x as f64 / 1000.0).unwrap_or(0.0);
+        writeln!(
+            f,
+            "  latency (ns)    : min {:.1} p50 {:.1} p90 {:.1} p99 {:.1} max {:.1}",
+            ns(self.latency_percentile(0.0)),
+            ns(self.latency_percentile(50.0)),
+            ns(self.latency_percentile(90.0)),
+            ns(self.latency_percentile(99.0)),
+            ns(self.latency_percentile(100.0))
+        )?;
+        let cycles = self.occupancy.iter().sum::<u64>().max(1);
+        writeln!(f, "  occupancy       : max {}", self.max_occupancy())?;
+        for (level, count) in self.occupancy.iter().enumerate() {
+            if *count != 0 {
+                writeln!(
+                    f,
+                    "    {:>6} : {:>5.1}%",
+                    level,
+                    *count as f64 * 100.0 / cycles as f64
+                )?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Performance instrumentation for a path made of FIFO buses (a FIFO, an
+/// expander or reducer, a cross clock FIFO, or a whole chain of them).  It
+/// watches a write bus going into the path and a read bus coming out of it,
+/// and at each rising edge of their clocks, timestamps the tokens going in
+/// and matches them up with the tokens coming out.  From that it collects
+/// the latency of each token, the occupancy of the path, and the throughput.
+/// Attach it with
+/// ```rust,ignore
+/// let instrument = FIFOInstrument::new("chain");
+/// let stats = instrument.statistics();
+/// sim.add_monitor(instrument, |x: &T| FIFOInstrumentSample {
+///     write: (x.clock.val(), &x.chain.bus_write).into(),
+///     read: (x.clock.val(), &x.chain.bus_read).into(),
+/// });
+/// ```
+/// The report is printed when the simulation ends, and the numbers are
+/// available from `stats` afterwards.  The instrument never fails the
+/// simulation.
+///
+/// Tokens are matched in order, so for a path that changes the number of
+/// tokens (like an expander), the latency is measured from the arrival of
+/// the n-th token in to the departure of the n-th token out.
+pub struct FIFOInstrument {
+    write_edges: EdgeTracker<FIFOPortSample>,
+    read_edges: EdgeTracker<FIFOPortSample>,
+    in_flight: VecDeque<u64>,
+    stats: Arc<Mutex<FIFOStatistics>>,
+}
+
+impl FIFOInstrument {
+    /// Create an instrument.  The name is used in the report.
+    pub fn new(name: &str) -> Self {
+        Self {
+            write_edges: Default::default(),
+            read_edges: Default::default(),
+            in_flight: Default::default(),
+            stats: Arc::new(Mutex::new(FIFOStatistics {
+                name: name.into(),
+                ..Default::default()
+            })),
+        }
+    }
+    /// A handle to the statistics, to read them once the simulation is over
+    pub fn statistics(&self) -> Arc<Mutex<FIFOStatistics>> {
+        self.stats.clone()
+    }
+}
+
+impl Monitor for FIFOInstrument {
+    type Sample = FIFOInstrumentSample;
+
+    fn name(&self) -> String {
+        self.stats.lock().unwrap().name.clone()
+    }
+
+    fn check(&mut self, sample: FIFOInstrumentSample, time: u64) -> Result<(), String> {
+        let mut stats = self.stats.lock().unwrap();
+        // Handle the read side first, so that a token never leaves on the
+        // same edge it arrived on.
+        if let Some(read) = self.read_edges.update(&sample.read, |x| x.clock) {
+            if stats.first_write.is_some() {
+                stats.read_cycles += 1;
+            }
+            if read.transfer {
+                stats.reads += 1;
+                stats.last_read = Some(time);
+                if let Some(start) = self.in_flight.pop_front() {
+                    stats.latencies.push(time - start);
+                }
+            }
+        }
+        if let Some(write) = self.write_edges.update(&sample.write, |x| x.clock) {
+            if write.transfer {
+                stats.writes += 1;
+                stats.first_write.get_or_insert(time);
+                self.in_flight.push_back(time);
+            }
+            let level = self.in_flight.len();
+            if stats.occupancy.len() <= level {
+                stats.occupancy.resize(level + 1, 0);
+            }
+            stats.occupancy[level] += 1;
+        }
+        Ok(())
+    }
+
+    fn finish(&mut self, _time: u64) -> Result<(), String> {
+        print!("{}", self.stats.lock().unwrap());
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[derive(LogicBlock, Default)]
+struct FIFOInstrumentTest {
+    clock: Signal<In, Clock>,
+    fifo: crate::fifo::SyncFIFO<Bits<8>, 4, 5, 1>,
+}
+
+#[cfg(test)]
+impl Logic for FIFOInstrumentTest {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, fifo);
+    }
+}
+
+#[test]
+fn test_fifo_instrument() {
+    let mut uut = FIFOInstrumentTest::default();
+    uut.fifo.bus_write.data.connect();
+    uut.fifo.bus_write.write.connect();
+    uut.fifo.bus_read.read.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<FIFOInstrumentTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    let instrument = FIFOInstrument::new("sync_fifo");
+    let stats = instrument.statistics();
+    sim.add_monitor(instrument, |x: &FIFOInstrumentTest| FIFOInstrumentSample {
+        write: (x.clock.val(), &x.fifo.bus_write).into(),
+        read: (x.clock.val(), &x.fifo.bus_read).into(),
+    });
+    sim.add_testbench(move |mut sim: Sim<FIFOInstrumentTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        // Write 8 tokens back to back, then read them out every other cycle
+        for i in 0..8_u32 {
+            x = sim.watch(|x| !x.fifo.bus_write.full.val(), x)?;
+            x.fifo.bus_write.data.next = i.to_bits();
+            x.fifo.bus_write.write.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.fifo.bus_write.write.next = false;
+            if i == 3 {
+                // Let the reader drain some
+                wait_clock_cycles!(sim, clock, x, 4);
+            }
+        }
+        wait_clock_cycles!(sim, clock, x, 20);
+        sim.done(x)
+    });
+    sim.add_testbench(move |mut sim: Sim<FIFOInstrumentTest>| {
+        let mut x = sim.init()?;
+        wait_clock_true!(sim, clock, x);
+        wait_clock_cycles!(sim, clock, x, 3);
+        for _ in 0..8 {
+            x = sim.watch(|x| !x.fifo.bus_read.empty.val(), x)?;
+            x.fifo.bus_read.read.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.fifo.bus_read.read.next = false;
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000).unwrap();
+    let stats = stats.lock().unwrap();
+    assert_eq!(stats.writes, 8);
+    assert_eq!(stats.reads, 8);
+    assert_eq!(stats.latencies.len(), 8);
+    assert!(stats.latency_percentile(0.0).unwrap() >= 10);
+    assert!(stats.latency_percentile(100.0) >= stats.latency_percentile(50.0));
+    assert!(stats.max_occupancy() >= 3 && stats.max_occupancy() <= 4);
+    assert!(stats.utilization() > 0.0 && stats.utilization() <= 0.5);
+    assert!(stats.to_string().contains("tokens in/out   : 8 / 8"));
+}
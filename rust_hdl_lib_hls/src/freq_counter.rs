@@ -0,0 +1,106 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_wide_port::MISOWidePort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+use std::time::Duration;
+
+// A bank of C frequency counters, that share a single set of registers
+// on the bus.  The host selects a channel by writing its number to the
+// select register, which also takes a snapshot of the measurements of
+// that channel.  The measurements are then read from the other registers.
+// See [FrequencyCounter] for what each of the measurements means.  All of
+// the counters are 32 bits, and are clocked by the bus clock.
+//
+// HLS ports
+// 0 - select (write only) - selects a channel, and takes a snapshot of it
+// 1 - edges (read only, 32 bits) - rising edges in the last gate time
+// 2 - period (read only, 32 bits) - clock cycles between the last two rising edges
+// 3 - high time (read only, 32 bits) - clock cycles the input was high in the last period
+#[derive(LogicBlock)]
+pub struct HLSFrequencyCounter<const D: usize, const A: usize, const C: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub inputs: [Signal<In, Bit>; C],
+    bridge: Bridge<D, A, 4>,
+    select_reg: MOSIPort<D>,
+    edges_reg: MISOWidePort<32, D>,
+    period_reg: MISOWidePort<32, D>,
+    high_time_reg: MISOWidePort<32, D>,
+    counters: [FrequencyCounter<32>; C],
+    selected_edges: Signal<Local, Bits<32>>,
+    selected_period: Signal<Local, Bits<32>>,
+    selected_high_time: Signal<Local, Bits<32>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize, const C: usize> HLSNamedPorts
+    for HLSFrequencyCounter<D, A, C>
+{
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize, const C: usize> HLSFrequencyCounter<D, A, C> {
+    pub fn new(frequency: u64, gate: Duration) -> Self {
+        Self {
+            upstream: Default::default(),
+            inputs: array_init::array_init(|_| Default::default()),
+            bridge: Bridge::new(["select", "edges", "period", "high_time"]),
+            select_reg: Default::default(),
+            edges_reg: Default::default(),
+            period_reg: Default::default(),
+            high_time_reg: Default::default(),
+            counters: array_init::array_init(|_| FrequencyCounter::new(frequency, gate)),
+            selected_edges: Default::default(),
+            selected_period: Default::default(),
+            selected_high_time: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize, const C: usize> Logic for HLSFrequencyCounter<D, A, C> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.select_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.edges_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.period_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.high_time_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        self.select_reg.ready.next = true;
+        for i in 0..C {
+            self.counters[i].clock.next = self.clock.val();
+            self.counters[i].pulses.next = self.inputs[i].val();
+        }
+        // Pick out the measurements of the selected channel
+        self.selected_edges.next = 0.into();
+        self.selected_period.next = 0.into();
+        self.selected_high_time.next = 0.into();
+        for i in 0..C {
+            if self.select_reg.port_out.val().index() == i {
+                self.selected_edges.next = self.counters[i].edges.val();
+                self.selected_period.next = self.counters[i].period.val();
+                self.selected_high_time.next = self.counters[i].high_time.val();
+            }
+        }
+        self.edges_reg.port_in.next = self.selected_edges.val();
+        self.period_reg.port_in.next = self.selected_period.val();
+        self.high_time_reg.port_in.next = self.selected_high_time.val();
+        self.edges_reg.strobe_in.next = self.select_reg.strobe_out.val();
+        self.period_reg.strobe_in.next = self.select_reg.strobe_out.val();
+        self.high_time_reg.strobe_in.next = self.select_reg.strobe_out.val();
+    }
+}
+
+#[test]
+fn test_hls_frequency_counter_is_synthesizable() {
+    let mut uut = HLSFrequencyCounter::<16, 8, 4>::new(100_000_000, Duration::from_millis(10));
+    uut.upstream.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_freq_counter", &vlog).unwrap();
+}
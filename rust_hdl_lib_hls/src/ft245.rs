@@ -0,0 +1,228 @@
+use crate::bus::{FIFOReadController, FIFOWriteController, SoCBusController};
+use crate::controller::BaseController;
+use crate::cross_fifo::{CrossNarrow, CrossWiden};
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// The pins of an FTDI chip (FT232H, FT2232H, FT600 in 8 bit mode, etc.)
+// in FT245 style synchronous FIFO mode, as seen by the FPGA.  The chip
+// provides the clock (60 MHz), and all of the signals are synchronous to it.
+// * rxf_n - low when the chip has data from the host
+// * txe_n - low when the chip has space for data to the host
+// * rd_n - pulled low to read a byte (on each clock edge)
+// * wr_n - pulled low to write a byte (on each clock edge)
+// * oe_n - pulled low to make the chip drive the data bus (one clock before rd_n)
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "FT245SyncBusChip"]
+pub struct FT245SyncBus {
+    pub data: Signal<InOut, Bits<8>>,
+    pub rxf_n: Signal<In, Bit>,
+    pub txe_n: Signal<In, Bit>,
+    pub rd_n: Signal<Out, Bit>,
+    pub wr_n: Signal<Out, Bit>,
+    pub oe_n: Signal<Out, Bit>,
+    pub clock: Signal<In, Clock>,
+}
+
+// The same pins, as seen by the FTDI chip
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "FT245SyncBus"]
+pub struct FT245SyncBusChip {
+    pub data: Signal<InOut, Bits<8>>,
+    pub rxf_n: Signal<Out, Bit>,
+    pub txe_n: Signal<Out, Bit>,
+    pub rd_n: Signal<In, Bit>,
+    pub wr_n: Signal<In, Bit>,
+    pub oe_n: Signal<In, Bit>,
+    pub clock: Signal<Out, Clock>,
+}
+
+#[derive(LogicState, Debug, Copy, Clone, PartialEq)]
+enum FT245State {
+    Idle,
+    Turnaround,
+    Reading,
+    Writing,
+}
+
+// Moves bytes between an FTDI chip in synchronous FIFO mode and a pair of
+// FIFOs.  Bytes from the host are written into `data_from_bus`, and bytes
+// in `data_to_bus` are sent to the host.  The FIFOs are clocked by the
+// clock of the chip (`bus.clock`), so they are normally asynchronous FIFOs
+// that cross into the clock domain of the design.  When there is traffic
+// in both directions, the bus alternates between them.
+#[derive(LogicBlock, Default)]
+pub struct FT245SyncFIFO {
+    pub bus: FT245SyncBus,
+    pub data_to_bus: FIFOReadController<Bits<8>>,
+    pub data_from_bus: FIFOWriteController<Bits<8>>,
+    pub clock: Signal<Out, Clock>,
+    bus_buffer: TristateBuffer<Bits<8>>,
+    state: DFF<FT245State>,
+    can_read: Signal<Local, Bit>,
+    can_write: Signal<Local, Bit>,
+}
+
+impl Logic for FT245SyncFIFO {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.clock.next = self.bus.clock.val();
+        dff_setup!(self, clock, state);
+        Signal::<InOut, Bits<8>>::link(&mut self.bus.data, &mut self.bus_buffer.bus);
+        self.bus_buffer.write_data.next = self.data_to_bus.data.val();
+        self.bus_buffer.write_enable.next = false;
+        self.data_from_bus.data.next = self.bus_buffer.read_data.val();
+        self.can_read.next = !self.bus.rxf_n.val() & !self.data_from_bus.full.val();
+        self.can_write.next = !self.bus.txe_n.val() & !self.data_to_bus.empty.val();
+        // Default values
+        self.bus.rd_n.next = true;
+        self.bus.wr_n.next = true;
+        self.bus.oe_n.next = true;
+        self.data_to_bus.read.next = false;
+        self.data_from_bus.write.next = false;
+        match self.state.q.val() {
+            FT245State::Idle => {
+                if self.can_write.val() {
+                    self.state.d.next = FT245State::Writing;
+                }
+                // Reads take priority, but a read burst hands over to any pending writes
+                if self.can_read.val() {
+                    self.state.d.next = FT245State::Turnaround;
+                }
+            }
+            FT245State::Turnaround => {
+                // The chip takes a clock to start driving the bus
+                self.bus.oe_n.next = false;
+                self.state.d.next = FT245State::Reading;
+            }
+            FT245State::Reading => {
+                self.bus.oe_n.next = false;
+                self.bus.rd_n.next = !self.can_read.val();
+                self.data_from_bus.write.next = self.can_read.val();
+                if !self.can_read.val() {
+                    self.state.d.next = FT245State::Idle;
+                    if self.can_write.val() {
+                        self.state.d.next = FT245State::Writing;
+                    }
+                }
+            }
+            FT245State::Writing => {
+                self.bus_buffer.write_enable.next = true;
+                self.bus.wr_n.next = !self.can_write.val();
+                self.data_to_bus.read.next = self.can_write.val();
+                if !self.can_write.val() {
+                    self.state.d.next = FT245State::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = FT245State::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ft245_sync_fifo_is_synthesizable() {
+    let mut uut = FT245SyncFIFO::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("ft245_sync_fifo", &vlog).unwrap();
+}
+
+// A model of the FTDI chip for testbenches.  Bytes from the host are taken
+// from `data_to_bus`, and bytes sent to the host are written into
+// `data_from_bus`, so the host side can be fed and checked with FIFOs.
+// The chip is clocked by `clock`, which it passes on to the bus.
+#[derive(LogicBlock, Default)]
+pub struct FT245SimulatedChip {
+    pub bus: FT245SyncBusChip,
+    pub clock: Signal<In, Clock>,
+    pub data_to_bus: FIFOReadController<Bits<8>>,
+    pub data_from_bus: FIFOWriteController<Bits<8>>,
+    bus_buffer: TristateBuffer<Bits<8>>,
+}
+
+impl Logic for FT245SimulatedChip {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.bus.clock.next = self.clock.val();
+        Signal::<InOut, Bits<8>>::link(&mut self.bus.data, &mut self.bus_buffer.bus);
+        // Data from the host
+        self.bus.rxf_n.next = self.data_to_bus.empty.val();
+        self.bus_buffer.write_data.next = self.data_to_bus.data.val();
+        self.bus_buffer.write_enable.next = !self.bus.oe_n.val();
+        self.data_to_bus.read.next = !self.bus.rd_n.val() & !self.data_to_bus.empty.val();
+        // Data to the host
+        self.bus.txe_n.next = self.data_from_bus.full.val();
+        self.data_from_bus.data.next = self.bus_buffer.read_data.val();
+        self.data_from_bus.write.next = !self.bus.wr_n.val() & !self.data_from_bus.full.val();
+    }
+}
+
+#[test]
+fn test_ft245_simulated_chip_is_synthesizable() {
+    let mut uut = FT245SimulatedChip::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("ft245_chip", &vlog).unwrap();
+}
+
+// Like the [Host], but connects an FTDI chip in synchronous FIFO mode to
+// a Controller, so that the host runtime can talk to the design over USB.
+#[derive(LogicBlock, Default)]
+pub struct FT245Host<const A: usize> {
+    pub ft_bus: FT245SyncBus,
+    pub bus: SoCBusController<16, A>,
+    pub sys_clock: Signal<In, Clock>,
+    ft_fifo: FT245SyncFIFO,
+    bus_to_controller: CrossWiden<8, 4, 5, 16, 3, 4>,
+    controller_to_bus: CrossNarrow<16, 3, 4, 8, 4, 5>,
+    controller: BaseController<A>,
+}
+
+impl<const A: usize> FT245Host<A> {
+    pub fn new(order: WordOrder) -> Self {
+        Self {
+            bus_to_controller: CrossWiden::new(order),
+            controller_to_bus: CrossNarrow::new(order),
+            ..Default::default()
+        }
+    }
+}
+
+impl<const A: usize> Logic for FT245Host<A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        FT245SyncBus::link(&mut self.ft_bus, &mut self.ft_fifo.bus);
+        FIFOWriteController::<Bits<8>>::join(
+            &mut self.ft_fifo.data_from_bus,
+            &mut self.bus_to_controller.narrow_bus,
+        );
+        self.bus_to_controller.narrow_clock.next = self.ft_fifo.clock.val();
+        self.bus_to_controller.wide_clock.next = self.sys_clock.val();
+        FIFOReadController::<Bits<8>>::join(
+            &mut self.ft_fifo.data_to_bus,
+            &mut self.controller_to_bus.narrow_bus,
+        );
+        self.controller_to_bus.narrow_clock.next = self.ft_fifo.clock.val();
+        self.controller_to_bus.wide_clock.next = self.sys_clock.val();
+        FIFOReadController::<Bits<16>>::join(
+            &mut self.controller.from_cpu,
+            &mut self.bus_to_controller.wide_bus,
+        );
+        FIFOWriteController::<Bits<16>>::join(
+            &mut self.controller.to_cpu,
+            &mut self.controller_to_bus.wide_bus,
+        );
+        clock!(self, sys_clock, controller);
+        SoCBusController::<16, A>::link(&mut self.bus, &mut self.controller.bus);
+    }
+}
+
+#[test]
+fn test_ft245_host_synthesizes() {
+    let mut uut = FT245Host::<8>::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("ft245_host", &vlog).unwrap();
+}
@@ -0,0 +1,146 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A general purpose IO block with N pins.  Each pin is driven through
+// a tristate buffer, so that the host can pick the direction of each
+// pin independently.  The inputs are synchronized to the bus clock
+// before being presented to the host, and any change on a pin that is
+// enabled in the interrupt mask is latched into the interrupt status
+// register.  Reading the status register clears it.  The pull register
+// is not connected to the pads (pull resistors are a property of the
+// IO cell, not the fabric), and is presented on `pull_enable` so that
+// the board support layer can route it where needed.
+//
+// HLS ports
+// 0 - direction (1 = output)
+// 1 - output value
+// 2 - input value (read only)
+// 3 - pull enable
+// 4 - interrupt mask (1 = interrupt on change)
+// 5 - interrupt status (read only, clear on read)
+#[derive(LogicBlock)]
+pub struct HLSGPIO<const D: usize, const A: usize, const N: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub pins: [Signal<InOut, Bit>; N],
+    pub pull_enable: Signal<Out, Bits<N>>,
+    pub irq: Signal<Out, Bit>,
+    bridge: Bridge<D, A, 6>,
+    dir_reg: MOSIPort<D>,
+    out_reg: MOSIPort<D>,
+    in_reg: MISOPort<D>,
+    pull_reg: MOSIPort<D>,
+    mask_reg: MOSIPort<D>,
+    status_reg: MISOPort<D>,
+    buffers: [TristateBuffer<Bit>; N],
+    pads: Signal<Local, Bits<N>>,
+    changed: Signal<Local, Bits<N>>,
+    sync_0: DFF<Bits<N>>,
+    sync_1: DFF<Bits<N>>,
+    previous: DFF<Bits<N>>,
+    pending: DFF<Bits<N>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize, const N: usize> HLSNamedPorts for HLSGPIO<D, A, N> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> Default for HLSGPIO<D, A, N> {
+    fn default() -> Self {
+        assert!(N <= D);
+        Self {
+            upstream: Default::default(),
+            pins: array_init::array_init(|_| Default::default()),
+            pull_enable: Default::default(),
+            irq: Default::default(),
+            bridge: Bridge::new([
+                "direction",
+                "output",
+                "input",
+                "pull",
+                "irq_mask",
+                "irq_status",
+            ]),
+            dir_reg: Default::default(),
+            out_reg: Default::default(),
+            in_reg: Default::default(),
+            pull_reg: Default::default(),
+            mask_reg: Default::default(),
+            status_reg: Default::default(),
+            buffers: array_init::array_init(|_| Default::default()),
+            pads: Default::default(),
+            changed: Default::default(),
+            sync_0: Default::default(),
+            sync_1: Default::default(),
+            previous: Default::default(),
+            pending: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> Logic for HLSGPIO<D, A, N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.dir_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.out_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.in_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.pull_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[4], &mut self.mask_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[5], &mut self.status_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        dff_setup!(self, clock, sync_0, sync_1, previous, pending);
+        self.dir_reg.ready.next = true;
+        self.out_reg.ready.next = true;
+        self.pull_reg.ready.next = true;
+        self.mask_reg.ready.next = true;
+        self.in_reg.ready_in.next = true;
+        self.status_reg.ready_in.next = true;
+        // Wire each pin through its own tristate buffer
+        for i in 0..N {
+            Signal::<InOut, Bit>::link(&mut self.pins[i], &mut self.buffers[i].bus);
+            self.buffers[i].write_enable.next = self.dir_reg.port_out.val().get_bit(i);
+            self.buffers[i].write_data.next = self.out_reg.port_out.val().get_bit(i);
+        }
+        // Gather the pad values into a single word, and synchronize it to the bus clock
+        self.pads.next = 0.into();
+        for i in 0..N {
+            self.pads.next = self
+                .pads
+                .val()
+                .replace_bit(i, self.buffers[i].read_data.val());
+        }
+        self.sync_0.d.next = self.pads.val();
+        self.sync_1.d.next = self.sync_0.q.val();
+        self.previous.d.next = self.sync_1.q.val();
+        self.in_reg.port_in.next = bit_cast::<D, N>(self.sync_1.q.val());
+        // Latch any enabled changes into the pending register
+        self.changed.next = (self.sync_1.q.val() ^ self.previous.q.val())
+            & bit_cast::<N, D>(self.mask_reg.port_out.val());
+        self.status_reg.port_in.next = bit_cast::<D, N>(self.pending.q.val());
+        if self.status_reg.strobe_out.val() {
+            self.pending.d.next = self.changed.val();
+        } else {
+            self.pending.d.next = self.pending.q.val() | self.changed.val();
+        }
+        self.irq.next = self.pending.q.val().any();
+        self.pull_enable.next = bit_cast::<N, D>(self.pull_reg.port_out.val());
+    }
+}
+
+#[test]
+fn test_hls_gpio_is_synthesizable() {
+    let mut uut = HLSGPIO::<16, 8, 8>::default();
+    uut.upstream.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_gpio", &vlog).unwrap();
+}
@@ -0,0 +1,162 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// Accumulates a [Histogram] of the `S` bit samples presented on `data_in`
+// (e.g., pulse heights from an ADC), with `2^B` bins, and makes it available
+// to the host.  The counts (and statistics) are `D` bits wide, and saturate.
+// To read out the histogram, write the first bin to `address`, and then
+// read `count` repeatedly - the address advances to the next bin after
+// each read.  The samples must be in the bus clock domain.
+//
+// HLS ports
+// 0 - clear (write only) - any write clears the histogram and statistics
+// 1 - offset (write only) - the sample value at the start of the first bin
+// 2 - shift (write only) - the bins are 2^shift sample values wide
+// 3 - address (write only) - the bin to read next
+// 4 - count (read only) - the count of the bin at address
+// 5 - total (read only) - the number of samples
+// 6 - underflow (read only) - the number of samples before the first bin
+// 7 - overflow (read only) - the number of samples past the last bin
+// 8 - busy (read only) - non-zero while the histogram is being cleared
+#[derive(LogicBlock)]
+pub struct HLSHistogram<const D: usize, const A: usize, const S: usize, const B: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub data_in: Signal<In, Bits<S>>,
+    pub strobe_in: Signal<In, Bit>,
+    bridge: Bridge<D, A, 9>,
+    clear_reg: MOSIPort<D>,
+    offset_reg: MOSIPort<D>,
+    shift_reg: MOSIPort<D>,
+    address_reg: MOSIPort<D>,
+    count_reg: MISOPort<D>,
+    total_reg: MISOPort<D>,
+    underflow_reg: MISOPort<D>,
+    overflow_reg: MISOPort<D>,
+    busy_reg: MISOPort<D>,
+    histogram: Histogram<S, B, D>,
+    offset: DFF<Bits<S>>,
+    shift: DFF<Bits<8>>,
+    address: DFF<Bits<B>>,
+    // The readout lags a change of address by a clock
+    pending: DFF<Bit>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize, const S: usize, const B: usize> HLSNamedPorts
+    for HLSHistogram<D, A, S, B>
+{
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize, const S: usize, const B: usize> Default
+    for HLSHistogram<D, A, S, B>
+{
+    fn default() -> Self {
+        assert!(B <= D);
+        Self {
+            upstream: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            bridge: Bridge::new([
+                "clear",
+                "offset",
+                "shift",
+                "address",
+                "count",
+                "total",
+                "underflow",
+                "overflow",
+                "busy",
+            ]),
+            clear_reg: Default::default(),
+            offset_reg: Default::default(),
+            shift_reg: Default::default(),
+            address_reg: Default::default(),
+            count_reg: Default::default(),
+            total_reg: Default::default(),
+            underflow_reg: Default::default(),
+            overflow_reg: Default::default(),
+            busy_reg: Default::default(),
+            histogram: Default::default(),
+            offset: Default::default(),
+            shift: Default::default(),
+            address: Default::default(),
+            pending: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize, const S: usize, const B: usize> Logic
+    for HLSHistogram<D, A, S, B>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.clear_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.offset_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.shift_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.address_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[4], &mut self.count_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[5], &mut self.total_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[6], &mut self.underflow_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[7], &mut self.overflow_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[8], &mut self.busy_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        clock!(self, clock, histogram);
+        dff_setup!(self, clock, offset, shift, address, pending);
+        self.clear_reg.ready.next = true;
+        self.offset_reg.ready.next = true;
+        self.shift_reg.ready.next = true;
+        self.address_reg.ready.next = true;
+        if self.offset_reg.strobe_out.val() {
+            self.offset.d.next = bit_cast::<S, D>(self.offset_reg.port_out.val());
+        }
+        if self.shift_reg.strobe_out.val() {
+            self.shift.d.next = self.shift_reg.port_out.val().get_bits::<8>(0);
+        }
+        self.pending.d.next = false;
+        if self.count_reg.strobe_out.val() {
+            self.address.d.next = self.address.q.val() + 1;
+            self.pending.d.next = true;
+        }
+        if self.address_reg.strobe_out.val() {
+            self.address.d.next = bit_cast::<B, D>(self.address_reg.port_out.val());
+            self.pending.d.next = true;
+        }
+        self.histogram.data_in.next = self.data_in.val();
+        self.histogram.strobe_in.next = self.strobe_in.val();
+        self.histogram.offset.next = self.offset.q.val();
+        self.histogram.shift.next = self.shift.q.val();
+        self.histogram.clear.next = self.clear_reg.strobe_out.val();
+        self.histogram.read_address.next = self.address.q.val();
+        self.count_reg.port_in.next = self.histogram.read_data.val();
+        self.count_reg.ready_in.next = !self.pending.q.val();
+        self.total_reg.port_in.next = self.histogram.total.val();
+        self.total_reg.ready_in.next = true;
+        self.underflow_reg.port_in.next = self.histogram.underflow.val();
+        self.underflow_reg.ready_in.next = true;
+        self.overflow_reg.port_in.next = self.histogram.overflow.val();
+        self.overflow_reg.ready_in.next = true;
+        self.busy_reg.port_in.next = bit_cast::<D, 1>(self.histogram.busy.val().into());
+        self.busy_reg.ready_in.next = true;
+    }
+}
+
+#[test]
+fn test_hls_histogram_is_synthesizable() {
+    let mut uut = HLSHistogram::<16, 8, 16, 6>::default();
+    uut.upstream.link_connect_dest();
+    uut.data_in.connect();
+    uut.strobe_in.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_histogram", &vlog).unwrap();
+}
@@ -1,28 +1,50 @@
 pub mod bidi;
 pub mod bridge;
+pub mod bringup;
 pub mod bus;
+pub mod bus_monitor;
+pub mod capture;
+pub mod constrained_random;
 pub mod controller;
 pub mod cross_fifo;
 pub mod expander;
 pub mod fifo;
 pub mod fifo_linker;
+pub mod fifo_stats;
+pub mod freq_counter;
+pub mod ft245;
+pub mod gpio;
+pub mod histogram;
 pub mod host;
+pub mod memory_tester;
 pub mod miso_fifo_port;
 pub mod miso_port;
 pub mod miso_wide_port;
 pub mod mosi_fifo_port;
 pub mod mosi_port;
 pub mod mosi_wide_port;
+pub mod parallel_bus;
+pub mod pin_test;
 pub mod prelude;
+pub mod ram;
+pub mod reboot;
 pub mod reducer;
 pub mod router;
 pub mod router_rom;
+pub mod rtc;
 pub mod sdram_controller;
 pub mod sdram_controller_tester;
 pub mod sdram_fifo;
+pub mod signal_gen;
 pub mod sim;
 pub mod spi;
+pub mod statistics;
+pub mod sysmon;
 pub mod test_helpers;
+pub mod timer;
+pub mod uart_host;
+pub mod uart_monitor;
+pub mod watchdog;
 
 pub trait HLSNamedPorts {
     fn ports(&self) -> Vec<String>;
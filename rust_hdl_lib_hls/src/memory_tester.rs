@@ -0,0 +1,405 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::miso_wide_port::MISOWidePort;
+use crate::mosi_port::MOSIPort;
+use crate::mosi_wide_port::MOSIWidePort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// The patterns the memory tester can write.  The test works on the 64 bit
+// transfers of the controller - transfer `n` covers addresses `4n..4n+3`.
+//   WalkingOnes - transfer `n` is `1 << (n % 64)`
+//   WalkingZeros - the complement of WalkingOnes
+//   AddressInAddress - transfer `n` is the address (`4n`) in the low 32 bits,
+//      and its complement in the high 32 bits, which catches address lines
+//      that are stuck or shorted (the memory aliases onto itself)
+//   Random - a 64 bit Galois LFSR (taps 64, 63, 61, 60), started from
+//      `(seed << 32) | !seed`, and stepped once per transfer
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemoryTestPattern {
+    WalkingOnes = 0,
+    WalkingZeros = 1,
+    AddressInAddress = 2,
+    Random = 3,
+}
+
+const LFSR_TAPS: u64 = 0xD800_0000_0000_0000;
+
+impl MemoryTestPattern {
+    // The transfers the pattern writes (and expects to read back), for
+    // checking a memory dump or a model against the hardware
+    pub fn words(self, count: usize, seed: u32) -> Vec<u64> {
+        let mut lfsr = ((seed as u64) << 32) | (!seed as u64);
+        (0..count)
+            .map(|n| {
+                let word = match self {
+                    MemoryTestPattern::WalkingOnes => 1 << (n % 64),
+                    MemoryTestPattern::WalkingZeros => !(1 << (n % 64)),
+                    MemoryTestPattern::AddressInAddress => {
+                        let address = (4 * n) as u32;
+                        ((!address as u64) << 32) | (address as u64)
+                    }
+                    MemoryTestPattern::Random => lfsr,
+                };
+                lfsr = (lfsr >> 1) ^ if lfsr & 1 != 0 { LFSR_TAPS } else { 0 };
+                word
+            })
+            .collect()
+    }
+}
+
+// Generates the words of a pattern, one transfer at a time.  The tester has
+// two of these - one for the writes, and one to check the reads against.
+#[derive(LogicBlock)]
+pub struct MemoryPatternGenerator {
+    pub clock: Signal<In, Clock>,
+    pub pattern: Signal<In, Bits<2>>,
+    pub seed: Signal<In, Bits<32>>,
+    // Go back to the first transfer
+    pub restart: Signal<In, Bit>,
+    // Move on to the next transfer
+    pub advance: Signal<In, Bit>,
+    pub word: Signal<Out, Bits<64>>,
+    pub address: Signal<Out, Bits<32>>,
+    state: DFF<Bits<64>>,
+    current: DFF<Bits<32>>,
+    taps: Constant<Bits<64>>,
+}
+
+impl Default for MemoryPatternGenerator {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            pattern: Default::default(),
+            seed: Default::default(),
+            restart: Default::default(),
+            advance: Default::default(),
+            word: Default::default(),
+            address: Default::default(),
+            state: Default::default(),
+            current: Default::default(),
+            taps: Constant::new(LFSR_TAPS.to_bits()),
+        }
+    }
+}
+
+impl Logic for MemoryPatternGenerator {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state, current);
+        self.address.next = self.current.q.val();
+        self.word.next = self.state.q.val();
+        if self.pattern.val() == 1 {
+            self.word.next = !self.state.q.val();
+        } else if self.pattern.val() == 2 {
+            self.word.next = (bit_cast::<64, 32>(!self.current.q.val()) << 32)
+                | bit_cast::<64, 32>(self.current.q.val());
+        }
+        if self.restart.val() {
+            self.current.d.next = 0.into();
+            self.state.d.next = 1.into();
+            if self.pattern.val() == 3 {
+                self.state.d.next = (bit_cast::<64, 32>(self.seed.val()) << 32)
+                    | bit_cast::<64, 32>(!self.seed.val());
+            }
+        } else if self.advance.val() {
+            self.current.d.next = self.current.q.val() + 4;
+            if self.pattern.val() == 3 {
+                self.state.d.next = self.state.q.val() >> 1;
+                if self.state.q.val().get_bit(0) {
+                    self.state.d.next = (self.state.q.val() >> 1) ^ self.taps.val();
+                }
+            } else {
+                self.state.d.next = (self.state.q.val() << 1)
+                    | bit_cast::<64, 1>(self.state.q.val().get_bit(63).into());
+            }
+        }
+    }
+}
+
+#[derive(Debug, Copy, Clone, LogicState, PartialEq)]
+enum State {
+    Idle,
+    Starting,
+    Writing,
+    Reading,
+    Draining,
+}
+
+// A memory tester for board bring-up.  It writes one of the patterns of
+// [MemoryTestPattern] through the SDRAM controller, reads it all back, and
+// counts the transfers that came back wrong.  The same block works in
+// simulation (against an SDRAMSimulator) and in a bring-up bitstream for a
+// new board, where the host side of the test (in the memory_test module of
+// the host runtime) runs it over whatever link the board has.
+//
+// HLS ports
+// 0 - length (write only, 32 bits) - the number of addresses to test (a multiple of 4)
+// 1 - seed (write only, 32 bits) - the seed of the Random pattern
+// 2 - start (write only) - starts a test with the pattern in bits 1:0
+// 3 - status (read only) - bit 0 is set while a test runs, bit 1 once it is
+//     done, and bit 2 if it found errors
+// 4 - errors (read only, 32 bits) - the number of transfers that were wrong
+// 5 - first_error (read only, 32 bits) - the address of the first bad transfer
+// 6 - last_error (read only, 32 bits) - the address of the last bad transfer
+// 7 - error_bits (read only, 64 bits) - the bits that were wrong in any transfer
+// 8 - checked (read only, 32 bits) - the number of transfers that were checked
+// The results (4 to 8) can be read once for each test, after it is done.
+#[derive(LogicBlock)]
+pub struct HLSMemoryTester<const R: usize, const C: usize> {
+    pub dram: SDRAMDriver<16>,
+    pub upstream: SoCBusResponder<16, 8>,
+    local_bridge: Bridge<16, 8, 9>,
+    length: MOSIWidePort<32, 16>,
+    seed: MOSIWidePort<32, 16>,
+    start: MOSIPort<16>,
+    status: MISOPort<16>,
+    errors_out: MISOWidePort<32, 16>,
+    first_error_out: MISOWidePort<32, 16>,
+    last_error_out: MISOWidePort<32, 16>,
+    error_bits_out: MISOWidePort<64, 16>,
+    checked_out: MISOWidePort<32, 16>,
+    controller: SDRAMBaseController<R, C, 64, 16>,
+    writer: MemoryPatternGenerator,
+    checker: MemoryPatternGenerator,
+    state: DFF<State>,
+    pattern: DFF<Bits<2>>,
+    read_address: DFF<Bits<32>>,
+    read_word: DFF<Bits<64>>,
+    read_valid: DFF<Bit>,
+    errors: DFF<Bits<32>>,
+    first_error: DFF<Bits<32>>,
+    last_error: DFF<Bits<32>>,
+    error_bits: DFF<Bits<64>>,
+    checked: DFF<Bits<32>>,
+    done: DFF<Bit>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const R: usize, const C: usize> HLSMemoryTester<R, C> {
+    pub fn new(cas_delay: u32, timings: MemoryTimings, buffer: OutputBuffer) -> Self {
+        Self {
+            dram: Default::default(),
+            upstream: Default::default(),
+            local_bridge: Bridge::new([
+                "length",
+                "seed",
+                "start",
+                "status",
+                "errors",
+                "first_error",
+                "last_error",
+                "error_bits",
+                "checked",
+            ]),
+            length: Default::default(),
+            seed: Default::default(),
+            start: Default::default(),
+            status: Default::default(),
+            errors_out: Default::default(),
+            first_error_out: Default::default(),
+            last_error_out: Default::default(),
+            error_bits_out: Default::default(),
+            checked_out: Default::default(),
+            controller: SDRAMBaseController::new(cas_delay, timings, buffer),
+            writer: Default::default(),
+            checker: Default::default(),
+            state: Default::default(),
+            pattern: Default::default(),
+            read_address: Default::default(),
+            read_word: Default::default(),
+            read_valid: Default::default(),
+            errors: Default::default(),
+            first_error: Default::default(),
+            last_error: Default::default(),
+            error_bits: Default::default(),
+            checked: Default::default(),
+            done: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const R: usize, const C: usize> HLSNamedPorts for HLSMemoryTester<R, C> {
+    fn ports(&self) -> Vec<String> {
+        self.local_bridge.ports()
+    }
+}
+
+impl<const R: usize, const C: usize> Logic for HLSMemoryTester<R, C> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<16, 8>::link(&mut self.upstream, &mut self.local_bridge.upstream);
+        SDRAMDriver::<16>::link(&mut self.dram, &mut self.controller.sdram);
+        self.clock.next = self.local_bridge.clock_out.val();
+        clock!(self, clock, controller, writer, checker);
+        dff_setup!(
+            self,
+            clock,
+            state,
+            pattern,
+            read_address,
+            read_word,
+            read_valid,
+            errors,
+            first_error,
+            last_error,
+            error_bits,
+            checked,
+            done
+        );
+        SoCPortController::<16>::join(&mut self.local_bridge.nodes[0], &mut self.length.bus);
+        SoCPortController::<16>::join(&mut self.local_bridge.nodes[1], &mut self.seed.bus);
+        SoCPortController::<16>::join(&mut self.local_bridge.nodes[2], &mut self.start.bus);
+        SoCPortController::<16>::join(&mut self.local_bridge.nodes[3], &mut self.status.bus);
+        SoCPortController::<16>::join(&mut self.local_bridge.nodes[4], &mut self.errors_out.bus);
+        SoCPortController::<16>::join(
+            &mut self.local_bridge.nodes[5],
+            &mut self.first_error_out.bus,
+        );
+        SoCPortController::<16>::join(
+            &mut self.local_bridge.nodes[6],
+            &mut self.last_error_out.bus,
+        );
+        SoCPortController::<16>::join(
+            &mut self.local_bridge.nodes[7],
+            &mut self.error_bits_out.bus,
+        );
+        SoCPortController::<16>::join(&mut self.local_bridge.nodes[8], &mut self.checked_out.bus);
+        // Both generators follow the pattern of the current test
+        self.writer.pattern.next = self.pattern.q.val();
+        self.writer.seed.next = self.seed.port_out.val();
+        self.checker.pattern.next = self.pattern.q.val();
+        self.checker.seed.next = self.seed.port_out.val();
+        self.writer.restart.next = false;
+        self.writer.advance.next = false;
+        self.checker.restart.next = false;
+        self.checker.advance.next = false;
+        self.start.ready.next = false;
+        self.controller.data_in.next = self.writer.word.val();
+        self.controller.cmd_address.next = self.writer.address.val();
+        self.controller.write_not_read.next = false;
+        self.controller.cmd_strobe.next = false;
+        self.errors_out.strobe_in.next = false;
+        self.first_error_out.strobe_in.next = false;
+        self.last_error_out.strobe_in.next = false;
+        self.error_bits_out.strobe_in.next = false;
+        self.checked_out.strobe_in.next = false;
+        match self.state.q.val() {
+            State::Idle => {
+                self.start.ready.next = true;
+                if self.start.strobe_out.val() {
+                    self.pattern.d.next = self.start.port_out.val().get_bits::<2>(0);
+                    self.state.d.next = State::Starting;
+                }
+            }
+            State::Starting => {
+                self.writer.restart.next = true;
+                self.checker.restart.next = true;
+                self.read_address.d.next = 0.into();
+                self.errors.d.next = 0.into();
+                self.first_error.d.next = 0.into();
+                self.last_error.d.next = 0.into();
+                self.error_bits.d.next = 0.into();
+                self.checked.d.next = 0.into();
+                self.done.d.next = false;
+                self.state.d.next = State::Writing;
+            }
+            State::Writing => {
+                if self.writer.address.val() >= self.length.port_out.val() {
+                    if !self.controller.busy.val() {
+                        self.state.d.next = State::Reading;
+                    }
+                } else if !self.controller.busy.val() {
+                    self.controller.write_not_read.next = true;
+                    self.controller.cmd_strobe.next = true;
+                    self.writer.advance.next = true;
+                }
+            }
+            State::Reading => {
+                self.controller.cmd_address.next = self.read_address.q.val();
+                if self.read_address.q.val() >= self.length.port_out.val() {
+                    self.state.d.next = State::Draining;
+                } else if !self.controller.busy.val() {
+                    self.controller.cmd_strobe.next = true;
+                    self.read_address.d.next = self.read_address.q.val() + 4;
+                }
+            }
+            State::Draining => {
+                if self.checker.address.val() >= self.length.port_out.val() {
+                    self.errors_out.strobe_in.next = true;
+                    self.first_error_out.strobe_in.next = true;
+                    self.last_error_out.strobe_in.next = true;
+                    self.error_bits_out.strobe_in.next = true;
+                    self.checked_out.strobe_in.next = true;
+                    self.done.d.next = true;
+                    self.state.d.next = State::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = State::Idle;
+            }
+        }
+        // The data that is read back goes through a pipeline register (for
+        // timing), and is then checked against the pattern
+        self.read_word.d.next = self.controller.data_out.val();
+        self.read_valid.d.next = self.controller.data_valid.val();
+        if self.read_valid.q.val() {
+            if self.read_word.q.val() != self.checker.word.val() {
+                if self.errors.q.val() == 0 {
+                    self.first_error.d.next = self.checker.address.val();
+                }
+                self.last_error.d.next = self.checker.address.val();
+                self.errors.d.next = self.errors.q.val() + 1;
+                self.error_bits.d.next =
+                    self.error_bits.q.val() | (self.read_word.q.val() ^ self.checker.word.val());
+            }
+            self.checked.d.next = self.checked.q.val() + 1;
+            self.checker.advance.next = true;
+        }
+        self.status.port_in.next = 0.into();
+        self.status.ready_in.next = true;
+        if self.state.q.val() != State::Idle {
+            self.status.port_in.next = 1.into();
+        } else if self.done.q.val() {
+            self.status.port_in.next = 2.into();
+            if self.errors.q.val().any() {
+                self.status.port_in.next = 6.into();
+            }
+        }
+        self.errors_out.port_in.next = self.errors.q.val();
+        self.first_error_out.port_in.next = self.first_error.q.val();
+        self.last_error_out.port_in.next = self.last_error.q.val();
+        self.error_bits_out.port_in.next = self.error_bits.q.val();
+        self.checked_out.port_in.next = self.checked.q.val();
+    }
+}
+
+#[test]
+fn test_memory_tester_synthesizes() {
+    let mut uut = HLSMemoryTester::<6, 4>::new(
+        3,
+        MemoryTimings::fast_boot_sim(100e6),
+        OutputBuffer::DelayOne,
+    );
+    uut.connect_all();
+    yosys_validate("memory_tester_hls", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_memory_test_patterns() {
+    let words = MemoryTestPattern::WalkingOnes.words(66, 0);
+    assert_eq!(words[0], 1);
+    assert_eq!(words[63], 1 << 63);
+    assert_eq!(words[65], 2);
+    assert_eq!(MemoryTestPattern::WalkingZeros.words(2, 0)[1], !2);
+    assert_eq!(
+        MemoryTestPattern::AddressInAddress.words(3, 0)[2],
+        0xFFFF_FFF7_0000_0008
+    );
+    let random = MemoryTestPattern::Random.words(3, 0x1234_5678);
+    assert_eq!(random[0], 0x1234_5678_EDCB_A987);
+    assert_eq!(random[1], (random[0] >> 1) ^ LFSR_TAPS);
+}
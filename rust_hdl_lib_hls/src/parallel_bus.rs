@@ -0,0 +1,324 @@
+use crate::bus::SoCBusController;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// The pins of an asynchronous (SRAM style) parallel memory bus, as driven
+// by a microcontroller (like the FMC of an STM32, or the parallel port of
+// an ESP32).  All of the strobes are active low.  `wait_n` is driven by
+// the memory to stretch an access until it is complete.
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "ParallelBusResponder"]
+pub struct ParallelBusController<const D: usize, const A: usize> {
+    pub data: Signal<InOut, Bits<D>>,
+    pub address: Signal<Out, Bits<A>>,
+    pub cs_n: Signal<Out, Bit>,
+    pub oe_n: Signal<Out, Bit>,
+    pub we_n: Signal<Out, Bit>,
+    pub wait_n: Signal<In, Bit>,
+}
+
+// The same pins, as seen by the memory (i.e., the FPGA)
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "ParallelBusController"]
+pub struct ParallelBusResponder<const D: usize, const A: usize> {
+    pub data: Signal<InOut, Bits<D>>,
+    pub address: Signal<In, Bits<A>>,
+    pub cs_n: Signal<In, Bit>,
+    pub oe_n: Signal<In, Bit>,
+    pub we_n: Signal<In, Bit>,
+    pub wait_n: Signal<Out, Bit>,
+}
+
+#[derive(LogicState, Debug, Copy, Clone, PartialEq)]
+enum ParallelBusSlaveState {
+    Idle,
+    Read,
+    ReadHold,
+    Write,
+}
+
+// Makes the FPGA look like an asynchronous SRAM to a microcontroller.  Each
+// read or write of the microcontroller becomes a transaction on the SoC bus,
+// with the address of the access as the address on the SoC bus.  The pins
+// are asynchronous to `clock`, so the strobes, address and data are all
+// synchronized before they are used.  Writes are posted - the write is
+// done on the SoC bus after the write strobe ends.  Reads hold `wait_n` low
+// until the data is ready, so the microcontroller must be set up to wait
+// on it (e.g., the NWAIT pin of the FMC), and further accesses wait until
+// any posted write is done.  The microcontroller must leave at least 3
+// clock cycles (of `clock`) between the strobes of consecutive accesses.
+#[derive(LogicBlock, Default)]
+pub struct ParallelBusSlave<const D: usize, const A: usize> {
+    pub pins: ParallelBusResponder<D, A>,
+    pub bus: SoCBusController<D, A>,
+    pub clock: Signal<In, Clock>,
+    pins_buffer: TristateBuffer<Bits<D>>,
+    cs_sync: [DFF<Bit>; 2],
+    oe_sync: [DFF<Bit>; 2],
+    we_sync: [DFF<Bit>; 2],
+    address_sync: [DFF<Bits<A>>; 2],
+    data_sync: [DFF<Bits<D>>; 2],
+    was_reading: DFF<Bit>,
+    was_writing: DFF<Bit>,
+    write_address: DFF<Bits<A>>,
+    write_data: DFF<Bits<D>>,
+    read_data: DFF<Bits<D>>,
+    state: DFF<ParallelBusSlaveState>,
+    reading: Signal<Local, Bit>,
+    writing: Signal<Local, Bit>,
+    busy: Signal<Local, Bit>,
+}
+
+impl<const D: usize, const A: usize> Logic for ParallelBusSlave<D, A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            was_reading,
+            was_writing,
+            write_address,
+            write_data,
+            read_data,
+            state
+        );
+        for i in 0..2 {
+            self.cs_sync[i].clock.next = self.clock.val();
+            self.oe_sync[i].clock.next = self.clock.val();
+            self.we_sync[i].clock.next = self.clock.val();
+            self.address_sync[i].clock.next = self.clock.val();
+            self.data_sync[i].clock.next = self.clock.val();
+        }
+        // Synchronize everything from the pins with the same delay
+        self.cs_sync[0].d.next = self.pins.cs_n.val();
+        self.oe_sync[0].d.next = self.pins.oe_n.val();
+        self.we_sync[0].d.next = self.pins.we_n.val();
+        self.address_sync[0].d.next = self.pins.address.val();
+        self.data_sync[0].d.next = self.pins_buffer.read_data.val();
+        self.cs_sync[1].d.next = self.cs_sync[0].q.val();
+        self.oe_sync[1].d.next = self.oe_sync[0].q.val();
+        self.we_sync[1].d.next = self.we_sync[0].q.val();
+        self.address_sync[1].d.next = self.address_sync[0].q.val();
+        self.data_sync[1].d.next = self.data_sync[0].q.val();
+        self.reading.next = !self.cs_sync[1].q.val() & !self.oe_sync[1].q.val();
+        self.writing.next = !self.cs_sync[1].q.val() & !self.we_sync[1].q.val();
+        self.was_reading.d.next = self.reading.val();
+        self.was_writing.d.next = self.writing.val();
+        // Keep the last address and data seen during the write strobe
+        if self.writing.val() {
+            self.write_address.d.next = self.address_sync[1].q.val();
+            self.write_data.d.next = self.data_sync[1].q.val();
+        }
+        // Drive the data pins during a read
+        Signal::<InOut, Bits<D>>::link(&mut self.pins.data, &mut self.pins_buffer.bus);
+        self.pins_buffer.write_data.next = self.read_data.q.val();
+        self.pins_buffer.write_enable.next = !self.pins.cs_n.val() & !self.pins.oe_n.val();
+        // The SoC bus
+        self.bus.clock.next = self.clock.val();
+        self.bus.address.next = 0.into();
+        self.bus.address_strobe.next = false;
+        self.bus.from_controller.next = 0.into();
+        self.bus.strobe.next = false;
+        match self.state.q.val() {
+            ParallelBusSlaveState::Idle => {
+                if self.reading.val() & !self.was_reading.q.val() {
+                    self.bus.address.next = self.address_sync[1].q.val();
+                    self.bus.address_strobe.next = true;
+                    self.state.d.next = ParallelBusSlaveState::Read;
+                } else if !self.writing.val() & self.was_writing.q.val() {
+                    self.bus.address.next = self.write_address.q.val();
+                    self.bus.address_strobe.next = true;
+                    self.state.d.next = ParallelBusSlaveState::Write;
+                }
+            }
+            ParallelBusSlaveState::Read => {
+                if self.bus.ready.val() {
+                    self.read_data.d.next = self.bus.to_controller.val();
+                    self.bus.strobe.next = true;
+                    self.state.d.next = ParallelBusSlaveState::ReadHold;
+                }
+            }
+            ParallelBusSlaveState::ReadHold => {
+                if !self.reading.val() {
+                    self.state.d.next = ParallelBusSlaveState::Idle;
+                }
+            }
+            ParallelBusSlaveState::Write => {
+                if self.bus.ready.val() {
+                    self.bus.from_controller.next = self.write_data.q.val();
+                    self.bus.strobe.next = true;
+                    self.state.d.next = ParallelBusSlaveState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = ParallelBusSlaveState::Idle;
+            }
+        }
+        // Hold off the microcontroller until the data of a read is ready,
+        // or while a posted write is still in progress
+        self.busy.next = (self.state.q.val() == ParallelBusSlaveState::Read)
+            | (self.state.q.val() == ParallelBusSlaveState::Write);
+        self.pins.wait_n.next = !(!self.pins.cs_n.val()
+            & (self.busy.val()
+                | (!self.pins.oe_n.val()
+                    & (self.state.q.val() != ParallelBusSlaveState::ReadHold))));
+    }
+}
+
+#[test]
+fn test_parallel_bus_slave_is_synthesizable() {
+    let mut uut = ParallelBusSlave::<16, 8>::default();
+    uut.bus.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("parallel_bus_slave", &vlog).unwrap();
+}
+
+#[derive(LogicState, Debug, Copy, Clone, PartialEq)]
+enum ParallelBusMasterState {
+    Idle,
+    Setup,
+    Strobe,
+    Hold,
+}
+
+// A model of a microcontroller accessing a [ParallelBusSlave], for
+// testbenches.  Pulsing `start` begins an access of `address` - a write
+// of `write_data` if `write` is set, otherwise a read.  The access has
+// the given number of clock cycles of address setup, strobe and hold,
+// and the strobe is stretched for as long as `wait_n` is low (the strobe
+// must be long enough for the slave to assert `wait_n`, i.e., at least
+// 4 cycles of the clock of the slave).  `done` is pulsed at the end of the
+// access, and `read_data` holds the data of the last read.  The
+// `parallel_bus_read` and `parallel_bus_write` macros drive it from a testbench.
+#[derive(LogicBlock)]
+pub struct ParallelBusSimulatedMaster<const D: usize, const A: usize> {
+    pub pins: ParallelBusController<D, A>,
+    pub clock: Signal<In, Clock>,
+    pub start: Signal<In, Bit>,
+    pub write: Signal<In, Bit>,
+    pub address: Signal<In, Bits<A>>,
+    pub write_data: Signal<In, Bits<D>>,
+    pub read_data: Signal<Out, Bits<D>>,
+    pub done: Signal<Out, Bit>,
+    pins_buffer: TristateBuffer<Bits<D>>,
+    state: DFF<ParallelBusMasterState>,
+    is_write: DFF<Bit>,
+    latched_address: DFF<Bits<A>>,
+    latched_data: DFF<Bits<D>>,
+    read_latch: DFF<Bits<D>>,
+    counter: DFF<Bits<8>>,
+    setup_cycles: Constant<Bits<8>>,
+    strobe_cycles: Constant<Bits<8>>,
+    hold_cycles: Constant<Bits<8>>,
+}
+
+impl<const D: usize, const A: usize> ParallelBusSimulatedMaster<D, A> {
+    pub fn new(setup: u8, strobe: u8, hold: u8) -> Self {
+        assert!(setup > 0 && strobe > 0 && hold > 0);
+        Self {
+            pins: Default::default(),
+            clock: Default::default(),
+            start: Default::default(),
+            write: Default::default(),
+            address: Default::default(),
+            write_data: Default::default(),
+            read_data: Default::default(),
+            done: Default::default(),
+            pins_buffer: Default::default(),
+            state: Default::default(),
+            is_write: Default::default(),
+            latched_address: Default::default(),
+            latched_data: Default::default(),
+            read_latch: Default::default(),
+            counter: Default::default(),
+            setup_cycles: Constant::new((setup as u64).into()),
+            strobe_cycles: Constant::new((strobe as u64).into()),
+            hold_cycles: Constant::new((hold as u64).into()),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize> Default for ParallelBusSimulatedMaster<D, A> {
+    fn default() -> Self {
+        Self::new(2, 4, 2)
+    }
+}
+
+impl<const D: usize, const A: usize> Logic for ParallelBusSimulatedMaster<D, A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            state,
+            is_write,
+            latched_address,
+            latched_data,
+            read_latch,
+            counter
+        );
+        Signal::<InOut, Bits<D>>::link(&mut self.pins.data, &mut self.pins_buffer.bus);
+        self.pins_buffer.write_data.next = self.latched_data.q.val();
+        self.pins_buffer.write_enable.next = false;
+        self.pins.address.next = self.latched_address.q.val();
+        self.pins.cs_n.next = true;
+        self.pins.oe_n.next = true;
+        self.pins.we_n.next = true;
+        self.read_data.next = self.read_latch.q.val();
+        self.done.next = false;
+        match self.state.q.val() {
+            ParallelBusMasterState::Idle => {
+                if self.start.val() {
+                    self.is_write.d.next = self.write.val();
+                    self.latched_address.d.next = self.address.val();
+                    self.latched_data.d.next = self.write_data.val();
+                    self.counter.d.next = 1.into();
+                    self.state.d.next = ParallelBusMasterState::Setup;
+                }
+            }
+            ParallelBusMasterState::Setup => {
+                self.pins.cs_n.next = false;
+                self.pins_buffer.write_enable.next = self.is_write.q.val();
+                self.counter.d.next = self.counter.q.val() + 1;
+                if self.counter.q.val() == self.setup_cycles.val() {
+                    self.counter.d.next = 1.into();
+                    self.state.d.next = ParallelBusMasterState::Strobe;
+                }
+            }
+            ParallelBusMasterState::Strobe => {
+                self.pins.cs_n.next = false;
+                self.pins.oe_n.next = self.is_write.q.val();
+                self.pins.we_n.next = !self.is_write.q.val();
+                self.pins_buffer.write_enable.next = self.is_write.q.val();
+                if self.counter.q.val() != self.strobe_cycles.val() {
+                    self.counter.d.next = self.counter.q.val() + 1;
+                } else if self.pins.wait_n.val() {
+                    self.read_latch.d.next = self.pins_buffer.read_data.val();
+                    self.counter.d.next = 1.into();
+                    self.state.d.next = ParallelBusMasterState::Hold;
+                }
+            }
+            ParallelBusMasterState::Hold => {
+                self.pins.cs_n.next = false;
+                self.pins_buffer.write_enable.next = self.is_write.q.val();
+                self.counter.d.next = self.counter.q.val() + 1;
+                if self.counter.q.val() == self.hold_cycles.val() {
+                    self.done.next = true;
+                    self.state.d.next = ParallelBusMasterState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = ParallelBusMasterState::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_parallel_bus_master_is_synthesizable() {
+    let mut uut = ParallelBusSimulatedMaster::<16, 8>::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("parallel_bus_master", &vlog).unwrap();
+}
@@ -0,0 +1,254 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+#[derive(LogicState, Debug, Copy, Clone, PartialEq)]
+enum PinSelfTestState {
+    Idle,
+    DriveHigh,
+    DriveLow,
+}
+
+// A pin wiggle self-test, in the style of a boundary scan, for testing the
+// IO of a board in a loopback jig.  The jig wires the pins of the board
+// together in pairs, which are given (as indices into `pins`) when the
+// block is built.  Once started, the test drives each pin in turn, first
+// high and then low, with all of the other pins released, and samples all
+// of the pins (synchronized to the bus clock) after `settle` clocks at
+// each level.  A pin "follows" the driven one if it reads high with the
+// pin driven high, and low with it driven low.  Each pin gets a result
+// with three flags:
+//   bit 0 - stuck - the driven pin did not read back its own level (it is
+//           shorted to a rail, or the pad can not be driven)
+//   bit 1 - open - the pin it is wired to in the jig did not follow it
+//   bit 2 - short - some pin it is not wired to followed it
+// Pins that are not wired to anything in the jig are only checked for
+// shorts.  The released pins are left floating, so the jig (or the IO
+// cells) should pull them to a known level.  The pins are usually claimed
+// from the pin catalog of the board with `IOPlanner::request_self_test`.
+//
+// HLS ports
+// 0 - start (write only) - any write starts the test
+// 1 - status (read only) - bit 0 busy, bit 1 done, bit 2 failed (some pin has a flag set)
+// 2 - select (write only) - the pin whose result is read from port 3
+// 3 - result (read only) - the flags of the selected pin
+#[derive(LogicBlock)]
+pub struct HLSPinSelfTest<const D: usize, const A: usize, const N: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub pins: [Signal<InOut, Bit>; N],
+    bridge: Bridge<D, A, 4>,
+    start_reg: MOSIPort<D>,
+    status_reg: MISOPort<D>,
+    select_reg: MOSIPort<D>,
+    result_reg: MISOPort<D>,
+    buffers: [TristateBuffer<Bit>; N],
+    taps: [Signal<Local, Bits<N>>; N],
+    sync_0: DFF<Bits<N>>,
+    sync_1: DFF<Bits<N>>,
+    state: DFF<PinSelfTestState>,
+    index: DFF<Bits<D>>,
+    timer: DFF<Bits<16>>,
+    high: DFF<Bits<N>>,
+    select: DFF<Bits<D>>,
+    results: [DFF<Bits<3>>; N],
+    done: DFF<Bit>,
+    failed: DFF<Bit>,
+    driven: Signal<Local, Bits<N>>,
+    followed: Signal<Local, Bits<N>>,
+    wired: Signal<Local, Bits<N>>,
+    stuck: Signal<Local, Bit>,
+    open: Signal<Local, Bit>,
+    short: Signal<Local, Bit>,
+    result: Signal<Local, Bits<3>>,
+    expected: [Constant<Bits<N>>; N],
+    one: Constant<Bits<N>>,
+    settle: Constant<Bits<16>>,
+    pin_count: Constant<Bits<D>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize, const N: usize> HLSNamedPorts for HLSPinSelfTest<D, A, N> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> HLSPinSelfTest<D, A, N> {
+    pub fn new(loopback: &[(usize, usize)], settle: u64) -> Self {
+        assert!(D < 64);
+        assert!((N as u64) < (1 << D));
+        assert!(settle > 0 && settle < (1 << 16));
+        let mut expected = [Bits::<N>::default(); N];
+        for &(a, b) in loopback {
+            assert!(a < N && b < N && a != b);
+            expected[a] = expected[a].replace_bit(b, true);
+            expected[b] = expected[b].replace_bit(a, true);
+        }
+        Self {
+            upstream: Default::default(),
+            pins: array_init::array_init(|_| Default::default()),
+            bridge: Bridge::new(["start", "status", "select", "result"]),
+            start_reg: Default::default(),
+            status_reg: Default::default(),
+            select_reg: Default::default(),
+            result_reg: Default::default(),
+            buffers: array_init::array_init(|_| Default::default()),
+            taps: array_init::array_init(|_| Default::default()),
+            sync_0: Default::default(),
+            sync_1: Default::default(),
+            state: Default::default(),
+            index: Default::default(),
+            timer: Default::default(),
+            high: Default::default(),
+            select: Default::default(),
+            results: array_init::array_init(|_| Default::default()),
+            done: Default::default(),
+            failed: Default::default(),
+            driven: Default::default(),
+            followed: Default::default(),
+            wired: Default::default(),
+            stuck: Default::default(),
+            open: Default::default(),
+            short: Default::default(),
+            result: Default::default(),
+            expected: array_init::array_init(|i| Constant::new(expected[i])),
+            one: Constant::new(1.into()),
+            settle: Constant::new(settle.to_bits()),
+            pin_count: Constant::new(N.to_bits()),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> Logic for HLSPinSelfTest<D, A, N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.start_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.status_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.select_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.result_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        dff_setup!(self, clock, sync_0, sync_1, state, index, timer, high, select, done, failed);
+        for i in 0..N {
+            self.results[i].clock.next = self.clock.val();
+            self.results[i].d.next = self.results[i].q.val();
+        }
+        self.start_reg.ready.next = true;
+        self.select_reg.ready.next = true;
+        self.status_reg.ready_in.next = true;
+        self.result_reg.ready_in.next = true;
+        // Drive the selected pin (while the test runs), and release the rest
+        self.driven.next = self.one.val() << self.index.q.val();
+        for i in 0..N {
+            Signal::<InOut, Bit>::link(&mut self.pins[i], &mut self.buffers[i].bus);
+            self.buffers[i].write_enable.next = false;
+            self.buffers[i].write_data.next = self.state.q.val() == PinSelfTestState::DriveHigh;
+        }
+        // Gather the pad values into a single word, and synchronize it
+        self.taps[0].next = bit_cast::<N, 1>(self.buffers[0].read_data.val().into());
+        for i in 1..N {
+            self.taps[i].next = self.taps[i - 1]
+                .val()
+                .replace_bit(i, self.buffers[i].read_data.val());
+        }
+        for i in 0..N {
+            self.sync_0.d.next = self.taps[i].val();
+        }
+        self.sync_1.d.next = self.sync_0.q.val();
+        // The pins that followed the driven one (with the samples at both
+        // levels in hand), and the ones the jig says should have
+        self.followed.next = self.high.q.val() & !self.sync_1.q.val() & !self.driven.val();
+        self.wired.next = 0.into();
+        for i in 0..N {
+            if self.index.q.val().index() == i {
+                self.wired.next = self.expected[i].val();
+            }
+        }
+        self.stuck.next = !(self.high.q.val() & self.driven.val()).any()
+            | (self.sync_1.q.val() & self.driven.val()).any();
+        self.open.next = (self.wired.val() & !self.followed.val()).any();
+        self.short.next = (self.followed.val() & !self.wired.val()).any();
+        self.result.next = (bit_cast::<3, 1>(self.short.val().into()) << 2)
+            | (bit_cast::<3, 1>(self.open.val().into()) << 1)
+            | bit_cast::<3, 1>(self.stuck.val().into());
+        self.timer.d.next = self.timer.q.val() + 1;
+        match self.state.q.val() {
+            PinSelfTestState::Idle => {
+                self.timer.d.next = 0.into();
+            }
+            PinSelfTestState::DriveHigh => {
+                for i in 0..N {
+                    if self.index.q.val().index() == i {
+                        self.buffers[i].write_enable.next = true;
+                    }
+                }
+                if self.timer.q.val() == self.settle.val() {
+                    self.high.d.next = self.sync_1.q.val();
+                    self.timer.d.next = 0.into();
+                    self.state.d.next = PinSelfTestState::DriveLow;
+                }
+            }
+            PinSelfTestState::DriveLow => {
+                for i in 0..N {
+                    if self.index.q.val().index() == i {
+                        self.buffers[i].write_enable.next = true;
+                    }
+                }
+                if self.timer.q.val() == self.settle.val() {
+                    for i in 0..N {
+                        if self.index.q.val().index() == i {
+                            self.results[i].d.next = self.result.val();
+                        }
+                    }
+                    if self.result.val().any() {
+                        self.failed.d.next = true;
+                    }
+                    self.timer.d.next = 0.into();
+                    self.index.d.next = self.index.q.val() + 1;
+                    self.state.d.next = PinSelfTestState::DriveHigh;
+                    if self.index.q.val() + 1 == self.pin_count.val() {
+                        self.done.d.next = true;
+                        self.state.d.next = PinSelfTestState::Idle;
+                    }
+                }
+            }
+        }
+        if self.start_reg.strobe_out.val() {
+            self.index.d.next = 0.into();
+            self.timer.d.next = 0.into();
+            self.done.d.next = false;
+            self.failed.d.next = false;
+            for i in 0..N {
+                self.results[i].d.next = 0.into();
+            }
+            self.state.d.next = PinSelfTestState::DriveHigh;
+        }
+        // Report the status and results to the host
+        self.status_reg.port_in.next = (bit_cast::<D, 1>(self.failed.q.val().into()) << 2)
+            | (bit_cast::<D, 1>(self.done.q.val().into()) << 1)
+            | bit_cast::<D, 1>((self.state.q.val() != PinSelfTestState::Idle).into());
+        if self.select_reg.strobe_out.val() {
+            self.select.d.next = self.select_reg.port_out.val();
+        }
+        self.result_reg.port_in.next = 0.into();
+        for i in 0..N {
+            if self.select.q.val().index() == i {
+                self.result_reg.port_in.next = bit_cast::<D, 3>(self.results[i].q.val());
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hls_pin_self_test_is_synthesizable() {
+    let mut uut = HLSPinSelfTest::<16, 8, 6>::new(&[(0, 1), (2, 3)], 8);
+    uut.upstream.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_pin_self_test", &vlog).unwrap();
+}
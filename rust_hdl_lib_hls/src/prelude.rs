@@ -1,16 +1,33 @@
 pub use crate::bidi::{BidiBusD, BidiBusM, BidiMaster, BidiSimulatedDevice};
 pub use crate::bridge::Bridge;
+pub use crate::bringup::HLSBringUp;
 pub use crate::bus::{
     FIFOReadController, FIFOReadResponder, FIFOWriteController, FIFOWriteResponder,
     SoCBusController, SoCBusResponder, SoCPortController, SoCPortResponder,
 };
 pub use crate::bus_address_strobe;
+pub use crate::bus_monitor::{
+    FIFOBusMonitor, FIFOBusSample, HandshakeMonitor, HandshakeSample, SoCBusMonitor, SoCBusSample,
+    SoCPortMonitor, SoCPortSample,
+};
+pub use crate::bus_port_read;
+pub use crate::bus_port_write;
+pub use crate::bus_read_strobe;
 pub use crate::bus_write_strobe;
+pub use crate::capture::HLSCapture;
+pub use crate::constrained_random::{FIFOOp, FIFOOpWeights, Gaps, StimulusRng};
 pub use crate::controller::BaseController;
 pub use crate::cross_fifo::{CrossNarrow, CrossWiden};
 pub use crate::expander::Expander;
 pub use crate::fifo::{AsyncFIFO, SyncFIFO};
 pub use crate::fifo_linker::FIFOLink;
+pub use crate::fifo_stats::{FIFOInstrument, FIFOInstrumentSample, FIFOPortSample, FIFOStatistics};
+pub use crate::freq_counter::HLSFrequencyCounter;
+pub use crate::ft245::{
+    FT245Host, FT245SimulatedChip, FT245SyncBus, FT245SyncBusChip, FT245SyncFIFO,
+};
+pub use crate::gpio::HLSGPIO;
+pub use crate::histogram::HLSHistogram;
 pub use crate::hls_fifo_read;
 pub use crate::hls_fifo_read_lazy;
 pub use crate::hls_fifo_write;
@@ -24,20 +41,40 @@ pub use crate::hls_host_ping;
 pub use crate::hls_host_put_word;
 pub use crate::hls_host_write;
 pub use crate::host::Host;
+pub use crate::memory_tester::{HLSMemoryTester, MemoryPatternGenerator, MemoryTestPattern};
 pub use crate::miso_fifo_port::MISOFIFOPort;
 pub use crate::miso_port::MISOPort;
 pub use crate::miso_wide_port::MISOWidePort;
 pub use crate::mosi_fifo_port::MOSIFIFOPort;
 pub use crate::mosi_port::MOSIPort;
 pub use crate::mosi_wide_port::MOSIWidePort;
+pub use crate::parallel_bus::{
+    ParallelBusController, ParallelBusResponder, ParallelBusSimulatedMaster, ParallelBusSlave,
+};
+pub use crate::parallel_bus_read;
+pub use crate::parallel_bus_write;
+pub use crate::pin_test::HLSPinSelfTest;
+pub use crate::ram::HLSRAM;
+pub use crate::reboot::{HLSRebootController, REBOOT_KEY};
 pub use crate::reducer::Reducer;
 pub use crate::router::Router;
 pub use crate::router_rom::*;
+pub use crate::rtc::HLSRealTimeClock;
 pub use crate::sdram_controller::SDRAMController;
 pub use crate::sdram_controller_tester::SDRAMControllerTester;
 pub use crate::sdram_fifo::SDRAMFIFO;
+pub use crate::signal_gen::HLSSignalGenerator;
 pub use crate::spi::HLSSPIMaster;
 pub use crate::spi::HLSSPIMasterDynamicMode;
 pub use crate::spi::{HLSSPIMuxMasters, HLSSPIMuxSlaves};
+pub use crate::statistics::HLSStatistics;
+pub use crate::sysmon::HLSSystemMonitor;
 pub use crate::test_helpers::*;
+pub use crate::timer::HLSTimer;
+pub use crate::uart_host::UARTHost;
+pub use crate::uart_monitor::{
+    UARTMonitor, MONITOR_BAD_COUNT, MONITOR_BAD_CRC, MONITOR_BAD_OPCODE, MONITOR_MAX_COUNT,
+    MONITOR_OK, MONITOR_PEEK, MONITOR_POKE, MONITOR_STREAM, MONITOR_TIMEOUT,
+};
+pub use crate::watchdog::{HLSWatchdog, WATCHDOG_KICK_KEY};
 pub use crate::HLSNamedPorts;
@@ -0,0 +1,127 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A [RAM] of 2^N words of D bits, mapped onto the bus as an address window,
+// so that a host can load a table of coefficients, or read out a capture
+// buffer, without a port per word.  Write the first word to `address`, and
+// then write or read `data` repeatedly - the address advances to the next
+// word after each access (and wraps at the end of the RAM).  For random
+// access, write the address before each access.
+//
+// The design gets its own read port (`read_address` and `read_data`, with
+// the data a clock after the address) and write port (`write_address`,
+// `write_data` and `write_enable`), all in the bus clock domain.  The host
+// takes the write port from the design when it writes, and the read port
+// while `read` is selected, so the two should take turns using the RAM.
+//
+// HLS ports
+// 0 - address (write only) - the word to access next
+// 1 - write (write only) - the value to store at address
+// 2 - read (read only) - the value stored at address
+#[derive(LogicBlock)]
+pub struct HLSRAM<const D: usize, const A: usize, const N: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub read_address: Signal<In, Bits<N>>,
+    pub read_data: Signal<Out, Bits<D>>,
+    pub write_address: Signal<In, Bits<N>>,
+    pub write_data: Signal<In, Bits<D>>,
+    pub write_enable: Signal<In, Bit>,
+    bridge: Bridge<D, A, 3>,
+    address_reg: MOSIPort<D>,
+    write_reg: MOSIPort<D>,
+    read_reg: MISOPort<D>,
+    ram: RAM<Bits<D>, N>,
+    address: DFF<Bits<N>>,
+    // The readout lags a change of address by a clock
+    pending: DFF<Bit>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize, const N: usize> HLSNamedPorts for HLSRAM<D, A, N> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> Default for HLSRAM<D, A, N> {
+    fn default() -> Self {
+        assert!(N <= D);
+        Self {
+            upstream: Default::default(),
+            read_address: Default::default(),
+            read_data: Default::default(),
+            write_address: Default::default(),
+            write_data: Default::default(),
+            write_enable: Default::default(),
+            bridge: Bridge::new(["address", "write", "read"]),
+            address_reg: Default::default(),
+            write_reg: Default::default(),
+            read_reg: Default::default(),
+            ram: Default::default(),
+            address: Default::default(),
+            pending: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> Logic for HLSRAM<D, A, N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.address_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.write_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.read_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        dff_setup!(self, clock, address, pending);
+        self.ram.read_clock.next = self.clock.val();
+        self.ram.write_clock.next = self.clock.val();
+        self.address_reg.ready.next = true;
+        self.write_reg.ready.next = true;
+        // The design uses the RAM, unless the host does
+        self.ram.write_address.next = self.write_address.val();
+        self.ram.write_data.next = self.write_data.val();
+        self.ram.write_enable.next = self.write_enable.val();
+        self.ram.read_address.next = self.read_address.val();
+        self.read_data.next = self.ram.read_data.val();
+        // Hold off a read until the RAM has caught up with the address
+        self.pending.d.next = !self.bridge.nodes[2].select.val();
+        if self.bridge.nodes[2].select.val() {
+            self.ram.read_address.next = self.address.q.val();
+        }
+        if self.write_reg.strobe_out.val() {
+            self.ram.write_address.next = self.address.q.val();
+            self.ram.write_data.next = self.write_reg.port_out.val();
+            self.ram.write_enable.next = true;
+            self.address.d.next = self.address.q.val() + 1;
+        }
+        if self.read_reg.strobe_out.val() {
+            self.address.d.next = self.address.q.val() + 1;
+            self.pending.d.next = true;
+        }
+        if self.address_reg.strobe_out.val() {
+            self.address.d.next = bit_cast::<N, D>(self.address_reg.port_out.val());
+            self.pending.d.next = true;
+        }
+        self.read_reg.port_in.next = self.ram.read_data.val();
+        self.read_reg.ready_in.next = !self.pending.q.val();
+    }
+}
+
+#[test]
+fn test_hls_ram_is_synthesizable() {
+    let mut uut = HLSRAM::<16, 8, 8>::default();
+    uut.upstream.link_connect_dest();
+    uut.read_address.connect();
+    uut.write_address.connect();
+    uut.write_data.connect();
+    uut.write_enable.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_ram", &vlog).unwrap();
+}
@@ -0,0 +1,91 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+/// The value the host must write to an [HLSRebootController] to reboot the FPGA.
+pub const REBOOT_KEY: u64 = 0xB007;
+
+// Lets the host reboot the FPGA into another bitstream, e.g. after it
+// has written an updated image to the flash.  The host first selects
+// the image, and then writes REBOOT_KEY to the reboot register - any
+// other value is ignored, so a stray write cannot take the design down.
+// Once triggered, `boot` stays high (the FPGA is reconfigured anyway).
+// On the iCE40, `image` and `boot` drive the warm boot primitive
+// (ICE40WarmBoot in the FPGA support library).  On the ECP5, `boot`
+// should pull PROGRAMN low, and the ECP5 reloads the image selected by
+// the layout of the flash (so `image` is not used).
+//
+// HLS ports
+// 0 - image (write only) - the image to boot
+// 1 - reboot (write only) - writing REBOOT_KEY reboots into the selected image
+#[derive(LogicBlock)]
+pub struct HLSRebootController<const D: usize, const A: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub image: Signal<Out, Bits<2>>,
+    pub boot: Signal<Out, Bit>,
+    bridge: Bridge<D, A, 2>,
+    image_reg: MOSIPort<D>,
+    reboot_reg: MOSIPort<D>,
+    key: Constant<Bits<D>>,
+    selected: DFF<Bits<2>>,
+    triggered: DFF<Bit>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize> HLSNamedPorts for HLSRebootController<D, A> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize> Default for HLSRebootController<D, A> {
+    fn default() -> Self {
+        assert!(D >= 16);
+        Self {
+            upstream: Default::default(),
+            image: Default::default(),
+            boot: Default::default(),
+            bridge: Bridge::new(["image", "reboot"]),
+            image_reg: Default::default(),
+            reboot_reg: Default::default(),
+            key: Constant::new(REBOOT_KEY.into()),
+            selected: Default::default(),
+            triggered: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize> Logic for HLSRebootController<D, A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.image_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.reboot_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        dff_setup!(self, clock, selected, triggered);
+        self.image_reg.ready.next = true;
+        self.reboot_reg.ready.next = true;
+        // The image cannot change once the reboot has started
+        if self.image_reg.strobe_out.val() & !self.triggered.q.val() {
+            self.selected.d.next = self.image_reg.port_out.val().get_bits::<2>(0);
+        }
+        if self.reboot_reg.strobe_out.val() & (self.reboot_reg.port_out.val() == self.key.val()) {
+            self.triggered.d.next = true;
+        }
+        self.image.next = self.selected.q.val();
+        self.boot.next = self.triggered.q.val();
+    }
+}
+
+#[test]
+fn test_hls_reboot_controller_is_synthesizable() {
+    let mut uut = HLSRebootController::<16, 8>::default();
+    uut.upstream.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_reboot", &vlog).unwrap();
+}
@@ -0,0 +1,140 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::miso_wide_port::MISOWidePort;
+use crate::mosi_port::MOSIPort;
+use crate::mosi_wide_port::MOSIWidePort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A real time clock that the host can set and read, with a queue of
+// timestamped events.  Times are 64 bits, with the seconds in the upper
+// 32 bits, and the number of clock cycles into the second in the lower
+// 32 bits.  Wide values are written and read most significant word first.
+// Each strobe on `event` captures the current time into a FIFO (of 16
+// entries), so that bursts of events can be queued until the host gets
+// to them.  If the FIFO is full, the event is dropped and the overflow
+// flag is set.  The clock of the RTC is the bus clock, and `event` must
+// be synchronous to it.
+//
+// HLS ports
+// 0 - seconds (write only, 32 bits) - sets the time, and restarts the current second
+// 1 - snapshot (write only) - any write copies the current time into the time register
+// 2 - time (read only, 64 bits)
+// 3 - event status (read only) - bit 0 = events are queued, bit 1 = overflow (clear on read)
+// 4 - event pop (write only) - any write moves the oldest event into the event register
+// 5 - event (read only, 64 bits)
+#[derive(LogicBlock)]
+pub struct HLSRealTimeClock<const D: usize, const A: usize, const N: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub event: Signal<In, Bit>,
+    pub seconds: Signal<Out, Bits<32>>,
+    pub pps: Signal<Out, Bit>,
+    bridge: Bridge<D, A, 6>,
+    seconds_reg: MOSIWidePort<32, D>,
+    snapshot_reg: MOSIPort<D>,
+    time_reg: MISOWidePort<64, D>,
+    status_reg: MISOPort<D>,
+    pop_reg: MOSIPort<D>,
+    event_reg: MISOWidePort<64, D>,
+    rtc: RealTimeClock<N>,
+    events: SynchronousFIFO<Bits<64>, 4, 5, 1>,
+    now: Signal<Local, Bits<64>>,
+    overflow: DFF<Bit>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize, const N: usize> HLSNamedPorts for HLSRealTimeClock<D, A, N> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> HLSRealTimeClock<D, A, N> {
+    pub fn new(frequency: u64) -> Self {
+        assert!(N <= 32);
+        Self {
+            upstream: Default::default(),
+            event: Default::default(),
+            seconds: Default::default(),
+            pps: Default::default(),
+            bridge: Bridge::new([
+                "seconds",
+                "snapshot",
+                "time",
+                "event_status",
+                "event_pop",
+                "event",
+            ]),
+            seconds_reg: Default::default(),
+            snapshot_reg: Default::default(),
+            time_reg: Default::default(),
+            status_reg: Default::default(),
+            pop_reg: Default::default(),
+            event_reg: Default::default(),
+            rtc: RealTimeClock::new(frequency),
+            events: Default::default(),
+            now: Default::default(),
+            overflow: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize, const N: usize> Logic for HLSRealTimeClock<D, A, N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.seconds_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.snapshot_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.time_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.status_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[4], &mut self.pop_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[5], &mut self.event_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        self.rtc.clock.next = self.clock.val();
+        self.events.clock.next = self.clock.val();
+        dff_setup!(self, clock, overflow);
+        self.snapshot_reg.ready.next = true;
+        self.pop_reg.ready.next = true;
+        self.status_reg.ready_in.next = true;
+        // Setting the seconds
+        self.rtc.set.next = self.seconds_reg.strobe_out.val();
+        self.rtc.set_seconds.next = self.seconds_reg.port_out.val();
+        self.seconds.next = self.rtc.seconds.val();
+        self.pps.next = self.rtc.pps.val();
+        self.now.next = self
+            .rtc
+            .seconds
+            .val()
+            .concat::<32, 64>(bit_cast::<32, N>(self.rtc.ticks.val()));
+        // Reading the time
+        self.time_reg.port_in.next = self.now.val();
+        self.time_reg.strobe_in.next = self.snapshot_reg.strobe_out.val();
+        // Queueing events
+        self.events.data_in.next = self.now.val();
+        self.events.write.next = self.event.val() & !self.events.full.val();
+        if self.event.val() & self.events.full.val() {
+            self.overflow.d.next = true;
+        }
+        // Reading events
+        self.event_reg.port_in.next = self.events.data_out.val();
+        self.event_reg.strobe_in.next = self.pop_reg.strobe_out.val() & !self.events.empty.val();
+        self.events.read.next = self.pop_reg.strobe_out.val() & !self.events.empty.val();
+        self.status_reg.port_in.next = bit_cast::<D, 1>((!self.events.empty.val()).into())
+            | (bit_cast::<D, 1>(self.overflow.q.val().into()) << 1);
+        if self.status_reg.strobe_out.val() {
+            self.overflow.d.next = false;
+        }
+    }
+}
+
+#[test]
+fn test_hls_rtc_is_synthesizable() {
+    let mut dev = HLSRealTimeClock::<16, 8, 24>::new(10_000_000);
+    dev.upstream.link_connect_dest();
+    dev.connect_all();
+    let vlog = generate_verilog(&dev);
+    yosys_validate("hls_rtc", &vlog).unwrap();
+}
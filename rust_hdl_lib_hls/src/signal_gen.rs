@@ -0,0 +1,222 @@
+use crate::bridge::Bridge;
+use crate::bus::{FIFOWriteResponder, SoCBusResponder, SoCPortController};
+use crate::fifo::SyncFIFO;
+use crate::mosi_port::MOSIPort;
+use crate::mosi_wide_port::MOSIWidePort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A quadrature signal generator, built from a direct digital synthesizer
+// (a 32 bit phase accumulator driving a [SinCosROM]).  Every clock, the
+// frequency word is added to the phase accumulator, so the output
+// frequency is `frequency * f_clock / 2^32`.  The upper 16 bits of the
+// phase (plus the phase offset) are looked up in the table, and the sine
+// and cosine are scaled by the amplitude (a signed value, where 0x7FFF is
+// full scale) before they appear on `sin_out` and `cos_out`.  While the
+// generator is enabled, `strobe_out` is asserted with each new pair.
+//
+// The `am`, `fm` and `pm` streams carry modulation samples (e.g., from a
+// waveform buffer, or another generator), which are queued in a short
+// FIFO each.  One sample is taken from each queue that is not empty once
+// every (rate + 1) clocks, and held until the next one is taken.  When
+// enabled in the control register, the samples are added to the amplitude,
+// to the frequency word (sign extended), and to the phase offset,
+// respectively.  The sums wrap around, so the host should leave enough
+// headroom in the amplitude for the AM samples.  The clock of the
+// generator is the bus clock.
+//
+// HLS ports
+// 0 - control - bit 0 enable, bit 1 AM, bit 2 FM, bit 3 PM, writing a 1 to bit 4 resets the phase
+// 1 - frequency - the 32 bit frequency word, most significant word first
+// 2 - phase - the phase offset (in 1/65536ths of a turn)
+// 3 - amplitude
+// 4 - rate - the modulation samples are taken once every (rate + 1) clocks
+#[derive(LogicBlock)]
+pub struct HLSSignalGenerator<const D: usize, const A: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub am: FIFOWriteResponder<Signed<16>>,
+    pub fm: FIFOWriteResponder<Signed<16>>,
+    pub pm: FIFOWriteResponder<Signed<16>>,
+    pub sin_out: Signal<Out, Signed<16>>,
+    pub cos_out: Signal<Out, Signed<16>>,
+    pub strobe_out: Signal<Out, Bit>,
+    bridge: Bridge<D, A, 5>,
+    control_reg: MOSIPort<D>,
+    frequency_reg: MOSIWidePort<32, D>,
+    phase_reg: MOSIPort<D>,
+    amplitude_reg: MOSIPort<D>,
+    rate_reg: MOSIPort<D>,
+    am_fifo: SyncFIFO<Signed<16>, 4, 5, 1>,
+    fm_fifo: SyncFIFO<Signed<16>, 4, 5, 1>,
+    pm_fifo: SyncFIFO<Signed<16>, 4, 5, 1>,
+    am_sample: DFF<Signed<16>>,
+    fm_sample: DFF<Signed<16>>,
+    pm_sample: DFF<Signed<16>>,
+    rate_count: DFF<Bits<D>>,
+    frequency: DFF<Bits<32>>,
+    accumulator: DFF<Bits<32>>,
+    rom: SinCosROM<16, 8, 16>,
+    sin_reg: DFF<Signed<16>>,
+    cos_reg: DFF<Signed<16>>,
+    strobe: DFF<Bit>,
+    take: Signal<Local, Bit>,
+    increment: Signal<Local, Bits<32>>,
+    phase: Signal<Local, Bits<16>>,
+    amplitude: Signal<Local, Signed<16>>,
+    sin_product: Signal<Local, Signed<32>>,
+    cos_product: Signal<Local, Signed<32>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize> HLSNamedPorts for HLSSignalGenerator<D, A> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize> Default for HLSSignalGenerator<D, A> {
+    fn default() -> Self {
+        assert!(D >= 16);
+        Self {
+            upstream: Default::default(),
+            am: Default::default(),
+            fm: Default::default(),
+            pm: Default::default(),
+            sin_out: Default::default(),
+            cos_out: Default::default(),
+            strobe_out: Default::default(),
+            bridge: Bridge::new(["control", "frequency", "phase", "amplitude", "rate"]),
+            control_reg: Default::default(),
+            frequency_reg: Default::default(),
+            phase_reg: Default::default(),
+            amplitude_reg: Default::default(),
+            rate_reg: Default::default(),
+            am_fifo: Default::default(),
+            fm_fifo: Default::default(),
+            pm_fifo: Default::default(),
+            am_sample: Default::default(),
+            fm_sample: Default::default(),
+            pm_sample: Default::default(),
+            rate_count: Default::default(),
+            frequency: Default::default(),
+            accumulator: Default::default(),
+            rom: SinCosROM::new(true),
+            sin_reg: Default::default(),
+            cos_reg: Default::default(),
+            strobe: Default::default(),
+            take: Default::default(),
+            increment: Default::default(),
+            phase: Default::default(),
+            amplitude: Default::default(),
+            sin_product: Default::default(),
+            cos_product: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize> Logic for HLSSignalGenerator<D, A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.control_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.frequency_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.phase_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.amplitude_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[4], &mut self.rate_reg.bus);
+        FIFOWriteResponder::<Signed<16>>::link(&mut self.am, &mut self.am_fifo.bus_write);
+        FIFOWriteResponder::<Signed<16>>::link(&mut self.fm, &mut self.fm_fifo.bus_write);
+        FIFOWriteResponder::<Signed<16>>::link(&mut self.pm, &mut self.pm_fifo.bus_write);
+        self.clock.next = self.bridge.clock_out.val();
+        dff_setup!(
+            self,
+            clock,
+            am_sample,
+            fm_sample,
+            pm_sample,
+            rate_count,
+            frequency,
+            accumulator,
+            sin_reg,
+            cos_reg,
+            strobe
+        );
+        clock!(self, clock, am_fifo, fm_fifo, pm_fifo, rom);
+        self.control_reg.ready.next = true;
+        self.phase_reg.ready.next = true;
+        self.amplitude_reg.ready.next = true;
+        self.rate_reg.ready.next = true;
+        // The wide port shifts the words in as they arrive, so only take the
+        // frequency once all of it has been written
+        if self.frequency_reg.strobe_out.val() {
+            self.frequency.d.next = self.frequency_reg.port_out.val();
+        }
+        // Take the next sample from each of the modulation queues
+        self.take.next = self.rate_count.q.val() == self.rate_reg.port_out.val();
+        self.rate_count.d.next = self.rate_count.q.val() + 1;
+        if self.take.val() {
+            self.rate_count.d.next = 0.into();
+        }
+        self.am_fifo.bus_read.read.next = self.take.val() & !self.am_fifo.bus_read.empty.val();
+        self.fm_fifo.bus_read.read.next = self.take.val() & !self.fm_fifo.bus_read.empty.val();
+        self.pm_fifo.bus_read.read.next = self.take.val() & !self.pm_fifo.bus_read.empty.val();
+        if self.am_fifo.bus_read.read.val() {
+            self.am_sample.d.next = self.am_fifo.bus_read.data.val();
+        }
+        if self.fm_fifo.bus_read.read.val() {
+            self.fm_sample.d.next = self.fm_fifo.bus_read.data.val();
+        }
+        if self.pm_fifo.bus_read.read.val() {
+            self.pm_sample.d.next = self.pm_fifo.bus_read.data.val();
+        }
+        // Apply the modulation
+        self.increment.next = self.frequency.q.val();
+        if self.control_reg.port_out.val().get_bit(2) {
+            self.increment.next = self.frequency.q.val()
+                + unsigned_cast(signed_bit_cast::<32, 16>(self.fm_sample.q.val()));
+        }
+        self.phase.next = self.accumulator.q.val().get_bits::<16>(16)
+            + bit_cast::<16, D>(self.phase_reg.port_out.val());
+        if self.control_reg.port_out.val().get_bit(3) {
+            self.phase.next = self.accumulator.q.val().get_bits::<16>(16)
+                + bit_cast::<16, D>(self.phase_reg.port_out.val())
+                + unsigned_cast(self.pm_sample.q.val());
+        }
+        self.amplitude.next = signed_cast(bit_cast::<16, D>(self.amplitude_reg.port_out.val()));
+        if self.control_reg.port_out.val().get_bit(1) {
+            self.amplitude.next = signed_cast(bit_cast::<16, D>(self.amplitude_reg.port_out.val()))
+                + self.am_sample.q.val();
+        }
+        // Run the phase accumulator
+        if self.control_reg.port_out.val().get_bit(0) {
+            self.accumulator.d.next = self.accumulator.q.val() + self.increment.val();
+        }
+        if self.control_reg.strobe_out.val() & self.control_reg.port_out.val().get_bit(4) {
+            self.accumulator.d.next = 0.into();
+        }
+        self.rom.phase_in.next = self.phase.val();
+        self.rom.strobe_in.next = self.control_reg.port_out.val().get_bit(0);
+        // Scale the outputs by the amplitude
+        self.sin_product.next = self.rom.sin_out.val() * self.amplitude.val();
+        self.cos_product.next = self.rom.cos_out.val() * self.amplitude.val();
+        self.sin_reg.d.next = self.sin_product.val().get_bits::<16>(15);
+        self.cos_reg.d.next = self.cos_product.val().get_bits::<16>(15);
+        self.strobe.d.next = self.rom.strobe_out.val();
+        self.sin_out.next = self.sin_reg.q.val();
+        self.cos_out.next = self.cos_reg.q.val();
+        self.strobe_out.next = self.strobe.q.val();
+    }
+}
+
+#[test]
+fn test_hls_signal_generator_is_synthesizable() {
+    let mut uut = HLSSignalGenerator::<16, 8>::default();
+    uut.upstream.link_connect_dest();
+    uut.am.link_connect_dest();
+    uut.fm.link_connect_dest();
+    uut.pm.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_signal_gen", &vlog).unwrap();
+}
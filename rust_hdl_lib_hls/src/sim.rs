@@ -181,3 +181,78 @@ macro_rules! bus_write_strobe {
         $uut.$field.strobe.next = false;
     }};
 }
+
+#[macro_export]
+macro_rules! bus_read_strobe {
+    ($sim: ident, $uut: ident, $field: ident) => {{
+        wait_clock_true!($sim, $field.clock, $uut);
+        $uut = $sim.watch(|x| x.$field.ready.val(), $uut)?;
+        let val = $uut.$field.to_controller.val();
+        $uut.$field.strobe.next = true;
+        wait_clock_cycle!($sim, $field.clock, $uut);
+        $uut.$field.strobe.next = false;
+        val
+    }};
+}
+
+// Write a sequence of words to the port at the given address (waiting for
+// the port to be ready before each one)
+#[macro_export]
+macro_rules! bus_port_write {
+    ($sim: ident, $uut: ident, $field: ident, $addr: expr, $words: expr) => {{
+        bus_address_strobe!($sim, $uut, $field, $addr);
+        for word in $words {
+            $uut = $sim.watch(|x| x.$field.ready.val(), $uut)?;
+            bus_write_strobe!($sim, $uut, $field, word as u64);
+        }
+    }};
+}
+
+// Read a number of words from the port at the given address, and return
+// them packed into a u64, with the first word read in the most significant
+// position.  Reads a single word if no count is given.
+#[macro_export]
+macro_rules! bus_port_read {
+    ($sim: ident, $uut: ident, $field: ident, $addr: expr) => {
+        bus_port_read!($sim, $uut, $field, $addr, 1)
+    };
+    ($sim: ident, $uut: ident, $field: ident, $addr: expr, $count: expr) => {{
+        bus_address_strobe!($sim, $uut, $field, $addr);
+        let mut val = 0_u64;
+        for _ in 0..$count {
+            let word = bus_read_strobe!($sim, $uut, $field);
+            val = (val << word.len()) | (word.index() as u64);
+        }
+        val
+    }};
+}
+
+#[macro_export]
+macro_rules! parallel_bus_write {
+    ($sim: ident, $uut: ident, $($master: ident).+, $addr: expr, $val: expr) => {{
+        wait_clock_true!($sim, $($master).+.clock, $uut);
+        $uut.$($master).+.address.next = ($addr as u64).to_bits();
+        $uut.$($master).+.write_data.next = ($val as u64).to_bits();
+        $uut.$($master).+.write.next = true;
+        $uut.$($master).+.start.next = true;
+        wait_clock_cycle!($sim, $($master).+.clock, $uut);
+        $uut.$($master).+.start.next = false;
+        $uut = $sim.watch(|x| x.$($master).+.done.val(), $uut)?;
+        wait_clock_cycle!($sim, $($master).+.clock, $uut);
+    }};
+}
+
+#[macro_export]
+macro_rules! parallel_bus_read {
+    ($sim: ident, $uut: ident, $($master: ident).+, $addr: expr) => {{
+        wait_clock_true!($sim, $($master).+.clock, $uut);
+        $uut.$($master).+.address.next = ($addr as u64).to_bits();
+        $uut.$($master).+.write.next = false;
+        $uut.$($master).+.start.next = true;
+        wait_clock_cycle!($sim, $($master).+.clock, $uut);
+        $uut.$($master).+.start.next = false;
+        $uut = $sim.watch(|x| x.$($master).+.done.val(), $uut)?;
+        wait_clock_cycle!($sim, $($master).+.clock, $uut);
+        $uut.$($master).+.read_data.val()
+    }};
+}
@@ -0,0 +1,148 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::miso_wide_port::MISOWidePort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// Measures the mean, variance and RMS value of the signed 16 bit samples
+// presented on `data_in` (e.g., the conversions of an ADC front end) over
+// windows of 2^window samples, using [WindowedStatistics].  The results are
+// in ADC counts - the mean, standard deviation and RMS value in LSBs, and the
+// variance and mean square in LSBs squared.  To convert them to volts,
+// multiply by the LSB size (or its square).  The results of the last window
+// are held until the host writes to `latch`, which copies all of them at once
+// into the read only ports, so that they are consistent with each other.
+// The 32 bit results are read most significant word first, and must be read
+// in full after each latch.  The `windows` count advances with each completed
+// window, so the host can poll it for new results.  The samples must be in
+// the bus clock domain.
+//
+// HLS ports
+// 0 - window (write only) - the log2 of the window size (0 to 16)
+// 1 - latch (write only) - any write latches the results of the last window
+// 2 - windows (read only) - the number of windows completed (wraps around)
+// 3 - mean (read only) - signed, sign extended to the width of the bus
+// 4 - std_dev (read only)
+// 5 - rms (read only)
+// 6 - variance (read only) - 32 bits
+// 7 - mean_square (read only) - 32 bits
+#[derive(LogicBlock)]
+pub struct HLSStatistics<const D: usize, const A: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub data_in: Signal<In, Signed<16>>,
+    pub strobe_in: Signal<In, Bit>,
+    bridge: Bridge<D, A, 8>,
+    window_reg: MOSIPort<D>,
+    latch_reg: MOSIPort<D>,
+    windows_reg: MISOPort<D>,
+    mean_reg: MISOPort<D>,
+    std_dev_reg: MISOPort<D>,
+    rms_reg: MISOPort<D>,
+    variance_reg: MISOWidePort<32, D>,
+    mean_square_reg: MISOWidePort<32, D>,
+    stats: WindowedStatistics,
+    windows: DFF<Bits<D>>,
+    mean: DFF<Signed<16>>,
+    std_dev: DFF<Bits<16>>,
+    rms: DFF<Bits<16>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize> HLSNamedPorts for HLSStatistics<D, A> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize> Default for HLSStatistics<D, A> {
+    fn default() -> Self {
+        assert!(D >= 16);
+        Self {
+            upstream: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            bridge: Bridge::new([
+                "window",
+                "latch",
+                "windows",
+                "mean",
+                "std_dev",
+                "rms",
+                "variance",
+                "mean_square",
+            ]),
+            window_reg: Default::default(),
+            latch_reg: Default::default(),
+            windows_reg: Default::default(),
+            mean_reg: Default::default(),
+            std_dev_reg: Default::default(),
+            rms_reg: Default::default(),
+            variance_reg: Default::default(),
+            mean_square_reg: Default::default(),
+            stats: Default::default(),
+            windows: Default::default(),
+            mean: Default::default(),
+            std_dev: Default::default(),
+            rms: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize> Logic for HLSStatistics<D, A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.window_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.latch_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.windows_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.mean_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[4], &mut self.std_dev_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[5], &mut self.rms_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[6], &mut self.variance_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[7], &mut self.mean_square_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        clock!(self, clock, stats);
+        dff_setup!(self, clock, windows, mean, std_dev, rms);
+        self.window_reg.ready.next = true;
+        self.latch_reg.ready.next = true;
+        self.stats.data_in.next = self.data_in.val();
+        self.stats.strobe_in.next = self.strobe_in.val();
+        self.stats.shift.next = self.window_reg.port_out.val().get_bits::<8>(0);
+        if self.stats.strobe_out.val() {
+            self.windows.d.next = self.windows.q.val() + 1;
+        }
+        // Take a snapshot of the results for the host
+        if self.latch_reg.strobe_out.val() {
+            self.mean.d.next = self.stats.mean.val();
+            self.std_dev.d.next = self.stats.std_dev.val();
+            self.rms.d.next = self.stats.rms.val();
+        }
+        self.variance_reg.port_in.next = self.stats.variance.val();
+        self.variance_reg.strobe_in.next = self.latch_reg.strobe_out.val();
+        self.mean_square_reg.port_in.next = self.stats.mean_square.val();
+        self.mean_square_reg.strobe_in.next = self.latch_reg.strobe_out.val();
+        self.windows_reg.port_in.next = self.windows.q.val();
+        self.windows_reg.ready_in.next = true;
+        self.mean_reg.port_in.next = unsigned_cast(signed_bit_cast::<D, 16>(self.mean.q.val()));
+        self.mean_reg.ready_in.next = true;
+        self.std_dev_reg.port_in.next = bit_cast::<D, 16>(self.std_dev.q.val());
+        self.std_dev_reg.ready_in.next = true;
+        self.rms_reg.port_in.next = bit_cast::<D, 16>(self.rms.q.val());
+        self.rms_reg.ready_in.next = true;
+    }
+}
+
+#[test]
+fn test_hls_statistics_is_synthesizable() {
+    let mut uut = HLSStatistics::<16, 8>::default();
+    uut.upstream.link_connect_dest();
+    uut.data_in.connect();
+    uut.strobe_in.connect();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_statistics", &vlog).unwrap();
+}
@@ -0,0 +1,88 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// Makes the readings of a [SystemMonitor] (like the XADC of a Xilinx 7
+// series part, or the DTR of an ECP5) available to the host, so that
+// a deployed design can report its die temperature and supply voltages.
+// The registers hold the most recent readings, in the units of the
+// [SystemMonitor] (use [temperature_celsius] to convert the temperature).
+// The sensor must be clocked by the bus clock.
+//
+// HLS ports
+// 0 - temperature (read only) - in 1/16 K
+// 1 - vccint (read only) - in mV
+// 2 - vccaux (read only) - in mV
+#[derive(LogicBlock)]
+pub struct HLSSystemMonitor<const D: usize, const A: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub sensor: SystemMonitorReader,
+    bridge: Bridge<D, A, 3>,
+    temperature_reg: MISOPort<D>,
+    vccint_reg: MISOPort<D>,
+    vccaux_reg: MISOPort<D>,
+    temperature: DFF<Bits<16>>,
+    vccint: DFF<Bits<16>>,
+    vccaux: DFF<Bits<16>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize> HLSNamedPorts for HLSSystemMonitor<D, A> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize> Default for HLSSystemMonitor<D, A> {
+    fn default() -> Self {
+        assert!(D >= 16);
+        Self {
+            upstream: Default::default(),
+            sensor: Default::default(),
+            bridge: Bridge::new(["temperature", "vccint", "vccaux"]),
+            temperature_reg: Default::default(),
+            vccint_reg: Default::default(),
+            vccaux_reg: Default::default(),
+            temperature: Default::default(),
+            vccint: Default::default(),
+            vccaux: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize> Logic for HLSSystemMonitor<D, A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.temperature_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.vccint_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.vccaux_reg.bus);
+        self.clock.next = self.bridge.clock_out.val();
+        dff_setup!(self, clock, temperature, vccint, vccaux);
+        if self.sensor.valid.val() {
+            self.temperature.d.next = self.sensor.temperature.val();
+            self.vccint.d.next = self.sensor.vccint.val();
+            self.vccaux.d.next = self.sensor.vccaux.val();
+        }
+        self.temperature_reg.ready_in.next = true;
+        self.vccint_reg.ready_in.next = true;
+        self.vccaux_reg.ready_in.next = true;
+        self.temperature_reg.port_in.next = bit_cast::<D, 16>(self.temperature.q.val());
+        self.vccint_reg.port_in.next = bit_cast::<D, 16>(self.vccint.q.val());
+        self.vccaux_reg.port_in.next = bit_cast::<D, 16>(self.vccaux.q.val());
+    }
+}
+
+#[test]
+fn test_hls_system_monitor_is_synthesizable() {
+    let mut uut = HLSSystemMonitor::<16, 8>::default();
+    uut.upstream.link_connect_dest();
+    uut.sensor.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_sysmon", &vlog).unwrap();
+}
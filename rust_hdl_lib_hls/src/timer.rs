@@ -0,0 +1,243 @@
+use crate::bridge::Bridge;
+use crate::bus::{SoCBusResponder, SoCPortController};
+use crate::miso_port::MISOPort;
+use crate::mosi_port::MOSIPort;
+use crate::HLSNamedPorts;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// A general purpose timer, in the style of the ones found in most
+// microcontrollers, with 4 capture/compare channels.  The counter
+// ticks once every (prescaler + 1) bus clocks while it is enabled, and
+// counts from 0 up to the auto-reload value, at which point it wraps
+// back to 0 and signals an update event.  Each channel can be used for
+// compare (and PWM) or input capture, as picked by the mode register:
+//   bits 0-3  - compare - the PWM output of the channel is high while
+//               the counter is below its compare value, and a compare
+//               event fires when the counter ticks onto that value
+//   bits 4-7  - capture on a rising edge of the capture input
+//   bits 8-11 - capture on a falling edge of the capture input
+// (bit i + 4 * n is for channel i).  A capture event latches the
+// counter into the capture register of the channel.  The capture
+// inputs are synchronized to the bus clock, so a pulse on them must
+// last at least two bus clocks to be seen.  The events are strobed on
+// `update` and `events` for one clock, and latched into the interrupt
+// status register (bit 0 update, bits 1-4 the channels).  Reading the
+// status register clears it, and `irq` is asserted while any status
+// bit that is set in the interrupt enable register is pending.  The
+// clock of the timer is the bus clock.
+//
+// HLS ports
+// 0 - control - bit 0 enable, writing a 1 to bit 1 restarts the count (and the prescaler)
+// 1 - prescaler
+// 2 - auto-reload
+// 3 - counter (read only)
+// 4 - mode
+// 5 - interrupt enable
+// 6 - interrupt status (read only, clear on read)
+// 7-10 - compare value of channels 0-3
+// 11-14 - capture value of channels 0-3 (read only)
+#[derive(LogicBlock)]
+pub struct HLSTimer<const D: usize, const A: usize> {
+    pub upstream: SoCBusResponder<D, A>,
+    pub capture: Signal<In, Bits<4>>,
+    pub pwm: Signal<Out, Bits<4>>,
+    pub update: Signal<Out, Bit>,
+    pub events: Signal<Out, Bits<4>>,
+    pub irq: Signal<Out, Bit>,
+    bridge: Bridge<D, A, 15>,
+    control_reg: MOSIPort<D>,
+    prescaler_reg: MOSIPort<D>,
+    reload_reg: MOSIPort<D>,
+    counter_reg: MISOPort<D>,
+    mode_reg: MOSIPort<D>,
+    irq_enable_reg: MOSIPort<D>,
+    status_reg: MISOPort<D>,
+    compare_reg: [MOSIPort<D>; 4],
+    capture_reg: [MISOPort<D>; 4],
+    prescale: DFF<Bits<D>>,
+    counter: DFF<Bits<D>>,
+    captured: [DFF<Bits<D>>; 4],
+    sync_0: DFF<Bits<4>>,
+    sync_1: DFF<Bits<4>>,
+    previous: DFF<Bits<4>>,
+    pending: DFF<Bits<5>>,
+    tick: Signal<Local, Bit>,
+    wrap: Signal<Local, Bit>,
+    count: Signal<Local, Bits<D>>,
+    rising: Signal<Local, Bits<4>>,
+    falling: Signal<Local, Bits<4>>,
+    active: Signal<Local, Bits<4>>,
+    fired: Signal<Local, Bits<4>>,
+    flags: Signal<Local, Bits<5>>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const A: usize> HLSNamedPorts for HLSTimer<D, A> {
+    fn ports(&self) -> Vec<String> {
+        self.bridge.ports()
+    }
+}
+
+impl<const D: usize, const A: usize> Default for HLSTimer<D, A> {
+    fn default() -> Self {
+        assert!(D >= 12);
+        Self {
+            upstream: Default::default(),
+            capture: Default::default(),
+            pwm: Default::default(),
+            update: Default::default(),
+            events: Default::default(),
+            irq: Default::default(),
+            bridge: Bridge::new([
+                "control",
+                "prescaler",
+                "reload",
+                "counter",
+                "mode",
+                "irq_enable",
+                "irq_status",
+                "compare_0",
+                "compare_1",
+                "compare_2",
+                "compare_3",
+                "capture_0",
+                "capture_1",
+                "capture_2",
+                "capture_3",
+            ]),
+            control_reg: Default::default(),
+            prescaler_reg: Default::default(),
+            reload_reg: Default::default(),
+            counter_reg: Default::default(),
+            mode_reg: Default::default(),
+            irq_enable_reg: Default::default(),
+            status_reg: Default::default(),
+            compare_reg: array_init::array_init(|_| Default::default()),
+            capture_reg: array_init::array_init(|_| Default::default()),
+            prescale: Default::default(),
+            counter: Default::default(),
+            captured: array_init::array_init(|_| Default::default()),
+            sync_0: Default::default(),
+            sync_1: Default::default(),
+            previous: Default::default(),
+            pending: Default::default(),
+            tick: Default::default(),
+            wrap: Default::default(),
+            count: Default::default(),
+            rising: Default::default(),
+            falling: Default::default(),
+            active: Default::default(),
+            fired: Default::default(),
+            flags: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const A: usize> Logic for HLSTimer<D, A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCBusResponder::<D, A>::link(&mut self.upstream, &mut self.bridge.upstream);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[0], &mut self.control_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[1], &mut self.prescaler_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[2], &mut self.reload_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[3], &mut self.counter_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[4], &mut self.mode_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[5], &mut self.irq_enable_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[6], &mut self.status_reg.bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[7], &mut self.compare_reg[0].bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[8], &mut self.compare_reg[1].bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[9], &mut self.compare_reg[2].bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[10], &mut self.compare_reg[3].bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[11], &mut self.capture_reg[0].bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[12], &mut self.capture_reg[1].bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[13], &mut self.capture_reg[2].bus);
+        SoCPortController::<D>::join(&mut self.bridge.nodes[14], &mut self.capture_reg[3].bus);
+        self.clock.next = self.bridge.clock_out.val();
+        dff_setup!(self, clock, prescale, counter, sync_0, sync_1, previous, pending);
+        for i in 0..4 {
+            self.captured[i].clock.next = self.clock.val();
+            self.captured[i].d.next = self.captured[i].q.val();
+            self.compare_reg[i].ready.next = true;
+            self.capture_reg[i].ready_in.next = true;
+            self.capture_reg[i].port_in.next = self.captured[i].q.val();
+        }
+        self.control_reg.ready.next = true;
+        self.prescaler_reg.ready.next = true;
+        self.reload_reg.ready.next = true;
+        self.mode_reg.ready.next = true;
+        self.irq_enable_reg.ready.next = true;
+        self.counter_reg.ready_in.next = true;
+        self.status_reg.ready_in.next = true;
+        // The prescaler divides the bus clock down to the counter ticks
+        self.tick.next = self.control_reg.port_out.val().get_bit(0)
+            & (self.prescale.q.val() == self.prescaler_reg.port_out.val());
+        self.prescale.d.next = self.prescale.q.val() + 1;
+        if !self.control_reg.port_out.val().get_bit(0) | self.tick.val() {
+            self.prescale.d.next = 0.into();
+        }
+        self.wrap.next = self.tick.val() & (self.counter.q.val() == self.reload_reg.port_out.val());
+        if self.wrap.val() {
+            self.count.next = 0.into();
+        } else {
+            self.count.next = self.counter.q.val() + 1;
+        }
+        if self.tick.val() {
+            self.counter.d.next = self.count.val();
+        }
+        if self.control_reg.strobe_out.val() & self.control_reg.port_out.val().get_bit(1) {
+            self.prescale.d.next = 0.into();
+            self.counter.d.next = 0.into();
+        }
+        self.counter_reg.port_in.next = self.counter.q.val();
+        // Synchronize the capture inputs to the bus clock, and find their edges
+        self.sync_0.d.next = self.capture.val();
+        self.sync_1.d.next = self.sync_0.q.val();
+        self.previous.d.next = self.sync_1.q.val();
+        self.rising.next = self.sync_1.q.val() & !self.previous.q.val();
+        self.falling.next = !self.sync_1.q.val() & self.previous.q.val();
+        // Run the channels
+        self.active.next = 0.into();
+        self.fired.next = 0.into();
+        for i in 0..4 {
+            if self.mode_reg.port_out.val().get_bit(i) {
+                if self.counter.q.val() < self.compare_reg[i].port_out.val() {
+                    self.active.next = self.active.val().replace_bit(i, true);
+                }
+                if self.tick.val() & (self.count.val() == self.compare_reg[i].port_out.val()) {
+                    self.fired.next = self.fired.val().replace_bit(i, true);
+                }
+            }
+            if (self.mode_reg.port_out.val().get_bit(i + 4) & self.rising.val().get_bit(i))
+                | (self.mode_reg.port_out.val().get_bit(i + 8) & self.falling.val().get_bit(i))
+            {
+                self.captured[i].d.next = self.counter.q.val();
+                self.fired.next = self.fired.val().replace_bit(i, true);
+            }
+        }
+        self.pwm.next = self.active.val();
+        self.update.next = self.wrap.val();
+        self.events.next = self.fired.val();
+        // Latch the events into the interrupt status register
+        self.flags.next =
+            (bit_cast::<5, 4>(self.fired.val()) << 1) | bit_cast::<5, 1>(self.wrap.val().into());
+        self.status_reg.port_in.next = bit_cast::<D, 5>(self.pending.q.val());
+        if self.status_reg.strobe_out.val() {
+            self.pending.d.next = self.flags.val();
+        } else {
+            self.pending.d.next = self.pending.q.val() | self.flags.val();
+        }
+        self.irq.next =
+            (self.pending.q.val() & bit_cast::<5, D>(self.irq_enable_reg.port_out.val())).any();
+    }
+}
+
+#[test]
+fn test_hls_timer_is_synthesizable() {
+    let mut uut = HLSTimer::<16, 8>::default();
+    uut.upstream.link_connect_dest();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("hls_timer", &vlog).unwrap();
+}
@@ -0,0 +1,120 @@
+use crate::bus::{FIFOReadController, FIFOWriteController, SoCBusController};
+use crate::controller::BaseController;
+use crate::expander::Expander;
+use crate::fifo::SyncFIFO;
+use crate::reducer::Reducer;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// Like the [Host], but connects a bare UART (i.e., a pair of pins to a
+// USB serial adapter) to a Controller.  As a UART has no flow control and
+// no error detection, the words in each direction are carried in HDLC
+// frames with a CRC (see [HDLCFramer]).  Frames from the host that are
+// corrupted are dropped, and the host runtime (the `HDLCTransport`) is
+// responsible for noticing the missing replies.  Frames from the host
+// must carry no more than 254 bytes of payload to fit in the deframer.
+// Everything runs on the one clock, whose frequency is needed to set the
+// baud rate.
+#[derive(LogicBlock)]
+pub struct UARTHost<const A: usize> {
+    pub rx: Signal<In, Bit>,
+    pub tx: Signal<Out, Bit>,
+    pub bus: SoCBusController<16, A>,
+    pub clock: Signal<In, Clock>,
+    receiver: UARTReceiver,
+    deframer: HDLCDeframer<8>,
+    expander: Expander<8, 16>,
+    bus_to_controller: SyncFIFO<Bits<16>, 3, 4, 1>,
+    controller: BaseController<A>,
+    controller_to_bus: SyncFIFO<Bits<16>, 3, 4, 1>,
+    reducer: Reducer<16, 8>,
+    frame_fifo: SyncFIFO<Bits<8>, 6, 7, 1>,
+    framer: HDLCFramer,
+    transmitter: UARTTransmitter,
+}
+
+impl<const A: usize> UARTHost<A> {
+    pub fn new(order: WordOrder, clock_speed_hz: u64, baud: u64) -> Self {
+        Self {
+            rx: Default::default(),
+            tx: Default::default(),
+            bus: Default::default(),
+            clock: Default::default(),
+            receiver: UARTReceiver::new(clock_speed_hz, baud),
+            deframer: Default::default(),
+            expander: Expander::new(order),
+            bus_to_controller: Default::default(),
+            controller: Default::default(),
+            controller_to_bus: Default::default(),
+            reducer: Reducer::new(order),
+            frame_fifo: Default::default(),
+            framer: HDLCFramer::new(256),
+            transmitter: UARTTransmitter::new(clock_speed_hz, baud),
+        }
+    }
+}
+
+impl<const A: usize> Logic for UARTHost<A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(
+            self,
+            clock,
+            receiver,
+            deframer,
+            expander,
+            bus_to_controller,
+            controller,
+            controller_to_bus,
+            reducer,
+            frame_fifo,
+            framer,
+            transmitter
+        );
+        // Host to controller - UART, deframer, then widen to words
+        self.receiver.rx.next = self.rx.val();
+        self.deframer.data_in.next = self.receiver.data_out.val();
+        self.deframer.strobe_in.next = self.receiver.strobe_out.val();
+        self.expander.bus_read.data.next = self.deframer.data_out.val();
+        self.expander.bus_read.empty.next = self.deframer.empty.val();
+        self.expander.bus_read.almost_empty.next = self.deframer.empty.val();
+        self.deframer.read.next = self.expander.bus_read.read.val();
+        FIFOWriteController::<Bits<16>>::join(
+            &mut self.expander.bus_write,
+            &mut self.bus_to_controller.bus_write,
+        );
+        FIFOReadController::<Bits<16>>::join(
+            &mut self.controller.from_cpu,
+            &mut self.bus_to_controller.bus_read,
+        );
+        // Controller to host - narrow to bytes, frame, then UART
+        FIFOWriteController::<Bits<16>>::join(
+            &mut self.controller.to_cpu,
+            &mut self.controller_to_bus.bus_write,
+        );
+        FIFOReadController::<Bits<16>>::join(
+            &mut self.reducer.bus_read,
+            &mut self.controller_to_bus.bus_read,
+        );
+        FIFOWriteController::<Bits<8>>::join(
+            &mut self.reducer.bus_write,
+            &mut self.frame_fifo.bus_write,
+        );
+        self.framer.data_in.next = self.frame_fifo.bus_read.data.val();
+        self.framer.empty_in.next = self.frame_fifo.bus_read.empty.val();
+        self.frame_fifo.bus_read.read.next = self.framer.read.val();
+        self.transmitter.data_in.next = self.framer.data_out.val();
+        self.transmitter.write.next = self.framer.write.val();
+        self.framer.full.next = self.transmitter.busy.val();
+        self.tx.next = self.transmitter.tx.val();
+        SoCBusController::<16, A>::link(&mut self.bus, &mut self.controller.bus);
+    }
+}
+
+#[test]
+fn test_uart_host_synthesizes() {
+    let mut uut = UARTHost::<8>::new(WordOrder::MostSignificantFirst, 50_000_000, 115_200);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("uart_host", &vlog).unwrap();
+}
@@ -0,0 +1,453 @@
+use crate::bus::SoCBusController;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+use std::time::Duration;
+
+// Op codes for the UART monitor
+pub const MONITOR_PEEK: u8 = b'R';
+pub const MONITOR_POKE: u8 = b'W';
+pub const MONITOR_STREAM: u8 = b'S';
+// Status codes in the replies of the UART monitor
+pub const MONITOR_OK: u8 = 0;
+pub const MONITOR_BAD_CRC: u8 = 1;
+pub const MONITOR_BAD_OPCODE: u8 = 2;
+pub const MONITOR_BAD_COUNT: u8 = 3;
+pub const MONITOR_TIMEOUT: u8 = 4;
+// The most words a single command can move
+pub const MONITOR_MAX_COUNT: usize = 256;
+
+#[derive(LogicState, Debug, Copy, Clone, PartialEq)]
+enum UARTMonitorState {
+    Idle,
+    Address,
+    CountHigh,
+    CountLow,
+    DataHigh,
+    DataLow,
+    CRCHigh,
+    CRCLow,
+    Check,
+    Settle,
+    Read,
+    Write,
+    Flush,
+    ReplyStatus,
+    ReplyCountHigh,
+    ReplyCountLow,
+    ReplyDataHigh,
+    ReplyDataLow,
+    ReplyCRCHigh,
+    ReplyCRCLow,
+}
+
+// A tiny monitor that gives a host access to the SoC bus over a bare
+// UART, without the word protocol of the BaseController.  Every command
+// and every reply carries a CRC-16/CCITT-FALSE (the same one as the HDLC
+// framing), so a noisy line can never cause a write that was not asked for.
+//
+// A command is
+//    op, address, count (MSB first), [2*count bytes of data for a poke], crc (MSB first)
+// with the CRC over all of the bytes before it, and a count of 1 to 256 words.
+// * peek (R) - read count words from the address
+// * poke (W) - write count words to the address (only once the CRC checks out)
+// * stream (S) - repeat the peek, sending a reply for each block, until
+//                the host sends any byte
+// Each command gets a reply of
+//    status, words done (MSB first), [2*words bytes of data for a peek], crc (MSB first)
+// If a command stops arriving partway through (for longer than the timeout),
+// it is discarded with a timeout status.  If the addressed port is not ready
+// for longer than the timeout, the command is cut short with a timeout status,
+// and the reply says how many words were moved.  Bytes that arrive while a
+// command is being carried out are ignored (except to stop a stream).
+#[derive(LogicBlock)]
+pub struct UARTMonitor<const A: usize> {
+    pub rx: Signal<In, Bit>,
+    pub tx: Signal<Out, Bit>,
+    pub bus: SoCBusController<16, A>,
+    pub clock: Signal<In, Clock>,
+    receiver: UARTReceiver,
+    transmitter: UARTTransmitter,
+    tx_fifo: SynchronousFIFO<Bits<8>, 4, 5, 1>,
+    buffer: SynchronousFIFO<Bits<16>, 8, 9, 1>,
+    rx_crc_calc: CRC16Byte,
+    tx_crc_calc: CRC16Byte,
+    state: DFF<UARTMonitorState>,
+    opcode: DFF<Bits<8>>,
+    address: DFF<Bits<8>>,
+    count: DFF<Bits<16>>,
+    remaining: DFF<Bits<16>>,
+    done: DFF<Bits<16>>,
+    word_high: DFF<Bits<8>>,
+    rx_crc: DFFWithInit<Bits<16>>,
+    tx_crc: DFFWithInit<Bits<16>>,
+    status: DFF<Bits<8>>,
+    timer: DFF<Bits<32>>,
+    stop: DFF<Bit>,
+    receiving: Signal<Local, Bit>,
+    tx_byte: Signal<Local, Bits<8>>,
+    tx_write: Signal<Local, Bit>,
+    timeout: Constant<Bits<32>>,
+    crc_init: Constant<Bits<16>>,
+    max_count: Constant<Bits<16>>,
+    op_peek: Constant<Bits<8>>,
+    op_poke: Constant<Bits<8>>,
+    op_stream: Constant<Bits<8>>,
+    status_ok: Constant<Bits<8>>,
+    status_bad_crc: Constant<Bits<8>>,
+    status_bad_opcode: Constant<Bits<8>>,
+    status_bad_count: Constant<Bits<8>>,
+    status_timeout: Constant<Bits<8>>,
+}
+
+impl<const A: usize> UARTMonitor<A> {
+    pub fn new(clock_speed_hz: u64, baud: u64, timeout: Duration) -> Self {
+        assert!(A <= 8);
+        let clocks = timeout.as_nanos() * clock_speed_hz as u128 / 1_000_000_000;
+        assert!(clocks > 1 && clocks < (1 << 32));
+        Self {
+            rx: Default::default(),
+            tx: Default::default(),
+            bus: Default::default(),
+            clock: Default::default(),
+            receiver: UARTReceiver::new(clock_speed_hz, baud),
+            transmitter: UARTTransmitter::new(clock_speed_hz, baud),
+            tx_fifo: Default::default(),
+            buffer: Default::default(),
+            rx_crc_calc: Default::default(),
+            tx_crc_calc: Default::default(),
+            state: Default::default(),
+            opcode: Default::default(),
+            address: Default::default(),
+            count: Default::default(),
+            remaining: Default::default(),
+            done: Default::default(),
+            word_high: Default::default(),
+            rx_crc: DFFWithInit::new(HDLC_CRC_INIT.to_bits()),
+            tx_crc: DFFWithInit::new(HDLC_CRC_INIT.to_bits()),
+            status: Default::default(),
+            timer: Default::default(),
+            stop: Default::default(),
+            receiving: Default::default(),
+            tx_byte: Default::default(),
+            tx_write: Default::default(),
+            timeout: Constant::new((clocks as u64).to_bits()),
+            crc_init: Constant::new(HDLC_CRC_INIT.to_bits()),
+            max_count: Constant::new(MONITOR_MAX_COUNT.to_bits()),
+            op_peek: Constant::new(MONITOR_PEEK.to_bits()),
+            op_poke: Constant::new(MONITOR_POKE.to_bits()),
+            op_stream: Constant::new(MONITOR_STREAM.to_bits()),
+            status_ok: Constant::new(MONITOR_OK.to_bits()),
+            status_bad_crc: Constant::new(MONITOR_BAD_CRC.to_bits()),
+            status_bad_opcode: Constant::new(MONITOR_BAD_OPCODE.to_bits()),
+            status_bad_count: Constant::new(MONITOR_BAD_COUNT.to_bits()),
+            status_timeout: Constant::new(MONITOR_TIMEOUT.to_bits()),
+        }
+    }
+}
+
+impl<const A: usize> Logic for UARTMonitor<A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self, clock, state, opcode, address, count, remaining, done, word_high, rx_crc, tx_crc,
+            status, timer, stop
+        );
+        clock!(self, clock, receiver, transmitter, tx_fifo, buffer);
+        // The serial port, with a FIFO to hold the reply
+        self.receiver.rx.next = self.rx.val();
+        self.tx.next = self.transmitter.tx.val();
+        self.transmitter.data_in.next = self.tx_fifo.data_out.val();
+        self.transmitter.write.next = !self.tx_fifo.empty.val() & !self.transmitter.busy.val();
+        self.tx_fifo.read.next = !self.tx_fifo.empty.val() & !self.transmitter.busy.val();
+        // Default values
+        self.bus.clock.next = self.clock.val();
+        self.bus.address.next = self.address.q.val().get_bits::<A>(0);
+        self.bus.address_strobe.next = false;
+        self.bus.from_controller.next = self.buffer.data_out.val();
+        self.bus.strobe.next = false;
+        self.buffer.data_in.next = self.bus.to_controller.val();
+        self.buffer.write.next = false;
+        self.buffer.read.next = false;
+        // The CRC of the command, which restarts with each command
+        self.rx_crc_calc.crc_in.next = self.rx_crc.q.val();
+        if self.state.q.val() == UARTMonitorState::Idle {
+            self.rx_crc_calc.crc_in.next = self.crc_init.val();
+        }
+        self.rx_crc_calc.data.next = self.receiver.data_out.val();
+        self.receiving.next = (self.state.q.val() == UARTMonitorState::Idle)
+            | (self.state.q.val() == UARTMonitorState::Address)
+            | (self.state.q.val() == UARTMonitorState::CountHigh)
+            | (self.state.q.val() == UARTMonitorState::CountLow)
+            | (self.state.q.val() == UARTMonitorState::DataHigh)
+            | (self.state.q.val() == UARTMonitorState::DataLow)
+            | (self.state.q.val() == UARTMonitorState::CRCHigh)
+            | (self.state.q.val() == UARTMonitorState::CRCLow);
+        if self.receiving.val() {
+            if self.receiver.strobe_out.val() {
+                self.rx_crc.d.next = self.rx_crc_calc.crc_out.val();
+                self.timer.d.next = 0.into();
+            } else if self.state.q.val() != UARTMonitorState::Idle {
+                // Give up on a command that stops arriving
+                self.timer.d.next = self.timer.q.val() + 1;
+                if self.timer.q.val() == self.timeout.val() {
+                    self.status.d.next = self.status_timeout.val();
+                    self.done.d.next = 0.into();
+                    self.state.d.next = UARTMonitorState::Flush;
+                }
+            }
+        } else if self.receiver.strobe_out.val() {
+            self.stop.d.next = true;
+        }
+        // The byte of the reply to send, and the CRC of the reply
+        self.tx_byte.next = self.status.q.val();
+        match self.state.q.val() {
+            UARTMonitorState::ReplyCountHigh => {
+                self.tx_byte.next = self.done.q.val().get_bits::<8>(8);
+            }
+            UARTMonitorState::ReplyCountLow => {
+                self.tx_byte.next = self.done.q.val().get_bits::<8>(0);
+            }
+            UARTMonitorState::ReplyDataHigh => {
+                self.tx_byte.next = self.buffer.data_out.val().get_bits::<8>(8);
+            }
+            UARTMonitorState::ReplyDataLow => {
+                self.tx_byte.next = self.buffer.data_out.val().get_bits::<8>(0);
+            }
+            UARTMonitorState::ReplyCRCHigh => {
+                self.tx_byte.next = self.tx_crc.q.val().get_bits::<8>(8);
+            }
+            UARTMonitorState::ReplyCRCLow => {
+                self.tx_byte.next = self.tx_crc.q.val().get_bits::<8>(0);
+            }
+            _ => {}
+        }
+        self.tx_crc_calc.crc_in.next = self.tx_crc.q.val();
+        if self.state.q.val() == UARTMonitorState::ReplyStatus {
+            self.tx_crc_calc.crc_in.next = self.crc_init.val();
+        }
+        self.tx_crc_calc.data.next = self.tx_byte.val();
+        self.tx_write.next = false;
+        match self.state.q.val() {
+            UARTMonitorState::Idle => {
+                if self.receiver.strobe_out.val() {
+                    self.opcode.d.next = self.receiver.data_out.val();
+                    self.state.d.next = UARTMonitorState::Address;
+                }
+            }
+            UARTMonitorState::Address => {
+                if self.receiver.strobe_out.val() {
+                    self.address.d.next = self.receiver.data_out.val();
+                    self.state.d.next = UARTMonitorState::CountHigh;
+                }
+            }
+            UARTMonitorState::CountHigh => {
+                if self.receiver.strobe_out.val() {
+                    self.count.d.next = bit_cast::<16, 8>(self.receiver.data_out.val()) << 8;
+                    self.state.d.next = UARTMonitorState::CountLow;
+                }
+            }
+            UARTMonitorState::CountLow => {
+                if self.receiver.strobe_out.val() {
+                    self.count.d.next =
+                        self.count.q.val() | bit_cast::<16, 8>(self.receiver.data_out.val());
+                    self.remaining.d.next =
+                        self.count.q.val() | bit_cast::<16, 8>(self.receiver.data_out.val());
+                    if (self.opcode.q.val() == self.op_poke.val())
+                        & ((self.count.q.val() | bit_cast::<16, 8>(self.receiver.data_out.val()))
+                            .any())
+                    {
+                        self.state.d.next = UARTMonitorState::DataHigh;
+                    } else {
+                        self.state.d.next = UARTMonitorState::CRCHigh;
+                    }
+                }
+            }
+            UARTMonitorState::DataHigh => {
+                if self.receiver.strobe_out.val() {
+                    self.word_high.d.next = self.receiver.data_out.val();
+                    self.state.d.next = UARTMonitorState::DataLow;
+                }
+            }
+            UARTMonitorState::DataLow => {
+                if self.receiver.strobe_out.val() {
+                    // Hold the data until the CRC has been checked (an oversized
+                    // poke is thrown away when it overflows the buffer)
+                    self.buffer.data_in.next = (bit_cast::<16, 8>(self.word_high.q.val()) << 8)
+                        | bit_cast::<16, 8>(self.receiver.data_out.val());
+                    self.buffer.write.next = !self.buffer.full.val();
+                    self.remaining.d.next = self.remaining.q.val() - 1;
+                    if self.remaining.q.val() == 1 {
+                        self.state.d.next = UARTMonitorState::CRCHigh;
+                    } else {
+                        self.state.d.next = UARTMonitorState::DataHigh;
+                    }
+                }
+            }
+            UARTMonitorState::CRCHigh => {
+                if self.receiver.strobe_out.val() {
+                    self.state.d.next = UARTMonitorState::CRCLow;
+                }
+            }
+            UARTMonitorState::CRCLow => {
+                if self.receiver.strobe_out.val() {
+                    self.state.d.next = UARTMonitorState::Check;
+                }
+            }
+            UARTMonitorState::Check => {
+                // A good CRC leaves a zero residue
+                self.done.d.next = 0.into();
+                self.remaining.d.next = self.count.q.val();
+                self.timer.d.next = 0.into();
+                self.stop.d.next = false;
+                self.state.d.next = UARTMonitorState::Flush;
+                if self.rx_crc.q.val().any() {
+                    self.status.d.next = self.status_bad_crc.val();
+                } else if !self.count.q.val().any() | (self.count.q.val() > self.max_count.val()) {
+                    self.status.d.next = self.status_bad_count.val();
+                } else if (self.opcode.q.val() == self.op_peek.val())
+                    | (self.opcode.q.val() == self.op_poke.val())
+                    | (self.opcode.q.val() == self.op_stream.val())
+                {
+                    self.bus.address_strobe.next = true;
+                    self.state.d.next = UARTMonitorState::Settle;
+                } else {
+                    self.status.d.next = self.status_bad_opcode.val();
+                }
+            }
+            UARTMonitorState::Settle => {
+                self.status.d.next = self.status_ok.val();
+                if self.opcode.q.val() == self.op_poke.val() {
+                    self.state.d.next = UARTMonitorState::Write;
+                } else {
+                    self.state.d.next = UARTMonitorState::Read;
+                }
+            }
+            UARTMonitorState::Read => {
+                if self.bus.ready.val() & !self.buffer.full.val() {
+                    self.buffer.write.next = true;
+                    self.bus.strobe.next = true;
+                    self.done.d.next = self.done.q.val() + 1;
+                    self.remaining.d.next = self.remaining.q.val() - 1;
+                    self.timer.d.next = 0.into();
+                    if self.remaining.q.val() == 1 {
+                        self.state.d.next = UARTMonitorState::ReplyStatus;
+                    }
+                } else {
+                    self.timer.d.next = self.timer.q.val() + 1;
+                    if self.timer.q.val() == self.timeout.val() {
+                        self.status.d.next = self.status_timeout.val();
+                        self.state.d.next = UARTMonitorState::ReplyStatus;
+                    }
+                }
+            }
+            UARTMonitorState::Write => {
+                if self.bus.ready.val() & !self.buffer.empty.val() {
+                    self.buffer.read.next = true;
+                    self.bus.strobe.next = true;
+                    self.done.d.next = self.done.q.val() + 1;
+                    self.remaining.d.next = self.remaining.q.val() - 1;
+                    self.timer.d.next = 0.into();
+                    if self.remaining.q.val() == 1 {
+                        self.state.d.next = UARTMonitorState::ReplyStatus;
+                    }
+                } else {
+                    self.timer.d.next = self.timer.q.val() + 1;
+                    if self.timer.q.val() == self.timeout.val() {
+                        self.status.d.next = self.status_timeout.val();
+                        self.state.d.next = UARTMonitorState::Flush;
+                    }
+                }
+            }
+            UARTMonitorState::Flush => {
+                // Throw away any data held for a command that failed
+                if !self.buffer.empty.val() {
+                    self.buffer.read.next = true;
+                } else {
+                    self.state.d.next = UARTMonitorState::ReplyStatus;
+                }
+            }
+            UARTMonitorState::ReplyStatus => {
+                if !self.tx_fifo.full.val() {
+                    self.tx_write.next = true;
+                    self.state.d.next = UARTMonitorState::ReplyCountHigh;
+                }
+            }
+            UARTMonitorState::ReplyCountHigh => {
+                if !self.tx_fifo.full.val() {
+                    self.tx_write.next = true;
+                    self.state.d.next = UARTMonitorState::ReplyCountLow;
+                }
+            }
+            UARTMonitorState::ReplyCountLow => {
+                if !self.tx_fifo.full.val() {
+                    self.tx_write.next = true;
+                    self.remaining.d.next = self.done.q.val();
+                    if (self.opcode.q.val() != self.op_poke.val()) & self.done.q.val().any() {
+                        self.state.d.next = UARTMonitorState::ReplyDataHigh;
+                    } else {
+                        self.state.d.next = UARTMonitorState::ReplyCRCHigh;
+                    }
+                }
+            }
+            UARTMonitorState::ReplyDataHigh => {
+                if !self.tx_fifo.full.val() {
+                    self.tx_write.next = true;
+                    self.state.d.next = UARTMonitorState::ReplyDataLow;
+                }
+            }
+            UARTMonitorState::ReplyDataLow => {
+                if !self.tx_fifo.full.val() {
+                    self.tx_write.next = true;
+                    self.buffer.read.next = true;
+                    self.remaining.d.next = self.remaining.q.val() - 1;
+                    if self.remaining.q.val() == 1 {
+                        self.state.d.next = UARTMonitorState::ReplyCRCHigh;
+                    } else {
+                        self.state.d.next = UARTMonitorState::ReplyDataHigh;
+                    }
+                }
+            }
+            UARTMonitorState::ReplyCRCHigh => {
+                if !self.tx_fifo.full.val() {
+                    self.tx_write.next = true;
+                    self.state.d.next = UARTMonitorState::ReplyCRCLow;
+                }
+            }
+            UARTMonitorState::ReplyCRCLow => {
+                if !self.tx_fifo.full.val() {
+                    self.tx_write.next = true;
+                    self.state.d.next = UARTMonitorState::Idle;
+                    // Streams go around again until the host asks them to stop
+                    if (self.opcode.q.val() == self.op_stream.val())
+                        & (self.status.q.val() == self.status_ok.val())
+                        & !self.stop.q.val()
+                    {
+                        self.done.d.next = 0.into();
+                        self.remaining.d.next = self.count.q.val();
+                        self.timer.d.next = 0.into();
+                        self.state.d.next = UARTMonitorState::Read;
+                    }
+                }
+            }
+            _ => {
+                self.state.d.next = UARTMonitorState::Idle;
+            }
+        }
+        self.tx_fifo.data_in.next = self.tx_byte.val();
+        self.tx_fifo.write.next = self.tx_write.val();
+        if self.tx_write.val()
+            & (self.state.q.val() != UARTMonitorState::ReplyCRCHigh)
+            & (self.state.q.val() != UARTMonitorState::ReplyCRCLow)
+        {
+            self.tx_crc.d.next = self.tx_crc_calc.crc_out.val();
+        }
+    }
+}
+
+#[test]
+fn test_uart_monitor_synthesizes() {
+    let mut uut = UARTMonitor::<8>::new(50_000_000, 115_200, Duration::from_millis(10));
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("uart_monitor", &vlog).unwrap();
+}
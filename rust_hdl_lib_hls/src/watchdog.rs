@@ -0,0 +1,68 @@
+use crate::bus::SoCPortResponder;
+use crate::mosi_port::MOSIPort;
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+use std::time::Duration;
+
+/// The value the host must write to an [HLSWatchdog] to kick it.
+pub const WATCHDOG_KICK_KEY: u64 = 0x5A;
+
+// A watchdog that must be serviced by the host software.  The host
+// kicks it by writing WATCHDOG_KICK_KEY to the port - any other value
+// is ignored, so a runaway host that scribbles over the bus does not
+// keep the watchdog alive.  The watchdog is armed by the first kick,
+// so the design is not reset while the host software is still starting
+// up.  Once armed, `expired` is asserted if the host does not kick it
+// within the timeout, and can be used to drive the reset logic of the
+// design (which also disarms the watchdog again).  The clock of the
+// watchdog is the bus clock.
+#[derive(LogicBlock)]
+pub struct HLSWatchdog<const D: usize, const N: usize> {
+    pub bus: SoCPortResponder<D>,
+    pub expired: Signal<Out, Bit>,
+    port: MOSIPort<D>,
+    watchdog: Watchdog<N>,
+    key: Constant<Bits<D>>,
+    armed: DFF<Bit>,
+    clock: Signal<Local, Clock>,
+}
+
+impl<const D: usize, const N: usize> HLSWatchdog<D, N> {
+    pub fn new(frequency: u64, timeout: Duration) -> Self {
+        Self {
+            bus: Default::default(),
+            expired: Default::default(),
+            port: Default::default(),
+            watchdog: Watchdog::new(frequency, timeout),
+            key: Constant::new(WATCHDOG_KICK_KEY.into()),
+            armed: Default::default(),
+            clock: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const N: usize> Logic for HLSWatchdog<D, N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        SoCPortResponder::<D>::link(&mut self.bus, &mut self.port.bus);
+        self.clock.next = self.port.clock_out.val();
+        self.watchdog.clock.next = self.clock.val();
+        dff_setup!(self, clock, armed);
+        self.port.ready.next = true;
+        self.watchdog.kick.next = false;
+        if self.port.strobe_out.val() & (self.port.port_out.val() == self.key.val()) {
+            self.watchdog.kick.next = true;
+            self.armed.d.next = true;
+        }
+        self.watchdog.enable.next = self.armed.q.val();
+        self.expired.next = self.watchdog.expired.val();
+    }
+}
+
+#[test]
+fn test_hls_watchdog_is_synthesizable() {
+    let mut dev = HLSWatchdog::<16, 16>::new(100_000_000, Duration::from_micros(100));
+    dev.connect_all();
+    let vlog = generate_verilog(&dev);
+    yosys_validate("hls_watchdog", &vlog).unwrap();
+}
@@ -0,0 +1,233 @@
+use crate::device::Device;
+use crate::error::HostError;
+use crate::transport::Transport;
+use serde::{Deserialize, Serialize};
+use std::fmt::{Display, Formatter};
+
+// The host side of a board bring-up test, run against an HLSBringUp block.
+// The plan lists the pins wired to the outputs and inputs of the block (in
+// bit order), and is usually built from the pin catalog of the board, and
+// saved as JSON next to the bitstream.  The same test runs over any
+// transport - the pipes of an OpalKelly board, or a UART/FTDI stream.
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BringUpPin {
+    pub name: String,
+    pub location: String,
+}
+
+impl BringUpPin {
+    pub fn new(name: &str, location: &str) -> Self {
+        Self {
+            name: name.into(),
+            location: location.into(),
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct BringUpPlan {
+    pub outputs: Vec<BringUpPin>,
+    pub inputs: Vec<BringUpPin>,
+}
+
+impl BringUpPlan {
+    pub fn new(outputs: Vec<BringUpPin>, inputs: Vec<BringUpPin>) -> Self {
+        Self { outputs, inputs }
+    }
+    pub fn from_json(json: &str) -> Result<Self, HostError> {
+        Ok(serde_json::from_str(json)?)
+    }
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+    // A checklist (in markdown) for the person with the probe.  The steps
+    // for the outputs follow the walk of the block, and the inputs are
+    // checked by driving each one and watching the report of the test.
+    pub fn checklist(&self) -> String {
+        let mut ret = String::from("# Bring-up checklist\n\n## Outputs\n\n");
+        for (step, pin) in self.outputs.iter().enumerate() {
+            ret += &format!(
+                "- [ ] Step {}: `{}` (pin {}) is high, and every other output is low\n",
+                step, pin.name, pin.location
+            );
+        }
+        ret += "\n## Inputs\n\n";
+        for pin in &self.inputs {
+            ret += &format!(
+                "- [ ] `{}` (pin {}) reads high when driven high, and low when driven low\n",
+                pin.name, pin.location
+            );
+        }
+        ret
+    }
+}
+
+// The value of each input, sampled while each output was driven (in
+// order), and once more with all of the outputs low.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BringUpReport {
+    pub outputs: Vec<String>,
+    pub inputs: Vec<String>,
+    pub samples: Vec<Vec<bool>>,
+}
+
+impl BringUpReport {
+    // The inputs that followed an output (high only while that output was
+    // driven), which is what a loopback jumper between them looks like.
+    pub fn loopbacks(&self) -> Vec<(String, String)> {
+        let mut ret = vec![];
+        for (input_ndx, input) in self.inputs.iter().enumerate() {
+            let column = self
+                .samples
+                .iter()
+                .map(|step| step[input_ndx])
+                .collect::<Vec<_>>();
+            if column.iter().filter(|x| **x).count() != 1 {
+                continue;
+            }
+            if let Some(output_ndx) = column.iter().position(|x| *x) {
+                if output_ndx < self.outputs.len() {
+                    ret.push((self.outputs[output_ndx].clone(), input.clone()));
+                }
+            }
+        }
+        ret
+    }
+    // The inputs that read the same value throughout the test
+    pub fn constant_inputs(&self) -> Vec<(String, bool)> {
+        self.inputs
+            .iter()
+            .enumerate()
+            .filter_map(|(ndx, input)| {
+                let first = self.samples.first()?[ndx];
+                self.samples
+                    .iter()
+                    .all(|step| step[ndx] == first)
+                    .then(|| (input.clone(), first))
+            })
+            .collect()
+    }
+}
+
+impl Display for BringUpReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        for (step, values) in self.samples.iter().enumerate() {
+            let driven = self
+                .outputs
+                .get(step)
+                .map(|x| x.as_str())
+                .unwrap_or("(none)");
+            let values = values
+                .iter()
+                .map(|x| if *x { '1' } else { '0' })
+                .collect::<String>();
+            writeln!(f, "{:<16} {}", driven, values)?;
+        }
+        Ok(())
+    }
+}
+
+// Walk a one through the outputs of the HLSBringUp block named `block` (the
+// prefix it was given in the router of the design, or empty if it is not
+// behind one), and sample every input at each step.
+pub fn run_bring_up<T: Transport>(
+    device: &mut Device<T>,
+    plan: &BringUpPlan,
+    block: &str,
+) -> Result<BringUpReport, HostError> {
+    let register = |name: &str| {
+        if block.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}_{}", block, name)
+        }
+    };
+    let mut samples = vec![];
+    for step in 0..=plan.outputs.len() {
+        device.write_word(&register("walk"), step as u16)?;
+        let mut values = vec![];
+        for input in 0..plan.inputs.len() {
+            device.write_word(&register("probe"), input as u16)?;
+            values.push(device.read_word(&register("sample"))? & 1 != 0);
+        }
+        samples.push(values);
+    }
+    Ok(BringUpReport {
+        outputs: plan.outputs.iter().map(|x| x.name.clone()).collect(),
+        inputs: plan.inputs.iter().map(|x| x.name.clone()).collect(),
+        samples,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::register_map::RegisterMap;
+    use crate::sim_transport::{SimulatedBus, SimulatedTransport};
+
+    // Three outputs and three inputs, with output 1 looped back to input 2,
+    // and input 0 tied high
+    #[derive(Default)]
+    struct TestBoard {
+        walk: u16,
+        probe: u16,
+    }
+
+    impl SimulatedBus for TestBoard {
+        fn read(&mut self, _address: u8) -> u16 {
+            match self.probe {
+                0 => 1,
+                2 => (self.walk == 1) as u16,
+                _ => 0,
+            }
+        }
+        fn write(&mut self, address: u8, value: u16) {
+            match address {
+                0 => self.walk = value,
+                _ => self.probe = value,
+            }
+        }
+    }
+
+    fn make_plan() -> BringUpPlan {
+        BringUpPlan::new(
+            vec![
+                BringUpPin::new("led0", "J11"),
+                BringUpPin::new("led1", "K11"),
+                BringUpPin::new("led2", "L11"),
+            ],
+            vec![
+                BringUpPin::new("btn0", "P8"),
+                BringUpPin::new("btn1", "P9"),
+                BringUpPin::new("btn2", "P10"),
+            ],
+        )
+    }
+
+    #[test]
+    fn test_bring_up_finds_loopbacks() {
+        let map = RegisterMap::new(vec!["walk".into(), "probe".into(), "sample".into()]);
+        let mut dev = Device::new(SimulatedTransport::new(TestBoard::default()), map);
+        let report = run_bring_up(&mut dev, &make_plan(), "").unwrap();
+        assert_eq!(report.samples.len(), 4);
+        assert_eq!(
+            report.loopbacks(),
+            vec![("led1".to_string(), "btn2".to_string())]
+        );
+        assert_eq!(
+            report.constant_inputs(),
+            vec![("btn0".to_string(), true), ("btn1".to_string(), false)]
+        );
+        assert!(report.to_string().contains("led1             101"));
+    }
+
+    #[test]
+    fn test_bring_up_plan_checklist() {
+        let plan = make_plan();
+        assert_eq!(BringUpPlan::from_json(&plan.to_json()).unwrap(), plan);
+        let checklist = plan.checklist();
+        assert!(checklist.contains("Step 2: `led2` (pin L11) is high"));
+        assert!(checklist.contains("`btn1` (pin P9) reads high"));
+    }
+}
@@ -0,0 +1,191 @@
+use crate::error::HostError;
+use crate::register_map::RegisterMap;
+use crate::transport::Transport;
+
+// The op codes understood by the BaseController
+const OP_PING: u16 = 0x0100;
+const OP_READ: u16 = 0x0200;
+const OP_WRITE: u16 = 0x0300;
+const OP_POLL: u16 = 0x0400;
+const OP_STREAM: u16 = 0x0500;
+// Any non-zero word stops a stream
+const STREAM_STOP: u16 = 0xFFFF;
+
+// An HLS SoC design, as seen from the host.  Registers are addressed by
+// name (through the [RegisterMap]), and the commands are sent over any
+// [Transport], so the same control software runs against a simulation
+// and against the hardware.  Values wider than a word (like those of the
+// MISOWidePort and MOSIWidePort) are sent most significant word first.
+pub struct Device<T: Transport> {
+    transport: T,
+    map: RegisterMap,
+}
+
+impl<T: Transport> Device<T> {
+    pub fn new(transport: T, map: RegisterMap) -> Self {
+        Self { transport, map }
+    }
+    pub fn register_map(&self) -> &RegisterMap {
+        &self.map
+    }
+    pub fn transport(&mut self) -> &mut T {
+        &mut self.transport
+    }
+    pub fn ping(&mut self, id: u8) -> Result<(), HostError> {
+        let sent = OP_PING | (id as u16);
+        self.transport.send(&[sent])?;
+        let received = self.transport.receive(1)?[0];
+        if received != sent {
+            return Err(HostError::PingMismatch { sent, received });
+        }
+        Ok(())
+    }
+    pub fn write(&mut self, register: &str, data: &[u16]) -> Result<(), HostError> {
+        let address = self.map.address(register)?;
+        for chunk in data.chunks(u16::MAX as usize) {
+            let mut msg = vec![OP_WRITE | (address as u16), chunk.len() as u16];
+            msg.extend_from_slice(chunk);
+            self.transport.send(&msg)?;
+        }
+        Ok(())
+    }
+    pub fn read(&mut self, register: &str, count: usize) -> Result<Vec<u16>, HostError> {
+        let address = self.map.address(register)?;
+        let mut ret = Vec::with_capacity(count);
+        let mut remaining = count;
+        while remaining > 0 {
+            let chunk = remaining.min(u16::MAX as usize);
+            self.transport
+                .send(&[OP_READ | (address as u16), chunk as u16])?;
+            ret.extend(self.transport.receive(chunk)?);
+            remaining -= chunk;
+        }
+        Ok(ret)
+    }
+    pub fn write_word(&mut self, register: &str, value: u16) -> Result<(), HostError> {
+        self.write(register, &[value])
+    }
+    pub fn read_word(&mut self, register: &str) -> Result<u16, HostError> {
+        Ok(self.read(register, 1)?[0])
+    }
+    pub fn write_u32(&mut self, register: &str, value: u32) -> Result<(), HostError> {
+        self.write(register, &[(value >> 16) as u16, value as u16])
+    }
+    pub fn read_u32(&mut self, register: &str) -> Result<u32, HostError> {
+        let words = self.read(register, 2)?;
+        Ok(((words[0] as u32) << 16) | (words[1] as u32))
+    }
+    pub fn write_u64(&mut self, register: &str, value: u64) -> Result<(), HostError> {
+        let words = [
+            (value >> 48) as u16,
+            (value >> 32) as u16,
+            (value >> 16) as u16,
+            value as u16,
+        ];
+        self.write(register, &words)
+    }
+    pub fn read_u64(&mut self, register: &str) -> Result<u64, HostError> {
+        let words = self.read(register, 4)?;
+        Ok(words
+            .iter()
+            .fold(0_u64, |acc, word| (acc << 16) | (*word as u64)))
+    }
+    // Check if the register has data (or space) without waiting for it
+    pub fn poll(&mut self, register: &str) -> Result<bool, HostError> {
+        let address = self.map.address(register)?;
+        self.transport.send(&[OP_POLL | (address as u16)])?;
+        Ok(self.transport.receive(1)?[0] & 1 != 0)
+    }
+    // Streaming reads the register over and over, for as long as it has
+    // data, until the stream is stopped.  No other commands can be sent
+    // while streaming.
+    pub fn start_stream(&mut self, register: &str) -> Result<(), HostError> {
+        let address = self.map.address(register)?;
+        self.transport.send(&[OP_STREAM | (address as u16)])
+    }
+    pub fn read_stream(&mut self, count: usize) -> Result<Vec<u16>, HostError> {
+        self.transport.receive(count)
+    }
+    pub fn stop_stream(&mut self) -> Result<(), HostError> {
+        self.transport.send(&[STREAM_STOP])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::sim_transport::{SimulatedBus, SimulatedTransport};
+    use std::collections::VecDeque;
+
+    // A scratch register, a 32 bit counter and a FIFO
+    #[derive(Default)]
+    struct TestBus {
+        scratch: u16,
+        counter: u32,
+        counter_words: VecDeque<u16>,
+        fifo: VecDeque<u16>,
+    }
+
+    impl SimulatedBus for TestBus {
+        fn read(&mut self, address: u8) -> u16 {
+            match address {
+                0 => self.scratch,
+                1 => {
+                    if self.counter_words.is_empty() {
+                        self.counter_words
+                            .extend([(self.counter >> 16) as u16, self.counter as u16]);
+                    }
+                    self.counter_words.pop_front().unwrap()
+                }
+                _ => self.fifo.pop_front().unwrap_or_default(),
+            }
+        }
+        fn write(&mut self, address: u8, value: u16) {
+            match address {
+                0 => self.scratch = value,
+                _ => self.fifo.push_back(value),
+            }
+        }
+        fn ready(&mut self, address: u8) -> bool {
+            address != 2 || !self.fifo.is_empty()
+        }
+    }
+
+    fn make_device() -> Device<SimulatedTransport<TestBus>> {
+        let map = RegisterMap::new(vec!["scratch".into(), "counter".into(), "fifo".into()]);
+        let bus = TestBus {
+            counter: 0xDEAD_BEEF,
+            ..Default::default()
+        };
+        Device::new(SimulatedTransport::new(bus), map)
+    }
+
+    #[test]
+    fn test_device_reads_and_writes_registers() {
+        let mut dev = make_device();
+        dev.ping(0x42).unwrap();
+        dev.write_word("scratch", 0x1234).unwrap();
+        assert_eq!(dev.read_word("scratch").unwrap(), 0x1234);
+        assert_eq!(dev.read_u32("counter").unwrap(), 0xDEAD_BEEF);
+        assert!(!dev.poll("fifo").unwrap());
+        dev.write("fifo", &[1, 2, 3, 4]).unwrap();
+        assert!(dev.poll("fifo").unwrap());
+        assert_eq!(dev.read("fifo", 2).unwrap(), vec![1, 2]);
+        dev.start_stream("fifo").unwrap();
+        assert_eq!(dev.read_stream(2).unwrap(), vec![3, 4]);
+        assert!(dev.read_stream(1).is_err());
+        dev.stop_stream().unwrap();
+        assert!(matches!(
+            dev.read_word("missing"),
+            Err(HostError::UnknownRegister(_))
+        ));
+    }
+
+    #[test]
+    fn test_register_map_round_trips_through_json() {
+        let map = RegisterMap::new(vec!["gpio_out".into(), "gpio_in".into()]);
+        let json = map.to_json();
+        assert_eq!(RegisterMap::from_json(&json).unwrap(), map);
+        assert_eq!(map.address("gpio_in").unwrap(), 1);
+    }
+}
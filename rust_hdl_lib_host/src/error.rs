@@ -0,0 +1,45 @@
+use std::fmt::{Display, Formatter};
+
+#[derive(Debug)]
+pub enum HostError {
+    // The transport failed (the message comes from the transport)
+    Transport(String),
+    Io(std::io::Error),
+    Json(serde_json::Error),
+    UnknownRegister(String),
+    // The device did not echo the ping back
+    PingMismatch { sent: u16, received: u16 },
+    // The register is not ready (from a poll)
+    NotReady(String),
+}
+
+impl Display for HostError {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        match self {
+            HostError::Transport(msg) => write!(f, "Transport error: {}", msg),
+            HostError::Io(err) => write!(f, "I/O error: {}", err),
+            HostError::Json(err) => write!(f, "Register map error: {}", err),
+            HostError::UnknownRegister(name) => write!(f, "Unknown register {}", name),
+            HostError::PingMismatch { sent, received } => write!(
+                f,
+                "Ping mismatch: sent {:04x} but received {:04x}",
+                sent, received
+            ),
+            HostError::NotReady(name) => write!(f, "Register {} is not ready", name),
+        }
+    }
+}
+
+impl std::error::Error for HostError {}
+
+impl From<std::io::Error> for HostError {
+    fn from(err: std::io::Error) -> Self {
+        HostError::Io(err)
+    }
+}
+
+impl From<serde_json::Error> for HostError {
+    fn from(err: serde_json::Error) -> Self {
+        HostError::Json(err)
+    }
+}
@@ -0,0 +1,232 @@
+use crate::error::HostError;
+use crate::transport::Transport;
+use rust_hdl_lib_widgets::prelude::{
+    WordOrder, HDLC_CRC_INIT, HDLC_ESCAPE, HDLC_ESCAPE_XOR, HDLC_FLAG,
+};
+use std::collections::VecDeque;
+use std::io::{Read, Write};
+
+// The frame check sequence used by the HDLCFramer and HDLCDeframer in
+// hardware (CRC-16/CCITT-FALSE).
+pub fn hdlc_crc(bytes: &[u8]) -> u16 {
+    bytes.iter().fold(HDLC_CRC_INIT, |crc, byte| {
+        (0..8).fold(crc ^ ((*byte as u16) << 8), |crc, _| {
+            if crc & 0x8000 != 0 {
+                (crc << 1) ^ 0x1021
+            } else {
+                crc << 1
+            }
+        })
+    })
+}
+
+// Wrap a payload in a frame (flag, escaped payload and CRC, flag)
+pub fn hdlc_encode(payload: &[u8]) -> Vec<u8> {
+    let crc = hdlc_crc(payload);
+    let mut frame = vec![HDLC_FLAG];
+    for byte in payload.iter().chain(crc.to_be_bytes().iter()) {
+        if *byte == HDLC_FLAG || *byte == HDLC_ESCAPE {
+            frame.push(HDLC_ESCAPE);
+            frame.push(byte ^ HDLC_ESCAPE_XOR);
+        } else {
+            frame.push(*byte);
+        }
+    }
+    frame.push(HDLC_FLAG);
+    frame
+}
+
+// Recovers payloads from a stream of bytes, one byte at a time.
+#[derive(Default)]
+pub struct HDLCDecoder {
+    frame: Vec<u8>,
+    escaped: bool,
+}
+
+impl HDLCDecoder {
+    // Returns the payload of a frame when its closing flag arrives, or an
+    // error if the frame was corrupted.  Empty frames are ignored.
+    pub fn feed(&mut self, byte: u8) -> Option<Result<Vec<u8>, HostError>> {
+        match byte {
+            HDLC_FLAG => {
+                self.escaped = false;
+                let mut frame = std::mem::take(&mut self.frame);
+                if frame.is_empty() {
+                    None
+                } else if frame.len() < 3 || hdlc_crc(&frame) != 0 {
+                    Some(Err(HostError::Transport(format!(
+                        "Dropped a corrupted frame of {} bytes",
+                        frame.len()
+                    ))))
+                } else {
+                    frame.truncate(frame.len() - 2);
+                    Some(Ok(frame))
+                }
+            }
+            HDLC_ESCAPE => {
+                self.escaped = true;
+                None
+            }
+            _ => {
+                self.frame.push(if self.escaped {
+                    byte ^ HDLC_ESCAPE_XOR
+                } else {
+                    byte
+                });
+                self.escaped = false;
+                None
+            }
+        }
+    }
+}
+
+// A transport over a byte stream (usually a serial port) to a design that
+// uses a UARTHost.  Words are sent in HDLC frames of up to `max_payload`
+// bytes, and the replies are checked as they arrive.  A corrupted reply
+// is reported as a transport error - the words it carried are lost, so
+// the caller should drain the link and retry the transaction.
+pub struct HDLCTransport<T: Read + Write> {
+    stream: T,
+    order: WordOrder,
+    max_payload: usize,
+    decoder: HDLCDecoder,
+    received: VecDeque<u8>,
+}
+
+impl<T: Read + Write> HDLCTransport<T> {
+    pub fn new(stream: T, order: WordOrder) -> Self {
+        Self::with_max_payload(stream, order, 64)
+    }
+    // The UARTHost can buffer frames with up to 254 bytes of payload
+    pub fn with_max_payload(stream: T, order: WordOrder, max_payload: usize) -> Self {
+        assert!((2..=254).contains(&max_payload));
+        Self {
+            stream,
+            order,
+            max_payload: max_payload & !1,
+            decoder: Default::default(),
+            received: Default::default(),
+        }
+    }
+    pub fn into_inner(self) -> T {
+        self.stream
+    }
+}
+
+impl<T: Read + Write> Transport for HDLCTransport<T> {
+    fn send(&mut self, words: &[u16]) -> Result<(), HostError> {
+        let bytes = words
+            .iter()
+            .flat_map(|word| match self.order {
+                WordOrder::MostSignificantFirst => word.to_be_bytes(),
+                WordOrder::LeastSignificantFirst => word.to_le_bytes(),
+            })
+            .collect::<Vec<_>>();
+        for chunk in bytes.chunks(self.max_payload) {
+            self.stream.write_all(&hdlc_encode(chunk))?;
+        }
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self, count: usize) -> Result<Vec<u16>, HostError> {
+        let mut buffer = [0_u8; 64];
+        while self.received.len() < count * 2 {
+            let len = self.stream.read(&mut buffer)?;
+            if len == 0 {
+                return Err(std::io::Error::from(std::io::ErrorKind::UnexpectedEof).into());
+            }
+            for byte in &buffer[..len] {
+                if let Some(payload) = self.decoder.feed(*byte) {
+                    self.received.extend(payload?);
+                }
+            }
+        }
+        let bytes = self.received.drain(..count * 2).collect::<Vec<_>>();
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|pair| match self.order {
+                WordOrder::MostSignificantFirst => u16::from_be_bytes([pair[0], pair[1]]),
+                WordOrder::LeastSignificantFirst => u16::from_le_bytes([pair[0], pair[1]]),
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // A stream that records what is written, and replays canned bytes
+    struct Loopback {
+        written: Vec<u8>,
+        to_read: VecDeque<u8>,
+    }
+
+    impl Read for Loopback {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.to_read.len());
+            for (dest, src) in buf.iter_mut().zip(self.to_read.drain(..len)) {
+                *dest = src;
+            }
+            Ok(len)
+        }
+    }
+
+    impl Write for Loopback {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_hdlc_crc_check_value() {
+        assert_eq!(hdlc_crc(b"123456789"), 0x29B1);
+    }
+
+    #[test]
+    fn test_hdlc_frames_round_trip() {
+        let payload = [0x7E, 0x01, 0x7D, 0x5E, 0xFF, 0x7E];
+        let frame = hdlc_encode(&payload);
+        assert_eq!(frame.iter().filter(|x| **x == HDLC_FLAG).count(), 2);
+        let mut decoder = HDLCDecoder::default();
+        let mut decoded = frame.iter().filter_map(|x| decoder.feed(*x));
+        assert_eq!(decoded.next().unwrap().unwrap(), payload.to_vec());
+        // Any flipped bit is caught
+        for bit in 8..(frame.len() - 1) * 8 {
+            let mut bad = frame.clone();
+            bad[bit / 8] ^= 1 << (bit % 8);
+            let mut decoder = HDLCDecoder::default();
+            let results = bad
+                .iter()
+                .filter_map(|x| decoder.feed(*x))
+                .collect::<Vec<_>>();
+            assert!(!results.is_empty() && results.iter().all(|x| x.is_err()));
+        }
+    }
+
+    #[test]
+    fn test_hdlc_transport_splits_and_joins_frames() {
+        let reply = [0x1234_u16, 0x7E7D, 0xABCD];
+        let mut to_read = VecDeque::new();
+        to_read.extend(hdlc_encode(&[0x12, 0x34, 0x7E]));
+        to_read.extend(hdlc_encode(&[0x7D, 0xAB, 0xCD]));
+        let stream = Loopback {
+            written: vec![],
+            to_read,
+        };
+        let mut transport =
+            HDLCTransport::with_max_payload(stream, WordOrder::MostSignificantFirst, 4);
+        transport.send(&[0x0102, 0x0304, 0x0506]).unwrap();
+        assert_eq!(transport.receive(3).unwrap(), reply);
+        assert!(transport.receive(1).is_err());
+        let stream = transport.into_inner();
+        let mut expected = hdlc_encode(&[1, 2, 3, 4]);
+        expected.extend(hdlc_encode(&[5, 6]));
+        assert_eq!(stream.written, expected);
+    }
+}
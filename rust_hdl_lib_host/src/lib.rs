@@ -0,0 +1,11 @@
+pub mod bringup;
+pub mod device;
+pub mod error;
+pub mod hdlc_transport;
+pub mod memory_test;
+pub mod monitor;
+pub mod prelude;
+pub mod register_map;
+pub mod sim_transport;
+pub mod stream_transport;
+pub mod transport;
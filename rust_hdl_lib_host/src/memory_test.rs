@@ -0,0 +1,259 @@
+use crate::device::Device;
+use crate::error::HostError;
+use crate::transport::Transport;
+use rust_hdl_lib_hls::memory_tester::MemoryTestPattern;
+use std::fmt::{Display, Formatter};
+
+// The host side of a memory test, run against an HLSMemoryTester block.
+// The test writes the pattern over the first `length` addresses of the
+// memory, reads it back, and reports what came back wrong.  Each pattern
+// finds different faults - the walking patterns find stuck or shorted data
+// lines, the address pattern finds stuck or shorted address lines, and the
+// random pattern (with a few different seeds) finds the rest.
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct MemoryTestReport {
+    pub pattern: MemoryTestPattern,
+    pub seed: u32,
+    // The number of 64 bit transfers that were checked, and that were wrong
+    pub checked: u32,
+    pub errors: u32,
+    // The addresses of the first and last bad transfers (if there were any)
+    pub first_error: Option<u32>,
+    pub last_error: Option<u32>,
+    // The bits that were wrong in any of the transfers
+    pub error_bits: u64,
+}
+
+impl MemoryTestReport {
+    pub fn passed(&self) -> bool {
+        self.errors == 0
+    }
+    // The data lines that were wrong, given the width of the memory.  Each
+    // transfer is made of 64/width words, so a bad line shows up once in
+    // each of them.
+    pub fn bad_data_lines(&self, width: usize) -> Vec<usize> {
+        (0..width)
+            .filter(|line| {
+                (0..64 / width).any(|word| self.error_bits & (1 << (word * width + line)) != 0)
+            })
+            .collect()
+    }
+}
+
+impl Display for MemoryTestReport {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{:?} (seed {:08x}): ", self.pattern, self.seed)?;
+        match (self.first_error, self.last_error) {
+            (Some(first), Some(last)) if !self.passed() => write!(
+                f,
+                "{} of {} transfers failed, from address {:08x} to {:08x}, bad bits {:016x}",
+                self.errors, self.checked, first, last, self.error_bits
+            ),
+            _ => write!(f, "{} transfers passed", self.checked),
+        }
+    }
+}
+
+// Run one pattern over the first `length` addresses with the HLSMemoryTester
+// block named `block` (the prefix it was given in the router of the design,
+// or empty if it is not behind one).  Waits for the test to finish.
+pub fn run_memory_test<T: Transport>(
+    device: &mut Device<T>,
+    block: &str,
+    pattern: MemoryTestPattern,
+    length: u32,
+    seed: u32,
+) -> Result<MemoryTestReport, HostError> {
+    let register = |name: &str| {
+        if block.is_empty() {
+            name.to_string()
+        } else {
+            format!("{}_{}", block, name)
+        }
+    };
+    device.write_u32(&register("length"), length)?;
+    device.write_u32(&register("seed"), seed)?;
+    device.write_word(&register("start"), pattern as u16)?;
+    while device.read_word(&register("status"))? & 3 != 2 {}
+    let errors = device.read_u32(&register("errors"))?;
+    let first_error = device.read_u32(&register("first_error"))?;
+    let last_error = device.read_u32(&register("last_error"))?;
+    let error_bits = device.read_u64(&register("error_bits"))?;
+    let checked = device.read_u32(&register("checked"))?;
+    Ok(MemoryTestReport {
+        pattern,
+        seed,
+        checked,
+        errors,
+        first_error: (errors != 0).then_some(first_error),
+        last_error: (errors != 0).then_some(last_error),
+        error_bits,
+    })
+}
+
+// Run all of the patterns (the random one once for each seed), and stop at
+// the first failure.
+pub fn run_memory_tests<T: Transport>(
+    device: &mut Device<T>,
+    block: &str,
+    length: u32,
+    seeds: &[u32],
+) -> Result<Vec<MemoryTestReport>, HostError> {
+    let mut tests = vec![
+        (MemoryTestPattern::WalkingOnes, 0),
+        (MemoryTestPattern::WalkingZeros, 0),
+        (MemoryTestPattern::AddressInAddress, 0),
+    ];
+    tests.extend(seeds.iter().map(|seed| (MemoryTestPattern::Random, *seed)));
+    let mut ret = vec![];
+    for (pattern, seed) in tests {
+        let report = run_memory_test(device, block, pattern, length, seed)?;
+        let passed = report.passed();
+        ret.push(report);
+        if !passed {
+            break;
+        }
+    }
+    Ok(ret)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::register_map::RegisterMap;
+    use crate::sim_transport::{SimulatedBus, SimulatedTransport};
+
+    // A model of the tester, over a 16 bit wide memory with data line 5
+    // stuck high.  The test completes after a few reads of the status.
+    #[derive(Default)]
+    struct TestTester {
+        length: Vec<u16>,
+        seed: Vec<u16>,
+        pattern: u16,
+        polls: u32,
+        results: Vec<u16>,
+    }
+
+    impl TestTester {
+        fn run(&mut self) {
+            let length = ((self.length[0] as usize) << 16) | (self.length[1] as usize);
+            let seed = ((self.seed[0] as u32) << 16) | (self.seed[1] as u32);
+            let pattern = [
+                MemoryTestPattern::WalkingOnes,
+                MemoryTestPattern::WalkingZeros,
+                MemoryTestPattern::AddressInAddress,
+                MemoryTestPattern::Random,
+            ][self.pattern as usize];
+            let stuck = 0x0020_0020_0020_0020_u64;
+            let bad = pattern
+                .words(length / 4, seed)
+                .into_iter()
+                .enumerate()
+                .filter(|(_, word)| word | stuck != *word)
+                .map(|(ndx, _)| ndx as u32 * 4)
+                .collect::<Vec<_>>();
+            let errors = bad.len() as u32;
+            let first = bad.first().copied().unwrap_or(0);
+            let last = bad.last().copied().unwrap_or(0);
+            let bits = if errors != 0 { stuck } else { 0 };
+            self.results = vec![
+                (errors >> 16) as u16,
+                errors as u16,
+                (first >> 16) as u16,
+                first as u16,
+                (last >> 16) as u16,
+                last as u16,
+                (bits >> 48) as u16,
+                (bits >> 32) as u16,
+                (bits >> 16) as u16,
+                bits as u16,
+                0,
+                (length / 4) as u16,
+            ];
+            self.results.reverse();
+        }
+    }
+
+    impl SimulatedBus for TestTester {
+        fn read(&mut self, address: u8) -> u16 {
+            match address {
+                3 => {
+                    self.polls += 1;
+                    if self.polls.is_multiple_of(4) {
+                        2
+                    } else {
+                        1
+                    }
+                }
+                _ => self.results.pop().unwrap(),
+            }
+        }
+        fn write(&mut self, address: u8, value: u16) {
+            match address {
+                0 => self.length.push(value),
+                1 => self.seed.push(value),
+                _ => {
+                    self.pattern = value;
+                    self.run();
+                    self.length.clear();
+                    self.seed.clear();
+                }
+            }
+        }
+    }
+
+    fn make_device() -> Device<SimulatedTransport<TestTester>> {
+        let map = RegisterMap::new(
+            [
+                "length",
+                "seed",
+                "start",
+                "status",
+                "errors",
+                "first_error",
+                "last_error",
+                "error_bits",
+                "checked",
+            ]
+            .iter()
+            .map(|x| x.to_string())
+            .collect(),
+        );
+        Device::new(SimulatedTransport::new(TestTester::default()), map)
+    }
+
+    #[test]
+    fn test_memory_test_finds_a_stuck_line() {
+        let mut dev = make_device();
+        let report =
+            run_memory_test(&mut dev, "", MemoryTestPattern::WalkingZeros, 512, 0).unwrap();
+        assert!(!report.passed());
+        assert_eq!(report.checked, 128);
+        assert_eq!(report.errors, 8);
+        assert_eq!(report.first_error, Some(5 * 4));
+        assert_eq!(report.last_error, Some(117 * 4));
+        assert_eq!(report.bad_data_lines(16), vec![5]);
+        assert_eq!(
+            report.to_string(),
+            "WalkingZeros (seed 00000000): 8 of 128 transfers failed, from address 00000014 to 000001d4, bad bits 0020002000200020"
+        );
+    }
+
+    #[test]
+    fn test_memory_tests_stop_at_the_first_failure() {
+        let mut dev = make_device();
+        let reports = run_memory_tests(&mut dev, "", 64, &[1, 2]).unwrap();
+        // Walking ones always has a zero on the stuck line
+        assert_eq!(reports.len(), 1);
+        assert_eq!(reports[0].errors, 16);
+        let mut dev = make_device();
+        let reports = run_memory_tests(&mut dev, "", 0, &[1, 2]).unwrap();
+        assert_eq!(reports.len(), 5);
+        assert!(reports.iter().all(|x| x.passed() && x.checked == 0));
+        assert_eq!(
+            reports[4].to_string(),
+            "Random (seed 00000002): 0 transfers passed"
+        );
+    }
+}
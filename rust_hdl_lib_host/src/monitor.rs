@@ -0,0 +1,179 @@
+use crate::error::HostError;
+use crate::hdlc_transport::hdlc_crc;
+use rust_hdl_lib_hls::prelude::{
+    MONITOR_BAD_COUNT, MONITOR_BAD_CRC, MONITOR_BAD_OPCODE, MONITOR_MAX_COUNT, MONITOR_OK,
+    MONITOR_PEEK, MONITOR_POKE, MONITOR_STREAM, MONITOR_TIMEOUT,
+};
+use std::io::{Read, Write};
+
+// Build a command for the UARTMonitor, including its CRC
+pub fn monitor_command(op: u8, address: u8, count: u16, data: &[u16]) -> Vec<u8> {
+    let mut command = vec![op, address];
+    command.extend(count.to_be_bytes());
+    command.extend(data.iter().flat_map(|word| word.to_be_bytes()));
+    let crc = hdlc_crc(&command);
+    command.extend(crc.to_be_bytes());
+    command
+}
+
+// Talks to a design through a UARTMonitor, which needs nothing but a
+// serial port.  Unlike a Device, there is no register map - registers
+// are addressed by number, and each call moves at most 256 words.
+pub struct MonitorClient<T: Read + Write> {
+    stream: T,
+}
+
+impl<T: Read + Write> MonitorClient<T> {
+    pub fn new(stream: T) -> Self {
+        Self { stream }
+    }
+    pub fn into_inner(self) -> T {
+        self.stream
+    }
+    fn send(&mut self, command: &[u8]) -> Result<(), HostError> {
+        self.stream.write_all(command)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+    // Read a reply, and check its CRC and status.  Returns the data (if the
+    // reply carries any)
+    fn reply(&mut self, address: u8, with_data: bool) -> Result<Vec<u16>, HostError> {
+        let mut reply = vec![0_u8; 3];
+        self.stream.read_exact(&mut reply)?;
+        let done = u16::from_be_bytes([reply[1], reply[2]]) as usize;
+        let len = if with_data { done * 2 } else { 0 } + 2;
+        let mut rest = vec![0_u8; len];
+        self.stream.read_exact(&mut rest)?;
+        reply.extend(rest);
+        if hdlc_crc(&reply) != 0 {
+            return Err(HostError::Transport(
+                "Reply from the monitor has a bad CRC".into(),
+            ));
+        }
+        match reply[0] {
+            MONITOR_OK => Ok(reply[3..reply.len() - 2]
+                .chunks_exact(2)
+                .map(|pair| u16::from_be_bytes([pair[0], pair[1]]))
+                .collect()),
+            MONITOR_TIMEOUT => Err(HostError::NotReady(format!(
+                "{:#04x} (after {} words)",
+                address, done
+            ))),
+            MONITOR_BAD_CRC => Err(HostError::Transport(
+                "Command was corrupted on the way to the monitor".into(),
+            )),
+            MONITOR_BAD_OPCODE => Err(HostError::Transport("Monitor rejected the op code".into())),
+            MONITOR_BAD_COUNT => Err(HostError::Transport("Monitor rejected the count".into())),
+            status => Err(HostError::Transport(format!(
+                "Unknown status {:#04x} from the monitor",
+                status
+            ))),
+        }
+    }
+    fn check_count(count: usize) -> Result<(), HostError> {
+        if count == 0 || count > MONITOR_MAX_COUNT {
+            return Err(HostError::Transport(format!(
+                "The monitor moves 1 to {} words at a time, not {}",
+                MONITOR_MAX_COUNT, count
+            )));
+        }
+        Ok(())
+    }
+    pub fn peek(&mut self, address: u8, count: usize) -> Result<Vec<u16>, HostError> {
+        Self::check_count(count)?;
+        self.send(&monitor_command(MONITOR_PEEK, address, count as u16, &[]))?;
+        self.reply(address, true)
+    }
+    pub fn poke(&mut self, address: u8, data: &[u16]) -> Result<(), HostError> {
+        Self::check_count(data.len())?;
+        self.send(&monitor_command(
+            MONITOR_POKE,
+            address,
+            data.len() as u16,
+            data,
+        ))?;
+        self.reply(address, false).map(|_| ())
+    }
+    // Start a stream of blocks of `count` words, which are then collected
+    // with `read_stream`.  The stream must be stopped with `stop_stream`.
+    pub fn start_stream(&mut self, address: u8, count: usize) -> Result<(), HostError> {
+        Self::check_count(count)?;
+        self.send(&monitor_command(MONITOR_STREAM, address, count as u16, &[]))
+    }
+    pub fn read_stream(&mut self, address: u8) -> Result<Vec<u16>, HostError> {
+        self.reply(address, true)
+    }
+    // Ask the stream to stop.  The block in flight (if any) still arrives,
+    // and should be drained with `read_stream`.
+    pub fn stop_stream(&mut self) -> Result<(), HostError> {
+        self.send(&[0])
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::VecDeque;
+
+    struct Canned {
+        written: Vec<u8>,
+        to_read: VecDeque<u8>,
+    }
+
+    impl Read for Canned {
+        fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+            let len = buf.len().min(self.to_read.len());
+            for (dest, src) in buf.iter_mut().zip(self.to_read.drain(..len)) {
+                *dest = src;
+            }
+            Ok(len)
+        }
+    }
+
+    impl Write for Canned {
+        fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+            self.written.extend_from_slice(buf);
+            Ok(buf.len())
+        }
+        fn flush(&mut self) -> std::io::Result<()> {
+            Ok(())
+        }
+    }
+
+    fn reply(status: u8, done: u16, data: &[u16]) -> Vec<u8> {
+        let mut reply = vec![status];
+        reply.extend(done.to_be_bytes());
+        reply.extend(data.iter().flat_map(|word| word.to_be_bytes()));
+        let crc = hdlc_crc(&reply);
+        reply.extend(crc.to_be_bytes());
+        reply
+    }
+
+    #[test]
+    fn test_monitor_client_checks_replies() {
+        let mut to_read = VecDeque::new();
+        to_read.extend(reply(MONITOR_OK, 2, &[0xDEAD, 0xBEEF]));
+        to_read.extend(reply(MONITOR_OK, 1, &[]));
+        to_read.extend(reply(MONITOR_TIMEOUT, 1, &[0x1234]));
+        let mut bad = reply(MONITOR_OK, 1, &[0x5678]);
+        bad[3] ^= 0x10;
+        to_read.extend(bad);
+        let mut client = MonitorClient::new(Canned {
+            written: vec![],
+            to_read,
+        });
+        assert_eq!(client.peek(3, 2).unwrap(), vec![0xDEAD, 0xBEEF]);
+        client.poke(4, &[0x7E7D]).unwrap();
+        assert!(matches!(client.peek(5, 2), Err(HostError::NotReady(_))));
+        assert!(matches!(client.peek(5, 1), Err(HostError::Transport(_))));
+        assert!(client.peek(5, 0).is_err());
+        let stream = client.into_inner();
+        let mut expected = monitor_command(MONITOR_PEEK, 3, 2, &[]);
+        expected.extend(monitor_command(MONITOR_POKE, 4, 1, &[0x7E7D]));
+        expected.extend(monitor_command(MONITOR_PEEK, 5, 2, &[]));
+        expected.extend(monitor_command(MONITOR_PEEK, 5, 1, &[]));
+        assert_eq!(stream.written, expected);
+        // The CRC of a command covers everything before it
+        assert_eq!(hdlc_crc(&expected[..6]), 0);
+    }
+}
@@ -0,0 +1,10 @@
+pub use crate::bringup::{run_bring_up, BringUpPin, BringUpPlan, BringUpReport};
+pub use crate::device::Device;
+pub use crate::error::HostError;
+pub use crate::hdlc_transport::{hdlc_crc, hdlc_encode, HDLCDecoder, HDLCTransport};
+pub use crate::memory_test::{run_memory_test, run_memory_tests, MemoryTestReport};
+pub use crate::monitor::{monitor_command, MonitorClient};
+pub use crate::register_map::RegisterMap;
+pub use crate::sim_transport::{SimulatedBus, SimulatedTransport};
+pub use crate::stream_transport::StreamTransport;
+pub use crate::transport::Transport;
@@ -0,0 +1,40 @@
+use crate::error::HostError;
+use rust_hdl_lib_hls::HLSNamedPorts;
+use serde::{Deserialize, Serialize};
+
+// The names of the registers of a design, in address order.  This is the
+// same list that the HLS blocks report through [HLSNamedPorts], so the map
+// can be taken straight from the design, or saved as JSON alongside the
+// bitstream and loaded by control software that does not link the design.
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct RegisterMap {
+    registers: Vec<String>,
+}
+
+impl RegisterMap {
+    pub fn new(registers: Vec<String>) -> Self {
+        // The controller has 8 bits for the address
+        assert!(registers.len() <= 256);
+        Self { registers }
+    }
+    pub fn from_design(design: &dyn HLSNamedPorts) -> Self {
+        Self::new(design.ports())
+    }
+    pub fn from_json(json: &str) -> Result<Self, HostError> {
+        let map: RegisterMap = serde_json::from_str(json)?;
+        Ok(Self::new(map.registers))
+    }
+    pub fn to_json(&self) -> String {
+        serde_json::to_string_pretty(self).unwrap()
+    }
+    pub fn address(&self, name: &str) -> Result<u8, HostError> {
+        self.registers
+            .iter()
+            .position(|x| x == name)
+            .map(|x| x as u8)
+            .ok_or_else(|| HostError::UnknownRegister(name.into()))
+    }
+    pub fn registers(&self) -> &[String] {
+        &self.registers
+    }
+}
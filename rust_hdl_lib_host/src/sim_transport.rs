@@ -0,0 +1,126 @@
+use crate::error::HostError;
+use crate::transport::Transport;
+use std::collections::VecDeque;
+
+// A software model of the registers of a design, so that control software
+// can be developed and tested without the hardware.
+pub trait SimulatedBus {
+    fn read(&mut self, address: u8) -> u16;
+    fn write(&mut self, address: u8, value: u16);
+    fn ready(&mut self, _address: u8) -> bool {
+        true
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+enum Command {
+    Idle,
+    ReadCount(u8),
+    WriteCount(u8),
+    Write(u8, u16),
+    Stream(u8),
+}
+
+// A transport that decodes the commands of the controller (the same word
+// protocol as the BaseController in hardware) and applies them to a
+// [SimulatedBus].
+pub struct SimulatedTransport<B: SimulatedBus> {
+    pub bus: B,
+    command: Command,
+    replies: VecDeque<u16>,
+}
+
+impl<B: SimulatedBus> SimulatedTransport<B> {
+    pub fn new(bus: B) -> Self {
+        Self {
+            bus,
+            command: Command::Idle,
+            replies: Default::default(),
+        }
+    }
+    fn decode(&mut self, word: u16) -> Result<(), HostError> {
+        self.command = match self.command {
+            Command::Idle => {
+                let address = (word & 0xFF) as u8;
+                match word >> 8 {
+                    0 => Command::Idle,
+                    1 => {
+                        self.replies.push_back(word);
+                        Command::Idle
+                    }
+                    2 => Command::ReadCount(address),
+                    3 => Command::WriteCount(address),
+                    4 => {
+                        self.replies
+                            .push_back(0xFF00 | (self.bus.ready(address) as u16));
+                        Command::Idle
+                    }
+                    5 => Command::Stream(address),
+                    op => {
+                        return Err(HostError::Transport(format!(
+                            "Unknown opcode {:02x} in simulation",
+                            op
+                        )))
+                    }
+                }
+            }
+            Command::ReadCount(address) => {
+                for _ in 0..word {
+                    let value = self.bus.read(address);
+                    self.replies.push_back(value);
+                }
+                Command::Idle
+            }
+            Command::WriteCount(address) => {
+                if word == 0 {
+                    Command::Idle
+                } else {
+                    Command::Write(address, word)
+                }
+            }
+            Command::Write(address, remaining) => {
+                self.bus.write(address, word);
+                if remaining == 1 {
+                    Command::Idle
+                } else {
+                    Command::Write(address, remaining - 1)
+                }
+            }
+            Command::Stream(address) => {
+                if word != 0 {
+                    Command::Idle
+                } else {
+                    Command::Stream(address)
+                }
+            }
+        };
+        Ok(())
+    }
+}
+
+impl<B: SimulatedBus> Transport for SimulatedTransport<B> {
+    fn send(&mut self, words: &[u16]) -> Result<(), HostError> {
+        for word in words {
+            self.decode(*word)?;
+        }
+        Ok(())
+    }
+
+    fn receive(&mut self, count: usize) -> Result<Vec<u16>, HostError> {
+        // While streaming, the device sends data for as long as it is ready
+        if let Command::Stream(address) = self.command {
+            while self.replies.len() < count && self.bus.ready(address) {
+                let value = self.bus.read(address);
+                self.replies.push_back(value);
+            }
+        }
+        if self.replies.len() < count {
+            return Err(HostError::Transport(format!(
+                "Expected {} words, but the simulation only has {}",
+                count,
+                self.replies.len()
+            )));
+        }
+        Ok(self.replies.drain(0..count).collect())
+    }
+}
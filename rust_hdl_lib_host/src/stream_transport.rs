@@ -0,0 +1,49 @@
+use crate::error::HostError;
+use crate::transport::Transport;
+use rust_hdl_lib_widgets::prelude::WordOrder;
+use std::io::{Read, Write};
+
+// A transport over any byte stream, like a serial port (UART), the virtual
+// COM port of an FTDI chip, or a socket.  The words are split into bytes
+// in the order given, which must match the order the design uses to
+// rebuild them (i.e., the `WordOrder` passed to the `Host` of the design).
+pub struct StreamTransport<T: Read + Write> {
+    stream: T,
+    order: WordOrder,
+}
+
+impl<T: Read + Write> StreamTransport<T> {
+    pub fn new(stream: T, order: WordOrder) -> Self {
+        Self { stream, order }
+    }
+    pub fn into_inner(self) -> T {
+        self.stream
+    }
+}
+
+impl<T: Read + Write> Transport for StreamTransport<T> {
+    fn send(&mut self, words: &[u16]) -> Result<(), HostError> {
+        let bytes = words
+            .iter()
+            .flat_map(|word| match self.order {
+                WordOrder::MostSignificantFirst => word.to_be_bytes(),
+                WordOrder::LeastSignificantFirst => word.to_le_bytes(),
+            })
+            .collect::<Vec<_>>();
+        self.stream.write_all(&bytes)?;
+        self.stream.flush()?;
+        Ok(())
+    }
+
+    fn receive(&mut self, count: usize) -> Result<Vec<u16>, HostError> {
+        let mut bytes = vec![0_u8; count * 2];
+        self.stream.read_exact(&mut bytes)?;
+        Ok(bytes
+            .chunks_exact(2)
+            .map(|pair| match self.order {
+                WordOrder::MostSignificantFirst => u16::from_be_bytes([pair[0], pair[1]]),
+                WordOrder::LeastSignificantFirst => u16::from_le_bytes([pair[0], pair[1]]),
+            })
+            .collect())
+    }
+}
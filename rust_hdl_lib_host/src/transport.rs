@@ -0,0 +1,12 @@
+use crate::error::HostError;
+
+// A link between the host and the controller of an HLS SoC design (the
+// BaseController).  The controller speaks in 16 bit words, so transports
+// move words, and are responsible for packing them into whatever the
+// underlying link carries (bytes, pipes, etc.).
+pub trait Transport {
+    // Send the words to the controller
+    fn send(&mut self, words: &[u16]) -> Result<(), HostError>;
+    // Wait for `count` words from the controller
+    fn receive(&mut self, count: usize) -> Result<Vec<u16>, HostError>;
+}
@@ -98,6 +98,63 @@ pub fn get_update_all(fields: Vec<TS>) -> syn::Result<TS> {
     })
 }
 
+// A block that was settled, and whose owner was not updated, cannot have anything
+// new in it, and is skipped entirely.
+pub fn get_update_changed(fields: Vec<TS>) -> syn::Result<TS> {
+    Ok(quote! {
+        fn update_changed(&mut self, state: &mut block::EventState, owner_updated: bool, _ports_written: bool) -> bool {
+            let (slot, was_settled) = state.enter();
+            let ports_written = owner_updated || state.external();
+            if was_settled && !ports_written {
+                state.skip(slot);
+                return true;
+            }
+            let updated = !was_settled;
+            if updated {
+                self.update();
+            }
+            let settled = true #(& self.#fields.update_changed(state, updated, ports_written))*;
+            state.leave(slot, settled);
+            settled
+        }
+    })
+}
+
+// Interfaces have no logic of their own, and belong to whoever owns them.
+pub fn get_update_changed_passthrough(fields: Vec<TS>) -> syn::Result<TS> {
+    Ok(quote! {
+        fn update_changed(&mut self, state: &mut block::EventState, owner_updated: bool, ports_written: bool) -> bool {
+            true #(& self.#fields.update_changed(state, owner_updated, ports_written))*
+        }
+    })
+}
+
+// Only the time spent in the block's own update is recorded against it - the
+// blocks inside it are timed separately.
+pub fn get_update_profiled(fields: Vec<TS>) -> syn::Result<TS> {
+    let fields_as_strings = fields.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    Ok(quote! {
+        fn update_profiled(&mut self, name: &str, profile: &mut block::SimProfile) {
+            let slot = profile.enter(name);
+            let start = std::time::Instant::now();
+            self.update();
+            profile.record(slot, start.elapsed());
+            #(self.#fields.update_profiled(#fields_as_strings, profile);)*
+            profile.leave();
+        }
+    })
+}
+
+// Interfaces have no logic of their own, so only the blocks inside them are timed.
+pub fn get_update_profiled_passthrough(fields: Vec<TS>) -> syn::Result<TS> {
+    let fields_as_strings = fields.iter().map(|x| x.to_string()).collect::<Vec<_>>();
+    Ok(quote! {
+        fn update_profiled(&mut self, _name: &str, profile: &mut block::SimProfile) {
+            #(self.#fields.update_profiled(#fields_as_strings, profile);)*
+        }
+    })
+}
+
 pub fn get_has_changed(fields: Vec<TS>) -> syn::Result<TS> {
     if fields.is_empty() {
         Ok(quote! {
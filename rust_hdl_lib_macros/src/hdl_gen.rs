@@ -2,6 +2,7 @@ use std::ops::Index;
 
 use quote::format_ident;
 use quote::quote;
+use quote::quote_spanned;
 use syn::spanned::Spanned;
 use syn::{BinOp, Expr, Pat, PathSegment, Result, Stmt, UnOp};
 
@@ -27,6 +28,11 @@ pub(crate) fn hdl_gen_process(item: syn::ItemFn) -> Result<TS> {
 fn hdl_block(block: &syn::Block) -> Result<TS> {
     let mut stmt = vec![];
     for statement in &block.stmts {
+        // The location is resolved by the compiler at the span of the original
+        // statement, so that readable Verilog can point back to it.
+        stmt.push(quote_spanned!(statement.span()=>
+            ast::VerilogStatement::Source(concat!(file!(), ":", line!()).to_string())
+        ));
         stmt.push(hdl_statement(statement)?);
     }
     Ok(quote! {
@@ -272,6 +278,7 @@ fn hdl_compute(m: &syn::Expr) -> Result<TS> {
         Expr::Call(call) => hdl_call(call),
         Expr::MethodCall(method) => hdl_method(method),
         Expr::Lit(lit) => hdl_literal(lit),
+        Expr::Cast(cast) => hdl_compute(&cast.expr),
         Expr::Index(_ndx) => {
             let ndx_expanded = common::fixup_ident(quote!(#m).to_string());
             Ok(quote!(ast::VerilogExpression::Signal(#ndx_expanded.to_string())))
@@ -543,6 +550,17 @@ fn hdl_method(method: &syn::ExprMethodCall) -> Result<TS> {
                 ast::VerilogExpression::Signed(Box::new(#target))
             }))
         }
+        "concat" => {
+            if method.args.len() != 1 {
+                return Err(syn::Error::new(
+                    method.span(),
+                    "concat needs one argument (the least significant bits)",
+                ));
+            }
+            let msb = hdl_concat_operand(method.receiver.as_ref())?;
+            let lsb = hdl_concat_operand(&method.args[0])?;
+            Ok(quote!({ ast::VerilogExpression::Concat(vec![#msb, #lsb]) }))
+        }
         "val" | "into" | "index" | "to_bits" => {
             let receiver = method.receiver.as_ref();
             hdl_compute(receiver)
@@ -554,6 +572,26 @@ fn hdl_method(method: &syn::ExprMethodCall) -> Result<TS> {
     }
 }
 
+// Every part of a Verilog concatenation must have a width.  A literal like
+// bits::<4>(3) is evaluated in Rust, so that it is written out with 4 bits.
+fn hdl_concat_operand(expr: &syn::Expr) -> Result<TS> {
+    match expr {
+        Expr::Lit(_) => Err(syn::Error::new(
+            expr.span(),
+            "Literals in a concatenation need a width (e.g., bits::<4>(3))",
+        )),
+        Expr::Call(call) if matches!(call.args.first(), Some(Expr::Lit(_))) => {
+            let funcname = quote!(#call).to_string();
+            if funcname.starts_with("bits") || funcname.starts_with("Bits") {
+                Ok(quote!(ast::VerilogExpression::Literal(#call.into())))
+            } else {
+                hdl_compute(expr)
+            }
+        }
+        _ => hdl_compute(expr),
+    }
+}
+
 fn hdl_body(body: &syn::Expr) -> Result<TS> {
     if let Expr::Block(b) = body {
         hdl_block(&b.block)
@@ -563,12 +601,70 @@ fn hdl_body(body: &syn::Expr) -> Result<TS> {
     }
 }
 
+// Verilog has no range labels in a case statement, so ranges are expanded
+// into a list of values.  Keep that list to a reasonable size.
+const MAX_RANGE_PATTERN_VALUES: u128 = 256;
+
+fn hdl_pattern_value(expr: &Expr) -> Result<u128> {
+    if let Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Int(x),
+        ..
+    }) = expr
+    {
+        return x.base10_parse::<u128>();
+    }
+    Err(syn::Error::new(
+        expr.span(),
+        "Only integer literals are allowed in range patterns for HDL",
+    ))
+}
+
 fn hdl_pattern(pat: &Pat) -> Result<String> {
     match pat {
         Pat::Ident(ident) => Ok(ident.ident.to_string()),
-        Pat::Lit(lit) => Ok(quote!(#lit).to_string()),
+        // Integers are written in decimal, without any suffix, so that 0x10 and 16_usize
+        // both end up as 16 in the case statement
+        Pat::Lit(lit) => match hdl_pattern_value(&lit.expr) {
+            Ok(x) => Ok(x.to_string()),
+            Err(_) => Ok(quote!(#lit).to_string()),
+        },
         Pat::Path(pat) => Ok(common::fixup_ident(quote!(#pat).to_string())),
         Pat::Wild(_pat) => Ok("default".to_string()),
+        Pat::Or(pat) => {
+            let cases = pat
+                .cases
+                .iter()
+                .map(hdl_pattern)
+                .collect::<Result<Vec<_>>>()?;
+            if cases.iter().any(|x| x == "default") {
+                return Err(syn::Error::new(
+                    pat.span(),
+                    "A wildcard cannot be combined with other patterns in HDL",
+                ));
+            }
+            Ok(cases.join(", "))
+        }
+        Pat::Range(range) => {
+            let lo = hdl_pattern_value(&range.lo)?;
+            let hi = hdl_pattern_value(&range.hi)?;
+            let hi = match range.limits {
+                syn::RangeLimits::HalfOpen(_) => hi.checked_sub(1),
+                syn::RangeLimits::Closed(_) => Some(hi),
+            };
+            match hi {
+                Some(hi) if hi >= lo && hi - lo < MAX_RANGE_PATTERN_VALUES => Ok((lo..=hi)
+                    .map(|x| x.to_string())
+                    .collect::<Vec<_>>()
+                    .join(", ")),
+                _ => Err(syn::Error::new(
+                    range.span(),
+                    format!(
+                        "Range patterns in HDL must be non-empty and cover at most {} values",
+                        MAX_RANGE_PATTERN_VALUES
+                    ),
+                )),
+            }
+        }
         _ => Err(syn::Error::new(
             pat.span(),
             format!(
@@ -596,6 +692,21 @@ fn hdl_macro(x: &syn::ExprMacro) -> Result<TS> {
                 .replace("\")", "");
             Ok(quote!(ast::VerilogStatement::Comment(#invocation_as_string.to_string())))
         }
+        "sim_log" => {
+            let args = x.mac.parse_body_with(
+                syn::punctuated::Punctuated::<Expr, syn::Token![,]>::parse_terminated,
+            )?;
+            let level = args.iter().nth(1).map(|x| quote!(#x).to_string());
+            let message = match args.iter().nth(2) {
+                Some(Expr::Lit(syn::ExprLit {
+                    lit: syn::Lit::Str(s),
+                    ..
+                })) => s.value(),
+                _ => String::new(),
+            };
+            let comment = format!("{}: {}", level.unwrap_or_default(), message);
+            Ok(quote!(ast::VerilogStatement::Comment(#comment.to_string())))
+        }
         "assert" => {
             let invocation_as_string = invocation_as_string
                 .replace("assert ! (\"", "")
@@ -19,7 +19,7 @@ use crate::logic_struct::get_impl_for_logic_struct;
 use proc_macro::TokenStream;
 use quote::quote;
 
-#[proc_macro_derive(LogicBlock)]
+#[proc_macro_derive(LogicBlock, attributes(partition))]
 pub fn logic_block(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
@@ -39,7 +39,7 @@ pub fn logic_interface(input: TokenStream) -> TokenStream {
     }
 }
 
-#[proc_macro_derive(LogicState)]
+#[proc_macro_derive(LogicState, attributes(encoding))]
 pub fn logic_state(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
 
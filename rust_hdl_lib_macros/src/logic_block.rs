@@ -1,12 +1,13 @@
 use quote::quote;
-use syn::Result;
+use syn::{Data, Result};
 
 use crate::common;
 use crate::common::TS;
 
 pub(crate) fn get_impl_for_logic_block(input: &syn::DeriveInput) -> Result<TS> {
     let fields = common::get_field_names(input)?;
-    let update_all = common::get_update_all(fields.clone())?;
+    let update_all = get_update_all(input, &fields)?;
+    let update_profiled = common::get_update_profiled(fields.clone())?;
     let has_changed = common::get_has_changed(fields.clone())?;
     let connect_all = common::get_connect_all(fields.clone())?;
     let accept = get_accept(fields)?;
@@ -16,12 +17,68 @@ pub(crate) fn get_impl_for_logic_block(input: &syn::DeriveInput) -> Result<TS> {
         impl #impl_generics block::Block for #name #ty_generics {
             #connect_all
             #update_all
+            #update_profiled
             #has_changed
             #accept
         }
     })
 }
 
+// Fields marked with #[partition] are updated in parallel with each other.  This is
+// safe because a child block can only see its own state during update_all.
+fn get_update_all(input: &syn::DeriveInput, fields: &[TS]) -> Result<TS> {
+    let mut partitions = vec![];
+    if let Data::Struct(ds) = &input.data {
+        for field in &ds.fields {
+            if field.attrs.iter().any(|x| x.path.is_ident("partition")) {
+                let name = &field.ident;
+                partitions.push(quote!(#name).to_string());
+            }
+        }
+    }
+    if partitions.is_empty() {
+        let update_all = common::get_update_all(fields.to_vec())?;
+        let update_changed = common::get_update_changed(fields.to_vec())?;
+        return Ok(quote! {
+            #update_all
+            #update_changed
+        });
+    }
+    let (parallel, serial): (Vec<TS>, Vec<TS>) = fields
+        .iter()
+        .cloned()
+        .partition(|x| partitions.contains(&x.to_string()));
+    let count = parallel.len();
+    Ok(quote! {
+        fn update_all(&mut self) {
+            self.update();
+            #(self.#serial.update_all();)*
+            block::update_all_parallel(&mut [#(&mut self.#parallel as &mut (dyn block::Block + Send)),*]);
+        }
+        fn update_changed(&mut self, state: &mut block::EventState, owner_updated: bool, _ports_written: bool) -> bool {
+            let (slot, was_settled) = state.enter();
+            let ports_written = owner_updated || state.external();
+            if was_settled && !ports_written {
+                state.skip(slot);
+                return true;
+            }
+            let updated = !was_settled;
+            if updated {
+                self.update();
+            }
+            let settled = true #(& self.#serial.update_changed(state, updated, ports_written))*;
+            let settled = settled & block::update_changed_parallel(
+                &mut [#(&mut self.#parallel as &mut (dyn block::Block + Send)),*],
+                state.split(#count),
+                updated,
+                ports_written,
+            );
+            state.leave(slot, settled);
+            settled
+        }
+    })
+}
+
 fn get_accept(fields: Vec<TS>) -> Result<TS> {
     let fields_as_strings = fields.iter().map(|x| x.to_string()).collect::<Vec<_>>();
     Ok(quote! {
@@ -30,5 +87,11 @@ fn get_accept(fields: Vec<TS>) -> Result<TS> {
             #(self.#fields.accept(#fields_as_strings, probe);)*
             probe.visit_end_scope(name, self);
         }
+
+        fn accept_mut(&mut self, name: &str, probe: &mut dyn probe::ProbeMut) {
+            probe.visit_start_scope(name);
+            #(self.#fields.accept_mut(#fields_as_strings, probe);)*
+            probe.visit_end_scope(name);
+        }
     })
 }
@@ -1,5 +1,6 @@
 use crate::common::{get_connect_all, get_has_changed, get_update_all, TS};
 use crate::common::{get_field_names, get_field_types};
+use crate::common::{get_update_changed_passthrough, get_update_profiled_passthrough};
 use quote::quote;
 use std::collections::HashMap;
 use syn::spanned::Spanned;
@@ -12,6 +13,8 @@ pub(crate) fn get_impl_for_logic_interface(input: &syn::DeriveInput) -> Result<T
     let link_hdl = get_link_hdl(fields.clone(), field_types.clone())?;
     let update_all = get_update_all(fields.clone())?;
     let has_changed = get_has_changed(fields.clone())?;
+    let update_changed = get_update_changed_passthrough(fields.clone())?;
+    let update_profiled = get_update_profiled_passthrough(fields.clone())?;
     let connect_all = get_connect_all(fields.clone())?;
     let join_connect = get_join_connect(fields.clone())?;
     let join_hdl = get_join_hdl(fields.clone(), field_types)?;
@@ -39,6 +42,8 @@ pub(crate) fn get_impl_for_logic_interface(input: &syn::DeriveInput) -> Result<T
             #connect_all
             #update_all
             #has_changed
+            #update_changed
+            #update_profiled
             #accept
         }
 
@@ -118,6 +123,12 @@ fn get_accept(fields: Vec<TS>) -> Result<TS> {
             #(self.#fields.accept(#fields_as_strings, probe);)*
             probe.visit_end_namespace(name, self);
         }
+
+        fn accept_mut(&mut self, name: &str, probe: &mut dyn probe::ProbeMut) {
+            probe.visit_start_namespace(name);
+            #(self.#fields.accept_mut(#fields_as_strings, probe);)*
+            probe.visit_end_namespace(name);
+        }
     })
 }
 
@@ -3,8 +3,55 @@ use quote::quote;
 use syn::spanned::Spanned;
 use syn::{Data, Result};
 
-fn get_variant_names(input: &syn::DeriveInput) -> Result<Vec<TS>> {
+enum Encoding {
+    Binary,
+    Gray,
+    OneHot,
+}
+
+fn get_encoding(input: &syn::DeriveInput) -> Result<Option<Encoding>> {
+    for attr in &input.attrs {
+        if !attr.path.is_ident("encoding") {
+            continue;
+        }
+        if let syn::Meta::NameValue(nv) = attr.parse_meta()? {
+            if let syn::Lit::Str(s) = &nv.lit {
+                return match s.value().as_str() {
+                    "binary" => Ok(Some(Encoding::Binary)),
+                    "gray" => Ok(Some(Encoding::Gray)),
+                    "one_hot" => Ok(Some(Encoding::OneHot)),
+                    _ => Err(syn::Error::new(
+                        s.span(),
+                        "encoding must be one of \"binary\", \"gray\" or \"one_hot\"",
+                    )),
+                };
+            }
+        }
+        return Err(syn::Error::new(
+            attr.span(),
+            "encoding attribute should look like #[encoding = \"one_hot\"]",
+        ));
+    }
+    Ok(None)
+}
+
+fn get_discriminant(expr: &syn::Expr) -> Result<usize> {
+    if let syn::Expr::Lit(syn::ExprLit {
+        lit: syn::Lit::Int(x),
+        ..
+    }) = expr
+    {
+        return x.base10_parse::<usize>();
+    }
+    Err(syn::Error::new(
+        expr.span(),
+        "enum discriminants must be integer literals",
+    ))
+}
+
+fn get_variants(input: &syn::DeriveInput) -> Result<(Vec<TS>, Vec<Option<usize>>)> {
     let mut variants = vec![];
+    let mut discriminants = vec![];
     match &input.data {
         Data::Enum(ed) => {
             for variant in &ed.variants {
@@ -14,12 +61,10 @@ fn get_variant_names(input: &syn::DeriveInput) -> Result<Vec<TS>> {
                         "enum variants cannot have fields",
                     ));
                 }
-                if variant.discriminant.is_some() {
-                    return Err(syn::Error::new(
-                        variant.span(),
-                        "enum variants cannot have discriminants",
-                    ));
-                }
+                discriminants.push(match &variant.discriminant {
+                    Some((_, expr)) => Some(get_discriminant(expr)?),
+                    None => None,
+                });
                 let name = &variant.ident;
                 variants.push(quote!(#name));
             }
@@ -31,14 +76,66 @@ fn get_variant_names(input: &syn::DeriveInput) -> Result<Vec<TS>> {
             ))
         }
     }
-    Ok(variants)
+    Ok((variants, discriminants))
+}
+
+// Work out the value that encodes each variant in hardware, and the number
+// of bits needed to hold it.
+fn get_values(input: &syn::DeriveInput, num_variants: usize) -> Result<(Vec<usize>, TS)> {
+    let (_, discriminants) = get_variants(input)?;
+    let encoding = get_encoding(input)?;
+    let explicit = discriminants.iter().filter(|x| x.is_some()).count();
+    if explicit != 0 {
+        if explicit != num_variants {
+            return Err(syn::Error::new(
+                input.span(),
+                "either all enum variants have discriminants, or none do",
+            ));
+        }
+        if encoding.is_some() {
+            return Err(syn::Error::new(
+                input.span(),
+                "enum discriminants cannot be combined with an encoding attribute",
+            ));
+        }
+        let values = discriminants.into_iter().flatten().collect::<Vec<_>>();
+        for (ndx, value) in values.iter().enumerate() {
+            if values[..ndx].contains(value) {
+                return Err(syn::Error::new(
+                    input.span(),
+                    format!("enum discriminant {} is used more than once", value),
+                ));
+            }
+        }
+        let max = values.iter().copied().max().unwrap_or(0);
+        return Ok((values, quote!(clog2(#max + 1))));
+    }
+    match encoding.unwrap_or(Encoding::Binary) {
+        Encoding::Binary => Ok(((0..num_variants).collect(), quote!(clog2(#num_variants)))),
+        Encoding::Gray => Ok((
+            (0..num_variants).map(|x| x ^ (x >> 1)).collect(),
+            quote!(clog2(#num_variants)),
+        )),
+        Encoding::OneHot => {
+            if num_variants > 64 {
+                return Err(syn::Error::new(
+                    input.span(),
+                    "one_hot encoding is limited to 64 variants",
+                ));
+            }
+            Ok((
+                (0..num_variants).map(|x| 1 << x).collect(),
+                quote!(#num_variants),
+            ))
+        }
+    }
 }
 
 pub fn get_logic_state_impls(input: &syn::DeriveInput) -> Result<TS> {
-    let variants = get_variant_names(input)?;
+    let (variants, _) = get_variants(input)?;
     let first_variant = variants[0].clone();
     let num_variants = variants.len();
-    let discriminants: Vec<usize> = (0_usize..variants.len()).collect();
+    let (discriminants, bits) = get_values(input, num_variants)?;
     let name = &input.ident;
     let name_as_string = name.to_string();
     let variants_as_strings = variants
@@ -51,11 +148,14 @@ pub fn get_logic_state_impls(input: &syn::DeriveInput) -> Result<TS> {
         .collect::<Vec<String>>();
     Ok(quote!(
         impl synth::Synth for #name {
-            const BITS: usize = clog2(#num_variants);
+            const BITS: usize = #bits;
             fn descriptor() -> type_descriptor::TypeDescriptor {
                 TypeDescriptor {
                     name: #name_as_string.to_string(),
-                    kind: TypeKind::Enum(vec![#(#variants_as_strings.to_string(),)*])
+                    kind: TypeKind::Enum(vec![#(EnumVariant {
+                        name: #variants_as_strings.to_string(),
+                        value: #discriminants,
+                    },)*])
                 }
             }
             fn vcd(self) -> VCDValue {
@@ -68,6 +168,12 @@ pub fn get_logic_state_impls(input: &syn::DeriveInput) -> Result<TS> {
                     #(#name::#variants => #discriminants.into(),)*
                 }
             }
+            fn from_vcd(value: &VCDValue) -> Option<Self> {
+                match value {
+                    #(VCDValue::String(x) if x == #variants_only_as_strings => Some(#name::#variants),)*
+                    _ => None,
+                }
+            }
         }
 
         impl Into<Bits<{#name::BITS}>> for #name {
@@ -22,6 +22,8 @@ pub(crate) fn get_impl_for_logic_struct(input: &syn::DeriveInput) -> Result<TS>
         });
         prev_field.push(quote!(#(+<#previous_fields>::BITS)*));
     }
+    let field_indices = (0..fields.len()).collect::<Vec<_>>();
+    let num_fields = fields.len();
     let (impl_generics, ty_generics, _where_clause) = &input.generics.split_for_impl();
     let name = &input.ident;
     Ok(quote! {
@@ -73,6 +75,15 @@ pub(crate) fn get_impl_for_logic_struct(input: &syn::DeriveInput) -> Result<TS>
                 let t: Bits<{Self::BITS}> = self.into();
                 t.into()
             }
+
+            fn from_vcd(value: &VCDValue) -> Option<Self> {
+                match value {
+                    VCDValue::Composite(x) if x.len() == #num_fields => Some(Self {
+                        #(#fields: <#field_types>::from_vcd(&x[#field_indices])?,)*
+                    }),
+                    _ => None,
+                }
+            }
         }
     })
 }
@@ -1,5 +1,6 @@
 use rust_hdl_lib_core::prelude::*;
 
+use super::ok_usb3::{OpalKellyUSB3Host, OpalKellyUSB3HostInterface};
 use super::OpalKellyHost;
 use super::OpalKellyHostInterface;
 
@@ -10,3 +11,13 @@ pub trait OpalKellyBSP {
     fn clocks() -> Vec<Signal<In, Clock>>;
     fn synth<U: Block>(uut: U, dir: &str);
 }
+
+// The USB3 modules use a different host interface, so they get their own
+// version of the board support trait.
+pub trait OpalKellyUSB3BSP {
+    fn hi() -> OpalKellyUSB3HostInterface;
+    fn ok_host() -> OpalKellyUSB3Host;
+    fn leds() -> Signal<Out, Bits<8>>;
+    fn clocks() -> Vec<Signal<In, Clock>>;
+    fn synth<U: Block>(uut: U, dir: &str);
+}
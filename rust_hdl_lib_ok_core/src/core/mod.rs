@@ -9,8 +9,11 @@ pub mod ok_download;
 pub mod ok_hi;
 pub mod ok_hls_bridge;
 pub mod ok_host;
+pub mod ok_host_transport;
 pub mod ok_pipe;
+pub mod ok_pipe_bfm;
 pub mod ok_trigger;
+pub mod ok_usb3;
 pub mod ok_wire;
 pub mod prelude;
 pub mod spi;
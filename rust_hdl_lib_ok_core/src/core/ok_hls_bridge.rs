@@ -197,7 +197,7 @@ pub fn write_bridge_bytes(
     hnd.write_to_pipe_in(config.pipe_in as i32, data)
 }
 
-fn read_bridge_bytes(
+pub fn read_bridge_bytes(
     hnd: &OkHandle,
     config: &OKHLSBridgeAddressConfig,
     len: usize,
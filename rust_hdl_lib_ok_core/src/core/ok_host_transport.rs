@@ -0,0 +1,34 @@
+use crate::core::ok_hls_bridge::{
+    mk_u8, read_bridge_bytes, write_bridge_bytes, OKHLSBridgeAddressConfig,
+};
+use rust_hdl_lib_host::prelude::*;
+use rust_hdl_lib_ok_frontpanel_sys::{make_u16_buffer, OkError, OkHandle};
+
+fn map_err(err: OkError) -> HostError {
+    HostError::Transport(format!("FrontPanel error {:?}", err.code))
+}
+
+// Carries the commands of the host runtime over the pipes of an
+// OpalKellyHLSBridge, so that a [Device] can talk to a design on an
+// OpalKelly board.
+pub struct OkHostTransport<'a> {
+    hnd: &'a OkHandle,
+    config: OKHLSBridgeAddressConfig,
+}
+
+impl<'a> OkHostTransport<'a> {
+    pub fn new(hnd: &'a OkHandle, config: OKHLSBridgeAddressConfig) -> Self {
+        Self { hnd, config }
+    }
+}
+
+impl<'a> Transport for OkHostTransport<'a> {
+    fn send(&mut self, words: &[u16]) -> Result<(), HostError> {
+        write_bridge_bytes(self.hnd, &self.config, &mk_u8(words)).map_err(map_err)
+    }
+
+    fn receive(&mut self, count: usize) -> Result<Vec<u16>, HostError> {
+        let data = read_bridge_bytes(self.hnd, &self.config, count * 2).map_err(map_err)?;
+        Ok(make_u16_buffer(&data))
+    }
+}
@@ -0,0 +1,429 @@
+use crate::core::ok_pipe::{BTPipeIn, BTPipeOut, PipeIn, PipeOut};
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_core::simulate::Result;
+
+/// The timing of transfers between the host and the pipes, as seen
+/// from the FPGA side (in cycles of `ti_clk`).  The pipes move data
+/// in USB packets, so a transfer arrives as a series of bursts with
+/// gaps between them, after some delay for the host to get started.
+/// The bus functional models ([ok_pipe_in_write], [ok_pipe_out_read],
+/// [ok_bt_pipe_in_write] and [ok_bt_pipe_out_read]) follow this timing,
+/// so that FIFOs can be sized against realistic throughput in simulation.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct OKPipeTiming {
+    /// The number of 16 bit words in each burst (i.e., the USB packet size).
+    /// For the block throttled pipes, this is also the block size.
+    pub burst_words: usize,
+    /// The number of idle cycles between bursts
+    pub inter_packet_gap: usize,
+    /// The number of idle cycles before the first burst of a transfer
+    pub host_latency: usize,
+}
+
+impl OKPipeTiming {
+    /// Data moves on every cycle, with no latency.  This is how the pipes
+    /// are usually modelled in a testbench, and the best case for the design.
+    pub fn ideal() -> Self {
+        Self {
+            burst_words: usize::MAX,
+            inter_packet_gap: 0,
+            host_latency: 0,
+        }
+    }
+    /// Roughly what a USB 2.0 high speed host achieves with a 48 MHz `ti_clk`.
+    /// Packets are 512 bytes, and the sustained rate is around 38 MB/s - about
+    /// 40% of what the 16 bit pipe could move.
+    pub fn usb2() -> Self {
+        Self {
+            burst_words: 256,
+            inter_packet_gap: 384,
+            host_latency: 2400,
+        }
+    }
+    /// The fraction of the cycles (once the transfer is started) that move data.
+    pub fn efficiency(&self) -> f64 {
+        if self.inter_packet_gap == 0 {
+            1.0
+        } else {
+            self.burst_words as f64 / (self.burst_words + self.inter_packet_gap) as f64
+        }
+    }
+}
+
+// Move to just after the next rising edge of the clock
+fn next_cycle<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    x: Box<T>,
+    clock: fn(&T) -> bool,
+) -> Result<Box<T>> {
+    let x = if clock(&x) {
+        sim.watch(move |x| !clock(x), x)?
+    } else {
+        x
+    };
+    sim.watch(clock, x)
+}
+
+fn idle_cycles<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    mut x: Box<T>,
+    clock: fn(&T) -> bool,
+    count: usize,
+) -> Result<Box<T>> {
+    for _ in 0..count {
+        x = next_cycle(sim, x, clock)?;
+    }
+    Ok(x)
+}
+
+/// Write data from the host to a [PipeIn], following the given [OKPipeTiming].
+/// The `clock` closure gives the level of `ti_clk`, and `pipe` selects the pipe
+/// in the circuit.  The testbench drives the outputs of the pipe, so this works
+/// on an unmodified design.  Returns once the last word has been written.
+pub fn ok_pipe_in_write<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    mut x: Box<T>,
+    timing: &OKPipeTiming,
+    clock: fn(&T) -> bool,
+    pipe: fn(&mut T) -> &mut PipeIn,
+    data: &[u16],
+) -> Result<Box<T>> {
+    x = idle_cycles(sim, x, clock, timing.host_latency)?;
+    for (ndx, burst) in data.chunks(timing.burst_words).enumerate() {
+        if ndx != 0 {
+            pipe(&mut x).write.next = false;
+            x = idle_cycles(sim, x, clock, timing.inter_packet_gap)?;
+        }
+        for word in burst {
+            pipe(&mut x).write.next = true;
+            pipe(&mut x).dataout.next = (*word as u64).into();
+            x = next_cycle(sim, x, clock)?;
+        }
+    }
+    pipe(&mut x).write.next = false;
+    Ok(x)
+}
+
+// The host asserts read for each word of the burst, and captures the data
+// on the rising edge that ends the cycle.
+fn read_burst<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    mut x: Box<T>,
+    clock: fn(&T) -> bool,
+    read: impl Fn(&mut T) -> &mut Signal<Out, Bit>,
+    datain: impl Fn(&mut T) -> Bits<16>,
+    count: usize,
+    data: &mut Vec<u16>,
+) -> Result<Box<T>> {
+    for _ in 0..count {
+        read(&mut x).next = true;
+        x = next_cycle(sim, x, clock)?;
+        data.push(datain(&mut x).index() as u16);
+    }
+    read(&mut x).next = false;
+    Ok(x)
+}
+
+/// Read `count` words from a [PipeOut] into the host, following the given
+/// [OKPipeTiming].  See [ok_pipe_in_write] for the meaning of the arguments.
+/// Returns the circuit, and the words that were read.
+pub fn ok_pipe_out_read<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    mut x: Box<T>,
+    timing: &OKPipeTiming,
+    clock: fn(&T) -> bool,
+    pipe: fn(&mut T) -> &mut PipeOut,
+    count: usize,
+) -> Result<(Box<T>, Vec<u16>)> {
+    let mut data = vec![];
+    x = idle_cycles(sim, x, clock, timing.host_latency)?;
+    while data.len() < count {
+        if !data.is_empty() {
+            x = idle_cycles(sim, x, clock, timing.inter_packet_gap)?;
+        }
+        let burst = timing.burst_words.min(count - data.len());
+        x = read_burst(
+            sim,
+            x,
+            clock,
+            |x| &mut pipe(x).read,
+            |x| pipe(x).datain.val(),
+            burst,
+            &mut data,
+        )?;
+    }
+    Ok((x, data))
+}
+
+// Wait until the design is ready for the next block, and then
+// strobe the start of the block.
+fn start_block<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    mut x: Box<T>,
+    clock: fn(&T) -> bool,
+    ready: impl Fn(&mut T) -> Bit,
+    blockstrobe: impl Fn(&mut T) -> &mut Signal<Out, Bit>,
+) -> Result<Box<T>> {
+    while !ready(&mut x) {
+        x = next_cycle(sim, x, clock)?;
+    }
+    blockstrobe(&mut x).next = true;
+    x = next_cycle(sim, x, clock)?;
+    blockstrobe(&mut x).next = false;
+    Ok(x)
+}
+
+/// Write data from the host to a [BTPipeIn], following the given [OKPipeTiming].
+/// The data is sent in blocks of [OKPipeTiming::burst_words], and each block
+/// waits for the design to assert `ready` before it starts.  The length of the
+/// data should be a multiple of the block size (the last block is short otherwise).
+/// See [ok_pipe_in_write] for the meaning of the arguments.
+pub fn ok_bt_pipe_in_write<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    mut x: Box<T>,
+    timing: &OKPipeTiming,
+    clock: fn(&T) -> bool,
+    pipe: fn(&mut T) -> &mut BTPipeIn,
+    data: &[u16],
+) -> Result<Box<T>> {
+    x = idle_cycles(sim, x, clock, timing.host_latency)?;
+    for (ndx, block) in data.chunks(timing.burst_words).enumerate() {
+        if ndx != 0 {
+            x = idle_cycles(sim, x, clock, timing.inter_packet_gap)?;
+        }
+        x = start_block(
+            sim,
+            x,
+            clock,
+            |x| pipe(x).ready.val(),
+            |x| &mut pipe(x).blockstrobe,
+        )?;
+        for word in block {
+            pipe(&mut x).write.next = true;
+            pipe(&mut x).dataout.next = (*word as u64).into();
+            x = next_cycle(sim, x, clock)?;
+        }
+        pipe(&mut x).write.next = false;
+    }
+    Ok(x)
+}
+
+/// Read `count` words from a [BTPipeOut] into the host, following the given
+/// [OKPipeTiming].  The data is read in blocks of [OKPipeTiming::burst_words],
+/// and each block waits for the design to assert `ready` before it starts.
+/// See [ok_pipe_in_write] for the meaning of the arguments.
+pub fn ok_bt_pipe_out_read<T: Send + 'static>(
+    sim: &mut Sim<T>,
+    mut x: Box<T>,
+    timing: &OKPipeTiming,
+    clock: fn(&T) -> bool,
+    pipe: fn(&mut T) -> &mut BTPipeOut,
+    count: usize,
+) -> Result<(Box<T>, Vec<u16>)> {
+    let mut data = vec![];
+    x = idle_cycles(sim, x, clock, timing.host_latency)?;
+    while data.len() < count {
+        if !data.is_empty() {
+            x = idle_cycles(sim, x, clock, timing.inter_packet_gap)?;
+        }
+        x = start_block(
+            sim,
+            x,
+            clock,
+            |x| pipe(x).ready.val(),
+            |x| &mut pipe(x).blockstrobe,
+        )?;
+        let burst = timing.burst_words.min(count - data.len());
+        x = read_burst(
+            sim,
+            x,
+            clock,
+            |x| &mut pipe(x).read,
+            |x| pipe(x).datain.val(),
+            burst,
+            &mut data,
+        )?;
+    }
+    Ok((x, data))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rust_hdl_lib_widgets::prelude::*;
+
+    declare_sync_fifo!(BFMTestFIFO, Bits<16>, 256, 1);
+
+    #[derive(LogicBlock)]
+    struct PipeLoopback {
+        ti_clk: Signal<In, Clock>,
+        ok1: Signal<In, Bits<31>>,
+        i_pipe: PipeIn,
+        o_pipe: PipeOut,
+        fifo: BFMTestFIFO,
+        delay_read: DFF<Bit>,
+    }
+
+    impl Default for PipeLoopback {
+        fn default() -> Self {
+            Self {
+                ti_clk: Default::default(),
+                ok1: Default::default(),
+                i_pipe: PipeIn::new(0x80),
+                o_pipe: PipeOut::new(0xA0),
+                fifo: Default::default(),
+                delay_read: Default::default(),
+            }
+        }
+    }
+
+    impl Logic for PipeLoopback {
+        #[hdl_gen]
+        fn update(&mut self) {
+            self.fifo.clock.next = self.ti_clk.val();
+            self.delay_read.clock.next = self.ti_clk.val();
+            self.i_pipe.ok1.next = self.ok1.val();
+            self.o_pipe.ok1.next = self.ok1.val();
+            self.fifo.write.next = self.i_pipe.write.val();
+            self.fifo.data_in.next = self.i_pipe.dataout.val();
+            self.delay_read.d.next = self.o_pipe.read.val();
+            self.fifo.read.next = self.delay_read.q.val();
+            self.o_pipe.datain.next = self.fifo.data_out.val();
+        }
+    }
+
+    // Write data through the loopback and read it back, and check that it took
+    // between `min_cycles` and `max_cycles` cycles of ti_clk.
+    fn loopback(timing: OKPipeTiming, min_cycles: u64, max_cycles: u64) {
+        let mut uut = PipeLoopback::default();
+        uut.connect_all();
+        let mut sim = Simulation::new();
+        sim.add_clock(10_000, |x: &mut Box<PipeLoopback>| {
+            x.ti_clk.next = !x.ti_clk.val()
+        });
+        sim.add_testbench(move |mut sim: Sim<PipeLoopback>| {
+            let data = (0..200).map(|x| x * 3 + 1).collect::<Vec<u16>>();
+            let x = sim.init()?;
+            let x = ok_pipe_in_write(
+                &mut sim,
+                x,
+                &timing,
+                |x| x.ti_clk.val().clk,
+                |x| &mut x.i_pipe,
+                &data,
+            )?;
+            let (mut x, out) = ok_pipe_out_read(
+                &mut sim,
+                x,
+                &timing,
+                |x| x.ti_clk.val().clk,
+                |x| &mut x.o_pipe,
+                data.len(),
+            )?;
+            sim_assert_eq!(sim, out, data, x);
+            // The number of rising edges of ti_clk so far
+            let cycles = (sim.time() + 10_000) / 20_000;
+            sim_assert!(sim, cycles >= min_cycles && cycles <= max_cycles, x);
+            // The read of the last word lands on the next cycle
+            wait_clock_cycles!(sim, ti_clk, x, 1);
+            sim_assert!(sim, x.fifo.empty.val(), x);
+            sim.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000_000).unwrap();
+    }
+
+    #[test]
+    fn test_pipe_bfm_ideal_loopback() {
+        // 400 words move in 400 cycles
+        loopback(OKPipeTiming::ideal(), 400, 401);
+    }
+
+    #[test]
+    fn test_pipe_bfm_shaped_loopback() {
+        // Each transfer adds the latency, and 3 gaps between its 4 bursts
+        loopback(
+            OKPipeTiming {
+                burst_words: 64,
+                inter_packet_gap: 32,
+                host_latency: 10,
+            },
+            400 + 2 * (10 + 3 * 32),
+            401 + 2 * (10 + 3 * 32),
+        );
+    }
+
+    #[derive(LogicBlock)]
+    struct BTPipeSink {
+        ti_clk: Signal<In, Clock>,
+        ok1: Signal<In, Bits<31>>,
+        pipe: BTPipeIn,
+        fifo: BFMTestFIFO,
+        sum: DFF<Bits<16>>,
+        blocks: DFF<Bits<8>>,
+    }
+
+    impl Default for BTPipeSink {
+        fn default() -> Self {
+            Self {
+                ti_clk: Default::default(),
+                ok1: Default::default(),
+                pipe: BTPipeIn::new(0x80),
+                fifo: Default::default(),
+                sum: Default::default(),
+                blocks: Default::default(),
+            }
+        }
+    }
+
+    impl Logic for BTPipeSink {
+        #[hdl_gen]
+        fn update(&mut self) {
+            dff_setup!(self, ti_clk, sum, blocks);
+            self.fifo.clock.next = self.ti_clk.val();
+            self.pipe.ok1.next = self.ok1.val();
+            self.fifo.write.next = self.pipe.write.val();
+            self.fifo.data_in.next = self.pipe.dataout.val();
+            // Only take a block once the previous one has drained
+            self.pipe.ready.next = self.fifo.empty.val();
+            self.fifo.read.next = !self.fifo.empty.val();
+            if !self.fifo.empty.val() {
+                self.sum.d.next = self.sum.q.val() + self.fifo.data_out.val();
+            }
+            if self.pipe.blockstrobe.val() {
+                self.blocks.d.next = self.blocks.q.val() + 1;
+            }
+        }
+    }
+
+    #[test]
+    fn test_bt_pipe_bfm_waits_for_ready() {
+        let mut uut = BTPipeSink::default();
+        uut.connect_all();
+        let mut sim = Simulation::new();
+        sim.add_clock(10_000, |x: &mut Box<BTPipeSink>| {
+            x.ti_clk.next = !x.ti_clk.val()
+        });
+        sim.add_testbench(move |mut sim: Sim<BTPipeSink>| {
+            let data = (0..256).collect::<Vec<u16>>();
+            let timing = OKPipeTiming {
+                burst_words: 64,
+                inter_packet_gap: 0,
+                host_latency: 0,
+            };
+            let x = sim.init()?;
+            let mut x = ok_bt_pipe_in_write(
+                &mut sim,
+                x,
+                &timing,
+                |x| x.ti_clk.val().clk,
+                |x| &mut x.pipe,
+                &data,
+            )?;
+            wait_clock_cycles!(sim, ti_clk, x, 10);
+            sim_assert_eq!(sim, x.sum.q.val(), 255 * 128, x);
+            sim_assert_eq!(sim, x.blocks.q.val(), 4, x);
+            sim.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000_000).unwrap();
+    }
+}
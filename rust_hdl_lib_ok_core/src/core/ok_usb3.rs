@@ -0,0 +1,458 @@
+use rust_hdl_lib_core::prelude::*;
+
+// The USB3 FrontPanel modules (e.g., the XEM7310) use a different host
+// interface from the USB2 ones.  The host bus is 32 bits wide, clocked
+// at 100.8 MHz, and the endpoint busses (okHE/okEH) are wider than the
+// ok1/ok2 pair used on the USB2 modules.  The endpoints are otherwise
+// used in the same way - fan out ok_he to every endpoint, and OR together
+// the ok_eh outputs of all of the endpoints into the host.
+pub const MHZ100_8: u64 = 100_800_000;
+
+#[derive(Clone, Debug, LogicInterface)]
+pub struct OpalKellyUSB3HostInterface {
+    pub sig_uh: Signal<In, Bits<5>>,
+    pub sig_hu: Signal<Out, Bits<3>>,
+    pub sig_uhu: Signal<InOut, Bits<32>>,
+    pub sig_aa: Signal<InOut, Bit>,
+}
+
+impl OpalKellyUSB3HostInterface {
+    pub fn xem_7310() -> OpalKellyUSB3HostInterface {
+        let mut hi_uh = Signal::<In, _>::default();
+        for (ndx, name) in ["W19", "V18", "U17", "W17", "T19"].iter().enumerate() {
+            hi_uh.add_location(ndx, name);
+            hi_uh.add_signal_type(ndx, SignalType::LowVoltageCMOS_3v3);
+            if ndx != 0 {
+                hi_uh.add_constraint(PinConstraint {
+                    index: ndx,
+                    constraint: Constraint::Timing(Timing::VivadoInputTiming(
+                        VivadoInputTimingConstraint {
+                            min_nanoseconds: 0.0,
+                            max_nanoseconds: 8.0,
+                            multicycle: 2,
+                            clock: "okUH0".to_string(),
+                        },
+                    )),
+                })
+            } else {
+                hi_uh.add_constraint(PinConstraint {
+                    index: 0,
+                    constraint: Constraint::Timing(Periodic(PeriodicTiming {
+                        net: "okUH0".into(),
+                        period_nanoseconds: 9.92,
+                        duty_cycle: 50.0,
+                    })),
+                });
+                hi_uh.add_constraint(PinConstraint {
+                    index: 0,
+                    constraint: Constraint::Timing(VivadoClockGroup(vec![
+                        vec!["okUH0".to_string()],
+                        vec!["SystemClk".to_string()],
+                    ])),
+                })
+            }
+        }
+        let mut hi_hu = Signal::<Out, _>::default();
+        for (ndx, name) in ["Y19", "R18", "R16"].iter().enumerate() {
+            hi_hu.add_location(ndx, name);
+            hi_hu.add_signal_type(ndx, SignalType::LowVoltageCMOS_3v3);
+            hi_hu.add_constraint(PinConstraint {
+                index: ndx,
+                constraint: Constraint::Slew(SlewType::Fast),
+            });
+            hi_hu.add_constraint(PinConstraint {
+                index: ndx,
+                constraint: Constraint::Timing(VivadoOutputTiming(VivadoOutputTimingConstraint {
+                    delay_nanoseconds: 2.0,
+                    clock: "okUH0".to_string(),
+                })),
+            })
+        }
+        let mut hi_uhu = Signal::<InOut, _>::default();
+        for (ndx, name) in [
+            "AB22", "AB21", "Y22", "AA21", "AA20", "W22", "W21", "T20", "R19", "P19", "U21", "T21",
+            "R21", "P21", "R22", "P22", "R14", "W20", "Y21", "P17", "U20", "N17", "N14", "V20",
+            "P16", "T18", "V19", "AB20", "P15", "V22", "U18", "AB18",
+        ]
+        .iter()
+        .enumerate()
+        {
+            hi_uhu.add_location(ndx, name);
+            hi_uhu.add_signal_type(ndx, SignalType::LowVoltageCMOS_3v3);
+            hi_uhu.add_constraint(PinConstraint {
+                index: ndx,
+                constraint: Constraint::Slew(SlewType::Fast),
+            });
+            hi_uhu.add_constraint(PinConstraint {
+                index: ndx,
+                constraint: Constraint::Timing(VivadoInputTiming(VivadoInputTimingConstraint {
+                    min_nanoseconds: 2.0,
+                    max_nanoseconds: 8.0,
+                    multicycle: 2,
+                    clock: "okUH0".to_string(),
+                })),
+            });
+            hi_uhu.add_constraint(PinConstraint {
+                index: ndx,
+                constraint: Constraint::Timing(VivadoOutputTiming(VivadoOutputTimingConstraint {
+                    delay_nanoseconds: 2.0,
+                    clock: "okUH0".to_string(),
+                })),
+            });
+        }
+        let mut hi_aa = Signal::<InOut, _>::default();
+        hi_aa.add_location(0, "N13");
+        hi_aa.add_signal_type(0, SignalType::LowVoltageCMOS_3v3);
+        Self {
+            sig_uh: hi_uh,
+            sig_hu: hi_hu,
+            sig_uhu: hi_uhu,
+            sig_aa: hi_aa,
+        }
+    }
+}
+
+#[derive(Clone, Debug, LogicBlock)]
+pub struct OpalKellyUSB3Host {
+    pub hi: OpalKellyUSB3HostInterface,
+    pub ok_he: Signal<Out, Bits<113>>,
+    pub ok_eh: Signal<In, Bits<65>>,
+    pub ok_clk: Signal<Out, Clock>,
+}
+
+impl Logic for OpalKellyUSB3Host {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.ok_he.connect();
+        self.ok_eh.connect();
+        self.hi.sig_uh.connect();
+        self.hi.sig_hu.connect();
+        self.hi.sig_uhu.connect();
+        self.hi.sig_aa.connect();
+        self.ok_clk.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Blackbox(BlackBox {
+            code: r#"
+module OpalKellyUSB3Host
+	(
+	input  wire [4:0]   hi$sig_uh,
+	output wire [2:0]   hi$sig_hu,
+	inout  wire [31:0]  hi$sig_uhu,
+	inout  wire         hi$sig_aa,
+	output wire         ok_clk,
+	output wire [112:0] ok_he,
+	input  wire [64:0]  ok_eh
+	);
+
+	okHost host(.okUH(hi$sig_uh),
+	            .okHU(hi$sig_hu),
+	            .okUHU(hi$sig_uhu),
+	            .okAA(hi$sig_aa),
+	            .okClk(ok_clk),
+	            .okHE(ok_he),
+	            .okEH(ok_eh));
+endmodule
+
+(* blackbox *)
+module okHost(
+	input  wire [4:0]   okUH,
+	output wire [2:0]   okHU,
+	inout  wire [31:0]  okUHU,
+	inout  wire         okAA,
+	output wire         okClk,
+	output wire [112:0] okHE,
+	input  wire [64:0]  okEH);
+endmodule
+           "#
+            .into(),
+            name: "OpalKellyUSB3Host".into(),
+        })
+    }
+}
+
+impl OpalKellyUSB3Host {
+    pub fn xem_7310() -> Self {
+        Self {
+            hi: OpalKellyUSB3HostInterface::xem_7310(),
+            ok_he: Signal::default(),
+            ok_eh: Signal::default(),
+            ok_clk: Signal::default(),
+        }
+    }
+}
+
+#[test]
+fn test_usb3_host_interface_synthesizes() {
+    let mut uut = TopWrap::new(OpalKellyUSB3Host::xem_7310());
+    uut.uut.ok_eh.connect();
+    uut.uut.hi.sig_uh.connect();
+    uut.connect_all();
+    yosys_validate("okhi_usb3", &generate_verilog(&uut)).unwrap();
+}
+
+#[derive(Clone, Debug, LogicBlock)]
+pub struct WireInUSB3 {
+    pub ok_he: Signal<In, Bits<113>>,
+    pub dataout: Signal<Out, Bits<32>>,
+    _n: u8,
+}
+
+impl WireInUSB3 {
+    pub fn new(n: u8) -> Self {
+        assert!(n < 0x20);
+        Self {
+            ok_he: Default::default(),
+            dataout: Default::default(),
+            _n: n,
+        }
+    }
+}
+
+impl Logic for WireInUSB3 {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.dataout.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let name = format!("WireInUSB3_{:x}", self._n);
+        Verilog::Blackbox(BlackBox {
+            code: format!(
+                r#"
+module {}
+    (
+    input wire [112:0] ok_he,
+    output wire [31:0] dataout
+    );
+
+    okWireIn mod_wire(.okHE(ok_he),
+                  .ep_addr({:x}),
+                  .ep_dataout(dataout));
+endmodule
+
+(* blackbox *)
+module okWireIn(
+    input wire [112:0] okHE,
+    input wire [7:0] ep_addr,
+    output wire [31:0] ep_dataout
+);
+endmodule  "#,
+                name,
+                VerilogLiteral::from(self._n)
+            ),
+            name,
+        })
+    }
+}
+
+#[test]
+fn test_wire_in_usb3_synth() {
+    let mut uut = TopWrap::new(WireInUSB3::new(0x02));
+    uut.uut.ok_he.connect();
+    uut.connect_all();
+    yosys_validate("wire_in_usb3", &generate_verilog(&uut)).unwrap();
+}
+
+#[derive(Clone, Debug, LogicBlock)]
+pub struct WireOutUSB3 {
+    pub ok_he: Signal<In, Bits<113>>,
+    pub ok_eh: Signal<Out, Bits<65>>,
+    pub datain: Signal<In, Bits<32>>,
+    _n: u8,
+}
+
+impl WireOutUSB3 {
+    pub fn new(n: u8) -> Self {
+        assert!((0x20..0x40).contains(&n));
+        Self {
+            ok_he: Default::default(),
+            ok_eh: Default::default(),
+            datain: Default::default(),
+            _n: n,
+        }
+    }
+}
+
+impl Logic for WireOutUSB3 {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.ok_eh.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let name = format!("WireOutUSB3_{:x}", self._n);
+        Verilog::Blackbox(BlackBox {
+            code: format!(
+                r#"
+module {}
+    (
+    input wire [112:0] ok_he,
+    output wire [64:0] ok_eh,
+    input wire [31:0] datain
+    );
+
+    okWireOut mod_wire(.okHE(ok_he),
+                       .okEH(ok_eh),
+                  .ep_addr({:x}),
+                  .ep_datain(datain));
+endmodule
+
+(* blackbox *)
+module okWireOut(
+    input wire [112:0] okHE,
+    output wire [64:0] okEH,
+    input wire [7:0] ep_addr,
+    input wire [31:0] ep_datain
+);
+endmodule  "#,
+                name,
+                VerilogLiteral::from(self._n)
+            ),
+            name,
+        })
+    }
+}
+
+#[test]
+fn test_wire_out_usb3_synth() {
+    let mut uut = TopWrap::new(WireOutUSB3::new(0x20));
+    uut.uut.ok_he.connect();
+    uut.uut.datain.connect();
+    uut.connect_all();
+    yosys_validate("wire_out_usb3", &generate_verilog(&uut)).unwrap();
+}
+
+#[derive(Clone, Debug, LogicBlock)]
+pub struct PipeInUSB3 {
+    pub ok_he: Signal<In, Bits<113>>,
+    pub ok_eh: Signal<Out, Bits<65>>,
+    pub write: Signal<Out, Bit>,
+    pub dataout: Signal<Out, Bits<32>>,
+    _n: u8,
+}
+
+impl PipeInUSB3 {
+    pub fn new(n: u8) -> Self {
+        assert!((0x80..0xA0).contains(&n));
+        Self {
+            ok_he: Default::default(),
+            ok_eh: Default::default(),
+            write: Default::default(),
+            dataout: Default::default(),
+            _n: n,
+        }
+    }
+}
+
+impl Logic for PipeInUSB3 {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.ok_eh.connect();
+        self.write.connect();
+        self.dataout.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let name = format!("PipeInUSB3_{:x}", self._n);
+        Verilog::Blackbox(BlackBox {
+            code: format!(
+                r#"
+module {}
+    (input wire  [112:0] ok_he,
+     output wire [64:0]  ok_eh,
+     output wire         write,
+     output wire [31:0]  dataout);
+
+     okPipeIn mod(.okHE(ok_he),.okEH(ok_eh),.ep_write(write),.ep_dataout(dataout),.ep_addr({:x}));
+endmodule
+
+(* blackbox *)
+module okPipeIn(okHE, okEH, ep_addr, ep_write, ep_dataout);
+	input  [112:0] okHE;
+	output [64:0]  okEH;
+	input  [7:0]   ep_addr;
+	output         ep_write;
+	output [31:0]  ep_dataout;
+endmodule
+                    "#,
+                name,
+                VerilogLiteral::from(self._n)
+            ),
+            name,
+        })
+    }
+}
+
+#[test]
+fn test_pipein_usb3_synthesizes() {
+    let mut uut = TopWrap::new(PipeInUSB3::new(0x80));
+    uut.uut.ok_he.connect();
+    uut.connect_all();
+    yosys_validate("pipein_usb3", &generate_verilog(&uut)).unwrap();
+}
+
+#[derive(Clone, Debug, LogicBlock)]
+pub struct PipeOutUSB3 {
+    pub ok_he: Signal<In, Bits<113>>,
+    pub ok_eh: Signal<Out, Bits<65>>,
+    pub read: Signal<Out, Bit>,
+    pub datain: Signal<In, Bits<32>>,
+    _n: u8,
+}
+
+impl PipeOutUSB3 {
+    pub fn new(n: u8) -> Self {
+        assert!((0xA0..0xC0).contains(&n));
+        Self {
+            ok_he: Default::default(),
+            ok_eh: Default::default(),
+            read: Default::default(),
+            datain: Default::default(),
+            _n: n,
+        }
+    }
+}
+
+impl Logic for PipeOutUSB3 {
+    fn update(&mut self) {}
+    fn connect(&mut self) {
+        self.ok_eh.connect();
+        self.read.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        let name = format!("PipeOutUSB3_{:x}", self._n);
+        Verilog::Blackbox(BlackBox {
+            code: format!(
+                r#"
+module {}
+    (input wire [112:0] ok_he,
+     output wire [64:0] ok_eh,
+     output wire        read,
+     input wire [31:0]  datain);
+
+     okPipeOut mod(.okHE(ok_he), .okEH(ok_eh), .ep_read(read), .ep_datain(datain), .ep_addr({:x}));
+endmodule
+
+(* blackbox *)
+module okPipeOut(okHE, okEH, ep_addr, ep_read, ep_datain);
+	input  [112:0] okHE;
+	output [64:0]  okEH;
+	input  [7:0]   ep_addr;
+	output         ep_read;
+	input  [31:0]  ep_datain;
+endmodule
+                "#,
+                name,
+                VerilogLiteral::from(self._n)
+            ),
+            name,
+        })
+    }
+}
+
+#[test]
+fn test_pipeout_usb3_synthesizes() {
+    let mut uut = TopWrap::new(PipeOutUSB3::new(0xA0));
+    uut.uut.ok_he.connect();
+    uut.uut.datain.connect();
+    uut.connect_all();
+    yosys_validate("pipeout_usb3", &generate_verilog(&uut)).unwrap();
+}
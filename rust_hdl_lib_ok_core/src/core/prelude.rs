@@ -2,8 +2,11 @@ pub use super::bsp::*;
 pub use super::ok_download::*;
 pub use super::ok_hi::*;
 pub use super::ok_host::*;
+pub use super::ok_host_transport::*;
 pub use super::ok_pipe::*;
+pub use super::ok_pipe_bfm::*;
 pub use super::ok_trigger::*;
+pub use super::ok_usb3::*;
 pub use super::ok_wire::*;
 pub use super::spi::*;
 pub use super::tools::*;
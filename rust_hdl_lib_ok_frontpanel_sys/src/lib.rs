@@ -129,6 +129,11 @@ impl OkHandle {
         unsafe { okFrontPanel_SetWireInValue(self.hnd, addr, val as u64, 0xFFFF) };
     }
 
+    // The USB3 modules have 32 bit wires
+    pub fn set_wire_in_u32(&self, addr: i32, val: u32) {
+        unsafe { okFrontPanel_SetWireInValue(self.hnd, addr, val as u64, 0xFFFF_FFFF) };
+    }
+
     pub fn update_wire_ins(&self) {
         unsafe { okFrontPanel_UpdateWireIns(self.hnd) };
     }
@@ -152,6 +157,10 @@ impl OkHandle {
         val
     }
 
+    pub fn get_wire_out_u32(&self, addr: i32) -> u32 {
+        unsafe { okFrontPanel_GetWireOutValue(self.hnd, addr) as u32 }
+    }
+
     pub fn configure_fpga(&self, firmware: &str) -> Result<(), OkError> {
         let filename = CString::new(firmware).expect("CString new failed");
         let code = unsafe { okFrontPanel_ConfigureFPGA(self.hnd, filename.as_ptr()) };
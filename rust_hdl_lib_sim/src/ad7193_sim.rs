@@ -202,7 +202,7 @@ impl Logic for AD7193Simulator {
             }
         }
         if self.spi_slave.transfer_done.val() & self.spi_slave.data_inbound.val().all() {
-            println!("Reset encountered");
+            sim_log!(self, Debug, "Reset encountered");
             self.state.d.next = AD7193State::Ready;
         }
     }
@@ -0,0 +1,264 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum EncoderState {
+    Idle,
+    Ack,
+    Frame,
+    Timeout,
+}
+
+// A BiSS-C encoder with an `N` bit position.  The first falling edge of MA
+// latches `position`, `error` and `warning`.  The encoder then acknowledges
+// (holds SLO low) for `ack_clocks` rising edges of MA, and sends the start
+// bit, the CDS bit (always 0), the position MSB first, the active low error
+// and warning bits and the inverted CRC6, a bit on each rising edge.  The
+// rising edge after the CRC pulls SLO low, and it stays low until MA has been
+// idle for the timeout.  Setting `corrupt_crc` flips a bit of the CRC.  The
+// edges of MA are found by sampling it with `clock`, which must run at least
+// 4 times faster.
+#[derive(LogicBlock)]
+pub struct BiSSEncoderSimulator<const N: usize> {
+    pub clock: Signal<In, Clock>,
+    pub wires: BiSSWiresSlave,
+    pub position: Signal<In, Bits<N>>,
+    pub error: Signal<In, Bit>,
+    pub warning: Signal<In, Bit>,
+    pub corrupt_crc: Signal<In, Bit>,
+    state: DFF<EncoderState>,
+    prev_ma: DFFWithInit<Bit>,
+    rising: Signal<Local, Bit>,
+    falling: Signal<Local, Bit>,
+    shift: DFF<Bits<N>>,
+    flags: DFF<Bits<2>>,
+    crc: DFF<Bits<6>>,
+    bit: Signal<Local, Bit>,
+    slo: DFFWithInit<Bit>,
+    count: DFF<Bits<8>>,
+    msb: Constant<Bits<8>>,
+    timer: DFF<Bits<32>>,
+    ack_clocks: Constant<Bits<8>>,
+    data_end: Constant<Bits<8>>,
+    flags_end: Constant<Bits<8>>,
+    frame_end: Constant<Bits<8>>,
+    timeout: Constant<Bits<32>>,
+}
+
+impl<const N: usize> BiSSEncoderSimulator<N> {
+    pub fn new(clock_speed: u64, ack_clocks: usize, timeout_us: u64) -> Self {
+        assert!((1..240).contains(&N));
+        assert!((1..256).contains(&ack_clocks));
+        Self {
+            clock: Default::default(),
+            wires: Default::default(),
+            position: Default::default(),
+            error: Default::default(),
+            warning: Default::default(),
+            corrupt_crc: Default::default(),
+            state: Default::default(),
+            prev_ma: DFFWithInit::new(true),
+            rising: Default::default(),
+            falling: Default::default(),
+            shift: Default::default(),
+            flags: Default::default(),
+            crc: Default::default(),
+            bit: Default::default(),
+            slo: DFFWithInit::new(true),
+            count: Default::default(),
+            msb: Constant::new((N - 1).to_bits()),
+            timer: Default::default(),
+            ack_clocks: Constant::new(ack_clocks.to_bits()),
+            data_end: Constant::new(N.to_bits()),
+            flags_end: Constant::new((N + 2).to_bits()),
+            frame_end: Constant::new((N + 8).to_bits()),
+            timeout: Constant::new((clock_speed * timeout_us / 1_000_000).to_bits()),
+        }
+    }
+}
+
+impl<const N: usize> Logic for BiSSEncoderSimulator<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state, prev_ma, shift, flags, crc, slo, count, timer);
+        self.prev_ma.d.next = self.wires.ma.val();
+        self.rising.next = self.wires.ma.val() & !self.prev_ma.q.val();
+        self.falling.next = !self.wires.ma.val() & self.prev_ma.q.val();
+        self.wires.slo.next = self.slo.q.val();
+        // The next bit of the position, or of the error and warning bits
+        self.bit.next = self.shift.q.val().get_bit(self.msb.val().index());
+        if self.count.q.val() > self.data_end.val() {
+            self.bit.next = self.flags.q.val().get_bit(1);
+        }
+        match self.state.q.val() {
+            EncoderState::Idle => {
+                self.slo.d.next = true;
+                if self.falling.val() {
+                    self.shift.d.next = self.position.val();
+                    self.flags.d.next = (bit_cast::<2, 1>((!self.error.val()).into()) << 1)
+                        | bit_cast::<2, 1>((!self.warning.val()).into());
+                    self.crc.d.next = 0.into();
+                    self.count.d.next = 0.into();
+                    self.state.d.next = EncoderState::Ack;
+                }
+            }
+            EncoderState::Timeout => {
+                self.timer.d.next = self.timer.q.val() + 1;
+                if self.rising.val() | self.falling.val() {
+                    self.timer.d.next = 0.into();
+                }
+                if self.timer.q.val() == self.timeout.val() {
+                    self.slo.d.next = true;
+                    self.state.d.next = EncoderState::Idle;
+                }
+            }
+            _ => {}
+        }
+        if (self.state.q.val() == EncoderState::Ack) & self.rising.val() {
+            if self.count.q.val() == self.ack_clocks.val() {
+                // The start bit
+                self.slo.d.next = true;
+                self.count.d.next = 0.into();
+                self.state.d.next = EncoderState::Frame;
+            } else {
+                self.slo.d.next = false;
+                self.count.d.next = self.count.q.val() + 1;
+            }
+        }
+        if (self.state.q.val() == EncoderState::Frame) & self.rising.val() {
+            self.count.d.next = self.count.q.val() + 1;
+            if self.count.q.val() == 0 {
+                // The CDS bit
+                self.slo.d.next = false;
+            } else if self.count.q.val() <= self.flags_end.val() {
+                self.slo.d.next = self.bit.val();
+                if self.count.q.val() <= self.data_end.val() {
+                    self.shift.d.next = self.shift.q.val() << 1;
+                } else {
+                    self.flags.d.next = self.flags.q.val() << 1;
+                }
+                if self.crc.q.val().get_bit(5) ^ self.bit.val() {
+                    self.crc.d.next = (self.crc.q.val() << 1) ^ 3_u64.to_bits();
+                } else {
+                    self.crc.d.next = self.crc.q.val() << 1;
+                }
+            } else if self.count.q.val() <= self.frame_end.val() {
+                self.slo.d.next = !self.crc.q.val().get_bit(5);
+                if self.corrupt_crc.val() & (self.count.q.val() == self.frame_end.val()) {
+                    self.slo.d.next = self.crc.q.val().get_bit(5);
+                }
+                self.crc.d.next = self.crc.q.val() << 1;
+            } else {
+                self.slo.d.next = false;
+                self.timer.d.next = 0.into();
+                self.state.d.next = EncoderState::Timeout;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_biss_encoder_synthesizes() {
+    let mut uut = BiSSEncoderSimulator::<18>::new(100_000_000, 3, 20);
+    uut.connect_all();
+    yosys_validate("biss_encoder", &generate_verilog(&uut)).unwrap();
+}
+
+#[derive(LogicBlock)]
+struct TestBiSS {
+    clock: Signal<In, Clock>,
+    master: BiSSMaster<18>,
+    encoder: BiSSEncoderSimulator<18>,
+}
+
+impl Logic for TestBiSS {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, master, encoder);
+        BiSSWiresMaster::join(&mut self.master.wires, &mut self.encoder.wires);
+    }
+}
+
+#[cfg(test)]
+fn mk_test_biss(ack_clocks: usize) -> TestBiSS {
+    let mut uut = TestBiSS {
+        clock: Default::default(),
+        master: BiSSMaster::new(BiSSConfig {
+            clock_speed: 100_000_000,
+            speed_hz: 2_000_000,
+            timeout_us: 40,
+            max_ack_clocks: 8,
+        }),
+        encoder: BiSSEncoderSimulator::new(100_000_000, ack_clocks, 20),
+    };
+    uut.master.start.connect();
+    uut.encoder.position.connect();
+    uut.encoder.error.connect();
+    uut.encoder.warning.connect();
+    uut.encoder.corrupt_crc.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_yosys_validate_biss_fixture() {
+    let uut = mk_test_biss(3);
+    yosys_validate("biss_fixture", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_biss_reads() {
+    let uut = mk_test_biss(3);
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TestBiSS>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<TestBiSS>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        for (position, error, warning, corrupt_crc) in [
+            (0x2_1234_u64, false, false, false),
+            (0x3_FFFF, true, false, false),
+            (0x0_0000, false, true, false),
+            (0x1_5555, false, false, true),
+            (0x2_AAAA, true, true, false),
+        ] {
+            x.encoder.position.next = position.to_bits();
+            x.encoder.error.next = error;
+            x.encoder.warning.next = warning;
+            x.encoder.corrupt_crc.next = corrupt_crc;
+            wait_clock_true!(sim, clock, x);
+            x.master.start.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.master.start.next = false;
+            x = sim.watch(|x| x.master.valid.val(), x)?;
+            sim_assert!(sim, !x.master.timeout.val(), x);
+            sim_assert_eq!(sim, x.master.crc_error.val(), corrupt_crc, x);
+            sim_assert_eq!(sim, x.master.encoder_error.val(), error, x);
+            sim_assert_eq!(sim, x.master.encoder_warning.val(), warning, x);
+            sim_assert_eq!(sim, x.master.position.val(), position, x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 2_000_000, &vcd_path!("biss_reads.vcd"))
+        .unwrap();
+}
+
+#[test]
+fn test_biss_slow_acknowledge_times_out() {
+    // The encoder takes longer to acknowledge than the master allows
+    let uut = mk_test_biss(12);
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TestBiSS>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<TestBiSS>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        x.master.start.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.master.start.next = false;
+        x = sim.watch(|x| x.master.valid.val(), x)?;
+        sim_assert!(sim, x.master.timeout.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 200_000, &vcd_path!("biss_ack_timeout.vcd"))
+        .unwrap();
+}
@@ -0,0 +1,170 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+// The pixel the camera simulator sends at column `x` of line `y` of frame
+// number `frame`.  Each of them moves the pattern differently, so that a
+// swapped or dropped pixel, line or frame shows up in the capture.  Only the
+// low byte is sent when there is 1 byte per pixel.
+pub fn dvp_test_pixel(frame: usize, x: usize, y: usize) -> u16 {
+    ((frame << 8) + (y << 6) + x * 3) as u16
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct DVPCameraConfig {
+    pub width: usize,
+    pub height: usize,
+    // 1 (raw or greyscale) or 2 (RGB565 or YUV)
+    pub bytes_per_pixel: usize,
+    // Pixel clocks between the lines
+    pub h_blank: usize,
+    // Lines between the frames (vsync is high for the first 2 of them)
+    pub v_blank: usize,
+}
+
+// A synthetic DVP camera, that sends frames of the `dvp_test_pixel` pattern
+// forever.  The camera runs from `clock` (the XCLK of a real camera), and
+// passes it back out as the pixel clock.  The outputs change on the rising
+// edge of the pixel clock, which is good enough for a receiver that is
+// clocked by the same signal.  The frame counter wraps after 256 frames.
+#[derive(LogicBlock)]
+pub struct DVPCameraSimulator {
+    pub clock: Signal<In, Clock>,
+    pub pclk: Signal<Out, Clock>,
+    pub wires: DVPWiresCamera,
+    byte_x: DFF<Bits<16>>,
+    line: DFF<Bits<16>>,
+    frame: DFF<Bits<8>>,
+    vsync: DFF<Bit>,
+    href: DFF<Bit>,
+    data: DFF<Bits<8>>,
+    x: Signal<Local, Bits<16>>,
+    y: Signal<Local, Bits<16>>,
+    pixel: Signal<Local, Bits<16>>,
+    line_length: Constant<Bits<16>>,
+    active_bytes: Constant<Bits<16>>,
+    frame_length: Constant<Bits<16>>,
+    v_blank: Constant<Bits<16>>,
+    two_bytes: Constant<Bit>,
+}
+
+impl DVPCameraSimulator {
+    pub fn new(config: DVPCameraConfig) -> Self {
+        assert!(config.bytes_per_pixel == 1 || config.bytes_per_pixel == 2);
+        assert!(config.width > 0 && config.height > 0);
+        assert!(config.h_blank > 0);
+        assert!(config.v_blank >= 3);
+        let active_bytes = config.width * config.bytes_per_pixel;
+        let line_length = active_bytes + config.h_blank;
+        let frame_length = config.height + config.v_blank;
+        assert!(line_length < (1 << 16) && frame_length < (1 << 16));
+        Self {
+            clock: Default::default(),
+            pclk: Default::default(),
+            wires: Default::default(),
+            byte_x: Default::default(),
+            line: Default::default(),
+            frame: Default::default(),
+            vsync: Default::default(),
+            href: Default::default(),
+            data: Default::default(),
+            x: Default::default(),
+            y: Default::default(),
+            pixel: Default::default(),
+            line_length: Constant::new((line_length - 1).to_bits()),
+            active_bytes: Constant::new(active_bytes.to_bits()),
+            frame_length: Constant::new((frame_length - 1).to_bits()),
+            v_blank: Constant::new(config.v_blank.to_bits()),
+            two_bytes: Constant::new(config.bytes_per_pixel == 2),
+        }
+    }
+}
+
+impl Logic for DVPCameraSimulator {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, byte_x, line, frame, vsync, href, data);
+        self.pclk.next = self.clock.val();
+        self.wires.vsync.next = self.vsync.q.val();
+        self.wires.href.next = self.href.q.val();
+        self.wires.data.next = self.data.q.val();
+        // Step through the bytes of the line, and the lines of the frame
+        self.byte_x.d.next = self.byte_x.q.val() + 1;
+        if self.byte_x.q.val() == self.line_length.val() {
+            self.byte_x.d.next = 0.into();
+            self.line.d.next = self.line.q.val() + 1;
+            if self.line.q.val() == self.frame_length.val() {
+                self.line.d.next = 0.into();
+                self.frame.d.next = self.frame.q.val() + 1;
+            }
+        }
+        // The pixel for the current byte
+        self.x.next = self.byte_x.q.val();
+        if self.two_bytes.val() {
+            self.x.next = self.byte_x.q.val() >> 1;
+        }
+        self.y.next = self.line.q.val() - self.v_blank.val();
+        self.pixel.next = (bit_cast::<16, 8>(self.frame.q.val()) << 8)
+            + (self.y.val() << 6)
+            + (self.x.val() << 1)
+            + self.x.val();
+        self.vsync.d.next = self.line.q.val() < 2_u64.to_bits();
+        self.href.d.next = (self.line.q.val() >= self.v_blank.val())
+            & (self.byte_x.q.val() < self.active_bytes.val());
+        self.data.d.next = bit_cast::<8, 16>(self.pixel.val());
+        if self.two_bytes.val() & !self.byte_x.q.val().get_bit(0) {
+            self.data.d.next = bit_cast::<8, 16>(self.pixel.val() >> 8);
+        }
+    }
+}
+
+#[test]
+fn test_dvp_camera_synthesizes() {
+    let mut uut = DVPCameraSimulator::new(DVPCameraConfig {
+        width: 640,
+        height: 480,
+        bytes_per_pixel: 2,
+        h_blank: 144,
+        v_blank: 10,
+    });
+    uut.connect_all();
+    yosys_validate("dvp_camera", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_dvp_camera_sends_test_pattern() {
+    let config = DVPCameraConfig {
+        width: 6,
+        height: 3,
+        bytes_per_pixel: 2,
+        h_blank: 4,
+        v_blank: 3,
+    };
+    let mut uut = DVPCameraSimulator::new(config);
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<DVPCameraSimulator>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<DVPCameraSimulator>| {
+        let mut x = sim.init()?;
+        for frame in 0..2 {
+            x = sim.watch(|x| x.wires.vsync.val(), x)?;
+            x = sim.watch(|x| !x.wires.vsync.val(), x)?;
+            for y in 0..config.height {
+                x = sim.watch(|x| x.clock.val().clk && x.wires.href.val(), x)?;
+                for px in 0..config.width {
+                    let mut pixel = 0_u16;
+                    for _ in 0..2 {
+                        sim_assert!(sim, x.wires.href.val(), x);
+                        pixel = (pixel << 8) | x.wires.data.val().index() as u16;
+                        wait_clock_cycle!(sim, clock, x);
+                    }
+                    sim_assert_eq!(sim, pixel, dvp_test_pixel(frame, px, y), x);
+                }
+                sim_assert!(sim, !x.wires.href.val(), x);
+            }
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
@@ -1,9 +1,13 @@
 pub mod ad7193_sim;
 pub mod ads8688_sim;
 pub mod ads868x_sim;
+pub mod biss_encoder_sim;
+pub mod dvp_camera_sim;
 pub mod max31856_sim;
 pub mod muxed_ad7193_sim;
 pub mod muxed_ads868x_sim;
 pub mod muxed_max31856_sim;
 pub mod prelude;
+pub mod qspi_flash_sim;
 pub mod sdr_sdram;
+pub mod ssi_encoder_sim;
@@ -1,7 +1,11 @@
 pub use super::ad7193_sim::*;
 pub use super::ads868x_sim::*;
+pub use super::biss_encoder_sim::*;
+pub use super::dvp_camera_sim::*;
 pub use super::max31856_sim::*;
 pub use super::max31856_sim::*;
 pub use super::muxed_ad7193_sim::*;
 pub use super::muxed_ads868x_sim::*;
+pub use super::qspi_flash_sim::*;
+pub use super::ssi_encoder_sim::*;
 pub use crate::sdr_sdram::chip::SDRAMSimulator;
@@ -0,0 +1,253 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum FlashState {
+    Command,
+    Address,
+    Dummy,
+    Data,
+    Unsupported,
+}
+
+// A quad SPI flash that only understands the Fast Read Quad I/O command
+// (in mode 0), with a fixed number of dummy cycles.  The contents are held
+// in a ROM of 2^A bytes, and the address wraps around at the end.  Any other
+// command, or both sides driving the data lines at once, raises
+// `test_error`.  The edges of the SPI clock are found by sampling it with
+// `clock`, which must run at least 4 times faster.
+#[derive(LogicBlock)]
+pub struct QSPIFlashSimulator<const A: usize> {
+    pub clock: Signal<In, Clock>,
+    pub wires: QSPIWiresSlave,
+    pub test_error: Signal<Out, Bit>,
+    rom: ROM<Bits<8>, A>,
+    state: DFF<FlashState>,
+    prev_sclk: DFF<Bit>,
+    rising: Signal<Local, Bit>,
+    falling: Signal<Local, Bit>,
+    command: DFF<Bits<8>>,
+    command_next: Signal<Local, Bits<8>>,
+    address: DFF<Bits<24>>,
+    count: DFF<Bits<8>>,
+    dq: DFF<Bits<4>>,
+    drive: DFF<Bit>,
+    high_nibble: DFF<Bit>,
+    error: DFF<Bit>,
+    last_dummy: Constant<Bits<8>>,
+    fast_read: Constant<Bits<8>>,
+}
+
+impl<const A: usize> QSPIFlashSimulator<A> {
+    pub fn new(dummy_cycles: usize, contents: &[u8]) -> Self {
+        assert!(dummy_cycles > 0 && dummy_cycles <= 256);
+        assert!(contents.len() <= (1 << A));
+        Self {
+            clock: Default::default(),
+            wires: Default::default(),
+            test_error: Default::default(),
+            rom: contents.iter().map(|x| x.to_bits()).into(),
+            state: Default::default(),
+            prev_sclk: Default::default(),
+            rising: Default::default(),
+            falling: Default::default(),
+            command: Default::default(),
+            command_next: Default::default(),
+            address: Default::default(),
+            count: Default::default(),
+            dq: Default::default(),
+            drive: Default::default(),
+            high_nibble: Default::default(),
+            error: Default::default(),
+            last_dummy: Constant::new((dummy_cycles - 1).to_bits()),
+            fast_read: Constant::new(QSPI_FAST_READ_QUAD_IO.to_bits()),
+        }
+    }
+}
+
+impl<const A: usize> Logic for QSPIFlashSimulator<A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            state,
+            prev_sclk,
+            command,
+            address,
+            count,
+            dq,
+            drive,
+            high_nibble,
+            error
+        );
+        self.prev_sclk.d.next = self.wires.sclk.val();
+        self.rising.next = self.wires.sclk.val() & !self.prev_sclk.q.val();
+        self.falling.next = !self.wires.sclk.val() & self.prev_sclk.q.val();
+        self.command_next.next =
+            (self.command.q.val() << 1) | bit_cast::<8, 4>(self.wires.dq_out.val() & 1);
+        self.rom.address.next = bit_cast::<A, 24>(self.address.q.val());
+        self.wires.dq_in.next = self.dq.q.val();
+        self.test_error.next = self.error.q.val();
+        if self.drive.q.val() & self.wires.dq_oe.val() {
+            self.error.d.next = true;
+        }
+        if self.wires.cs_not.val() {
+            self.state.d.next = FlashState::Command;
+            self.count.d.next = 0.into();
+            self.drive.d.next = false;
+        } else if self.rising.val() {
+            match self.state.q.val() {
+                FlashState::Command => {
+                    self.command.d.next = self.command_next.val();
+                    self.count.d.next = self.count.q.val() + 1;
+                    if self.count.q.val() == 7 {
+                        self.count.d.next = 0.into();
+                        if self.command_next.val() == self.fast_read.val() {
+                            self.state.d.next = FlashState::Address;
+                        } else {
+                            self.error.d.next = true;
+                            self.state.d.next = FlashState::Unsupported;
+                        }
+                    }
+                }
+                FlashState::Address => {
+                    self.address.d.next =
+                        (self.address.q.val() << 4) | bit_cast::<24, 4>(self.wires.dq_out.val());
+                    self.count.d.next = self.count.q.val() + 1;
+                    if self.count.q.val() == 5 {
+                        self.count.d.next = 0.into();
+                        self.state.d.next = FlashState::Dummy;
+                    }
+                }
+                FlashState::Dummy => {
+                    self.count.d.next = self.count.q.val() + 1;
+                    if self.count.q.val() == self.last_dummy.val() {
+                        self.high_nibble.d.next = true;
+                        self.state.d.next = FlashState::Data;
+                    }
+                }
+                _ => {}
+            }
+        }
+        // The data changes on the falling edge, most significant nibble first
+        if !self.wires.cs_not.val() & self.falling.val() & (self.state.q.val() == FlashState::Data)
+        {
+            self.drive.d.next = true;
+            self.high_nibble.d.next = !self.high_nibble.q.val();
+            if self.high_nibble.q.val() {
+                self.dq.d.next = bit_cast::<4, 8>(self.rom.data.val() >> 4);
+            } else {
+                self.dq.d.next = bit_cast::<4, 8>(self.rom.data.val());
+                self.address.d.next = self.address.q.val() + 1;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_qspi_flash_synthesizes() {
+    let mut uut = QSPIFlashSimulator::<8>::new(6, &[0xDE, 0xAD, 0xBE, 0xEF]);
+    uut.connect_all();
+    yosys_validate("qspi_flash", &generate_verilog(&uut)).unwrap();
+}
+
+#[derive(LogicBlock)]
+struct TestXIP {
+    clock: Signal<In, Clock>,
+    reader: QSPIXIPReader,
+    flash: QSPIFlashSimulator<10>,
+}
+
+impl Logic for TestXIP {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, reader, flash);
+        QSPIWiresMaster::join(&mut self.reader.wires, &mut self.flash.wires);
+    }
+}
+
+#[cfg(test)]
+fn flash_contents() -> Vec<u8> {
+    (0..1024_u32)
+        .map(|x| (x.wrapping_mul(0x9E37_79B9) >> 24) as u8)
+        .collect()
+}
+
+#[cfg(test)]
+fn mk_test_xip() -> TestXIP {
+    let mut uut = TestXIP {
+        clock: Default::default(),
+        reader: QSPIXIPReader::new(QSPIConfig {
+            clock_speed: 48_000_000,
+            speed_hz: 8_000_000,
+            dummy_cycles: 6,
+        }),
+        flash: QSPIFlashSimulator::new(6, &flash_contents()),
+    };
+    uut.reader.address.connect();
+    uut.reader.read.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_yosys_validate_xip_fixture() {
+    let uut = mk_test_xip();
+    yosys_validate("qspi_xip", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_xip_reads_through_line_cache() {
+    let uut = mk_test_xip();
+    let contents = flash_contents();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TestXIP>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<TestXIP>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        // A run of straight line code, a jump back into the cached line, a
+        // jump to another line, and one that misses by one line
+        for (address, hit) in [
+            (0x100, false),
+            (0x104, true),
+            (0x108, true),
+            (0x10C, true),
+            (0x110, false),
+            (0x114, true),
+            (0x118, true),
+            (0x114, true),
+            (0x3F0, false),
+            (0x3E0, false),
+            (0x3E4, true),
+        ] {
+            wait_clock_true!(sim, clock, x);
+            x.reader.address.next = address.to_bits();
+            x.reader.read.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.reader.read.next = false;
+            let mut clocks = 0;
+            while !x.reader.valid.val() {
+                wait_clock_cycle!(sim, clock, x);
+                clocks += 1;
+            }
+            if hit {
+                sim_assert_eq!(sim, clocks, 0, x);
+            } else {
+                sim_assert!(sim, clocks > 50, x);
+            }
+            let word = u32::from_le_bytes([
+                contents[address],
+                contents[address + 1],
+                contents[address + 2],
+                contents[address + 3],
+            ]);
+            sim_assert_eq!(sim, x.reader.data.val(), word.to_bits::<32>(), x);
+            sim_assert!(sim, !x.flash.test_error.val(), x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 1_000_000, &vcd_path!("qspi_xip.vcd"))
+        .unwrap();
+}
@@ -47,20 +47,23 @@ pub struct MemoryBank<const R: usize, const C: usize, const A: usize, const D: u
     active_col: DFF<Bits<C>>,
     delay_counter: DFF<Bits<32>>,
     t_activate: DFF<Bits<32>>,
-    t_ras: Constant<Bits<32>>, // Min time from activate to precharge
-    t_rc: Constant<Bits<32>>,  // Min time from active to activate
-    t_rcd: Constant<Bits<32>>, // Min time from active to read/write
-    t_rp: Constant<Bits<32>>,  // Precharge command time
-    t_wr: Constant<Bits<32>>,  // Write recovery time
+    t_ras: Constant<Bits<32>>,     // Min time from activate to precharge
+    t_ras_max: Constant<Bits<32>>, // Max time a row can be left open
+    t_rc: Constant<Bits<32>>,      // Min time from active to activate
+    t_rcd: Constant<Bits<32>>,     // Min time from active to read/write
+    t_rp: Constant<Bits<32>>,      // Precharge command time
+    t_wr: Constant<Bits<32>>,      // Write recovery time
     t_refresh_max: Constant<Bits<32>>,
     t_rfc: Constant<Bits<32>>,
     row_shift: Constant<Bits<A>>,
+    row_open: Signal<Local, Bit>,
 }
 
 impl<const R: usize, const C: usize, const A: usize, const D: usize> MemoryBank<R, C, A, D> {
     pub fn new(timings: MemoryTimings) -> Self {
         assert_eq!(R + C, A);
         let t_ras = timings.t_ras() - 1;
+        let t_ras_max = timings.t_ras_max() - 1;
         let t_rc = timings.t_rc() - 1;
         let t_rcd = timings.t_rcd() - 1;
         let t_rp = timings.t_rp() - 1;
@@ -94,6 +97,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> MemoryBank<
             refresh_active: Default::default(),
             t_activate: Default::default(),
             t_ras: Constant::new(t_ras.to_bits()),
+            t_ras_max: Constant::new(t_ras_max.to_bits()),
             t_rc: Constant::new(t_rc.to_bits()),
             t_rcd: Constant::new(t_rcd.to_bits()),
             t_rp: Constant::new(t_rp.to_bits()),
@@ -101,6 +105,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> MemoryBank<
             t_refresh_max: Constant::new(t_refresh_max.to_bits()),
             t_rfc: Constant::new(t_rfc.to_bits()),
             row_shift: Constant::new(C.to_bits()),
+            row_open: Default::default(),
         }
     }
 }
@@ -150,6 +155,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
         self.read_delay_line.delay.next = self.cas_delay.val() - 1;
         self.read_valid.next = self.read_delay_line.data_out.val();
         self.refresh_counter.d.next = self.refresh_counter.q.val() + self.refresh_active.q.val();
+        self.row_open.next = false;
         match self.state.q.val() {
             BankState::Boot => {
                 self.t_activate.d.next = 0xFFFF.into();
@@ -177,8 +183,11 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
                         SDRAMCommand::NOP => {}
                         SDRAMCommand::Precharge => {} // See ISSI docs.  Precharging an idle bank is a NOP
                         SDRAMCommand::AutoRefresh => {
-                            if self.refresh_active.q.val()
-                                & (self.refresh_counter.q.val() < self.t_rc.val())
+                            // The refresh activates a row internally, so tRC applies
+                            // to both the previous refresh and the previous activate
+                            if (self.refresh_active.q.val()
+                                & (self.refresh_counter.q.val() < self.t_rc.val()))
+                                | (self.t_activate.q.val() < self.t_rc.val())
                             {
                                 self.state.d.next = BankState::Error;
                             } else {
@@ -195,6 +204,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
                 }
             }
             BankState::Active => {
+                self.row_open.next = true;
                 if self.select.val() {
                     match self.cmd.val() {
                         SDRAMCommand::NOP => {}
@@ -238,6 +248,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
                 }
             }
             BankState::Reading => {
+                self.row_open.next = true;
                 // Process the read command
                 self.burst_counter.d.next = self.burst_counter.q.val() + 1;
                 self.active_col.d.next = self.active_col.q.val() + 1;
@@ -304,6 +315,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
                 }
             }
             BankState::Writing => {
+                self.row_open.next = true;
                 self.mem.write_enable.next = true;
                 // Process the write command
                 self.burst_counter.d.next = self.burst_counter.q.val() + 1;
@@ -345,6 +357,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
                 self.error.next = true;
             }
             BankState::WriteRecovery => {
+                self.row_open.next = true;
                 if self.delay_counter.q.val() == self.t_wr.val() {
                     self.state.d.next = BankState::Active;
                 }
@@ -376,6 +389,10 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
         if self.refresh_counter.q.val() >= self.t_refresh_max.val() {
             self.state.d.next = BankState::Error;
         }
+        // A row cannot be held open indefinitely - it must be precharged within tRAS(max)
+        if self.row_open.val() & (self.t_activate.q.val() > self.t_ras_max.val()) {
+            self.state.d.next = BankState::Error;
+        }
     }
 }
 
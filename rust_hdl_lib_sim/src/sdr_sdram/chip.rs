@@ -38,6 +38,8 @@ pub struct SDRAMSimulator<
     burst_type: DFF<Bit>,
     burst_len: DFF<Bits<3>>,
     op_mode: DFF<Bits<2>>,
+    // Number of clocks since the last activate on any bank
+    t_activate: DFF<Bits<32>>,
     banks: [MemoryBank<R, C, A, D>; 4],
     // Timings
     // Number of clocks to delay for boot initialization
@@ -65,7 +67,8 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
             cas_latency,
             burst_type,
             burst_len,
-            op_mode
+            op_mode,
+            t_activate
         );
         // Connect the command decoder to the bus
         self.decode.we_not.next = self.sdram.we_not.val();
@@ -75,6 +78,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
         self.cmd.next = self.decode.cmd.val();
         self.test_error.next = false;
         self.test_ready.next = false;
+        self.t_activate.d.next = self.t_activate.q.val() + 1;
         // Connect up the banks to the I/O buffer
         self.sdram.read_data.next = 0.into();
         for i in 0..4 {
@@ -212,6 +216,33 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> Logic
             }
             MasterState::Ready => {
                 self.test_ready.next = true;
+                match self.cmd.val() {
+                    SDRAMCommand::Active => {
+                        // Activates to different banks must be separated by tRRD
+                        if self.t_activate.q.val() < self.t_rrd.val() {
+                            self.state.d.next = MasterState::Error;
+                        }
+                        self.t_activate.d.next = 0.into();
+                    }
+                    SDRAMCommand::LoadModeRegister => {
+                        // The mode register can only be changed with all banks idle
+                        if self.banks_busy.val() {
+                            self.state.d.next = MasterState::Error;
+                        } else {
+                            self.counter.d.next = 0.into();
+                            self.state.d.next = MasterState::LoadModeRegister;
+                            self.burst_len.d.next = self.sdram.address.val().get_bits::<3>(0);
+                            self.burst_type.d.next = self.sdram.address.val().get_bit(3);
+                            self.cas_latency.d.next = self.sdram.address.val().get_bits::<3>(4);
+                            self.op_mode.d.next = self.sdram.address.val().get_bits::<2>(7);
+                            self.write_burst_mode.d.next = self.sdram.address.val().get_bit(9);
+                            if self.sdram.address.val().get_bits::<2>(10) != 0 {
+                                self.state.d.next = MasterState::Error;
+                            }
+                        }
+                    }
+                    _ => {}
+                }
             }
             _ => {
                 self.state.d.next = MasterState::Boot;
@@ -246,6 +277,7 @@ impl<const R: usize, const C: usize, const A: usize, const D: usize> SDRAMSimula
             burst_type: Default::default(),
             burst_len: Default::default(),
             op_mode: Default::default(),
+            t_activate: Default::default(),
             banks: array_init::array_init(|_| MemoryBank::new(timings)),
             boot_delay: Constant::new(boot_delay.to_bits()),
             t_rp: Constant::new(precharge_delay.to_bits()),
@@ -526,3 +558,246 @@ fn test_sdram_init_works() {
     sim.run_to_file(Box::new(uut), 200_000_000, &vcd_path!("sdr_init.vcd"))
         .unwrap()
 }
+
+#[cfg(test)]
+type TestSDRAM = SDRAMSimulator<5, 5, 10, 16>;
+
+// A sequence of commands sent to the chip by a testbench
+#[cfg(test)]
+type CommandSequence = fn(
+    &mut Sim<TestSDRAM>,
+    Box<TestSDRAM>,
+    MemoryTimings,
+) -> std::result::Result<Box<TestSDRAM>, SimError>;
+
+// Boot the simulated chip, set a burst length of 8 and CAS latency of 3, and then
+// run the supplied command sequence.  Returns the state of the error flag afterwards.
+#[cfg(test)]
+fn sdram_sim_flags_error(timings: MemoryTimings, sequence: CommandSequence) -> bool {
+    let mut uut = SDRAMSimulator::new(timings);
+    uut.sdram.link_connect_dest();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(4000, |x: &mut Box<TestSDRAM>| {
+        x.sdram.clk.next = !x.sdram.clk.val();
+    });
+    let error = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(false));
+    let error_flag = error.clone();
+    sim.add_testbench(move |mut sim: Sim<TestSDRAM>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 16);
+        sdram_boot!(sim, clock, x, timings);
+        sdram_cmd!(x, SDRAMCommand::LoadModeRegister);
+        x.sdram.address.next = 0b000_0_00_011_0_011.into();
+        wait_clock_cycle!(sim, clock, x);
+        sdram_cmd!(x, SDRAMCommand::NOP);
+        wait_clock_cycles!(sim, clock, x, 5);
+        sim_assert_eq!(sim, x.state.q.val(), MasterState::Ready, x);
+        x = sequence(&mut sim, x, timings)?;
+        sdram_cmd!(x, SDRAMCommand::NOP);
+        wait_clock_cycles!(sim, clock, x, 4);
+        error_flag.store(x.test_error.val(), std::sync::atomic::Ordering::SeqCst);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 200_000_000).unwrap();
+    error.load(std::sync::atomic::Ordering::SeqCst)
+}
+
+#[test]
+fn test_sdram_sim_accepts_legal_sequence() {
+    assert!(!sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, timings| {
+            sdram_activate!(sim, clock, x, 0, 3);
+            wait_clock_cycles!(sim, clock, x, timings.t_rrd());
+            sdram_activate!(sim, clock, x, 1, 3);
+            wait_clock_cycles!(sim, clock, x, timings.t_rcd());
+            sdram_write!(sim, clock, x, 1, 0, [1, 2, 3, 4, 5, 6, 7, 8]);
+            sdram_cmd!(x, SDRAMCommand::NOP);
+            wait_clock_cycles!(sim, clock, x, timings.t_wr() + 1);
+            sdram_precharge_one!(sim, clock, x, 1);
+            sdram_precharge_one!(sim, clock, x, 0);
+            wait_clock_cycles!(sim, clock, x, timings.t_rp());
+            sdram_refresh!(sim, clock, x, timings);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_rp_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, timings| {
+            sdram_activate!(sim, clock, x, 0, 3);
+            wait_clock_cycles!(sim, clock, x, timings.t_ras());
+            sdram_precharge_one!(sim, clock, x, 0);
+            // Re-open the bank without waiting for the precharge to complete
+            sdram_activate!(sim, clock, x, 0, 4);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_rfc_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, _timings| {
+            sdram_cmd!(x, SDRAMCommand::AutoRefresh);
+            wait_clock_cycle!(sim, clock, x);
+            sdram_cmd!(x, SDRAMCommand::NOP);
+            wait_clock_cycles!(sim, clock, x, 2);
+            sdram_activate!(sim, clock, x, 0, 3);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_ras_min_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, _timings| {
+            sdram_activate!(sim, clock, x, 0, 3);
+            wait_clock_cycle!(sim, clock, x);
+            sdram_precharge_one!(sim, clock, x, 0);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_ras_max_violation() {
+    // Shorten tRAS(max) so that it expires before a refresh is due
+    let timings = MemoryTimings {
+        t_ras_row_active_max_time_nanoseconds: 400.0,
+        ..MemoryTimings::fast_boot_sim(125e6)
+    };
+    assert!(sdram_sim_flags_error(timings, |sim, mut x, timings| {
+        sdram_activate!(sim, clock, x, 0, 3);
+        wait_clock_cycles!(sim, clock, x, timings.t_ras_max() + 1);
+        sdram_precharge_one!(sim, clock, x, 0);
+        Ok(x)
+    }));
+    assert!(!sdram_sim_flags_error(timings, |sim, mut x, timings| {
+        sdram_activate!(sim, clock, x, 0, 3);
+        wait_clock_cycles!(sim, clock, x, timings.t_ras_max() - 1);
+        sdram_precharge_one!(sim, clock, x, 0);
+        Ok(x)
+    }));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_rc_violation() {
+    // Make tRC longer than tRAS + tRP so that it is the binding constraint.
+    // tRFC cannot be shorter than tRC, or the boot sequence refreshes would fail.
+    let timings = MemoryTimings {
+        t_rc_row_to_row_min_time_nanoseconds: 120.0,
+        t_rfc_autorefresh_period_nanoseconds: 120.0,
+        ..MemoryTimings::fast_boot_sim(125e6)
+    };
+    assert!(sdram_sim_flags_error(timings, |sim, mut x, timings| {
+        sdram_activate!(sim, clock, x, 0, 3);
+        wait_clock_cycles!(sim, clock, x, timings.t_ras());
+        sdram_precharge_one!(sim, clock, x, 0);
+        wait_clock_cycles!(sim, clock, x, timings.t_rp());
+        sdram_activate!(sim, clock, x, 0, 4);
+        Ok(x)
+    }));
+    // A refresh also counts as an activate for the purposes of tRC
+    assert!(sdram_sim_flags_error(timings, |sim, mut x, timings| {
+        sdram_activate!(sim, clock, x, 0, 3);
+        wait_clock_cycles!(sim, clock, x, timings.t_ras());
+        sdram_precharge_one!(sim, clock, x, 0);
+        wait_clock_cycles!(sim, clock, x, timings.t_rp());
+        sdram_refresh!(sim, clock, x, timings);
+        Ok(x)
+    }));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_rcd_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, _timings| {
+            sdram_activate!(sim, clock, x, 0, 3);
+            sdram_write!(sim, clock, x, 0, 0, [1, 2, 3, 4, 5, 6, 7, 8]);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_rrd_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, _timings| {
+            sdram_activate!(sim, clock, x, 0, 3);
+            sdram_activate!(sim, clock, x, 1, 3);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_t_wr_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, timings| {
+            sdram_activate!(sim, clock, x, 0, 3);
+            wait_clock_cycles!(sim, clock, x, timings.t_ras());
+            sdram_write!(sim, clock, x, 0, 0, [1, 2, 3, 4, 5, 6, 7, 8]);
+            sdram_cmd!(x, SDRAMCommand::NOP);
+            wait_clock_cycle!(sim, clock, x);
+            // Precharge while the bank is still in write recovery
+            sdram_precharge_one!(sim, clock, x, 0);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_load_mode_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, _timings| {
+            sdram_cmd!(x, SDRAMCommand::LoadModeRegister);
+            x.sdram.address.next = 0b000_0_00_011_0_011.into();
+            wait_clock_cycle!(sim, clock, x);
+            sdram_activate!(sim, clock, x, 0, 3);
+            Ok(x)
+        }
+    ));
+    // The mode register cannot be loaded while a row is open
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, _timings| {
+            sdram_activate!(sim, clock, x, 0, 3);
+            sdram_cmd!(x, SDRAMCommand::LoadModeRegister);
+            x.sdram.address.next = 0b000_0_00_011_0_011.into();
+            wait_clock_cycle!(sim, clock, x);
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_flags_refresh_interval_violation() {
+    assert!(sdram_sim_flags_error(
+        MemoryTimings::fast_boot_sim(125e6),
+        |sim, mut x, timings| {
+            wait_clock_cycles!(sim, clock, x, timings.t_refresh_max());
+            Ok(x)
+        }
+    ));
+}
+
+#[test]
+fn test_sdram_sim_banks_share_a_module() {
+    let uut = mk_sdr_sim();
+    let vlog = generate_verilog(&uut);
+    assert_eq!(vlog.matches("module top$banks$0(").count(), 1);
+    assert!(!vlog.contains("module top$banks$1("));
+    assert_eq!(vlog.matches("top$banks$0 banks$").count(), 4);
+}
@@ -0,0 +1,227 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_widgets::prelude::*;
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum EncoderState {
+    Idle,
+    Shift,
+    Monoflop,
+}
+
+// An SSI absolute encoder with an `N` bit position.  The first falling edge
+// of the clock latches `position` (as gray code if `gray_code` is set), and
+// the bits go out MSB first on the rising edges that follow.  The rising edge
+// after the last bit pulls the data line low, and it stays low until the clock
+// has been idle for the monoflop time.  The edges of the SSI clock are found by
+// sampling it with `clock`, which must run at least 4 times faster.
+#[derive(LogicBlock)]
+pub struct SSIEncoderSimulator<const N: usize> {
+    pub clock: Signal<In, Clock>,
+    pub wires: SSIWiresSlave,
+    pub position: Signal<In, Bits<N>>,
+    state: DFF<EncoderState>,
+    prev_clk: DFFWithInit<Bit>,
+    rising: Signal<Local, Bit>,
+    falling: Signal<Local, Bit>,
+    shift: DFF<Bits<N>>,
+    data: DFFWithInit<Bit>,
+    count: DFF<Bits<8>>,
+    msb: Constant<Bits<8>>,
+    timer: DFF<Bits<32>>,
+    gray: BinaryToGray<N>,
+    frame_bits: Constant<Bits<8>>,
+    monoflop: Constant<Bits<32>>,
+    gray_code: Constant<Bit>,
+}
+
+impl<const N: usize> SSIEncoderSimulator<N> {
+    pub fn new(clock_speed: u64, monoflop_us: u64, gray_code: bool) -> Self {
+        assert!((1..256).contains(&N));
+        Self {
+            clock: Default::default(),
+            wires: Default::default(),
+            position: Default::default(),
+            state: Default::default(),
+            prev_clk: DFFWithInit::new(true),
+            rising: Default::default(),
+            falling: Default::default(),
+            shift: Default::default(),
+            data: DFFWithInit::new(true),
+            count: Default::default(),
+            msb: Constant::new((N - 1).to_bits()),
+            timer: Default::default(),
+            gray: Default::default(),
+            frame_bits: Constant::new(N.to_bits()),
+            monoflop: Constant::new((clock_speed * monoflop_us / 1_000_000).to_bits()),
+            gray_code: Constant::new(gray_code),
+        }
+    }
+}
+
+impl<const N: usize> Logic for SSIEncoderSimulator<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state, prev_clk, shift, data, count, timer);
+        self.prev_clk.d.next = self.wires.clk.val();
+        self.rising.next = self.wires.clk.val() & !self.prev_clk.q.val();
+        self.falling.next = !self.wires.clk.val() & self.prev_clk.q.val();
+        self.gray.data_in.next = self.position.val();
+        self.wires.data.next = self.data.q.val();
+        match self.state.q.val() {
+            EncoderState::Idle => {
+                self.data.d.next = true;
+                if self.falling.val() {
+                    self.shift.d.next = self.position.val();
+                    if self.gray_code.val() {
+                        self.shift.d.next = self.gray.data_out.val();
+                    }
+                    self.count.d.next = 0.into();
+                    self.state.d.next = EncoderState::Shift;
+                }
+            }
+            EncoderState::Monoflop => {
+                self.timer.d.next = self.timer.q.val() + 1;
+                if self.rising.val() | self.falling.val() {
+                    self.timer.d.next = 0.into();
+                }
+                if self.timer.q.val() == self.monoflop.val() {
+                    self.data.d.next = true;
+                    self.state.d.next = EncoderState::Idle;
+                }
+            }
+            _ => {}
+        }
+        if (self.state.q.val() == EncoderState::Shift) & self.rising.val() {
+            if self.count.q.val() == self.frame_bits.val() {
+                self.data.d.next = false;
+                self.timer.d.next = 0.into();
+                self.state.d.next = EncoderState::Monoflop;
+            } else {
+                self.data.d.next = self.shift.q.val().get_bit(self.msb.val().index());
+                self.shift.d.next = self.shift.q.val() << 1;
+                self.count.d.next = self.count.q.val() + 1;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ssi_encoder_synthesizes() {
+    let mut uut = SSIEncoderSimulator::<13>::new(100_000_000, 20, true);
+    uut.connect_all();
+    yosys_validate("ssi_encoder", &generate_verilog(&uut)).unwrap();
+}
+
+#[derive(LogicBlock)]
+struct TestSSI {
+    clock: Signal<In, Clock>,
+    master: SSIMaster<13>,
+    encoder: SSIEncoderSimulator<13>,
+}
+
+impl Logic for TestSSI {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, master, encoder);
+        SSIWiresMaster::join(&mut self.master.wires, &mut self.encoder.wires);
+    }
+}
+
+#[cfg(test)]
+fn mk_test_ssi(gray_code: bool) -> TestSSI {
+    let mut uut = TestSSI {
+        clock: Default::default(),
+        master: SSIMaster::new(SSIConfig {
+            clock_speed: 100_000_000,
+            speed_hz: 2_000_000,
+            gray_code,
+            timeout_us: 40,
+        }),
+        encoder: SSIEncoderSimulator::new(100_000_000, 20, gray_code),
+    };
+    uut.master.start.connect();
+    uut.encoder.position.connect();
+    uut.connect_all();
+    uut
+}
+
+#[test]
+fn test_yosys_validate_ssi_fixture() {
+    let uut = mk_test_ssi(true);
+    yosys_validate("ssi_fixture", &generate_verilog(&uut)).unwrap();
+}
+
+#[cfg(test)]
+fn ssi_read_test(gray_code: bool, name: &str) {
+    let uut = mk_test_ssi(gray_code);
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<TestSSI>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<TestSSI>| {
+        let mut x = sim.init()?;
+        wait_clock_cycles!(sim, clock, x, 10);
+        // Back to back reads have to wait for the monoflop
+        for position in [0x1234_u64, 0x0, 0x1FFF, 0x0AAA, 0x1555] {
+            x.encoder.position.next = position.to_bits();
+            wait_clock_true!(sim, clock, x);
+            x.master.start.next = true;
+            wait_clock_cycle!(sim, clock, x);
+            x.master.start.next = false;
+            x = sim.watch(|x| x.master.valid.val(), x)?;
+            sim_assert!(sim, !x.master.error.val(), x);
+            sim_assert_eq!(sim, x.master.position.val(), position, x);
+            wait_clock_cycle!(sim, clock, x);
+        }
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 2_000_000, &vcd_path!(name))
+        .unwrap();
+}
+
+#[test]
+fn test_ssi_binary_reads() {
+    ssi_read_test(false, "ssi_binary.vcd");
+}
+
+#[test]
+fn test_ssi_gray_code_reads() {
+    ssi_read_test(true, "ssi_gray.vcd");
+}
+
+#[test]
+fn test_ssi_stuck_data_line_is_an_error() {
+    let mut uut = SSIMaster::<13>::new(SSIConfig {
+        clock_speed: 100_000_000,
+        speed_hz: 2_000_000,
+        gray_code: false,
+        timeout_us: 40,
+    });
+    uut.start.connect();
+    uut.wires.data.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<SSIMaster<13>>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_testbench(move |mut sim: Sim<SSIMaster<13>>| {
+        let mut x = sim.init()?;
+        // With the data line stuck high, the frame is not ended by the monoflop
+        x.wires.data.next = true;
+        wait_clock_cycles!(sim, clock, x, 10);
+        x.start.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.start.next = false;
+        x = sim.watch(|x| x.valid.val(), x)?;
+        sim_assert!(sim, x.error.val(), x);
+        // With the data line stuck low, the encoder never becomes ready
+        x.wires.data.next = false;
+        wait_clock_cycles!(sim, clock, x, 10);
+        x.start.next = true;
+        wait_clock_cycle!(sim, clock, x);
+        x.start.next = false;
+        x = sim.watch(|x| x.valid.val(), x)?;
+        sim_assert!(sim, x.error.val(), x);
+        sim.done(x)
+    });
+    sim.run_to_file(Box::new(uut), 200_000, &vcd_path!("ssi_stuck.vcd"))
+        .unwrap();
+}
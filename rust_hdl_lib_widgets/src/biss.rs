@@ -0,0 +1,334 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::dff_with_init::DFFWithInit;
+use crate::synchronizer::BitSynchronizer;
+use rust_hdl_lib_core::prelude::*;
+
+/// The wires of a BiSS-C link to an encoder.  The master clock (`ma`) idles high, and
+/// the slave data line (`slo`) is high while the encoder is ready for a read.
+#[derive(LogicInterface, Default)]
+#[join = "BiSSWiresSlave"]
+pub struct BiSSWiresMaster {
+    pub ma: Signal<Out, Bit>,
+    pub slo: Signal<In, Bit>,
+}
+
+#[derive(LogicInterface, Default)]
+#[join = "BiSSWiresMaster"]
+pub struct BiSSWiresSlave {
+    pub ma: Signal<In, Bit>,
+    pub slo: Signal<Out, Bit>,
+}
+
+/// Compute the BiSS CRC6 (polynomial `x^6 + x + 1`, start value 0) of the lowest `count`
+/// bits of `bits`, taken MSB first.  The CRC is sent inverted on the wire.
+pub fn biss_crc6(bits: u64, count: usize) -> u8 {
+    let mut crc = 0_u8;
+    for ndx in (0..count).rev() {
+        let feedback = ((crc >> 5) ^ (bits >> ndx) as u8) & 1;
+        crc = (crc << 1) & 0x3F;
+        if feedback != 0 {
+            crc ^= 0x03;
+        }
+    }
+    crc
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct BiSSConfig {
+    /// The frequency of the clock driving the master
+    pub clock_speed: u64,
+    /// The frequency of the master clock (MA)
+    pub speed_hz: u64,
+    /// How long to wait for the encoder to be ready before a read (this needs to be
+    /// longer than the timeout of the encoder)
+    pub timeout_us: u64,
+    /// How many clocks to wait for the acknowledge and start bits from the encoder
+    pub max_ack_clocks: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum BiSSState {
+    Idle,
+    WaitReady,
+    Ack,
+    Frame,
+    Stop,
+    Done,
+}
+
+/// A [BiSSMaster] reads the `N` bit position of a BiSS-C encoder (sensor data only, with
+/// the usual error and warning bits and CRC6).  A read is started by asserting `start`
+/// while not `busy`.  Once the encoder is ready (`slo` is high), the master clocks `ma`
+/// and samples `slo` on each falling edge.  The encoder answers with an acknowledge
+/// (low) while it latches the position, then a start bit (high), a CDS bit (which is
+/// ignored), the position MSB first, the (active low) error and warning bits and the
+/// inverted CRC6 of the position and the error and warning bits.
+///
+/// When the read is done, the position is presented on `position`, and `valid` is
+/// pulsed, along with these flags:
+/// - `encoder_error` and `encoder_warning` when the encoder reported them,
+/// - `crc_error` when the CRC did not match (the position should not be used),
+/// - `timeout` when the encoder was not ready in time, or did not send the acknowledge
+///   and start bits within `max_ack_clocks`.
+#[derive(LogicBlock)]
+pub struct BiSSMaster<const N: usize> {
+    pub clock: Signal<In, Clock>,
+    /// Assert to read the position
+    pub start: Signal<In, Bit>,
+    pub position: Signal<Out, Bits<N>>,
+    pub valid: Signal<Out, Bit>,
+    pub encoder_error: Signal<Out, Bit>,
+    pub encoder_warning: Signal<Out, Bit>,
+    pub crc_error: Signal<Out, Bit>,
+    pub timeout: Signal<Out, Bit>,
+    pub busy: Signal<Out, Bit>,
+    pub wires: BiSSWiresMaster,
+    state: DFF<BiSSState>,
+    ma: DFFWithInit<Bit>,
+    slo_sync: BitSynchronizer,
+    acked: DFF<Bit>,
+    shift: DFF<Bits<N>>,
+    flags: DFF<Bits<2>>,
+    crc: DFF<Bits<6>>,
+    crc_rx: DFF<Bits<6>>,
+    bits: DFF<Bits<8>>,
+    timer: DFF<Bits<32>>,
+    counter: DFF<Bits<16>>,
+    tick: Signal<Local, Bit>,
+    slo: Signal<Local, Bit>,
+    position_flop: DFF<Bits<N>>,
+    valid_flop: DFF<Bit>,
+    error_flop: DFF<Bit>,
+    warning_flop: DFF<Bit>,
+    crc_error_flop: DFF<Bit>,
+    timeout_flop: DFF<Bit>,
+    half_period: Constant<Bits<16>>,
+    ready_timeout: Constant<Bits<32>>,
+    max_ack_clocks: Constant<Bits<8>>,
+    data_end: Constant<Bits<8>>,
+    flags_end: Constant<Bits<8>>,
+    frame_end: Constant<Bits<8>>,
+}
+
+impl<const N: usize> BiSSMaster<N> {
+    pub fn new(config: BiSSConfig) -> Self {
+        assert!((1..240).contains(&N));
+        assert!((1..256).contains(&config.max_ack_clocks));
+        let half_period =
+            (config.clock_speed as f64 / (2.0 * config.speed_hz as f64)).round() as u64;
+        assert!((8..(1 << 16)).contains(&half_period));
+        let ready_timeout = config.clock_speed * config.timeout_us / 1_000_000;
+        assert!(ready_timeout < (1 << 32));
+        Self {
+            clock: Default::default(),
+            start: Default::default(),
+            position: Default::default(),
+            valid: Default::default(),
+            encoder_error: Default::default(),
+            encoder_warning: Default::default(),
+            crc_error: Default::default(),
+            timeout: Default::default(),
+            busy: Default::default(),
+            wires: Default::default(),
+            state: Default::default(),
+            ma: DFFWithInit::new(true),
+            slo_sync: Default::default(),
+            acked: Default::default(),
+            shift: Default::default(),
+            flags: Default::default(),
+            crc: Default::default(),
+            crc_rx: Default::default(),
+            bits: Default::default(),
+            timer: Default::default(),
+            counter: Default::default(),
+            tick: Default::default(),
+            slo: Default::default(),
+            position_flop: Default::default(),
+            valid_flop: Default::default(),
+            error_flop: Default::default(),
+            warning_flop: Default::default(),
+            crc_error_flop: Default::default(),
+            timeout_flop: Default::default(),
+            half_period: Constant::new(half_period.to_bits()),
+            ready_timeout: Constant::new(ready_timeout.to_bits()),
+            max_ack_clocks: Constant::new(config.max_ack_clocks.to_bits()),
+            data_end: Constant::new(N.to_bits()),
+            flags_end: Constant::new((N + 2).to_bits()),
+            frame_end: Constant::new((N + 8).to_bits()),
+        }
+    }
+}
+
+impl<const N: usize> Logic for BiSSMaster<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            state,
+            ma,
+            acked,
+            shift,
+            flags,
+            crc,
+            crc_rx,
+            bits,
+            timer,
+            counter,
+            position_flop,
+            valid_flop,
+            error_flop,
+            warning_flop,
+            crc_error_flop,
+            timeout_flop
+        );
+        clock!(self, clock, slo_sync);
+        self.slo_sync.sig_in.next = self.wires.slo.val();
+        self.slo.next = self.slo_sync.sig_out.val();
+        self.wires.ma.next = self.ma.q.val();
+        self.position.next = self.position_flop.q.val();
+        self.valid.next = self.valid_flop.q.val();
+        self.encoder_error.next = self.error_flop.q.val();
+        self.encoder_warning.next = self.warning_flop.q.val();
+        self.crc_error.next = self.crc_error_flop.q.val();
+        self.timeout.next = self.timeout_flop.q.val();
+        self.busy.next = self.state.q.val() != BiSSState::Idle;
+        self.valid_flop.d.next = false;
+        // One tick for each edge of MA
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.tick.next = self.counter.q.val() == self.half_period.val();
+        if self.tick.val() {
+            self.counter.d.next = 1.into();
+        }
+        match self.state.q.val() {
+            BiSSState::Idle => {
+                self.ma.d.next = true;
+                if self.start.val() {
+                    self.timer.d.next = 0.into();
+                    self.state.d.next = BiSSState::WaitReady;
+                }
+            }
+            BiSSState::WaitReady => {
+                self.timer.d.next = self.timer.q.val() + 1;
+                if self.slo.val() {
+                    self.ma.d.next = false;
+                    self.counter.d.next = 1.into();
+                    self.bits.d.next = 0.into();
+                    self.acked.d.next = false;
+                    self.state.d.next = BiSSState::Ack;
+                } else if self.timer.q.val() == self.ready_timeout.val() {
+                    self.error_flop.d.next = false;
+                    self.warning_flop.d.next = false;
+                    self.crc_error_flop.d.next = false;
+                    self.timeout_flop.d.next = true;
+                    self.valid_flop.d.next = true;
+                    self.state.d.next = BiSSState::Idle;
+                }
+            }
+            BiSSState::Ack => {
+                if self.tick.val() {
+                    self.ma.d.next = !self.ma.q.val();
+                    // Sample on the falling edges - first the acknowledge, then the start bit
+                    if self.ma.q.val() {
+                        self.bits.d.next = self.bits.q.val() + 1;
+                        if !self.slo.val() {
+                            self.acked.d.next = true;
+                        } else if self.acked.q.val() {
+                            self.bits.d.next = 0.into();
+                            self.crc.d.next = 0.into();
+                            self.state.d.next = BiSSState::Frame;
+                        }
+                    } else if self.bits.q.val() == self.max_ack_clocks.val() {
+                        self.error_flop.d.next = false;
+                        self.warning_flop.d.next = false;
+                        self.crc_error_flop.d.next = false;
+                        self.timeout_flop.d.next = true;
+                        self.valid_flop.d.next = true;
+                        self.state.d.next = BiSSState::Idle;
+                    }
+                }
+            }
+            BiSSState::Frame => {
+                if self.tick.val() {
+                    self.ma.d.next = !self.ma.q.val();
+                    if self.ma.q.val() {
+                        // The CDS bit (0) is skipped, then come the position, the
+                        // error and warning bits (that are covered by the CRC), and the CRC
+                        self.bits.d.next = self.bits.q.val() + 1;
+                        if (self.bits.q.val() > 0_u64.to_bits())
+                            & (self.bits.q.val() <= self.flags_end.val())
+                        {
+                            if self.crc.q.val().get_bit(5) ^ self.slo.val() {
+                                self.crc.d.next = (self.crc.q.val() << 1) ^ 3_u64.to_bits();
+                            } else {
+                                self.crc.d.next = self.crc.q.val() << 1;
+                            }
+                        }
+                        if (self.bits.q.val() > 0_u64.to_bits())
+                            & (self.bits.q.val() <= self.data_end.val())
+                        {
+                            self.shift.d.next =
+                                (self.shift.q.val() << 1) | bit_cast::<N, 1>(self.slo.val().into());
+                        } else if (self.bits.q.val() > self.data_end.val())
+                            & (self.bits.q.val() <= self.flags_end.val())
+                        {
+                            self.flags.d.next =
+                                (self.flags.q.val() << 1) | bit_cast::<2, 1>(self.slo.val().into());
+                        } else if self.bits.q.val() > self.flags_end.val() {
+                            self.crc_rx.d.next = (self.crc_rx.q.val() << 1)
+                                | bit_cast::<6, 1>(self.slo.val().into());
+                        }
+                        if self.bits.q.val() == self.frame_end.val() {
+                            self.state.d.next = BiSSState::Stop;
+                        }
+                    }
+                }
+            }
+            BiSSState::Stop => {
+                // The last rising edge of MA starts the timeout in the encoder
+                if self.tick.val() {
+                    self.ma.d.next = true;
+                    self.state.d.next = BiSSState::Done;
+                }
+            }
+            BiSSState::Done => {
+                // Wait half a clock, so that the encoder has pulled SLO low before
+                // the next read looks for it to be ready
+                if self.tick.val() {
+                    self.position_flop.d.next = self.shift.q.val();
+                    self.error_flop.d.next = !self.flags.q.val().get_bit(1);
+                    self.warning_flop.d.next = !self.flags.q.val().get_bit(0);
+                    self.crc_error_flop.d.next = self.crc_rx.q.val() != !self.crc.q.val();
+                    self.timeout_flop.d.next = false;
+                    self.valid_flop.d.next = true;
+                    self.state.d.next = BiSSState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = BiSSState::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_biss_crc6() {
+    // A 6 bit value followed by its CRC has a remainder of zero
+    let crc = biss_crc6(0b101101, 6);
+    assert_eq!(biss_crc6((0b101101 << 6) | crc as u64, 12), 0);
+    assert_eq!(biss_crc6(0, 26), 0);
+    assert_eq!(biss_crc6(1, 1), 0x03);
+}
+
+#[test]
+fn test_biss_master_is_synthesizable() {
+    let mut uut = BiSSMaster::<26>::new(BiSSConfig {
+        clock_speed: 100_000_000,
+        speed_hz: 2_000_000,
+        timeout_us: 40,
+        max_ack_clocks: 16,
+    });
+    uut.connect_all();
+    yosys_validate("biss_master", &generate_verilog(&uut)).unwrap();
+}
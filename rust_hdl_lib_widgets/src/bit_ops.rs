@@ -0,0 +1,305 @@
+use rust_hdl_lib_core::prelude::*;
+use rust_hdl_lib_core::timing::TimingInfo;
+
+// These widgets generate their logic as explicit trees (of depth `clog2(N)`), rather
+// than as a chain of `N` comparisons, which is what a loop in `update` turns into.  If
+// constructed with `registered` set, the outputs are registered on `clock`, otherwise
+// they are combinational, and `clock` is unused.
+
+// The zero counting tree shared by the priority encoder and the leading zero counter.
+// Each node of level `l` covers `2^l` bits of `{prefix}_v0`, and holds whether any of
+// them are set (in `{prefix}_v{l}`) and the number of zeros before the first set bit
+// (`l` bits, in `{prefix}_x{l}`).  With `leading` set, the zeros are counted from the
+// MSB end of the node, otherwise from the LSB end.
+fn zero_count_tree(prefix: &str, leading: bool, levels: usize) -> String {
+    let mut code = vec![];
+    for l in 1..=levels {
+        let nodes = 1 << (levels - l);
+        code.push(format!("wire [{}:0] {prefix}_v{l};", nodes - 1));
+        code.push(format!("wire [{}:0] {prefix}_x{l};", nodes * l - 1));
+        for j in 0..nodes {
+            // The half of the node that is searched first, and the other half
+            let (first, second) = if leading {
+                (2 * j + 1, 2 * j)
+            } else {
+                (2 * j, 2 * j + 1)
+            };
+            code.push(format!(
+                "assign {prefix}_v{l}[{j}] = {prefix}_v{p}[{first}] | {prefix}_v{p}[{second}];",
+                p = l - 1
+            ));
+            if l == 1 {
+                code.push(format!("assign {prefix}_x1[{j}] = ~{prefix}_v0[{first}];"));
+            } else {
+                let p = l - 1;
+                code.push(format!(
+                    "assign {prefix}_x{l}[{lo} +: {l}] = {prefix}_v{p}[{first}] ? {{1'b0, {prefix}_x{p}[{a} +: {p}]}} : {{1'b1, {prefix}_x{p}[{b} +: {p}]}};",
+                    lo = j * l,
+                    a = first * p,
+                    b = second * p,
+                ));
+            }
+        }
+    }
+    code.join("\n")
+}
+
+// The output stage, which is either a register or a combinational assignment
+fn output_stage(registered: bool, assignments: &[(&str, String)]) -> String {
+    let (trigger, op) = if registered {
+        ("posedge clock", "<=")
+    } else {
+        ("*", "=")
+    };
+    let body = assignments
+        .iter()
+        .map(|(name, value)| format!("   {} {} {};", name, op, value))
+        .collect::<Vec<_>>()
+        .join("\n");
+    format!("always @({}) begin\n{}\nend", trigger, body)
+}
+
+// Pads the input out to a power of two bits, with zeros at the MSB end (or the LSB end
+// if `at_lsb` is set).
+fn padded_input<const N: usize>(name: &str, at_lsb: bool) -> String {
+    let size = 1 << clog2(N);
+    if size == N {
+        format!("wire [{}:0] {} = data_in;", N - 1, name)
+    } else if at_lsb {
+        format!(
+            "wire [{}:0] {} = {{data_in, {}'b0}};",
+            size - 1,
+            name,
+            size - N
+        )
+    } else {
+        format!(
+            "wire [{}:0] {} = {{{}'b0, data_in}};",
+            size - 1,
+            name,
+            size - N
+        )
+    }
+}
+
+fn registered_timing(registered: bool, outputs: &[&str]) -> Vec<TimingInfo> {
+    if registered {
+        vec![TimingInfo {
+            name: "bit_ops".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Rising,
+            inputs: vec!["data_in".into()],
+            outputs: outputs.iter().map(|x| x.to_string()).collect(),
+        }]
+    } else {
+        vec![]
+    }
+}
+
+// Finds the index of the lowest set bit of `data_in`.  `valid` is asserted if any bit
+// is set (otherwise `index` is zero).  Bit 0 has the highest priority.
+#[derive(LogicBlock)]
+pub struct PriorityEncoder<const N: usize, const M: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<N>>,
+    pub index: Signal<Out, Bits<M>>,
+    pub valid: Signal<Out, Bit>,
+    _registered: bool,
+}
+
+impl<const N: usize, const M: usize> PriorityEncoder<N, M> {
+    pub fn new(registered: bool) -> Self {
+        assert!(N >= 2);
+        assert!(M >= clog2(N));
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            index: Default::default(),
+            valid: Default::default(),
+            _registered: registered,
+        }
+    }
+}
+
+impl<const N: usize, const M: usize> Logic for PriorityEncoder<N, M> {
+    fn update(&mut self) {
+        if !self._registered || self.clock.pos_edge() {
+            let data = self.data_in.val();
+            let index = (0..N).find(|ndx| data.get_bit(*ndx));
+            self.index.next = index.unwrap_or(0).to_bits();
+            self.valid.next = index.is_some();
+        }
+    }
+
+    fn connect(&mut self) {
+        self.index.connect();
+        self.valid.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        let levels = clog2(N);
+        Verilog::Custom(format!(
+            "{}\n{}\n{}\n",
+            padded_input::<N>("pe_v0", false),
+            zero_count_tree("pe", false, levels),
+            output_stage(
+                self._registered,
+                &[
+                    ("index", format!("pe_v{levels}[0] ? pe_x{levels} : {M}'d0")),
+                    ("valid", format!("pe_v{levels}[0]")),
+                ]
+            )
+        ))
+    }
+
+    fn timing(&self) -> Vec<TimingInfo> {
+        registered_timing(self._registered, &["index", "valid"])
+    }
+}
+
+// Counts the number of zeros above the highest set bit of `data_in`.  If no bit is
+// set, `count` is `N`.
+#[derive(LogicBlock)]
+pub struct LeadingZeroCounter<const N: usize, const M: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<N>>,
+    pub count: Signal<Out, Bits<M>>,
+    _registered: bool,
+}
+
+impl<const N: usize, const M: usize> LeadingZeroCounter<N, M> {
+    pub fn new(registered: bool) -> Self {
+        assert!(N >= 2);
+        assert!(M >= clog2(N + 1));
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            count: Default::default(),
+            _registered: registered,
+        }
+    }
+}
+
+impl<const N: usize, const M: usize> Logic for LeadingZeroCounter<N, M> {
+    fn update(&mut self) {
+        if !self._registered || self.clock.pos_edge() {
+            let data = self.data_in.val();
+            let count = (0..N).take_while(|ndx| !data.get_bit(N - 1 - ndx)).count();
+            self.count.next = count.to_bits();
+        }
+    }
+
+    fn connect(&mut self) {
+        self.count.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        let levels = clog2(N);
+        Verilog::Custom(format!(
+            "{}\n{}\n{}\n",
+            padded_input::<N>("lz_v0", true),
+            zero_count_tree("lz", true, levels),
+            output_stage(
+                self._registered,
+                &[(
+                    "count",
+                    format!("lz_v{levels}[0] ? lz_x{levels} : {M}'d{N}")
+                )]
+            )
+        ))
+    }
+
+    fn timing(&self) -> Vec<TimingInfo> {
+        registered_timing(self._registered, &["count"])
+    }
+}
+
+// Counts the number of set bits in `data_in`, with a tree of adders that grow by one
+// bit per level.
+#[derive(LogicBlock)]
+pub struct PopulationCount<const N: usize, const M: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<N>>,
+    pub count: Signal<Out, Bits<M>>,
+    _registered: bool,
+}
+
+impl<const N: usize, const M: usize> PopulationCount<N, M> {
+    pub fn new(registered: bool) -> Self {
+        assert!(N >= 2);
+        assert!(M >= clog2(N + 1));
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            count: Default::default(),
+            _registered: registered,
+        }
+    }
+}
+
+impl<const N: usize, const M: usize> Logic for PopulationCount<N, M> {
+    fn update(&mut self) {
+        if !self._registered || self.clock.pos_edge() {
+            let data = self.data_in.val();
+            let count = (0..N).filter(|ndx| data.get_bit(*ndx)).count();
+            self.count.next = count.to_bits();
+        }
+    }
+
+    fn connect(&mut self) {
+        self.count.connect();
+    }
+
+    fn hdl(&self) -> Verilog {
+        let levels = clog2(N);
+        let mut code = vec![padded_input::<N>("pc_c0", false)];
+        // Each node of level `l` is `l + 1` bits wide
+        for l in 1..=levels {
+            let nodes = 1 << (levels - l);
+            let p = l - 1;
+            code.push(format!("wire [{}:0] pc_c{l};", nodes * (l + 1) - 1));
+            for j in 0..nodes {
+                code.push(format!(
+                    "assign pc_c{l}[{lo} +: {w}] = pc_c{p}[{a} +: {l}] + pc_c{p}[{b} +: {l}];",
+                    lo = j * (l + 1),
+                    w = l + 1,
+                    a = 2 * j * l,
+                    b = (2 * j + 1) * l,
+                ));
+            }
+        }
+        code.push(output_stage(
+            self._registered,
+            &[("count", format!("pc_c{levels}"))],
+        ));
+        Verilog::Custom(code.join("\n"))
+    }
+
+    fn timing(&self) -> Vec<TimingInfo> {
+        registered_timing(self._registered, &["count"])
+    }
+}
+
+#[test]
+fn test_priority_encoder_is_synthesizable() {
+    let mut uut = PriorityEncoder::<13, 4>::new(false);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("priority_encoder", &vlog).unwrap();
+}
+
+#[test]
+fn test_leading_zero_counter_is_synthesizable() {
+    let mut uut = LeadingZeroCounter::<13, 4>::new(true);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("leading_zero_counter", &vlog).unwrap();
+}
+
+#[test]
+fn test_population_count_is_synthesizable() {
+    let mut uut = PopulationCount::<13, 4>::new(true);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("population_count", &vlog).unwrap();
+}
@@ -0,0 +1,67 @@
+use rust_hdl_lib_core::prelude::*;
+
+// A glitch-free clock gate, which stops a clock (to save the power of the
+// logic, and clock tree, that it drives) while `enable` is low.  The enable
+// is captured on the falling edge of the clock, so it only changes while
+// the clock is low, and the output never sees a runt pulse - it either
+// passes a whole high phase of the clock, or none of it.  An enable that
+// goes high before a rising edge (by at least half a period) lets that
+// edge through.  Where one is available, a clock buffer with a built in
+// enable (like the BUFGCE of Xilinx, or the DCCA of the ECP5, both in the
+// FPGA support library) is preferred, since it keeps the gated clock on the
+// clock network.  To simply hold a register, use a [DFFWithEnable] instead.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct ClockGate {
+    pub clock: Signal<In, Clock>,
+    pub enable: Signal<In, Bit>,
+    pub clock_out: Signal<Out, Clock>,
+    _model: ClockGateModel,
+}
+
+// The simulation model of the gate, which is shared with the vendor clock
+// gates (which behave the same way, as seen from outside).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ClockGateModel {
+    enabled: bool,
+}
+
+impl ClockGateModel {
+    pub fn update(&mut self, clock: &Signal<In, Clock>, enable: bool) -> Clock {
+        if clock.neg_edge() {
+            self.enabled = enable;
+        }
+        Clock {
+            clk: clock.val().clk && self.enabled,
+        }
+    }
+}
+
+impl Logic for ClockGate {
+    fn update(&mut self) {
+        self.clock_out.next = self._model.update(&self.clock, self.enable.val());
+    }
+    fn connect(&mut self) {
+        self.clock_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(
+            "\
+reg enabled;
+
+initial begin
+   enabled = 0;
+end
+
+always @(negedge clock) enabled <= enable;
+always @(*) clock_out = clock & enabled;"
+                .into(),
+        )
+    }
+}
+
+#[test]
+fn test_clock_gate_synthesizes() {
+    let mut uut = ClockGate::default();
+    uut.connect_all();
+    yosys_validate("clock_gate", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,97 @@
+use rust_hdl_lib_core::prelude::*;
+
+// A glitch-free multiplexer between two clocks (which need not be related,
+// like an on-board oscillator and a recovered or PLL clock).  Each clock is
+// gated by an enable that is synchronized onto it in two stages - the first
+// on the rising edge, and the second on the falling edge, so that the enable
+// only ever changes while its clock is low.  A clock is enabled only once the
+// other one has been disabled, so the output never sees a runt pulse.  On a
+// switch, the output holds low for a couple of cycles of each clock, and then
+// follows the new clock.  `select` picks `clock_1` when high, and can come
+// from any clock domain.  The FPGA support library has the same block built
+// on the clock muxes of the Xilinx (BUFGMUX) and ECP5 (DCSC) families, which
+// are preferred where they are available.
+#[derive(Clone, Debug, LogicBlock, Default)]
+pub struct ClockMux {
+    pub clock_0: Signal<In, Clock>,
+    pub clock_1: Signal<In, Clock>,
+    pub select: Signal<In, Bit>,
+    pub clock_out: Signal<Out, Clock>,
+    _model: ClockMuxModel,
+}
+
+// The simulation model of the switch, which is shared with the vendor
+// clock muxes (whose switching behaves the same way, as seen from outside).
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ClockMuxModel {
+    sync_0: bool,
+    enable_0: bool,
+    sync_1: bool,
+    enable_1: bool,
+}
+
+impl ClockMuxModel {
+    pub fn update(
+        &mut self,
+        clock_0: &Signal<In, Clock>,
+        clock_1: &Signal<In, Clock>,
+        select: bool,
+    ) -> Clock {
+        if clock_0.pos_edge() {
+            self.sync_0 = !select && !self.enable_1;
+        }
+        if clock_0.neg_edge() {
+            self.enable_0 = self.sync_0;
+        }
+        if clock_1.pos_edge() {
+            self.sync_1 = select && !self.enable_0;
+        }
+        if clock_1.neg_edge() {
+            self.enable_1 = self.sync_1;
+        }
+        Clock {
+            clk: (clock_0.val().clk && self.enable_0) || (clock_1.val().clk && self.enable_1),
+        }
+    }
+}
+
+impl Logic for ClockMux {
+    fn update(&mut self) {
+        self.clock_out.next = self
+            ._model
+            .update(&self.clock_0, &self.clock_1, self.select.val());
+    }
+    fn connect(&mut self) {
+        self.clock_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(
+            "\
+reg sync_0;
+reg enable_0;
+reg sync_1;
+reg enable_1;
+
+initial begin
+   sync_0 = 0;
+   enable_0 = 0;
+   sync_1 = 0;
+   enable_1 = 0;
+end
+
+always @(posedge clock_0) sync_0 <= ~select & ~enable_1;
+always @(negedge clock_0) enable_0 <= sync_0;
+always @(posedge clock_1) sync_1 <= select & ~enable_0;
+always @(negedge clock_1) enable_1 <= sync_1;
+always @(*) clock_out = (clock_0 & enable_0) | (clock_1 & enable_1);"
+                .into(),
+        )
+    }
+}
+
+#[test]
+fn test_clock_mux_synthesizes() {
+    let mut uut = ClockMux::default();
+    uut.connect_all();
+    yosys_validate("clock_mux", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,172 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use rust_hdl_lib_core::prelude::*;
+
+// Recovers the symbol boundaries from a deserializer that delivers 10 bits
+// per clock at an arbitrary bit offset.  The last two words are searched
+// for a comma (the `0011111` or `1100000` run at the start of K28.1, K28.5
+// and K28.7) at each of the 10 possible offsets, and when one is found
+// (and `enable` is set), the aligner locks onto that offset.  Clearing
+// `enable` freezes the alignment, which is useful once the link is up.
+// There is one clock of latency, on top of the word held for the search.
+#[derive(LogicBlock, Default)]
+pub struct CommaAligner {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<10>>,
+    pub enable: Signal<In, Bit>,
+    pub data_out: Signal<Out, Bits<10>>,
+    // Set when `data_out` starts with a comma
+    pub comma: Signal<Out, Bit>,
+    // Set once a comma has been seen
+    pub locked: Signal<Out, Bit>,
+    previous: DFF<Bits<10>>,
+    offset: DFF<Bits<4>>,
+    aligned: DFF<Bits<10>>,
+    comma_flag: DFF<Bit>,
+    locked_flag: DFF<Bit>,
+    window: Signal<Local, Bits<20>>,
+    found: Signal<Local, Bit>,
+    found_offset: Signal<Local, Bits<4>>,
+    shift: Signal<Local, Bits<4>>,
+}
+
+impl Logic for CommaAligner {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            previous,
+            offset,
+            aligned,
+            comma_flag,
+            locked_flag
+        );
+        self.previous.d.next = self.data_in.val();
+        // The older word holds the earlier bits
+        self.window.next = bit_cast::<20, 10>(self.previous.q.val())
+            | (bit_cast::<20, 10>(self.data_in.val()) << 10);
+        self.found.next = true;
+        self.found_offset.next = 0.into();
+        if (self.window.val().get_bits::<7>(0) == 0x7C)
+            | (self.window.val().get_bits::<7>(0) == 0x03)
+        {
+            self.found_offset.next = 0.into();
+        } else if (self.window.val().get_bits::<7>(1) == 0x7C)
+            | (self.window.val().get_bits::<7>(1) == 0x03)
+        {
+            self.found_offset.next = 1.into();
+        } else if (self.window.val().get_bits::<7>(2) == 0x7C)
+            | (self.window.val().get_bits::<7>(2) == 0x03)
+        {
+            self.found_offset.next = 2.into();
+        } else if (self.window.val().get_bits::<7>(3) == 0x7C)
+            | (self.window.val().get_bits::<7>(3) == 0x03)
+        {
+            self.found_offset.next = 3.into();
+        } else if (self.window.val().get_bits::<7>(4) == 0x7C)
+            | (self.window.val().get_bits::<7>(4) == 0x03)
+        {
+            self.found_offset.next = 4.into();
+        } else if (self.window.val().get_bits::<7>(5) == 0x7C)
+            | (self.window.val().get_bits::<7>(5) == 0x03)
+        {
+            self.found_offset.next = 5.into();
+        } else if (self.window.val().get_bits::<7>(6) == 0x7C)
+            | (self.window.val().get_bits::<7>(6) == 0x03)
+        {
+            self.found_offset.next = 6.into();
+        } else if (self.window.val().get_bits::<7>(7) == 0x7C)
+            | (self.window.val().get_bits::<7>(7) == 0x03)
+        {
+            self.found_offset.next = 7.into();
+        } else if (self.window.val().get_bits::<7>(8) == 0x7C)
+            | (self.window.val().get_bits::<7>(8) == 0x03)
+        {
+            self.found_offset.next = 8.into();
+        } else if (self.window.val().get_bits::<7>(9) == 0x7C)
+            | (self.window.val().get_bits::<7>(9) == 0x03)
+        {
+            self.found_offset.next = 9.into();
+        } else {
+            self.found.next = false;
+        }
+        self.shift.next = self.offset.q.val();
+        if self.found.val() & self.enable.val() {
+            self.shift.next = self.found_offset.val();
+            self.locked_flag.d.next = true;
+        }
+        self.offset.d.next = self.shift.val();
+        self.aligned.d.next = self.window.val().get_bits::<10>(self.shift.val().index());
+        self.comma_flag.d.next = self.found.val() & (self.found_offset.val() == self.shift.val());
+        self.data_out.next = self.aligned.q.val();
+        self.comma.next = self.comma_flag.q.val();
+        self.locked.next = self.locked_flag.q.val();
+    }
+}
+
+#[test]
+fn test_comma_aligner_synthesizes() {
+    let mut uut = CommaAligner::default();
+    uut.connect_all();
+    yosys_validate("comma_aligner", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_comma_aligner_finds_every_offset() {
+    use crate::code8b10b::{encode_8b10b, K28_5};
+    // Build a bit stream of an idle pattern, with data in between commas
+    let mut rd = false;
+    let mut symbols = vec![];
+    for n in 0..64_u32 {
+        let (control, data) = if n % 8 == 0 {
+            (true, K28_5)
+        } else {
+            (false, (n * 29 % 256) as u8)
+        };
+        let (code, rd_out) = encode_8b10b(data, control, rd);
+        rd = rd_out;
+        symbols.push(code);
+    }
+    let bits: Vec<bool> = symbols
+        .iter()
+        .flat_map(|x| (0..10).map(move |b| x & (1 << b) != 0))
+        .collect();
+    for slip in 0..10 {
+        let words: Vec<u64> = bits[slip..]
+            .chunks_exact(10)
+            .map(|w| {
+                w.iter()
+                    .enumerate()
+                    .map(|(ndx, b)| if *b { 1 << ndx } else { 0 })
+                    .sum()
+            })
+            .collect();
+        let symbols = symbols.clone();
+        let mut uut = CommaAligner::default();
+        uut.connect_all();
+        let mut sim = Simulation::new();
+        sim.add_clock(5, |x: &mut Box<CommaAligner>| x.clock.next = !x.clock.val());
+        sim.add_testbench(move |mut sim: Sim<CommaAligner>| {
+            let mut x = sim.init()?;
+            x.enable.next = true;
+            wait_clock_true!(sim, clock, x);
+            let mut matched = 0;
+            for word in &words {
+                x.data_in.next = (*word).into();
+                wait_clock_cycle!(sim, clock, x);
+                if x.locked.val() {
+                    // Once locked, every output word must be a whole symbol
+                    let out = x.data_out.val().index() as u16;
+                    sim_assert!(sim, symbols.contains(&out), x);
+                    if x.comma.val() {
+                        matched += 1;
+                    }
+                }
+            }
+            sim_assert!(sim, matched >= 6, x);
+            sim.done(x)
+        });
+        sim.run(Box::new(uut), 100_000).unwrap();
+    }
+}
@@ -0,0 +1,145 @@
+use crate::code8b10b::decode_8b10b;
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::ramrom::rom::ROM;
+use rust_hdl_lib_core::prelude::*;
+
+// Decodes one code word per clock, with one clock of latency.  Invalid
+// code words raise `code_error`, and valid code words that are not allowed
+// at the current running disparity raise `disparity_error`.  Either one
+// usually means the link has lost alignment.
+#[derive(LogicBlock)]
+pub struct Decoder8b10b {
+    pub clock: Signal<In, Clock>,
+    pub code_in: Signal<In, Bits<10>>,
+    pub data_out: Signal<Out, Bits<8>>,
+    pub control: Signal<Out, Bit>,
+    pub code_error: Signal<Out, Bit>,
+    pub disparity_error: Signal<Out, Bit>,
+    // Running disparity (true when positive)
+    rd: DFF<Bit>,
+    data: DFF<Bits<8>>,
+    control_flag: DFF<Bit>,
+    code_error_flag: DFF<Bit>,
+    disparity_error_flag: DFF<Bit>,
+    // Indexed by the code word, and holds
+    // {rd goes negative, rd goes positive, allowed at rd+, allowed at rd-, valid, control, data}
+    table: ROM<Bits<14>, 10>,
+    entry: Signal<Local, Bits<14>>,
+}
+
+impl Default for Decoder8b10b {
+    fn default() -> Self {
+        let table = (0..1024_u16).map(|code| {
+            let mut entry = match decode_8b10b(code) {
+                Some(x) => {
+                    x.data as u64
+                        | if x.control { 1 << 8 } else { 0 }
+                        | 1 << 9
+                        | if x.allowed_rd_negative { 1 << 10 } else { 0 }
+                        | if x.allowed_rd_positive { 1 << 11 } else { 0 }
+                }
+                None => 0,
+            };
+            if code.count_ones() > 5 {
+                entry |= 1 << 12;
+            }
+            if code.count_ones() < 5 {
+                entry |= 1 << 13;
+            }
+            Bits::<14>::from(entry)
+        });
+        Self {
+            clock: Default::default(),
+            code_in: Default::default(),
+            data_out: Default::default(),
+            control: Default::default(),
+            code_error: Default::default(),
+            disparity_error: Default::default(),
+            rd: Default::default(),
+            data: Default::default(),
+            control_flag: Default::default(),
+            code_error_flag: Default::default(),
+            disparity_error_flag: Default::default(),
+            table: table.into(),
+            entry: Default::default(),
+        }
+    }
+}
+
+impl Logic for Decoder8b10b {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            rd,
+            data,
+            control_flag,
+            code_error_flag,
+            disparity_error_flag
+        );
+        self.table.address.next = self.code_in.val();
+        self.entry.next = self.table.data.val();
+        self.data.d.next = self.entry.val().get_bits::<8>(0);
+        self.control_flag.d.next = self.entry.val().get_bit(8);
+        self.code_error_flag.d.next = !self.entry.val().get_bit(9);
+        self.disparity_error_flag.d.next = self.entry.val().get_bit(9)
+            & ((self.rd.q.val() & !self.entry.val().get_bit(11))
+                | (!self.rd.q.val() & !self.entry.val().get_bit(10)));
+        if self.entry.val().get_bit(12) {
+            self.rd.d.next = true;
+        }
+        if self.entry.val().get_bit(13) {
+            self.rd.d.next = false;
+        }
+        self.data_out.next = self.data.q.val();
+        self.control.next = self.control_flag.q.val();
+        self.code_error.next = self.code_error_flag.q.val();
+        self.disparity_error.next = self.disparity_error_flag.q.val();
+    }
+}
+
+#[test]
+fn test_decoder_synthesizes() {
+    let mut uut = Decoder8b10b::default();
+    uut.connect_all();
+    yosys_validate("decoder_8b10b", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_decoder_flags_errors() {
+    use crate::code8b10b::{encode_8b10b, K28_5};
+    let mut uut = Decoder8b10b::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Decoder8b10b>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Decoder8b10b>| {
+        let mut x = sim.init()?;
+        let mut rd = false;
+        wait_clock_true!(sim, clock, x);
+        for n in 0..300_u32 {
+            let data = (n * 91 % 256) as u8;
+            let (code, rd_out) = encode_8b10b(data, false, rd);
+            rd = rd_out;
+            x.code_in.next = (code as u64).into();
+            wait_clock_cycle!(sim, clock, x);
+            sim_assert_eq!(sim, x.data_out.val(), data as u64, x);
+            sim_assert!(sim, !x.control.val(), x);
+            sim_assert!(sim, !x.code_error.val(), x);
+            sim_assert!(sim, !x.disparity_error.val(), x);
+        }
+        // Send the K28.5 for the wrong running disparity
+        let (code, _) = encode_8b10b(K28_5, true, !rd);
+        x.code_in.next = (code as u64).into();
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert!(sim, x.control.val(), x);
+        sim_assert!(sim, x.disparity_error.val(), x);
+        // An all zeros code word is not valid at all
+        x.code_in.next = 0.into();
+        wait_clock_cycle!(sim, clock, x);
+        sim_assert!(sim, x.code_error.val(), x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
@@ -0,0 +1,88 @@
+use crate::code8b10b::encode_8b10b;
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::ramrom::rom::ROM;
+use rust_hdl_lib_core::prelude::*;
+
+// Encodes one symbol per clock, with one clock of latency.  Set `control`
+// to send a control (K) symbol - only the 12 valid ones are honored, the
+// rest are sent as data.
+#[derive(LogicBlock)]
+pub struct Encoder8b10b {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<8>>,
+    pub control: Signal<In, Bit>,
+    pub code_out: Signal<Out, Bits<10>>,
+    // Running disparity (true when positive)
+    rd: DFF<Bit>,
+    code: DFF<Bits<10>>,
+    // Indexed by {control, rd, data}, and holds {new rd, code}
+    table: ROM<Bits<11>, 10>,
+}
+
+impl Default for Encoder8b10b {
+    fn default() -> Self {
+        let table = (0..1024_u32).map(|address| {
+            let data = (address & 0xFF) as u8;
+            let rd = address & 0x100 != 0;
+            let control = address & 0x200 != 0;
+            let (code, rd_out) = encode_8b10b(data, control, rd);
+            let entry = code as u64 | if rd_out { 1 << 10 } else { 0 };
+            Bits::<11>::from(entry)
+        });
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            control: Default::default(),
+            code_out: Default::default(),
+            rd: Default::default(),
+            code: Default::default(),
+            table: table.into(),
+        }
+    }
+}
+
+impl Logic for Encoder8b10b {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, rd, code);
+        self.table.address.next = bit_cast::<10, 8>(self.data_in.val())
+            | (bit_cast::<10, 1>(self.rd.q.val().into()) << 8)
+            | (bit_cast::<10, 1>(self.control.val().into()) << 9);
+        self.code.d.next = self.table.data.val().get_bits::<10>(0);
+        self.rd.d.next = self.table.data.val().get_bit(10);
+        self.code_out.next = self.code.q.val();
+    }
+}
+
+#[test]
+fn test_encoder_synthesizes() {
+    let mut uut = Encoder8b10b::default();
+    uut.connect_all();
+    yosys_validate("encoder_8b10b", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_encoder_matches_reference() {
+    let mut uut = Encoder8b10b::default();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5, |x: &mut Box<Encoder8b10b>| x.clock.next = !x.clock.val());
+    sim.add_testbench(move |mut sim: Sim<Encoder8b10b>| {
+        let mut x = sim.init()?;
+        let mut rd = false;
+        wait_clock_true!(sim, clock, x);
+        for n in 0..600_u32 {
+            let data = (n * 37 % 256) as u8;
+            let control = n % 7 == 0;
+            x.data_in.next = (data as u64).into();
+            x.control.next = control;
+            wait_clock_cycle!(sim, clock, x);
+            let (code, rd_out) = encode_8b10b(data, control, rd);
+            rd = rd_out;
+            sim_assert_eq!(sim, x.code_out.val(), code as u64, x);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000).unwrap();
+}
@@ -0,0 +1,218 @@
+// 8b/10b line coding (Widmer and Franaszek), as used by SATA, SGMII,
+// PCIe gen 1/2, DisplayPort and friends.  The 10 bit code words are held
+// with `a` in bit 0 and `j` in bit 9 (i.e., `abcdei fghj` from LSB to MSB),
+// which is the order in which serializers transmit them, LSB first.  The
+// 8 bit data value is `HGF EDCBA`, with `A` in bit 0, and is usually
+// written as D.x.y (or K.x.y for control symbols) with x = EDCBA and
+// y = HGF.
+//
+// The encoder and decoder widgets are table driven.  The tables are built
+// from the software codec in this module, which can also be used from
+// test benches.
+pub mod comma_aligner;
+pub mod decoder;
+pub mod encoder;
+
+// 5b/6b code words for a negative running disparity, indexed by x = EDCBA
+// and written `abcdei`.
+const CODE_5B6B: [&str; 32] = [
+    "100111", "011101", "101101", "110001", "110101", "101001", "011001", "111000", "111001",
+    "100101", "010101", "110100", "001101", "101100", "011100", "010111", "011011", "100011",
+    "010011", "110010", "001011", "101010", "011010", "111010", "110011", "100110", "010110",
+    "110110", "001110", "101110", "011110", "101011",
+];
+
+// K.28 is the only control symbol with its own 5b/6b code word.
+const CODE_K28_6B: &str = "001111";
+
+// 3b/4b code words for a negative running disparity, indexed by y = HGF
+// and written `fghj`.  Entry 7 is the primary D.x.P7 code.
+const CODE_3B4B: [&str; 8] = [
+    "1011", "1001", "0101", "1100", "1101", "1010", "0110", "1110",
+];
+
+// Control symbols use these instead, again for a negative running disparity.
+const CODE_3B4B_K: [&str; 8] = [
+    "1011", "0110", "1010", "1100", "1101", "0101", "1001", "0111",
+];
+
+// The alternate D.x.A7 code, used to avoid a run of 5 identical bits.
+const CODE_A7: &str = "0111";
+
+/// The K28.5 comma symbol, which is the usual choice for link alignment.
+pub const K28_5: u8 = 0xBC;
+/// The K28.1 symbol, which also contains a comma.
+pub const K28_1: u8 = 0x3C;
+/// The K28.7 symbol, which also contains a comma (and should not be
+/// sent back to back).
+pub const K28_7: u8 = 0xFC;
+
+// Turn a code word written in transmission order into bits, first bit in
+// bit 0.
+fn code_bits(code: &str) -> u16 {
+    code.chars()
+        .enumerate()
+        .map(|(ndx, c)| if c == '1' { 1 << ndx } else { 0 })
+        .sum()
+}
+
+fn complement(code: u16, width: usize) -> u16 {
+    !code & ((1 << width) - 1)
+}
+
+/// Returns true if `data` is one of the 12 valid control (K) symbols.
+pub fn is_valid_control(data: u8) -> bool {
+    let x = data & 0x1F;
+    let y = data >> 5;
+    x == 28 || (y == 7 && matches!(x, 23 | 27 | 29 | 30))
+}
+
+/// Encode a single symbol.  The running disparity is `true` when positive.
+/// Returns the 10 bit code word and the new running disparity.  Invalid
+/// control symbols are encoded as data.
+pub fn encode_8b10b(data: u8, control: bool, rd_positive: bool) -> (u16, bool) {
+    let control = control && is_valid_control(data);
+    let x = (data & 0x1F) as usize;
+    let y = (data >> 5) as usize;
+    // 5b/6b
+    let (six, x_neg_only) = if control && x == 28 {
+        (code_bits(CODE_K28_6B), false)
+    } else {
+        (code_bits(CODE_5B6B[x]), x == 7)
+    };
+    let six_unbalanced = six.count_ones() != 3;
+    let six = if rd_positive && (six_unbalanced || x_neg_only) {
+        complement(six, 6)
+    } else {
+        six
+    };
+    let rd_mid = if six_unbalanced {
+        !rd_positive
+    } else {
+        rd_positive
+    };
+    // 3b/4b
+    let (four, y_alternates) = if control {
+        (code_bits(CODE_3B4B_K[y]), true)
+    } else if y == 7
+        && ((!rd_mid && matches!(x, 17 | 18 | 20)) || (rd_mid && matches!(x, 11 | 13 | 14)))
+    {
+        (code_bits(CODE_A7), true)
+    } else {
+        (code_bits(CODE_3B4B[y]), y == 3)
+    };
+    let four_unbalanced = four.count_ones() != 2;
+    let four = if rd_mid && (four_unbalanced || y_alternates) {
+        complement(four, 4)
+    } else {
+        four
+    };
+    let rd_out = if four_unbalanced { !rd_mid } else { rd_mid };
+    (six | (four << 6), rd_out)
+}
+
+/// A decoded code word.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Decoded8b10b {
+    pub data: u8,
+    pub control: bool,
+    // Set if the code word can follow a negative running disparity
+    pub allowed_rd_negative: bool,
+    // Set if the code word can follow a positive running disparity
+    pub allowed_rd_positive: bool,
+}
+
+/// Decode a single code word, returning `None` if it is not a valid
+/// 8b/10b code word for either running disparity.
+pub fn decode_8b10b(code: u16) -> Option<Decoded8b10b> {
+    let mut ret: Option<Decoded8b10b> = None;
+    for data in 0..=255_u8 {
+        for control in [false, true] {
+            if control && !is_valid_control(data) {
+                continue;
+            }
+            for rd in [false, true] {
+                if encode_8b10b(data, control, rd).0 == code {
+                    let entry = ret.get_or_insert(Decoded8b10b {
+                        data,
+                        control,
+                        allowed_rd_negative: false,
+                        allowed_rd_positive: false,
+                    });
+                    if rd {
+                        entry.allowed_rd_positive = true;
+                    } else {
+                        entry.allowed_rd_negative = true;
+                    }
+                }
+            }
+        }
+    }
+    ret
+}
+
+#[test]
+fn test_known_code_words() {
+    // K28.5 is 001111 1010 from RD-, and 110000 0101 from RD+
+    assert_eq!(
+        encode_8b10b(K28_5, true, false),
+        (code_bits("0011111010"), true)
+    );
+    assert_eq!(
+        encode_8b10b(K28_5, true, true),
+        (code_bits("1100000101"), false)
+    );
+    // D.21.5 is the neutral 101010 1010 in both cases
+    assert_eq!(
+        encode_8b10b(0xB5, false, false),
+        (code_bits("1010101010"), false)
+    );
+    assert_eq!(
+        encode_8b10b(0xB5, false, true),
+        (code_bits("1010101010"), true)
+    );
+    // D.11.7 uses the alternate encoding from RD+, D.17.7 from RD-
+    assert_eq!(
+        encode_8b10b(0xEB, false, true),
+        (code_bits("1101001000"), false)
+    );
+    assert_eq!(
+        encode_8b10b(0xF1, false, false),
+        (code_bits("1000110111"), true)
+    );
+    // D.7.3 alternates, even though both halves are balanced
+    assert_eq!(encode_8b10b(0x67, false, false).0, code_bits("1110001100"));
+    assert_eq!(encode_8b10b(0x67, false, true).0, code_bits("0001110011"));
+}
+
+#[test]
+fn test_code_properties() {
+    let mut codes = std::collections::BTreeSet::new();
+    for data in 0..=255_u8 {
+        for control in [false, true] {
+            if control && !is_valid_control(data) {
+                continue;
+            }
+            for rd in [false, true] {
+                let (code, rd_out) = encode_8b10b(data, control, rd);
+                let ones = code.count_ones();
+                // Every code word is balanced or off by 2 in the right direction
+                match ones {
+                    5 => assert_eq!(rd, rd_out),
+                    6 => assert!(!rd && rd_out),
+                    4 => assert!(rd && !rd_out),
+                    _ => panic!("Bad disparity for {:x} {}", data, control),
+                }
+                // No more than 5 identical bits in a row
+                assert!(!format!("{:010b}", code).contains("000000"));
+                assert!(!format!("{:010b}", code).contains("111111"));
+                let decoded = decode_8b10b(code).unwrap();
+                assert_eq!(decoded.data, data);
+                assert_eq!(decoded.control, control);
+                codes.insert((code, rd));
+            }
+        }
+    }
+    // 256 data + 12 control symbols, for each running disparity
+    assert_eq!(codes.len(), 268 * 2);
+}
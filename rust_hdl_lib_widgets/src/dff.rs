@@ -50,6 +50,7 @@ use rust_hdl_lib_core::prelude::*;
 /// It is a good idea to connect [`q`](Self::q) to [`d`](Self::d) to ensure that [`d`](Self::d) is never undriven. The [`dff_setup`] macro can generate that code for you.
 ///
 /// If you need to set an initial value for the flip-flop use [`DFFWithInit`](crate::dff_with_init::DFFWithInit) instead.
+/// For a flip-flop on the falling edge of the clock (or on both edges) use [`DFFNeg`](crate::dff_neg::DFFNeg) (or [`DualEdgeDFF`](crate::dff_dual_edge::DualEdgeDFF)), rather than inverting the clock.
 #[derive(Clone, Debug, LogicBlock)]
 pub struct DFF<T: Synth> {
     /// Input for data that will be stored on the next rising edge of [`clock`](Self::clock).
@@ -97,6 +98,7 @@ end
         vec![TimingInfo {
             name: "dff".into(),
             clock: "clock".into(),
+            edge: ClockEdge::Rising,
             inputs: vec!["d".into()],
             outputs: vec!["q".into()],
         }]
@@ -0,0 +1,90 @@
+use rust_hdl_lib_core::prelude::*;
+
+/// D Flip-Flop on both edges of the clock
+///
+/// The data from [`d`](Self::d) is transfered to [`q`](Self::q) on every rising and every falling
+/// edge of [`clock`](Self::clock), so it runs at twice the rate of a [`DFF`](crate::dff::DFF) on the
+/// same clock (e.g., to produce or follow a double data rate signal).
+///
+/// ### Inputs
+///
+/// * [`clock`](Self::clock) On every edge the data from [`d`](Self::d) is stored into the flip-flop.
+/// * [`d`](Self::d) Input for data that will be stored on the next edge of [`clock`](Self::clock).
+///
+/// ### Outputs
+///
+/// * [`q`](Self::q) Outputs the currently stored data.
+///
+/// ### Additional info
+///
+/// Very few parts have a register that triggers on both edges, so this is built from a pair of
+/// registers, one on each edge.  Each one stores the new data XOR'ed with the other one, and
+/// [`q`](Self::q) is the XOR of the two, which is the value stored at the last edge.  That only
+/// needs rising and falling edge registers, which nearly every FPGA family has, and keeps the
+/// clock out of the data path, unlike a mux on the clock.  The price is a little logic after the
+/// registers, and (as with a [`DFFNeg`](crate::dff_neg::DFFNeg)) only half a clock period for the
+/// logic that drives [`d`](Self::d).
+#[derive(Clone, Debug, LogicBlock)]
+pub struct DualEdgeDFF<T: Synth> {
+    /// Input for data that will be stored on the next edge of [`clock`](Self::clock).
+    pub d: Signal<In, T>,
+    /// Outputs the currently stored data.
+    pub q: Signal<Out, T>,
+    /// On every edge the data from [`d`](Self::d) is stored into the flip-flop.
+    pub clock: Signal<In, Clock>,
+}
+
+impl<T: Synth> Default for DualEdgeDFF<T> {
+    fn default() -> DualEdgeDFF<T> {
+        Self {
+            d: Signal::default(),
+            q: Signal::default(),
+            clock: Signal::default(),
+        }
+    }
+}
+
+impl<T: Synth> Logic for DualEdgeDFF<T> {
+    fn update(&mut self) {
+        if self.clock.pos_edge() || self.clock.neg_edge() {
+            self.q.next = self.d.val()
+        }
+    }
+    fn connect(&mut self) {
+        self.q.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+reg [{msb}:0] rise;
+reg [{msb}:0] fall;
+
+initial begin
+   rise = {init:x};
+   fall = 0;
+end
+
+always @(posedge clock) begin
+   rise <= d ^ fall;
+end
+
+always @(negedge clock) begin
+   fall <= d ^ rise;
+end
+
+always @(*) q = rise ^ fall;
+      ",
+            msb = T::BITS - 1,
+            init = T::default().verilog()
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "dff_dual_edge".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Both,
+            inputs: vec!["d".into()],
+            outputs: vec!["q".into()],
+        }]
+    }
+}
@@ -0,0 +1,78 @@
+use rust_hdl_lib_core::prelude::*;
+
+/// D Flip-Flop on the falling edge of the clock
+///
+/// The same as a [`DFF`](crate::dff::DFF), except that the data from [`d`](Self::d) is transfered
+/// to [`q`](Self::q) on every falling edge of [`clock`](Self::clock).  Some interfaces want data that
+/// is launched or sampled half a clock away from the rising edge (e.g., capturing the commands an
+/// SDRAM sees, or the SPI modes where data changes on one edge and is sampled on the other).  Use
+/// this rather than feeding a [`DFF`](crate::dff::DFF) with an inverted clock, which puts logic in
+/// the clock path (and which the tools may not recognize as a clock at all).
+///
+/// ### Inputs
+///
+/// * [`clock`](Self::clock) On every falling edge the data from [`d`](Self::d) is stored into the flip-flop.
+/// * [`d`](Self::d) Input for data that will be stored on the next falling edge of [`clock`](Self::clock).
+///
+/// ### Outputs
+///
+/// * [`q`](Self::q) Outputs the currently stored data.
+///
+/// ### Additional info
+///
+/// Like a [`DFF`](crate::dff::DFF), the [`dff_setup`] macro can be used to connect the clock, and
+/// to feed [`q`](Self::q) back to [`d`](Self::d).  Paths between rising and falling edge registers
+/// only have half a clock period to settle, so keep the logic between them short.
+#[derive(Clone, Debug, LogicBlock)]
+pub struct DFFNeg<T: Synth> {
+    /// Input for data that will be stored on the next falling edge of [`clock`](Self::clock).
+    pub d: Signal<In, T>,
+    /// Outputs the currently stored data.
+    pub q: Signal<Out, T>,
+    /// On every falling edge the data from [`d`](Self::d) is stored into the flip-flop.
+    pub clock: Signal<In, Clock>,
+}
+
+impl<T: Synth> Default for DFFNeg<T> {
+    fn default() -> DFFNeg<T> {
+        Self {
+            d: Signal::default(),
+            q: Signal::default(),
+            clock: Signal::default(),
+        }
+    }
+}
+
+impl<T: Synth> Logic for DFFNeg<T> {
+    fn update(&mut self) {
+        if self.clock.neg_edge() {
+            self.q.next = self.d.val()
+        }
+    }
+    fn connect(&mut self) {
+        self.q.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+initial begin
+   q = {:x};
+end
+
+always @(negedge clock) begin
+   q <= d;
+end
+      ",
+            T::default().verilog()
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "dff_neg".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Falling,
+            inputs: vec!["d".into()],
+            outputs: vec!["q".into()],
+        }]
+    }
+}
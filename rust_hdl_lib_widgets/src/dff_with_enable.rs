@@ -0,0 +1,86 @@
+use rust_hdl_lib_core::prelude::*;
+
+/// D Flip-Flop with a clock enable
+///
+/// The same as a [`DFF`](crate::dff::DFF), except that the data from [`d`](Self::d) is only
+/// transfered to [`q`](Self::q) on the rising edges of [`clock`](Self::clock) where
+/// [`enable`](Self::enable) is high.  On the other edges, the flip-flop holds its value.
+///
+/// ### Inputs
+///
+/// * [`clock`](Self::clock) On every rising edge (with [`enable`](Self::enable) high) the data from [`d`](Self::d) is stored into the flip-flop.
+/// * [`d`](Self::d) Input for data that will be stored on the next enabled rising edge of [`clock`](Self::clock).
+/// * [`enable`](Self::enable) The flip-flop only stores new data while this is high.
+///
+/// ### Outputs
+///
+/// * [`q`](Self::q) Outputs the currently stored data.
+///
+/// ### Additional info
+///
+/// This is the safe way to stop a register (or a whole block of them) from changing - the clock
+/// keeps running, and the enable maps onto the clock enable pin that nearly every FPGA flip-flop
+/// has, so it costs no logic.  AND'ing the enable into the clock instead can glitch, and takes the
+/// clock off of the clock network (see
+/// [`check_clock_gating`](rust_hdl_lib_core::check_clock_gating::check_clock_gating)).  If the
+/// point is to save the power of the clock tree itself, use a [`ClockGate`](crate::clock_gate::ClockGate)
+/// (or one of the vendor specific ones), which are built to switch without glitches.
+///
+/// The [`dff_setup`] macro connects the clock, and feeds [`q`](Self::q) back to
+/// [`d`](Self::d), but the [`enable`](Self::enable) still needs to be driven.
+#[derive(Clone, Debug, LogicBlock)]
+pub struct DFFWithEnable<T: Synth> {
+    /// Input for data that will be stored on the next enabled rising edge of [`clock`](Self::clock).
+    pub d: Signal<In, T>,
+    /// Outputs the currently stored data.
+    pub q: Signal<Out, T>,
+    /// The flip-flop only stores new data while this is high.
+    pub enable: Signal<In, Bit>,
+    /// On every rising edge (with [`enable`](Self::enable) high) the data from [`d`](Self::d) is stored into the flip-flop.
+    pub clock: Signal<In, Clock>,
+}
+
+impl<T: Synth> Default for DFFWithEnable<T> {
+    fn default() -> DFFWithEnable<T> {
+        Self {
+            d: Signal::default(),
+            q: Signal::default(),
+            enable: Signal::default(),
+            clock: Signal::default(),
+        }
+    }
+}
+
+impl<T: Synth> Logic for DFFWithEnable<T> {
+    fn update(&mut self) {
+        if self.clock.pos_edge() && self.enable.val() {
+            self.q.next = self.d.val()
+        }
+    }
+    fn connect(&mut self) {
+        self.q.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+initial begin
+   q = {:x};
+end
+
+always @(posedge clock) begin
+   if (enable) q <= d;
+end
+      ",
+            T::default().verilog()
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "dff_with_enable".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Rising,
+            inputs: vec!["d".into(), "enable".into()],
+            outputs: vec!["q".into()],
+        }]
+    }
+}
@@ -0,0 +1,312 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::fifo::async_fifo::AsynchronousFIFO;
+use crate::ramrom::ram::RAM;
+use crate::synchronizer::BitSynchronizer;
+use rust_hdl_lib_core::prelude::*;
+
+/// The wires of a parallel (DVP) camera port, as found on the OV7670 and its
+/// relatives.  The camera drives `href` high while the bytes of a line are on `data`,
+/// and pulses `vsync` high between frames.  Everything changes with the pixel clock.
+#[derive(LogicInterface, Default)]
+#[join = "DVPWiresReceiver"]
+pub struct DVPWiresCamera {
+    pub vsync: Signal<Out, Bit>,
+    pub href: Signal<Out, Bit>,
+    pub data: Signal<Out, Bits<8>>,
+}
+
+#[derive(LogicInterface, Default)]
+#[join = "DVPWiresCamera"]
+pub struct DVPWiresReceiver {
+    pub vsync: Signal<In, Bit>,
+    pub href: Signal<In, Bit>,
+    pub data: Signal<In, Bits<8>>,
+}
+
+/// A [DVPCapture] samples the pixels of a DVP camera on the rising edge of the pixel
+/// clock, and queues them in a 256 entry asynchronous FIFO, which is read in the
+/// `clock` domain.  A pixel is either 1 byte (raw Bayer or greyscale, in the low byte
+/// of `data_out`) or 2 bytes (RGB565 or YUV, most significant byte first on the wire).
+/// The first pixel of each frame is marked with `start_of_frame`, and the first pixel
+/// of each line with `start_of_line`.  Capture starts mid-frame, so the pixels before
+/// the first `start_of_frame` should be thrown away.  Pixels that arrive while the FIFO
+/// is full are dropped, and latch `overflow`.
+///
+/// The FIFO side can feed an `SDRAMFIFOController` directly, or go through a
+/// [BayerDecimator] first.
+#[derive(LogicBlock)]
+pub struct DVPCapture {
+    /// The clock for the FIFO reads
+    pub clock: Signal<In, Clock>,
+    /// The pixel at the head of the FIFO
+    pub data_out: Signal<Out, Bits<16>>,
+    /// Set if the pixel at the head of the FIFO starts a frame
+    pub start_of_frame: Signal<Out, Bit>,
+    /// Set if the pixel at the head of the FIFO starts a line
+    pub start_of_line: Signal<Out, Bit>,
+    /// Assert to take the pixel at the head of the FIFO
+    pub read: Signal<In, Bit>,
+    /// Asserted when the FIFO is empty
+    pub empty: Signal<Out, Bit>,
+    /// Latched when a pixel is dropped (synchronous to `clock`)
+    pub overflow: Signal<Out, Bit>,
+    /// The pixel clock from the camera
+    pub pclk: Signal<In, Clock>,
+    /// The wires of the camera port
+    pub wires: DVPWiresReceiver,
+    fifo: AsynchronousFIFO<Bits<18>, 8, 9, 1>,
+    high_byte: DFF<Bits<8>>,
+    second_byte: DFF<Bit>,
+    frame_pending: DFF<Bit>,
+    line_pending: DFF<Bit>,
+    dropped: DFF<Bit>,
+    overflow_sync: BitSynchronizer,
+    pixel: Signal<Local, Bits<16>>,
+    pixel_done: Signal<Local, Bit>,
+    two_bytes: Constant<Bit>,
+}
+
+impl DVPCapture {
+    pub fn new(bytes_per_pixel: usize) -> Self {
+        assert!(bytes_per_pixel == 1 || bytes_per_pixel == 2);
+        Self {
+            clock: Default::default(),
+            data_out: Default::default(),
+            start_of_frame: Default::default(),
+            start_of_line: Default::default(),
+            read: Default::default(),
+            empty: Default::default(),
+            overflow: Default::default(),
+            pclk: Default::default(),
+            wires: Default::default(),
+            fifo: Default::default(),
+            high_byte: Default::default(),
+            second_byte: Default::default(),
+            frame_pending: Default::default(),
+            line_pending: Default::default(),
+            dropped: Default::default(),
+            overflow_sync: Default::default(),
+            pixel: Default::default(),
+            pixel_done: Default::default(),
+            two_bytes: Constant::new(bytes_per_pixel == 2),
+        }
+    }
+}
+
+impl Logic for DVPCapture {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            pclk,
+            high_byte,
+            second_byte,
+            frame_pending,
+            line_pending,
+            dropped
+        );
+        clock!(self, clock, overflow_sync);
+        self.fifo.write_clock.next = self.pclk.val();
+        self.fifo.read_clock.next = self.clock.val();
+        self.fifo.read.next = self.read.val();
+        self.data_out.next = bit_cast::<16, 18>(self.fifo.data_out.val());
+        self.start_of_frame.next = self.fifo.data_out.val().get_bit(17);
+        self.start_of_line.next = self.fifo.data_out.val().get_bit(16);
+        self.empty.next = self.fifo.empty.val();
+        self.overflow_sync.sig_in.next = self.dropped.q.val();
+        self.overflow.next = self.overflow_sync.sig_out.val();
+        // Mark the next pixel after the frame and line syncs
+        if self.wires.vsync.val() {
+            self.frame_pending.d.next = true;
+        }
+        if !self.wires.href.val() {
+            self.line_pending.d.next = true;
+            self.second_byte.d.next = false;
+        }
+        // Assemble the pixels
+        self.pixel_done.next = false;
+        self.pixel.next = bit_cast::<16, 8>(self.wires.data.val());
+        if self.wires.href.val() {
+            if self.two_bytes.val() {
+                self.second_byte.d.next = !self.second_byte.q.val();
+                self.high_byte.d.next = self.wires.data.val();
+                if self.second_byte.q.val() {
+                    self.pixel.next = (bit_cast::<16, 8>(self.high_byte.q.val()) << 8)
+                        | bit_cast::<16, 8>(self.wires.data.val());
+                    self.pixel_done.next = true;
+                }
+            } else {
+                self.pixel_done.next = true;
+            }
+        }
+        self.fifo.data_in.next = bit_cast::<18, 16>(self.pixel.val())
+            | (bit_cast::<18, 1>(self.frame_pending.q.val().into()) << 17)
+            | (bit_cast::<18, 1>(self.line_pending.q.val().into()) << 16);
+        self.fifo.write.next = false;
+        if self.pixel_done.val() {
+            if self.fifo.full.val() {
+                self.dropped.d.next = true;
+            } else {
+                self.fifo.write.next = true;
+                self.frame_pending.d.next = false;
+                self.line_pending.d.next = false;
+            }
+        }
+    }
+}
+
+/// A [BayerDecimator] turns a stream of raw Bayer pixels (in RGGB order, in the low
+/// byte of `data_in`) into RGB565 pixels at half the resolution, by merging each 2x2
+/// block of the mosaic into a single pixel (with the average of the two greens).  The
+/// even lines are held in a line buffer of 2^A pixel pairs, so lines can be up to
+/// 2^(A+1) pixels wide.  The input is read like a FIFO (e.g., from a [DVPCapture]), and
+/// the output is written like one, with the same frame and line markers.  Pixels are
+/// only taken from the input when the output is not full.
+#[derive(LogicBlock)]
+pub struct BayerDecimator<const A: usize> {
+    pub clock: Signal<In, Clock>,
+    /// The raw pixel at the head of the input FIFO
+    pub data_in: Signal<In, Bits<16>>,
+    pub start_of_frame_in: Signal<In, Bit>,
+    pub start_of_line_in: Signal<In, Bit>,
+    pub empty_in: Signal<In, Bit>,
+    /// Asserted to take the pixel at the head of the input FIFO
+    pub read: Signal<Out, Bit>,
+    /// The RGB565 pixel to write
+    pub data_out: Signal<Out, Bits<16>>,
+    pub start_of_frame: Signal<Out, Bit>,
+    pub start_of_line: Signal<Out, Bit>,
+    /// Asserted to write `data_out`
+    pub write: Signal<Out, Bit>,
+    pub full: Signal<In, Bit>,
+    line: RAM<Bits<16>, A>,
+    odd_row: DFF<Bit>,
+    odd_column: DFF<Bit>,
+    column: DFF<Bits<A>>,
+    first: DFF<Bits<8>>,
+    frame_pending: DFF<Bit>,
+    line_pending: DFF<Bit>,
+    row_now: Signal<Local, Bit>,
+    column_odd_now: Signal<Local, Bit>,
+    column_now: Signal<Local, Bits<A>>,
+    take: Signal<Local, Bit>,
+    value: Signal<Local, Bits<8>>,
+    green: Signal<Local, Bits<9>>,
+}
+
+impl<const A: usize> Default for BayerDecimator<A> {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            start_of_frame_in: Default::default(),
+            start_of_line_in: Default::default(),
+            empty_in: Default::default(),
+            read: Default::default(),
+            data_out: Default::default(),
+            start_of_frame: Default::default(),
+            start_of_line: Default::default(),
+            write: Default::default(),
+            full: Default::default(),
+            line: Default::default(),
+            odd_row: Default::default(),
+            odd_column: Default::default(),
+            column: Default::default(),
+            first: Default::default(),
+            frame_pending: Default::default(),
+            line_pending: Default::default(),
+            row_now: Default::default(),
+            column_odd_now: Default::default(),
+            column_now: Default::default(),
+            take: Default::default(),
+            value: Default::default(),
+            green: Default::default(),
+        }
+    }
+}
+
+impl<const A: usize> Logic for BayerDecimator<A> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            odd_row,
+            odd_column,
+            column,
+            first,
+            frame_pending,
+            line_pending
+        );
+        self.line.read_clock.next = self.clock.val();
+        self.line.write_clock.next = self.clock.val();
+        self.value.next = bit_cast::<8, 16>(self.data_in.val());
+        self.take.next = !self.empty_in.val() & !self.full.val();
+        self.read.next = self.take.val();
+        // Where the pixel at the head of the input sits in the mosaic
+        self.row_now.next = self.odd_row.q.val();
+        self.column_odd_now.next = self.odd_column.q.val();
+        self.column_now.next = self.column.q.val();
+        if self.start_of_line_in.val() {
+            self.row_now.next = !self.odd_row.q.val();
+            self.column_odd_now.next = false;
+            self.column_now.next = 0.into();
+        }
+        if self.start_of_frame_in.val() {
+            self.row_now.next = false;
+        }
+        // The red and first green of the block come from the line buffer, the
+        // second green was held from the last pixel, and the blue is on the input
+        self.line.read_address.next = self.column_now.val();
+        self.line.write_address.next = self.column_now.val();
+        self.line.write_data.next =
+            (bit_cast::<16, 8>(self.first.q.val()) << 8) | bit_cast::<16, 8>(self.value.val());
+        self.line.write_enable.next = false;
+        self.green.next = bit_cast::<9, 8>(bit_cast::<8, 16>(self.line.read_data.val()))
+            + bit_cast::<9, 8>(self.first.q.val());
+        self.data_out.next = ((self.line.read_data.val() >> 11) << 11)
+            | (bit_cast::<16, 9>(self.green.val() >> 3) << 5)
+            | bit_cast::<16, 8>(self.value.val() >> 3);
+        self.start_of_frame.next = self.frame_pending.q.val();
+        self.start_of_line.next = self.line_pending.q.val();
+        self.write.next = false;
+        if self.take.val() {
+            self.odd_row.d.next = self.row_now.val();
+            self.odd_column.d.next = !self.column_odd_now.val();
+            self.column.d.next = self.column_now.val();
+            if self.start_of_frame_in.val() {
+                self.frame_pending.d.next = true;
+            }
+            if self.start_of_line_in.val() & self.row_now.val() {
+                self.line_pending.d.next = true;
+            }
+            if !self.column_odd_now.val() {
+                self.first.d.next = self.value.val();
+            } else {
+                self.column.d.next = self.column_now.val() + 1;
+                if !self.row_now.val() {
+                    self.line.write_enable.next = true;
+                } else {
+                    self.write.next = true;
+                    self.frame_pending.d.next = false;
+                    self.line_pending.d.next = false;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_dvp_capture_is_synthesizable() {
+    let mut uut = DVPCapture::new(2);
+    uut.connect_all();
+    yosys_validate("dvp_capture", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_bayer_decimator_is_synthesizable() {
+    let mut uut = BayerDecimator::<8>::default();
+    uut.connect_all();
+    yosys_validate("bayer_decimator", &generate_verilog(&uut)).unwrap();
+}
@@ -43,6 +43,7 @@ always @(posedge clk) q <= d;",
         vec![TimingInfo {
             name: "edge_ff".to_string(),
             clock: "clk".to_string(),
+            edge: ClockEdge::Rising,
             inputs: vec!["d".into()],
             outputs: vec!["q".into()],
         }]
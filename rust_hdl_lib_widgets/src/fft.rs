@@ -0,0 +1,406 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::ramrom::ram::RAM;
+use crate::ramrom::sync_rom::SyncROM;
+use rust_hdl_lib_core::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, LogicState)]
+enum FFTState {
+    Load,
+    ReadA,
+    ReadB,
+    WriteA,
+    WriteB,
+    Unload,
+}
+
+/// Radix-2 FFT with block floating point
+///
+/// Computes the forward DFT of blocks of `N = 2^B` complex samples.  The samples are streamed
+/// in on [`re_in`](Self::re_in) and [`im_in`](Self::im_in) (one for each clock that
+/// [`strobe_in`](Self::strobe_in) is asserted, while [`ready`](Self::ready) is high), and
+/// are stored in bit reversed order.  The transform is then computed in place, one stage at
+/// a time, with one radix-2 butterfly every 4 clocks, and the bins are streamed out in natural
+/// order on [`re_out`](Self::re_out) and [`im_out`](Self::im_out), one per clock, with
+/// [`strobe_out`](Self::strobe_out) asserted.  A transform takes about `2 N B` clocks, plus
+/// `N` clocks each to load and unload the samples.  The next block can be loaded as soon as
+/// the last bin has been sent.
+///
+/// To keep the full 16 bits of precision through the transform without overflowing, the
+/// block is scaled as needed between the stages (block floating point).  Before each stage,
+/// the largest value in the block is checked, and the stage divides its outputs by 2 or 4
+/// if they could otherwise overflow.  The total scaling is presented on
+/// [`exponent`](Self::exponent), so that bin `k` of the DFT is
+/// `(re_out + j im_out) * 2^exponent`.
+///
+/// ### Inputs
+///
+/// * [`clock`](Self::clock) The clock for the FFT.
+/// * [`re_in`](Self::re_in), [`im_in`](Self::im_in) The samples.
+/// * [`strobe_in`](Self::strobe_in) Assert for one clock with each sample.
+///
+/// ### Outputs
+///
+/// * [`ready`](Self::ready) Asserted while the FFT accepts samples.
+/// * [`re_out`](Self::re_out), [`im_out`](Self::im_out) The bins.
+/// * [`exponent`](Self::exponent) The scaling of the bins (valid with the bins).
+/// * [`strobe_out`](Self::strobe_out) Asserted for one clock with each bin.
+///
+/// ### Additional info
+///
+/// The twiddle factors are kept in two `N` entry ROMs (only the first half is used), and the
+/// samples in two `N` entry RAMs.  Use [`FFT256`] or [`FFT1024`] for the common sizes.
+#[derive(LogicBlock)]
+pub struct FFT<const B: usize> {
+    /// The clock for the FFT.
+    pub clock: Signal<In, Clock>,
+    /// The real part of the samples.
+    pub re_in: Signal<In, Signed<16>>,
+    /// The imaginary part of the samples.
+    pub im_in: Signal<In, Signed<16>>,
+    /// Assert for one clock with each sample.
+    pub strobe_in: Signal<In, Bit>,
+    /// Asserted while the FFT accepts samples.
+    pub ready: Signal<Out, Bit>,
+    /// The real part of the bins.
+    pub re_out: Signal<Out, Signed<16>>,
+    /// The imaginary part of the bins.
+    pub im_out: Signal<Out, Signed<16>>,
+    /// The scaling of the bins, as a power of 2.
+    pub exponent: Signal<Out, Bits<8>>,
+    /// Asserted for one clock with each bin.
+    pub strobe_out: Signal<Out, Bit>,
+    re_mem: RAM<Signed<16>, B>,
+    im_mem: RAM<Signed<16>, B>,
+    cos_rom: SyncROM<Signed<16>, B>,
+    sin_rom: SyncROM<Signed<16>, B>,
+    state: DFF<FFTState>,
+    count: DFF<Bits<B>>,
+    stage: DFF<Bits<8>>,
+    twiddle_shift: DFF<Bits<8>>,
+    shift: DFF<Bits<8>>,
+    scale: DFF<Bits<8>>,
+    big_13: DFF<Bit>,
+    big_14: DFF<Bit>,
+    a_re: DFF<Signed<16>>,
+    a_im: DFF<Signed<16>>,
+    b_out_re: DFF<Signed<16>>,
+    b_out_im: DFF<Signed<16>>,
+    emit: DFF<Bit>,
+    // Addressing
+    reversed: Signal<Local, Bits<B>>,
+    half: Signal<Local, Bits<B>>,
+    mask: Signal<Local, Bits<B>>,
+    addr_a: Signal<Local, Bits<B>>,
+    addr_b: Signal<Local, Bits<B>>,
+    // The butterfly
+    t_re: Signal<Local, Signed<33>>,
+    t_im: Signal<Local, Signed<33>>,
+    sum_re: Signal<Local, Signed<18>>,
+    sum_im: Signal<Local, Signed<18>>,
+    dif_re: Signal<Local, Signed<18>>,
+    dif_im: Signal<Local, Signed<18>>,
+    out_a_re: Signal<Local, Signed<16>>,
+    out_a_im: Signal<Local, Signed<16>>,
+    out_b_re: Signal<Local, Signed<16>>,
+    out_b_im: Signal<Local, Signed<16>>,
+    // The block floating point
+    sample_13: Signal<Local, Bit>,
+    sample_14: Signal<Local, Bit>,
+    next_13: Signal<Local, Bit>,
+    next_14: Signal<Local, Bit>,
+    next_shift: Signal<Local, Bits<8>>,
+    round: Signal<Local, Signed<18>>,
+    half_lsb: Constant<Signed<33>>,
+    unit: Constant<Bits<18>>,
+    one: Constant<Bits<B>>,
+    last_sample: Constant<Bits<B>>,
+    last_butterfly: Constant<Bits<B>>,
+    last_stage: Constant<Bits<8>>,
+    first_twiddle_shift: Constant<Bits<8>>,
+}
+
+/// A 256 point [FFT]
+pub type FFT256 = FFT<8>;
+/// A 1024 point [FFT]
+pub type FFT1024 = FFT<10>;
+
+impl<const B: usize> Default for FFT<B> {
+    fn default() -> Self {
+        assert!((2..=12).contains(&B));
+        let n = 1_usize << B;
+        let twiddle = |f: fn(f64) -> f64| -> SyncROM<Signed<16>, B> {
+            (0..n)
+                .map(|k| {
+                    let angle = 2.0 * std::f64::consts::PI * k as f64 / n as f64;
+                    ((f(angle) * 32767.0).round() as i64).to_signed_bits::<16>()
+                })
+                .into()
+        };
+        Self {
+            clock: Default::default(),
+            re_in: Default::default(),
+            im_in: Default::default(),
+            strobe_in: Default::default(),
+            ready: Default::default(),
+            re_out: Default::default(),
+            im_out: Default::default(),
+            exponent: Default::default(),
+            strobe_out: Default::default(),
+            re_mem: Default::default(),
+            im_mem: Default::default(),
+            cos_rom: twiddle(f64::cos),
+            sin_rom: twiddle(|x| -x.sin()),
+            state: Default::default(),
+            count: Default::default(),
+            stage: Default::default(),
+            twiddle_shift: Default::default(),
+            shift: Default::default(),
+            scale: Default::default(),
+            big_13: Default::default(),
+            big_14: Default::default(),
+            a_re: Default::default(),
+            a_im: Default::default(),
+            b_out_re: Default::default(),
+            b_out_im: Default::default(),
+            emit: Default::default(),
+            reversed: Default::default(),
+            half: Default::default(),
+            mask: Default::default(),
+            addr_a: Default::default(),
+            addr_b: Default::default(),
+            t_re: Default::default(),
+            t_im: Default::default(),
+            sum_re: Default::default(),
+            sum_im: Default::default(),
+            dif_re: Default::default(),
+            dif_im: Default::default(),
+            out_a_re: Default::default(),
+            out_a_im: Default::default(),
+            out_b_re: Default::default(),
+            out_b_im: Default::default(),
+            sample_13: Default::default(),
+            sample_14: Default::default(),
+            next_13: Default::default(),
+            next_14: Default::default(),
+            next_shift: Default::default(),
+            round: Default::default(),
+            half_lsb: Constant::new((1_i64 << 14).to_signed_bits()),
+            unit: Constant::new(1.into()),
+            one: Constant::new(1.into()),
+            last_sample: Constant::new((n - 1).to_bits()),
+            last_butterfly: Constant::new((n / 2 - 1).to_bits()),
+            last_stage: Constant::new((B - 1).to_bits()),
+            first_twiddle_shift: Constant::new((B - 1).to_bits()),
+        }
+    }
+}
+
+impl<const B: usize> Logic for FFT<B> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            state,
+            count,
+            stage,
+            twiddle_shift,
+            shift,
+            scale,
+            big_13,
+            big_14,
+            a_re,
+            a_im,
+            b_out_re,
+            b_out_im,
+            emit
+        );
+        self.re_mem.read_clock.next = self.clock.val();
+        self.re_mem.write_clock.next = self.clock.val();
+        self.im_mem.read_clock.next = self.clock.val();
+        self.im_mem.write_clock.next = self.clock.val();
+        clock!(self, clock, cos_rom, sin_rom);
+        // The samples are stored at the bit reversed address
+        self.reversed.next = 0.into();
+        for i in 0..B {
+            self.reversed.next =
+                (self.reversed.val() << 1) | bit_cast::<B, 1>(self.count.q.val().get_bit(i).into());
+        }
+        // Butterfly `count` of the stage combines the points `half` apart,
+        // and the twiddle factor depends on where it sits in its group
+        self.half.next = self.one.val() << self.stage.q.val();
+        self.mask.next = self.half.val() - 1;
+        self.addr_a.next =
+            ((self.count.q.val() & !self.mask.val()) << 1) | (self.count.q.val() & self.mask.val());
+        self.addr_b.next = self.addr_a.val() | self.half.val();
+        self.cos_rom.address.next =
+            (self.count.q.val() & self.mask.val()) << self.twiddle_shift.q.val();
+        self.sin_rom.address.next =
+            (self.count.q.val() & self.mask.val()) << self.twiddle_shift.q.val();
+        // a +/- b w, with b read from the memories, and scaled for the stage
+        // (the products are rounded to the nearest)
+        self.t_re.next =
+            signed_bit_cast::<33, 32>(self.re_mem.read_data.val() * self.cos_rom.data.val())
+                - signed_bit_cast::<33, 32>(self.im_mem.read_data.val() * self.sin_rom.data.val())
+                + self.half_lsb.val();
+        self.t_im.next =
+            signed_bit_cast::<33, 32>(self.re_mem.read_data.val() * self.sin_rom.data.val())
+                + signed_bit_cast::<33, 32>(self.im_mem.read_data.val() * self.cos_rom.data.val())
+                + self.half_lsb.val();
+        // Round to nearest when the stage is scaled
+        self.round.next = signed_cast((self.unit.val() << self.shift.q.val()) >> 1);
+        self.sum_re.next = signed_bit_cast::<18, 16>(self.a_re.q.val())
+            + self.t_re.val().get_bits::<18>(15)
+            + self.round.val();
+        self.sum_im.next = signed_bit_cast::<18, 16>(self.a_im.q.val())
+            + self.t_im.val().get_bits::<18>(15)
+            + self.round.val();
+        self.dif_re.next = signed_bit_cast::<18, 16>(self.a_re.q.val())
+            - self.t_re.val().get_bits::<18>(15)
+            + self.round.val();
+        self.dif_im.next = signed_bit_cast::<18, 16>(self.a_im.q.val())
+            - self.t_im.val().get_bits::<18>(15)
+            + self.round.val();
+        self.out_a_re.next = self.sum_re.val().get_bits::<16>(self.shift.q.val().index());
+        self.out_a_im.next = self.sum_im.val().get_bits::<16>(self.shift.q.val().index());
+        self.out_b_re.next = self.dif_re.val().get_bits::<16>(self.shift.q.val().index());
+        self.out_b_im.next = self.dif_im.val().get_bits::<16>(self.shift.q.val().index());
+        // Values outside [-2^13, 2^13) (or [-2^14, 2^14)) can overflow the
+        // next stage, unless it is scaled by 2 (or 4)
+        self.sample_13.next = (self.re_in.val().get_bit(15) ^ self.re_in.val().get_bit(13))
+            | (self.re_in.val().get_bit(15) ^ self.re_in.val().get_bit(14))
+            | (self.im_in.val().get_bit(15) ^ self.im_in.val().get_bit(13))
+            | (self.im_in.val().get_bit(15) ^ self.im_in.val().get_bit(14));
+        self.sample_14.next = (self.re_in.val().get_bit(15) ^ self.re_in.val().get_bit(14))
+            | (self.im_in.val().get_bit(15) ^ self.im_in.val().get_bit(14));
+        if self.state.q.val() != FFTState::Load {
+            self.sample_13.next = (self.out_a_re.val().get_bit(15)
+                ^ self.out_a_re.val().get_bit(13))
+                | (self.out_a_re.val().get_bit(15) ^ self.out_a_re.val().get_bit(14))
+                | (self.out_a_im.val().get_bit(15) ^ self.out_a_im.val().get_bit(13))
+                | (self.out_a_im.val().get_bit(15) ^ self.out_a_im.val().get_bit(14))
+                | (self.out_b_re.val().get_bit(15) ^ self.out_b_re.val().get_bit(13))
+                | (self.out_b_re.val().get_bit(15) ^ self.out_b_re.val().get_bit(14))
+                | (self.out_b_im.val().get_bit(15) ^ self.out_b_im.val().get_bit(13))
+                | (self.out_b_im.val().get_bit(15) ^ self.out_b_im.val().get_bit(14));
+            self.sample_14.next = (self.out_a_re.val().get_bit(15)
+                ^ self.out_a_re.val().get_bit(14))
+                | (self.out_a_im.val().get_bit(15) ^ self.out_a_im.val().get_bit(14))
+                | (self.out_b_re.val().get_bit(15) ^ self.out_b_re.val().get_bit(14))
+                | (self.out_b_im.val().get_bit(15) ^ self.out_b_im.val().get_bit(14));
+        }
+        self.next_13.next = self.big_13.q.val();
+        self.next_14.next = self.big_14.q.val();
+        if self.strobe_in.val() & (self.state.q.val() == FFTState::Load)
+            | (self.state.q.val() == FFTState::WriteA)
+        {
+            self.next_13.next = self.big_13.q.val() | self.sample_13.val();
+            self.next_14.next = self.big_14.q.val() | self.sample_14.val();
+        }
+        self.big_13.d.next = self.next_13.val();
+        self.big_14.d.next = self.next_14.val();
+        self.next_shift.next = 0.into();
+        if self.next_14.val() {
+            self.next_shift.next = 2.into();
+        } else if self.next_13.val() {
+            self.next_shift.next = 1.into();
+        }
+        // Memory defaults
+        self.re_mem.read_address.next = self.addr_a.val();
+        self.im_mem.read_address.next = self.addr_a.val();
+        self.re_mem.write_address.next = self.addr_a.val();
+        self.im_mem.write_address.next = self.addr_a.val();
+        self.re_mem.write_data.next = self.out_a_re.val();
+        self.im_mem.write_data.next = self.out_a_im.val();
+        self.re_mem.write_enable.next = false;
+        self.im_mem.write_enable.next = false;
+        self.emit.d.next = false;
+        match self.state.q.val() {
+            FFTState::Load => {
+                self.re_mem.write_address.next = self.reversed.val();
+                self.im_mem.write_address.next = self.reversed.val();
+                self.re_mem.write_data.next = self.re_in.val();
+                self.im_mem.write_data.next = self.im_in.val();
+                self.re_mem.write_enable.next = self.strobe_in.val();
+                self.im_mem.write_enable.next = self.strobe_in.val();
+                if self.strobe_in.val() {
+                    self.count.d.next = self.count.q.val() + 1;
+                    if self.count.q.val() == self.last_sample.val() {
+                        self.count.d.next = 0.into();
+                        self.stage.d.next = 0.into();
+                        self.twiddle_shift.d.next = self.first_twiddle_shift.val();
+                        self.shift.d.next = self.next_shift.val();
+                        self.scale.d.next = self.next_shift.val();
+                        self.big_13.d.next = false;
+                        self.big_14.d.next = false;
+                        self.state.d.next = FFTState::ReadA;
+                    }
+                }
+            }
+            FFTState::ReadA => {
+                self.state.d.next = FFTState::ReadB;
+            }
+            FFTState::ReadB => {
+                self.re_mem.read_address.next = self.addr_b.val();
+                self.im_mem.read_address.next = self.addr_b.val();
+                self.a_re.d.next = self.re_mem.read_data.val();
+                self.a_im.d.next = self.im_mem.read_data.val();
+                self.state.d.next = FFTState::WriteA;
+            }
+            FFTState::WriteA => {
+                self.re_mem.write_enable.next = true;
+                self.im_mem.write_enable.next = true;
+                self.b_out_re.d.next = self.out_b_re.val();
+                self.b_out_im.d.next = self.out_b_im.val();
+                self.state.d.next = FFTState::WriteB;
+            }
+            FFTState::WriteB => {
+                self.re_mem.write_address.next = self.addr_b.val();
+                self.im_mem.write_address.next = self.addr_b.val();
+                self.re_mem.write_data.next = self.b_out_re.q.val();
+                self.im_mem.write_data.next = self.b_out_im.q.val();
+                self.re_mem.write_enable.next = true;
+                self.im_mem.write_enable.next = true;
+                self.count.d.next = self.count.q.val() + 1;
+                self.state.d.next = FFTState::ReadA;
+                if self.count.q.val() == self.last_butterfly.val() {
+                    self.count.d.next = 0.into();
+                    self.stage.d.next = self.stage.q.val() + 1;
+                    self.twiddle_shift.d.next = self.twiddle_shift.q.val() - 1;
+                    self.shift.d.next = self.next_shift.val();
+                    self.scale.d.next = self.scale.q.val() + self.next_shift.val();
+                    self.big_13.d.next = false;
+                    self.big_14.d.next = false;
+                    if self.stage.q.val() == self.last_stage.val() {
+                        self.scale.d.next = self.scale.q.val();
+                        self.state.d.next = FFTState::Unload;
+                    }
+                }
+            }
+            FFTState::Unload => {
+                self.re_mem.read_address.next = self.count.q.val();
+                self.im_mem.read_address.next = self.count.q.val();
+                self.emit.d.next = true;
+                self.count.d.next = self.count.q.val() + 1;
+                if self.count.q.val() == self.last_sample.val() {
+                    self.count.d.next = 0.into();
+                    self.state.d.next = FFTState::Load;
+                }
+            }
+        }
+        self.ready.next = self.state.q.val() == FFTState::Load;
+        self.re_out.next = self.re_mem.read_data.val();
+        self.im_out.next = self.im_mem.read_data.val();
+        self.exponent.next = self.scale.q.val();
+        self.strobe_out.next = self.emit.q.val();
+    }
+}
+
+#[test]
+fn test_fft_is_synthesizable() {
+    let mut uut = FFT256::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("fft", &vlog).unwrap();
+}
@@ -83,6 +83,21 @@ impl<D: Synth, const N: usize, const NP1: usize, const BLOCK_SIZE: u32> Logic
         self.write_fill.next = self.write_logic.fill_level.val();
         self.read_fill.next = self.read_logic.fill_level.val();
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DATA_BITS", D::BITS, "Width of each entry"),
+            ModuleParameter::new(
+                "N",
+                N,
+                "Number of address bits (the FIFO holds 2^N entries)",
+            ),
+            ModuleParameter::new(
+                "BLOCK_SIZE",
+                BLOCK_SIZE,
+                "Granularity of the almost full/empty flags",
+            ),
+        ]
+    }
 }
 
 #[test]
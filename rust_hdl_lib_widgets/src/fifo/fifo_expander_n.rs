@@ -72,6 +72,12 @@ impl<const DN: usize, const DW: usize> Logic for FIFOExpanderN<DN, DW> {
             self.load_count.d.next = 0.into();
         }
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DN", DN, "Width of the words read from the input FIFO"),
+            ModuleParameter::new("DW", DW, "Width of the words written to the output FIFO"),
+        ]
+    }
 }
 
 impl<const DN: usize, const DW: usize> FIFOExpanderN<DN, DW> {
@@ -74,6 +74,13 @@ impl<const DW: usize, const DN: usize, const REVERSE: bool> Logic for FIFOReduce
         // Advance the read interface if it is not empty and we wont be loaded
         self.read.next = self.loaded.q.val() && self.will_run.val() && !self.empty.val();
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DW", DW, "Width of the words read from the input FIFO"),
+            ModuleParameter::new("DN", DN, "Width of the words written to the output FIFO"),
+            ModuleParameter::new("REVERSE", REVERSE, "Emit the most significant half first"),
+        ]
+    }
 }
 
 #[test]
@@ -73,6 +73,12 @@ impl<const DW: usize, const DN: usize> Logic for FIFOReducerN<DW, DN> {
         self.write.next = self.will_write.val();
         self.read.next = self.will_consume.val();
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DW", DW, "Width of the words read from the input FIFO"),
+            ModuleParameter::new("DN", DN, "Width of the words written to the output FIFO"),
+        ]
+    }
 }
 
 impl<const DW: usize, const DN: usize> FIFOReducerN<DW, DN> {
@@ -113,3 +119,12 @@ fn fifo_reducern_is_synthesizable() {
     dev.connect_all();
     yosys_validate("fifo_reducern", &generate_verilog(&dev)).unwrap();
 }
+
+#[test]
+fn fifo_reducern_verilog_names_its_parameters() {
+    let mut dev = FIFOReducerN::<32, 4>::new(WordOrder::MostSignificantFirst);
+    dev.connect_all();
+    let vlog = generate_verilog(&dev);
+    assert!(vlog.contains("localparam DW = 32; // Width of the words read from the input FIFO"));
+    assert!(vlog.contains("localparam DN = 4; // Width of the words written to the output FIFO"));
+}
@@ -76,6 +76,21 @@ impl<D: Synth, const N: usize, const NP1: usize, const BLOCK_SIZE: u32> Logic
         self.read_logic.write_address_delayed.next = self.write_logic.write_address_delayed.val();
         self.write_logic.read_address.next = self.read_logic.read_address_out.val();
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DATA_BITS", D::BITS, "Width of each entry"),
+            ModuleParameter::new(
+                "N",
+                N,
+                "Number of address bits (the FIFO holds 2^N entries)",
+            ),
+            ModuleParameter::new(
+                "BLOCK_SIZE",
+                BLOCK_SIZE,
+                "Granularity of the almost full/empty flags",
+            ),
+        ]
+    }
 }
 
 #[test]
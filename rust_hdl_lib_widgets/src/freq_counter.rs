@@ -0,0 +1,164 @@
+use rust_hdl_lib_core::prelude::*;
+use std::time::Duration;
+
+use crate::{dff::DFF, dff_setup};
+
+/// A [FrequencyCounter] characterizes a pulse train (like an external clock,
+/// or the output of a sensor) against the clock of the circuit.  It makes two kinds
+/// of measurement at the same time:
+///
+/// * Direct counting - the number of rising edges of the input within a fixed gate time.
+///   The frequency is then `edges / gate time`.  This is accurate for fast inputs, since
+///   the error is at most one edge per gate.
+/// * Reciprocal counting - the number of clock cycles between two rising edges of the
+///   input (the period), and how many of those cycles the input was high (the pulse width).
+///   The frequency is then `clock frequency / period`.  This is accurate for slow inputs,
+///   since the error is at most one clock cycle per period.
+///
+/// The input is synchronized to the clock, so it can be asynchronous, but it must be
+/// slower than half of the clock frequency.  The argument [N] of the generic
+/// [FrequencyCounter<N>] sets the width of all of the counters, which must be wide
+/// enough to hold the gate time and the longest period (in clock cycles).
+#[derive(Clone, Debug, LogicBlock)]
+pub struct FrequencyCounter<const N: usize> {
+    /// The clock that drives the [FrequencyCounter], and is the reference for the measurements.
+    pub clock: Signal<In, Clock>,
+    /// The pulse train to measure.
+    pub pulses: Signal<In, Bit>,
+    /// The number of rising edges of the input in the last gate time.
+    pub edges: Signal<Out, Bits<N>>,
+    /// Fires for one clock cycle when `edges` is updated (at the end of each gate time).
+    pub edges_valid: Signal<Out, Bit>,
+    /// The number of clock cycles between the last two rising edges of the input.
+    pub period: Signal<Out, Bits<N>>,
+    /// The number of clock cycles the input was high during the last period.
+    pub high_time: Signal<Out, Bits<N>>,
+    /// Fires for one clock cycle when `period` and `high_time` are updated (after each rising edge).
+    pub period_valid: Signal<Out, Bit>,
+    last_gate_count: Constant<Bits<N>>,
+    sync_0: DFF<Bit>,
+    sync_1: DFF<Bit>,
+    previous: DFF<Bit>,
+    rising: Signal<Local, Bit>,
+    gate_done: Signal<Local, Bit>,
+    gate_counter: DFF<Bits<N>>,
+    edge_counter: DFF<Bits<N>>,
+    edge_latch: DFF<Bits<N>>,
+    edge_strobe: DFF<Bit>,
+    period_counter: DFF<Bits<N>>,
+    high_counter: DFF<Bits<N>>,
+    period_latch: DFF<Bits<N>>,
+    high_latch: DFF<Bits<N>>,
+    period_strobe: DFF<Bit>,
+    seen_edge: DFF<Bit>,
+}
+
+impl<const N: usize> FrequencyCounter<N> {
+    /// Generate a [FrequencyCounter] widget that can be used in a RustHDL circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency`: The frequency (in Hz) of the clock signal driving the circuit.
+    /// * `gate`: The gate time used to count the edges of the input.  This is rounded
+    ///   down to a whole number of clock cycles.
+    ///
+    /// returns: FrequencyCounter<{ N }>
+    pub fn new(frequency: u64, gate: Duration) -> Self {
+        let gate_femto = gate.as_nanos() as f64 * NANOS_PER_FEMTO;
+        let clock_period_femto = freq_hz_to_period_femto(frequency as f64);
+        let clocks = (gate_femto / clock_period_femto).floor() as u64;
+        assert!((clocks as u128) < (1_u128 << (N as u128)));
+        assert!(clocks > 1);
+        Self {
+            clock: Default::default(),
+            pulses: Default::default(),
+            edges: Default::default(),
+            edges_valid: Default::default(),
+            period: Default::default(),
+            high_time: Default::default(),
+            period_valid: Default::default(),
+            last_gate_count: Constant::new((clocks - 1).into()),
+            sync_0: Default::default(),
+            sync_1: Default::default(),
+            previous: Default::default(),
+            rising: Default::default(),
+            gate_done: Default::default(),
+            gate_counter: Default::default(),
+            edge_counter: Default::default(),
+            edge_latch: Default::default(),
+            edge_strobe: Default::default(),
+            period_counter: Default::default(),
+            high_counter: Default::default(),
+            period_latch: Default::default(),
+            high_latch: Default::default(),
+            period_strobe: Default::default(),
+            seen_edge: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize> Logic for FrequencyCounter<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            sync_0,
+            sync_1,
+            previous,
+            gate_counter,
+            edge_counter,
+            edge_latch,
+            edge_strobe,
+            period_counter,
+            high_counter,
+            period_latch,
+            high_latch,
+            period_strobe,
+            seen_edge
+        );
+        self.sync_0.d.next = self.pulses.val();
+        self.sync_1.d.next = self.sync_0.q.val();
+        self.previous.d.next = self.sync_1.q.val();
+        self.rising.next = self.sync_1.q.val() & !self.previous.q.val();
+        // Direct counting of the edges within the gate
+        self.gate_done.next = self.gate_counter.q.val() == self.last_gate_count.val();
+        self.gate_counter.d.next = self.gate_counter.q.val() + 1;
+        if self.rising.val() {
+            self.edge_counter.d.next = self.edge_counter.q.val() + 1;
+        }
+        if self.gate_done.val() {
+            self.gate_counter.d.next = 0.into();
+            self.edge_latch.d.next = self.edge_counter.q.val();
+            self.edge_counter.d.next = bit_cast::<N, 1>(self.rising.val().into());
+        }
+        self.edge_strobe.d.next = self.gate_done.val();
+        // Reciprocal counting of the clock cycles between edges
+        self.period_counter.d.next = self.period_counter.q.val() + 1;
+        if self.sync_1.q.val() {
+            self.high_counter.d.next = self.high_counter.q.val() + 1;
+        }
+        if self.rising.val() {
+            self.period_latch.d.next = self.period_counter.q.val();
+            self.high_latch.d.next = self.high_counter.q.val();
+            self.period_counter.d.next = 1.into();
+            self.high_counter.d.next = 1.into();
+            self.seen_edge.d.next = true;
+        }
+        // The first edge only starts the first period
+        self.period_strobe.d.next = self.rising.val() & self.seen_edge.q.val();
+        self.edges.next = self.edge_latch.q.val();
+        self.edges_valid.next = self.edge_strobe.q.val();
+        self.period.next = self.period_latch.q.val();
+        self.high_time.next = self.high_latch.q.val();
+        self.period_valid.next = self.period_strobe.q.val();
+    }
+}
+
+#[test]
+fn test_frequency_counter_is_synthesizable() {
+    let mut uut = FrequencyCounter::<32>::new(100_000_000, Duration::from_millis(10));
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("freq_counter", &vlog).unwrap();
+}
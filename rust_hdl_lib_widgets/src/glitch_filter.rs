@@ -0,0 +1,73 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use rust_hdl_lib_core::prelude::*;
+
+/// A [GlitchFilter] removes short glitches from a slowly changing input (e.g., a limit switch,
+/// or an interlock line) by taking the majority of its last `N` samples.  A glitch (or a burst
+/// of them) that lasts less than `(N+1)/2` of any `N` clocks never reaches the output, while a
+/// real change of the input appears on the output `(N+1)/2` clocks later.  The number of ones in
+/// the window is kept as a running count, so the cost grows only slowly with `N`.  The input must
+/// be synchronous to the clock - pass asynchronous inputs through a [BitSynchronizer](crate::synchronizer::BitSynchronizer)
+/// first.  The filter starts out with a window full of zeros.
+#[derive(LogicBlock)]
+pub struct GlitchFilter<const N: usize> {
+    /// The clock for the filter
+    pub clock: Signal<In, Clock>,
+    /// The (synchronous) input signal
+    pub data_in: Signal<In, Bit>,
+    /// The filtered output, which is registered
+    pub data_out: Signal<Out, Bit>,
+    history: DFF<Bits<N>>,
+    count: DFF<Bits<8>>,
+    filtered: DFF<Bit>,
+    next_count: Signal<Local, Bits<8>>,
+    oldest: Constant<Bits<8>>,
+    half: Constant<Bits<8>>,
+}
+
+impl<const N: usize> Default for GlitchFilter<N> {
+    fn default() -> Self {
+        assert!(
+            N % 2 == 1 && N < 256,
+            "The window of a glitch filter must be odd and below 256"
+        );
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            data_out: Default::default(),
+            history: Default::default(),
+            count: Default::default(),
+            filtered: Default::default(),
+            next_count: Default::default(),
+            oldest: Constant::new((N - 1).to_bits()),
+            half: Constant::new((N / 2).to_bits()),
+        }
+    }
+}
+
+impl<const N: usize> Logic for GlitchFilter<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, history, count, filtered);
+        self.history.d.next =
+            (self.history.q.val() << 1) | bit_cast::<N, 1>(self.data_in.val().into());
+        // Track the number of ones in the window as samples enter and leave it
+        self.next_count.next = self.count.q.val();
+        if self.data_in.val() & !self.history.q.val().get_bit(self.oldest.val().index()) {
+            self.next_count.next = self.count.q.val() + 1;
+        }
+        if !self.data_in.val() & self.history.q.val().get_bit(self.oldest.val().index()) {
+            self.next_count.next = self.count.q.val() - 1;
+        }
+        self.count.d.next = self.next_count.val();
+        self.filtered.d.next = self.next_count.val() > self.half.val();
+        self.data_out.next = self.filtered.q.val();
+    }
+}
+
+#[test]
+fn test_glitch_filter_is_synthesizable() {
+    let mut uut = GlitchFilter::<5>::default();
+    uut.connect_all();
+    yosys_validate("glitch_filter", &generate_verilog(&uut)).unwrap();
+}
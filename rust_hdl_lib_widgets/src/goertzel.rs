@@ -0,0 +1,243 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use rust_hdl_lib_core::prelude::*;
+
+// Multiplies a 48 bit value by a fixed 16 bit coefficient with `frac` fraction
+// bits.  Only 16 x 16 multipliers are available, so the value is split into
+// a signed top word and two unsigned words.  The unsigned words are scaled by
+// the magnitude of the coefficient, and the sign is applied afterwards.
+#[derive(LogicBlock)]
+struct CoefficientScaler {
+    pub data_in: Signal<In, Signed<48>>,
+    pub data_out: Signal<Out, Signed<48>>,
+    hi_product: Signal<Local, Signed<32>>,
+    mid_product: Signal<Local, Bits<32>>,
+    lo_product: Signal<Local, Bits<32>>,
+    low: Signal<Local, Signed<64>>,
+    total: Signal<Local, Signed<64>>,
+    coeff: Constant<Signed<16>>,
+    magnitude: Constant<Bits<16>>,
+    negative: Constant<Bit>,
+    frac: Constant<Bits<8>>,
+}
+
+impl CoefficientScaler {
+    fn new(coeff: i64, frac: usize) -> Self {
+        assert!((-32768..32768).contains(&coeff));
+        Self {
+            data_in: Default::default(),
+            data_out: Default::default(),
+            hi_product: Default::default(),
+            mid_product: Default::default(),
+            lo_product: Default::default(),
+            low: Default::default(),
+            total: Default::default(),
+            coeff: Constant::new(coeff.to_signed_bits()),
+            magnitude: Constant::new((coeff.unsigned_abs()).to_bits()),
+            negative: Constant::new(coeff < 0),
+            frac: Constant::new(frac.to_bits()),
+        }
+    }
+}
+
+impl Logic for CoefficientScaler {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.hi_product.next = self.coeff.val() * self.data_in.val().get_bits::<16>(32);
+        self.mid_product.next =
+            self.magnitude.val() * unsigned_cast(self.data_in.val().get_bits::<16>(16));
+        self.lo_product.next =
+            self.magnitude.val() * unsigned_cast(self.data_in.val().get_bits::<16>(0));
+        self.low.next = signed_cast(
+            (bit_cast::<64, 32>(self.mid_product.val()) << 16)
+                + bit_cast::<64, 32>(self.lo_product.val()),
+        );
+        if self.negative.val() {
+            self.low.next = -signed_cast(
+                (bit_cast::<64, 32>(self.mid_product.val()) << 16)
+                    + bit_cast::<64, 32>(self.lo_product.val()),
+            );
+        }
+        self.total.next =
+            signed_cast(unsigned_cast(signed_bit_cast::<64, 32>(self.hi_product.val())) << 32)
+                + self.low.val();
+        self.data_out.next = self.total.val().get_bits::<48>(self.frac.val().index());
+    }
+}
+
+/// Goertzel single bin detector
+///
+/// Computes a single bin of the DFT of each block of `length` samples presented on
+/// [`data_in`](Self::data_in), using the Goertzel recurrence
+/// `s[n] = x[n] + 2 cos(w) s[n-1] - s[n-2]`, with `w = 2 pi bin / length`.  This is much
+/// cheaper than a full FFT when only a few tones are of interest (e.g., DTMF digits, or a
+/// pilot tone).  At the end of each block, the result
+/// `s[N-1] - exp(-jw) s[N-2]` (the DFT bin, rotated by `w (N - 1)`) appears on
+/// [`real`](Self::real) and [`imag`](Self::imag), along with an estimate of its magnitude,
+/// and [`strobe_out`](Self::strobe_out) is asserted for one clock.  The results are held
+/// until the end of the next block.
+///
+/// ### Inputs
+///
+/// * [`clock`](Self::clock) The clock for the detector.
+/// * [`data_in`](Self::data_in) The samples.
+/// * [`strobe_in`](Self::strobe_in) Assert for one clock with each sample.
+/// * [`threshold`](Self::threshold) The magnitude above which the tone is detected.
+///
+/// ### Outputs
+///
+/// * [`real`](Self::real), [`imag`](Self::imag) The bin for the last block.
+/// * [`magnitude`](Self::magnitude) The magnitude of the bin (estimated as `15/16 max + 15/32 min`
+///   of the absolute values of the real and imaginary parts, which is within about 6% of the
+///   true value).
+/// * [`detected`](Self::detected) Asserted while the magnitude is above the threshold.
+/// * [`strobe_out`](Self::strobe_out) Asserted for one clock when a new result is available.
+///
+/// ### Additional info
+///
+/// The state is kept to 48 bits, which is enough for blocks of up to 65536 full scale samples
+/// at any bin, except for DC, which is not supported.  A tone of amplitude `A` in the bin gives
+/// a magnitude of about `A length / 2`.
+#[derive(LogicBlock)]
+pub struct Goertzel {
+    /// The clock for the detector.
+    pub clock: Signal<In, Clock>,
+    /// The samples.
+    pub data_in: Signal<In, Signed<16>>,
+    /// Assert for one clock with each sample.
+    pub strobe_in: Signal<In, Bit>,
+    /// The magnitude above which the tone is detected.
+    pub threshold: Signal<In, Bits<48>>,
+    /// The real part of the bin for the last block.
+    pub real: Signal<Out, Signed<48>>,
+    /// The imaginary part of the bin for the last block.
+    pub imag: Signal<Out, Signed<48>>,
+    /// An estimate of the magnitude of the bin.
+    pub magnitude: Signal<Out, Bits<48>>,
+    /// Asserted while the magnitude is above the threshold.
+    pub detected: Signal<Out, Bit>,
+    /// Asserted for one clock when a new result is available.
+    pub strobe_out: Signal<Out, Bit>,
+    feedback: CoefficientScaler,
+    real_scale: CoefficientScaler,
+    imag_scale: CoefficientScaler,
+    s0: Signal<Local, Signed<48>>,
+    s1: DFF<Signed<48>>,
+    s2: DFF<Signed<48>>,
+    count: DFF<Bits<16>>,
+    final_1: DFF<Signed<48>>,
+    final_2: DFF<Signed<48>>,
+    finished: DFF<Bit>,
+    real_reg: DFF<Signed<48>>,
+    imag_reg: DFF<Signed<48>>,
+    strobe: DFF<Bit>,
+    abs_real: Signal<Local, Bits<48>>,
+    abs_imag: Signal<Local, Bits<48>>,
+    max: Signal<Local, Bits<48>>,
+    min: Signal<Local, Bits<48>>,
+    last: Constant<Bits<16>>,
+}
+
+impl Goertzel {
+    pub fn new(bin: usize, length: usize) -> Self {
+        assert!((2..=65536).contains(&length));
+        assert!(bin > 0 && 2 * bin < length);
+        let w = 2.0 * std::f64::consts::PI * bin as f64 / length as f64;
+        let quantize = |x: f64, frac: usize| -> i64 {
+            ((x * (1 << frac) as f64).round() as i64).clamp(-32768, 32767)
+        };
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            threshold: Default::default(),
+            real: Default::default(),
+            imag: Default::default(),
+            magnitude: Default::default(),
+            detected: Default::default(),
+            strobe_out: Default::default(),
+            feedback: CoefficientScaler::new(quantize(2.0 * w.cos(), 14), 14),
+            real_scale: CoefficientScaler::new(quantize(w.cos(), 15), 15),
+            imag_scale: CoefficientScaler::new(quantize(w.sin(), 15), 15),
+            s0: Default::default(),
+            s1: Default::default(),
+            s2: Default::default(),
+            count: Default::default(),
+            final_1: Default::default(),
+            final_2: Default::default(),
+            finished: Default::default(),
+            real_reg: Default::default(),
+            imag_reg: Default::default(),
+            strobe: Default::default(),
+            abs_real: Default::default(),
+            abs_imag: Default::default(),
+            max: Default::default(),
+            min: Default::default(),
+            last: Constant::new((length - 1).to_bits()),
+        }
+    }
+}
+
+impl Logic for Goertzel {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self, clock, s1, s2, count, final_1, final_2, finished, real_reg, imag_reg, strobe
+        );
+        // Run the recurrence
+        self.feedback.data_in.next = self.s1.q.val();
+        self.s0.next = signed_bit_cast::<48, 16>(self.data_in.val()) + self.feedback.data_out.val()
+            - self.s2.q.val();
+        self.finished.d.next = false;
+        if self.strobe_in.val() {
+            self.s1.d.next = self.s0.val();
+            self.s2.d.next = self.s1.q.val();
+            self.count.d.next = self.count.q.val() + 1;
+            if self.count.q.val() == self.last.val() {
+                self.final_1.d.next = self.s0.val();
+                self.final_2.d.next = self.s1.q.val();
+                self.finished.d.next = true;
+                self.s1.d.next = 0.into();
+                self.s2.d.next = 0.into();
+                self.count.d.next = 0.into();
+            }
+        }
+        // Rotate the final state into the bin
+        self.real_scale.data_in.next = self.final_2.q.val();
+        self.imag_scale.data_in.next = self.final_2.q.val();
+        self.strobe.d.next = self.finished.q.val();
+        if self.finished.q.val() {
+            self.real_reg.d.next = self.final_1.q.val() - self.real_scale.data_out.val();
+            self.imag_reg.d.next = self.imag_scale.data_out.val();
+        }
+        self.real.next = self.real_reg.q.val();
+        self.imag.next = self.imag_reg.q.val();
+        self.strobe_out.next = self.strobe.q.val();
+        // Estimate the magnitude
+        self.abs_real.next = unsigned_cast(self.real_reg.q.val());
+        if self.real_reg.q.val().get_bit(47) {
+            self.abs_real.next = unsigned_cast(-self.real_reg.q.val());
+        }
+        self.abs_imag.next = unsigned_cast(self.imag_reg.q.val());
+        if self.imag_reg.q.val().get_bit(47) {
+            self.abs_imag.next = unsigned_cast(-self.imag_reg.q.val());
+        }
+        self.max.next = self.abs_real.val();
+        self.min.next = self.abs_imag.val();
+        if self.abs_imag.val() > self.abs_real.val() {
+            self.max.next = self.abs_imag.val();
+            self.min.next = self.abs_real.val();
+        }
+        self.magnitude.next =
+            self.max.val() - (self.max.val() >> 4) + (self.min.val() >> 1) - (self.min.val() >> 5);
+        self.detected.next = self.magnitude.val() > self.threshold.val();
+    }
+}
+
+#[test]
+fn test_goertzel_is_synthesizable() {
+    let mut uut = Goertzel::new(10, 256);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("goertzel", &vlog).unwrap();
+}
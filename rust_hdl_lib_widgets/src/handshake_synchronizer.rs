@@ -0,0 +1,100 @@
+use crate::{dff::DFF, dff_setup, synchronizer::BitSynchronizer};
+use rust_hdl_lib_core::prelude::*;
+
+/// A [HandshakeSynchronizer] moves a value from one clock domain to another with a four
+/// phase handshake, and (unlike a [VectorSynchronizer](crate::synchronizer::VectorSynchronizer))
+/// waits for the destination to accept the value before it completes.  That makes it useful
+/// for commands that have to be acted on, where the source needs to know when that happened.
+///
+/// The four phases are:
+///  1. The source latches [data_in] when [send] is raised, and raises the request.
+///  2. The destination sees the request, holds the value on [data_out] and asserts [valid].
+///     Once the value is taken (by raising [accept] for one clock), the destination raises
+///     the acknowledge.
+///  3. The source sees the acknowledge, strobes [ack] for one clock, and drops the request.
+///  4. The destination sees the request drop, and drops the acknowledge.  Once the source
+///     sees that, it is no longer [busy].
+///
+/// Each flag crosses a [BitSynchronizer], and without metastability (i.e., in simulation),
+/// the latencies are bounded by:
+///  - [valid] is asserted within 2 periods of [clock_out] of the [clock_in] edge that
+///    sampled [send].
+///  - [ack] is strobed within 2 periods of [clock_in] of the [clock_out] edge that
+///    sampled [accept].
+///  - [busy] clears within 5 periods of [clock_in] plus 3 periods of [clock_out] of the
+///    edge that sampled [accept].
+///
+/// A synchronizer that is slow to resolve adds up to one period of its clock to each crossing.
+/// The value crosses on its own wires, and is stable for as long as the request is raised,
+/// so it does not need synchronizing.
+#[derive(LogicBlock, Default)]
+pub struct HandshakeSynchronizer<T: Synth> {
+    /// The clock of the source domain
+    pub clock_in: Signal<In, Clock>,
+    /// The value to send (latched when [send] is raised)
+    pub data_in: Signal<In, T>,
+    /// Raise for one clock (when not [busy]) to send [data_in]
+    pub send: Signal<In, Bit>,
+    /// Asserted (in the source domain) until the handshake is complete
+    pub busy: Signal<Out, Bit>,
+    /// Strobed (in the source domain) when the destination has accepted the value
+    pub ack: Signal<Out, Bit>,
+    /// The clock of the destination domain
+    pub clock_out: Signal<In, Clock>,
+    /// The value sent, held while [valid] is asserted
+    pub data_out: Signal<Out, T>,
+    /// Asserted (in the destination domain) while there is a value waiting to be accepted
+    pub valid: Signal<Out, Bit>,
+    /// Raise for one clock (while [valid] is asserted) to accept the value
+    pub accept: Signal<In, Bit>,
+    hold: DFF<T>,
+    request: DFF<Bit>,
+    acknowledge_sync: BitSynchronizer,
+    data: DFF<T>,
+    pending: DFF<Bit>,
+    acknowledge: DFF<Bit>,
+    request_sync: BitSynchronizer,
+}
+
+impl<T: Synth> Logic for HandshakeSynchronizer<T> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock_in, hold, request);
+        dff_setup!(self, clock_out, data, pending, acknowledge);
+        clock!(self, clock_in, acknowledge_sync);
+        clock!(self, clock_out, request_sync);
+        // Source side
+        self.acknowledge_sync.sig_in.next = self.acknowledge.q.val();
+        self.busy.next = self.request.q.val() | self.acknowledge_sync.sig_out.val();
+        self.ack.next = self.request.q.val() & self.acknowledge_sync.sig_out.val();
+        if self.send.val() & !self.request.q.val() & !self.acknowledge_sync.sig_out.val() {
+            self.hold.d.next = self.data_in.val();
+            self.request.d.next = true;
+        }
+        if self.request.q.val() & self.acknowledge_sync.sig_out.val() {
+            self.request.d.next = false;
+        }
+        // Destination side
+        self.request_sync.sig_in.next = self.request.q.val();
+        self.data_out.next = self.data.q.val();
+        self.valid.next = self.pending.q.val();
+        if self.request_sync.sig_out.val() & !self.pending.q.val() & !self.acknowledge.q.val() {
+            self.data.d.next = self.hold.q.val();
+            self.pending.d.next = true;
+        }
+        if self.pending.q.val() & self.accept.val() {
+            self.pending.d.next = false;
+            self.acknowledge.d.next = true;
+        }
+        if self.acknowledge.q.val() & !self.request_sync.sig_out.val() {
+            self.acknowledge.d.next = false;
+        }
+    }
+}
+
+#[test]
+fn test_handshake_synchronizer_is_synthesizable() {
+    let mut uut: HandshakeSynchronizer<Bits<8>> = Default::default();
+    uut.connect_all();
+    yosys_validate("handshake_sync", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,389 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::dff_with_init::DFFWithInit;
+use crate::ramrom::ram::RAM;
+use rust_hdl_lib_core::prelude::*;
+
+/// The byte that starts and ends every HDLC frame
+pub const HDLC_FLAG: u8 = 0x7E;
+/// The byte that marks the next byte of an HDLC frame as escaped
+pub const HDLC_ESCAPE: u8 = 0x7D;
+/// Escaped bytes are sent XORed with this value
+pub const HDLC_ESCAPE_XOR: u8 = 0x20;
+/// The initial value of the frame check sequence (CRC-16/CCITT-FALSE)
+pub const HDLC_CRC_INIT: u16 = 0xFFFF;
+
+/// A [CRC16Byte] advances a CRC-16/CCITT (polynomial `0x1021`, MSB first) by one byte.  It is
+/// purely combinational (the 8 shifts are unrolled), so the CRC register lives in the parent.
+/// Start the register at [HDLC_CRC_INIT] for the CRC-16/CCITT-FALSE used by the HDLC framing.
+#[derive(LogicBlock)]
+pub struct CRC16Byte {
+    /// The CRC so far
+    pub crc_in: Signal<In, Bits<16>>,
+    /// The next byte
+    pub data: Signal<In, Bits<8>>,
+    /// The CRC including the byte
+    pub crc_out: Signal<Out, Bits<16>>,
+    crc: Signal<Local, Bits<16>>,
+    polynomial: Constant<Bits<16>>,
+}
+
+impl Default for CRC16Byte {
+    fn default() -> Self {
+        Self {
+            crc_in: Default::default(),
+            data: Default::default(),
+            crc_out: Default::default(),
+            crc: Default::default(),
+            polynomial: Constant::new(0x1021.into()),
+        }
+    }
+}
+
+impl Logic for CRC16Byte {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.crc.next = self.crc_in.val();
+        for i in 0..8 {
+            if self.crc.val().get_bit(15) ^ self.data.val().get_bit(7 - i) {
+                self.crc.next = (self.crc.val() << 1) ^ self.polynomial.val();
+            } else {
+                self.crc.next = self.crc.val() << 1;
+            }
+        }
+        self.crc_out.next = self.crc.val();
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum HDLCFramerState {
+    Idle,
+    Open,
+    Data,
+    CRCHigh,
+    CRCLow,
+    Close,
+}
+
+/// An [HDLCFramer] packs a stream of bytes into HDLC-like frames for an unreliable byte link
+/// (like a UART).  Each frame is a flag byte (`0x7E`), the payload, a CRC-16/CCITT-FALSE of the
+/// payload (sent MSB first), and a closing flag.  Flag and escape (`0x7D`) bytes that appear
+/// in the payload or the CRC are sent as an escape followed by the byte XORed with `0x20`.
+///
+/// The payload is read from a first-word-fall-through FIFO, and the framed bytes are written
+/// into anything that looks like the write side of a FIFO (such as a [UARTTransmitter](crate::uart::UARTTransmitter),
+/// with `full` tied to its `busy` output).  A frame is closed when the payload FIFO runs dry at
+/// the moment the output could take another byte, or when it reaches `max_payload` bytes.  As
+/// the output is normally much slower than the clock, a steady producer gets long frames.
+#[derive(LogicBlock)]
+pub struct HDLCFramer {
+    /// The clock for the framer
+    pub clock: Signal<In, Clock>,
+    /// The next payload byte (from a FWFT FIFO)
+    pub data_in: Signal<In, Bits<8>>,
+    /// Asserted when there are no payload bytes
+    pub empty_in: Signal<In, Bit>,
+    /// Strobe to take the payload byte on `data_in`
+    pub read: Signal<Out, Bit>,
+    /// The framed byte to send
+    pub data_out: Signal<Out, Bits<8>>,
+    /// Strobe to send the byte on `data_out`
+    pub write: Signal<Out, Bit>,
+    /// Asserted when the output cannot take a byte
+    pub full: Signal<In, Bit>,
+    state: DFF<HDLCFramerState>,
+    crc: DFFWithInit<Bits<16>>,
+    count: DFF<Bits<16>>,
+    stuffed: DFF<Bit>,
+    crc_calc: CRC16Byte,
+    raw: Signal<Local, Bits<8>>,
+    special: Signal<Local, Bit>,
+    flag: Constant<Bits<8>>,
+    escape: Constant<Bits<8>>,
+    escape_xor: Constant<Bits<8>>,
+    crc_init: Constant<Bits<16>>,
+    max_payload: Constant<Bits<16>>,
+}
+
+impl HDLCFramer {
+    pub fn new(max_payload: usize) -> Self {
+        assert!(
+            max_payload > 0 && max_payload < 65536,
+            "The payload of an HDLC frame must hold between 1 and 65535 bytes"
+        );
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            empty_in: Default::default(),
+            read: Default::default(),
+            data_out: Default::default(),
+            write: Default::default(),
+            full: Default::default(),
+            state: Default::default(),
+            crc: DFFWithInit::new(HDLC_CRC_INIT.to_bits()),
+            count: Default::default(),
+            stuffed: Default::default(),
+            crc_calc: Default::default(),
+            raw: Default::default(),
+            special: Default::default(),
+            flag: Constant::new(HDLC_FLAG.to_bits()),
+            escape: Constant::new(HDLC_ESCAPE.to_bits()),
+            escape_xor: Constant::new(HDLC_ESCAPE_XOR.to_bits()),
+            crc_init: Constant::new(HDLC_CRC_INIT.to_bits()),
+            max_payload: Constant::new(max_payload.to_bits()),
+        }
+    }
+}
+
+impl Logic for HDLCFramer {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state, crc, count, stuffed);
+        self.crc_calc.crc_in.next = self.crc.q.val();
+        self.crc_calc.data.next = self.data_in.val();
+        // The byte we want to send next (before escaping)
+        self.raw.next = self.flag.val();
+        self.special.next = false;
+        match self.state.q.val() {
+            HDLCFramerState::Data => {
+                self.raw.next = self.data_in.val();
+            }
+            HDLCFramerState::CRCHigh => {
+                self.raw.next = self.crc.q.val().get_bits::<8>(8);
+            }
+            HDLCFramerState::CRCLow => {
+                self.raw.next = self.crc.q.val().get_bits::<8>(0);
+            }
+            _ => {}
+        }
+        if (self.state.q.val() == HDLCFramerState::Data)
+            | (self.state.q.val() == HDLCFramerState::CRCHigh)
+            | (self.state.q.val() == HDLCFramerState::CRCLow)
+        {
+            self.special.next =
+                (self.raw.val() == self.flag.val()) | (self.raw.val() == self.escape.val());
+        }
+        self.data_out.next = self.raw.val();
+        if self.stuffed.q.val() {
+            self.data_out.next = self.raw.val() ^ self.escape_xor.val();
+        } else if self.special.val() {
+            self.data_out.next = self.escape.val();
+        }
+        self.read.next = false;
+        self.write.next = false;
+        match self.state.q.val() {
+            HDLCFramerState::Idle => {
+                if !self.empty_in.val() {
+                    self.crc.d.next = self.crc_init.val();
+                    self.count.d.next = 0.into();
+                    self.state.d.next = HDLCFramerState::Open;
+                }
+            }
+            HDLCFramerState::Open => {
+                if !self.full.val() {
+                    self.write.next = true;
+                    self.state.d.next = HDLCFramerState::Data;
+                }
+            }
+            HDLCFramerState::Data => {
+                if !self.full.val() {
+                    if self.empty_in.val() | (self.count.q.val() == self.max_payload.val()) {
+                        self.state.d.next = HDLCFramerState::CRCHigh;
+                    } else {
+                        self.write.next = true;
+                        if self.special.val() & !self.stuffed.q.val() {
+                            self.stuffed.d.next = true;
+                        } else {
+                            self.stuffed.d.next = false;
+                            self.read.next = true;
+                            self.crc.d.next = self.crc_calc.crc_out.val();
+                            self.count.d.next = self.count.q.val() + 1;
+                        }
+                    }
+                }
+            }
+            HDLCFramerState::CRCHigh => {
+                if !self.full.val() {
+                    self.write.next = true;
+                    if self.special.val() & !self.stuffed.q.val() {
+                        self.stuffed.d.next = true;
+                    } else {
+                        self.stuffed.d.next = false;
+                        self.state.d.next = HDLCFramerState::CRCLow;
+                    }
+                }
+            }
+            HDLCFramerState::CRCLow => {
+                if !self.full.val() {
+                    self.write.next = true;
+                    if self.special.val() & !self.stuffed.q.val() {
+                        self.stuffed.d.next = true;
+                    } else {
+                        self.stuffed.d.next = false;
+                        self.state.d.next = HDLCFramerState::Close;
+                    }
+                }
+            }
+            HDLCFramerState::Close => {
+                if !self.full.val() {
+                    self.write.next = true;
+                    self.state.d.next = HDLCFramerState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = HDLCFramerState::Idle;
+            }
+        }
+    }
+}
+
+/// An [HDLCDeframer] recovers the payloads of frames sent by an [HDLCFramer] (or the host
+/// side encoder) from a stream of received bytes, such as the output of a [UARTReceiver](crate::uart::UARTReceiver).
+/// Escapes are removed, and the payload is collected in a circular buffer of `2^N` bytes.
+/// When the closing flag arrives, the CRC is checked, and only then is the payload released to
+/// the first-word-fall-through output.  Frames with a bad CRC, frames that are too short to
+/// hold a CRC, and frames that do not fit in the free space of the buffer are thrown away, and
+/// reported with a strobe on `dropped`.  Empty frames (i.e., back to back flags) are ignored.
+#[derive(LogicBlock)]
+pub struct HDLCDeframer<const N: usize> {
+    /// The clock for the deframer
+    pub clock: Signal<In, Clock>,
+    /// The received byte
+    pub data_in: Signal<In, Bits<8>>,
+    /// Strobe for each received byte
+    pub strobe_in: Signal<In, Bit>,
+    /// The next payload byte (first word fall through)
+    pub data_out: Signal<Out, Bits<8>>,
+    /// Asserted when there are no checked payload bytes
+    pub empty: Signal<Out, Bit>,
+    /// Strobe to take the byte on `data_out`
+    pub read: Signal<In, Bit>,
+    /// Strobes for one clock when a frame is thrown away
+    pub dropped: Signal<Out, Bit>,
+    buffer: RAM<Bits<8>, N>,
+    write_ptr: DFF<Bits<N>>,
+    committed: DFF<Bits<N>>,
+    read_ptr: DFF<Bits<N>>,
+    escaped: DFF<Bit>,
+    length: DFF<Bits<2>>,
+    overflow: DFF<Bit>,
+    drop: DFF<Bit>,
+    crc: DFFWithInit<Bits<16>>,
+    crc_calc: CRC16Byte,
+    byte: Signal<Local, Bits<8>>,
+    next_read: Signal<Local, Bits<N>>,
+    is_empty: Signal<Local, Bit>,
+    flag: Constant<Bits<8>>,
+    escape: Constant<Bits<8>>,
+    escape_xor: Constant<Bits<8>>,
+    crc_init: Constant<Bits<16>>,
+}
+
+impl<const N: usize> Default for HDLCDeframer<N> {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            data_out: Default::default(),
+            empty: Default::default(),
+            read: Default::default(),
+            dropped: Default::default(),
+            buffer: Default::default(),
+            write_ptr: Default::default(),
+            committed: Default::default(),
+            read_ptr: Default::default(),
+            escaped: Default::default(),
+            length: Default::default(),
+            overflow: Default::default(),
+            drop: Default::default(),
+            crc: DFFWithInit::new(HDLC_CRC_INIT.to_bits()),
+            crc_calc: Default::default(),
+            byte: Default::default(),
+            next_read: Default::default(),
+            is_empty: Default::default(),
+            flag: Constant::new(HDLC_FLAG.to_bits()),
+            escape: Constant::new(HDLC_ESCAPE.to_bits()),
+            escape_xor: Constant::new(HDLC_ESCAPE_XOR.to_bits()),
+            crc_init: Constant::new(HDLC_CRC_INIT.to_bits()),
+        }
+    }
+}
+
+impl<const N: usize> Logic for HDLCDeframer<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self, clock, write_ptr, committed, read_ptr, escaped, length, overflow, drop, crc
+        );
+        self.buffer.read_clock.next = self.clock.val();
+        self.buffer.write_clock.next = self.clock.val();
+        // Read side - the buffer is read ahead so that the output falls through
+        self.is_empty.next = self.read_ptr.q.val() == self.committed.q.val();
+        self.next_read.next = self.read_ptr.q.val();
+        if self.read.val() & !self.is_empty.val() {
+            self.next_read.next = self.read_ptr.q.val() + 1;
+        }
+        self.read_ptr.d.next = self.next_read.val();
+        self.buffer.read_address.next = self.next_read.val();
+        self.data_out.next = self.buffer.read_data.val();
+        self.empty.next = self.is_empty.val();
+        self.dropped.next = self.drop.q.val();
+        // Write side
+        self.byte.next = self.data_in.val();
+        if self.escaped.q.val() {
+            self.byte.next = self.data_in.val() ^ self.escape_xor.val();
+        }
+        self.crc_calc.crc_in.next = self.crc.q.val();
+        self.crc_calc.data.next = self.byte.val();
+        self.buffer.write_address.next = self.write_ptr.q.val();
+        self.buffer.write_data.next = self.byte.val();
+        self.buffer.write_enable.next = false;
+        self.drop.d.next = false;
+        if self.strobe_in.val() {
+            if self.data_in.val() == self.flag.val() {
+                // End of a frame - a good CRC leaves a zero residue.  Keep the payload, but
+                // not the CRC itself.
+                if (self.length.q.val() == 3) & !self.crc.q.val().any() & !self.overflow.q.val() {
+                    self.committed.d.next = self.write_ptr.q.val() - 2;
+                    self.write_ptr.d.next = self.write_ptr.q.val() - 2;
+                } else {
+                    self.write_ptr.d.next = self.committed.q.val();
+                    self.drop.d.next = self.length.q.val().any() | self.overflow.q.val();
+                }
+                self.length.d.next = 0.into();
+                self.crc.d.next = self.crc_init.val();
+                self.escaped.d.next = false;
+                self.overflow.d.next = false;
+            } else if self.data_in.val() == self.escape.val() {
+                self.escaped.d.next = true;
+            } else {
+                self.escaped.d.next = false;
+                if (self.write_ptr.q.val() + 1) == self.read_ptr.q.val() {
+                    self.overflow.d.next = true;
+                } else {
+                    self.buffer.write_enable.next = true;
+                    self.write_ptr.d.next = self.write_ptr.q.val() + 1;
+                    self.crc.d.next = self.crc_calc.crc_out.val();
+                    if self.length.q.val() != 3 {
+                        self.length.d.next = self.length.q.val() + 1;
+                    }
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_hdlc_framer_is_synthesizable() {
+    let mut uut = HDLCFramer::new(256);
+    uut.connect_all();
+    yosys_validate("hdlc_framer", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_hdlc_deframer_is_synthesizable() {
+    let mut uut = HDLCDeframer::<8>::default();
+    uut.connect_all();
+    yosys_validate("hdlc_deframer", &generate_verilog(&uut)).unwrap();
+}
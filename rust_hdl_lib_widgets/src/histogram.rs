@@ -0,0 +1,201 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::ramrom::ram::RAM;
+use rust_hdl_lib_core::prelude::*;
+
+#[derive(Copy, Clone, Debug, PartialEq, LogicState)]
+enum HistogramState {
+    Clearing,
+    Running,
+}
+
+// Accumulates a histogram of the samples presented on `data_in` (when `strobe_in`
+// is asserted).  A sample `x` is counted in bin `(x - offset) >> shift`, so the
+// bins are `2^shift` wide, and there are `2^B` of them.  Samples below the offset
+// are counted in `underflow`, and samples past the last bin in `overflow`.  All
+// counts saturate at their maximum value instead of wrapping.  One sample can
+// be accepted every clock.
+//
+// The counts are held in a pair of RAMs with a common write port, so that the
+// bin at `read_address` can be read out (on `read_data`, one clock later) while
+// the histogram is accumulating.  Asserting `clear` zeros the counts and the
+// statistics.  Clearing takes `2^B` clocks, during which `busy` is asserted and
+// samples are ignored.  The histogram is cleared after reset too.
+#[derive(LogicBlock)]
+pub struct Histogram<const S: usize, const B: usize, const C: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<S>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub offset: Signal<In, Bits<S>>,
+    pub shift: Signal<In, Bits<8>>,
+    pub clear: Signal<In, Bit>,
+    pub busy: Signal<Out, Bit>,
+    pub read_address: Signal<In, Bits<B>>,
+    pub read_data: Signal<Out, Bits<C>>,
+    pub total: Signal<Out, Bits<C>>,
+    pub underflow: Signal<Out, Bits<C>>,
+    pub overflow: Signal<Out, Bits<C>>,
+    // The counts, as seen by the accumulator
+    counts: RAM<Bits<C>, B>,
+    // A copy of the counts for readout
+    readout: RAM<Bits<C>, B>,
+    state: DFF<HistogramState>,
+    clear_address: DFF<Bits<B>>,
+    // Bin computation
+    delta: Signal<Local, Bits<S>>,
+    is_under: Signal<Local, Bit>,
+    is_over: Signal<Local, Bit>,
+    // Stage 1 - the count for the bin is being read
+    bin_1: DFF<Bits<B>>,
+    valid_1: DFF<Bit>,
+    // Stage 2 - the count for the bin is being written
+    bin_2: DFF<Bits<B>>,
+    count_2: DFF<Bits<C>>,
+    valid_2: DFF<Bit>,
+    count: Signal<Local, Bits<C>>,
+    new_count: Signal<Local, Bits<C>>,
+    total_count: DFF<Bits<C>>,
+    underflow_count: DFF<Bits<C>>,
+    overflow_count: DFF<Bits<C>>,
+    bin_bits: Constant<Bits<8>>,
+}
+
+impl<const S: usize, const B: usize, const C: usize> Default for Histogram<S, B, C> {
+    fn default() -> Self {
+        assert!(B <= S);
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            offset: Default::default(),
+            shift: Default::default(),
+            clear: Default::default(),
+            busy: Default::default(),
+            read_address: Default::default(),
+            read_data: Default::default(),
+            total: Default::default(),
+            underflow: Default::default(),
+            overflow: Default::default(),
+            counts: Default::default(),
+            readout: Default::default(),
+            state: Default::default(),
+            clear_address: Default::default(),
+            delta: Default::default(),
+            is_under: Default::default(),
+            is_over: Default::default(),
+            bin_1: Default::default(),
+            valid_1: Default::default(),
+            bin_2: Default::default(),
+            count_2: Default::default(),
+            valid_2: Default::default(),
+            count: Default::default(),
+            new_count: Default::default(),
+            total_count: Default::default(),
+            underflow_count: Default::default(),
+            overflow_count: Default::default(),
+            bin_bits: Constant::new(B.to_bits()),
+        }
+    }
+}
+
+impl<const S: usize, const B: usize, const C: usize> Logic for Histogram<S, B, C> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            state,
+            clear_address,
+            bin_1,
+            valid_1,
+            bin_2,
+            count_2,
+            valid_2,
+            total_count,
+            underflow_count,
+            overflow_count
+        );
+        self.counts.read_clock.next = self.clock.val();
+        self.counts.write_clock.next = self.clock.val();
+        self.readout.read_clock.next = self.clock.val();
+        self.readout.write_clock.next = self.clock.val();
+        // Find the bin for the incoming sample
+        self.delta.next = (self.data_in.val() - self.offset.val()) >> self.shift.val();
+        self.is_under.next = self.data_in.val() < self.offset.val();
+        self.is_over.next = !self.is_under.val() & (self.delta.val() >> self.bin_bits.val()).any();
+        self.counts.read_address.next = bit_cast::<B, S>(self.delta.val());
+        self.bin_1.d.next = bit_cast::<B, S>(self.delta.val());
+        self.valid_1.d.next = false;
+        // The count read from the RAM is stale if the previous sample went to the same
+        // bin, since that write has not landed yet.
+        self.count.next = self.counts.read_data.val();
+        if self.valid_2.q.val() & (self.bin_2.q.val() == self.bin_1.q.val()) {
+            self.count.next = self.count_2.q.val();
+        }
+        self.new_count.next = self.count.val();
+        if !self.count.val().all() {
+            self.new_count.next = self.count.val() + 1;
+        }
+        self.bin_2.d.next = self.bin_1.q.val();
+        self.count_2.d.next = self.new_count.val();
+        self.valid_2.d.next = self.valid_1.q.val();
+        self.counts.write_address.next = self.bin_1.q.val();
+        self.counts.write_data.next = self.new_count.val();
+        self.counts.write_enable.next = self.valid_1.q.val();
+        self.busy.next = false;
+        match self.state.q.val() {
+            HistogramState::Clearing => {
+                self.busy.next = true;
+                self.counts.write_address.next = self.clear_address.q.val();
+                self.counts.write_data.next = 0.into();
+                self.counts.write_enable.next = true;
+                self.clear_address.d.next = self.clear_address.q.val() + 1;
+                if self.clear_address.q.val().all() {
+                    self.state.d.next = HistogramState::Running;
+                }
+            }
+            HistogramState::Running => {
+                if self.strobe_in.val() {
+                    self.valid_1.d.next = !self.is_under.val() & !self.is_over.val();
+                    if !self.total_count.q.val().all() {
+                        self.total_count.d.next = self.total_count.q.val() + 1;
+                    }
+                    if self.is_under.val() & !self.underflow_count.q.val().all() {
+                        self.underflow_count.d.next = self.underflow_count.q.val() + 1;
+                    }
+                    if self.is_over.val() & !self.overflow_count.q.val().all() {
+                        self.overflow_count.d.next = self.overflow_count.q.val() + 1;
+                    }
+                }
+            }
+            _ => {
+                self.state.d.next = HistogramState::Clearing;
+            }
+        }
+        if self.clear.val() {
+            self.state.d.next = HistogramState::Clearing;
+            self.clear_address.d.next = 0.into();
+            self.valid_1.d.next = false;
+            self.total_count.d.next = 0.into();
+            self.underflow_count.d.next = 0.into();
+            self.overflow_count.d.next = 0.into();
+        }
+        // Both RAMs get the same writes
+        self.readout.write_address.next = self.counts.write_address.val();
+        self.readout.write_data.next = self.counts.write_data.val();
+        self.readout.write_enable.next = self.counts.write_enable.val();
+        self.readout.read_address.next = self.read_address.val();
+        self.read_data.next = self.readout.read_data.val();
+        self.total.next = self.total_count.q.val();
+        self.underflow.next = self.underflow_count.q.val();
+        self.overflow.next = self.overflow_count.q.val();
+    }
+}
+
+#[test]
+fn test_histogram_is_synthesizable() {
+    let mut uut = Histogram::<16, 6, 16>::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("histogram", &vlog).unwrap();
+}
@@ -347,3 +347,42 @@ fn test_i2c_controller_operation() {
     )
     .unwrap()
 }
+
+// The target has to let go of SDA before the controller clocks the ACK
+// of a byte that it read, or the release (with SCL high) looks like a STOP.
+#[test]
+fn test_i2c_target_releases_sda_for_the_ack() {
+    let mut uut = I2CControllerTest::default();
+    uut.clock.connect();
+    uut.controller.cmd.connect();
+    uut.controller.run.connect();
+    uut.controller.write_data_in.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(500_000, |x: &mut Box<I2CControllerTest>| {
+        x.clock.next = !x.clock.val()
+    });
+    sim.add_monitor(
+        crate::i2c::monitor::I2CMonitor::new(),
+        |x: &I2CControllerTest| (&x.test_bus).into(),
+    );
+    sim.add_testbench(move |mut sim: Sim<I2CControllerTest>| {
+        let mut x = sim.init()?;
+        i2c_begin_write!(sim, clock, x, 0x53_u32);
+        i2c_write!(sim, clock, x, 0_u32);
+        i2c_write!(sim, clock, x, 0x80_u32);
+        i2c_write!(sim, clock, x, 0x01_u32);
+        i2c_end_transmission!(sim, clock, x);
+        i2c_begin_write!(sim, clock, x, 0x53_u32);
+        i2c_write!(sim, clock, x, 0_u32);
+        i2c_end_transmission!(sim, clock, x);
+        i2c_begin_read!(sim, clock, x, 0x53_u32);
+        let byte = i2c_read!(sim, clock, x);
+        sim_assert_eq!(sim, byte, 0x80_u8.to_bits::<8>(), x);
+        let byte = i2c_read_last!(sim, clock, x);
+        sim_assert_eq!(sim, byte, 0x01_u8.to_bits::<8>(), x);
+        i2c_end_transmission!(sim, clock, x);
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 20_000_000_000).unwrap()
+}
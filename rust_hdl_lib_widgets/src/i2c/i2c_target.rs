@@ -78,16 +78,19 @@ impl Logic for I2CTarget {
                 }
             }
             State::Writing => {
-                if self.accum.q.val().get_bit(7) {
-                    self.set_sda.next = true;
-                } else {
-                    self.clear_sda.next = true;
-                }
-                self.count.d.next = self.count.q.val() + 1;
-                self.accum.d.next = self.accum.q.val() << 1;
-                self.state.d.next = State::WaitSCLHigh;
                 if self.count.q.val() == 8 {
+                    // Release SDA so the controller can drive the ACK bit
+                    self.set_sda.next = true;
                     self.state.d.next = State::WaitSCLHighAck;
+                } else {
+                    if self.accum.q.val().get_bit(7) {
+                        self.set_sda.next = true;
+                    } else {
+                        self.clear_sda.next = true;
+                    }
+                    self.count.d.next = self.count.q.val() + 1;
+                    self.accum.d.next = self.accum.q.val() << 1;
+                    self.state.d.next = State::WaitSCLHigh;
                 }
             }
             State::WaitSCLHighAck => {
@@ -2,5 +2,6 @@ pub mod i2c_controller;
 pub mod i2c_driver;
 pub mod i2c_target;
 pub mod i2c_test_target;
+pub mod monitor;
 pub mod sim;
 pub mod i2c_bus;
@@ -0,0 +1,211 @@
+use crate::i2c::i2c_test_target::I2CTestBus;
+use rust_hdl_lib_core::prelude::*;
+
+// A passive checker for an I2C bus.  It watches the resolved state of the
+// SDA and SCL lines, and flags
+//   - SCL activity when no START condition has been seen
+//   - a STOP without a matching START
+//   - a START or STOP in the middle of a byte (or its ACK bit)
+//   - SCL low or high times shorter than the optional minimums
+// The checks are armed the first time the bus is seen idle (both lines
+// high), so that the lines settling at power up are not reported.
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct I2CSample {
+    pub sda: bool,
+    pub scl: bool,
+}
+
+impl<const N: usize> From<&I2CTestBus<N>> for I2CSample {
+    fn from(x: &I2CTestBus<N>) -> Self {
+        Self {
+            sda: x.sda_state.val(),
+            scl: x.scl_state.val(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct I2CMonitor {
+    min_clock_low: u64,
+    min_clock_high: u64,
+    previous: Option<I2CSample>,
+    armed: bool,
+    in_transaction: bool,
+    clock_pulses: usize,
+    scl_changed_at: u64,
+}
+
+impl I2CMonitor {
+    pub fn new() -> Self {
+        Default::default()
+    }
+    /// Flag SCL low periods shorter than `time` (in simulation time units).
+    pub fn min_clock_low(self, time: u64) -> Self {
+        Self {
+            min_clock_low: time,
+            ..self
+        }
+    }
+    /// Flag SCL high periods shorter than `time` (in simulation time units).
+    pub fn min_clock_high(self, time: u64) -> Self {
+        Self {
+            min_clock_high: time,
+            ..self
+        }
+    }
+    // Each byte is 8 data bits and an ACK bit.  A START or STOP needs SCL
+    // to rise once more before SDA moves, so a well placed condition sees
+    // one clock pulse more than a multiple of 9.
+    fn on_byte_boundary(&self) -> bool {
+        self.clock_pulses % 9 == 1
+    }
+}
+
+impl Monitor for I2CMonitor {
+    type Sample = I2CSample;
+
+    fn name(&self) -> String {
+        "I2C".into()
+    }
+
+    fn check(&mut self, sample: I2CSample, time: u64) -> Result<(), String> {
+        let prev = match self.previous.replace(sample) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        if !self.armed {
+            if !(prev.sda && prev.scl) {
+                return Ok(());
+            }
+            self.armed = true;
+            self.scl_changed_at = time;
+        }
+        if sample.scl != prev.scl {
+            let width = time - self.scl_changed_at;
+            self.scl_changed_at = time;
+            if !self.in_transaction {
+                if !sample.scl {
+                    return Err("SCL driven low without a START condition".into());
+                }
+                return Ok(());
+            }
+            if prev.scl && width < self.min_clock_high {
+                return Err(format!(
+                    "SCL high for {}, less than the minimum of {}",
+                    width, self.min_clock_high
+                ));
+            }
+            if !prev.scl && width < self.min_clock_low {
+                return Err(format!(
+                    "SCL low for {}, less than the minimum of {}",
+                    width, self.min_clock_low
+                ));
+            }
+            if sample.scl {
+                self.clock_pulses += 1;
+            }
+        } else if sample.scl && sample.sda != prev.sda {
+            if !sample.sda {
+                // START (or repeated START)
+                if self.in_transaction && !self.on_byte_boundary() {
+                    return Err(format!(
+                        "Repeated START in the middle of a byte (bit {})",
+                        self.clock_pulses % 9
+                    ));
+                }
+                self.in_transaction = true;
+                self.clock_pulses = 0;
+            } else {
+                // STOP
+                if !self.in_transaction {
+                    return Err("STOP without a START condition".into());
+                }
+                if !self.on_byte_boundary() {
+                    return Err(format!(
+                        "STOP in the middle of a byte (bit {})",
+                        self.clock_pulses % 9
+                    ));
+                }
+                self.in_transaction = false;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[derive(LogicBlock, Default)]
+struct I2CMonitorBus {
+    sda: Signal<In, Bit>,
+    scl: Signal<In, Bit>,
+}
+
+#[cfg(test)]
+impl Logic for I2CMonitorBus {
+    fn update(&mut self) {}
+}
+
+#[cfg(test)]
+fn run_i2c_monitor(monitor: I2CMonitor, script: &[(bool, bool)]) -> Result<(), SimError> {
+    let script = script.to_vec();
+    let mut uut = I2CMonitorBus::default();
+    uut.sda.connect();
+    uut.scl.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_monitor(monitor, |x: &I2CMonitorBus| I2CSample {
+        sda: x.sda.val(),
+        scl: x.scl.val(),
+    });
+    sim.add_testbench(move |mut sim: Sim<I2CMonitorBus>| {
+        let mut x = sim.init()?;
+        for (sda, scl) in &script {
+            x.sda.next = *sda;
+            x.scl.next = *scl;
+            x = sim.wait(100, x)?;
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000)
+}
+
+#[cfg(test)]
+fn i2c_monitor_byte(byte: u8) -> Vec<(bool, bool)> {
+    // 8 data bits, MSB first, then the ACK (driven low by the target)
+    (0..9)
+        .flat_map(|bit| {
+            let sda = bit < 8 && (byte & (0x80 >> bit)) != 0;
+            [(sda, false), (sda, true), (sda, false)]
+        })
+        .collect()
+}
+
+#[test]
+fn test_i2c_monitor_flags_violations() {
+    let start = vec![(true, true), (true, true), (false, true), (false, false)];
+    let stop = vec![(false, false), (false, true), (true, true)];
+    let good = [start.clone(), i2c_monitor_byte(0xA6), stop.clone()].concat();
+    assert!(run_i2c_monitor(I2CMonitor::new(), &good).is_ok());
+    // STOP after only 4 bits
+    let short = [
+        start.clone(),
+        i2c_monitor_byte(0xA6)[0..12].to_vec(),
+        stop.clone(),
+    ]
+    .concat();
+    assert!(matches!(
+        run_i2c_monitor(I2CMonitor::new(), &short),
+        Err(SimError::ProtocolViolation { .. })
+    ));
+    // Clock with no START
+    assert!(matches!(
+        run_i2c_monitor(I2CMonitor::new(), &[(true, true), (true, false)]),
+        Err(SimError::ProtocolViolation { time: 100, .. })
+    ));
+    // The clock high time is 100 - a minimum of 200 is violated
+    assert!(matches!(
+        run_i2c_monitor(I2CMonitor::new().min_clock_high(200), &good),
+        Err(SimError::ProtocolViolation { .. })
+    ));
+}
@@ -1,24 +1,61 @@
 pub mod accum;
 pub mod auto_reset;
+pub mod biss;
+pub mod bit_ops;
+pub mod clock_gate;
+pub mod clock_mux;
+pub mod code8b10b;
 pub mod delay_line;
 pub mod dff;
+pub mod dff_dual_edge;
+pub mod dff_neg;
+pub mod dff_with_enable;
 pub mod dff_with_init;
+pub mod dvp;
 pub mod edge_detector;
 pub mod edge_ff;
+pub mod fft;
 pub mod fifo;
+pub mod freq_counter;
+pub mod glitch_filter;
+pub mod goertzel;
+pub mod gray;
+pub mod handshake_synchronizer;
+pub mod hdlc;
+pub mod histogram;
 pub mod i2c;
 pub mod mac_fir;
+pub mod majority_voter;
+#[cfg(feature = "mipi")]
+pub mod mipi;
 pub mod open_drain;
+pub mod pipeline;
 pub mod png;
 pub mod prelude;
+pub mod pulse_synchronizer;
 pub mod pulser;
 pub mod pwm;
 pub mod ramrom;
+pub mod rational_strobe;
 pub mod registered_edge_tristate;
+pub mod reset_controller;
+pub mod reset_synchronizer;
+pub mod rtc;
 pub mod sdram;
 pub mod shot;
+pub mod sincos;
+pub mod smoothing;
 pub mod spi;
+pub mod ssi;
+pub mod statistics;
 pub mod strobe;
 pub mod synchronizer;
+pub mod sysmon;
+pub mod tdc;
+pub mod tdm;
+pub mod timestamp;
+pub mod trigger;
 //pub mod test_helpers;
 pub mod tristate;
+pub mod uart;
+pub mod watchdog;
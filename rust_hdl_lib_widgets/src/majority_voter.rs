@@ -0,0 +1,54 @@
+use rust_hdl_lib_core::prelude::*;
+
+/// A [MajorityVoter] is the voting element of triple modular redundancy (TMR).  The same
+/// logic is built three times, and the voter takes the bitwise majority of the three copies,
+/// so that an upset in any one copy (e.g., a flipped register bit) is outvoted by the other
+/// two.  Feeding the voted value back into the state of each copy scrubs the upset on the
+/// next clock.  The voter also reports which copies disagree with the vote, so that the
+/// upsets can be counted or logged.  Note that TMR only protects against an upset in a
+/// single copy - if two copies are upset in the same bit, the vote goes with them, and the
+/// good copy is reported as the faulty one.  The voter is combinational.
+#[derive(LogicBlock, Default)]
+pub struct MajorityVoter<const N: usize> {
+    /// The first copy
+    pub a: Signal<In, Bits<N>>,
+    /// The second copy
+    pub b: Signal<In, Bits<N>>,
+    /// The third copy
+    pub c: Signal<In, Bits<N>>,
+    /// The bitwise majority of the three copies
+    pub data_out: Signal<Out, Bits<N>>,
+    /// Bit `i` is set when copy `i` (a, b, c) disagrees with the vote
+    pub faulty: Signal<Out, Bits<3>>,
+    /// Asserted when the copies do not all agree
+    pub mismatch: Signal<Out, Bit>,
+    vote: Signal<Local, Bits<N>>,
+}
+
+impl<const N: usize> Logic for MajorityVoter<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.vote.next = (self.a.val() & self.b.val())
+            | (self.b.val() & self.c.val())
+            | (self.a.val() & self.c.val());
+        self.faulty.next = 0.into();
+        if self.a.val() != self.vote.val() {
+            self.faulty.next = self.faulty.val().replace_bit(0, true);
+        }
+        if self.b.val() != self.vote.val() {
+            self.faulty.next = self.faulty.val().replace_bit(1, true);
+        }
+        if self.c.val() != self.vote.val() {
+            self.faulty.next = self.faulty.val().replace_bit(2, true);
+        }
+        self.mismatch.next = self.faulty.val().any();
+        self.data_out.next = self.vote.val();
+    }
+}
+
+#[test]
+fn test_majority_voter_is_synthesizable() {
+    let mut uut = MajorityVoter::<8>::default();
+    uut.connect_all();
+    yosys_validate("majority_voter", &generate_verilog(&uut)).unwrap();
+}
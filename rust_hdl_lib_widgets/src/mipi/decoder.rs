@@ -0,0 +1,374 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use rust_hdl_lib_core::prelude::*;
+
+/// The CSI-2 data types understood by the [MIPICSI2Decoder]
+pub const MIPI_DT_FRAME_START: u8 = 0x00;
+pub const MIPI_DT_FRAME_END: u8 = 0x01;
+pub const MIPI_DT_LINE_START: u8 = 0x02;
+pub const MIPI_DT_LINE_END: u8 = 0x03;
+pub const MIPI_DT_RAW8: u8 = 0x2A;
+pub const MIPI_DT_RAW10: u8 = 0x2B;
+
+// The header bits that are covered by each of the 6 bits of the header ECC
+const MIPI_ECC_MASKS: [u32; 6] = [0xF12CB7, 0xF2555B, 0x749A6D, 0xB8E38E, 0xDF03F0, 0xEFFC00];
+
+/// The ECC of a CSI-2 packet header, given the data identifier in the low byte of
+/// `header`, and the word count in the next 2 bytes.
+pub fn mipi_ecc(header: u32) -> u8 {
+    MIPI_ECC_MASKS
+        .iter()
+        .enumerate()
+        .map(|(ndx, mask)| (((header & mask).count_ones() & 1) << ndx) as u8)
+        .fold(0, |a, b| a | b)
+}
+
+/// The checksum of the payload of a CSI-2 long packet (CRC-16 with polynomial `0x8408`,
+/// LSB first, starting from `0xFFFF`).
+pub fn mipi_crc16(payload: &[u8]) -> u16 {
+    let mut crc = 0xFFFF_u16;
+    for byte in payload {
+        for bit in 0..8 {
+            if ((crc ^ (*byte as u16 >> bit)) & 1) != 0 {
+                crc = (crc >> 1) ^ 0x8408;
+            } else {
+                crc >>= 1;
+            }
+        }
+    }
+    crc
+}
+
+/// The bytes of a CSI-2 short packet, in the order they are sent.
+pub fn mipi_short_packet(virtual_channel: u8, data_type: u8, data: u16) -> Vec<u8> {
+    let header =
+        ((virtual_channel as u32 & 3) << 6) | (data_type as u32 & 0x3F) | ((data as u32) << 8);
+    let mut ret = header.to_le_bytes()[0..3].to_vec();
+    ret.push(mipi_ecc(header));
+    ret
+}
+
+/// The bytes of a CSI-2 long packet (header, payload and checksum), in the order they
+/// are sent.
+pub fn mipi_long_packet(virtual_channel: u8, data_type: u8, payload: &[u8]) -> Vec<u8> {
+    assert!(payload.len() < (1 << 16));
+    let mut ret = mipi_short_packet(virtual_channel, data_type, payload.len() as u16);
+    ret.extend_from_slice(payload);
+    ret.extend_from_slice(&mipi_crc16(payload).to_le_bytes());
+    ret
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum CSI2State {
+    Header,
+    Payload,
+    Unpack,
+    CRCLow,
+    CRCHigh,
+    Skip,
+}
+
+/// A [MIPICSI2Decoder] takes the byte stream of a CSI-2 link (e.g., from a
+/// [MIPILaneMerger](super::merger::MIPILaneMerger)), and decodes the packets in it.
+/// Frame start and end short packets are pulsed on `frame_start` and `frame_end`, and
+/// the payload of RAW8 and RAW10 long packets comes out as pixels (one per clock, with
+/// RAW8 pixels in the low 8 bits of `pixel`), with the first pixel of each packet marked
+/// with `start_of_line`.  The payload of other long packets is checked and thrown away.
+///
+/// Headers that fail the ECC check (the single bit errors that the ECC could fix are not
+/// corrected) pulse `header_error`, and long packets with a bad checksum pulse
+/// `crc_error` after their last pixel.  The decoder expects one packet per high speed
+/// burst, so after each packet it ignores the bytes until `active` drops.
+#[derive(LogicBlock)]
+pub struct MIPICSI2Decoder {
+    pub clock: Signal<In, Clock>,
+    /// The next byte of the stream, valid when `valid` is asserted
+    pub data: Signal<In, Bits<8>>,
+    pub valid: Signal<In, Bit>,
+    /// Asserted when the decoder can take a byte
+    pub ready: Signal<Out, Bit>,
+    /// High during a high speed burst
+    pub active: Signal<In, Bit>,
+    /// The pixel, valid for the one clock that `pixel_valid` is asserted
+    pub pixel: Signal<Out, Bits<10>>,
+    pub pixel_valid: Signal<Out, Bit>,
+    /// Set with the first pixel of each line
+    pub start_of_line: Signal<Out, Bit>,
+    pub frame_start: Signal<Out, Bit>,
+    pub frame_end: Signal<Out, Bit>,
+    /// The virtual channel and data type from the last good header
+    pub virtual_channel: Signal<Out, Bits<2>>,
+    pub data_type: Signal<Out, Bits<6>>,
+    pub header_error: Signal<Out, Bit>,
+    pub crc_error: Signal<Out, Bit>,
+    state: DFF<CSI2State>,
+    header: DFF<Bits<32>>,
+    header_next: Signal<Local, Bits<32>>,
+    ecc: Signal<Local, Bits<8>>,
+    count: DFF<Bits<3>>,
+    remaining: DFF<Bits<16>>,
+    crc: DFF<Bits<16>>,
+    crc_next: Signal<Local, Bits<16>>,
+    crc_low: DFF<Bits<8>>,
+    raw8: DFF<Bit>,
+    raw10: DFF<Bit>,
+    upper: DFF<Bits<32>>,
+    lows: DFF<Bits<8>>,
+    line_pending: DFF<Bit>,
+    pixel_flop: DFF<Bits<10>>,
+    pixel_valid_flop: DFF<Bit>,
+    start_of_line_flop: DFF<Bit>,
+    frame_start_flop: DFF<Bit>,
+    frame_end_flop: DFF<Bit>,
+    header_error_flop: DFF<Bit>,
+    crc_error_flop: DFF<Bit>,
+    virtual_channel_flop: DFF<Bits<2>>,
+    data_type_flop: DFF<Bits<6>>,
+    ecc_masks: [Constant<Bits<24>>; 6],
+    polynomial: Constant<Bits<16>>,
+    raw8_type: Constant<Bits<6>>,
+    raw10_type: Constant<Bits<6>>,
+    frame_start_type: Constant<Bits<6>>,
+    frame_end_type: Constant<Bits<6>>,
+}
+
+impl Default for MIPICSI2Decoder {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            data: Default::default(),
+            valid: Default::default(),
+            ready: Default::default(),
+            active: Default::default(),
+            pixel: Default::default(),
+            pixel_valid: Default::default(),
+            start_of_line: Default::default(),
+            frame_start: Default::default(),
+            frame_end: Default::default(),
+            virtual_channel: Default::default(),
+            data_type: Default::default(),
+            header_error: Default::default(),
+            crc_error: Default::default(),
+            state: Default::default(),
+            header: Default::default(),
+            header_next: Default::default(),
+            ecc: Default::default(),
+            count: Default::default(),
+            remaining: Default::default(),
+            crc: Default::default(),
+            crc_next: Default::default(),
+            crc_low: Default::default(),
+            raw8: Default::default(),
+            raw10: Default::default(),
+            upper: Default::default(),
+            lows: Default::default(),
+            line_pending: Default::default(),
+            pixel_flop: Default::default(),
+            pixel_valid_flop: Default::default(),
+            start_of_line_flop: Default::default(),
+            frame_start_flop: Default::default(),
+            frame_end_flop: Default::default(),
+            header_error_flop: Default::default(),
+            crc_error_flop: Default::default(),
+            virtual_channel_flop: Default::default(),
+            data_type_flop: Default::default(),
+            ecc_masks: array_init::array_init(|ndx| {
+                Constant::new((MIPI_ECC_MASKS[ndx] as u64).to_bits())
+            }),
+            polynomial: Constant::new(0x8408.into()),
+            raw8_type: Constant::new(MIPI_DT_RAW8.to_bits()),
+            raw10_type: Constant::new(MIPI_DT_RAW10.to_bits()),
+            frame_start_type: Constant::new(MIPI_DT_FRAME_START.to_bits()),
+            frame_end_type: Constant::new(MIPI_DT_FRAME_END.to_bits()),
+        }
+    }
+}
+
+impl Logic for MIPICSI2Decoder {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            state,
+            header,
+            count,
+            remaining,
+            crc,
+            crc_low,
+            raw8,
+            raw10,
+            upper,
+            lows,
+            line_pending,
+            pixel_flop,
+            pixel_valid_flop,
+            start_of_line_flop,
+            frame_start_flop,
+            frame_end_flop,
+            header_error_flop,
+            crc_error_flop,
+            virtual_channel_flop,
+            data_type_flop
+        );
+        // The header is shifted in from the top, so the first byte ends up in the low byte
+        self.header_next.next =
+            (self.header.q.val() >> 8) | (bit_cast::<32, 8>(self.data.val()) << 24);
+        self.ecc.next = 0.into();
+        for i in 0..6 {
+            self.ecc.next = self.ecc.val().replace_bit(
+                i,
+                (self.header_next.val().get_bits::<24>(0) & self.ecc_masks[i].val()).xor(),
+            );
+        }
+        // The checksum is computed LSB first
+        self.crc_next.next = self.crc.q.val();
+        for i in 0..8 {
+            if self.crc_next.val().get_bit(0) ^ self.data.val().get_bit(i) {
+                self.crc_next.next = (self.crc_next.val() >> 1) ^ self.polynomial.val();
+            } else {
+                self.crc_next.next = self.crc_next.val() >> 1;
+            }
+        }
+        self.ready.next = self.state.q.val() != CSI2State::Unpack;
+        self.pixel.next = self.pixel_flop.q.val();
+        self.pixel_valid.next = self.pixel_valid_flop.q.val();
+        self.start_of_line.next = self.start_of_line_flop.q.val();
+        self.frame_start.next = self.frame_start_flop.q.val();
+        self.frame_end.next = self.frame_end_flop.q.val();
+        self.header_error.next = self.header_error_flop.q.val();
+        self.crc_error.next = self.crc_error_flop.q.val();
+        self.virtual_channel.next = self.virtual_channel_flop.q.val();
+        self.data_type.next = self.data_type_flop.q.val();
+        self.pixel_valid_flop.d.next = false;
+        self.start_of_line_flop.d.next = false;
+        self.frame_start_flop.d.next = false;
+        self.frame_end_flop.d.next = false;
+        self.header_error_flop.d.next = false;
+        self.crc_error_flop.d.next = false;
+        // The RAW10 pixels are unpacked while the input waits, and the
+        // rest of the states move on with each byte
+        if self.state.q.val() == CSI2State::Unpack {
+            self.pixel_flop.d.next = (bit_cast::<10, 8>(self.upper.q.val().get_bits::<8>(0)) << 2)
+                | bit_cast::<10, 2>(self.lows.q.val().get_bits::<2>(0));
+            self.pixel_valid_flop.d.next = true;
+            self.start_of_line_flop.d.next = self.line_pending.q.val();
+            self.line_pending.d.next = false;
+            self.upper.d.next = self.upper.q.val() >> 8;
+            self.lows.d.next = self.lows.q.val() >> 2;
+            self.count.d.next = self.count.q.val() + 1;
+            if self.count.q.val() == 3 {
+                self.count.d.next = 0.into();
+                self.state.d.next = CSI2State::Payload;
+                if !self.remaining.q.val().any() {
+                    self.state.d.next = CSI2State::CRCLow;
+                }
+            }
+        } else if self.valid.val() {
+            match self.state.q.val() {
+                CSI2State::Header => {
+                    self.header.d.next = self.header_next.val();
+                    self.count.d.next = self.count.q.val() + 1;
+                    if self.count.q.val() == 3 {
+                        self.count.d.next = 0.into();
+                        self.remaining.d.next = self.header_next.val().get_bits::<16>(8);
+                        self.crc.d.next = 0xFFFF.into();
+                        self.raw8.d.next =
+                            self.header_next.val().get_bits::<6>(0) == self.raw8_type.val();
+                        self.raw10.d.next =
+                            self.header_next.val().get_bits::<6>(0) == self.raw10_type.val();
+                        self.line_pending.d.next = true;
+                        self.state.d.next = CSI2State::Skip;
+                        if self.ecc.val().get_bits::<6>(0)
+                            != self.header_next.val().get_bits::<6>(24)
+                        {
+                            self.header_error_flop.d.next = true;
+                        } else {
+                            self.virtual_channel_flop.d.next =
+                                self.header_next.val().get_bits::<2>(6);
+                            self.data_type_flop.d.next = self.header_next.val().get_bits::<6>(0);
+                            if self.header_next.val().get_bits::<2>(4).any() {
+                                // A long packet
+                                self.state.d.next = CSI2State::Payload;
+                                if self.header_next.val().get_bits::<16>(8) == 0 {
+                                    self.state.d.next = CSI2State::CRCLow;
+                                }
+                            }
+                            if self.header_next.val().get_bits::<6>(0)
+                                == self.frame_start_type.val()
+                            {
+                                self.frame_start_flop.d.next = true;
+                            }
+                            if self.header_next.val().get_bits::<6>(0) == self.frame_end_type.val()
+                            {
+                                self.frame_end_flop.d.next = true;
+                            }
+                        }
+                    }
+                }
+                CSI2State::Payload => {
+                    self.crc.d.next = self.crc_next.val();
+                    self.remaining.d.next = self.remaining.q.val() - 1;
+                    if self.remaining.q.val() == 1 {
+                        self.state.d.next = CSI2State::CRCLow;
+                    }
+                    if self.raw8.q.val() {
+                        self.pixel_flop.d.next = bit_cast::<10, 8>(self.data.val());
+                        self.pixel_valid_flop.d.next = true;
+                        self.start_of_line_flop.d.next = self.line_pending.q.val();
+                        self.line_pending.d.next = false;
+                    }
+                    if self.raw10.q.val() {
+                        // 4 pixels in 5 bytes - the upper 8 bits of each pixel, and
+                        // then the low 2 bits of all of them
+                        self.count.d.next = self.count.q.val() + 1;
+                        if self.count.q.val() == 4 {
+                            self.count.d.next = 0.into();
+                            self.lows.d.next = self.data.val();
+                            self.state.d.next = CSI2State::Unpack;
+                        } else {
+                            self.upper.d.next = (self.upper.q.val() >> 8)
+                                | (bit_cast::<32, 8>(self.data.val()) << 24);
+                        }
+                    }
+                }
+                CSI2State::CRCLow => {
+                    self.crc_low.d.next = self.data.val();
+                    self.state.d.next = CSI2State::CRCHigh;
+                }
+                CSI2State::CRCHigh => {
+                    if self.data.val().concat(self.crc_low.q.val()) != self.crc.q.val() {
+                        self.crc_error_flop.d.next = true;
+                    }
+                    self.state.d.next = CSI2State::Skip;
+                }
+                _ => {}
+            }
+        }
+        if !self.active.val() {
+            self.state.d.next = CSI2State::Header;
+            self.count.d.next = 0.into();
+        }
+    }
+}
+
+#[test]
+fn test_mipi_ecc_matches_the_spec() {
+    assert_eq!(mipi_ecc(0x01F037), 0x3F);
+}
+
+#[test]
+fn test_mipi_crc_matches_the_spec() {
+    let payload = [
+        0xFF, 0x00, 0x00, 0x02, 0xB9, 0xDC, 0xF3, 0x72, 0xBB, 0xD4, 0xB8, 0x5A, 0xC8, 0x75, 0xC2,
+        0x7C, 0x81, 0xF8, 0x05, 0xDF, 0xFF, 0x00, 0x00, 0x01,
+    ];
+    assert_eq!(mipi_crc16(&payload), 0x00F0);
+}
+
+#[test]
+fn test_mipi_csi2_decoder_is_synthesizable() {
+    let mut uut = MIPICSI2Decoder::default();
+    uut.connect_all();
+    yosys_validate("mipi_csi2_decoder", &generate_verilog(&uut)).unwrap();
+}
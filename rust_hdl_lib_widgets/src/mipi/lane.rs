@@ -0,0 +1,147 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::synchronizer::BitSynchronizer;
+use rust_hdl_lib_core::prelude::*;
+
+/// The byte that each lane sends at the start of a high speed burst, after the run of
+/// zeros that follows the switch from low power mode.
+pub const MIPI_SYNC_BYTE: u8 = 0xB8;
+
+/// A [MIPILaneSampler] recovers the bits of `L` MIPI D-PHY data lanes by oversampling
+/// them (and the clock lane) with `clock`, which is not related to the D-PHY clock.  In
+/// high speed mode the clock lane is a DDR clock, with an edge in the middle of each
+/// bit, so a bit is taken from every data lane on each edge of the clock lane.  The
+/// data lanes are delayed by as many flops as the clock lane synchronizer, so they are
+/// sampled just after the clock lane edge.  This only works for slow links - `clock`
+/// must run at least 3 times faster than the bit rate of a lane.
+///
+/// The high speed receivers are plain LVDS inputs, which cannot see the low power
+/// states of the lanes.  So the start and end of each burst come in on `hs_enable`,
+/// which is expected to come from separate low power receivers on the data lanes (and
+/// is high while the lanes are in high speed mode).
+#[derive(LogicBlock, Default)]
+pub struct MIPILaneSampler<const L: usize> {
+    /// The oversampling clock
+    pub clock: Signal<In, Clock>,
+    /// The clock lane (from an LVDS input)
+    pub clock_lane: Signal<In, Bit>,
+    /// The data lanes (from LVDS inputs), with lane 0 in bit 0
+    pub data_lanes: Signal<In, Bits<L>>,
+    /// High while the data lanes are in high speed mode
+    pub hs_enable: Signal<In, Bit>,
+    /// One bit from each data lane, valid when `strobe` is asserted
+    pub bits: Signal<Out, Bits<L>>,
+    /// Asserted for one clock per bit
+    pub strobe: Signal<Out, Bit>,
+    /// `hs_enable`, synchronized to `clock` (and lined up with the bits)
+    pub active: Signal<Out, Bit>,
+    clock_sync: BitSynchronizer,
+    hs_sync: BitSynchronizer,
+    data_0: DFF<Bits<L>>,
+    data_1: DFF<Bits<L>>,
+    previous: DFF<Bit>,
+}
+
+impl<const L: usize> Logic for MIPILaneSampler<L> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, data_0, data_1, previous);
+        clock!(self, clock, clock_sync, hs_sync);
+        self.clock_sync.sig_in.next = self.clock_lane.val();
+        self.hs_sync.sig_in.next = self.hs_enable.val();
+        self.data_0.d.next = self.data_lanes.val();
+        self.data_1.d.next = self.data_0.q.val();
+        self.previous.d.next = self.clock_sync.sig_out.val();
+        self.bits.next = self.data_1.q.val();
+        self.strobe.next = self.clock_sync.sig_out.val() ^ self.previous.q.val();
+        self.active.next = self.hs_sync.sig_out.val();
+    }
+}
+
+/// A [MIPIByteAligner] finds the byte boundaries in the bits of one data lane.  The bits
+/// of each byte are sent LSB first.  At the start of a burst, the aligner hunts for the
+/// [MIPI_SYNC_BYTE] at every bit offset, and once it has been seen, every 8 bits that
+/// follow are passed on as a byte.  The aligner goes back to hunting when `active` is
+/// deasserted (at the end of the burst).
+#[derive(LogicBlock)]
+pub struct MIPIByteAligner {
+    pub clock: Signal<In, Clock>,
+    /// The next bit from the lane, valid when `strobe` is asserted
+    pub bit: Signal<In, Bit>,
+    pub strobe: Signal<In, Bit>,
+    /// High during a high speed burst
+    pub active: Signal<In, Bit>,
+    /// The byte, valid for the one clock that `valid` is asserted
+    pub data: Signal<Out, Bits<8>>,
+    pub valid: Signal<Out, Bit>,
+    /// High once the sync byte has been found
+    pub synced: Signal<Out, Bit>,
+    shift: DFF<Bits<8>>,
+    shift_next: Signal<Local, Bits<8>>,
+    count: DFF<Bits<3>>,
+    sync_flop: DFF<Bit>,
+    valid_flop: DFF<Bit>,
+    sync_byte: Constant<Bits<8>>,
+}
+
+impl Default for MIPIByteAligner {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            bit: Default::default(),
+            strobe: Default::default(),
+            active: Default::default(),
+            data: Default::default(),
+            valid: Default::default(),
+            synced: Default::default(),
+            shift: Default::default(),
+            shift_next: Default::default(),
+            count: Default::default(),
+            sync_flop: Default::default(),
+            valid_flop: Default::default(),
+            sync_byte: Constant::new(MIPI_SYNC_BYTE.to_bits()),
+        }
+    }
+}
+
+impl Logic for MIPIByteAligner {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, shift, count, sync_flop, valid_flop);
+        self.shift_next.next =
+            (self.shift.q.val() >> 1) | (bit_cast::<8, 1>(self.bit.val().into()) << 7);
+        self.data.next = self.shift.q.val();
+        self.valid.next = self.valid_flop.q.val();
+        self.synced.next = self.sync_flop.q.val();
+        self.valid_flop.d.next = false;
+        if self.strobe.val() {
+            self.shift.d.next = self.shift_next.val();
+            self.count.d.next = self.count.q.val() + 1;
+            if !self.sync_flop.q.val() & (self.shift_next.val() == self.sync_byte.val()) {
+                self.sync_flop.d.next = true;
+                self.count.d.next = 0.into();
+            }
+            if self.sync_flop.q.val() & (self.count.q.val() == 7) {
+                self.valid_flop.d.next = true;
+            }
+        }
+        if !self.active.val() {
+            self.shift.d.next = 0.into();
+            self.sync_flop.d.next = false;
+        }
+    }
+}
+
+#[test]
+fn test_mipi_lane_sampler_is_synthesizable() {
+    let mut uut = MIPILaneSampler::<2>::default();
+    uut.connect_all();
+    yosys_validate("mipi_lane_sampler", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_mipi_byte_aligner_is_synthesizable() {
+    let mut uut = MIPIByteAligner::default();
+    uut.connect_all();
+    yosys_validate("mipi_byte_aligner", &generate_verilog(&uut)).unwrap();
+}
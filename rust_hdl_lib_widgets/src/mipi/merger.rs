@@ -0,0 +1,110 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use rust_hdl_lib_core::prelude::*;
+
+/// A [MIPILaneMerger] puts the bytes from `L` data lanes (up to 4) back into a single
+/// stream.  The bytes of a packet are dealt out to the lanes in turn, starting with
+/// lane 0, and the lanes of a [MIPILaneSampler](super::lane::MIPILaneSampler) run in
+/// step, so a byte comes in from every lane at once.  They are then handed on one at a
+/// time, whenever `ready` is asserted.  If the next set of bytes comes in before the
+/// last set has been taken, the old bytes are lost, and `overflow` is pulsed.  If only
+/// some of the lanes have a byte, `skew_error` is pulsed (and the bytes are dropped).
+#[derive(LogicBlock)]
+pub struct MIPILaneMerger<const L: usize> {
+    pub clock: Signal<In, Clock>,
+    /// The bytes from each lane
+    pub lane_data: [Signal<In, Bits<8>>; L],
+    pub lane_valid: [Signal<In, Bit>; L],
+    /// High during a high speed burst - the merger is emptied when it drops
+    pub active: Signal<In, Bit>,
+    /// The next byte, valid when `valid` is asserted
+    pub data: Signal<Out, Bits<8>>,
+    pub valid: Signal<Out, Bit>,
+    /// Assert to take the byte on `data`
+    pub ready: Signal<In, Bit>,
+    pub overflow: Signal<Out, Bit>,
+    pub skew_error: Signal<Out, Bit>,
+    word: DFF<Bits<32>>,
+    pending: DFF<Bits<3>>,
+    overflow_flop: DFF<Bit>,
+    skew_flop: DFF<Bit>,
+    word_next: Signal<Local, Bits<32>>,
+    all_valid: Signal<Local, Bit>,
+    any_valid: Signal<Local, Bit>,
+    lanes: Constant<Bits<3>>,
+    align: Constant<Bits<8>>,
+}
+
+impl<const L: usize> Default for MIPILaneMerger<L> {
+    fn default() -> Self {
+        assert!((1..=4).contains(&L));
+        Self {
+            clock: Default::default(),
+            lane_data: array_init::array_init(|_| Default::default()),
+            lane_valid: array_init::array_init(|_| Default::default()),
+            active: Default::default(),
+            data: Default::default(),
+            valid: Default::default(),
+            ready: Default::default(),
+            overflow: Default::default(),
+            skew_error: Default::default(),
+            word: Default::default(),
+            pending: Default::default(),
+            overflow_flop: Default::default(),
+            skew_flop: Default::default(),
+            word_next: Default::default(),
+            all_valid: Default::default(),
+            any_valid: Default::default(),
+            lanes: Constant::new(L.to_bits()),
+            align: Constant::new((8 * (4 - L)).to_bits()),
+        }
+    }
+}
+
+impl<const L: usize> Logic for MIPILaneMerger<L> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, word, pending, overflow_flop, skew_flop);
+        // Shift the lanes in from the top, so lane 0 ends up in the low byte
+        self.all_valid.next = true;
+        self.any_valid.next = false;
+        self.word_next.next = 0.into();
+        for i in 0..L {
+            self.all_valid.next = self.all_valid.val() & self.lane_valid[i].val();
+            self.any_valid.next = self.any_valid.val() | self.lane_valid[i].val();
+            self.word_next.next =
+                (self.word_next.val() >> 8) | (bit_cast::<32, 8>(self.lane_data[i].val()) << 24);
+        }
+        self.data.next = bit_cast::<8, 32>(self.word.q.val());
+        self.valid.next = self.pending.q.val().any();
+        self.overflow.next = self.overflow_flop.q.val();
+        self.skew_error.next = self.skew_flop.q.val();
+        self.overflow_flop.d.next = false;
+        self.skew_flop.d.next = false;
+        if self.pending.q.val().any() & self.ready.val() {
+            self.word.d.next = self.word.q.val() >> 8;
+            self.pending.d.next = self.pending.q.val() - 1;
+        }
+        if self.all_valid.val() {
+            if (self.pending.q.val() > 1_u64.to_bits())
+                | ((self.pending.q.val() == 1) & !self.ready.val())
+            {
+                self.overflow_flop.d.next = true;
+            }
+            self.word.d.next = self.word_next.val() >> self.align.val();
+            self.pending.d.next = self.lanes.val();
+        } else if self.any_valid.val() {
+            self.skew_flop.d.next = true;
+        }
+        if !self.active.val() {
+            self.pending.d.next = 0.into();
+        }
+    }
+}
+
+#[test]
+fn test_mipi_lane_merger_is_synthesizable() {
+    let mut uut = MIPILaneMerger::<2>::default();
+    uut.connect_all();
+    yosys_validate("mipi_lane_merger", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,4 @@
+pub mod decoder;
+pub mod lane;
+pub mod merger;
+pub mod receiver;
@@ -0,0 +1,107 @@
+use crate::mipi::decoder::MIPICSI2Decoder;
+use crate::mipi::lane::{MIPIByteAligner, MIPILaneSampler};
+use crate::mipi::merger::MIPILaneMerger;
+use rust_hdl_lib_core::prelude::*;
+
+/// A [MIPICSI2Receiver] is an (experimental) MIPI CSI-2 camera receiver for `L` data
+/// lanes (up to 4), built from a [MIPILaneSampler], a [MIPIByteAligner] for each lane, a
+/// [MIPILaneMerger] and a [MIPICSI2Decoder].  The lanes are oversampled with `clock`, so
+/// only slow links work (`clock` must run at least 3 times faster than the bit rate of
+/// each lane).  On an ECP5, the clock and data lanes go to pins set up as LVDS inputs
+/// (e.g., `IO_TYPE=LVDS` in the LPF), and `hs_enable` comes from low power receivers on
+/// the data lanes (usually a pair of LVCMOS12 inputs, through a resistor network).
+#[derive(LogicBlock)]
+pub struct MIPICSI2Receiver<const L: usize> {
+    pub clock: Signal<In, Clock>,
+    /// The clock lane
+    pub clock_lane: Signal<In, Bit>,
+    /// The data lanes, with lane 0 in bit 0
+    pub data_lanes: Signal<In, Bits<L>>,
+    /// High while the data lanes are in high speed mode
+    pub hs_enable: Signal<In, Bit>,
+    /// The pixels and markers from the [MIPICSI2Decoder]
+    pub pixel: Signal<Out, Bits<10>>,
+    pub pixel_valid: Signal<Out, Bit>,
+    pub start_of_line: Signal<Out, Bit>,
+    pub frame_start: Signal<Out, Bit>,
+    pub frame_end: Signal<Out, Bit>,
+    pub virtual_channel: Signal<Out, Bits<2>>,
+    pub data_type: Signal<Out, Bits<6>>,
+    pub header_error: Signal<Out, Bit>,
+    pub crc_error: Signal<Out, Bit>,
+    /// Pulsed when bytes are lost in the [MIPILaneMerger]
+    pub overflow: Signal<Out, Bit>,
+    /// Pulsed when the lanes do not agree on the byte boundaries
+    pub lane_error: Signal<Out, Bit>,
+    sampler: MIPILaneSampler<L>,
+    aligners: [MIPIByteAligner; L],
+    merger: MIPILaneMerger<L>,
+    decoder: MIPICSI2Decoder,
+}
+
+impl<const L: usize> Default for MIPICSI2Receiver<L> {
+    fn default() -> Self {
+        Self {
+            clock: Default::default(),
+            clock_lane: Default::default(),
+            data_lanes: Default::default(),
+            hs_enable: Default::default(),
+            pixel: Default::default(),
+            pixel_valid: Default::default(),
+            start_of_line: Default::default(),
+            frame_start: Default::default(),
+            frame_end: Default::default(),
+            virtual_channel: Default::default(),
+            data_type: Default::default(),
+            header_error: Default::default(),
+            crc_error: Default::default(),
+            overflow: Default::default(),
+            lane_error: Default::default(),
+            sampler: Default::default(),
+            aligners: array_init::array_init(|_| Default::default()),
+            merger: Default::default(),
+            decoder: Default::default(),
+        }
+    }
+}
+
+impl<const L: usize> Logic for MIPICSI2Receiver<L> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, clock, sampler, merger, decoder);
+        self.sampler.clock_lane.next = self.clock_lane.val();
+        self.sampler.data_lanes.next = self.data_lanes.val();
+        self.sampler.hs_enable.next = self.hs_enable.val();
+        for i in 0..L {
+            self.aligners[i].clock.next = self.clock.val();
+            self.aligners[i].bit.next = self.sampler.bits.val().get_bit(i);
+            self.aligners[i].strobe.next = self.sampler.strobe.val();
+            self.aligners[i].active.next = self.sampler.active.val();
+            self.merger.lane_data[i].next = self.aligners[i].data.val();
+            self.merger.lane_valid[i].next = self.aligners[i].valid.val();
+        }
+        self.merger.active.next = self.sampler.active.val();
+        self.decoder.active.next = self.sampler.active.val();
+        self.decoder.data.next = self.merger.data.val();
+        self.decoder.valid.next = self.merger.valid.val();
+        self.merger.ready.next = self.decoder.ready.val();
+        self.pixel.next = self.decoder.pixel.val();
+        self.pixel_valid.next = self.decoder.pixel_valid.val();
+        self.start_of_line.next = self.decoder.start_of_line.val();
+        self.frame_start.next = self.decoder.frame_start.val();
+        self.frame_end.next = self.decoder.frame_end.val();
+        self.virtual_channel.next = self.decoder.virtual_channel.val();
+        self.data_type.next = self.decoder.data_type.val();
+        self.header_error.next = self.decoder.header_error.val();
+        self.crc_error.next = self.decoder.crc_error.val();
+        self.overflow.next = self.merger.overflow.val();
+        self.lane_error.next = self.merger.skew_error.val();
+    }
+}
+
+#[test]
+fn test_mipi_csi2_receiver_is_synthesizable() {
+    let mut uut = MIPICSI2Receiver::<2>::default();
+    uut.connect_all();
+    yosys_validate("mipi_csi2_receiver", &generate_verilog(&uut)).unwrap();
+}
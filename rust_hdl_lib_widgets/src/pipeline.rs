@@ -0,0 +1,146 @@
+use rust_hdl_lib_core::prelude::*;
+
+/// Which way the synthesis tool is asked to move the registers of a [`Pipeline`]
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default)]
+pub enum Retiming {
+    /// Leave the registers where they are
+    None,
+    /// Move the registers back into the logic that drives the pipeline (e.g., a wide
+    /// multiplier followed by a couple of stages)
+    #[default]
+    Backward,
+    /// Move the registers forward into the logic that the pipeline drives
+    Forward,
+}
+
+/// Pipeline registers
+///
+/// A chain of `N` registers, so that [`data_out`](Self::data_out) follows [`data_in`](Self::data_in)
+/// `N` rising edges of [`clock`](Self::clock) later.  Drop one in after (or before) a slow path, like
+/// a wide multiplier or a long chain of FIFO expanders and reducers, to mark the point where the path
+/// can be broken.  The registers carry retiming attributes, so that the synthesis tools are free to
+/// move them into the neighbouring logic, and balance the path, without it having to be broken up
+/// by hand.  The depth is a generic parameter, so it can be tuned along with the rest of the design,
+/// and a depth of `0` turns the pipeline into a plain wire.
+///
+/// ### Inputs
+///
+/// * [`clock`](Self::clock) The clock for the registers.
+/// * [`data_in`](Self::data_in) The data that enters the pipeline.
+///
+/// ### Outputs
+///
+/// * [`data_out`](Self::data_out) The data that leaves the pipeline, `N` cycles later.
+///
+/// ### Additional info
+///
+/// The attributes are the `retiming_backward`/`retiming_forward` of Vivado.  Shift register
+/// extraction is turned off for the chain, since a shift register can not be retimed.  The open
+/// source flow ignores the attributes, but retimes the whole design when yosys is run with the
+/// `-retime` option of the `synth_*` commands.  Use [`Retiming::None`] to keep the registers where
+/// they are (e.g., for the stages of a pipeline that are already balanced).
+#[derive(Clone, Debug, LogicBlock)]
+pub struct Pipeline<T: Synth, const N: usize> {
+    /// The clock for the registers.
+    pub clock: Signal<In, Clock>,
+    /// The data that enters the pipeline.
+    pub data_in: Signal<In, T>,
+    /// The data that leaves the pipeline, `N` cycles later.
+    pub data_out: Signal<Out, T>,
+    _retiming: Retiming,
+    _stages: Vec<T>,
+}
+
+impl<T: Synth, const N: usize> Pipeline<T, N> {
+    pub fn new(retiming: Retiming) -> Self {
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            data_out: Default::default(),
+            _retiming: retiming,
+            _stages: vec![T::default(); N],
+        }
+    }
+}
+
+impl<T: Synth, const N: usize> Default for Pipeline<T, N> {
+    fn default() -> Self {
+        Self::new(Retiming::default())
+    }
+}
+
+impl<T: Synth, const N: usize> Logic for Pipeline<T, N> {
+    fn update(&mut self) {
+        if N == 0 {
+            self.data_out.next = self.data_in.val();
+            return;
+        }
+        if self.clock.pos_edge() {
+            self._stages.rotate_right(1);
+            self._stages[0] = self.data_in.val();
+        }
+        self.data_out.next = self._stages[N - 1];
+    }
+    fn connect(&mut self) {
+        self.data_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        if N == 0 {
+            return Verilog::Custom("always @(*) data_out = data_in;".into());
+        }
+        let attributes = match self._retiming {
+            Retiming::None => r#"(* shreg_extract = "no" *)"#,
+            Retiming::Backward => r#"(* shreg_extract = "no", retiming_backward = 1 *)"#,
+            Retiming::Forward => r#"(* shreg_extract = "no", retiming_forward = 1 *)"#,
+        };
+        let init = T::default().verilog();
+        let mut declarations = vec![];
+        let mut inits = vec![];
+        let mut shifts = vec![];
+        for i in 0..N {
+            declarations.push(format!(
+                "{} reg [{}:0] stage_{};",
+                attributes,
+                T::BITS - 1,
+                i
+            ));
+            inits.push(format!("   stage_{} = {:x};", i, init));
+            let source = if i == 0 {
+                "data_in".to_string()
+            } else {
+                format!("stage_{}", i - 1)
+            };
+            shifts.push(format!("   stage_{} <= {};", i, source));
+        }
+        Verilog::Custom(format!(
+            "\
+{declarations}
+
+initial begin
+{inits}
+end
+
+always @(posedge clock) begin
+{shifts}
+end
+
+always @(*) data_out = stage_{last};",
+            declarations = declarations.join("\n"),
+            inits = inits.join("\n"),
+            shifts = shifts.join("\n"),
+            last = N - 1
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        if N == 0 {
+            return vec![];
+        }
+        vec![TimingInfo {
+            name: "pipeline".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Rising,
+            inputs: vec!["data_in".into()],
+            outputs: vec!["data_out".into()],
+        }]
+    }
+}
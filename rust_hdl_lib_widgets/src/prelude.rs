@@ -1,13 +1,26 @@
 pub use crate::auto_reset::AutoReset;
+pub use crate::biss::{biss_crc6, BiSSConfig, BiSSMaster, BiSSWiresMaster, BiSSWiresSlave};
+pub use crate::bit_ops::{LeadingZeroCounter, PopulationCount, PriorityEncoder};
+pub use crate::clock_gate::{ClockGate, ClockGateModel};
+pub use crate::clock_mux::{ClockMux, ClockMuxModel};
+pub use crate::code8b10b::comma_aligner::CommaAligner;
+pub use crate::code8b10b::decoder::Decoder8b10b;
+pub use crate::code8b10b::encoder::Encoder8b10b;
+pub use crate::code8b10b::{decode_8b10b, encode_8b10b, K28_1, K28_5, K28_7};
 pub use crate::declare_async_fifo;
 pub use crate::declare_expanding_fifo;
 pub use crate::declare_narrowing_fifo;
 pub use crate::declare_sync_fifo;
 pub use crate::delay_line::DelayLine;
 pub use crate::dff::DFF;
+pub use crate::dff_dual_edge::DualEdgeDFF;
+pub use crate::dff_neg::DFFNeg;
 pub use crate::dff_setup;
+pub use crate::dff_with_enable::DFFWithEnable;
 pub use crate::dff_with_init::DFFWithInit;
+pub use crate::dvp::{BayerDecimator, DVPCapture, DVPWiresCamera, DVPWiresReceiver};
 pub use crate::edge_detector::EdgeDetector;
+pub use crate::fft::{FFT, FFT1024, FFT256};
 pub use crate::fifo::async_fifo::AsynchronousFIFO;
 pub use crate::fifo::cross_fifo::CrossNarrowFIFO;
 pub use crate::fifo::cross_fifo::CrossWidenFIFO;
@@ -17,35 +30,89 @@ pub use crate::fifo::fifo_reducer::FIFOReducer;
 pub use crate::fifo::fifo_reducer_n::FIFOReducerN;
 pub use crate::fifo::fifo_register::RegisterFIFO;
 pub use crate::fifo::sync_fifo::SynchronousFIFO;
+pub use crate::freq_counter::FrequencyCounter;
+pub use crate::glitch_filter::GlitchFilter;
+pub use crate::goertzel::Goertzel;
+pub use crate::gray::{binary_to_gray, gray_to_binary, BinaryToGray, GrayCounter, GrayToBinary};
+pub use crate::handshake_synchronizer::HandshakeSynchronizer;
+pub use crate::hdlc::{
+    CRC16Byte, HDLCDeframer, HDLCFramer, HDLC_CRC_INIT, HDLC_ESCAPE, HDLC_ESCAPE_XOR, HDLC_FLAG,
+};
+pub use crate::histogram::Histogram;
 pub use crate::i2c::i2c_bus::*;
 pub use crate::i2c::i2c_driver::I2CConfig;
 pub use crate::i2c::i2c_target::I2CTarget;
 pub use crate::i2c::i2c_test_target::*;
+pub use crate::i2c::monitor::{I2CMonitor, I2CSample};
 pub use crate::mac_fir::MultiplyAccumulateSymmetricFiniteImpulseResponseFilter;
+pub use crate::majority_voter::MajorityVoter;
+#[cfg(feature = "mipi")]
+pub use crate::mipi::decoder::{
+    mipi_crc16, mipi_ecc, mipi_long_packet, mipi_short_packet, MIPICSI2Decoder, MIPI_DT_FRAME_END,
+    MIPI_DT_FRAME_START, MIPI_DT_LINE_END, MIPI_DT_LINE_START, MIPI_DT_RAW10, MIPI_DT_RAW8,
+};
+#[cfg(feature = "mipi")]
+pub use crate::mipi::lane::{MIPIByteAligner, MIPILaneSampler, MIPI_SYNC_BYTE};
+#[cfg(feature = "mipi")]
+pub use crate::mipi::merger::MIPILaneMerger;
+#[cfg(feature = "mipi")]
+pub use crate::mipi::receiver::MIPICSI2Receiver;
 pub use crate::open_drain::*;
+pub use crate::pipeline::{Pipeline, Retiming};
 pub use crate::png::lfsr::LFSRSimple;
+pub use crate::pulse_synchronizer::PulseSynchronizer;
 pub use crate::pulser::Pulser;
 pub use crate::pwm::PulseWidthModulator;
 pub use crate::ramrom::ram::RAM;
 pub use crate::ramrom::rom::ROM;
 pub use crate::ramrom::sync_rom::SyncROM;
+pub use crate::rational_strobe::RationalStrobe;
+pub use crate::reset_controller::ResetController;
+pub use crate::reset_synchronizer::ResetSynchronizer;
+pub use crate::rtc::RealTimeClock;
 pub use crate::sdram::basic_controller::SDRAMBaseController;
 pub use crate::sdram::buffer::SDRAMOnChipBuffer;
 pub use crate::sdram::burst_controller::SDRAMBurstController;
+pub use crate::sdram::capture::SDRAMCapture;
 pub use crate::sdram::cmd::SDRAMCommand;
 pub use crate::sdram::fifo_sdram::SDRAMFIFOController;
+pub use crate::sdram::monitor::{SDRAMMonitor, SDRAMSample};
 pub use crate::sdram::timings::MemoryTimings;
 pub use crate::sdram::OutputBuffer;
 pub use crate::sdram::SDRAMDriver;
 pub use crate::shot::Shot;
+pub use crate::sincos::{quarter_wave_table, SinCosROM};
+pub use crate::smoothing::{ExponentialSmoother, MovingAverage};
 pub use crate::spi::master::SPIWiresSlave;
 pub use crate::spi::master::{SPIConfig, SPIMaster, SPIWiresMaster};
+pub use crate::spi::master_dynamic_baud::SPIMasterDynamicBaud;
 pub use crate::spi::master_dynamic_mode::{SPIConfigDynamicMode, SPIMasterDynamicMode};
+pub use crate::spi::monitor::{SPIMonitor, SPISample};
 pub use crate::spi::mux::{MuxMasters, MuxSlaves};
+pub use crate::spi::qspi::{
+    QSPIConfig, QSPIWiresMaster, QSPIWiresSlave, QSPIXIPReader, QSPI_FAST_READ_QUAD_IO,
+};
+pub use crate::spi::router::{SPIRouter, SPIRouterTarget};
 pub use crate::spi::slave::SPISlave;
+pub use crate::ssi::{SSIConfig, SSIMaster, SSIWiresMaster, SSIWiresSlave};
+pub use crate::statistics::WindowedStatistics;
 pub use crate::strobe::Strobe;
-pub use crate::synchronizer::{BitSynchronizer, SyncReceiver, SyncSender, VectorSynchronizer};
+pub use crate::synchronizer::{
+    set_metastability_model, synchronizer_mtbf, synchronizer_stages_for_mtbf, BitSynchronizer,
+    FlipFlopCharacteristics, MetastabilityModel, SyncReceiver, SyncSender, VectorSynchronizer,
+};
+pub use crate::sysmon::{temperature_celsius, SystemMonitor, SystemMonitorReader};
+pub use crate::tdc::counter::CounterTDC;
+pub use crate::tdc::fine::FineTDC;
+pub use crate::tdc::interval::IntervalTDC;
+pub use crate::tdm::{
+    TDMConfig, TDMDeserializer, TDMSerializer, TDMWiresReceiver, TDMWiresTransmitter,
+};
+pub use crate::timestamp::TimestampCounter;
+pub use crate::trigger::{LevelTrigger, TriggerSequencer};
 pub use crate::tristate::TristateBuffer;
+pub use crate::uart::{UARTReceiver, UARTTransmitter};
+pub use crate::watchdog::Watchdog;
 pub use crate::{
     i2c_begin_read, i2c_begin_write, i2c_end_transmission, i2c_read, i2c_read_last, i2c_write,
 };
@@ -0,0 +1,64 @@
+use crate::{dff::DFF, dff_setup, synchronizer::BitSynchronizer};
+use rust_hdl_lib_core::prelude::*;
+
+/// A [PulseSynchronizer] moves single clock pulses (strobes) from one clock domain to another.
+/// A strobe cannot be passed through a [BitSynchronizer] directly, since it may be over before
+/// the destination clock ever samples it.  Instead, each pulse flips a toggle flip flop in the
+/// source domain, and the change of the toggle is synchronized into the destination domain,
+/// where it is turned back into a pulse that lasts one cycle of [clock_out].  The toggle is
+/// then synchronized back to the source domain, so that [busy] can tell when the next pulse
+/// can be sent.  This works for any ratio of the two clocks.
+///
+/// Pulses that arrive on [pulse_in] while [busy] is asserted are dropped.  Without
+/// metastability (i.e., in simulation), the latencies are bounded by:
+///  - [pulse_out] fires within 2 periods of [clock_out] of the [clock_in] edge that
+///    sampled [pulse_in].
+///  - [busy] clears within 3 periods of [clock_out] plus 2 periods of [clock_in] of that
+///    same edge.
+///
+/// A synchronizer that is slow to resolve adds up to one period of its clock to each of these.
+#[derive(LogicBlock, Default)]
+pub struct PulseSynchronizer {
+    /// The clock of the source domain
+    pub clock_in: Signal<In, Clock>,
+    /// The pulse to send (it is sampled on each edge of [clock_in])
+    pub pulse_in: Signal<In, Bit>,
+    /// Asserted (in the source domain) while a pulse is on its way
+    pub busy: Signal<Out, Bit>,
+    /// The clock of the destination domain
+    pub clock_out: Signal<In, Clock>,
+    /// A pulse lasting one period of [clock_out] for each pulse sent
+    pub pulse_out: Signal<Out, Bit>,
+    toggle: DFF<Bit>,
+    toggle_sync: BitSynchronizer,
+    seen: DFF<Bit>,
+    seen_sync: BitSynchronizer,
+}
+
+impl Logic for PulseSynchronizer {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock_in, toggle);
+        dff_setup!(self, clock_out, seen);
+        clock!(self, clock_in, seen_sync);
+        clock!(self, clock_out, toggle_sync);
+        // Source side - the toggle flips for each pulse, and the pulse is on its
+        // way until the toggle comes back from the destination side
+        self.seen_sync.sig_in.next = self.seen.q.val();
+        self.busy.next = self.toggle.q.val() != self.seen_sync.sig_out.val();
+        if self.pulse_in.val() & (self.toggle.q.val() == self.seen_sync.sig_out.val()) {
+            self.toggle.d.next = !self.toggle.q.val();
+        }
+        // Destination side - a change of the toggle is a pulse
+        self.toggle_sync.sig_in.next = self.toggle.q.val();
+        self.seen.d.next = self.toggle_sync.sig_out.val();
+        self.pulse_out.next = self.toggle_sync.sig_out.val() != self.seen.q.val();
+    }
+}
+
+#[test]
+fn test_pulse_synchronizer_is_synthesizable() {
+    let mut uut = PulseSynchronizer::default();
+    uut.connect_all();
+    yosys_validate("pulse_sync", &generate_verilog(&uut)).unwrap();
+}
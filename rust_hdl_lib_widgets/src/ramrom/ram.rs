@@ -102,12 +102,14 @@ end
             TimingInfo {
                 name: "ram_read".into(),
                 clock: "read_clock".into(),
+                edge: ClockEdge::Rising,
                 inputs: vec!["read_address".into()],
                 outputs: vec!["read_data".into()],
             },
             TimingInfo {
                 name: "ram_write".into(),
                 clock: "write_clock".into(),
+                edge: ClockEdge::Rising,
                 inputs: vec![
                     "write_address".into(),
                     "write_data".into(),
@@ -117,4 +119,10 @@ end
             },
         ]
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DATA_BITS", D::BITS, "Width of each word"),
+            ModuleParameter::new("N", N, "Number of address bits"),
+        ]
+    }
 }
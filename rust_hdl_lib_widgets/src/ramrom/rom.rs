@@ -69,4 +69,10 @@ endcase
             default = D::default().verilog().to_string()
         ))
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DATA_BITS", D::BITS, "Width of each word"),
+            ModuleParameter::new("N", N, "Number of address bits"),
+        ]
+    }
 }
@@ -72,8 +72,15 @@ end",
         vec![TimingInfo {
             name: "sync_rom".to_string(),
             clock: "clock".to_string(),
+            edge: ClockEdge::Rising,
             inputs: vec!["address".to_string()],
             outputs: vec!["data".to_string()],
         }]
     }
+    fn parameters(&self) -> Vec<ModuleParameter> {
+        vec![
+            ModuleParameter::new("DATA_BITS", D::BITS, "Width of each word"),
+            ModuleParameter::new("N", N, "Number of address bits"),
+        ]
+    }
 }
@@ -0,0 +1,110 @@
+use rust_hdl_lib_core::prelude::*;
+
+use crate::{dff::DFF, dff_setup};
+
+fn gcd(a: u64, b: u64) -> u64 {
+    if b == 0 {
+        a
+    } else {
+        gcd(b, a % b)
+    }
+}
+
+/// A [RationalStrobe] generates a pulse train, with single clock-cycle wide pulses,
+/// at exactly `numerator/denominator` times the rate of the clock.  Unlike
+/// [Strobe](crate::strobe::Strobe), which rounds the interval between pulses to a
+/// whole number of clocks, the
+/// [RationalStrobe] adds `numerator` to an accumulator on each clock, and fires
+/// (subtracting `denominator`) whenever the accumulator reaches `denominator`.  So
+/// every `denominator` clocks there are exactly `numerator` pulses, with no long term
+/// drift, and the interval between pulses only ever varies by one clock.  The
+/// argument [N] sizes the accumulator, and a compile time assert ensures that it
+/// is wide enough to hold twice the (reduced) denominator.
+#[derive(Clone, Debug, LogicBlock)]
+pub struct RationalStrobe<const N: usize> {
+    /// Set this to true to enable the pulse train.
+    pub enable: Signal<In, Bit>,
+    /// This is the strobing signal - it will fire for 1 clock cycle each time a pulse is due.
+    pub strobe: Signal<Out, Bit>,
+    /// The clock that drives the [RationalStrobe].  All signals are synchronous to this clock.
+    pub clock: Signal<In, Clock>,
+    numerator: Constant<Bits<N>>,
+    denominator: Constant<Bits<N>>,
+    accum: DFF<Bits<N>>,
+    sum: Signal<Local, Bits<N>>,
+}
+
+impl<const N: usize> RationalStrobe<N> {
+    /// Generate a [RationalStrobe] that fires `numerator` times every `denominator` clocks.
+    ///
+    /// # Arguments
+    ///
+    /// * `numerator`: The number of pulses per `denominator` clocks.  Must not be zero.
+    /// * `denominator`: The number of clocks.  Must be at least `numerator`.
+    ///
+    /// The ratio is reduced to lowest terms before sizing the accumulator.
+    ///
+    /// returns: RationalStrobe<{ N }>
+    pub fn with_ratio(numerator: u64, denominator: u64) -> Self {
+        assert!(numerator > 0);
+        assert!(numerator <= denominator);
+        let divisor = gcd(numerator, denominator);
+        let numerator = numerator / divisor;
+        let denominator = denominator / divisor;
+        assert!((denominator as u128) < (1_u128 << ((N - 1) as u128)));
+        Self {
+            enable: Signal::default(),
+            strobe: Signal::default(),
+            clock: Signal::default(),
+            numerator: Constant::new(numerator.to_bits()),
+            denominator: Constant::new(denominator.to_bits()),
+            accum: Default::default(),
+            sum: Default::default(),
+        }
+    }
+
+    /// Generate a [RationalStrobe] that fires at exactly `strobe_freq_hz`.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency`: The frequency (in Hz) of the clock signal driving the circuit.
+    /// * `strobe_freq_hz`: The desired frequency in Hz of the output strobe (e.g., a baud
+    ///   rate or audio sample rate).  This must not be more than `frequency`.
+    ///
+    /// returns: RationalStrobe<{ N }>
+    pub fn new(frequency: u64, strobe_freq_hz: u64) -> Self {
+        Self::with_ratio(strobe_freq_hz, frequency)
+    }
+}
+
+impl<const N: usize> Logic for RationalStrobe<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Connect the accumulator clock to my clock
+        dff_setup!(self, clock, accum);
+        self.sum.next = self.accum.q.val() + self.numerator.val();
+        self.strobe.next = false;
+        if self.enable.val() {
+            self.accum.d.next = self.sum.val();
+            if self.sum.val() >= self.denominator.val() {
+                self.accum.d.next = self.sum.val() - self.denominator.val();
+                self.strobe.next = true;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_rational_strobe_is_synthesizable() {
+    let mut uut = RationalStrobe::<32>::new(100_000_000, 115_200);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("rational_strobe", &vlog).unwrap();
+}
+
+#[test]
+#[should_panic]
+fn test_rational_strobe_checks_accumulator_width() {
+    // 115200/100M reduces to 18/15625, which needs 15 bits (with the headroom)
+    let _ = RationalStrobe::<14>::new(100_000_000, 115_200);
+}
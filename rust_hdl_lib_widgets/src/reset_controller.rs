@@ -0,0 +1,152 @@
+use array_init::array_init;
+use rust_hdl_lib_core::prelude::*;
+use std::time::Duration;
+
+use crate::synchronizer::BitSynchronizer;
+use crate::{dff::DFF, dff_setup};
+
+fn duration_to_clocks(frequency: u64, duration: Duration) -> u64 {
+    let duration_femto = duration.as_nanos() as f64 * NANOS_PER_FEMTO;
+    let clock_period_femto = freq_hz_to_period_femto(frequency as f64);
+    (duration_femto / clock_period_femto).floor() as u64
+}
+
+/// A [ResetController] generalizes [AutoReset](crate::auto_reset::AutoReset) into the
+/// reset logic a complete design needs.  It combines several reset sources:
+///
+/// * Power on - the reset is held for the prescribed time after configuration.
+/// * An external button, which is synchronized and debounced, and may be active low.
+/// * A PLL lock indicator - the reset is held while the PLL is not locked.
+/// * A watchdog (e.g., the `expired` output of a [Watchdog](crate::watchdog::Watchdog)).
+///
+/// Once all of the sources have been inactive for the hold time, `reset` (which is
+/// synchronous to `clock`) is released.  The controller also drives a reset for each of
+/// the `D` clock domains in `domain_clocks`.  These are asserted as soon as `reset` is,
+/// but only released after passing through a pair of flip flops in the domain, so that
+/// the release is synchronous to that domain's clock.  `clock` should be a free running
+/// clock (e.g., the board oscillator, rather than a PLL output).  As with
+/// [Watchdog](crate::watchdog::Watchdog), the argument [N] sizes the internal counters,
+/// and the constructor asserts that the times fit in them.
+#[derive(LogicBlock)]
+pub struct ResetController<const D: usize, const N: usize> {
+    /// The free running clock for the controller.
+    pub clock: Signal<In, Clock>,
+    /// The (asynchronous) reset button.
+    pub button: Signal<In, Bit>,
+    /// Tie this high if there is no PLL to wait for.
+    pub pll_locked: Signal<In, Bit>,
+    /// Tie this low if there is no watchdog.
+    pub watchdog_expired: Signal<In, Bit>,
+    /// The reset, synchronous to [clock](Self::clock).
+    pub reset: Signal<Out, Bit>,
+    /// The clocks for each of the domains that need a reset.
+    pub domain_clocks: [Signal<In, Clock>; D],
+    /// The resets for each of the domains, released synchronously to the domain clock.
+    pub domain_resets: [Signal<Out, Bit>; D],
+    button_sync: BitSynchronizer,
+    // Debounced button state (true if pressed)
+    pressed: DFF<Bit>,
+    debounce_counter: DFF<Bits<N>>,
+    hold_counter: DFF<Bits<N>>,
+    domain_sync_0: [DFF<Bit>; D],
+    domain_sync_1: [DFF<Bit>; D],
+    button_level: Signal<Local, Bit>,
+    active_low: Constant<Bit>,
+    debounce: Constant<Bits<N>>,
+    hold: Constant<Bits<N>>,
+}
+
+impl<const D: usize, const N: usize> ResetController<D, N> {
+    /// Generate a [ResetController] widget that can be used in a RustHDL circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency`: The frequency (in Hz) of the clock signal driving the controller.
+    /// * `hold`: How long the reset is held after power on, and after the last reset
+    ///   source goes inactive.
+    /// * `debounce`: How long the button must be stable before a change is accepted.
+    /// * `button_active_low`: Set this if the button reads low when pressed.
+    ///
+    /// returns: ResetController<{ D }, { N }>
+    pub fn new(
+        frequency: u64,
+        hold: Duration,
+        debounce: Duration,
+        button_active_low: bool,
+    ) -> Self {
+        let hold = duration_to_clocks(frequency, hold);
+        let debounce = duration_to_clocks(frequency, debounce);
+        assert!((hold as u128) < (1_u128 << (N as u128)));
+        assert!((debounce as u128) < (1_u128 << (N as u128)));
+        assert!(hold > 1);
+        assert!(debounce > 1);
+        Self {
+            clock: Default::default(),
+            button: Default::default(),
+            pll_locked: Default::default(),
+            watchdog_expired: Default::default(),
+            reset: Default::default(),
+            domain_clocks: array_init(|_| Default::default()),
+            domain_resets: array_init(|_| Default::default()),
+            button_sync: Default::default(),
+            pressed: Default::default(),
+            debounce_counter: Default::default(),
+            hold_counter: Default::default(),
+            domain_sync_0: array_init(|_| Default::default()),
+            domain_sync_1: array_init(|_| Default::default()),
+            button_level: Default::default(),
+            active_low: Constant::new(button_active_low),
+            debounce: Constant::new(debounce.to_bits()),
+            hold: Constant::new(hold.to_bits()),
+        }
+    }
+}
+
+impl<const D: usize, const N: usize> Logic for ResetController<D, N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, pressed, debounce_counter, hold_counter);
+        clock!(self, clock, button_sync);
+        // Debounce the button - a change must persist for the debounce time
+        self.button_sync.sig_in.next = self.button.val();
+        self.button_level.next = self.button_sync.sig_out.val() ^ self.active_low.val();
+        self.debounce_counter.d.next = 0.into();
+        if self.button_level.val() != self.pressed.q.val() {
+            self.debounce_counter.d.next = self.debounce_counter.q.val() + 1;
+            if self.debounce_counter.q.val() == self.debounce.val() {
+                self.pressed.d.next = self.button_level.val();
+                self.debounce_counter.d.next = 0.into();
+            }
+        }
+        // The hold counter starts from zero at power on, and restarts whenever
+        // any of the sources is active.
+        if self.hold_counter.q.val() != self.hold.val() {
+            self.hold_counter.d.next = self.hold_counter.q.val() + 1;
+        }
+        if self.pressed.q.val() | !self.pll_locked.val() | self.watchdog_expired.val() {
+            self.hold_counter.d.next = 0.into();
+        }
+        self.reset.next = self.hold_counter.q.val() != self.hold.val();
+        // Assert each domain reset immediately, and release it in the domain
+        for i in 0..D {
+            self.domain_sync_0[i].clock.next = self.domain_clocks[i].val();
+            self.domain_sync_1[i].clock.next = self.domain_clocks[i].val();
+            self.domain_sync_0[i].d.next = self.reset.val();
+            self.domain_sync_1[i].d.next = self.domain_sync_0[i].q.val();
+            self.domain_resets[i].next = self.reset.val() | self.domain_sync_1[i].q.val();
+        }
+    }
+}
+
+#[test]
+fn test_reset_controller_is_synthesizable() {
+    let mut uut = ResetController::<2, 20>::new(
+        100_000_000,
+        Duration::from_micros(10),
+        Duration::from_millis(1),
+        true,
+    );
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("reset_controller", &vlog).unwrap();
+}
@@ -0,0 +1,108 @@
+use crate::synchronizer::capture_missed;
+use rust_hdl_lib_core::prelude::*;
+
+/// A [ResetSynchronizer] turns an asynchronous reset (like a button, or the reset from a
+/// [ResetController](crate::reset_controller::ResetController)) into a reset for one clock
+/// domain.  The output is asserted as soon as the input is (even if the clock is not
+/// running), but is only released after the release has passed through a chain of `STAGES`
+/// flip flops clocked by the domain's clock.  So every register in the domain leaves reset
+/// on the same clock edge, and none of them can go metastable on the release.
+///
+/// Use one [ResetSynchronizer] per clock domain, and only sample its output in that domain.
+/// [check_reset_domains](rust_hdl_lib_core::check_reset_domains::check_reset_domains) finds
+/// the registers that do not.  The output is asserted at power up, and released `STAGES`
+/// clocks after the input is low.  With
+/// [set_metastability_model](crate::synchronizer::set_metastability_model), the release
+/// may take a clock longer, as it can in hardware.
+#[derive(LogicBlock)]
+pub struct ResetSynchronizer<const STAGES: usize = 2> {
+    /// The clock of the domain to release the reset in
+    pub clock: Signal<In, Clock>,
+    /// The (asynchronous) reset input
+    pub reset_in: Signal<In, Bit>,
+    /// The reset for the domain - asserted with [reset_in](Self::reset_in), and released
+    /// synchronously to [clock](Self::clock)
+    pub reset_out: Signal<Out, Bit>,
+    _stages: Vec<Bit>,
+    _released: Bit,
+}
+
+impl<const STAGES: usize> Default for ResetSynchronizer<STAGES> {
+    fn default() -> Self {
+        assert!(
+            STAGES >= 2,
+            "A reset synchronizer needs at least two stages"
+        );
+        Self {
+            clock: Default::default(),
+            reset_in: Default::default(),
+            reset_out: Default::default(),
+            _stages: vec![true; STAGES],
+            _released: false,
+        }
+    }
+}
+
+impl<const STAGES: usize> Logic for ResetSynchronizer<STAGES> {
+    fn update(&mut self) {
+        if self.reset_in.val() {
+            self._stages.iter_mut().for_each(|x| *x = true);
+            self._released = true;
+        } else if self.clock.pos_edge() {
+            // Only the first edge after the release can be too close to it
+            let missed = self._released && capture_missed();
+            self._released = false;
+            self._stages.rotate_right(1);
+            self._stages[0] = missed;
+        }
+        self.reset_out.next = self._stages[STAGES - 1];
+    }
+    fn connect(&mut self) {
+        self.reset_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+(* ASYNC_REG = \"TRUE\" *) reg [{msb}:0] sync_stages;
+
+initial begin
+   sync_stages = {{{stages}{{1'b1}}}};
+end
+
+always @(posedge clock or posedge reset_in) begin
+   if (reset_in)
+      sync_stages <= {{{stages}{{1'b1}}}};
+   else
+      sync_stages <= {{sync_stages[{shift}:0], 1'b0}};
+end
+
+always @(*) reset_out = sync_stages[{msb}];",
+            msb = STAGES - 1,
+            stages = STAGES,
+            shift = STAGES - 2
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "reset_synchronizer".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Rising,
+            inputs: vec![],
+            outputs: vec!["reset_out".into()],
+        }]
+    }
+    fn resets(&self) -> Vec<ResetInfo> {
+        vec![ResetInfo {
+            clock: "clock".into(),
+            reset_in: "reset_in".into(),
+            reset_out: "reset_out".into(),
+        }]
+    }
+}
+
+#[test]
+fn test_reset_synchronizer_is_synthesizable() {
+    let mut uut: ResetSynchronizer = Default::default();
+    uut.connect_all();
+    yosys_validate("reset_sync", &generate_verilog(&uut)).unwrap();
+}
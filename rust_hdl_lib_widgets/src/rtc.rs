@@ -0,0 +1,81 @@
+use rust_hdl_lib_core::prelude::*;
+
+use crate::{dff::DFF, dff_setup};
+
+/// A [RealTimeClock] keeps the time of day as a count of seconds (e.g., since
+/// the Unix epoch), and the number of clock cycles into the current second.
+/// The seconds can be set (e.g., by the host software) at any time, which
+/// also restarts the current second.  The argument [N] of the generic
+/// [RealTimeClock<N>] sizes the counter of clock cycles, and must be wide enough
+/// to count a full second.
+#[derive(Clone, Debug, LogicBlock)]
+pub struct RealTimeClock<const N: usize> {
+    /// The clock that drives the [RealTimeClock].  All signals are synchronous to this clock.
+    pub clock: Signal<In, Clock>,
+    /// Assert this for one clock cycle to load `set_seconds` into the clock.
+    pub set: Signal<In, Bit>,
+    /// The number of seconds to load when `set` is asserted.
+    pub set_seconds: Signal<In, Bits<32>>,
+    /// The current time, in seconds.
+    pub seconds: Signal<Out, Bits<32>>,
+    /// The number of clock cycles into the current second.
+    pub ticks: Signal<Out, Bits<N>>,
+    /// Fires for one clock cycle at the end of each second (a pulse per second).
+    pub pps: Signal<Out, Bit>,
+    last_tick: Constant<Bits<N>>,
+    second_count: DFF<Bits<32>>,
+    tick_count: DFF<Bits<N>>,
+}
+
+impl<const N: usize> RealTimeClock<N> {
+    /// Generate a [RealTimeClock] widget that can be used in a RustHDL circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency`: The frequency (in Hz) of the clock signal driving the circuit.
+    ///
+    /// returns: RealTimeClock<{ N }>
+    pub fn new(frequency: u64) -> Self {
+        assert!(frequency > 1);
+        assert!(((frequency - 1) as u128) < (1_u128 << (N as u128)));
+        Self {
+            clock: Signal::default(),
+            set: Signal::default(),
+            set_seconds: Signal::default(),
+            seconds: Signal::default(),
+            ticks: Signal::default(),
+            pps: Signal::default(),
+            last_tick: Constant::new((frequency - 1).into()),
+            second_count: Default::default(),
+            tick_count: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize> Logic for RealTimeClock<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, second_count, tick_count);
+        self.pps.next = self.tick_count.q.val() == self.last_tick.val();
+        if self.pps.val() {
+            self.tick_count.d.next = 0.into();
+            self.second_count.d.next = self.second_count.q.val() + 1;
+        } else {
+            self.tick_count.d.next = self.tick_count.q.val() + 1;
+        }
+        if self.set.val() {
+            self.tick_count.d.next = 0.into();
+            self.second_count.d.next = self.set_seconds.val();
+        }
+        self.seconds.next = self.second_count.q.val();
+        self.ticks.next = self.tick_count.q.val();
+    }
+}
+
+#[test]
+fn test_rtc_is_synthesizable() {
+    let mut uut = RealTimeClock::<24>::new(10_000_000);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("rtc", &vlog).unwrap();
+}
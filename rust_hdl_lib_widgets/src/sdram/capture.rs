@@ -0,0 +1,373 @@
+use crate::{
+    dff::DFF,
+    dff_setup,
+    fifo::async_fifo::AsynchronousFIFO,
+    sdram::SDRAMDriver,
+    synchronizer::{BitSynchronizer, VectorSynchronizer},
+};
+use rust_hdl_lib_core::prelude::*;
+
+use super::{burst_controller::SDRAMBurstController, timings::MemoryTimings, OutputBuffer};
+
+#[derive(Copy, Clone, Debug, PartialEq, LogicState)]
+enum State {
+    Idle,
+    Read,
+    Write,
+    Busy,
+}
+
+// The capture engine of a scope or logic analyzer.  The samples on `data_in`
+// (one per `strobe_in`, thinned out to one in every `decimate + 1`) go into
+// the SDRAM, which is used as a ring buffer of 2^A samples, so that it always
+// holds the latest history of the input.  When `trigger` fires, the engine
+// notes the address of the sample (in `trigger_address`), keeps writing for
+// `post_trigger` more samples (and up to the end of the line), and then
+// freezes the buffer.  `done` goes high once the last of the samples is in
+// the SDRAM.  A pulse on `arm` clears the trigger, and starts over.
+//
+// To read out the buffer, set `read_start` and `read_count` (in samples, both
+// multiples of the line size L), and pulse `read_go`.  The samples come out
+// of the FIFO interface (`data_out`, `read` and `empty`), which can be hooked
+// up to a pipe to the host.  Both the addresses and the count wrap around the
+// end of the SDRAM, so the history before the trigger starts at
+// `trigger_address - pre` for a window of `pre` samples.
+//
+// Everything but the SDRAM side is in the `clock` domain.  If the SDRAM
+// can't keep up with the samples, some are dropped (leaving a gap in the
+// capture), and `overflow` stays high until the next arm.  Writing a line
+// takes about L + 16 clocks of the SDRAM, so keep the samples to no more
+// than one in every other SDRAM clock.
+#[derive(LogicBlock)]
+pub struct SDRAMCapture<
+    const R: usize, // Number of rows in the SDRAM
+    const C: usize, // Number of columns in the SDRAM
+    const L: u32,   // Line size (multiple of the SDRAM interface width) - rem(2^C, L) = 0
+    const D: usize, // Number of bits in the SDRAM interface width (and in each sample)
+    const A: usize, // Number of address bits in the SDRAM (should be C + R + B)
+> {
+    pub clock: Signal<In, Clock>,
+    pub sdram: SDRAMDriver<D>,
+    pub ram_clock: Signal<In, Clock>,
+    // Sample interface
+    pub data_in: Signal<In, Bits<D>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub decimate: Signal<In, Bits<16>>,
+    pub trigger: Signal<In, Bit>,
+    // Control and status
+    pub arm: Signal<In, Bit>,
+    pub post_trigger: Signal<In, Bits<A>>,
+    pub armed: Signal<Out, Bit>,
+    pub triggered: Signal<Out, Bit>,
+    pub done: Signal<Out, Bit>,
+    pub overflow: Signal<Out, Bit>,
+    pub trigger_address: Signal<Out, Bits<A>>,
+    // Readout interface
+    pub read_start: Signal<In, Bits<A>>,
+    pub read_count: Signal<In, Bits<A>>,
+    pub read_go: Signal<In, Bit>,
+    pub read_busy: Signal<Out, Bit>,
+    pub data_out: Signal<Out, Bits<D>>,
+    pub read: Signal<In, Bit>,
+    pub empty: Signal<Out, Bit>,
+    controller: SDRAMBurstController<R, C, L, D>,
+    fp: AsynchronousFIFO<Bits<D>, 6, 7, L>,
+    bp: AsynchronousFIFO<Bits<D>, 5, 6, L>,
+    // Sample clock domain
+    decimation: DFF<Bits<16>>,
+    keep: Signal<Local, Bit>,
+    freeze: Signal<Local, Bit>,
+    push: Signal<Local, Bit>,
+    sample_count: DFF<Bits<A>>,
+    line_word: DFF<Bits<A>>,
+    is_frozen: DFF<Bit>,
+    is_triggered: DFF<Bit>,
+    pending: DFF<Bit>,
+    remaining: DFF<Bits<A>>,
+    trigger_at: DFF<Bits<A>>,
+    lost: DFF<Bit>,
+    notify: DFF<Bit>,
+    go_toggle: DFF<Bit>,
+    // The frozen flag goes over to the SDRAM side, along with the sample
+    // count (which holds still while the buffer is frozen)
+    freeze_sync: VectorSynchronizer<Bit>,
+    go_sync: VectorSynchronizer<Bit>,
+    drained_sync: BitSynchronizer,
+    // SDRAM clock domain
+    stop: DFF<Bit>,
+    go_seen: DFF<Bit>,
+    final_pointer: DFF<Bits<A>>,
+    drained: DFF<Bit>,
+    can_write: DFF<Bit>,
+    can_read: DFF<Bit>,
+    read_pointer: DFF<Bits<A>>,
+    read_remaining: DFF<Bits<A>>,
+    write_pointer: DFF<Bits<A>>,
+    state: DFF<State>,
+    line_to_word_ratio: Constant<Bits<A>>,
+    last_word: Constant<Bits<A>>,
+}
+
+impl<const R: usize, const C: usize, const L: u32, const D: usize, const A: usize>
+    SDRAMCapture<R, C, L, D, A>
+{
+    pub fn new(cas_delay: u32, timings: MemoryTimings, buffer: OutputBuffer) -> Self {
+        assert_eq!((1 << C) % L, 0);
+        assert_eq!(A, C + R + 2);
+        assert!(L < 32);
+        Self {
+            clock: Default::default(),
+            sdram: Default::default(),
+            ram_clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            decimate: Default::default(),
+            trigger: Default::default(),
+            arm: Default::default(),
+            post_trigger: Default::default(),
+            armed: Default::default(),
+            triggered: Default::default(),
+            done: Default::default(),
+            overflow: Default::default(),
+            trigger_address: Default::default(),
+            read_start: Default::default(),
+            read_count: Default::default(),
+            read_go: Default::default(),
+            read_busy: Default::default(),
+            data_out: Default::default(),
+            read: Default::default(),
+            empty: Default::default(),
+            controller: SDRAMBurstController::new(cas_delay, timings, buffer),
+            fp: Default::default(),
+            bp: Default::default(),
+            decimation: Default::default(),
+            keep: Default::default(),
+            freeze: Default::default(),
+            push: Default::default(),
+            sample_count: Default::default(),
+            line_word: Default::default(),
+            is_frozen: Default::default(),
+            is_triggered: Default::default(),
+            pending: Default::default(),
+            remaining: Default::default(),
+            trigger_at: Default::default(),
+            lost: Default::default(),
+            notify: Default::default(),
+            go_toggle: Default::default(),
+            freeze_sync: Default::default(),
+            go_sync: Default::default(),
+            drained_sync: Default::default(),
+            stop: Default::default(),
+            go_seen: Default::default(),
+            final_pointer: Default::default(),
+            drained: Default::default(),
+            can_write: Default::default(),
+            can_read: Default::default(),
+            read_pointer: Default::default(),
+            read_remaining: Default::default(),
+            write_pointer: Default::default(),
+            state: Default::default(),
+            line_to_word_ratio: Constant::new(L.to_bits()),
+            last_word: Constant::new((L - 1).to_bits()),
+        }
+    }
+}
+
+impl<const R: usize, const C: usize, const L: u32, const D: usize, const A: usize> Logic
+    for SDRAMCapture<R, C, L, D, A>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        clock!(self, ram_clock, controller);
+        SDRAMDriver::<D>::link(&mut self.sdram, &mut self.controller.sdram);
+        dff_setup!(
+            self,
+            clock,
+            decimation,
+            sample_count,
+            line_word,
+            is_frozen,
+            is_triggered,
+            pending,
+            remaining,
+            trigger_at,
+            lost,
+            notify,
+            go_toggle
+        );
+        dff_setup!(
+            self,
+            ram_clock,
+            stop,
+            go_seen,
+            final_pointer,
+            drained,
+            can_write,
+            can_read,
+            read_pointer,
+            read_remaining,
+            write_pointer,
+            state
+        );
+        // The FP carries samples to the DRAM, and the BP carries the readout back
+        self.fp.write_clock.next = self.clock.val();
+        self.fp.read_clock.next = self.ram_clock.val();
+        self.bp.write_clock.next = self.ram_clock.val();
+        self.bp.read_clock.next = self.clock.val();
+        self.freeze_sync.clock_in.next = self.clock.val();
+        self.freeze_sync.clock_out.next = self.ram_clock.val();
+        self.go_sync.clock_in.next = self.clock.val();
+        self.go_sync.clock_out.next = self.ram_clock.val();
+        self.drained_sync.clock.next = self.clock.val();
+        // Keep one sample in every decimate + 1
+        self.keep.next = false;
+        if self.strobe_in.val() {
+            if self.decimation.q.val() == 0 {
+                self.keep.next = true;
+                self.decimation.d.next = self.decimate.val();
+            } else {
+                self.decimation.d.next = self.decimation.q.val() - 1;
+            }
+        }
+        // A trigger that falls between kept samples is held for the next one
+        if self.trigger.val() {
+            self.pending.d.next = true;
+        }
+        // Freeze once the samples after the trigger are in, and the last
+        // line is full (the DRAM is only written a line at a time)
+        self.freeze.next = self.is_triggered.q.val()
+            & (self.remaining.q.val() == 0)
+            & (self.line_word.q.val() == 0)
+            & !self.is_frozen.q.val();
+        if self.freeze.val() {
+            self.is_frozen.d.next = true;
+            self.notify.d.next = true;
+        }
+        self.fp.data_in.next = self.data_in.val();
+        // A sample that doesn't fit is dropped (and not counted), so that the
+        // sample count keeps in step with the write pointer of the DRAM
+        self.push.next =
+            self.keep.val() & !self.is_frozen.q.val() & !self.freeze.val() & !self.fp.full.val();
+        if self.keep.val() & !self.is_frozen.q.val() & self.fp.full.val() {
+            self.lost.d.next = true;
+        }
+        self.fp.write.next = self.push.val();
+        if self.push.val() {
+            self.sample_count.d.next = self.sample_count.q.val() + 1;
+            self.line_word.d.next = self.line_word.q.val() + 1;
+            if self.line_word.q.val() == self.last_word.val() {
+                self.line_word.d.next = 0.into();
+            }
+            if self.is_triggered.q.val() {
+                if self.remaining.q.val() != 0 {
+                    self.remaining.d.next = self.remaining.q.val() - 1;
+                }
+            } else if self.pending.q.val() | self.trigger.val() {
+                self.is_triggered.d.next = true;
+                self.trigger_at.d.next = self.sample_count.q.val();
+                self.remaining.d.next = self.post_trigger.val();
+                self.pending.d.next = false;
+            }
+        }
+        if self.arm.val() {
+            self.is_frozen.d.next = false;
+            self.is_triggered.d.next = false;
+            self.pending.d.next = false;
+            self.lost.d.next = false;
+            self.notify.d.next = true;
+        }
+        // Tell the DRAM side whenever the frozen flag changes
+        self.freeze_sync.sig_in.next = self.is_frozen.q.val();
+        self.freeze_sync.send.next = false;
+        if self.notify.q.val() & !self.freeze_sync.busy.val() {
+            self.freeze_sync.send.next = true;
+            self.notify.d.next = false;
+        }
+        self.armed.next = !self.is_triggered.q.val() & !self.is_frozen.q.val();
+        self.triggered.next = self.is_triggered.q.val();
+        self.overflow.next = self.lost.q.val();
+        self.trigger_address.next = self.trigger_at.q.val();
+        self.drained_sync.sig_in.next = self.drained.q.val();
+        self.done.next = self.is_frozen.q.val()
+            & !self.notify.q.val()
+            & !self.freeze_sync.busy.val()
+            & self.drained_sync.sig_out.val();
+        // The readout request goes over the same way (with the start and count
+        // held still until it is done).  The synchronizer can deliver a value
+        // more than once, so each request flips a bit, rather than pulsing it.
+        self.go_sync.sig_in.next = self.go_toggle.q.val();
+        self.go_sync.send.next = false;
+        if self.read_go.val() & !self.go_sync.busy.val() {
+            self.go_sync.sig_in.next = !self.go_toggle.q.val();
+            self.go_sync.send.next = true;
+            self.go_toggle.d.next = !self.go_toggle.q.val();
+        }
+        self.read_busy.next = self.go_sync.busy.val();
+        self.data_out.next = self.bp.data_out.val();
+        self.bp.read.next = self.read.val();
+        self.empty.next = self.bp.empty.val();
+        // The DRAM side
+        self.controller.data_in.next = self.fp.data_out.val();
+        self.fp.read.next = self.controller.data_strobe.val();
+        self.bp.data_in.next = self.controller.data_out.val();
+        self.bp.write.next = self.controller.data_valid.val();
+        if self.freeze_sync.update.val() {
+            self.stop.d.next = self.freeze_sync.sig_out.val();
+            self.final_pointer.d.next = self.sample_count.q.val();
+        }
+        if self.go_sync.update.val() & (self.go_sync.sig_out.val() != self.go_seen.q.val()) {
+            self.go_seen.d.next = self.go_sync.sig_out.val();
+            self.read_pointer.d.next = self.read_start.val();
+            self.read_remaining.d.next = self.read_count.val();
+        }
+        self.drained.d.next = self.stop.q.val()
+            & (self.write_pointer.q.val() == self.final_pointer.q.val())
+            & (self.state.q.val() == State::Idle)
+            & !self.freeze_sync.update.val();
+        self.can_write.d.next = !self.fp.almost_empty.val();
+        self.can_read.d.next = (self.read_remaining.q.val() != 0) & !self.bp.almost_full.val();
+        self.controller.cmd_address.next = 0.into();
+        self.controller.write_not_read.next = false;
+        self.controller.cmd_strobe.next = false;
+        match self.state.q.val() {
+            State::Idle => {
+                // Writes come first, so that the samples don't pile up in the FIFO
+                if !self.controller.busy.val() {
+                    if self.can_write.q.val() {
+                        self.state.d.next = State::Write;
+                        self.controller.cmd_address.next =
+                            bit_cast::<32, A>(self.write_pointer.q.val());
+                        self.controller.write_not_read.next = true;
+                        self.controller.cmd_strobe.next = true;
+                    } else if self.can_read.q.val() {
+                        self.state.d.next = State::Read;
+                        self.controller.cmd_address.next =
+                            bit_cast::<32, A>(self.read_pointer.q.val());
+                        self.controller.write_not_read.next = false;
+                        self.controller.cmd_strobe.next = true;
+                    }
+                }
+            }
+            State::Read => {
+                self.read_pointer.d.next =
+                    self.read_pointer.q.val() + self.line_to_word_ratio.val();
+                self.read_remaining.d.next =
+                    self.read_remaining.q.val() - self.line_to_word_ratio.val();
+                self.state.d.next = State::Busy;
+            }
+            State::Write => {
+                self.write_pointer.d.next =
+                    self.write_pointer.q.val() + self.line_to_word_ratio.val();
+                self.state.d.next = State::Busy;
+            }
+            State::Busy => {
+                if !self.controller.busy.val() {
+                    self.state.d.next = State::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = State::Idle;
+            }
+        }
+    }
+}
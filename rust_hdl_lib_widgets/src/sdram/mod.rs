@@ -1,8 +1,10 @@
 pub mod basic_controller;
 pub mod buffer;
 pub mod burst_controller;
+pub mod capture;
 pub mod cmd;
 pub mod fifo_sdram;
+pub mod monitor;
 pub mod timings;
 
 use rust_hdl_lib_core::prelude::*;
@@ -0,0 +1,314 @@
+use crate::sdram::cmd::SDRAMCommand;
+use crate::sdram::timings::MemoryTimings;
+use crate::sdram::{SDRAMDevice, SDRAMDriver};
+use rust_hdl_lib_core::prelude::*;
+
+// A passive checker for the SDRAM command bus.  Commands are decoded at
+// each rising edge of the SDRAM clock, and checked against the bank state
+// and the minimum delays in the MemoryTimings (converted to clocks):
+//   - ACTIVE needs the bank idle, and respects tRP, tRC and tRRD
+//   - READ/WRITE need an open row in the bank, and respect tRCD
+//   - PRECHARGE respects tRAS and tWR for each bank it closes
+//   - AUTO REFRESH and LOAD MODE need all banks idle
+//   - no command may be issued within tRFC of a refresh or tMRD of a
+//     mode register load
+// An auto-precharge (A10 set on a READ/WRITE) is treated as closing the
+// bank at the time of the command.
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SDRAMSample {
+    pub clk: bool,
+    pub cs_not: bool,
+    pub ras_not: bool,
+    pub cas_not: bool,
+    pub we_not: bool,
+    pub bank: usize,
+    pub address: usize,
+}
+
+impl<const D: usize> From<&SDRAMDriver<D>> for SDRAMSample {
+    fn from(x: &SDRAMDriver<D>) -> Self {
+        Self {
+            clk: x.clk.val().clk,
+            cs_not: x.cs_not.val(),
+            ras_not: x.ras_not.val(),
+            cas_not: x.cas_not.val(),
+            we_not: x.we_not.val(),
+            bank: x.bank.val().index(),
+            address: x.address.val().index(),
+        }
+    }
+}
+
+impl<const D: usize> From<&SDRAMDevice<D>> for SDRAMSample {
+    fn from(x: &SDRAMDevice<D>) -> Self {
+        Self {
+            clk: x.clk.val().clk,
+            cs_not: x.cs_not.val(),
+            ras_not: x.ras_not.val(),
+            cas_not: x.cas_not.val(),
+            we_not: x.we_not.val(),
+            bank: x.bank.val().index(),
+            address: x.address.val().index(),
+        }
+    }
+}
+
+impl SDRAMSample {
+    pub fn command(&self) -> SDRAMCommand {
+        if self.cs_not {
+            return SDRAMCommand::NOP;
+        }
+        match (self.ras_not, self.cas_not, self.we_not) {
+            (false, false, false) => SDRAMCommand::LoadModeRegister,
+            (false, false, true) => SDRAMCommand::AutoRefresh,
+            (false, true, false) => SDRAMCommand::Precharge,
+            (false, true, true) => SDRAMCommand::Active,
+            (true, false, false) => SDRAMCommand::Write,
+            (true, false, true) => SDRAMCommand::Read,
+            (true, true, false) => SDRAMCommand::BurstTerminate,
+            (true, true, true) => SDRAMCommand::NOP,
+        }
+    }
+    fn auto_precharge(&self) -> bool {
+        self.address & (1 << 10) != 0
+    }
+}
+
+#[derive(Copy, Clone, Debug, Default)]
+struct BankState {
+    open_row: Option<usize>,
+    activated: Option<u64>,
+    precharged: Option<u64>,
+    written: Option<u64>,
+}
+
+pub struct SDRAMMonitor {
+    timings: MemoryTimings,
+    edges: EdgeTracker<SDRAMSample>,
+    banks: [BankState; 4],
+    activated: Option<u64>,
+    refreshed: Option<u64>,
+    mode_loaded: Option<u64>,
+}
+
+impl SDRAMMonitor {
+    pub fn new(timings: MemoryTimings) -> Self {
+        Self {
+            timings,
+            edges: Default::default(),
+            banks: Default::default(),
+            activated: None,
+            refreshed: None,
+            mode_loaded: None,
+        }
+    }
+}
+
+fn check_delay(name: &str, since: Option<u64>, now: u64, min: u16) -> Result<(), String> {
+    match since {
+        Some(t) if now - t < min as u64 => Err(format!(
+            "{} violated - {} clocks elapsed, {} required",
+            name,
+            now - t,
+            min
+        )),
+        _ => Ok(()),
+    }
+}
+
+impl Monitor for SDRAMMonitor {
+    type Sample = SDRAMSample;
+
+    fn name(&self) -> String {
+        "SDRAM".into()
+    }
+
+    fn check(&mut self, sample: SDRAMSample, _time: u64) -> Result<(), String> {
+        let cycle = match self.edges.update(&sample, |x| x.clk) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let now = self.edges.cycles();
+        let cmd = cycle.command();
+        if matches!(cmd, SDRAMCommand::NOP) {
+            return Ok(());
+        }
+        let t = self.timings;
+        check_delay("tRFC", self.refreshed, now, t.t_rfc())
+            .and_then(|_| {
+                check_delay(
+                    "tMRD",
+                    self.mode_loaded,
+                    now,
+                    t.load_mode_command_timing_clocks as u16,
+                )
+            })
+            .map_err(|e| format!("{:?}: {}", cmd, e))?;
+        let bank = cycle.bank;
+        match cmd {
+            SDRAMCommand::Active => {
+                let state = &mut self.banks[bank];
+                if let Some(row) = state.open_row {
+                    return Err(format!(
+                        "ACTIVE to bank {} while row {} is still open",
+                        bank, row
+                    ));
+                }
+                check_delay("tRP", state.precharged, now, t.t_rp())
+                    .and_then(|_| check_delay("tRC", state.activated, now, t.t_rc()))
+                    .and_then(|_| check_delay("tRRD", self.activated, now, t.t_rrd()))
+                    .map_err(|e| format!("ACTIVE to bank {}: {}", bank, e))?;
+                state.open_row = Some(cycle.address);
+                state.activated = Some(now);
+                self.activated = Some(now);
+            }
+            SDRAMCommand::Read | SDRAMCommand::Write => {
+                let state = &mut self.banks[bank];
+                if state.open_row.is_none() {
+                    return Err(format!("{:?} to bank {} with no open row", cmd, bank));
+                }
+                check_delay("tRCD", state.activated, now, t.t_rcd())
+                    .map_err(|e| format!("{:?} to bank {}: {}", cmd, bank, e))?;
+                if matches!(cmd, SDRAMCommand::Write) {
+                    state.written = Some(now);
+                }
+                if cycle.auto_precharge() {
+                    state.open_row = None;
+                    state.precharged = Some(now);
+                }
+            }
+            SDRAMCommand::Precharge => {
+                let all = cycle.auto_precharge();
+                for (ndx, state) in self.banks.iter_mut().enumerate() {
+                    if !all && ndx != bank {
+                        continue;
+                    }
+                    if state.open_row.is_some() {
+                        check_delay("tRAS", state.activated, now, t.t_ras())
+                            .and_then(|_| check_delay("tWR", state.written, now, t.t_wr()))
+                            .map_err(|e| format!("PRECHARGE of bank {}: {}", ndx, e))?;
+                    }
+                    state.open_row = None;
+                    state.precharged = Some(now);
+                }
+            }
+            SDRAMCommand::AutoRefresh | SDRAMCommand::LoadModeRegister => {
+                for (ndx, state) in self.banks.iter().enumerate() {
+                    if state.open_row.is_some() {
+                        return Err(format!("{:?} while bank {} is open", cmd, ndx));
+                    }
+                    check_delay("tRP", state.precharged, now, t.t_rp())
+                        .map_err(|e| format!("{:?} after precharge of bank {}: {}", cmd, ndx, e))?;
+                }
+                if matches!(cmd, SDRAMCommand::AutoRefresh) {
+                    self.refreshed = Some(now);
+                } else {
+                    self.mode_loaded = Some(now);
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+#[derive(LogicBlock, Default)]
+struct SDRAMMonitorBus {
+    sdram: SDRAMDevice<16>,
+}
+
+#[cfg(test)]
+impl Logic for SDRAMMonitorBus {
+    #[hdl_gen]
+    fn update(&mut self) {
+        self.sdram.read_data.next = 0.into();
+    }
+}
+
+#[cfg(test)]
+fn run_sdram_monitor(script: &[(SDRAMCommand, usize, usize, u32)]) -> Result<(), SimError> {
+    let script = script.to_vec();
+    let mut uut = SDRAMMonitorBus::default();
+    uut.sdram.clk.connect();
+    uut.sdram.we_not.connect();
+    uut.sdram.cas_not.connect();
+    uut.sdram.ras_not.connect();
+    uut.sdram.cs_not.connect();
+    uut.sdram.bank.connect();
+    uut.sdram.address.connect();
+    uut.sdram.write_data.connect();
+    uut.sdram.write_enable.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_clock(5000, |x: &mut Box<SDRAMMonitorBus>| {
+        x.sdram.clk.next = !x.sdram.clk.val()
+    });
+    // At 100 MHz, tRP = tRCD = 2, tRAS = 5, tRC = 7 clocks
+    sim.add_monitor(
+        SDRAMMonitor::new(MemoryTimings::fast_boot_sim(100e6)),
+        |x: &SDRAMMonitorBus| (&x.sdram).into(),
+    );
+    sim.add_testbench(move |mut sim: Sim<SDRAMMonitorBus>| {
+        let mut x = sim.init()?;
+        x.sdram.cs_not.next = true;
+        wait_clock_cycles!(sim, sdram.clk, x, 4);
+        for (cmd, bank, address, delay) in &script {
+            wait_clock_false!(sim, sdram.clk, x);
+            let (ras_not, cas_not, we_not) = match cmd {
+                SDRAMCommand::LoadModeRegister => (false, false, false),
+                SDRAMCommand::AutoRefresh => (false, false, true),
+                SDRAMCommand::Precharge => (false, true, false),
+                SDRAMCommand::Active => (false, true, true),
+                SDRAMCommand::Write => (true, false, false),
+                SDRAMCommand::Read => (true, false, true),
+                SDRAMCommand::BurstTerminate => (true, true, false),
+                SDRAMCommand::NOP => (true, true, true),
+            };
+            x.sdram.cs_not.next = false;
+            x.sdram.ras_not.next = ras_not;
+            x.sdram.cas_not.next = cas_not;
+            x.sdram.we_not.next = we_not;
+            x.sdram.bank.next = (*bank).to_bits();
+            x.sdram.address.next = (*address).to_bits();
+            wait_clock_cycle!(sim, sdram.clk, x);
+            x.sdram.cs_not.next = true;
+            wait_clock_cycles!(sim, sdram.clk, x, *delay);
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 10_000_000)
+}
+
+#[test]
+fn test_sdram_monitor_flags_violations() {
+    use SDRAMCommand::*;
+    let ok = [
+        (Active, 0, 5, 2),
+        (Write, 0, 0, 6),
+        (Precharge, 0, 0, 2),
+        (Active, 0, 6, 1),
+        (Active, 1, 6, 2),
+        (Read, 1, 0, 1),
+    ];
+    assert!(run_sdram_monitor(&ok).is_ok());
+    let violations: [&[(SDRAMCommand, usize, usize, u32)]; 5] = [
+        // tRCD
+        &[(Active, 0, 5, 0), (Read, 0, 0, 1)],
+        // Read with no open row
+        &[(Read, 2, 0, 1)],
+        // tRAS
+        &[(Active, 0, 5, 2), (Precharge, 0, 0, 1)],
+        // tRP
+        &[(Active, 0, 5, 8), (Precharge, 0, 0, 0), (Active, 0, 5, 1)],
+        // Refresh with an open bank
+        &[(Active, 3, 5, 8), (AutoRefresh, 0, 0, 1)],
+    ];
+    for script in violations {
+        assert!(matches!(
+            run_sdram_monitor(script),
+            Err(SimError::ProtocolViolation { .. })
+        ));
+    }
+}
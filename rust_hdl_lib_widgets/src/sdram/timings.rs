@@ -5,6 +5,7 @@ pub struct MemoryTimings {
     pub t_rfc_autorefresh_period_nanoseconds: f64,
     pub load_mode_command_timing_clocks: u32,
     pub t_ras_row_active_min_time_nanoseconds: f64,
+    pub t_ras_row_active_max_time_nanoseconds: f64,
     pub t_rc_row_to_row_min_time_nanoseconds: f64,
     pub t_rcd_row_to_column_min_time_nanoseconds: f64,
     pub t_rrd_bank_to_bank_activate_min_time_nanoseconds: f64,
@@ -24,6 +25,7 @@ impl MemoryTimings {
             t_rfc_autorefresh_period_nanoseconds: 66.0,
             load_mode_command_timing_clocks: 2,
             t_ras_row_active_min_time_nanoseconds: 44.0,
+            t_ras_row_active_max_time_nanoseconds: 120.0e3,
             t_rc_row_to_row_min_time_nanoseconds: 66.0,
             t_rcd_row_to_column_min_time_nanoseconds: 20.0,
             t_rrd_bank_to_bank_activate_min_time_nanoseconds: 15.0,
@@ -42,6 +44,7 @@ impl MemoryTimings {
             t_rfc_autorefresh_period_nanoseconds: 60.0,
             load_mode_command_timing_clocks: 2,
             t_ras_row_active_min_time_nanoseconds: 37.0,
+            t_ras_row_active_max_time_nanoseconds: 100.0e3,
             t_rc_row_to_row_min_time_nanoseconds: 60.0,
             t_rcd_row_to_column_min_time_nanoseconds: 15.0,
             t_rrd_bank_to_bank_activate_min_time_nanoseconds: 14.0,
@@ -60,6 +63,7 @@ impl MemoryTimings {
             t_rfc_autorefresh_period_nanoseconds: 66.0,
             load_mode_command_timing_clocks: 2,
             t_ras_row_active_min_time_nanoseconds: 44.0,
+            t_ras_row_active_max_time_nanoseconds: 120.0e3,
             t_rc_row_to_row_min_time_nanoseconds: 66.0,
             t_rcd_row_to_column_min_time_nanoseconds: 20.0,
             t_rrd_bank_to_bank_activate_min_time_nanoseconds: 15.0,
@@ -89,6 +93,12 @@ impl MemoryTimings {
             self.clock_speed_hz,
         )
     }
+    pub fn t_ras_max(&self) -> u16 {
+        nanos_to_clocks(
+            self.t_ras_row_active_max_time_nanoseconds,
+            self.clock_speed_hz,
+        )
+    }
     pub fn t_rc(&self) -> u16 {
         nanos_to_clocks(
             self.t_rc_row_to_row_min_time_nanoseconds,
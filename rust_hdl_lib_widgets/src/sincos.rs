@@ -0,0 +1,265 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::ramrom::sync_rom::SyncROM;
+use rust_hdl_lib_core::prelude::*;
+
+// The quarter wave table.  Entry `i` holds `sin(2*pi*(i + 1/2)/(4*2^TABLE))`, scaled to
+// the full range of a signed `AMP` bit value.  The half-sample offset makes the table
+// symmetric, so the other three quadrants are obtained by inverting the index and/or
+// negating the value, without needing an extra entry at the end of the quadrant.
+pub fn quarter_wave_table<const TABLE: usize, const AMP: usize>() -> Vec<i64> {
+    let size = 1_usize << TABLE;
+    let amplitude = ((1_u64 << (AMP - 1)) - 1) as f64;
+    (0..size)
+        .map(|i| {
+            let angle = 2.0 * std::f64::consts::PI * (i as f64 + 0.5) / (4 * size) as f64;
+            (angle.sin() * amplitude).round() as i64
+        })
+        .collect()
+}
+
+// Computes the sine and cosine of the phase presented on `phase_in` when `strobe_in` is
+// asserted.  A full turn is `2^PHASE` counts.  The upper `TABLE + 2` bits of the phase
+// select a point on the (quarter wave compressed) table, and the remaining bits are the
+// fraction used by the optional linear interpolation stage.  Without interpolation, the
+// outputs are the table value for the phase rounded to the nearest table point.  The
+// results appear on `sin_out` and `cos_out` with `strobe_out` asserted 3 clocks later.
+// One phase can be presented every clock.
+#[derive(LogicBlock)]
+pub struct SinCosROM<const PHASE: usize, const TABLE: usize, const AMP: usize> {
+    pub clock: Signal<In, Clock>,
+    pub phase_in: Signal<In, Bits<PHASE>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub sin_out: Signal<Out, Signed<AMP>>,
+    pub cos_out: Signal<Out, Signed<AMP>>,
+    pub strobe_out: Signal<Out, Bit>,
+    // One table per point needed (sine and cosine, at the point and the next one)
+    sin_a_rom: SyncROM<Signed<AMP>, TABLE>,
+    sin_b_rom: SyncROM<Signed<AMP>, TABLE>,
+    cos_a_rom: SyncROM<Signed<AMP>, TABLE>,
+    cos_b_rom: SyncROM<Signed<AMP>, TABLE>,
+    // Position on the full wave for each of the points
+    phase: Signal<Local, Bits<PHASE>>,
+    wave_sin_a: Signal<Local, Bits<PHASE>>,
+    wave_sin_b: Signal<Local, Bits<PHASE>>,
+    wave_cos_a: Signal<Local, Bits<PHASE>>,
+    wave_cos_b: Signal<Local, Bits<PHASE>>,
+    // Stage 1 - table lookup
+    negate_sin_a: DFF<Bit>,
+    negate_sin_b: DFF<Bit>,
+    negate_cos_a: DFF<Bit>,
+    negate_cos_b: DFF<Bit>,
+    frac_1: DFF<Bits<16>>,
+    strobe_1: DFF<Bit>,
+    // Stage 2 - sign correction
+    sin_a_val: Signal<Local, Signed<AMP>>,
+    sin_b_val: Signal<Local, Signed<AMP>>,
+    cos_a_val: Signal<Local, Signed<AMP>>,
+    cos_b_val: Signal<Local, Signed<AMP>>,
+    sin_a: DFF<Signed<AMP>>,
+    sin_delta: DFF<Signed<16>>,
+    cos_a: DFF<Signed<AMP>>,
+    cos_delta: DFF<Signed<16>>,
+    frac_2: DFF<Bits<16>>,
+    strobe_2: DFF<Bit>,
+    // Stage 3 - interpolation
+    sin_product: Signal<Local, Signed<32>>,
+    cos_product: Signal<Local, Signed<32>>,
+    sin_reg: DFF<Signed<AMP>>,
+    cos_reg: DFF<Signed<AMP>>,
+    strobe_3: DFF<Bit>,
+    interpolate: Constant<Bit>,
+    frac_bits: Constant<Bits<PHASE>>,
+    frac_mask: Constant<Bits<PHASE>>,
+    half_step: Constant<Bits<PHASE>>,
+    rounding: Constant<Signed<32>>,
+    quarter: Constant<Bits<PHASE>>,
+    half: Constant<Bits<PHASE>>,
+}
+
+impl<const PHASE: usize, const TABLE: usize, const AMP: usize> SinCosROM<PHASE, TABLE, AMP> {
+    pub fn new(interpolate: bool) -> Self {
+        assert!(PHASE >= TABLE + 2);
+        // The interpolation uses a 16x16 multiplier
+        assert!(PHASE - TABLE - 2 <= 15);
+        assert!(AMP <= 16);
+        let frac_bits = PHASE - TABLE - 2;
+        let table = quarter_wave_table::<TABLE, AMP>();
+        let rom = || -> SyncROM<Signed<AMP>, TABLE> {
+            table.iter().map(|x| x.to_signed_bits::<AMP>()).into()
+        };
+        Self {
+            clock: Default::default(),
+            phase_in: Default::default(),
+            strobe_in: Default::default(),
+            sin_out: Default::default(),
+            cos_out: Default::default(),
+            strobe_out: Default::default(),
+            sin_a_rom: rom(),
+            sin_b_rom: rom(),
+            cos_a_rom: rom(),
+            cos_b_rom: rom(),
+            phase: Default::default(),
+            wave_sin_a: Default::default(),
+            wave_sin_b: Default::default(),
+            wave_cos_a: Default::default(),
+            wave_cos_b: Default::default(),
+            negate_sin_a: Default::default(),
+            negate_sin_b: Default::default(),
+            negate_cos_a: Default::default(),
+            negate_cos_b: Default::default(),
+            frac_1: Default::default(),
+            strobe_1: Default::default(),
+            sin_a_val: Default::default(),
+            sin_b_val: Default::default(),
+            cos_a_val: Default::default(),
+            cos_b_val: Default::default(),
+            sin_a: Default::default(),
+            sin_delta: Default::default(),
+            cos_a: Default::default(),
+            cos_delta: Default::default(),
+            frac_2: Default::default(),
+            strobe_2: Default::default(),
+            sin_product: Default::default(),
+            cos_product: Default::default(),
+            sin_reg: Default::default(),
+            cos_reg: Default::default(),
+            strobe_3: Default::default(),
+            interpolate: Constant::new(interpolate),
+            frac_bits: Constant::new(frac_bits.to_bits()),
+            frac_mask: Constant::new(((1_u64 << frac_bits) - 1).to_bits()),
+            half_step: Constant::new(if frac_bits > 0 {
+                (1_u64 << (frac_bits - 1)).to_bits()
+            } else {
+                0.into()
+            }),
+            rounding: Constant::new(if frac_bits > 0 {
+                (1_i64 << (frac_bits - 1)).to_signed_bits()
+            } else {
+                0_i64.to_signed_bits()
+            }),
+            quarter: Constant::new((1_u64 << TABLE).to_bits()),
+            half: Constant::new((2_u64 << TABLE).to_bits()),
+        }
+    }
+}
+
+impl<const PHASE: usize, const TABLE: usize, const AMP: usize> Logic
+    for SinCosROM<PHASE, TABLE, AMP>
+{
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            negate_sin_a,
+            negate_sin_b,
+            negate_cos_a,
+            negate_cos_b,
+            frac_1,
+            strobe_1,
+            sin_a,
+            sin_delta,
+            cos_a,
+            cos_delta,
+            frac_2,
+            strobe_2,
+            sin_reg,
+            cos_reg,
+            strobe_3
+        );
+        clock!(self, clock, sin_a_rom, sin_b_rom, cos_a_rom, cos_b_rom);
+        // When interpolating, the table points sit half a step into each interval,
+        // so move the phase back by half a step to line them up.
+        self.phase.next = self.phase_in.val();
+        if self.interpolate.val() {
+            self.phase.next = self.phase_in.val() - self.half_step.val();
+        }
+        // The cosine is the sine a quarter turn later
+        self.wave_sin_a.next = self.phase.val() >> self.frac_bits.val();
+        self.wave_sin_b.next = self.wave_sin_a.val() + 1;
+        self.wave_cos_a.next = self.wave_sin_a.val() + self.quarter.val();
+        self.wave_cos_b.next = self.wave_sin_b.val() + self.quarter.val();
+        // The second and fourth quadrants run through the table backwards, and the
+        // third and fourth quadrants are negated.
+        self.sin_a_rom.address.next = bit_cast::<TABLE, PHASE>(self.wave_sin_a.val());
+        if (self.wave_sin_a.val() & self.quarter.val()).any() {
+            self.sin_a_rom.address.next = !bit_cast::<TABLE, PHASE>(self.wave_sin_a.val());
+        }
+        self.sin_b_rom.address.next = bit_cast::<TABLE, PHASE>(self.wave_sin_b.val());
+        if (self.wave_sin_b.val() & self.quarter.val()).any() {
+            self.sin_b_rom.address.next = !bit_cast::<TABLE, PHASE>(self.wave_sin_b.val());
+        }
+        self.cos_a_rom.address.next = bit_cast::<TABLE, PHASE>(self.wave_cos_a.val());
+        if (self.wave_cos_a.val() & self.quarter.val()).any() {
+            self.cos_a_rom.address.next = !bit_cast::<TABLE, PHASE>(self.wave_cos_a.val());
+        }
+        self.cos_b_rom.address.next = bit_cast::<TABLE, PHASE>(self.wave_cos_b.val());
+        if (self.wave_cos_b.val() & self.quarter.val()).any() {
+            self.cos_b_rom.address.next = !bit_cast::<TABLE, PHASE>(self.wave_cos_b.val());
+        }
+        self.negate_sin_a.d.next = (self.wave_sin_a.val() & self.half.val()).any();
+        self.negate_sin_b.d.next = (self.wave_sin_b.val() & self.half.val()).any();
+        self.negate_cos_a.d.next = (self.wave_cos_a.val() & self.half.val()).any();
+        self.negate_cos_b.d.next = (self.wave_cos_b.val() & self.half.val()).any();
+        self.frac_1.d.next = bit_cast::<16, PHASE>(self.phase.val() & self.frac_mask.val());
+        self.strobe_1.d.next = self.strobe_in.val();
+        // Stage 2 - apply the signs, and compute the slope to the next point
+        self.sin_a_val.next = self.sin_a_rom.data.val();
+        if self.negate_sin_a.q.val() {
+            self.sin_a_val.next = -self.sin_a_rom.data.val();
+        }
+        self.sin_b_val.next = self.sin_b_rom.data.val();
+        if self.negate_sin_b.q.val() {
+            self.sin_b_val.next = -self.sin_b_rom.data.val();
+        }
+        self.cos_a_val.next = self.cos_a_rom.data.val();
+        if self.negate_cos_a.q.val() {
+            self.cos_a_val.next = -self.cos_a_rom.data.val();
+        }
+        self.cos_b_val.next = self.cos_b_rom.data.val();
+        if self.negate_cos_b.q.val() {
+            self.cos_b_val.next = -self.cos_b_rom.data.val();
+        }
+        self.sin_a.d.next = self.sin_a_val.val();
+        self.cos_a.d.next = self.cos_a_val.val();
+        self.sin_delta.d.next =
+            signed_bit_cast::<16, AMP>(self.sin_b_val.val() - self.sin_a_val.val());
+        self.cos_delta.d.next =
+            signed_bit_cast::<16, AMP>(self.cos_b_val.val() - self.cos_a_val.val());
+        self.frac_2.d.next = self.frac_1.q.val();
+        self.strobe_2.d.next = self.strobe_1.q.val();
+        // Stage 3 - linear interpolation between the points
+        // (rounded to the nearest output value)
+        self.sin_product.next =
+            self.sin_delta.q.val() * signed_cast(self.frac_2.q.val()) + self.rounding.val();
+        self.cos_product.next =
+            self.cos_delta.q.val() * signed_cast(self.frac_2.q.val()) + self.rounding.val();
+        self.sin_reg.d.next = self.sin_a.q.val();
+        self.cos_reg.d.next = self.cos_a.q.val();
+        if self.interpolate.val() {
+            self.sin_reg.d.next = self.sin_a.q.val()
+                + self
+                    .sin_product
+                    .val()
+                    .get_bits::<AMP>(self.frac_bits.val().index());
+            self.cos_reg.d.next = self.cos_a.q.val()
+                + self
+                    .cos_product
+                    .val()
+                    .get_bits::<AMP>(self.frac_bits.val().index());
+        }
+        self.strobe_3.d.next = self.strobe_2.q.val();
+        self.sin_out.next = self.sin_reg.q.val();
+        self.cos_out.next = self.cos_reg.q.val();
+        self.strobe_out.next = self.strobe_3.q.val();
+    }
+}
+
+#[test]
+fn test_sin_cos_rom_is_synthesizable() {
+    let mut uut = SinCosROM::<12, 8, 12>::new(true);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("sincos", &vlog).unwrap();
+}
@@ -0,0 +1,169 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::ramrom::ram::RAM;
+use rust_hdl_lib_core::prelude::*;
+
+// A boxcar (moving average) filter over the last `2^L` samples presented on `data_in`
+// (when `strobe_in` is asserted).  The running sum is held in `W` bits, which must
+// be at least `D + L` so that it cannot overflow.  Each new sample is added to the sum
+// and the sample that falls out of the window (held in a RAM) is subtracted.  The
+// average appears on `data_out` with `strobe_out` asserted one clock later.  When
+// `round` is set, the average is rounded to the nearest value (halves are rounded up),
+// otherwise it is truncated (towards negative infinity).  The window starts out
+// full of zeros.  One sample can be accepted every clock.
+#[derive(LogicBlock)]
+pub struct MovingAverage<const D: usize, const L: usize, const W: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Signed<D>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub data_out: Signal<Out, Signed<D>>,
+    pub strobe_out: Signal<Out, Bit>,
+    window: RAM<Signed<D>, L>,
+    // Where the next sample goes (and the oldest sample comes from)
+    head: DFF<Bits<L>>,
+    sum: DFF<Signed<W>>,
+    strobe: DFF<Bit>,
+    rounded: Signal<Local, Signed<W>>,
+    rounding: Constant<Signed<W>>,
+    length_bits: Constant<Bits<8>>,
+}
+
+impl<const D: usize, const L: usize, const W: usize> MovingAverage<D, L, W> {
+    pub fn new(round: bool) -> Self {
+        assert!(L >= 1);
+        assert!(W >= D + L);
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            data_out: Default::default(),
+            strobe_out: Default::default(),
+            window: std::iter::repeat_n(Signed::<D>::default(), 1 << L).into(),
+            head: Default::default(),
+            sum: Default::default(),
+            strobe: Default::default(),
+            rounded: Default::default(),
+            rounding: Constant::new(if round {
+                (1_i64 << (L - 1)).to_signed_bits()
+            } else {
+                0_i64.to_signed_bits()
+            }),
+            length_bits: Constant::new(L.to_bits()),
+        }
+    }
+}
+
+impl<const D: usize, const L: usize, const W: usize> Logic for MovingAverage<D, L, W> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, head, sum, strobe);
+        self.window.read_clock.next = self.clock.val();
+        self.window.write_clock.next = self.clock.val();
+        // Reading ahead means the oldest sample is ready when the next one arrives,
+        // even if they are back to back.
+        self.window.read_address.next = self.head.d.val();
+        self.window.write_address.next = self.head.q.val();
+        self.window.write_data.next = self.data_in.val();
+        self.window.write_enable.next = self.strobe_in.val();
+        if self.strobe_in.val() {
+            self.head.d.next = self.head.q.val() + 1;
+            self.sum.d.next = self.sum.q.val() + signed_bit_cast::<W, D>(self.data_in.val())
+                - signed_bit_cast::<W, D>(self.window.read_data.val());
+        }
+        self.strobe.d.next = self.strobe_in.val();
+        self.rounded.next = self.sum.q.val() + self.rounding.val();
+        self.data_out.next = self
+            .rounded
+            .val()
+            .get_bits::<D>(self.length_bits.val().index());
+        self.strobe_out.next = self.strobe.q.val();
+    }
+}
+
+// A single pole IIR (exponential smoothing) filter, which computes
+// `y <- y + (x - y)/2^K` for each sample `x` presented on `data_in` (when `strobe_in`
+// is asserted).  The state carries `K` fraction bits, and is held in `W` bits, which
+// must be at least `D + K`.  The filtered value appears on `data_out` with `strobe_out`
+// asserted one clock later.  When `round` is set, the output is rounded to the nearest
+// value (halves are rounded up), otherwise it is truncated (towards negative infinity).
+// The time constant is roughly `2^K` samples.  One sample can be accepted every clock.
+#[derive(LogicBlock)]
+pub struct ExponentialSmoother<const D: usize, const K: usize, const W: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Signed<D>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub data_out: Signal<Out, Signed<D>>,
+    pub strobe_out: Signal<Out, Bit>,
+    state: DFF<Signed<W>>,
+    strobe: DFF<Bit>,
+    // The state without the fraction bits
+    current: Signal<Local, Signed<D>>,
+    rounded: Signal<Local, Signed<W>>,
+    rounding: Constant<Signed<W>>,
+    frac_bits: Constant<Bits<8>>,
+}
+
+impl<const D: usize, const K: usize, const W: usize> ExponentialSmoother<D, K, W> {
+    pub fn new(round: bool) -> Self {
+        assert!(K >= 1);
+        assert!(W >= D + K);
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            data_out: Default::default(),
+            strobe_out: Default::default(),
+            state: Default::default(),
+            strobe: Default::default(),
+            current: Default::default(),
+            rounded: Default::default(),
+            rounding: Constant::new(if round {
+                (1_i64 << (K - 1)).to_signed_bits()
+            } else {
+                0_i64.to_signed_bits()
+            }),
+            frac_bits: Constant::new(K.to_bits()),
+        }
+    }
+}
+
+impl<const D: usize, const K: usize, const W: usize> Logic for ExponentialSmoother<D, K, W> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state, strobe);
+        self.current.next = self
+            .state
+            .q
+            .val()
+            .get_bits::<D>(self.frac_bits.val().index());
+        // The difference is added at the fraction point, so the state stays within
+        // 2^K times the range of the input.
+        if self.strobe_in.val() {
+            self.state.d.next = self.state.q.val() + signed_bit_cast::<W, D>(self.data_in.val())
+                - signed_bit_cast::<W, D>(self.current.val());
+        }
+        self.strobe.d.next = self.strobe_in.val();
+        self.rounded.next = self.state.q.val() + self.rounding.val();
+        self.data_out.next = self
+            .rounded
+            .val()
+            .get_bits::<D>(self.frac_bits.val().index());
+        self.strobe_out.next = self.strobe.q.val();
+    }
+}
+
+#[test]
+fn test_moving_average_is_synthesizable() {
+    let mut uut = MovingAverage::<16, 4, 20>::new(true);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("moving_average", &vlog).unwrap();
+}
+
+#[test]
+fn test_exponential_smoother_is_synthesizable() {
+    let mut uut = ExponentialSmoother::<16, 4, 20>::new(true);
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("exponential_smoother", &vlog).unwrap();
+}
@@ -0,0 +1,228 @@
+use crate::spi::master::{SPIConfig, SPIWiresMaster};
+use crate::{dff::DFF, dff_setup, dff_with_init::DFFWithInit};
+use rust_hdl_lib_core::prelude::*;
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum SPIState {
+    Idle,
+    Dwell,
+    LoadBit,
+    MActive,
+    SampleMISO,
+    MIdle,
+    Finish,
+}
+
+// The same as an SPIMaster, but with a `divisor` input that sets the speed at
+// run time, so one master can serve peripherals that run at different speeds.
+// A divisor of D runs the master as if the SPIConfig had a `speed_hz` of
+// `clock_speed / D`, and a divisor of 0 keeps the speed from the SPIConfig.
+// The divisor is rounded down to a multiple of 4 (and is at least 8), and it
+// is only taken up while the master is idle, so that a transfer in flight
+// always runs at one speed.
+#[derive(LogicBlock)]
+pub struct SPIMasterDynamicBaud<const N: usize> {
+    pub clock: Signal<In, Clock>,
+    pub bits_outbound: Signal<In, Bits<16>>,
+    pub data_outbound: Signal<In, Bits<N>>,
+    pub data_inbound: Signal<Out, Bits<N>>,
+    pub start_send: Signal<In, Bit>,
+    pub transfer_done: Signal<Out, Bit>,
+    pub continued_transaction: Signal<In, Bit>,
+    pub divisor: Signal<In, Bits<16>>,
+    pub busy: Signal<Out, Bit>,
+    pub wires: SPIWiresMaster,
+    register_out: DFF<Bits<N>>,
+    register_in: DFF<Bits<N>>,
+    state: DFF<SPIState>,
+    baud_counter: DFF<Bits<16>>,
+    quarter: DFFWithInit<Bits<16>>,
+    quarter_next: Signal<Local, Bits<16>>,
+    strobe: Signal<Local, Bit>,
+    pointer: DFF<Bits<16>>,
+    pointerm1: Signal<Local, Bits<16>>,
+    clock_state: DFF<Bit>,
+    done_flop: DFF<Bit>,
+    msel_flop: DFFWithInit<Bit>,
+    mosi_flop: DFF<Bit>,
+    continued_save: DFF<Bit>,
+    default_quarter: Constant<Bits<16>>,
+    cs_off: Constant<Bit>,
+    mosi_off: Constant<Bit>,
+    cpha: Constant<Bit>,
+    cpol: Constant<Bit>,
+}
+
+impl<const N: usize> SPIMasterDynamicBaud<N> {
+    pub fn new(config: SPIConfig) -> Self {
+        assert!(8 * config.speed_hz <= config.clock_speed);
+        let quarter = (config.clock_speed as f64 / (4.0 * config.speed_hz as f64)).round() as u64;
+        assert!(quarter < (1 << 16));
+        Self {
+            clock: Default::default(),
+            bits_outbound: Default::default(),
+            data_outbound: Default::default(),
+            data_inbound: Default::default(),
+            start_send: Default::default(),
+            transfer_done: Default::default(),
+            continued_transaction: Default::default(),
+            divisor: Default::default(),
+            busy: Default::default(),
+            wires: Default::default(),
+            register_out: Default::default(),
+            register_in: Default::default(),
+            state: Default::default(),
+            baud_counter: Default::default(),
+            quarter: DFFWithInit::new(quarter.to_bits()),
+            quarter_next: Default::default(),
+            strobe: Default::default(),
+            pointer: Default::default(),
+            pointerm1: Default::default(),
+            clock_state: Default::default(),
+            done_flop: Default::default(),
+            msel_flop: DFFWithInit::new(config.cs_off),
+            mosi_flop: Default::default(),
+            continued_save: Default::default(),
+            default_quarter: Constant::new(quarter.to_bits()),
+            cs_off: Constant::new(config.cs_off),
+            mosi_off: Constant::new(config.mosi_off),
+            cpha: Constant::new(config.cpha),
+            cpol: Constant::new(config.cpol),
+        }
+    }
+}
+
+impl<const N: usize> Logic for SPIMasterDynamicBaud<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        // Setup the internals
+        dff_setup!(
+            self,
+            clock,
+            register_out,
+            register_in,
+            state,
+            baud_counter,
+            quarter,
+            pointer,
+            clock_state,
+            done_flop,
+            msel_flop,
+            mosi_flop,
+            continued_save
+        );
+        // The baud strobe fires every quarter of the divisor.  The compare is not an
+        // equality, so that the counter cannot run past a shorter period.
+        self.baud_counter.d.next = self.baud_counter.q.val() + 1;
+        self.strobe.next = self.baud_counter.q.val() >= self.quarter.q.val();
+        if self.strobe.val() {
+            self.baud_counter.d.next = 1.into();
+        }
+        // The period requested by the divisor input
+        self.quarter_next.next = self.default_quarter.val();
+        if self.divisor.val().any() {
+            self.quarter_next.next = self.divisor.val() >> 2;
+        }
+        if self.quarter_next.val() < 2_u64.to_bits() {
+            self.quarter_next.next = 2.into();
+        }
+        // Connect the rest of the SPI lines to the flops
+        self.wires.mclk.next = self.clock_state.q.val();
+        self.wires.mosi.next = self.mosi_flop.q.val();
+        self.wires.msel.next = self.msel_flop.q.val();
+        // Connect the output signals to the internal registers
+        self.data_inbound.next = self.register_in.q.val();
+        self.transfer_done.next = self.done_flop.q.val();
+        self.done_flop.d.next = false;
+        self.pointerm1.next = self.pointer.q.val() - 1;
+        self.busy.next = true;
+        // The main state machine
+        match self.state.q.val() {
+            SPIState::Idle => {
+                self.busy.next = false;
+                // Only take up a new speed between transfers
+                self.quarter.d.next = self.quarter_next.val();
+                self.clock_state.d.next = self.cpol.val();
+                if self.start_send.val() {
+                    // Capture the outgoing data in our register
+                    self.register_out.d.next = self.data_outbound.val();
+                    self.state.d.next = SPIState::Dwell; // Transition to the DWELL state
+                    self.pointer.d.next = self.bits_outbound.val(); // set bit pointer to number of bit to send (1 based)
+                    self.register_in.d.next = 0.into(); // Clear out the input store register
+                    self.msel_flop.d.next = !self.cs_off.val(); // Activate the chip select
+                    self.continued_save.d.next = self.continued_transaction.val();
+                    self.baud_counter.d.next = 1.into(); // Start the dwell with a full period
+                } else if !self.continued_save.q.val() {
+                    self.msel_flop.d.next = self.cs_off.val(); // Set the chip select signal to be "off"
+                }
+                self.mosi_flop.d.next = self.mosi_off.val(); // Set the mosi signal to be "off"
+            }
+            SPIState::Dwell => {
+                if self.strobe.val() {
+                    // Dwell timeout has reached zero
+                    self.state.d.next = SPIState::LoadBit; // Transition to the loadbit state
+                }
+            }
+            SPIState::LoadBit => {
+                if self.pointer.q.val().any() {
+                    // We have data to send
+                    self.mosi_flop.d.next = self
+                        .register_out
+                        .q
+                        .val()
+                        .get_bit(self.pointerm1.val().index()); // Fetch the corresponding bit out of the register
+                    self.pointer.d.next = self.pointerm1.val(); // Decrement the pointer
+                    self.state.d.next = SPIState::MActive; // Move to the hold mclock low state
+                    self.clock_state.d.next = self.cpol.val() ^ self.cpha.val();
+                } else {
+                    self.mosi_flop.d.next = self.mosi_off.val(); // Set the mosi signal to be "off"
+                    self.clock_state.d.next = self.cpol.val();
+                    self.state.d.next = SPIState::Finish; // No data, go back to idle
+                }
+            }
+            SPIState::MActive => {
+                if self.strobe.val() {
+                    self.state.d.next = SPIState::SampleMISO;
+                }
+            }
+            SPIState::SampleMISO => {
+                self.register_in.d.next = self
+                    .register_in
+                    .q
+                    .val()
+                    .replace_bit(self.pointer.q.val().index(), self.wires.miso.val());
+                self.clock_state.d.next = !self.clock_state.q.val();
+                self.state.d.next = SPIState::MIdle;
+            }
+            SPIState::MIdle => {
+                if self.strobe.val() {
+                    self.state.d.next = SPIState::LoadBit;
+                }
+            }
+            SPIState::Finish => {
+                if self.strobe.val() {
+                    self.state.d.next = SPIState::Idle;
+                    self.done_flop.d.next = true;
+                }
+            }
+            _ => {
+                self.state.d.next = SPIState::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_spi_master_dynamic_baud_is_synthesizable() {
+    let config = SPIConfig {
+        clock_speed: 48_000_000,
+        cs_off: true,
+        mosi_off: false,
+        speed_hz: 1_000_000,
+        cpha: true,
+        cpol: false,
+    };
+    let mut dev = SPIMasterDynamicBaud::<64>::new(config);
+    dev.connect_all();
+    yosys_validate("spi_master_dyn_baud", &generate_verilog(&dev)).unwrap();
+}
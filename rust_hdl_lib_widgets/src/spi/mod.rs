@@ -1,4 +1,8 @@
 pub mod master;
+pub mod master_dynamic_baud;
 pub mod master_dynamic_mode;
+pub mod monitor;
 pub mod mux;
+pub mod qspi;
+pub mod router;
 pub mod slave;
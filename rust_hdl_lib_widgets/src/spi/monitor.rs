@@ -0,0 +1,277 @@
+use crate::spi::master::{SPIConfig, SPIWiresMaster, SPIWiresSlave};
+use rust_hdl_lib_core::prelude::*;
+
+// A passive checker for an SPI bus.  Attach it to a simulation with
+//   sim.add_monitor(SPIMonitor::new(config), |x: &T| (&x.wires).into());
+// It flags
+//   - clock activity while the chip select is inactive
+//   - a chip select change while the clock is not at its idle level
+//   - a chip select assertion with no clock edges (a glitch)
+//   - a chip select pulse shorter than the optional minimum time
+//   - MOSI changing at the same instant as the sampling edge
+// MISO is not checked, since the slave timing is device dependent.  The
+// checks are armed by the first chip select assertion, so that the clock
+// settling to its idle level at power up is not reported.
+
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct SPISample {
+    pub mosi: bool,
+    pub miso: bool,
+    pub msel: bool,
+    pub mclk: bool,
+}
+
+impl From<&SPIWiresMaster> for SPISample {
+    fn from(x: &SPIWiresMaster) -> Self {
+        Self {
+            mosi: x.mosi.val(),
+            miso: x.miso.val(),
+            msel: x.msel.val(),
+            mclk: x.mclk.val(),
+        }
+    }
+}
+
+impl From<&SPIWiresSlave> for SPISample {
+    fn from(x: &SPIWiresSlave) -> Self {
+        Self {
+            mosi: x.mosi.val(),
+            miso: x.miso.val(),
+            msel: x.msel.val(),
+            mclk: x.mclk.val(),
+        }
+    }
+}
+
+pub struct SPIMonitor {
+    config: SPIConfig,
+    min_select_time: u64,
+    previous: Option<SPISample>,
+    armed: bool,
+    select_changed_at: u64,
+    clock_edges: usize,
+    mosi_changed_at: Option<u64>,
+    sample_edge_at: Option<u64>,
+}
+
+impl SPIMonitor {
+    pub fn new(config: SPIConfig) -> Self {
+        Self {
+            config,
+            min_select_time: 0,
+            previous: None,
+            armed: false,
+            select_changed_at: 0,
+            clock_edges: 0,
+            mosi_changed_at: None,
+            sample_edge_at: None,
+        }
+    }
+    /// Flag chip select pulses (active or inactive) shorter than `time`
+    /// (in simulation time units).
+    pub fn min_select_time(self, time: u64) -> Self {
+        Self {
+            min_select_time: time,
+            ..self
+        }
+    }
+    fn selected(&self, x: &SPISample) -> bool {
+        x.msel != self.config.cs_off
+    }
+}
+
+impl Monitor for SPIMonitor {
+    type Sample = SPISample;
+
+    fn name(&self) -> String {
+        "SPI".into()
+    }
+
+    fn check(&mut self, sample: SPISample, time: u64) -> Result<(), String> {
+        let prev = match self.previous.replace(sample) {
+            Some(x) => x,
+            None => return Ok(()),
+        };
+        let was_selected = self.selected(&prev);
+        let selected = self.selected(&sample);
+        if !self.armed {
+            if !selected {
+                return Ok(());
+            }
+            self.armed = true;
+        }
+        if was_selected != selected {
+            if sample.mclk != self.config.cpol || prev.mclk != self.config.cpol {
+                return Err(format!(
+                    "Chip select changed while the clock was not idle (cpol = {})",
+                    self.config.cpol
+                ));
+            }
+            if time - self.select_changed_at < self.min_select_time {
+                return Err(format!(
+                    "Chip select pulse of {} is shorter than the minimum of {}",
+                    time - self.select_changed_at,
+                    self.min_select_time
+                ));
+            }
+            if was_selected && self.clock_edges == 0 {
+                return Err("Chip select glitch - asserted with no clock edges".into());
+            }
+            self.select_changed_at = time;
+            self.clock_edges = 0;
+        }
+        if sample.mclk != prev.mclk {
+            if !selected {
+                return Err("Clock edge while chip select is inactive".into());
+            }
+            self.clock_edges += 1;
+            // The leading edge moves the clock away from its idle level.
+            // Data is sampled on the leading edge if cpha is false, and on
+            // the trailing edge if it is true.
+            let leading = prev.mclk == self.config.cpol;
+            if leading != self.config.cpha {
+                self.sample_edge_at = Some(time);
+            }
+        }
+        if sample.mosi != prev.mosi && selected {
+            self.mosi_changed_at = Some(time);
+        }
+        if self.sample_edge_at.is_some() && self.sample_edge_at == self.mosi_changed_at {
+            return Err("MOSI changed on the sampling edge of the clock".into());
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+fn spi_monitor_config(cpha: bool, cpol: bool) -> SPIConfig {
+    SPIConfig {
+        clock_speed: 48_000_000,
+        cs_off: true,
+        mosi_off: true,
+        speed_hz: 1_200_000,
+        cpha,
+        cpol,
+    }
+}
+
+#[test]
+fn test_spi_monitor_accepts_master() {
+    use crate::spi::master::SPIMaster;
+    for mode in 0..4 {
+        let config = spi_monitor_config(mode & 1 != 0, mode & 2 != 0);
+        let mut uut = SPIMaster::<64>::new(config);
+        uut.bits_outbound.connect();
+        uut.data_outbound.connect();
+        uut.start_send.connect();
+        uut.continued_transaction.connect();
+        uut.wires.miso.connect();
+        uut.connect_all();
+        let mut sim = Simulation::new();
+        sim.add_clock(5, |x: &mut Box<SPIMaster<64>>| {
+            x.clock.next = !x.clock.val()
+        });
+        sim.add_monitor(SPIMonitor::new(config), |x: &SPIMaster<64>| {
+            (&x.wires).into()
+        });
+        sim.add_testbench(move |mut sim: Sim<SPIMaster<64>>| {
+            let mut x = sim.init()?;
+            for data in [0xDEAD_BEEF_u32, 0xCAFE_BABE] {
+                x = sim.watch(|x| !x.busy.val(), x)?;
+                wait_clock_true!(sim, clock, x);
+                x.data_outbound.next = data.to_bits();
+                x.bits_outbound.next = 32.into();
+                x.start_send.next = true;
+                wait_clock_cycle!(sim, clock, x);
+                x.start_send.next = false;
+                x = sim.watch(|x| x.transfer_done.val(), x)?;
+            }
+            wait_clock_cycles!(sim, clock, x, 10);
+            sim.done(x)
+        });
+        sim.run(Box::new(uut), 1_000_000).unwrap();
+    }
+}
+
+#[cfg(test)]
+#[derive(LogicBlock, Default)]
+struct SPIMonitorBus {
+    wires: SPIWiresSlave,
+}
+
+#[cfg(test)]
+impl Logic for SPIMonitorBus {
+    fn update(&mut self) {
+        self.wires.miso.next = false;
+    }
+}
+
+#[cfg(test)]
+fn run_spi_monitor(script: &'static [(bool, bool, bool)]) -> Result<(), SimError> {
+    let mut uut = SPIMonitorBus::default();
+    uut.wires.mosi.connect();
+    uut.wires.msel.connect();
+    uut.wires.mclk.connect();
+    uut.wires.miso.connect();
+    uut.connect_all();
+    let mut sim = Simulation::new();
+    sim.add_monitor(
+        SPIMonitor::new(spi_monitor_config(false, false)),
+        |x: &SPIMonitorBus| (&x.wires).into(),
+    );
+    sim.add_testbench(move |mut sim: Sim<SPIMonitorBus>| {
+        let mut x = sim.init()?;
+        for (msel, mclk, mosi) in script {
+            x.wires.msel.next = *msel;
+            x.wires.mclk.next = *mclk;
+            x.wires.mosi.next = *mosi;
+            x = sim.wait(100, x)?;
+        }
+        sim.done(x)
+    });
+    sim.run(Box::new(uut), 100_000)
+}
+
+#[test]
+fn test_spi_monitor_flags_violations() {
+    // A well formed single bit transfer
+    assert!(run_spi_monitor(&[
+        (true, false, true),
+        (false, false, true),
+        (false, false, false),
+        (false, true, false),
+        (false, false, false),
+        (true, false, true)
+    ])
+    .is_ok());
+    // Chip select glitch
+    assert!(matches!(
+        run_spi_monitor(&[
+            (true, false, true),
+            (false, false, true),
+            (true, false, true)
+        ]),
+        Err(SimError::ProtocolViolation { time: 200, .. })
+    ));
+    // Clock while deselected
+    assert!(matches!(
+        run_spi_monitor(&[
+            (true, false, true),
+            (false, false, true),
+            (false, true, true),
+            (false, false, true),
+            (true, false, true),
+            (true, true, true)
+        ]),
+        Err(SimError::ProtocolViolation { time: 500, .. })
+    ));
+    // MOSI changes with the sampling edge
+    assert!(matches!(
+        run_spi_monitor(&[
+            (true, false, true),
+            (false, false, true),
+            (false, true, false)
+        ]),
+        Err(SimError::ProtocolViolation { time: 200, .. })
+    ));
+}
@@ -0,0 +1,255 @@
+use crate::{dff::DFF, dff_setup, dff_with_init::DFFWithInit, strobe::Strobe};
+use rust_hdl_lib_core::prelude::*;
+
+// The Fast Read Quad I/O command - the command goes out on DQ0, and the
+// address, dummy cycles and data all use the 4 lines.
+pub const QSPI_FAST_READ_QUAD_IO: u8 = 0xEB;
+
+// The wires of a quad SPI bus.  The data lines are split into an output, an
+// output enable and an input, so that they can be fed to a TristateBuffer
+// at the top of the design.
+#[derive(LogicInterface, Default)]
+#[join = "QSPIWiresSlave"]
+pub struct QSPIWiresMaster {
+    pub sclk: Signal<Out, Bit>,
+    pub cs_not: Signal<Out, Bit>,
+    pub dq_out: Signal<Out, Bits<4>>,
+    pub dq_oe: Signal<Out, Bit>,
+    pub dq_in: Signal<In, Bits<4>>,
+}
+
+#[derive(LogicInterface, Default)]
+#[join = "QSPIWiresMaster"]
+pub struct QSPIWiresSlave {
+    pub sclk: Signal<In, Bit>,
+    pub cs_not: Signal<In, Bit>,
+    pub dq_out: Signal<In, Bits<4>>,
+    pub dq_oe: Signal<In, Bit>,
+    pub dq_in: Signal<Out, Bits<4>>,
+}
+
+#[derive(Copy, Clone, Debug)]
+pub struct QSPIConfig {
+    pub clock_speed: u64,
+    pub speed_hz: u64,
+    // Number of clocks between the address and the data.  The data lines
+    // are released during these clocks, so with the usual pull-ups the
+    // flash sees a mode byte of 0xFF, and does not enter continuous read.
+    pub dummy_cycles: usize,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum XIPState {
+    Idle,
+    Command,
+    Address,
+    Dummy,
+    Data,
+    Deselect,
+}
+
+// An execute-in-place read path for a quad SPI flash.  Reads of 32-bit
+// (little endian) words at a byte address are served from a copy of the
+// last 16 byte line fetched from the flash, so a CPU running straight
+// line code only pays for a flash transaction every 4 instructions.  A
+// read that misses fetches the whole line with a Fast Read Quad I/O
+// command (in mode 0).  A read is taken when `read` is asserted while not
+// `busy`, and the word comes back with a pulse on `valid` - one clock
+// later for a hit.
+#[derive(LogicBlock)]
+pub struct QSPIXIPReader {
+    pub clock: Signal<In, Clock>,
+    pub address: Signal<In, Bits<24>>,
+    pub read: Signal<In, Bit>,
+    pub data: Signal<Out, Bits<32>>,
+    pub valid: Signal<Out, Bit>,
+    pub busy: Signal<Out, Bit>,
+    pub wires: QSPIWiresMaster,
+    state: DFF<XIPState>,
+    strobe: Strobe<16>,
+    sclk: DFF<Bit>,
+    cs_not: DFFWithInit<Bit>,
+    shift: DFF<Bits<32>>,
+    count: DFF<Bits<8>>,
+    request: DFF<Bits<24>>,
+    tag: DFF<Bits<20>>,
+    tag_valid: DFF<Bit>,
+    line: DFF<Bits<128>>,
+    nibble: DFF<Bits<4>>,
+    data_flop: DFF<Bits<32>>,
+    valid_flop: DFF<Bit>,
+    hit: Signal<Local, Bit>,
+    cached_word: Signal<Local, Bits<32>>,
+    fetched_word: Signal<Local, Bits<32>>,
+    command: Constant<Bits<32>>,
+    last_dummy: Constant<Bits<8>>,
+}
+
+impl QSPIXIPReader {
+    pub fn new(config: QSPIConfig) -> Self {
+        assert!(config.dummy_cycles > 0 && config.dummy_cycles <= 256);
+        Self {
+            clock: Default::default(),
+            address: Default::default(),
+            read: Default::default(),
+            data: Default::default(),
+            valid: Default::default(),
+            busy: Default::default(),
+            wires: Default::default(),
+            state: Default::default(),
+            strobe: Strobe::new(config.clock_speed, 2.0 * config.speed_hz as f64),
+            sclk: Default::default(),
+            cs_not: DFFWithInit::new(true),
+            shift: Default::default(),
+            count: Default::default(),
+            request: Default::default(),
+            tag: Default::default(),
+            tag_valid: Default::default(),
+            line: Default::default(),
+            nibble: Default::default(),
+            data_flop: Default::default(),
+            valid_flop: Default::default(),
+            hit: Default::default(),
+            cached_word: Default::default(),
+            fetched_word: Default::default(),
+            command: Constant::new(((QSPI_FAST_READ_QUAD_IO as u32) << 24).to_bits()),
+            last_dummy: Constant::new((config.dummy_cycles - 1).to_bits()),
+        }
+    }
+}
+
+impl Logic for QSPIXIPReader {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self, clock, state, sclk, cs_not, shift, count, request, tag, tag_valid, line, nibble,
+            data_flop, valid_flop
+        );
+        clock!(self, clock, strobe);
+        // The strobe runs at twice the SPI clock - one tick per edge
+        self.strobe.enable.next = true;
+        self.wires.sclk.next = self.sclk.q.val();
+        self.wires.cs_not.next = self.cs_not.q.val();
+        self.wires.dq_out.next = 0.into();
+        self.wires.dq_oe.next = false;
+        self.data.next = self.data_flop.q.val();
+        self.valid.next = self.valid_flop.q.val();
+        self.busy.next = self.state.q.val() != XIPState::Idle;
+        self.valid_flop.d.next = false;
+        // The line holds the bytes in address order, with the first one
+        // in the least significant byte
+        self.hit.next =
+            self.tag_valid.q.val() & (self.tag.q.val() == self.address.val().get_bits::<20>(4));
+        self.cached_word.next = bit_cast::<32, 128>(
+            self.line.q.val() >> (bit_cast::<7, 2>(self.address.val().get_bits::<2>(2)) << 5),
+        );
+        self.fetched_word.next = bit_cast::<32, 128>(
+            self.line.q.val() >> (bit_cast::<7, 2>(self.request.q.val().get_bits::<2>(2)) << 5),
+        );
+        match self.state.q.val() {
+            XIPState::Idle => {
+                self.cs_not.d.next = true;
+                self.sclk.d.next = false;
+                if self.read.val() {
+                    self.request.d.next = self.address.val();
+                    if self.hit.val() {
+                        self.data_flop.d.next = self.cached_word.val();
+                        self.valid_flop.d.next = true;
+                    } else {
+                        // Fetch the line that holds the word
+                        self.tag_valid.d.next = false;
+                        self.shift.d.next =
+                            self.command.val() | bit_cast::<32, 24>(self.address.val() & 0xFF_FFF0);
+                        self.count.d.next = 0.into();
+                        self.cs_not.d.next = false;
+                        self.state.d.next = XIPState::Command;
+                    }
+                }
+            }
+            XIPState::Command => {
+                self.wires.dq_out.next = bit_cast::<4, 32>(self.shift.q.val() >> 31);
+                self.wires.dq_oe.next = true;
+            }
+            XIPState::Address => {
+                self.wires.dq_out.next = bit_cast::<4, 32>(self.shift.q.val() >> 28);
+                self.wires.dq_oe.next = true;
+            }
+            _ => {}
+        }
+        // The flash samples on the rising edge of the clock, and changes its
+        // outputs on the falling edge.  So everything moves on when the clock
+        // falls, with the data sampled just before that.
+        if self.strobe.strobe.val()
+            & (self.state.q.val() != XIPState::Idle)
+            & (self.state.q.val() != XIPState::Deselect)
+        {
+            self.sclk.d.next = !self.sclk.q.val();
+            if self.sclk.q.val() {
+                match self.state.q.val() {
+                    XIPState::Command => {
+                        self.count.d.next = self.count.q.val() + 1;
+                        self.shift.d.next = self.shift.q.val() << 1;
+                        if self.count.q.val() == 7 {
+                            self.count.d.next = 0.into();
+                            self.state.d.next = XIPState::Address;
+                        }
+                    }
+                    XIPState::Address => {
+                        self.count.d.next = self.count.q.val() + 1;
+                        self.shift.d.next = self.shift.q.val() << 4;
+                        if self.count.q.val() == 5 {
+                            self.count.d.next = 0.into();
+                            self.state.d.next = XIPState::Dummy;
+                        }
+                    }
+                    XIPState::Dummy => {
+                        self.count.d.next = self.count.q.val() + 1;
+                        if self.count.q.val() == self.last_dummy.val() {
+                            self.count.d.next = 0.into();
+                            self.state.d.next = XIPState::Data;
+                        }
+                    }
+                    XIPState::Data => {
+                        self.count.d.next = self.count.q.val() + 1;
+                        // Each byte comes in as 2 nibbles, most significant first
+                        if !self.count.q.val().get_bit(0) {
+                            self.nibble.d.next = self.wires.dq_in.val();
+                        } else {
+                            self.line.d.next = (self.line.q.val() >> 8)
+                                | (bit_cast::<128, 8>(
+                                    (bit_cast::<8, 4>(self.nibble.q.val()) << 4)
+                                        | bit_cast::<8, 4>(self.wires.dq_in.val()),
+                                ) << 120);
+                        }
+                        if self.count.q.val() == 31 {
+                            self.state.d.next = XIPState::Deselect;
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+        if self.state.q.val() == XIPState::Deselect {
+            // Release the flash, and answer the read that missed
+            self.cs_not.d.next = true;
+            if self.strobe.strobe.val() {
+                self.tag.d.next = self.request.q.val().get_bits::<20>(4);
+                self.tag_valid.d.next = true;
+                self.data_flop.d.next = self.fetched_word.val();
+                self.valid_flop.d.next = true;
+                self.state.d.next = XIPState::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_qspi_xip_reader_is_synthesizable() {
+    let mut uut = QSPIXIPReader::new(QSPIConfig {
+        clock_speed: 48_000_000,
+        speed_hz: 8_000_000,
+        dummy_cycles: 6,
+    });
+    uut.connect_all();
+    yosys_validate("qspi_xip_reader", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,151 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::spi::master::SPIWiresMaster;
+use crate::spi::master_dynamic_mode::{SPIConfigDynamicMode, SPIMasterDynamicMode};
+use rust_hdl_lib_core::prelude::*;
+
+// The settings for one of the targets behind an SPIRouter.  If `fixed_mode`
+// is set, the target is always driven with the given cpol/cpha, and the
+// mode bits of the transaction are ignored.
+#[derive(Copy, Clone, Debug)]
+pub struct SPIRouterTarget {
+    pub cs_off: bool,
+    pub mosi_off: bool,
+    pub speed_hz: u64,
+    pub fixed_mode: bool,
+    pub cpha: bool,
+    pub cpol: bool,
+}
+
+// Routes transactions to one of S SPI targets, each with its own set of
+// wires (and so its own chip select).  The interface is the same as the
+// SPIMasterDynamicMode, with the target index carried in bits_outbound
+// alongside the bit count and the mode:
+//
+//   bits  7:0  - number of bits to send
+//   bit   8    - cpol
+//   bit   9    - cpha
+//   bits 15:10 - target index
+//
+// Each target gets its own master, so the targets can run at different
+// speeds.  A transaction can only be started when the router is not busy,
+// and a continued transaction should be finished before talking to
+// another target.
+#[derive(LogicBlock)]
+pub struct SPIRouter<const N: usize, const S: usize> {
+    pub clock: Signal<In, Clock>,
+    pub bits_outbound: Signal<In, Bits<16>>,
+    pub data_outbound: Signal<In, Bits<N>>,
+    pub data_inbound: Signal<Out, Bits<N>>,
+    pub start_send: Signal<In, Bit>,
+    pub transfer_done: Signal<Out, Bit>,
+    pub continued_transaction: Signal<In, Bit>,
+    pub wires: [SPIWiresMaster; S],
+    pub busy: Signal<Out, Bit>,
+    masters: [SPIMasterDynamicMode<N>; S],
+    selected: Signal<Local, Bits<6>>,
+    target: DFF<Bits<6>>,
+    mode_mask: [Constant<Bits<16>>; S],
+    mode_bits: [Constant<Bits<16>>; S],
+}
+
+impl<const N: usize, const S: usize> SPIRouter<N, S> {
+    pub fn new(clock_speed: u64, targets: [SPIRouterTarget; S]) -> Self {
+        assert!(S > 0 && S <= 64);
+        Self {
+            clock: Default::default(),
+            bits_outbound: Default::default(),
+            data_outbound: Default::default(),
+            data_inbound: Default::default(),
+            start_send: Default::default(),
+            transfer_done: Default::default(),
+            continued_transaction: Default::default(),
+            wires: array_init::array_init(|_| Default::default()),
+            busy: Default::default(),
+            masters: array_init::array_init(|i| {
+                SPIMasterDynamicMode::new(SPIConfigDynamicMode {
+                    clock_speed,
+                    cs_off: targets[i].cs_off,
+                    mosi_off: targets[i].mosi_off,
+                    speed_hz: targets[i].speed_hz,
+                })
+            }),
+            selected: Default::default(),
+            target: Default::default(),
+            mode_mask: array_init::array_init(|i| {
+                let mask: u16 = if targets[i].fixed_mode {
+                    0x00FF
+                } else {
+                    0x03FF
+                };
+                Constant::new(mask.to_bits())
+            }),
+            mode_bits: array_init::array_init(|i| {
+                let target = targets[i];
+                let mode = if target.fixed_mode {
+                    (target.cpha as u16) << 9 | (target.cpol as u16) << 8
+                } else {
+                    0
+                };
+                Constant::new(mode.to_bits())
+            }),
+        }
+    }
+}
+
+impl<const N: usize, const S: usize> Logic for SPIRouter<N, S> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, target);
+        self.selected.next = self.bits_outbound.val().get_bits::<6>(10);
+        // Latch prevention
+        self.busy.next = false;
+        self.transfer_done.next = false;
+        self.data_inbound.next = 0.into();
+        for i in 0..S {
+            self.masters[i].clock.next = self.clock.val();
+            SPIWiresMaster::link(&mut self.wires[i], &mut self.masters[i].wires);
+            self.masters[i].bits_outbound.next =
+                (self.bits_outbound.val() & self.mode_mask[i].val()) | self.mode_bits[i].val();
+            self.masters[i].data_outbound.next = self.data_outbound.val();
+            self.masters[i].continued_transaction.next = self.continued_transaction.val();
+            self.busy.next = self.busy.val() | self.masters[i].busy.val();
+            self.transfer_done.next =
+                self.transfer_done.val() | self.masters[i].transfer_done.val();
+            if self.target.q.val().index() == i {
+                self.data_inbound.next = self.masters[i].data_inbound.val();
+            }
+        }
+        // Only the selected target sees the start of a transaction
+        for i in 0..S {
+            self.masters[i].start_send.next = false;
+            if self.start_send.val() & !self.busy.val() & (self.selected.val().index() == i) {
+                self.masters[i].start_send.next = true;
+            }
+        }
+        if self.start_send.val() & !self.busy.val() {
+            self.target.d.next = self.selected.val();
+        }
+    }
+}
+
+#[test]
+fn test_spi_router_is_synthesizable() {
+    let target = SPIRouterTarget {
+        cs_off: true,
+        mosi_off: false,
+        speed_hz: 1_000_000,
+        fixed_mode: false,
+        cpha: false,
+        cpol: false,
+    };
+    let slow = SPIRouterTarget {
+        speed_hz: 100_000,
+        fixed_mode: true,
+        cpha: true,
+        ..target
+    };
+    let mut uut = SPIRouter::<32, 3>::new(48_000_000, [target, slow, target]);
+    uut.connect_all();
+    yosys_validate("spi_router", &generate_verilog(&uut)).unwrap();
+}
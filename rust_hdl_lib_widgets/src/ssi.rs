@@ -0,0 +1,216 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::dff_with_init::DFFWithInit;
+use crate::gray::GrayToBinary;
+use crate::synchronizer::BitSynchronizer;
+use rust_hdl_lib_core::prelude::*;
+
+/// The wires of a synchronous serial interface (SSI) link to an absolute encoder.  The
+/// clock idles high, and the data line is high while the encoder is ready for a read.
+#[derive(LogicInterface, Default)]
+#[join = "SSIWiresSlave"]
+pub struct SSIWiresMaster {
+    pub clk: Signal<Out, Bit>,
+    pub data: Signal<In, Bit>,
+}
+
+#[derive(LogicInterface, Default)]
+#[join = "SSIWiresMaster"]
+pub struct SSIWiresSlave {
+    pub clk: Signal<In, Bit>,
+    pub data: Signal<Out, Bit>,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SSIConfig {
+    /// The frequency of the clock driving the master
+    pub clock_speed: u64,
+    /// The frequency of the SSI clock
+    pub speed_hz: u64,
+    /// The encoder sends its position as gray code
+    pub gray_code: bool,
+    /// How long to wait for the encoder to be ready before a read (this needs to be
+    /// longer than the monoflop time of the encoder)
+    pub timeout_us: u64,
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum SSIState {
+    Idle,
+    WaitReady,
+    Clocking,
+    Check,
+}
+
+/// An [SSIMaster] reads the `N` bit position of an SSI absolute encoder.  A read is
+/// started by asserting `start` while not `busy`.  Once the encoder is ready (the data
+/// line is high), the first falling edge of the clock latches the position in the
+/// encoder, which then sends it MSB first, a bit on each rising edge.  The master takes
+/// each bit on the falling edge that follows.  After the last bit, the encoder holds the
+/// data line low until its monoflop time runs out, which marks the end of the frame.
+///
+/// When the read is done, the position (converted from gray code if the encoder uses it)
+/// is presented on `position`, and `valid` is pulsed.  `error` is set along with `valid`
+/// if the encoder did not become ready within the timeout, or if the data line was not
+/// low after the frame (e.g., if the encoder is not connected).
+#[derive(LogicBlock)]
+pub struct SSIMaster<const N: usize> {
+    pub clock: Signal<In, Clock>,
+    /// Assert to read the position
+    pub start: Signal<In, Bit>,
+    pub position: Signal<Out, Bits<N>>,
+    pub valid: Signal<Out, Bit>,
+    pub error: Signal<Out, Bit>,
+    pub busy: Signal<Out, Bit>,
+    pub wires: SSIWiresMaster,
+    state: DFF<SSIState>,
+    clk: DFFWithInit<Bit>,
+    data_sync: BitSynchronizer,
+    shift: DFF<Bits<N>>,
+    bits: DFF<Bits<8>>,
+    timer: DFF<Bits<32>>,
+    counter: DFF<Bits<16>>,
+    tick: Signal<Local, Bit>,
+    position_flop: DFF<Bits<N>>,
+    valid_flop: DFF<Bit>,
+    error_flop: DFF<Bit>,
+    gray: GrayToBinary<N>,
+    half_period: Constant<Bits<16>>,
+    timeout: Constant<Bits<32>>,
+    frame_bits: Constant<Bits<8>>,
+    gray_code: Constant<Bit>,
+}
+
+impl<const N: usize> SSIMaster<N> {
+    pub fn new(config: SSIConfig) -> Self {
+        assert!((1..256).contains(&N));
+        let half_period =
+            (config.clock_speed as f64 / (2.0 * config.speed_hz as f64)).round() as u64;
+        assert!((8..(1 << 16)).contains(&half_period));
+        let timeout = config.clock_speed * config.timeout_us / 1_000_000;
+        assert!(timeout < (1 << 32));
+        Self {
+            clock: Default::default(),
+            start: Default::default(),
+            position: Default::default(),
+            valid: Default::default(),
+            error: Default::default(),
+            busy: Default::default(),
+            wires: Default::default(),
+            state: Default::default(),
+            clk: DFFWithInit::new(true),
+            data_sync: Default::default(),
+            shift: Default::default(),
+            bits: Default::default(),
+            timer: Default::default(),
+            counter: Default::default(),
+            tick: Default::default(),
+            position_flop: Default::default(),
+            valid_flop: Default::default(),
+            error_flop: Default::default(),
+            gray: Default::default(),
+            half_period: Constant::new(half_period.to_bits()),
+            timeout: Constant::new(timeout.to_bits()),
+            frame_bits: Constant::new(N.to_bits()),
+            gray_code: Constant::new(config.gray_code),
+        }
+    }
+}
+
+impl<const N: usize> Logic for SSIMaster<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            state,
+            clk,
+            shift,
+            bits,
+            timer,
+            counter,
+            position_flop,
+            valid_flop,
+            error_flop
+        );
+        clock!(self, clock, data_sync);
+        self.data_sync.sig_in.next = self.wires.data.val();
+        self.wires.clk.next = self.clk.q.val();
+        self.gray.data_in.next = self.shift.q.val();
+        self.position.next = self.position_flop.q.val();
+        self.valid.next = self.valid_flop.q.val();
+        self.error.next = self.error_flop.q.val();
+        self.busy.next = self.state.q.val() != SSIState::Idle;
+        self.valid_flop.d.next = false;
+        // One tick for each edge of the SSI clock
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.tick.next = self.counter.q.val() == self.half_period.val();
+        if self.tick.val() {
+            self.counter.d.next = 1.into();
+        }
+        match self.state.q.val() {
+            SSIState::Idle => {
+                self.clk.d.next = true;
+                if self.start.val() {
+                    self.timer.d.next = 0.into();
+                    self.state.d.next = SSIState::WaitReady;
+                }
+            }
+            SSIState::WaitReady => {
+                self.timer.d.next = self.timer.q.val() + 1;
+                if self.data_sync.sig_out.val() {
+                    // The first falling edge latches the position
+                    self.clk.d.next = false;
+                    self.counter.d.next = 1.into();
+                    self.bits.d.next = 0.into();
+                    self.state.d.next = SSIState::Clocking;
+                } else if self.timer.q.val() == self.timeout.val() {
+                    self.error_flop.d.next = true;
+                    self.valid_flop.d.next = true;
+                    self.state.d.next = SSIState::Idle;
+                }
+            }
+            SSIState::Clocking => {
+                if self.tick.val() {
+                    self.clk.d.next = !self.clk.q.val();
+                    if self.clk.q.val() {
+                        // A falling edge - take the bit sent on the last rising edge
+                        self.shift.d.next = (self.shift.q.val() << 1)
+                            | bit_cast::<N, 1>(self.data_sync.sig_out.val().into());
+                        self.bits.d.next = self.bits.q.val() + 1;
+                    } else if self.bits.q.val() == self.frame_bits.val() {
+                        self.state.d.next = SSIState::Check;
+                    }
+                }
+            }
+            SSIState::Check => {
+                // Half a clock after the last rising edge, the encoder should be
+                // holding the data line low
+                if self.tick.val() {
+                    self.error_flop.d.next = self.data_sync.sig_out.val();
+                    self.position_flop.d.next = self.shift.q.val();
+                    if self.gray_code.val() {
+                        self.position_flop.d.next = self.gray.data_out.val();
+                    }
+                    self.valid_flop.d.next = true;
+                    self.state.d.next = SSIState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = SSIState::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_ssi_master_is_synthesizable() {
+    let mut uut = SSIMaster::<25>::new(SSIConfig {
+        clock_speed: 100_000_000,
+        speed_hz: 1_000_000,
+        gray_code: true,
+        timeout_us: 40,
+    });
+    uut.connect_all();
+    yosys_validate("ssi_master", &generate_verilog(&uut)).unwrap();
+}
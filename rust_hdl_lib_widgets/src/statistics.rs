@@ -0,0 +1,273 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use rust_hdl_lib_core::prelude::*;
+
+// Computes the integer square root (rounded down) of `data_in` one bit per clock,
+// with the restoring (digit by digit) method.  Assert `start` for one clock, and the
+// root appears on `root` 16 clocks later, with `done` asserted for one clock.
+#[derive(LogicBlock, Default)]
+struct SquareRoot {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<32>>,
+    pub start: Signal<In, Bit>,
+    pub root: Signal<Out, Bits<16>>,
+    pub done: Signal<Out, Bit>,
+    value: DFF<Bits<32>>,
+    remainder: DFF<Bits<34>>,
+    partial: DFF<Bits<16>>,
+    count: DFF<Bits<5>>,
+    busy: DFF<Bit>,
+    finished: DFF<Bit>,
+    shifted: Signal<Local, Bits<34>>,
+    trial: Signal<Local, Bits<34>>,
+}
+
+impl Logic for SquareRoot {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, value, remainder, partial, count, busy, finished);
+        // Bring down the next two bits, and try the next bit of the root
+        self.shifted.next =
+            (self.remainder.q.val() << 2) | bit_cast::<34, 2>(self.value.q.val().get_bits::<2>(30));
+        self.trial.next = (bit_cast::<34, 16>(self.partial.q.val()) << 2) | 1;
+        self.finished.d.next = false;
+        if self.busy.q.val() {
+            self.value.d.next = self.value.q.val() << 2;
+            self.count.d.next = self.count.q.val() + 1;
+            if self.shifted.val() >= self.trial.val() {
+                self.remainder.d.next = self.shifted.val() - self.trial.val();
+                self.partial.d.next = (self.partial.q.val() << 1) | 1;
+            } else {
+                self.remainder.d.next = self.shifted.val();
+                self.partial.d.next = self.partial.q.val() << 1;
+            }
+            if self.count.q.val() == 15 {
+                self.busy.d.next = false;
+                self.finished.d.next = true;
+            }
+        }
+        if self.start.val() {
+            self.value.d.next = self.data_in.val();
+            self.remainder.d.next = 0.into();
+            self.partial.d.next = 0.into();
+            self.count.d.next = 0.into();
+            self.busy.d.next = true;
+        }
+        self.root.next = self.partial.q.val();
+        self.done.next = self.finished.q.val();
+    }
+}
+
+/// Windowed mean, variance and RMS
+///
+/// Computes the statistics of the samples presented on [`data_in`](Self::data_in) over windows
+/// of `2^shift` samples (e.g., to measure the noise of an ADC front end, or the RMS value of an AC
+/// signal).  Rather than summing the samples and their squares directly (where the variance is
+/// the small difference of two large numbers), the sums are taken of the deviation of each sample
+/// from the mean of an earlier window (the one before last, as the mean of the last window is still
+/// being computed when the next one starts), in the spirit of Welford's method.  Once the mean
+/// settles, the deviations are small, and the variance is computed without losing any bits.  At the
+/// end of each window, the results are updated as:
+///
+/// * [`mean`](Self::mean) - the mean of the samples (rounded down).
+/// * [`variance`](Self::variance) - the (population) variance of the samples, in LSBs squared.
+/// * [`mean_square`](Self::mean_square) - the mean of the squares of the samples (`variance + mean^2`).
+/// * [`std_dev`](Self::std_dev) - the standard deviation (the square root of the variance, rounded down).
+/// * [`rms`](Self::rms) - the RMS value of the samples (the square root of the mean square, rounded down).
+///
+/// The square roots take 16 clocks, after which [`strobe_out`](Self::strobe_out) is asserted for one
+/// clock.  The results are held until the end of the next window.
+///
+/// ### Inputs
+///
+/// * [`clock`](Self::clock) The clock for the widget.
+/// * [`data_in`](Self::data_in) The samples.
+/// * [`strobe_in`](Self::strobe_in) Assert for one clock with each sample.
+/// * [`shift`](Self::shift) The log2 of the window size (from 0 to 16).  Change it between windows.
+///
+/// ### Additional info
+///
+/// The mean is rounded down before it is squared, so the variance can be off by up to
+/// `2 |m - m'| + 1` LSBs squared, where `m` is the mean of the window, and `m'` is the mean the
+/// deviations were taken from.  For a steady signal, this is the rounding of the mean, and the variance is essentially exact.
+/// The variance is clamped at zero.  A window of full scale samples with a large DC step can
+/// overflow the 32 bit results, since the largest possible mean square is `2^30`.
+#[derive(LogicBlock, Default)]
+pub struct WindowedStatistics {
+    /// The clock for the widget.
+    pub clock: Signal<In, Clock>,
+    /// The samples.
+    pub data_in: Signal<In, Signed<16>>,
+    /// Assert for one clock with each sample.
+    pub strobe_in: Signal<In, Bit>,
+    /// The log2 of the window size (from 0 to 16).
+    pub shift: Signal<In, Bits<8>>,
+    /// The mean of the last window.
+    pub mean: Signal<Out, Signed<16>>,
+    /// The variance of the last window.
+    pub variance: Signal<Out, Bits<32>>,
+    /// The mean square of the last window.
+    pub mean_square: Signal<Out, Bits<32>>,
+    /// The standard deviation of the last window.
+    pub std_dev: Signal<Out, Bits<16>>,
+    /// The RMS value of the last window.
+    pub rms: Signal<Out, Bits<16>>,
+    /// Asserted for one clock when the results are updated.
+    pub strobe_out: Signal<Out, Bit>,
+    std_dev_root: SquareRoot,
+    rms_root: SquareRoot,
+    reference: DFF<Signed<16>>,
+    next_reference: DFF<Signed<16>>,
+    final_reference: DFF<Signed<16>>,
+    sum: DFF<Signed<34>>,
+    sum_squares: DFF<Bits<48>>,
+    count: DFF<Bits<17>>,
+    final_sum: DFF<Signed<34>>,
+    final_sum_squares: DFF<Bits<48>>,
+    finished: DFF<Bit>,
+    mean_reg: DFF<Signed<16>>,
+    variance_reg: DFF<Bits<32>>,
+    mean_square_reg: DFF<Bits<32>>,
+    std_dev_reg: DFF<Bits<16>>,
+    rms_reg: DFF<Bits<16>>,
+    std_dev_ready: DFF<Bit>,
+    rms_ready: DFF<Bit>,
+    calculate: DFF<Bit>,
+    strobe: DFF<Bit>,
+    deviation: Signal<Local, Signed<17>>,
+    magnitude: Signal<Local, Bits<16>>,
+    next_sum: Signal<Local, Signed<34>>,
+    next_sum_squares: Signal<Local, Bits<48>>,
+    last: Signal<Local, Bits<17>>,
+    mean_deviation: Signal<Local, Signed<17>>,
+    mean_magnitude: Signal<Local, Bits<16>>,
+    mean_value: Signal<Local, Signed<17>>,
+    abs_mean: Signal<Local, Bits<16>>,
+    spread: Signal<Local, Bits<32>>,
+    variance_value: Signal<Local, Bits<32>>,
+}
+
+impl Logic for WindowedStatistics {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            reference,
+            next_reference,
+            final_reference,
+            sum,
+            sum_squares,
+            count,
+            final_sum,
+            final_sum_squares,
+            finished,
+            mean_reg,
+            variance_reg,
+            mean_square_reg,
+            std_dev_reg,
+            rms_reg,
+            std_dev_ready,
+            rms_ready,
+            calculate,
+            strobe
+        );
+        clock!(self, clock, std_dev_root, rms_root);
+        // Accumulate the deviations from the reference, and their squares
+        self.deviation.next = signed_bit_cast::<17, 16>(self.data_in.val())
+            - signed_bit_cast::<17, 16>(self.reference.q.val());
+        self.magnitude.next = bit_cast::<16, 17>(unsigned_cast(self.deviation.val()));
+        if self.deviation.val().get_bit(16) {
+            self.magnitude.next = bit_cast::<16, 17>(unsigned_cast(-self.deviation.val()));
+        }
+        self.next_sum.next = self.sum.q.val() + signed_bit_cast::<34, 17>(self.deviation.val());
+        self.next_sum_squares.next = self.sum_squares.q.val()
+            + bit_cast::<48, 32>(self.magnitude.val() * self.magnitude.val());
+        self.last.next = (bit_cast::<17, 1>(true.into()) << self.shift.val()) - 1;
+        self.finished.d.next = false;
+        if self.strobe_in.val() {
+            self.sum.d.next = self.next_sum.val();
+            self.sum_squares.d.next = self.next_sum_squares.val();
+            self.count.d.next = self.count.q.val() + 1;
+            if self.count.q.val() >= self.last.val() {
+                self.final_sum.d.next = self.next_sum.val();
+                self.final_sum_squares.d.next = self.next_sum_squares.val();
+                self.final_reference.d.next = self.reference.q.val();
+                self.reference.d.next = self.next_reference.q.val();
+                self.finished.d.next = true;
+                self.sum.d.next = 0.into();
+                self.sum_squares.d.next = 0.into();
+                self.count.d.next = 0.into();
+            }
+        }
+        // At the end of the window, divide the sums by the window size, and remove
+        // the mean from the mean square deviation
+        self.mean_deviation.next = self
+            .final_sum
+            .q
+            .val()
+            .get_bits::<17>(self.shift.val().index());
+        self.mean_magnitude.next = bit_cast::<16, 17>(unsigned_cast(self.mean_deviation.val()));
+        if self.mean_deviation.val().get_bit(16) {
+            self.mean_magnitude.next =
+                bit_cast::<16, 17>(unsigned_cast(-self.mean_deviation.val()));
+        }
+        self.mean_value.next =
+            signed_bit_cast::<17, 16>(self.final_reference.q.val()) + self.mean_deviation.val();
+        self.abs_mean.next = bit_cast::<16, 17>(unsigned_cast(self.mean_value.val()));
+        if self.mean_value.val().get_bit(16) {
+            self.abs_mean.next = bit_cast::<16, 17>(unsigned_cast(-self.mean_value.val()));
+        }
+        self.spread.next = self
+            .final_sum_squares
+            .q
+            .val()
+            .get_bits::<32>(self.shift.val().index());
+        self.variance_value.next = 0.into();
+        if self.spread.val() > self.mean_magnitude.val() * self.mean_magnitude.val() {
+            self.variance_value.next =
+                self.spread.val() - self.mean_magnitude.val() * self.mean_magnitude.val();
+        }
+        self.calculate.d.next = self.finished.q.val();
+        if self.finished.q.val() {
+            self.next_reference.d.next = signed_bit_cast::<16, 17>(self.mean_value.val());
+            self.mean_reg.d.next = signed_bit_cast::<16, 17>(self.mean_value.val());
+            self.variance_reg.d.next = self.variance_value.val();
+            self.mean_square_reg.d.next =
+                self.variance_value.val() + self.abs_mean.val() * self.abs_mean.val();
+        }
+        // Take the square roots
+        self.std_dev_root.data_in.next = self.variance_reg.q.val();
+        self.std_dev_root.start.next = self.calculate.q.val();
+        self.rms_root.data_in.next = self.mean_square_reg.q.val();
+        self.rms_root.start.next = self.calculate.q.val();
+        self.strobe.d.next = false;
+        if self.std_dev_root.done.val() {
+            self.std_dev_reg.d.next = self.std_dev_root.root.val();
+            self.std_dev_ready.d.next = true;
+        }
+        if self.rms_root.done.val() {
+            self.rms_reg.d.next = self.rms_root.root.val();
+            self.rms_ready.d.next = true;
+        }
+        if self.std_dev_ready.q.val() & self.rms_ready.q.val() {
+            self.std_dev_ready.d.next = false;
+            self.rms_ready.d.next = false;
+            self.strobe.d.next = true;
+        }
+        self.mean.next = self.mean_reg.q.val();
+        self.variance.next = self.variance_reg.q.val();
+        self.mean_square.next = self.mean_square_reg.q.val();
+        self.std_dev.next = self.std_dev_reg.q.val();
+        self.rms.next = self.rms_reg.q.val();
+        self.strobe_out.next = self.strobe.q.val();
+    }
+}
+
+#[test]
+fn test_windowed_statistics_is_synthesizable() {
+    let mut uut = WindowedStatistics::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("windowed_statistics", &vlog).unwrap();
+}
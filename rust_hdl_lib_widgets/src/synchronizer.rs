@@ -1,30 +1,186 @@
 use rust_hdl_lib_core::prelude::*;
+use std::cell::RefCell;
 
 use crate::{dff::DFF, dff_setup};
 
 /// A [BitSynchronizer] is used to move signals that are asynchronous to a clock into that
-/// clock domain using a pair of back-to-back flip-flops.  While the first flip flop may
-/// become metastable, the second one is likely to be stable.
-#[derive(LogicBlock, Default)]
-pub struct BitSynchronizer {
+/// clock domain using a chain of back-to-back flip-flops (two by default).  While the first
+/// flip flop may become metastable, it has the rest of the clock period to settle before the
+/// second one samples it, and each extra stage buys another period (see [synchronizer_mtbf]
+/// for how much that is worth).  The flip flops carry the `ASYNC_REG` attribute, so that the
+/// vendor tools place them next to each other, and do not turn them into a shift register.
+///
+/// In simulation, a flip flop never goes metastable, so the synchronizer always has the same
+/// latency.  In hardware, an input that changes close to the clock edge may be seen one clock
+/// later than expected.  Logic that depends on the exact latency of a synchronizer is broken,
+/// and [set_metastability_model] can be used to shake it out in simulation.
+#[derive(LogicBlock)]
+pub struct BitSynchronizer<const STAGES: usize = 2> {
     /// The input signal, which is asynchronous to the clock
     pub sig_in: Signal<In, Bit>,
     /// The output signal, synchronized to the clock
     pub sig_out: Signal<Out, Bit>,
     /// The clock signal to synchronize the output to
     pub clock: Signal<In, Clock>,
-    dff0: DFF<Bit>,
-    dff1: DFF<Bit>,
+    _stages: Vec<Bit>,
+    _sampled: Bit,
 }
 
-impl Logic for BitSynchronizer {
-    #[hdl_gen]
+impl<const STAGES: usize> Default for BitSynchronizer<STAGES> {
+    fn default() -> Self {
+        assert!(STAGES >= 2, "A synchronizer needs at least two stages");
+        Self {
+            sig_in: Default::default(),
+            sig_out: Default::default(),
+            clock: Default::default(),
+            _stages: vec![false; STAGES],
+            _sampled: false,
+        }
+    }
+}
+
+/// How the first stage of every [BitSynchronizer] resolves when it captures a change (see
+/// [set_metastability_model]).
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct MetastabilityModel {
+    /// The chance that a change is missed at the clock edge that captures it (and so is
+    /// seen one clock later)
+    pub probability: f64,
+    /// The seed for the random choices, so that a failing run can be repeated
+    pub seed: u64,
+}
+
+thread_local! {
+    static METASTABILITY: RefCell<Option<(f64, u64)>> = const { RefCell::new(None) };
+}
+
+/// Model metastability in every [BitSynchronizer] simulated on this thread (or stop, with
+/// `None`).  At the first clock edge after the input of a synchronizer changes, the change
+/// is missed (the first stage resolves to the old value) with the given probability, and is
+/// then picked up at the next edge, so it reaches the output a clock later.  That is what a
+/// real synchronizer does when the input changes close to the clock edge.  Each test runs on
+/// its own thread, so the model only applies to the simulations in the test that sets it.
+pub fn set_metastability_model(model: Option<MetastabilityModel>) {
+    METASTABILITY.with(|x| {
+        *x.borrow_mut() = model.map(|model| (model.probability, model.seed | 1));
+    });
+}
+
+// Roll the dice for a capture (an xorshift generator is plenty for this)
+pub(crate) fn capture_missed() -> bool {
+    METASTABILITY.with(|x| match &mut *x.borrow_mut() {
+        Some((probability, state)) => {
+            *state ^= *state << 13;
+            *state ^= *state >> 7;
+            *state ^= *state << 17;
+            ((*state >> 11) as f64 / (1_u64 << 53) as f64) < *probability
+        }
+        None => false,
+    })
+}
+
+impl<const STAGES: usize> Logic for BitSynchronizer<STAGES> {
     fn update(&mut self) {
-        dff_setup!(self, clock, dff0, dff1);
-        self.dff0.d.next = self.sig_in.val();
-        self.dff1.d.next = self.dff0.q.val();
-        self.sig_out.next = self.dff1.q.val();
+        if self.clock.pos_edge() {
+            // Only the first edge after a change can be too close to it
+            let changed = self.sig_in.val() != self._sampled;
+            self._sampled = self.sig_in.val();
+            let captured = if changed && capture_missed() {
+                self._stages[0]
+            } else {
+                self.sig_in.val()
+            };
+            self._stages.rotate_right(1);
+            self._stages[0] = captured;
+        }
+        self.sig_out.next = self._stages[STAGES - 1];
     }
+    fn connect(&mut self) {
+        self.sig_out.connect();
+    }
+    fn hdl(&self) -> Verilog {
+        Verilog::Custom(format!(
+            "\
+(* ASYNC_REG = \"TRUE\" *) reg [{msb}:0] sync_stages;
+
+initial begin
+   sync_stages = 0;
+end
+
+always @(posedge clock) begin
+   sync_stages <= {{sync_stages[{last}:0], sig_in}};
+end
+
+always @(*) sig_out = sync_stages[{msb}];",
+            msb = STAGES - 1,
+            last = STAGES - 2
+        ))
+    }
+    fn timing(&self) -> Vec<TimingInfo> {
+        vec![TimingInfo {
+            name: "synchronizer".into(),
+            clock: "clock".into(),
+            edge: ClockEdge::Rising,
+            inputs: vec!["sig_in".into()],
+            outputs: vec!["sig_out".into()],
+        }]
+    }
+}
+
+/// The properties of the flip flops of a synchronizer that set how quickly they recover from
+/// metastability (see [synchronizer_mtbf]).  The defaults are rough figures for the fabric
+/// flip flops of a recent FPGA - use the numbers from the vendor when they are available.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct FlipFlopCharacteristics {
+    /// The time constant of the decay of a metastable state (in seconds)
+    pub tau: f64,
+    /// The width of the window around the clock edge in which a change can cause
+    /// metastability (in seconds)
+    pub window: f64,
+    /// The part of each clock period that is not available for settling (the clock to out
+    /// and setup times, and the routing between the stages) (in seconds)
+    pub overhead: f64,
+}
+
+impl Default for FlipFlopCharacteristics {
+    fn default() -> Self {
+        Self {
+            tau: 25e-12,
+            window: 100e-12,
+            overhead: 500e-12,
+        }
+    }
+}
+
+/// Estimate the mean time between failures (in seconds) of a synchronizer with the given
+/// number of stages, clocked at `clock_hz`, for an input that changes `toggle_hz` times a
+/// second.  Every stage after the first gives a metastable state another clock period (less
+/// the overhead) to resolve, and the chance that it has not resolved falls exponentially with
+/// the time it is given.  The result is only as good as the [FlipFlopCharacteristics], but it
+/// shows how quickly the margin falls with the clock speed, and how much an extra stage buys.
+pub fn synchronizer_mtbf(
+    clock_hz: f64,
+    toggle_hz: f64,
+    stages: usize,
+    flops: &FlipFlopCharacteristics,
+) -> f64 {
+    let settle = (stages.saturating_sub(1) as f64) * (1.0 / clock_hz - flops.overhead);
+    if settle <= 0.0 {
+        return 0.0;
+    }
+    (settle / flops.tau).exp() / (flops.window * clock_hz * toggle_hz)
+}
+
+/// The fewest stages that give a synchronizer an MTBF (see [synchronizer_mtbf]) of at
+/// least `target` seconds (or `None` if the clock is too fast for any number of stages
+/// up to 8 to be enough).
+pub fn synchronizer_stages_for_mtbf(
+    clock_hz: f64,
+    toggle_hz: f64,
+    target: f64,
+    flops: &FlipFlopCharacteristics,
+) -> Option<usize> {
+    (2..=8).find(|stages| synchronizer_mtbf(clock_hz, toggle_hz, *stages, flops) >= target)
 }
 
 #[test]
@@ -0,0 +1,34 @@
+use rust_hdl_lib_core::prelude::*;
+
+/// The readings of an on-die system monitor (die temperature and supply voltages),
+/// in units that are the same for every FPGA.  The wrappers for the monitors of each
+/// family (e.g., the XADC of the Xilinx 7 series, or the DTR of the ECP5) provide
+/// this interface, so that the logic (and host software) that uses the readings does
+/// not need to know which FPGA it is on.  A reading that a family cannot make is 0.
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "SystemMonitorReader"]
+pub struct SystemMonitor {
+    /// The die temperature, in units of 1/16 Kelvin.  See [temperature_celsius].
+    pub temperature: Signal<Out, Bits<16>>,
+    /// The core supply voltage, in millivolts
+    pub vccint: Signal<Out, Bits<16>>,
+    /// The auxiliary supply voltage, in millivolts
+    pub vccaux: Signal<Out, Bits<16>>,
+    /// Fires for one clock cycle when the readings are updated
+    pub valid: Signal<Out, Bit>,
+}
+
+/// The receiving side of a [SystemMonitor].
+#[derive(Clone, Debug, Default, LogicInterface)]
+#[join = "SystemMonitor"]
+pub struct SystemMonitorReader {
+    pub temperature: Signal<In, Bits<16>>,
+    pub vccint: Signal<In, Bits<16>>,
+    pub vccaux: Signal<In, Bits<16>>,
+    pub valid: Signal<In, Bit>,
+}
+
+/// Convert the temperature reported by a [SystemMonitor] into degrees Celsius.
+pub fn temperature_celsius(temperature: u16) -> f64 {
+    temperature as f64 / 16.0 - 273.15
+}
@@ -0,0 +1,113 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::edge_detector::EdgeDetector;
+use crate::fifo::sync_fifo::SynchronousFIFO;
+use crate::synchronizer::BitSynchronizer;
+use rust_hdl_lib_core::prelude::*;
+
+/// A [CounterTDC] is the simple variant of a time-to-digital converter, that measures
+/// the time from a rising edge on `start` to rising edges on `stop` by counting clocks.
+/// Both inputs are asynchronous, and go through the same synchronizers, so the delay
+/// through them cancels out (the result is still only good to a clock).  Every `stop`
+/// edge in the window (`window` clocks after `start`) pushes its time into the result
+/// FIFO, so a burst of echoes can be timed from a single `start`.  A `start` edge while
+/// the window is open restarts it.
+///
+/// The results are read from the FIFO with `data`, `empty` and `read`.  If the window
+/// closes without a `stop` edge, `timeout` is pulsed.  If a result is lost because the
+/// FIFO is full, `overflow` is pulsed.
+#[derive(LogicBlock)]
+pub struct CounterTDC<const C: usize> {
+    pub clock: Signal<In, Clock>,
+    pub start: Signal<In, Bit>,
+    pub stop: Signal<In, Bit>,
+    /// The time from `start` to `stop` in clocks
+    pub data: Signal<Out, Bits<C>>,
+    pub empty: Signal<Out, Bit>,
+    pub read: Signal<In, Bit>,
+    /// High while the window is open
+    pub busy: Signal<Out, Bit>,
+    pub timeout: Signal<Out, Bit>,
+    pub overflow: Signal<Out, Bit>,
+    start_sync: BitSynchronizer,
+    stop_sync: BitSynchronizer,
+    start_edge: EdgeDetector,
+    stop_edge: EdgeDetector,
+    counter: DFF<Bits<C>>,
+    armed: DFF<Bit>,
+    seen: DFF<Bit>,
+    timeout_flop: DFF<Bit>,
+    fifo: SynchronousFIFO<Bits<C>, 4, 5, 1>,
+    window: Constant<Bits<C>>,
+}
+
+impl<const C: usize> CounterTDC<C> {
+    pub fn new(window: u64) -> Self {
+        assert!(window > 0 && (C >= 64 || window < (1 << C)));
+        Self {
+            clock: Default::default(),
+            start: Default::default(),
+            stop: Default::default(),
+            data: Default::default(),
+            empty: Default::default(),
+            read: Default::default(),
+            busy: Default::default(),
+            timeout: Default::default(),
+            overflow: Default::default(),
+            start_sync: Default::default(),
+            stop_sync: Default::default(),
+            start_edge: EdgeDetector::new(true),
+            stop_edge: EdgeDetector::new(true),
+            counter: Default::default(),
+            armed: Default::default(),
+            seen: Default::default(),
+            timeout_flop: Default::default(),
+            fifo: Default::default(),
+            window: Constant::new(window.to_bits()),
+        }
+    }
+}
+
+impl<const C: usize> Logic for CounterTDC<C> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter, armed, seen, timeout_flop);
+        clock!(self, clock, start_sync, stop_sync, start_edge, stop_edge, fifo);
+        self.start_sync.sig_in.next = self.start.val();
+        self.stop_sync.sig_in.next = self.stop.val();
+        self.start_edge.input_signal.next = self.start_sync.sig_out.val();
+        self.stop_edge.input_signal.next = self.stop_sync.sig_out.val();
+        self.data.next = self.fifo.data_out.val();
+        self.empty.next = self.fifo.empty.val();
+        self.fifo.read.next = self.read.val();
+        self.overflow.next = self.fifo.overflow.val();
+        self.busy.next = self.armed.q.val();
+        self.timeout.next = self.timeout_flop.q.val();
+        self.timeout_flop.d.next = false;
+        self.fifo.data_in.next = self.counter.q.val();
+        self.fifo.write.next = false;
+        if self.armed.q.val() {
+            self.counter.d.next = self.counter.q.val() + 1;
+            if self.stop_edge.edge_signal.val() {
+                self.fifo.write.next = true;
+                self.seen.d.next = true;
+            }
+            if self.counter.q.val() == self.window.val() {
+                self.armed.d.next = false;
+                self.timeout_flop.d.next = !self.seen.q.val() & !self.stop_edge.edge_signal.val();
+            }
+        }
+        if self.start_edge.edge_signal.val() {
+            self.counter.d.next = 1.into();
+            self.armed.d.next = true;
+            self.seen.d.next = false;
+        }
+    }
+}
+
+#[test]
+fn test_counter_tdc_is_synthesizable() {
+    let mut uut = CounterTDC::<16>::new(10_000);
+    uut.connect_all();
+    yosys_validate("counter_tdc", &generate_verilog(&uut)).unwrap();
+}
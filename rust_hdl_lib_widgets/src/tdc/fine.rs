@@ -0,0 +1,201 @@
+use crate::bit_ops::PopulationCount;
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::histogram::Histogram;
+use crate::ramrom::ram::RAM;
+use rust_hdl_lib_core::prelude::*;
+use std::collections::BTreeMap;
+
+#[derive(Copy, Clone, Debug, PartialEq, LogicState)]
+enum CalibrationState {
+    Idle,
+    Clear,
+    Clearing,
+    Collect,
+    Settle,
+    Build,
+}
+
+/// A [FineTDC] measures where, within a clock, a hit arrived, from the taps of a delay
+/// line (such as the carry chain delay lines in the FPGA support crate) that are sampled
+/// on each clock.  A hit that enters the line sets the taps one after another, so the
+/// number of set taps (the raw code) grows with the time from the hit to the clock edge
+/// that samples them.  The delay line must be longer than a clock, and the taps must be
+/// clear between hits.  The raw code is found by counting the set taps, which tolerates
+/// the bubbles that real delay lines have.
+///
+/// The taps of a delay line are far from even, so the raw code is turned into a time by
+/// a lookup table, that is built by calibration (a code density test).  Asserting
+/// `calibrate` clears a [Histogram] of the raw codes, collects `2^K` hits (that must be
+/// uncorrelated with the clock, e.g., from a free running oscillator), and then sets the
+/// time of each code to the middle of its bin in the cumulative histogram.
+/// `calibrating` is high until the new table is in place.  Until the first calibration,
+/// the taps are assumed to be even, with `T` of them spanning a clock.
+///
+/// For each hit, `hit` is pulsed (two clocks after the taps come in), along with
+/// the raw code on `raw` and the time from the hit to the clock edge on `fine`, as a
+/// fraction of a clock (where 65536 is a whole clock).
+#[derive(LogicBlock)]
+pub struct FineTDC<const T: usize, const F: usize> {
+    pub clock: Signal<In, Clock>,
+    /// The taps of the delay line, sampled by `clock`
+    pub taps: Signal<In, Bits<T>>,
+    pub calibrate: Signal<In, Bit>,
+    pub calibrating: Signal<Out, Bit>,
+    pub hit: Signal<Out, Bit>,
+    pub raw: Signal<Out, Bits<F>>,
+    pub fine: Signal<Out, Bits<16>>,
+    first_tap: DFF<Bit>,
+    popcount: PopulationCount<T, F>,
+    hit_1: DFF<Bit>,
+    hit_2: DFF<Bit>,
+    raw_2: DFF<Bits<F>>,
+    histogram: Histogram<F, F, 24>,
+    lut: RAM<Bits<16>, F>,
+    state: DFF<CalibrationState>,
+    address: DFF<Bits<F>>,
+    bin: DFF<Bits<F>>,
+    bin_valid: DFF<Bit>,
+    cumulative: DFF<Bits<24>>,
+    middle: Signal<Local, Bits<32>>,
+    samples: Constant<Bits<24>>,
+    scale: Constant<Bits<8>>,
+}
+
+impl<const T: usize, const F: usize> FineTDC<T, F> {
+    /// Calibration collects `2^samples_log2` hits, which is at most `2^16`
+    pub fn new(samples_log2: usize) -> Self {
+        assert!(F >= clog2(T + 1) && F <= 16);
+        assert!(samples_log2 <= 16);
+        // Until calibration, each tap is taken to be a T-th of a clock
+        let lut: BTreeMap<Bits<F>, Bits<16>> = (0..(1 << F))
+            .map(|code| {
+                let time = ((2 * code + 1) << 16) / (2 * T);
+                (code.to_bits(), time.min(0xFFFF).to_bits())
+            })
+            .collect();
+        Self {
+            clock: Default::default(),
+            taps: Default::default(),
+            calibrate: Default::default(),
+            calibrating: Default::default(),
+            hit: Default::default(),
+            raw: Default::default(),
+            fine: Default::default(),
+            first_tap: Default::default(),
+            popcount: PopulationCount::new(true),
+            hit_1: Default::default(),
+            hit_2: Default::default(),
+            raw_2: Default::default(),
+            histogram: Default::default(),
+            lut: RAM::new(lut),
+            state: Default::default(),
+            address: Default::default(),
+            bin: Default::default(),
+            bin_valid: Default::default(),
+            cumulative: Default::default(),
+            middle: Default::default(),
+            samples: Constant::new((1_u64 << samples_log2).to_bits()),
+            scale: Constant::new((16 - samples_log2).to_bits()),
+        }
+    }
+}
+
+impl<const T: usize, const F: usize> Logic for FineTDC<T, F> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self, clock, first_tap, hit_1, hit_2, raw_2, state, address, bin, bin_valid, cumulative
+        );
+        clock!(self, clock, popcount, histogram);
+        self.lut.read_clock.next = self.clock.val();
+        self.lut.write_clock.next = self.clock.val();
+        // A hit has entered the line when the first tap is set
+        self.first_tap.d.next = self.taps.val().get_bit(0);
+        self.popcount.data_in.next = self.taps.val();
+        self.hit_1.d.next = self.taps.val().get_bit(0) & !self.first_tap.q.val();
+        // Look up the time for the raw code
+        self.lut.read_address.next = self.popcount.count.val();
+        self.raw_2.d.next = self.popcount.count.val();
+        self.hit_2.d.next = self.hit_1.q.val();
+        self.hit.next = self.hit_2.q.val();
+        self.raw.next = self.raw_2.q.val();
+        self.fine.next = self.lut.read_data.val();
+        // Calibration
+        self.histogram.data_in.next = self.popcount.count.val();
+        self.histogram.strobe_in.next = false;
+        self.histogram.offset.next = 0.into();
+        self.histogram.shift.next = 0.into();
+        self.histogram.clear.next = false;
+        self.histogram.read_address.next = self.address.q.val();
+        self.calibrating.next = self.state.q.val() != CalibrationState::Idle;
+        self.bin_valid.d.next = false;
+        self.bin.d.next = self.address.q.val();
+        // The middle of the bin, as a fraction of the clock
+        self.middle.next = (bit_cast::<32, 24>(self.cumulative.q.val())
+            + bit_cast::<32, 24>(self.histogram.read_data.val() >> 1))
+            << self.scale.val();
+        if self.middle.val().get_bit(16) {
+            self.middle.next = 0xFFFF.into();
+        }
+        self.lut.write_address.next = self.bin.q.val();
+        self.lut.write_data.next = bit_cast::<16, 32>(self.middle.val());
+        self.lut.write_enable.next = false;
+        match self.state.q.val() {
+            CalibrationState::Idle => {
+                if self.calibrate.val() {
+                    self.state.d.next = CalibrationState::Clear;
+                }
+            }
+            CalibrationState::Clear => {
+                self.histogram.clear.next = true;
+                self.state.d.next = CalibrationState::Clearing;
+            }
+            CalibrationState::Clearing => {
+                if !self.histogram.busy.val() {
+                    self.state.d.next = CalibrationState::Collect;
+                }
+            }
+            CalibrationState::Collect => {
+                self.histogram.strobe_in.next =
+                    self.hit_1.q.val() & (self.histogram.total.val() != self.samples.val());
+                if self.histogram.total.val() == self.samples.val() {
+                    self.address.d.next = 0.into();
+                    self.state.d.next = CalibrationState::Settle;
+                }
+            }
+            CalibrationState::Settle => {
+                // Let the last counts land in the histogram
+                self.address.d.next = self.address.q.val() + 1;
+                if self.address.q.val() == 3 {
+                    self.address.d.next = 0.into();
+                    self.cumulative.d.next = 0.into();
+                    self.state.d.next = CalibrationState::Build;
+                }
+            }
+            CalibrationState::Build => {
+                // The count for each bin arrives a clock after its address
+                self.address.d.next = self.address.q.val() + 1;
+                self.bin_valid.d.next = true;
+                if self.bin_valid.q.val() {
+                    self.lut.write_enable.next = true;
+                    self.cumulative.d.next =
+                        self.cumulative.q.val() + self.histogram.read_data.val();
+                }
+                if self.bin_valid.q.val() & self.bin.q.val().all() {
+                    self.state.d.next = CalibrationState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = CalibrationState::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_fine_tdc_is_synthesizable() {
+    let mut uut = FineTDC::<32, 6>::new(12);
+    uut.connect_all();
+    yosys_validate("fine_tdc", &generate_verilog(&uut)).unwrap();
+}
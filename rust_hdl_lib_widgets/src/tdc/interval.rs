@@ -0,0 +1,150 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::fifo::sync_fifo::SynchronousFIFO;
+use crate::tdc::fine::FineTDC;
+use rust_hdl_lib_core::prelude::*;
+
+/// An [IntervalTDC] measures the time from a hit on the start channel to the hits on the
+/// stop channel, with a [FineTDC] on each channel (fed by the taps of a delay line) and a
+/// coarse count of the clocks between them.  The time of each hit is the clock edge that
+/// sampled it, less the fine time, so the result is good to a fraction of a tap, as long
+/// as the delay lines have been calibrated.  Asserting `calibrate` calibrates both
+/// channels at once (with hits on both channels that are uncorrelated with the clock),
+/// and `calibrating` is high until both are done (no results are recorded meanwhile).
+///
+/// Every stop hit in the window (`window` clocks after the start hit) pushes its time
+/// into the result FIFO, which is read with `data`, `empty` and `read`.  The times are
+/// in clocks, with 16 fractional bits, so the window must be shorter than `2^16` clocks.
+/// If the window closes without a stop hit, `timeout` is pulsed.  If a result is lost
+/// because the FIFO is full, `overflow` is pulsed.
+#[derive(LogicBlock)]
+pub struct IntervalTDC<const T: usize, const F: usize> {
+    pub clock: Signal<In, Clock>,
+    /// The taps of the delay line for the start channel, sampled by `clock`
+    pub start_taps: Signal<In, Bits<T>>,
+    /// The taps of the delay line for the stop channel, sampled by `clock`
+    pub stop_taps: Signal<In, Bits<T>>,
+    pub calibrate: Signal<In, Bit>,
+    pub calibrating: Signal<Out, Bit>,
+    /// The time from the start hit to the stop hit in clocks (16.16 fixed point)
+    pub data: Signal<Out, Bits<32>>,
+    pub empty: Signal<Out, Bit>,
+    pub read: Signal<In, Bit>,
+    /// High while the window is open
+    pub busy: Signal<Out, Bit>,
+    pub timeout: Signal<Out, Bit>,
+    pub overflow: Signal<Out, Bit>,
+    start: FineTDC<T, F>,
+    stop: FineTDC<T, F>,
+    coarse: DFF<Bits<16>>,
+    start_time: DFF<Bits<32>>,
+    origin: Signal<Local, Bits<32>>,
+    stop_time: Signal<Local, Bits<32>>,
+    elapsed: DFF<Bits<16>>,
+    armed: DFF<Bit>,
+    seen: DFF<Bit>,
+    timeout_flop: DFF<Bit>,
+    fifo: SynchronousFIFO<Bits<32>, 4, 5, 1>,
+    window: Constant<Bits<16>>,
+}
+
+impl<const T: usize, const F: usize> IntervalTDC<T, F> {
+    /// See [FineTDC::new] for `samples_log2`
+    pub fn new(samples_log2: usize, window: u64) -> Self {
+        assert!(window > 0 && window < (1 << 16));
+        Self {
+            clock: Default::default(),
+            start_taps: Default::default(),
+            stop_taps: Default::default(),
+            calibrate: Default::default(),
+            calibrating: Default::default(),
+            data: Default::default(),
+            empty: Default::default(),
+            read: Default::default(),
+            busy: Default::default(),
+            timeout: Default::default(),
+            overflow: Default::default(),
+            start: FineTDC::new(samples_log2),
+            stop: FineTDC::new(samples_log2),
+            coarse: Default::default(),
+            start_time: Default::default(),
+            origin: Default::default(),
+            stop_time: Default::default(),
+            elapsed: Default::default(),
+            armed: Default::default(),
+            seen: Default::default(),
+            timeout_flop: Default::default(),
+            fifo: Default::default(),
+            window: Constant::new(window.to_bits()),
+        }
+    }
+}
+
+impl<const T: usize, const F: usize> Logic for IntervalTDC<T, F> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(
+            self,
+            clock,
+            coarse,
+            start_time,
+            elapsed,
+            armed,
+            seen,
+            timeout_flop
+        );
+        clock!(self, clock, start, stop, fifo);
+        self.start.taps.next = self.start_taps.val();
+        self.stop.taps.next = self.stop_taps.val();
+        self.start.calibrate.next = self.calibrate.val();
+        self.stop.calibrate.next = self.calibrate.val();
+        self.calibrating.next = self.start.calibrating.val() | self.stop.calibrating.val();
+        self.data.next = self.fifo.data_out.val();
+        self.empty.next = self.fifo.empty.val();
+        self.fifo.read.next = self.read.val();
+        self.overflow.next = self.fifo.overflow.val();
+        self.busy.next = self.armed.q.val();
+        self.timeout.next = self.timeout_flop.q.val();
+        self.timeout_flop.d.next = false;
+        // Both channels have the same latency, so the coarse count can be taken as is
+        self.coarse.d.next = self.coarse.q.val() + 1;
+        self.origin.next = self.start_time.q.val();
+        if self.start.hit.val() {
+            self.origin.next = (bit_cast::<32, 16>(self.coarse.q.val()) << 16)
+                - bit_cast::<32, 16>(self.start.fine.val());
+        }
+        self.stop_time.next = (bit_cast::<32, 16>(self.coarse.q.val()) << 16)
+            - bit_cast::<32, 16>(self.stop.fine.val());
+        self.fifo.data_in.next = self.stop_time.val() - self.origin.val();
+        self.fifo.write.next = false;
+        if self.armed.q.val() {
+            self.elapsed.d.next = self.elapsed.q.val() + 1;
+            if self.elapsed.q.val() == self.window.val() {
+                self.armed.d.next = false;
+                self.timeout_flop.d.next = !self.seen.q.val() & !self.stop.hit.val();
+            }
+        }
+        if self.start.hit.val() {
+            self.start_time.d.next = self.origin.val();
+            self.elapsed.d.next = 1.into();
+            self.armed.d.next = true;
+            self.seen.d.next = false;
+        }
+        if self.stop.hit.val() & (self.armed.q.val() | self.start.hit.val()) {
+            self.fifo.write.next = true;
+            self.seen.d.next = true;
+        }
+        // The hits used for calibration are not measured
+        if self.calibrating.val() {
+            self.armed.d.next = false;
+            self.fifo.write.next = false;
+        }
+    }
+}
+
+#[test]
+fn test_interval_tdc_is_synthesizable() {
+    let mut uut = IntervalTDC::<32, 6>::new(12, 1000);
+    uut.connect_all();
+    yosys_validate("interval_tdc", &generate_verilog(&uut)).unwrap();
+}
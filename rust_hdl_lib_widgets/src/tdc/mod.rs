@@ -0,0 +1,3 @@
+pub mod counter;
+pub mod fine;
+pub mod interval;
@@ -0,0 +1,331 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::fifo::async_fifo::AsynchronousFIFO;
+use rust_hdl_lib_core::prelude::*;
+
+/// The framing of a time division multiplexed (TDM) link, as used by multi-channel
+/// audio codecs (e.g., DSP/TDM modes of I2S codecs) and telecom style serial links.
+/// Each frame is made of `slots` slots of `slot_width` bit clocks each.  The samples
+/// are sent MSB first, left justified in their slot, and the rest of the slot is
+/// padded with zeros.  The frame sync is asserted for one bit clock, `sync_offset`
+/// bit clocks before the MSB of slot 0 (so 0 lines it up with the first bit of the
+/// frame, and 1 gives the common "DSP mode A" framing).
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct TDMConfig {
+    /// The number of slots in each frame (up to 256)
+    pub slots: usize,
+    /// The number of bit clocks in each slot (up to 256)
+    pub slot_width: usize,
+    /// The frame sync is active high if set, and active low otherwise
+    pub sync_active_high: bool,
+    /// The number of bit clocks from the frame sync to the MSB of slot 0
+    pub sync_offset: usize,
+}
+
+impl TDMConfig {
+    fn frame_length(&self) -> usize {
+        self.slots * self.slot_width
+    }
+    fn check<const W: usize, const S: usize>(&self) {
+        assert!((1..=256).contains(&self.slots));
+        assert!((1..=256).contains(&self.slot_width));
+        assert!(S <= self.slots, "More channels than slots in the TDM frame");
+        assert!(
+            W <= self.slot_width,
+            "The samples do not fit in the TDM slots"
+        );
+        assert!(self.sync_offset < self.frame_length());
+    }
+}
+
+#[derive(LogicInterface, Default)]
+#[join = "TDMWiresReceiver"]
+pub struct TDMWiresTransmitter {
+    pub fsync: Signal<Out, Bit>,
+    pub sdata: Signal<Out, Bit>,
+}
+
+#[derive(LogicInterface, Default)]
+#[join = "TDMWiresTransmitter"]
+pub struct TDMWiresReceiver {
+    pub fsync: Signal<In, Bit>,
+    pub sdata: Signal<In, Bit>,
+}
+
+/// A [TDMSerializer] sends `S` channels of `W` bit samples over a TDM link, in the
+/// first `S` slots of each frame (the remaining slots are sent as zeros).  It is the
+/// master of the link, and generates the frame sync from the bit clock.  The samples
+/// for each channel are queued in a 16 entry asynchronous FIFO, which is written in
+/// the `clock` domain, and one sample is taken from each FIFO per frame.  If the FIFO
+/// of a channel is empty when its slot comes around (or the frame comes before the first
+/// frame sync), the slot is sent as zeros.  The
+/// data and frame sync change on the rising edge of the bit clock, so the receiver
+/// should sample them on the falling edge (or the next rising edge, as [TDMDeserializer]
+/// does).
+#[derive(LogicBlock)]
+pub struct TDMSerializer<const W: usize, const S: usize> {
+    /// The clock for the FIFO writes
+    pub clock: Signal<In, Clock>,
+    /// The samples for each channel
+    pub data_in: [Signal<In, Bits<W>>; S],
+    /// Assert to queue the sample on `data_in` for the channel
+    pub write: [Signal<In, Bit>; S],
+    /// Asserted when the FIFO of the channel is full
+    pub full: [Signal<Out, Bit>; S],
+    /// The bit clock of the link
+    pub bit_clock: Signal<In, Clock>,
+    /// The wires of the link
+    pub wires: TDMWiresTransmitter,
+    fifos: [AsynchronousFIFO<Bits<W>, 4, 5, 1>; S],
+    shift: DFF<Bits<W>>,
+    slot: DFF<Bits<8>>,
+    slot_bit: DFF<Bits<8>>,
+    position: DFF<Bits<16>>,
+    armed: DFF<Bit>,
+    started: DFF<Bit>,
+    sync: Signal<Local, Bit>,
+    send: Signal<Local, Bit>,
+    next_slot: Signal<Local, Bits<8>>,
+    last_bit: Constant<Bits<8>>,
+    last_slot: Constant<Bits<8>>,
+    frame_last: Constant<Bits<16>>,
+    sync_position: Constant<Bits<16>>,
+    active_high: Constant<Bit>,
+    msb: Constant<Bits<8>>,
+}
+
+impl<const W: usize, const S: usize> TDMSerializer<W, S> {
+    pub fn new(config: TDMConfig) -> Self {
+        config.check::<W, S>();
+        let frame = config.frame_length();
+        Self {
+            clock: Default::default(),
+            data_in: array_init::array_init(|_| Default::default()),
+            write: array_init::array_init(|_| Default::default()),
+            full: array_init::array_init(|_| Default::default()),
+            bit_clock: Default::default(),
+            wires: Default::default(),
+            fifos: array_init::array_init(|_| Default::default()),
+            shift: Default::default(),
+            slot: Default::default(),
+            slot_bit: Default::default(),
+            position: Default::default(),
+            armed: Default::default(),
+            started: Default::default(),
+            sync: Default::default(),
+            send: Default::default(),
+            next_slot: Default::default(),
+            last_bit: Constant::new((config.slot_width - 1).to_bits()),
+            last_slot: Constant::new((config.slots - 1).to_bits()),
+            frame_last: Constant::new((frame - 1).to_bits()),
+            sync_position: Constant::new(((frame - config.sync_offset) % frame).to_bits()),
+            active_high: Constant::new(config.sync_active_high),
+            msb: Constant::new((W - 1).to_bits()),
+        }
+    }
+}
+
+impl<const W: usize, const S: usize> Logic for TDMSerializer<W, S> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, bit_clock, shift, slot, slot_bit, position, armed, started);
+        for i in 0..S {
+            self.fifos[i].write_clock.next = self.clock.val();
+            self.fifos[i].read_clock.next = self.bit_clock.val();
+            self.fifos[i].data_in.next = self.data_in[i].val();
+            self.fifos[i].write.next = self.write[i].val();
+            self.full[i].next = self.fifos[i].full.val();
+            self.fifos[i].read.next = false;
+        }
+        // Track the position of the bit on the wire
+        self.position.d.next = self.position.q.val() + 1;
+        if self.position.q.val() == self.frame_last.val() {
+            self.position.d.next = 0.into();
+        }
+        // Nothing is sent until the first frame after the first frame sync, so
+        // that the receiver does not miss any samples
+        self.sync.next = self.position.q.val() == self.sync_position.val();
+        if self.sync.val() {
+            self.armed.d.next = true;
+        }
+        self.next_slot.next = self.slot.q.val() + 1;
+        if self.slot.q.val() == self.last_slot.val() {
+            self.next_slot.next = 0.into();
+        }
+        self.send.next = self.started.q.val()
+            | ((self.armed.q.val() | self.sync.val()) & !self.next_slot.val().any());
+        // Shift out the sample, and load the next one at the end of the slot
+        self.slot_bit.d.next = self.slot_bit.q.val() + 1;
+        self.shift.d.next = self.shift.q.val() << 1;
+        if self.slot_bit.q.val() == self.last_bit.val() {
+            self.slot_bit.d.next = 0.into();
+            self.slot.d.next = self.next_slot.val();
+            self.shift.d.next = 0.into();
+            self.started.d.next = self.send.val();
+            for i in 0..S {
+                if (self.next_slot.val().index() == i)
+                    & !self.fifos[i].empty.val()
+                    & self.send.val()
+                {
+                    self.shift.d.next = self.fifos[i].data_out.val();
+                    self.fifos[i].read.next = true;
+                }
+            }
+        }
+        self.wires.sdata.next = self.shift.q.val().get_bit(self.msb.val().index());
+        self.wires.fsync.next = self.sync.val() ^ !self.active_high.val();
+    }
+}
+
+/// A [TDMDeserializer] receives `S` channels of `W` bit samples from the first `S` slots
+/// of each frame of a TDM link, and queues them in a 16 entry asynchronous FIFO for each
+/// channel, which is read in the `clock` domain.  The data and frame sync are sampled on
+/// the rising edge of the bit clock.  The deserializer waits for the first frame sync
+/// before it queues any samples, and realigns to each frame sync after that.  Samples
+/// that arrive while the FIFO of their channel is full are dropped.
+#[derive(LogicBlock)]
+pub struct TDMDeserializer<const W: usize, const S: usize> {
+    /// The clock for the FIFO reads
+    pub clock: Signal<In, Clock>,
+    /// The sample at the head of the FIFO of each channel
+    pub data_out: [Signal<Out, Bits<W>>; S],
+    /// Assert to take the sample at the head of the FIFO of the channel
+    pub read: [Signal<In, Bit>; S],
+    /// Asserted when the FIFO of the channel is empty
+    pub empty: [Signal<Out, Bit>; S],
+    /// The bit clock of the link
+    pub bit_clock: Signal<In, Clock>,
+    /// The wires of the link
+    pub wires: TDMWiresReceiver,
+    fifos: [AsynchronousFIFO<Bits<W>, 4, 5, 1>; S],
+    shift: DFF<Bits<W>>,
+    slot: DFF<Bits<8>>,
+    slot_bit: DFF<Bits<8>>,
+    countdown: DFF<Bits<16>>,
+    locked: DFF<Bit>,
+    sync: Signal<Local, Bit>,
+    aligned: Signal<Local, Bit>,
+    current_slot: Signal<Local, Bits<8>>,
+    current_bit: Signal<Local, Bits<8>>,
+    sample: Signal<Local, Bits<W>>,
+    last_bit: Constant<Bits<8>>,
+    last_slot: Constant<Bits<8>>,
+    sync_offset: Constant<Bits<16>>,
+    active_high: Constant<Bit>,
+    last_data: Constant<Bits<8>>,
+}
+
+impl<const W: usize, const S: usize> TDMDeserializer<W, S> {
+    pub fn new(config: TDMConfig) -> Self {
+        config.check::<W, S>();
+        Self {
+            clock: Default::default(),
+            data_out: array_init::array_init(|_| Default::default()),
+            read: array_init::array_init(|_| Default::default()),
+            empty: array_init::array_init(|_| Default::default()),
+            bit_clock: Default::default(),
+            wires: Default::default(),
+            fifos: array_init::array_init(|_| Default::default()),
+            shift: Default::default(),
+            slot: Default::default(),
+            slot_bit: Default::default(),
+            countdown: Default::default(),
+            locked: Default::default(),
+            sync: Default::default(),
+            aligned: Default::default(),
+            current_slot: Default::default(),
+            current_bit: Default::default(),
+            sample: Default::default(),
+            last_bit: Constant::new((config.slot_width - 1).to_bits()),
+            last_slot: Constant::new((config.slots - 1).to_bits()),
+            sync_offset: Constant::new(config.sync_offset.to_bits()),
+            active_high: Constant::new(config.sync_active_high),
+            last_data: Constant::new((W - 1).to_bits()),
+        }
+    }
+}
+
+impl<const W: usize, const S: usize> Logic for TDMDeserializer<W, S> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, bit_clock, shift, slot, slot_bit, countdown, locked);
+        for i in 0..S {
+            self.fifos[i].write_clock.next = self.bit_clock.val();
+            self.fifos[i].read_clock.next = self.clock.val();
+            self.fifos[i].read.next = self.read[i].val();
+            self.data_out[i].next = self.fifos[i].data_out.val();
+            self.empty[i].next = self.fifos[i].empty.val();
+            self.fifos[i].write.next = false;
+        }
+        // The MSB of slot 0 arrives `sync_offset` bits after the frame sync
+        self.sync.next = self.wires.fsync.val() ^ !self.active_high.val();
+        if self.countdown.q.val().any() {
+            self.countdown.d.next = self.countdown.q.val() - 1;
+        }
+        self.aligned.next = self.countdown.q.val() == 1;
+        if self.sync.val() {
+            self.countdown.d.next = self.sync_offset.val();
+            if !self.sync_offset.val().any() {
+                self.aligned.next = true;
+            }
+        }
+        // Work out which bit is on the wire
+        self.current_slot.next = self.slot.q.val();
+        self.current_bit.next = self.slot_bit.q.val();
+        if self.aligned.val() {
+            self.current_slot.next = 0.into();
+            self.current_bit.next = 0.into();
+            self.locked.d.next = true;
+        }
+        self.slot_bit.d.next = self.current_bit.val() + 1;
+        self.slot.d.next = self.current_slot.val();
+        if self.current_bit.val() == self.last_bit.val() {
+            self.slot_bit.d.next = 0.into();
+            self.slot.d.next = self.current_slot.val() + 1;
+            if self.current_slot.val() == self.last_slot.val() {
+                self.slot.d.next = 0.into();
+            }
+        }
+        // Shift in the sample, and queue it once the LSB arrives
+        self.sample.next =
+            (self.shift.q.val() << 1) | bit_cast::<W, 1>(self.wires.sdata.val().into());
+        self.shift.d.next = self.sample.val();
+        for i in 0..S {
+            self.fifos[i].data_in.next = self.sample.val();
+        }
+        if self.locked.q.val() | self.aligned.val() {
+            for i in 0..S {
+                if (self.current_slot.val().index() == i)
+                    & (self.current_bit.val() == self.last_data.val())
+                    & !self.fifos[i].full.val()
+                {
+                    self.fifos[i].write.next = true;
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_tdm_serializer_is_synthesizable() {
+    let mut uut = TDMSerializer::<16, 4>::new(TDMConfig {
+        slots: 8,
+        slot_width: 32,
+        sync_active_high: true,
+        sync_offset: 1,
+    });
+    uut.connect_all();
+    yosys_validate("tdm_serializer", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_tdm_deserializer_is_synthesizable() {
+    let mut uut = TDMDeserializer::<16, 4>::new(TDMConfig {
+        slots: 8,
+        slot_width: 32,
+        sync_active_high: true,
+        sync_offset: 1,
+    });
+    uut.connect_all();
+    yosys_validate("tdm_deserializer", &generate_verilog(&uut)).unwrap();
+}
@@ -0,0 +1,48 @@
+use rust_hdl_lib_core::prelude::*;
+
+use crate::{dff::DFF, dff_setup};
+
+/// A [TimestampCounter] is a free-running 64 bit counter of clock cycles,
+/// that can capture its value when an event happens.  At any practical
+/// clock frequency, 64 bits will not wrap around for centuries, so the
+/// captured values can be used to timestamp events in a data acquisition
+/// system without worrying about overflow.
+#[derive(Clone, Debug, Default, LogicBlock)]
+pub struct TimestampCounter {
+    /// The clock that drives the counter.  All signals are synchronous to this clock.
+    pub clock: Signal<In, Clock>,
+    /// Assert this for one clock cycle to capture the current timestamp.
+    pub capture: Signal<In, Bit>,
+    /// The current value of the counter.
+    pub timestamp: Signal<Out, Bits<64>>,
+    /// The value of the counter when `capture` was last asserted.
+    pub captured: Signal<Out, Bits<64>>,
+    /// Fires for one clock cycle when `captured` holds a new value.
+    pub captured_strobe: Signal<Out, Bit>,
+    counter: DFF<Bits<64>>,
+    latch: DFF<Bits<64>>,
+    strobe: DFF<Bit>,
+}
+
+impl Logic for TimestampCounter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter, latch, strobe);
+        self.counter.d.next = self.counter.q.val() + 1;
+        self.strobe.d.next = self.capture.val();
+        if self.capture.val() {
+            self.latch.d.next = self.counter.q.val();
+        }
+        self.timestamp.next = self.counter.q.val();
+        self.captured.next = self.latch.q.val();
+        self.captured_strobe.next = self.strobe.q.val();
+    }
+}
+
+#[test]
+fn test_timestamp_counter_is_synthesizable() {
+    let mut uut = TimestampCounter::default();
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("timestamp", &vlog).unwrap();
+}
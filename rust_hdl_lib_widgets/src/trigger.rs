@@ -0,0 +1,237 @@
+use crate::{dff::DFF, dff_setup, ramrom::ram::RAM};
+use rust_hdl_lib_core::prelude::*;
+
+// A level trigger for a stream of D bit samples, each of which packs D/W
+// channels of W bits (channel 0 in the low bits).  It watches the channel
+// picked by `channel`, and raises `trigger` on the strobe of the sample that
+// crosses `level` - going up (from below `level` to `level` or above), or
+// going down when `falling` is set.  For a logic analyzer, use W = 1 and a
+// level of 1.  `force` raises `trigger` right away, for a capture that should
+// not wait for the signal.
+#[derive(LogicBlock)]
+pub struct LevelTrigger<const D: usize, const W: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<D>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub channel: Signal<In, Bits<8>>,
+    pub level: Signal<In, Bits<W>>,
+    pub falling: Signal<In, Bit>,
+    pub force: Signal<In, Bit>,
+    pub trigger: Signal<Out, Bit>,
+    previous: DFF<Bits<W>>,
+    offset: Signal<Local, Bits<32>>,
+    current: Signal<Local, Bits<W>>,
+    width: Constant<Bits<16>>,
+}
+
+impl<const D: usize, const W: usize> Default for LevelTrigger<D, W> {
+    fn default() -> Self {
+        assert!(W <= D);
+        assert_eq!(D % W, 0);
+        assert!(D / W <= 256);
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            channel: Default::default(),
+            level: Default::default(),
+            falling: Default::default(),
+            force: Default::default(),
+            trigger: Default::default(),
+            previous: Default::default(),
+            offset: Default::default(),
+            current: Default::default(),
+            width: Constant::new(W.to_bits()),
+        }
+    }
+}
+
+impl<const D: usize, const W: usize> Logic for LevelTrigger<D, W> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, previous);
+        self.offset.next = bit_cast::<16, 8>(self.channel.val()) * self.width.val();
+        self.current.next = self.data_in.val().get_bits::<W>(self.offset.val().index());
+        if self.strobe_in.val() {
+            self.previous.d.next = self.current.val();
+        }
+        self.trigger.next = self.force.val();
+        if self.strobe_in.val() {
+            if self.falling.val() {
+                if (self.previous.q.val() >= self.level.val())
+                    & (self.current.val() < self.level.val())
+                {
+                    self.trigger.next = true;
+                }
+            } else if (self.previous.q.val() < self.level.val())
+                & (self.current.val() >= self.level.val())
+            {
+                self.trigger.next = true;
+            }
+        }
+    }
+}
+
+// A trigger sequencer (in the style of the trigger state machines of vendor
+// logic analyzer cores) for a stream of D bit samples, with up to 2^N stages.
+// Each stage waits for a sample that matches its `value` in the bits set in
+// its `mask` (the other bits are don't cares), and completes on the `count`th
+// such sample (0 and 1 both mean the first).  Once a stage completes, the
+// sequencer moves on to the next one, and when the last stage in use (stage
+// `depth - 1`, so `depth` is at most 2^N) completes, it raises `trigger` on the strobe of that sample,
+// and starts over.  A stage (other than the first) with a nonzero `window`
+// must complete within that many samples of the one before it, or the
+// sequencer starts over (with the next sample) - so "A then B within 10
+// samples" is stage 0 matching A, and stage 1 matching B with a window of 10.
+//
+// The stages are configured (while the sequencer is not in use) by setting
+// `config_mask`, `config_value`, `config_count` and `config_window`, and
+// raising `config_write` for a clock to store them in stage `config_stage`.
+// A `depth` of 0 turns the sequencer off, and `arm` sends it back to stage 0.
+// `stage` is the stage it is waiting on.
+#[derive(LogicBlock)]
+pub struct TriggerSequencer<const D: usize, const N: usize> {
+    pub clock: Signal<In, Clock>,
+    pub data_in: Signal<In, Bits<D>>,
+    pub strobe_in: Signal<In, Bit>,
+    pub arm: Signal<In, Bit>,
+    pub depth: Signal<In, Bits<8>>,
+    pub config_stage: Signal<In, Bits<N>>,
+    pub config_mask: Signal<In, Bits<D>>,
+    pub config_value: Signal<In, Bits<D>>,
+    pub config_count: Signal<In, Bits<16>>,
+    pub config_window: Signal<In, Bits<16>>,
+    pub config_write: Signal<In, Bit>,
+    pub stage: Signal<Out, Bits<N>>,
+    pub trigger: Signal<Out, Bit>,
+    masks: RAM<Bits<D>, N>,
+    values: RAM<Bits<D>, N>,
+    counts: RAM<Bits<16>, N>,
+    windows: RAM<Bits<16>, N>,
+    current: DFF<Bits<N>>,
+    matches: DFF<Bits<16>>,
+    elapsed: DFF<Bits<16>>,
+    differs: Signal<Local, Bits<D>>,
+    hit: Signal<Local, Bit>,
+    complete: Signal<Local, Bit>,
+    last: Signal<Local, Bit>,
+    expired: Signal<Local, Bit>,
+}
+
+impl<const D: usize, const N: usize> Default for TriggerSequencer<D, N> {
+    fn default() -> Self {
+        assert!(N <= 8);
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            strobe_in: Default::default(),
+            arm: Default::default(),
+            depth: Default::default(),
+            config_stage: Default::default(),
+            config_mask: Default::default(),
+            config_value: Default::default(),
+            config_count: Default::default(),
+            config_window: Default::default(),
+            config_write: Default::default(),
+            stage: Default::default(),
+            trigger: Default::default(),
+            masks: Default::default(),
+            values: Default::default(),
+            counts: Default::default(),
+            windows: Default::default(),
+            current: Default::default(),
+            matches: Default::default(),
+            elapsed: Default::default(),
+            differs: Default::default(),
+            hit: Default::default(),
+            complete: Default::default(),
+            last: Default::default(),
+            expired: Default::default(),
+        }
+    }
+}
+
+impl<const D: usize, const N: usize> Logic for TriggerSequencer<D, N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, current, matches, elapsed);
+        self.masks.read_clock.next = self.clock.val();
+        self.masks.write_clock.next = self.clock.val();
+        self.values.read_clock.next = self.clock.val();
+        self.values.write_clock.next = self.clock.val();
+        self.counts.read_clock.next = self.clock.val();
+        self.counts.write_clock.next = self.clock.val();
+        self.windows.read_clock.next = self.clock.val();
+        self.windows.write_clock.next = self.clock.val();
+        // Configuration
+        self.masks.write_address.next = self.config_stage.val();
+        self.masks.write_data.next = self.config_mask.val();
+        self.masks.write_enable.next = self.config_write.val();
+        self.values.write_address.next = self.config_stage.val();
+        self.values.write_data.next = self.config_value.val();
+        self.values.write_enable.next = self.config_write.val();
+        self.counts.write_address.next = self.config_stage.val();
+        self.counts.write_data.next = self.config_count.val();
+        self.counts.write_enable.next = self.config_write.val();
+        self.windows.write_address.next = self.config_stage.val();
+        self.windows.write_data.next = self.config_window.val();
+        self.windows.write_enable.next = self.config_write.val();
+        // The stage the sequencer is waiting on is read out of the RAMs
+        // one clock ahead, so it is there for the first sample in the stage
+        self.masks.read_address.next = self.current.d.val();
+        self.values.read_address.next = self.current.d.val();
+        self.counts.read_address.next = self.current.d.val();
+        self.windows.read_address.next = self.current.d.val();
+        self.differs.next =
+            (self.data_in.val() ^ self.values.read_data.val()) & self.masks.read_data.val();
+        self.hit.next = self.strobe_in.val() & !self.differs.val().any();
+        self.complete.next =
+            self.hit.val() & ((self.matches.q.val() + 1) >= self.counts.read_data.val());
+        self.last.next = bit_cast::<8, N>(self.current.q.val()) == (self.depth.val() - 1);
+        self.expired.next = self.strobe_in.val()
+            & self.current.q.val().any()
+            & self.windows.read_data.val().any()
+            & ((self.elapsed.q.val() + 1) >= self.windows.read_data.val());
+        self.stage.next = self.current.q.val();
+        self.trigger.next = false;
+        if self.hit.val() {
+            self.matches.d.next = self.matches.q.val() + 1;
+        }
+        if self.strobe_in.val() {
+            self.elapsed.d.next = self.elapsed.q.val() + 1;
+        }
+        if self.complete.val() {
+            self.current.d.next = self.current.q.val() + 1;
+            self.matches.d.next = 0.into();
+            self.elapsed.d.next = 0.into();
+            if self.last.val() {
+                self.trigger.next = true;
+                self.current.d.next = 0.into();
+            }
+        } else if self.expired.val() {
+            self.current.d.next = 0.into();
+            self.matches.d.next = 0.into();
+            self.elapsed.d.next = 0.into();
+        }
+        if self.arm.val() | !self.depth.val().any() {
+            self.trigger.next = false;
+            self.current.d.next = 0.into();
+            self.matches.d.next = 0.into();
+            self.elapsed.d.next = 0.into();
+        }
+    }
+}
+
+#[test]
+fn test_level_trigger_synthesizes() {
+    let mut uut = LevelTrigger::<16, 4>::default();
+    uut.connect_all();
+    yosys_validate("level_trigger", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_trigger_sequencer_synthesizes() {
+    let mut uut = TriggerSequencer::<16, 2>::default();
+    uut.connect_all();
+    yosys_validate("trigger_sequencer", &generate_verilog(&uut)).unwrap();
+}
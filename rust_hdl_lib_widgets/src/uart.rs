@@ -0,0 +1,221 @@
+use crate::dff::DFF;
+use crate::dff_setup;
+use crate::dff_with_init::DFFWithInit;
+use crate::synchronizer::BitSynchronizer;
+use rust_hdl_lib_core::prelude::*;
+
+fn clocks_per_bit(clock_speed_hz: u64, baud: u64) -> u64 {
+    let clocks = clock_speed_hz / baud;
+    assert!(
+        (4..65536).contains(&clocks),
+        "A UART needs between 4 and 65535 clocks per bit"
+    );
+    clocks
+}
+
+/// A [UARTTransmitter] sends bytes on a serial line in the usual 8N1 format (an idle high
+/// line, a start bit, 8 data bits sent LSB first, and a stop bit).  Bytes are written with
+/// the `write` strobe while `busy` is low, so the transmitter can be fed directly from the
+/// read side of a FIFO, or treated as a FIFO that is full while a byte is in flight.
+#[derive(LogicBlock)]
+pub struct UARTTransmitter {
+    /// The clock for the transmitter
+    pub clock: Signal<In, Clock>,
+    /// The byte to send
+    pub data_in: Signal<In, Bits<8>>,
+    /// Strobe to start sending `data_in` (ignored while `busy`)
+    pub write: Signal<In, Bit>,
+    /// Asserted while a byte is being sent
+    pub busy: Signal<Out, Bit>,
+    /// The serial output
+    pub tx: Signal<Out, Bit>,
+    shift: DFFWithInit<Bits<10>>,
+    bits: DFF<Bits<4>>,
+    timer: DFF<Bits<16>>,
+    divider: Constant<Bits<16>>,
+    stop_bit: Constant<Bits<10>>,
+}
+
+impl UARTTransmitter {
+    pub fn new(clock_speed_hz: u64, baud: u64) -> Self {
+        Self {
+            clock: Default::default(),
+            data_in: Default::default(),
+            write: Default::default(),
+            busy: Default::default(),
+            tx: Default::default(),
+            shift: DFFWithInit::new(0x3FF.into()),
+            bits: Default::default(),
+            timer: Default::default(),
+            divider: Constant::new((clocks_per_bit(clock_speed_hz, baud) - 1).to_bits()),
+            stop_bit: Constant::new(0x200.into()),
+        }
+    }
+}
+
+impl Logic for UARTTransmitter {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, shift, bits, timer);
+        self.busy.next = self.bits.q.val().any();
+        self.tx.next = self.shift.q.val().get_bit(0);
+        if !self.bits.q.val().any() {
+            if self.write.val() {
+                // The start bit goes out now, followed by the data and the stop bit
+                self.shift.d.next =
+                    (bit_cast::<10, 8>(self.data_in.val()) << 1) | self.stop_bit.val();
+                self.bits.d.next = 10.into();
+                self.timer.d.next = self.divider.val();
+            }
+        } else if self.timer.q.val().any() {
+            self.timer.d.next = self.timer.q.val() - 1;
+        } else {
+            self.shift.d.next = (self.shift.q.val() >> 1) | self.stop_bit.val();
+            self.bits.d.next = self.bits.q.val() - 1;
+            self.timer.d.next = self.divider.val();
+        }
+    }
+}
+
+#[derive(Copy, Clone, PartialEq, Debug, LogicState)]
+enum UARTReceiverState {
+    Idle,
+    Start,
+    Data,
+    Stop,
+    Break,
+}
+
+/// A [UARTReceiver] decodes bytes in the 8N1 format from a serial line.  The line is
+/// synchronized to the clock, and each bit is sampled near its middle.  A received byte
+/// is presented on `data_out` with a single clock `strobe_out`, so it can be written directly
+/// into a FIFO.  A byte with a missing stop bit is dropped, and reported with a strobe on
+/// `framing_error`, after which the receiver waits for the line to go idle again.
+#[derive(LogicBlock)]
+pub struct UARTReceiver {
+    /// The clock for the receiver
+    pub clock: Signal<In, Clock>,
+    /// The serial input (need not be synchronous to the clock)
+    pub rx: Signal<In, Bit>,
+    /// The last byte received
+    pub data_out: Signal<Out, Bits<8>>,
+    /// Strobes for one clock when a byte is received
+    pub strobe_out: Signal<Out, Bit>,
+    /// Strobes for one clock when a byte without a stop bit is received
+    pub framing_error: Signal<Out, Bit>,
+    rx_sync: BitSynchronizer,
+    state: DFF<UARTReceiverState>,
+    shift: DFF<Bits<8>>,
+    bits: DFF<Bits<3>>,
+    timer: DFF<Bits<16>>,
+    data: DFF<Bits<8>>,
+    strobe: DFF<Bit>,
+    error: DFF<Bit>,
+    divider: Constant<Bits<16>>,
+    half_divider: Constant<Bits<16>>,
+}
+
+impl UARTReceiver {
+    pub fn new(clock_speed_hz: u64, baud: u64) -> Self {
+        let clocks = clocks_per_bit(clock_speed_hz, baud);
+        Self {
+            clock: Default::default(),
+            rx: Default::default(),
+            data_out: Default::default(),
+            strobe_out: Default::default(),
+            framing_error: Default::default(),
+            rx_sync: Default::default(),
+            state: Default::default(),
+            shift: Default::default(),
+            bits: Default::default(),
+            timer: Default::default(),
+            data: Default::default(),
+            strobe: Default::default(),
+            error: Default::default(),
+            divider: Constant::new((clocks - 1).to_bits()),
+            half_divider: Constant::new((clocks / 2 - 1).to_bits()),
+        }
+    }
+}
+
+impl Logic for UARTReceiver {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, state, shift, bits, timer, data, strobe, error);
+        clock!(self, clock, rx_sync);
+        self.rx_sync.sig_in.next = self.rx.val();
+        self.data_out.next = self.data.q.val();
+        self.strobe_out.next = self.strobe.q.val();
+        self.framing_error.next = self.error.q.val();
+        self.strobe.d.next = false;
+        self.error.d.next = false;
+        if self.timer.q.val().any() {
+            self.timer.d.next = self.timer.q.val() - 1;
+        }
+        match self.state.q.val() {
+            UARTReceiverState::Idle => {
+                if !self.rx_sync.sig_out.val() {
+                    self.state.d.next = UARTReceiverState::Start;
+                    self.timer.d.next = self.half_divider.val();
+                }
+            }
+            UARTReceiverState::Start => {
+                if !self.timer.q.val().any() {
+                    // Check that the start bit is still there half a bit later
+                    if self.rx_sync.sig_out.val() {
+                        self.state.d.next = UARTReceiverState::Idle;
+                    } else {
+                        self.state.d.next = UARTReceiverState::Data;
+                        self.bits.d.next = 0.into();
+                        self.timer.d.next = self.divider.val();
+                    }
+                }
+            }
+            UARTReceiverState::Data => {
+                if !self.timer.q.val().any() {
+                    self.shift.d.next = (self.shift.q.val() >> 1)
+                        | (bit_cast::<8, 1>(self.rx_sync.sig_out.val().into()) << 7);
+                    self.bits.d.next = self.bits.q.val() + 1;
+                    self.timer.d.next = self.divider.val();
+                    if self.bits.q.val() == 7 {
+                        self.state.d.next = UARTReceiverState::Stop;
+                    }
+                }
+            }
+            UARTReceiverState::Stop => {
+                if !self.timer.q.val().any() {
+                    if self.rx_sync.sig_out.val() {
+                        self.data.d.next = self.shift.q.val();
+                        self.strobe.d.next = true;
+                        self.state.d.next = UARTReceiverState::Idle;
+                    } else {
+                        self.error.d.next = true;
+                        self.state.d.next = UARTReceiverState::Break;
+                    }
+                }
+            }
+            UARTReceiverState::Break => {
+                if self.rx_sync.sig_out.val() {
+                    self.state.d.next = UARTReceiverState::Idle;
+                }
+            }
+            _ => {
+                self.state.d.next = UARTReceiverState::Idle;
+            }
+        }
+    }
+}
+
+#[test]
+fn test_uart_transmitter_is_synthesizable() {
+    let mut uut = UARTTransmitter::new(50_000_000, 115_200);
+    uut.connect_all();
+    yosys_validate("uart_tx", &generate_verilog(&uut)).unwrap();
+}
+
+#[test]
+fn test_uart_receiver_is_synthesizable() {
+    let mut uut = UARTReceiver::new(50_000_000, 115_200);
+    uut.connect_all();
+    yosys_validate("uart_rx", &generate_verilog(&uut)).unwrap();
+}
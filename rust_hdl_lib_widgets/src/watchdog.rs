@@ -0,0 +1,80 @@
+use rust_hdl_lib_core::prelude::*;
+use std::time::Duration;
+
+use crate::{dff::DFF, dff_setup};
+
+/// A [Watchdog] counts clock cycles while it is enabled, and asserts `expired`
+/// if it is not kicked within the prescribed timeout.  Once expired, the output
+/// stays high until the watchdog is kicked (or disabled), so it can be used directly
+/// to drive reset logic.  It can also serve as a plain timeout, by kicking it
+/// whenever there is activity.  As with [Strobe](crate::strobe::Strobe), the argument
+/// [N] sizes the internal counter, and the constructor asserts that the
+/// timeout fits in it.
+#[derive(Clone, Debug, LogicBlock)]
+pub struct Watchdog<const N: usize> {
+    /// Set this to true to arm the watchdog.  While it is false, the watchdog is held in reset.
+    pub enable: Signal<In, Bit>,
+    /// Assert this (for at least one clock cycle) to restart the timeout.
+    pub kick: Signal<In, Bit>,
+    /// Goes high when the timeout elapses without a kick, and stays high until the next kick.
+    pub expired: Signal<Out, Bit>,
+    /// The clock that drives the [Watchdog].  All signals are synchronous to this clock.
+    pub clock: Signal<In, Clock>,
+    timeout: Constant<Bits<N>>,
+    counter: DFF<Bits<N>>,
+    fired: DFF<Bit>,
+}
+
+impl<const N: usize> Watchdog<N> {
+    /// Generate a [Watchdog] widget that can be used in a RustHDL circuit.
+    ///
+    /// # Arguments
+    ///
+    /// * `frequency`: The frequency (in Hz) of the clock signal driving the circuit.
+    /// * `timeout`: How long the watchdog waits for a kick before it expires.  This is
+    ///   rounded down to a whole number of clock cycles.
+    ///
+    /// returns: Watchdog<{ N }>
+    pub fn new(frequency: u64, timeout: Duration) -> Self {
+        let timeout_femto = timeout.as_nanos() as f64 * NANOS_PER_FEMTO;
+        let clock_period_femto = freq_hz_to_period_femto(frequency as f64);
+        let clocks = (timeout_femto / clock_period_femto).floor() as u64;
+        assert!((clocks as u128) < (1_u128 << (N as u128)));
+        assert!(clocks > 1);
+        Self {
+            enable: Signal::default(),
+            kick: Signal::default(),
+            expired: Signal::default(),
+            clock: Signal::default(),
+            timeout: Constant::new(clocks.into()),
+            counter: Default::default(),
+            fired: Default::default(),
+        }
+    }
+}
+
+impl<const N: usize> Logic for Watchdog<N> {
+    #[hdl_gen]
+    fn update(&mut self) {
+        dff_setup!(self, clock, counter, fired);
+        if self.enable.val() & !self.fired.q.val() {
+            self.counter.d.next = self.counter.q.val() + 1;
+        }
+        if self.enable.val() & (self.counter.q.val() == self.timeout.val()) {
+            self.fired.d.next = true;
+        }
+        if self.kick.val() | !self.enable.val() {
+            self.counter.d.next = 0.into();
+            self.fired.d.next = false;
+        }
+        self.expired.next = self.fired.q.val();
+    }
+}
+
+#[test]
+fn test_watchdog_is_synthesizable() {
+    let mut uut = Watchdog::<16>::new(100_000_000, Duration::from_micros(100));
+    uut.connect_all();
+    let vlog = generate_verilog(&uut);
+    yosys_validate("watchdog", &vlog).unwrap();
+}